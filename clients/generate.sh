@@ -0,0 +1,15 @@
+#!/bin/sh
+# 根据服务端导出的OpenAPI描述生成Rust和Dart客户端.
+# 描述由handler上的utoipa标注和little-walk-types中的类型生成, 接口或类型变化后重新运行.
+# 集成测试等Rust调用方也可以直接依赖little-walk-types, 使用与服务端相同的类型.
+set -e
+
+cd "$(dirname "$0")/.."
+cargo run --quiet -p little-walk-http-api -- openapi > clients/openapi.json
+
+GENERATOR="docker run --rm -u $(id -u):$(id -g) -v $PWD/clients:/local openapitools/openapi-generator-cli:v7.10.0"
+
+$GENERATOR generate -i /local/openapi.json -g rust -o /local/rust \
+    --additional-properties=packageName=little-walk-client,library=reqwest,supportAsync=true
+$GENERATOR generate -i /local/openapi.json -g dart-dio -o /local/dart \
+    --additional-properties=pubName=little_walk_client,dateLibrary=core
@@ -0,0 +1,211 @@
+use std::{
+    collections::HashMap,
+    sync::RwLock,
+    time::{Duration, Instant},
+};
+
+use super::{
+    entities::{BreedCareTips, NeighborhoodStats, WalkRequest, WalkerStats},
+    geo::haversine_distance,
+};
+
+const CELL_SIZE: f64 = 0.01; // 经纬度网格大小(约1公里)
+const RADIUS_STEP: f64 = 100.0; // 半径取整步长(米)
+
+// 附近查询的网格. 同一网格内的查询共用以网格中心点查询到的候选请求, 查询半径按网格的半对角线放大,
+// 保证覆盖网格内任意一点的查询范围, 再由调用方按实际位置筛选
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NearbyCell {
+    latitude: i64,
+    longitude: i64,
+    radius: i64,
+}
+
+impl NearbyCell {
+    pub fn new(latitude: f64, longitude: f64, radius: f64) -> Self {
+        Self {
+            latitude: (latitude / CELL_SIZE).round() as i64,
+            longitude: (longitude / CELL_SIZE).round() as i64,
+            radius: (radius / RADIUS_STEP).ceil() as i64,
+        }
+    }
+
+    pub fn latitude(&self) -> f64 {
+        self.latitude as f64 * CELL_SIZE
+    }
+
+    pub fn longitude(&self) -> f64 {
+        self.longitude as f64 * CELL_SIZE
+    }
+
+    pub fn radius(&self) -> f64 {
+        self.radius as f64 * RADIUS_STEP
+    }
+
+    // 网格中心到最远角点的距离, 靠近赤道一侧的角点经度方向更宽
+    fn half_diagonal(&self) -> f64 {
+        let half = CELL_SIZE / 2.0;
+        [half, -half]
+            .into_iter()
+            .map(|d_lat| {
+                haversine_distance(
+                    self.longitude(),
+                    self.latitude(),
+                    self.longitude() + half,
+                    self.latitude() + d_lat,
+                )
+            })
+            .fold(0.0, f64::max)
+    }
+
+    pub fn query_radius(&self) -> f64 {
+        self.radius() + self.half_diagonal()
+    }
+
+    pub fn covers(&self, latitude: f64, longitude: f64) -> bool {
+        haversine_distance(self.longitude(), self.latitude(), longitude, latitude)
+            <= self.query_radius()
+    }
+}
+
+// 候选请求只按网格缓存, 同一网格的所有调用方共用. 优先推送范围和屏蔽关系由调用方在读取缓存后筛选
+pub struct NearbyCache {
+    ttl: Duration,
+    entries: RwLock<HashMap<NearbyCell, (Instant, Vec<WalkRequest>)>>,
+}
+
+impl NearbyCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn get(&self, cell: &NearbyCell) -> Option<Vec<WalkRequest>> {
+        let entries = self.entries.read().ok()?;
+        entries
+            .get(cell)
+            .filter(|(cached_at, _)| cached_at.elapsed() < self.ttl)
+            .map(|(_, requests)| requests.clone())
+    }
+
+    pub fn put(&self, cell: NearbyCell, requests: Vec<WalkRequest>) {
+        if let Ok(mut entries) = self.entries.write() {
+            entries.retain(|_, (cached_at, _)| cached_at.elapsed() < self.ttl);
+            entries.insert(cell, (Instant::now(), requests));
+        }
+    }
+
+    // 新建或被接单的请求所在位置会影响覆盖该位置的所有网格
+    pub fn invalidate(&self, latitude: f64, longitude: f64) {
+        if let Ok(mut entries) = self.entries.write() {
+            entries.retain(|cell, _| !cell.covers(latitude, longitude));
+        }
+    }
+}
+
+// 遛狗人统计由多个聚合查询计算, 在有效期内直接返回缓存结果
+pub struct WalkerStatsCache {
+    ttl: Duration,
+    entries: RwLock<HashMap<String, (Instant, WalkerStats)>>,
+}
+
+impl WalkerStatsCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn get(&self, walker_id: &str) -> Option<WalkerStats> {
+        let entries = self.entries.read().ok()?;
+        entries
+            .get(walker_id)
+            .filter(|(cached_at, _)| cached_at.elapsed() < self.ttl)
+            .map(|(_, stats)| stats.clone())
+    }
+
+    pub fn put(&self, stats: WalkerStats) {
+        if let Ok(mut entries) = self.entries.write() {
+            entries.retain(|_, (cached_at, _)| cached_at.elapsed() < self.ttl);
+            entries.insert(stats.walker_id.clone(), (Instant::now(), stats));
+        }
+    }
+
+    pub fn invalidate(&self, walker_id: &str) {
+        if let Ok(mut entries) = self.entries.write() {
+            entries.remove(walker_id);
+        }
+    }
+}
+
+// 公开页面的区域数据允许较长时间的延迟, 同一网格的查询直接返回缓存结果
+pub struct NeighborhoodStatsCache {
+    ttl: Duration,
+    entries: RwLock<HashMap<NearbyCell, (Instant, Vec<NeighborhoodStats>)>>,
+}
+
+impl NeighborhoodStatsCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn get(&self, cell: &NearbyCell) -> Option<Vec<NeighborhoodStats>> {
+        let entries = self.entries.read().ok()?;
+        entries
+            .get(cell)
+            .filter(|(cached_at, _)| cached_at.elapsed() < self.ttl)
+            .map(|(_, stats)| stats.clone())
+    }
+
+    pub fn put(&self, cell: NearbyCell, stats: Vec<NeighborhoodStats>) {
+        if let Ok(mut entries) = self.entries.write() {
+            entries.retain(|_, (cached_at, _)| cached_at.elapsed() < self.ttl);
+            entries.insert(cell, (Instant::now(), stats));
+        }
+    }
+}
+
+// 照护建议很少变化, 按品种和请求的语言缓存查询结果(包括没有建议的情况), 管理员修改时清除该品种的缓存
+pub struct BreedCareTipsCache {
+    ttl: Duration,
+    entries: RwLock<HashMap<(String, String), (Instant, Option<BreedCareTips>)>>,
+}
+
+impl BreedCareTipsCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn get(&self, breed_id: &str, locale: &str) -> Option<Option<BreedCareTips>> {
+        let entries = self.entries.read().ok()?;
+        entries
+            .get(&(breed_id.to_owned(), locale.to_owned()))
+            .filter(|(cached_at, _)| cached_at.elapsed() < self.ttl)
+            .map(|(_, tips)| tips.clone())
+    }
+
+    pub fn put(&self, breed_id: &str, locale: &str, tips: Option<BreedCareTips>) {
+        if let Ok(mut entries) = self.entries.write() {
+            entries.retain(|_, (cached_at, _)| cached_at.elapsed() < self.ttl);
+            entries.insert(
+                (breed_id.to_owned(), locale.to_owned()),
+                (Instant::now(), tips),
+            );
+        }
+    }
+
+    pub fn invalidate(&self, breed_id: &str) {
+        if let Ok(mut entries) = self.entries.write() {
+            entries.retain(|(id, _), _| id != breed_id);
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        RwLock,
+    },
+};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+use super::entities::{ChatMessage, ChatReadMarker};
+
+// 推送给聊天连接的事件, 序列化后作为WebSocket文本帧发送
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChatEvent {
+    #[serde(rename_all = "camelCase")]
+    Message {
+        id: String,
+        walk_request_id: String,
+        sender_id: String,
+        text: String,
+        image_ids: Vec<String>,
+        sent_at: Option<DateTime<Utc>>,
+    },
+    // 参与者已读到message_id这条消息, 对方据此显示已读状态
+    #[serde(rename_all = "camelCase")]
+    Read {
+        walk_request_id: String,
+        user_id: String,
+        message_id: String,
+        read_at: Option<DateTime<Utc>>,
+    },
+    // 只发给出错的连接
+    Error {
+        message: String,
+    },
+}
+
+impl From<ChatMessage> for ChatEvent {
+    fn from(message: ChatMessage) -> Self {
+        ChatEvent::Message {
+            id: message.id,
+            walk_request_id: message.walk_request_id,
+            sender_id: message.sender_id,
+            text: message.text,
+            image_ids: message.image_ids,
+            sent_at: message.created_at,
+        }
+    }
+}
+
+impl From<ChatReadMarker> for ChatEvent {
+    fn from(marker: ChatReadMarker) -> Self {
+        ChatEvent::Read {
+            walk_request_id: marker.walk_request_id,
+            user_id: marker.user_id,
+            message_id: marker.message_id,
+            read_at: marker.updated_at,
+        }
+    }
+}
+
+struct ChatConnection {
+    id: u64,
+    sender: UnboundedSender<ChatEvent>,
+}
+
+// 每个遛狗请求一个聊天室, 只在内存中记录当前在线的连接. 多实例部署时需要按请求id会话保持
+#[derive(Default)]
+pub struct ChatHub {
+    next_connection_id: AtomicU64,
+    rooms: RwLock<HashMap<String, Vec<ChatConnection>>>,
+}
+
+impl ChatHub {
+    // 加入聊天室, 返回连接id和该连接的事件接收端
+    pub fn join(&self, walk_request_id: &str) -> (u64, UnboundedReceiver<ChatEvent>) {
+        let id = self.next_connection_id.fetch_add(1, Ordering::Relaxed);
+        let (sender, receiver) = unbounded_channel();
+        if let Ok(mut rooms) = self.rooms.write() {
+            rooms
+                .entry(walk_request_id.to_owned())
+                .or_default()
+                .push(ChatConnection { id, sender });
+        }
+        (id, receiver)
+    }
+
+    // 离开聊天室, 聊天室没有连接时一并移除
+    pub fn leave(&self, walk_request_id: &str, connection_id: u64) {
+        if let Ok(mut rooms) = self.rooms.write() {
+            if let Some(connections) = rooms.get_mut(walk_request_id) {
+                connections.retain(|c| c.id != connection_id);
+                if connections.is_empty() {
+                    rooms.remove(walk_request_id);
+                }
+            }
+        }
+    }
+
+    // 发给聊天室内的全部连接, 包括发送方自己的连接, 各端以此确认消息已送达
+    pub fn publish(&self, walk_request_id: &str, event: ChatEvent) {
+        if let Ok(rooms) = self.rooms.read() {
+            if let Some(connections) = rooms.get(walk_request_id) {
+                for connection in connections {
+                    let _ = connection.sender.send(event.clone());
+                }
+            }
+        }
+    }
+}
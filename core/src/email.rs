@@ -0,0 +1,105 @@
+use crate::{entities::Email, error::Error};
+
+// 邮件发送通道, 具体实现位于mailers
+pub trait Mailer {
+    async fn send(&self, email: &Email) -> Result<(), Error>;
+}
+
+// 一轮发送的结果, failures为发送失败的原因, 由调用方记录
+#[derive(Debug, Default)]
+pub struct EmailSummary {
+    pub sent: u64,
+    pub failures: Vec<String>,
+}
+
+// 邮件模板, 占位符为{{name}}. 纯文本和HTML两个版本, HTML版本替换时转义
+#[derive(Debug, Clone, Copy)]
+pub struct Template {
+    pub subject: &'static str,
+    pub text: &'static str,
+    pub html: &'static str,
+}
+
+#[derive(Debug, Clone)]
+pub struct RenderedEmail {
+    pub subject: String,
+    pub text: String,
+    pub html: String,
+}
+
+impl Template {
+    pub fn render(&self, values: &[(&str, &str)]) -> RenderedEmail {
+        RenderedEmail {
+            subject: fill(self.subject, values, false),
+            text: fill(self.text, values, false),
+            html: LAYOUT.replace("{{content}}", &fill(self.html, values, true)),
+        }
+    }
+}
+
+fn fill(template: &str, values: &[(&str, &str)], escape: bool) -> String {
+    values
+        .iter()
+        .fold(template.to_owned(), |filled, (name, value)| {
+            let value = if escape {
+                escape_html(value)
+            } else {
+                (*value).to_owned()
+            };
+            filled.replace(&format!("{{{{{}}}}}", name), &value)
+        })
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+const LAYOUT: &str = r#"<!DOCTYPE html>
+<html>
+<head><meta charset="utf-8"></head>
+<body style="font-family: sans-serif; color: #333; line-height: 1.6;">
+{{content}}
+<p style="color: #999; font-size: 12px;">此邮件由系统自动发送, 请勿直接回复. 可在App的通知设置中关闭此类邮件.</p>
+</body>
+</html>"#;
+
+pub const RECEIPT: Template = Template {
+    subject: "提现到账凭证 {{withdrawal_id}}",
+    text: "你申请的提现已打款成功.\n\n提现编号: {{withdrawal_id}}\n金额: ¥{{amount}}\n到账时间: {{paid_at}}\n",
+    html: r#"<p>你申请的提现已打款成功.</p>
+<table>
+<tr><td>提现编号</td><td>{{withdrawal_id}}</td></tr>
+<tr><td>金额</td><td>¥{{amount}}</td></tr>
+<tr><td>到账时间</td><td>{{paid_at}}</td></tr>
+</table>"#,
+};
+
+pub const WEEKLY_SUMMARY: Template = Template {
+    subject: "遛狗周报 {{from}} - {{to}}",
+    text: "{{from}}至{{to}}:\n\n你的狗狗完成遛狗{{owner_walks}}次\n你作为遛狗人完成遛狗{{walker_walks}}次\n累计遛狗{{minutes}}分钟\n",
+    html: r#"<p>{{from}}至{{to}}:</p>
+<ul>
+<li>你的狗狗完成遛狗{{owner_walks}}次</li>
+<li>你作为遛狗人完成遛狗{{walker_walks}}次</li>
+<li>累计遛狗{{minutes}}分钟</li>
+</ul>"#,
+};
+
+pub const VERIFICATION_APPROVED: Template = Template {
+    subject: "遛狗人身份认证已通过",
+    text: "你的遛狗人身份认证已通过, 现在可以接单了.\n",
+    html: "<p>你的遛狗人身份认证已通过, 现在可以接单了.</p>",
+};
+
+pub const VERIFICATION_REJECTED: Template = Template {
+    subject: "遛狗人身份认证未通过",
+    text: "你的遛狗人身份认证未通过.\n\n原因: {{reason}}\n\n请按要求重新上传证件后再次提交.\n",
+    html: r#"<p>你的遛狗人身份认证未通过.</p>
+<p>原因: {{reason}}</p>
+<p>请按要求重新上传证件后再次提交.</p>"#,
+};
@@ -0,0 +1,1859 @@
+use chrono::{DateTime, Utc};
+use nb_field_names::FieldNames;
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+use utoipa::ToSchema;
+
+use crate::error::Error;
+use crate::ids::{BreedId, DogId, WalkRequestId};
+use crate::repository::Cursor;
+use crate::translation::Language;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum Category {
+    Small,
+    Medium,
+    Large,
+    Giant,
+}
+
+impl Display for Category {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Category::Small => "Small",
+                Category::Medium => "Medium",
+                Category::Large => "Large",
+                Category::Giant => "Giant",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FieldNames)]
+pub struct Breed {
+    pub id: BreedId,
+    pub category: Category,
+    pub name: String,
+    pub updated_at: Option<DateTime<Utc>>, // 狗狗中内嵌的品种没有该字段
+}
+
+// 性别
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum Gender {
+    Other,
+    Male,
+    Female,
+}
+
+impl Default for Gender {
+    fn default() -> Self {
+        Self::Other
+    }
+}
+
+impl Display for Gender {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Gender::Other => "Other",
+                Gender::Male => "Male",
+                Gender::Female => "Female",
+            }
+        )
+    }
+}
+
+// 狗狗
+#[derive(Debug, Clone, Serialize, Deserialize, FieldNames)]
+pub struct Dog {
+    pub id: DogId,
+    pub name: String,
+    pub gender: Gender,
+    pub breed: Breed,            // 品种
+    pub birthday: DateTime<Utc>, // 生日
+    // pub is_sterilized: bool,     // 是否绝育
+    // pub introduction: String,
+    pub owner_id: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub portrait_id: Option<String>,
+    #[serde(default)]
+    pub photos: Vec<String>, // 相册上传ID, 按展示顺序排列
+    #[serde(default)]
+    pub medical_flags: Vec<String>, // 过敏、用药等需要照看者注意的健康状况
+    #[serde(default)]
+    pub version: i64, // 每次更新加1, 用于乐观并发控制
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl Breed {
+    // 列表接口允许排序的字段
+    pub fn sortable_fields() -> Vec<String> {
+        vec![Self::name(), Self::category(), Self::updated_at()]
+    }
+}
+
+impl Dog {
+    // 列表接口允许排序的字段
+    pub fn sortable_fields() -> Vec<String> {
+        vec![
+            Self::name(),
+            Self::gender(),
+            Self::birthday(),
+            Self::owner_id(),
+            Self::updated_at(),
+        ]
+    }
+}
+
+// 狗狗信息更新结果, 离线编辑早于服务端最近一次更新时返回服务端版本以供客户端合并
+#[derive(Debug, Clone)]
+pub enum DogUpdateOutcome {
+    Updated,
+    Unchanged,
+    Conflict(Dog),
+}
+
+// 按字段选择查询时未投影的字段取默认值
+#[derive(Debug, Clone, Deserialize, Serialize, FieldNames, Default)]
+#[serde(default)]
+pub struct WalkRequest {
+    pub id: WalkRequestId,
+    pub dogs: Vec<Dog>,
+    pub should_start_after: Option<DateTime<Utc>>,
+    pub should_start_before: Option<DateTime<Utc>>,
+    pub should_end_after: Option<DateTime<Utc>>,
+    pub should_end_before: Option<DateTime<Utc>>,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub distance: Option<f64>,
+    pub canceled_at: Option<DateTime<Utc>>,
+    pub accepted_by: Option<String>,
+    pub accepted_at: Option<DateTime<Utc>>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub status: String,
+    pub acceptances: Option<Vec<String>>,
+    pub priority_walkers: Option<Vec<String>>, // 优先推送的收藏遛狗人
+    pub priority_until: Option<DateTime<Utc>>, // 优先推送截止时间, 此前仅对priority_walkers开放
+    pub notify_walker_nearby: bool,            // 遛狗人接近接狗地点时通知狗狗主人
+    pub walker_nearby_notified_at: Option<DateTime<Utc>>,
+    pub route_preference: Option<RoutePreference>, // 狗狗主人设定的路线偏好
+    pub route_assessment: Option<RouteAssessment>, // 结束时的轨迹偏离评估
+    pub deleted_at: Option<DateTime<Utc>>,         // 软删除时间, 默认查询不返回已删除的请求
+    pub partner_id: Option<String>,                // 代狗狗主人创建请求的合作方
+    #[serde(default)]
+    pub version: i64,         // 每次更新加1, 用于乐观并发控制
+    pub created_by: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl WalkRequest {
+    // 优先推送期内仅对收藏的遛狗人开放
+    pub fn is_open_to(&self, user_id: &str, now: DateTime<Utc>) -> bool {
+        self.priority_until.map_or(true, |until| until <= now)
+            || self
+                .priority_walkers
+                .as_ref()
+                .is_some_and(|walkers| walkers.iter().any(|w| w == user_id))
+    }
+}
+
+// 自checkpoint以来的遛狗请求变化, 已取消或已删除的请求视为从列表中删除.
+// checkpoint为最后一条变化的(updated_at, _id), 同一时间戳的多条变化分页时不会遗漏
+#[derive(Debug, Clone)]
+pub struct WalkRequestChanges {
+    pub created: Vec<WalkRequest>,
+    pub updated: Vec<WalkRequest>,
+    pub deleted: Vec<String>,
+    pub checkpoint: Cursor,
+    pub has_more: bool, // 变化数量超过单次上限, 需以新的checkpoint继续拉取
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, FieldNames, Default)]
+pub struct WalkingLocation {
+    pub id: String,
+    pub request_id: String,
+    pub longitude: f64,
+    pub latitude: f64,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+// 遛狗人身份认证状态
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub enum VerificationStatus {
+    Unverified,
+    Pending,
+    Verified,
+    Rejected,
+}
+
+impl Default for VerificationStatus {
+    fn default() -> Self {
+        Self::Unverified
+    }
+}
+
+impl Display for VerificationStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                VerificationStatus::Unverified => "Unverified",
+                VerificationStatus::Pending => "Pending",
+                VerificationStatus::Verified => "Verified",
+                VerificationStatus::Rejected => "Rejected",
+            }
+        )
+    }
+}
+
+// 遛狗人
+#[derive(Debug, Clone, Deserialize, Serialize, FieldNames, Default)]
+pub struct Walker {
+    pub id: String,
+    pub user_id: String,
+    pub verification_status: VerificationStatus,
+    pub id_document_ids: Vec<String>, // 身份证件上传ID
+    pub rejected_reason: Option<String>,
+    pub longitude: Option<f64>,
+    pub latitude: Option<f64>,
+    pub avatar_id: Option<String>, // 头像上传ID, 取自狗狗主人资料
+    pub submitted_at: Option<DateTime<Utc>>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+// 遛狗人搜索结果, score由评分、完成次数和距离综合计算
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct RankedWalker {
+    pub user_id: String,
+    pub longitude: f64,
+    pub latitude: f64,
+    pub distance: f64,
+    pub average_rating: f64,
+    pub completed_walks: i64,
+    pub score: f64,
+    pub avatar_id: Option<String>,
+}
+
+// 评价方向, 早期的评价没有该字段, 都是狗狗主人评价遛狗人
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default, ToSchema)]
+pub enum ReviewKind {
+    #[default]
+    OwnerToWalker,
+    WalkerToOwner,
+}
+
+impl Display for ReviewKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ReviewKind::OwnerToWalker => "OwnerToWalker",
+                ReviewKind::WalkerToOwner => "WalkerToOwner",
+            }
+        )
+    }
+}
+
+// 评价的公开状态, 双方都提交或超过公开期限后从Hidden变为Revealed, 不会再隐藏
+// 早期的评价没有该字段, 视为已公开
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default, ToSchema)]
+pub enum ReviewState {
+    Hidden,
+    #[default]
+    Revealed,
+}
+
+impl Display for ReviewState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ReviewState::Hidden => "Hidden",
+                ReviewState::Revealed => "Revealed",
+            }
+        )
+    }
+}
+
+// 遛狗完成后双方的互评, kind区分评价方向
+#[derive(Debug, Clone, Deserialize, Serialize, FieldNames, Default)]
+pub struct Review {
+    pub id: String,
+    pub walk_request_id: String,
+    pub walker_id: String,
+    pub owner_id: String,
+    #[serde(default)]
+    pub kind: ReviewKind,
+    pub rating: i32, // 1-5星
+    pub content: String,
+    #[serde(default)]
+    pub photo_ids: Vec<String>, // 评价附带照片的上传ID
+    #[serde(default)]
+    pub helpful_count: i64, // 其他用户的有用投票数, 查询时汇总
+    #[serde(default)]
+    pub state: ReviewState,
+    pub revealed_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+// 评价等用户内容的译文缓存, 按原文id和目标语言保存. 原文修改后source_hash不再匹配, 需重新翻译
+#[derive(Debug, Clone, Deserialize, Serialize, FieldNames)]
+pub struct Translation {
+    pub source_id: String,
+    pub language: Language,
+    pub source_hash: String,
+    pub text: String,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+// 狗狗主人最近一段时间的请求和评价汇总
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct OwnerReputationStats {
+    pub requests: i64,
+    pub accepted: i64,
+    pub canceled_after_acceptance: i64,
+    pub no_shows: i64, // 已接单未取消, 过了预定时间仍未开始
+    pub average_rating: f64,
+    pub review_count: i64,
+}
+
+// 狗狗主人信誉, 遛狗人接单前参考. score为0-100, 比例的分母为被接单的请求数
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct OwnerReputation {
+    pub owner_id: String,
+    pub requests: i64,
+    pub cancellation_rate: f64,
+    pub no_show_rate: f64,
+    pub payment_failures: i64,
+    pub average_rating: f64, // 遛狗人对狗狗主人的评分
+    pub review_count: i64,
+    pub score: f64,
+}
+
+// 遛狗人统计数据, 距离单位为米
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct WalkerStats {
+    pub walker_id: String,
+    pub completed_walks: i64,
+    pub total_distance: f64,
+    pub cancellation_rate: f64, // 已取消的接单占全部接单的比例
+    pub average_rating: f64,
+    pub review_count: i64,
+}
+
+// 狗狗主人
+#[derive(Debug, Clone, Deserialize, Serialize, FieldNames, Default)]
+pub struct Owner {
+    pub id: String,
+    pub user_id: String,
+    pub nickname: Option<String>,
+    pub avatar_id: Option<String>,
+    #[serde(default)]
+    pub emergency_contacts: Vec<EmergencyContact>, // 仅用于导出遛狗请求, 不出现在公开档案中
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+// 狗狗主人联系不上时的紧急联系人, 手机号与其他手机号字段一样加密保存
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct EmergencyContact {
+    pub name: String,
+    pub phone: String,
+    pub relationship: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct OwnerProfile {
+    pub user_id: String,
+    pub nickname: Option<String>,
+    pub avatar_id: Option<String>,
+    pub dog_count: i64,
+    pub member_since: Option<DateTime<Utc>>,
+    pub achievements: Vec<Achievement>,
+}
+
+// 狗狗主人屏蔽的遛狗人
+#[derive(Debug, Clone, Deserialize, Serialize, FieldNames, Default)]
+pub struct Block {
+    pub id: String,
+    pub owner_id: String,
+    pub walker_id: String,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub enum ReportStatus {
+    Pending,
+    Resolved,
+    Dismissed,
+}
+
+impl Default for ReportStatus {
+    fn default() -> Self {
+        Self::Pending
+    }
+}
+
+impl Display for ReportStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ReportStatus::Pending => "Pending",
+                ReportStatus::Resolved => "Resolved",
+                ReportStatus::Dismissed => "Dismissed",
+            }
+        )
+    }
+}
+
+// 举报
+#[derive(Debug, Clone, Deserialize, Serialize, FieldNames, Default)]
+pub struct Report {
+    pub id: String,
+    pub reporter_id: String,
+    pub target_user_id: String,
+    pub walk_request_id: Option<String>,
+    pub reason: String,
+    pub description: String,
+    pub status: ReportStatus,
+    pub resolution_note: Option<String>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+// 狗狗主人收藏的遛狗人
+#[derive(Debug, Clone, Deserialize, Serialize, FieldNames, Default)]
+pub struct Favorite {
+    pub id: String,
+    pub owner_id: String,
+    pub walker_id: String,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, ToSchema)]
+pub enum AchievementKind {
+    FirstWalk,            // 完成第一次遛狗
+    HundredKilometers,    // 累计遛狗100公里
+    FiftyFiveStarReviews, // 获得50个五星评价
+}
+
+impl Display for AchievementKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                AchievementKind::FirstWalk => "FirstWalk",
+                AchievementKind::HundredKilometers => "HundredKilometers",
+                AchievementKind::FiftyFiveStarReviews => "FiftyFiveStarReviews",
+            }
+        )
+    }
+}
+
+// 用户获得的成就
+#[derive(Debug, Clone, Deserialize, Serialize, FieldNames)]
+pub struct Achievement {
+    pub id: String,
+    pub user_id: String,
+    pub kind: AchievementKind,
+    pub achieved_at: Option<DateTime<Utc>>,
+}
+
+// 计算成就所需的用户累计数据, 距离单位为米
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct AchievementProgress {
+    pub user_id: String,
+    pub finished_walks: i64,
+    pub total_distance: f64,
+    pub five_star_reviews: i64,
+}
+
+// 刷新令牌, 仅保存令牌的哈希值; 同一次登录轮换出的令牌属于同一family
+#[derive(Debug, Clone, Deserialize, Serialize, FieldNames)]
+pub struct RefreshToken {
+    pub id: String,
+    pub family_id: String,
+    pub user_id: String,
+    pub phone: String,
+    pub token_hash: String,
+    pub used: bool,
+    pub revoked: bool,
+    pub device_id: Option<String>,
+    pub platform: Option<String>,
+    pub signed_in_at: Option<DateTime<Utc>>, // 整个family首次登录的时间, 轮换时沿用
+    pub expires_at: DateTime<Utc>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+// 客户端设备信息, 取自登录请求的X-Device-ID和X-Platform请求头
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Device {
+    pub device_id: Option<String>,
+    pub platform: Option<String>,
+}
+
+// 登录会话, 对应一个仍然有效的刷新令牌family, 最近一次刷新的时间视为最后活跃时间
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Session {
+    pub id: String,
+    pub device_id: Option<String>,
+    pub platform: Option<String>,
+    pub signed_in_at: Option<DateTime<Utc>>,
+    pub last_seen_at: Option<DateTime<Utc>>,
+}
+
+// 账号合并中某个集合字段被改写的记录数
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MergedReference {
+    pub collection: String,
+    pub field: String,
+    pub count: u64,
+}
+
+// 账号合并审计记录
+#[derive(Debug, Clone, Deserialize, Serialize, FieldNames)]
+pub struct AccountMerge {
+    pub id: String,
+    pub from_user_id: String,
+    pub to_user_id: String,
+    pub operator_id: String,
+    pub references: Vec<MergedReference>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+// 注销账号的清理结果, 同时作为审计记录保存
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct AccountDeletion {
+    pub id: String,
+    pub user_id: String,
+    pub dogs_reassigned_to: Option<String>,
+    pub dogs: u64,
+    pub canceled_walk_requests: u64,
+    pub removed_acceptances: u64,
+    pub scheduled_uploads: u64,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+// 验证码用途, 不同用途的验证码不能混用
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum OtpPurpose {
+    Login,
+    PasswordReset,
+    PhoneChange,
+}
+
+impl Display for OtpPurpose {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                OtpPurpose::Login => "Login",
+                OtpPurpose::PasswordReset => "PasswordReset",
+                OtpPurpose::PhoneChange => "PhoneChange",
+            }
+        )
+    }
+}
+
+// 短信验证码, 仅保存验证码的哈希值
+#[derive(Debug, Clone, Deserialize, Serialize, FieldNames)]
+pub struct Otp {
+    pub id: String,
+    pub phone: String,
+    pub purpose: OtpPurpose,
+    pub code_hash: String,
+    pub used: bool,
+    #[serde(default)]
+    pub failed_attempts: i64, // 输错的次数, 达到上限后作废
+    pub expires_at: DateTime<Utc>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+// 照片识别得到的品种建议
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BreedSuggestion {
+    pub breed: Breed,
+    pub confidence: f64,
+}
+
+// 重置密码令牌, 验证码校验通过后签发, 一次性使用
+#[derive(Debug, Clone, Deserialize, Serialize, FieldNames)]
+pub struct PasswordResetToken {
+    pub id: String,
+    pub phone: String,
+    pub token_hash: String,
+    pub used: bool,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+// 更换后的手机号, 每个用户一条. auth_service以注册时的手机号作为登录标识且不支持修改,
+// 调用auth_service前把当前手机号映射回注册手机号. 两个手机号都与其他手机号字段一样加密保存
+#[derive(Debug, Clone, Deserialize, Serialize, FieldNames)]
+pub struct PhoneBinding {
+    pub id: String,
+    pub user_id: String,
+    pub phone: String,
+    pub auth_phone: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+// 第三方登录渠道
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum OAuthProviderKind {
+    WeChat,
+    Apple,
+}
+
+impl Display for OAuthProviderKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                OAuthProviderKind::WeChat => "WeChat",
+                OAuthProviderKind::Apple => "Apple",
+            }
+        )
+    }
+}
+
+impl FromStr for OAuthProviderKind {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "wechat" => Ok(OAuthProviderKind::WeChat),
+            "apple" => Ok(OAuthProviderKind::Apple),
+            _ => Err(Error::msg("不支持的登录方式")),
+        }
+    }
+}
+
+// 第三方账号与手机号账号的绑定关系
+#[derive(Debug, Clone, Deserialize, Serialize, FieldNames)]
+pub struct OAuthAccount {
+    pub id: String,
+    pub provider: OAuthProviderKind,
+    pub subject: String,
+    pub user_id: String,
+    pub phone: String,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+// 未绑定的第三方账号登录时签发, 验证手机号后用于完成绑定, 一次性使用
+#[derive(Debug, Clone, Deserialize, Serialize, FieldNames)]
+pub struct OAuthLinkToken {
+    pub id: String,
+    pub provider: OAuthProviderKind,
+    pub subject: String,
+    pub token_hash: String,
+    pub used: bool,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone)]
+pub enum OAuthLoginOutcome {
+    LoggedIn(OAuthAccount),
+    LinkRequired(String),
+}
+
+// 头像质量问题, 返回给客户端作为提示代码
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub enum PortraitIssue {
+    Blurry,
+    TooDark,
+    NoDogDetected,
+}
+
+// 头像质量检查模式: 仅提示或拒绝不合格头像
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum PortraitCheckMode {
+    Warn,
+    Enforce,
+}
+
+impl FromStr for PortraitCheckMode {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "warn" => Ok(PortraitCheckMode::Warn),
+            "enforce" => Ok(PortraitCheckMode::Enforce),
+            _ => Err(Error::msg("invalid portrait check mode")),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum PortraitUpdateOutcome {
+    Updated {
+        has_updated: bool,
+        warnings: Vec<PortraitIssue>,
+    },
+    Rejected(Vec<PortraitIssue>),
+}
+
+// 客服工单分类
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub enum TicketCategory {
+    Account,
+    WalkRequest,
+    Payment,
+    Other,
+}
+
+impl Display for TicketCategory {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                TicketCategory::Account => "Account",
+                TicketCategory::WalkRequest => "WalkRequest",
+                TicketCategory::Payment => "Payment",
+                TicketCategory::Other => "Other",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub enum TicketStatus {
+    Open,
+    InProgress,
+    Resolved,
+    Closed,
+}
+
+impl Default for TicketStatus {
+    fn default() -> Self {
+        Self::Open
+    }
+}
+
+impl Display for TicketStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                TicketStatus::Open => "Open",
+                TicketStatus::InProgress => "InProgress",
+                TicketStatus::Resolved => "Resolved",
+                TicketStatus::Closed => "Closed",
+            }
+        )
+    }
+}
+
+impl TicketStatus {
+    // 已关闭的工单不能再变更状态, 已解决的工单可以重新打开
+    pub fn can_transition_to(&self, to: TicketStatus) -> bool {
+        matches!(
+            (self, to),
+            (TicketStatus::Open, TicketStatus::InProgress)
+                | (TicketStatus::Open, TicketStatus::Closed)
+                | (TicketStatus::InProgress, TicketStatus::Resolved)
+                | (TicketStatus::InProgress, TicketStatus::Closed)
+                | (TicketStatus::Resolved, TicketStatus::InProgress)
+                | (TicketStatus::Resolved, TicketStatus::Closed)
+        )
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct TicketMessage {
+    pub author_id: String,
+    pub from_staff: bool,
+    pub content: String,
+    pub attachment_ids: Vec<String>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+// 客服工单, 往来消息内嵌在工单中
+#[derive(Debug, Clone, Deserialize, Serialize, FieldNames)]
+pub struct Ticket {
+    pub id: String,
+    pub user_id: String,
+    pub category: TicketCategory,
+    pub subject: String,
+    pub status: TicketStatus,
+    pub assignee_id: Option<String>,
+    pub messages: Vec<TicketMessage>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+// 帮助中心文章, 每次编辑保存为新版本, 按slug和locale区分
+#[derive(Debug, Clone, Deserialize, Serialize, FieldNames, Default)]
+pub struct HelpArticle {
+    pub id: String,
+    pub slug: String,
+    pub locale: String,
+    pub version: i64,
+    pub title: String,
+    pub markdown: String,
+    pub author_id: String,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+// 品种的照护和遛狗建议, 每个品种每种语言一份, 由管理员维护
+#[derive(Debug, Clone, Deserialize, Serialize, FieldNames)]
+pub struct BreedCareTips {
+    pub id: String,
+    pub breed_id: String,
+    pub locale: String,
+    pub tips: Vec<String>,
+    pub updated_by: String,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+// 遛狗人收款方式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub enum PayoutMethod {
+    Alipay,
+    WeChat,
+    Bank,
+}
+
+impl Display for PayoutMethod {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                PayoutMethod::Alipay => "Alipay",
+                PayoutMethod::WeChat => "WeChat",
+                PayoutMethod::Bank => "Bank",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub enum PayoutAccountStatus {
+    Pending,
+    Verified,
+    Failed,
+}
+
+impl Display for PayoutAccountStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                PayoutAccountStatus::Pending => "Pending",
+                PayoutAccountStatus::Verified => "Verified",
+                PayoutAccountStatus::Failed => "Failed",
+            }
+        )
+    }
+}
+
+// 遛狗人收款账户, 只保存脱敏后的户名和账号, 完整信息由打款服务保管
+#[derive(Debug, Clone, Deserialize, Serialize, FieldNames)]
+pub struct PayoutAccount {
+    pub id: String,
+    pub user_id: String,
+    pub method: PayoutMethod,
+    pub masked_holder_name: String,
+    pub masked_account_number: String,
+    pub bank_name: Option<String>,
+    pub provider_reference: String,
+    pub status: PayoutAccountStatus,
+    pub failure_reason: Option<String>,
+    pub verified_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl PayoutAccount {
+    // 收款账户核验通过后才能提现
+    pub fn can_withdraw(&self) -> bool {
+        self.status == PayoutAccountStatus::Verified
+    }
+}
+
+// 账本流水类型: 收入入账, 提现冻结, 提现被拒绝或打款失败时退回, 迟取消罚金, 退款扣回
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub enum LedgerEntryKind {
+    Earning,
+    Withdrawal,
+    WithdrawalReversal,
+    CancellationPenalty,
+    Refund,
+}
+
+impl Display for LedgerEntryKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                LedgerEntryKind::Earning => "Earning",
+                LedgerEntryKind::Withdrawal => "Withdrawal",
+                LedgerEntryKind::WithdrawalReversal => "WithdrawalReversal",
+                LedgerEntryKind::CancellationPenalty => "CancellationPenalty",
+                LedgerEntryKind::Refund => "Refund",
+            }
+        )
+    }
+}
+
+// 遛狗费用报价, 金额以分为单位, 遛狗人收入为总价扣除平台服务费
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct WalkQuote {
+    pub price: i64,
+    pub platform_fee: i64,
+    pub walker_earning: i64,
+}
+
+// 遛狗人账本流水, 金额以分为单位, 入账为正出账为负, 余额为流水之和
+#[derive(Debug, Clone, Deserialize, Serialize, FieldNames)]
+pub struct LedgerEntry {
+    pub id: String,
+    pub user_id: String,
+    pub kind: LedgerEntryKind,
+    pub amount: i64,
+    pub withdrawal_id: Option<String>,
+    pub walk_request_id: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub enum WithdrawalStatus {
+    Requested,
+    Approved,
+    Processing,
+    Paid,
+    Failed,
+    Rejected,
+}
+
+impl Display for WithdrawalStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                WithdrawalStatus::Requested => "Requested",
+                WithdrawalStatus::Approved => "Approved",
+                WithdrawalStatus::Processing => "Processing",
+                WithdrawalStatus::Paid => "Paid",
+                WithdrawalStatus::Failed => "Failed",
+                WithdrawalStatus::Rejected => "Rejected",
+            }
+        )
+    }
+}
+
+impl WithdrawalStatus {
+    // 被拒绝或打款失败的提现需要把冻结的金额退回账本
+    pub fn is_refunded(&self) -> bool {
+        matches!(self, WithdrawalStatus::Rejected | WithdrawalStatus::Failed)
+    }
+}
+
+// 提现申请, 申请时即从账本扣除金额, 审核通过后由打款任务提交给打款服务
+#[derive(Debug, Clone, Deserialize, Serialize, FieldNames)]
+pub struct Withdrawal {
+    pub id: String,
+    pub user_id: String,
+    pub amount: i64,
+    pub status: WithdrawalStatus,
+    pub destination_reference: String,
+    pub payout_reference: Option<String>,
+    pub failure_reason: Option<String>,
+    pub reviewed_by: Option<String>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub paid_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+// 用户在时间窗口内的付款尝试统计, 用于识别异常的付款频率和金额
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct PaymentAttemptStats {
+    pub failed: i64,
+    pub succeeded_amount: i64,
+}
+
+// 上传图片的缩略图规格
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageSize {
+    Small,
+    Medium,
+    Large,
+}
+
+impl Display for ImageSize {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ImageSize::Small => "small",
+                ImageSize::Medium => "medium",
+                ImageSize::Large => "large",
+            }
+        )
+    }
+}
+
+impl ImageSize {
+    // 缩放后的最长边, 单位为像素
+    pub fn max_dimension(&self) -> u32 {
+        match self {
+            ImageSize::Small => 128,
+            ImageSize::Medium => 512,
+            ImageSize::Large => 1024,
+        }
+    }
+}
+
+// 上传图片的缩略图, 缩略图本身也保存为一条上传记录
+#[derive(Debug, Clone, Deserialize, Serialize, FieldNames)]
+pub struct UploadVariant {
+    pub upload_id: String,
+    pub size: ImageSize,
+    pub variant_id: String,
+}
+
+// 遛狗人取消时距预定开始时间的档位
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub enum CancellationPenaltyTier {
+    Late,     // 开始前24小时内
+    VeryLate, // 开始前2小时内
+    NoShow,   // 已过预定开始时间
+}
+
+impl Display for CancellationPenaltyTier {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                CancellationPenaltyTier::Late => "Late",
+                CancellationPenaltyTier::VeryLate => "VeryLate",
+                CancellationPenaltyTier::NoShow => "NoShow",
+            }
+        )
+    }
+}
+
+// 遛狗人迟取消的罚金明细, 罚金从遛狗人账本扣除, 余额可以为负
+#[derive(Debug, Clone, Deserialize, Serialize, FieldNames)]
+pub struct CancellationPenalty {
+    pub id: String,
+    pub walk_request_id: String,
+    pub walker_id: String,
+    pub owner_id: String,
+    pub tier: CancellationPenaltyTier,
+    pub scheduled_start: Option<DateTime<Utc>>,
+    pub notice_minutes: i64, // 取消时距预定开始的分钟数, 已过开始时间为负
+    pub amount: i64,
+    pub balance_after: i64,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub enum RefundStatus {
+    Requested,
+    Approved,
+    Rejected,
+}
+
+impl Display for RefundStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                RefundStatus::Requested => "Requested",
+                RefundStatus::Approved => "Approved",
+                RefundStatus::Rejected => "Rejected",
+            }
+        )
+    }
+}
+
+// 狗狗主人对已结束遛狗的退款申请, 金额以分为单位. 批准金额可以少于申请金额,
+// walker_debit为批准时从遛狗人账本扣回的部分, 其余由平台服务费承担
+#[derive(Debug, Clone, Deserialize, Serialize, FieldNames)]
+pub struct RefundRequest {
+    pub id: String,
+    pub walk_request_id: String,
+    pub owner_id: String,
+    pub walker_id: String,
+    pub amount: i64,
+    pub reason: String,
+    #[serde(default)]
+    pub evidence_ids: Vec<String>, // 证据图片的上传ID
+    pub status: RefundStatus,
+    pub approved_amount: Option<i64>,
+    pub walker_debit: Option<i64>,
+    pub reviewed_by: Option<String>,
+    pub review_note: Option<String>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub enum MeetAndGreetStatus {
+    Proposed,
+    Accepted,
+    Declined,
+    Completed,
+    Canceled,
+}
+
+impl Display for MeetAndGreetStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                MeetAndGreetStatus::Proposed => "Proposed",
+                MeetAndGreetStatus::Accepted => "Accepted",
+                MeetAndGreetStatus::Declined => "Declined",
+                MeetAndGreetStatus::Completed => "Completed",
+                MeetAndGreetStatus::Canceled => "Canceled",
+            }
+        )
+    }
+}
+
+// 遛狗前狗狗主人与报名或接单的遛狗人的见面, 由狗狗主人发起, 遛狗人确认
+#[derive(Debug, Clone, Deserialize, Serialize, FieldNames)]
+pub struct MeetAndGreet {
+    pub id: String,
+    pub walk_request_id: String,
+    pub owner_id: String,
+    pub walker_id: String,
+    pub status: MeetAndGreetStatus,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub longitude: f64,
+    pub latitude: f64,
+    pub note: Option<String>,
+    pub accepted_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub canceled_by: Option<String>, // 拒绝或取消的一方
+    pub canceled_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+// 遛狗请求内狗狗主人与接单遛狗人之间的聊天消息
+#[derive(Debug, Clone, Deserialize, Serialize, FieldNames)]
+pub struct ChatMessage {
+    pub id: String,
+    pub walk_request_id: String,
+    pub sender_id: String,
+    pub text: String,
+    pub image_ids: Vec<String>, // 已上传图片的ID
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+// 聊天参与者已读到的最后一条消息, 每个遛狗请求每个用户一条
+#[derive(Debug, Clone, Deserialize, Serialize, FieldNames)]
+pub struct ChatReadMarker {
+    pub walk_request_id: String,
+    pub user_id: String,
+    pub message_id: String,
+    pub message_created_at: Option<DateTime<Utc>>, // 已读消息的发送时间, 之后收到的消息计为未读
+    pub updated_at: Option<DateTime<Utc>>,         // 标记已读的时间
+}
+
+// 运营看板快照, 失败打款为统计窗口内的数量
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct OperationsSnapshot {
+    pub active_walks: i64,
+    pub failed_payouts: i64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub enum DirectUploadStatus {
+    Pending,
+    Confirmed,
+}
+
+impl Display for DirectUploadStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                DirectUploadStatus::Pending => "Pending",
+                DirectUploadStatus::Confirmed => "Confirmed",
+            }
+        )
+    }
+}
+
+// 通过预签名地址直传到对象存储的文件, 客户端上传后确认, 确认前不可下载
+#[derive(Debug, Clone, Deserialize, Serialize, FieldNames)]
+pub struct DirectUpload {
+    pub id: String,
+    pub uploader: String,
+    pub key: String,
+    pub filename: String,
+    pub content_type: String,
+    pub size: i64, // 申请时声明的大小, 确认后为实际大小
+    pub status: DirectUploadStatus,
+    pub expires_at: Option<DateTime<Utc>>, // 上传地址的过期时间
+    pub confirmed_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+// 合成监控流程中一个步骤的耗时, 失败的步骤记录错误信息, 之后的步骤不再执行
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SyntheticStep {
+    pub name: String,
+    pub millis: i64,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub enum LocationAccessKind {
+    WalkingLocations, // 遛狗轨迹
+    LivePosition,     // 遛狗人实时位置
+}
+
+impl Display for LocationAccessKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                LocationAccessKind::WalkingLocations => "WalkingLocations",
+                LocationAccessKind::LivePosition => "LivePosition",
+            }
+        )
+    }
+}
+
+// 位置数据访问记录, 每次读取轨迹或实时位置都会记录访问者和被访问的用户
+#[derive(Debug, Clone, Deserialize, Serialize, FieldNames)]
+pub struct LocationAccess {
+    pub id: String,
+    pub accessor_id: String,
+    pub kind: LocationAccessKind,
+    pub walk_request_id: Option<String>,
+    pub subject_ids: Vec<String>, // 位置被读取的用户
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+// 用户角色, 签发访问令牌时写入令牌
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Owner,
+    Walker,
+    Admin,
+}
+
+// 需要二次确认的敏感操作, 每种操作的确认令牌只能用于该操作
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub enum SensitiveAction {
+    DeleteAccount,
+    RequestWithdrawal,
+    ChangePayoutAccount, // 绑定或解绑收款账户
+}
+
+impl Display for SensitiveAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                SensitiveAction::DeleteAccount => "DeleteAccount",
+                SensitiveAction::RequestWithdrawal => "RequestWithdrawal",
+                SensitiveAction::ChangePayoutAccount => "ChangePayoutAccount",
+            }
+        )
+    }
+}
+
+// 敏感操作确认令牌, 用户重新验证密码后签发, 短时间有效且只能使用一次
+#[derive(Debug, Clone, Deserialize, Serialize, FieldNames)]
+pub struct ActionToken {
+    pub id: String,
+    pub user_id: String,
+    pub action: SensitiveAction,
+    pub token_hash: String,
+    pub used: bool,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub enum NotificationKind {
+    WalkerNearby,    // 遛狗人已接近接狗地点
+    RequestAccepted, // 请求已有遛狗人接单
+    WalkStarted,
+    WalkFinished,
+    RequestCanceled, // 已接单的请求被取消
+    MeetAndGreetProposed,
+    MeetAndGreetAccepted,
+    MeetAndGreetCanceled, // 见面被拒绝或取消
+}
+
+impl Display for NotificationKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                NotificationKind::WalkerNearby => "WalkerNearby",
+                NotificationKind::RequestAccepted => "RequestAccepted",
+                NotificationKind::WalkStarted => "WalkStarted",
+                NotificationKind::WalkFinished => "WalkFinished",
+                NotificationKind::RequestCanceled => "RequestCanceled",
+                NotificationKind::MeetAndGreetProposed => "MeetAndGreetProposed",
+                NotificationKind::MeetAndGreetAccepted => "MeetAndGreetAccepted",
+                NotificationKind::MeetAndGreetCanceled => "MeetAndGreetCanceled",
+            }
+        )
+    }
+}
+
+// 站内通知, 客户端拉取展示, 同时由推送任务推送到用户登记的设备
+#[derive(Debug, Clone, Deserialize, Serialize, FieldNames)]
+pub struct Notification {
+    pub id: String,
+    pub user_id: String,
+    pub kind: NotificationKind,
+    pub walk_request_id: Option<String>,
+    pub content: String,
+    #[serde(default)]
+    pub sms: bool, // 紧急通知, 用户开启了短信通知时同时发送短信
+    pub pushed_at: Option<DateTime<Utc>>, // 推送任务领取的时间, 为空表示尚未推送
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+// 推送通道, Android经FCM推送, iOS经APNs推送
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub enum PushPlatform {
+    Android,
+    Ios,
+}
+
+impl Display for PushPlatform {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                PushPlatform::Android => "Android",
+                PushPlatform::Ios => "Ios",
+            }
+        )
+    }
+}
+
+// App登记的推送令牌, 同一令牌只属于最近登记的用户
+#[derive(Debug, Clone, Deserialize, Serialize, FieldNames)]
+pub struct DeviceToken {
+    pub id: String,
+    pub user_id: String,
+    pub platform: PushPlatform,
+    pub token: String,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+// 用户的通知设置, 没有记录时不发送短信和邮件
+#[derive(Debug, Clone, Deserialize, Serialize, FieldNames, Default)]
+pub struct NotificationSettings {
+    pub user_id: String,
+    pub sms_enabled: bool,
+    pub sms_phone: Option<String>, // 接收短信通知的手机号, 与其他手机号字段一样加密保存
+    pub email: Option<String>,     // 接收邮件的地址, 为空时不发送任何邮件
+    #[serde(default)]
+    pub email_receipts: bool,
+    #[serde(default)]
+    pub email_weekly_summary: bool,
+    #[serde(default)]
+    pub email_verification_decisions: bool,
+    pub weekly_summary_sent_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl NotificationSettings {
+    // 用户开启了该类邮件时返回收件地址
+    pub fn email_to(&self, kind: EmailKind) -> Option<&str> {
+        let enabled = match kind {
+            EmailKind::Receipt => self.email_receipts,
+            EmailKind::WeeklySummary => self.email_weekly_summary,
+            EmailKind::VerificationDecision => self.email_verification_decisions,
+        };
+        self.email.as_deref().filter(|_| enabled)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub enum EmailKind {
+    Receipt,              // 提现到账凭证
+    WeeklySummary,        // 每周遛狗汇总
+    VerificationDecision, // 遛狗人身份认证结果
+}
+
+impl Display for EmailKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                EmailKind::Receipt => "Receipt",
+                EmailKind::WeeklySummary => "WeeklySummary",
+                EmailKind::VerificationDecision => "VerificationDecision",
+            }
+        )
+    }
+}
+
+// 待发送的事务邮件, 入队时按模板渲染好正文, 由邮件任务发送
+#[derive(Debug, Clone, Deserialize, Serialize, FieldNames)]
+pub struct Email {
+    pub id: String,
+    pub user_id: String,
+    pub kind: EmailKind,
+    pub to: String,
+    pub subject: String,
+    pub text: String,
+    pub html: String,
+    pub sent_at: Option<DateTime<Utc>>, // 邮件任务领取的时间, 为空表示尚未发送
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, ToSchema)]
+pub struct GeoPoint {
+    pub longitude: f64,
+    pub latitude: f64,
+}
+
+// 圆形禁行区域, 如繁忙道路、狗狗公园
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct NoGoZone {
+    pub longitude: f64,
+    pub latitude: f64,
+    pub radius: f64, // 半径(米)
+    pub label: Option<String>,
+}
+
+// 狗狗主人为遛狗请求设定的路线偏好
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct RoutePreference {
+    pub preferred_route: Vec<GeoPoint>, // 期望路线折线, 为空表示不限路线
+    pub no_go_zones: Vec<NoGoZone>,
+}
+
+impl RoutePreference {
+    pub fn is_empty(&self) -> bool {
+        self.preferred_route.is_empty() && self.no_go_zones.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub enum RouteDeviationKind {
+    OffRoute, // 偏离期望路线
+    NoGoZone, // 进入禁行区域
+}
+
+impl Display for RouteDeviationKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                RouteDeviationKind::OffRoute => "OffRoute",
+                RouteDeviationKind::NoGoZone => "NoGoZone",
+            }
+        )
+    }
+}
+
+// 一段连续偏离路线偏好的轨迹
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RouteDeviation {
+    pub kind: RouteDeviationKind,
+    pub zone: Option<String>, // 进入的禁行区域名称
+    pub longitude: f64,       // 开始偏离的位置
+    pub latitude: f64,
+    pub points: i64,       // 偏离的轨迹点数
+    pub max_distance: f64, // 距期望路线最远的距离或深入禁行区域的距离(米)
+}
+
+// 遛狗结束时实际轨迹相对路线偏好的评估
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RouteAssessment {
+    pub score: f64, // 偏离路线偏好的里程占比(0~100), 越高偏离越多
+    pub deviations: Vec<RouteDeviation>,
+    pub assessed_at: DateTime<Utc>,
+}
+
+// 遛狗结束后实际轨迹与路线偏好的对比
+#[derive(Debug, Clone, Default)]
+pub struct WalkRouteReport {
+    pub walk_request_id: String,
+    pub route: Vec<GeoPoint>,
+    pub route_preference: Option<RoutePreference>,
+    pub route_assessment: Option<RouteAssessment>,
+    pub poi_visits: Vec<PoiVisit>,
+}
+
+// 地点目录中的兴趣点, 如狗狗公园. 设置了边界时按边界多边形判断是否在其中, 否则按圆形范围判断
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Poi {
+    pub id: String,
+    pub name: String,
+    pub longitude: f64, // 中心点
+    pub latitude: f64,
+    pub radius: f64,             // 半径(米)
+    pub boundary: Vec<GeoPoint>, // 边界多边形的顶点, 为空表示按半径判断
+}
+
+// 遛狗途中在兴趣点内的一次停留
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PoiVisit {
+    pub poi_id: String,
+    pub name: String,
+    pub entered_at: DateTime<Utc>,
+    pub left_at: DateTime<Utc>,
+}
+
+impl PoiVisit {
+    pub fn minutes(&self) -> i64 {
+        (self.left_at - self.entered_at).num_minutes()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub enum WalkRequestAuditAction {
+    Accepted, // 遛狗人接单
+    Assigned, // 狗狗主人从报名者中指定遛狗人
+    Canceled,
+    Started,
+    Finished,
+}
+
+impl Display for WalkRequestAuditAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                WalkRequestAuditAction::Accepted => "Accepted",
+                WalkRequestAuditAction::Assigned => "Assigned",
+                WalkRequestAuditAction::Canceled => "Canceled",
+                WalkRequestAuditAction::Started => "Started",
+                WalkRequestAuditAction::Finished => "Finished",
+            }
+        )
+    }
+}
+
+// 清理软删除数据时物理删除的记录数
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PurgedCounts {
+    pub dogs: u64,
+    pub walk_requests: u64,
+    pub walking_locations: u64,
+}
+
+// 打印给寄养合作方的遛狗请求摘要
+#[derive(Debug, Clone)]
+pub struct WalkRequestExport {
+    pub request: WalkRequest,
+    pub dogs: Vec<Dog>, // 狗狗的最新信息, 已被清理的狗狗使用请求中的快照
+    pub owner_nickname: Option<String>,
+    pub emergency_contacts: Vec<EmergencyContact>,
+    pub generated_at: DateTime<Utc>,
+}
+
+// 遛狗请求变更的审计记录, 保存操作人和变更前后的请求快照
+#[derive(Debug, Clone, Deserialize, Serialize, FieldNames)]
+pub struct AuditLog {
+    pub id: String,
+    pub walk_request_id: String,
+    pub actor_id: Option<String>, // 变更时未指明操作人则为空
+    pub action: WalkRequestAuditAction,
+    pub before: Option<WalkRequest>,
+    pub after: WalkRequest,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+// 合作方类型
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub enum PartnerKind {
+    PetStore,
+    VetClinic,
+}
+
+impl Display for PartnerKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                PartnerKind::PetStore => "PetStore",
+                PartnerKind::VetClinic => "VetClinic",
+            }
+        )
+    }
+}
+
+// 合作方API密钥的权限范围
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub enum PartnerScope {
+    CreateWalkRequests,     // 代已授权的狗狗主人创建遛狗请求
+    ReadWalkRequests,       // 查询代为创建的遛狗请求状态
+    ReadWalkerAvailability, // 查询区域内遛狗人的汇总可用情况
+}
+
+impl Display for PartnerScope {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                PartnerScope::CreateWalkRequests => "CreateWalkRequests",
+                PartnerScope::ReadWalkRequests => "ReadWalkRequests",
+                PartnerScope::ReadWalkerAvailability => "ReadWalkerAvailability",
+            }
+        )
+    }
+}
+
+// 合作方(宠物店、宠物医院), 通过API密钥代狗狗主人创建和查询遛狗请求
+#[derive(Debug, Clone, Deserialize, Serialize, FieldNames)]
+pub struct Partner {
+    pub id: String,
+    pub name: String,
+    pub kind: PartnerKind,
+    pub scopes: Vec<PartnerScope>,
+    pub rate_limit: i64, // 每分钟最多请求次数
+    pub disabled: bool,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+// 合作方API密钥, 只保存哈希和用于辨认的前缀
+#[derive(Debug, Clone, Deserialize, Serialize, FieldNames)]
+pub struct PartnerApiKey {
+    pub id: String,
+    pub partner_id: String,
+    pub prefix: String,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+// 狗狗主人授权合作方代为创建遛狗请求
+#[derive(Debug, Clone, Deserialize, Serialize, FieldNames)]
+pub struct PartnerConsent {
+    pub id: String,
+    pub partner_id: String,
+    pub owner_id: String,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+// 合作方按天和接口汇总的调用次数
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PartnerUsage {
+    pub date: String, // UTC日期, 格式为%Y-%m-%d
+    pub endpoint: String,
+    pub count: i64,
+}
+
+// 区域和时间窗口内遛狗人的汇总可用情况, 不包含任何单个遛狗人的信息
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WalkerAvailability {
+    pub available_walkers: i64,
+    pub median_response_seconds: Option<i64>, // 可用人数过少时不返回, 避免推断出单个遛狗人
+}
+
+// 按网格汇总的区域数据, 经纬度为网格中心点. 价格为近期完成的遛狗收入(分)的平均值
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct NeighborhoodStats {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub active_requests: i64,
+    pub priced_walks: i64,
+    pub average_price: Option<f64>,
+}
+
+// 邀请未注册用户的关系类型
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub enum InviteKind {
+    FamilyMember,    // 家庭成员
+    PreferredWalker, // 常用的遛狗人
+}
+
+impl Display for InviteKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                InviteKind::FamilyMember => "FamilyMember",
+                InviteKind::PreferredWalker => "PreferredWalker",
+            }
+        )
+    }
+}
+
+// 通过短信发出的邀请, 链接中的邀请码只保存哈希. 被邀请人用该手机号注册即视为转化
+#[derive(Debug, Clone, Deserialize, Serialize, FieldNames)]
+pub struct Invite {
+    pub id: String,
+    pub inviter_id: String,
+    pub phone: String,
+    pub kind: InviteKind,
+    pub locale: String,
+    pub opened_at: Option<DateTime<Utc>>,
+    pub converted_user_id: Option<String>,
+    pub converted_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+// 按邀请类型汇总的发送、打开和注册转化数量
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct InviteConversion {
+    pub kind: InviteKind,
+    pub sent: i64,
+    pub opened: i64,
+    pub converted: i64,
+}
+
+// 短链接跳转到的App页面类型
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub enum ShortLinkKind {
+    ShareToken, // 分享链接
+    Invite,     // 邀请链接
+    QrCode,     // 二维码
+}
+
+impl Display for ShortLinkKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ShortLinkKind::ShareToken => "ShareToken",
+                ShortLinkKind::Invite => "Invite",
+                ShortLinkKind::QrCode => "QrCode",
+            }
+        )
+    }
+}
+
+impl ShortLinkKind {
+    // 深度链接中对应页面的路径
+    pub fn deep_link_path(&self) -> &'static str {
+        match self {
+            ShortLinkKind::ShareToken => "share",
+            ShortLinkKind::Invite => "invites",
+            ShortLinkKind::QrCode => "qr",
+        }
+    }
+}
+
+// 访问/s/{code}时跳转到App深度链接, 过期后失效
+#[derive(Debug, Clone, Deserialize, Serialize, FieldNames)]
+pub struct ShortLink {
+    pub id: String,
+    pub code: String,
+    pub kind: ShortLinkKind,
+    pub token: String,
+    pub created_by: String,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+// 完整性检查发现的悬空引用类型
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum IntegrityIssueKind {
+    DogMissingBreed,       // 狗狗的品种记录已不存在
+    WalkRequestDeletedDog, // 未结束的遛狗请求中包含已删除的狗狗
+    MissingUpload,         // 引用的上传记录已不存在
+}
+
+impl IntegrityIssueKind {
+    pub const ALL: [IntegrityIssueKind; 3] = [
+        IntegrityIssueKind::DogMissingBreed,
+        IntegrityIssueKind::WalkRequestDeletedDog,
+        IntegrityIssueKind::MissingUpload,
+    ];
+}
+
+impl FromStr for IntegrityIssueKind {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dog_missing_breed" => Ok(IntegrityIssueKind::DogMissingBreed),
+            "walk_request_deleted_dog" => Ok(IntegrityIssueKind::WalkRequestDeletedDog),
+            "missing_upload" => Ok(IntegrityIssueKind::MissingUpload),
+            _ => Err(Error::msg("invalid integrity issue kind")),
+        }
+    }
+}
+
+// 定时检查发现问题后的处理方式, 未配置的类型只报告
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum IntegrityPolicy {
+    #[default]
+    Report,
+    Fix,
+}
+
+impl FromStr for IntegrityPolicy {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "report" => Ok(IntegrityPolicy::Report),
+            "fix" => Ok(IntegrityPolicy::Fix),
+            _ => Err(Error::msg("invalid integrity policy")),
+        }
+    }
+}
+
+// record_id所在记录的field字段引用了不存在的reference_id
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct IntegrityIssue {
+    pub kind: IntegrityIssueKind,
+    pub collection: String,
+    pub record_id: String,
+    pub field: String,
+    pub reference_id: String,
+    #[serde(default)]
+    pub fixed: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct IntegrityReport {
+    pub checked_at: DateTime<Utc>,
+    pub issues: Vec<IntegrityIssue>,
+}
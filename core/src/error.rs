@@ -0,0 +1,149 @@
+use std::fmt::{Debug, Display};
+
+// 错误类别, 接口层据此选择响应状态码. 除Internal外消息都可以直接展示给用户
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorKind {
+    #[default]
+    Internal, // 存储、外部服务等内部错误, 消息不返回给客户端
+    Invalid, // 参数不合法或不满足业务规则
+    NotFound,
+    Forbidden,
+    Conflict, // 并发修改冲突, 客户端应刷新后重试; 或违反唯一约束, 此时fields为冲突的字段
+}
+
+pub struct Error {
+    kind: ErrorKind,
+    message: String,
+    cause: Option<Box<dyn Display + Send + Sync>>,
+    fields: Vec<String>, // 违反唯一约束的字段
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(cause) = &self.cause {
+            return write!(f, "{}: {}", self.message, cause);
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Debug for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(cause) = &self.cause {
+            return write!(f, "{}: {}", self.message, cause);
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error {
+    pub fn new<S>(message: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            kind: ErrorKind::Internal,
+            message: message.into(),
+            cause: None,
+            fields: Vec::new(),
+        }
+    }
+
+    pub fn with_cause(self, cause: impl Display + Send + Sync + 'static) -> Self {
+        Self {
+            cause: Some(Box::new(cause)),
+            ..self
+        }
+    }
+
+    pub fn msg(msg: &str) -> Self {
+        Self {
+            kind: ErrorKind::Invalid,
+            message: msg.into(),
+            cause: None,
+            fields: Vec::new(),
+        }
+    }
+
+    pub fn from_error<E>(err: E) -> Self
+    where
+        E: Display,
+    {
+        Self {
+            kind: ErrorKind::Internal,
+            message: err.to_string(),
+            cause: None,
+            fields: Vec::new(),
+        }
+    }
+
+    pub fn wrap<E>(err: E, msg: &str) -> Self
+    where
+        E: Display + Send + Sync + 'static,
+    {
+        Self {
+            kind: ErrorKind::Internal,
+            message: msg.into(),
+            cause: Some(Box::new(err)),
+            fields: Vec::new(),
+        }
+    }
+
+    pub fn not_found(msg: &str) -> Self {
+        Self {
+            kind: ErrorKind::NotFound,
+            message: msg.into(),
+            cause: None,
+            fields: Vec::new(),
+        }
+    }
+
+    pub fn forbidden(msg: &str) -> Self {
+        Self {
+            kind: ErrorKind::Forbidden,
+            message: msg.into(),
+            cause: None,
+            fields: Vec::new(),
+        }
+    }
+
+    pub fn conflict(msg: &str) -> Self {
+        Self {
+            kind: ErrorKind::Conflict,
+            message: msg.into(),
+            cause: None,
+            fields: Vec::new(),
+        }
+    }
+
+    // 与已有记录的唯一字段重复, 无法确定字段时fields为空
+    pub fn duplicate(fields: &[&str]) -> Self {
+        let message = match fields {
+            [] => "记录已存在".to_owned(),
+            fields => format!("{}已存在", fields.join(", ")),
+        };
+        Self {
+            kind: ErrorKind::Conflict,
+            message,
+            cause: None,
+            fields: fields.iter().map(|f| f.to_string()).collect(),
+        }
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    // 不含原因的消息
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn fields(&self) -> &[String] {
+        &self.fields
+    }
+
+    pub fn is_duplicate(&self, field: &str) -> bool {
+        self.kind == ErrorKind::Conflict && self.fields.iter().any(|f| f == field)
+    }
+}
@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{entities::WalkRequestExport, error::Error};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Pdf,
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Pdf => "application/pdf",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Pdf => "pdf",
+        }
+    }
+}
+
+// 遛狗请求导出的排版渲染, 具体实现位于renderers
+pub trait Renderer {
+    fn render(&self, format: ExportFormat, export: &WalkRequestExport) -> Result<Vec<u8>, Error>;
+}
@@ -0,0 +1,133 @@
+pub const EARTH_RADIUS: f64 = 6_371_008.8; // 地球平均半径(米)
+
+// 两点间球面距离(米)
+pub fn haversine_distance(longitude1: f64, latitude1: f64, longitude2: f64, latitude2: f64) -> f64 {
+    let (lat1, lat2) = (latitude1.to_radians(), latitude2.to_radians());
+    let d_lat = (latitude2 - latitude1).to_radians();
+    let d_lng = (longitude2 - longitude1).to_radians();
+    let a = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lng / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS * a.sqrt().asin()
+}
+
+// 以(longitude, latitude)为圆心、radius(米)为半径的圆的外接矩形, 返回((最小经度, 最大经度), (最小纬度, 最大纬度)).
+// 圆跨越极点或180度经线时经度不做限制
+pub fn bounding_box(longitude: f64, latitude: f64, radius: f64) -> ((f64, f64), (f64, f64)) {
+    let angle = radius / EARTH_RADIUS;
+    let d_lat = angle.to_degrees();
+    let (min_lat, max_lat) = (latitude - d_lat, latitude + d_lat);
+    let ratio = angle.sin() / latitude.to_radians().cos();
+    if min_lat <= -90.0 || max_lat >= 90.0 || !(0.0..1.0).contains(&ratio) {
+        return ((-180.0, 180.0), (min_lat.max(-90.0), max_lat.min(90.0)));
+    }
+    let d_lng = ratio.asin().to_degrees();
+    if longitude - d_lng < -180.0 || longitude + d_lng > 180.0 {
+        return ((-180.0, 180.0), (min_lat, max_lat));
+    }
+    ((longitude - d_lng, longitude + d_lng), (min_lat, max_lat))
+}
+
+// 点到线段的最短距离(米). 以该点为原点按等距圆柱投影展开为平面计算, 适用于城市范围内的短距离
+pub fn distance_to_segment(longitude: f64, latitude: f64, from: (f64, f64), to: (f64, f64)) -> f64 {
+    let scale = latitude.to_radians().cos();
+    let project = |(lng, lat): (f64, f64)| {
+        (
+            (lng - longitude).to_radians() * scale * EARTH_RADIUS,
+            (lat - latitude).to_radians() * EARTH_RADIUS,
+        )
+    };
+    let (ax, ay) = project(from);
+    let (bx, by) = project(to);
+    let (dx, dy) = (bx - ax, by - ay);
+    let length = dx * dx + dy * dy;
+    let t = if length == 0.0 {
+        0.0
+    } else {
+        (-(ax * dx + ay * dy) / length).clamp(0.0, 1.0)
+    };
+    (ax + t * dx).hypot(ay + t * dy)
+}
+
+// 点到折线(经度, 纬度)的最短距离(米), 折线为空时返回None
+pub fn distance_to_polyline(longitude: f64, latitude: f64, polyline: &[(f64, f64)]) -> Option<f64> {
+    match polyline {
+        [] => None,
+        [(lng, lat)] => Some(haversine_distance(longitude, latitude, *lng, *lat)),
+        _ => polyline
+            .windows(2)
+            .map(|s| distance_to_segment(longitude, latitude, s[0], s[1]))
+            .reduce(f64::min),
+    }
+}
+
+// 点是否在多边形(经度, 纬度)内, 按射线法判断. 城市范围内可以把经纬度直接当作平面坐标
+pub fn point_in_polygon(longitude: f64, latitude: f64, polygon: &[(f64, f64)]) -> bool {
+    let mut inside = false;
+    let mut j = polygon.len().wrapping_sub(1);
+    for (i, &(lng_i, lat_i)) in polygon.iter().enumerate() {
+        let (lng_j, lat_j) = polygon[j];
+        if (lat_i > latitude) != (lat_j > latitude)
+            && longitude < (lng_j - lng_i) * (latitude - lat_i) / (lat_j - lat_i) + lng_i
+        {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 从圆心沿方位角bearing(度)移动distance(米)后的点
+    fn destination(longitude: f64, latitude: f64, bearing: f64, distance: f64) -> (f64, f64) {
+        let (lat, lng, bearing) = (
+            latitude.to_radians(),
+            longitude.to_radians(),
+            bearing.to_radians(),
+        );
+        let angle = distance / EARTH_RADIUS;
+        let lat2 = (lat.sin() * angle.cos() + lat.cos() * angle.sin() * bearing.cos()).asin();
+        let lng2 = lng
+            + (bearing.sin() * angle.sin() * lat.cos()).atan2(angle.cos() - lat.sin() * lat2.sin());
+        (lng2.to_degrees(), lat2.to_degrees())
+    }
+
+    #[test]
+    fn box_contains_the_circle() {
+        let (longitude, latitude, radius) = (116.4, 39.9, 5_000.0);
+        let ((min_lng, max_lng), (min_lat, max_lat)) = bounding_box(longitude, latitude, radius);
+        assert!(max_lng - min_lng < 0.2 && max_lat - min_lat < 0.1);
+        for bearing in (0..360).step_by(5) {
+            let (lng, lat) = destination(longitude, latitude, bearing as f64, radius * 0.999);
+            assert!((min_lng..=max_lng).contains(&lng), "bearing {}", bearing);
+            assert!((min_lat..=max_lat).contains(&lat), "bearing {}", bearing);
+        }
+    }
+
+    #[test]
+    fn circle_across_antimeridian_spans_all_longitudes() {
+        for longitude in [179.99, -179.99] {
+            let ((min_lng, max_lng), (min_lat, max_lat)) = bounding_box(longitude, -16.5, 5_000.0);
+            assert_eq!((min_lng, max_lng), (-180.0, 180.0));
+            assert!(min_lat < -16.5 && max_lat > -16.5 && max_lat - min_lat < 0.1);
+        }
+    }
+
+    #[test]
+    fn circle_over_a_pole_spans_all_longitudes_and_clamps_latitude() {
+        let ((min_lng, max_lng), (_, max_lat)) = bounding_box(30.0, 89.99, 5_000.0);
+        assert_eq!(((min_lng, max_lng), max_lat), ((-180.0, 180.0), 90.0));
+        let ((min_lng, max_lng), (min_lat, _)) = bounding_box(30.0, -89.99, 5_000.0);
+        assert_eq!(((min_lng, max_lng), min_lat), ((-180.0, 180.0), -90.0));
+        // 靠近但不经过极点时经度跨度变大, 仍然包含整个圆
+        let (longitude, latitude, radius) = (30.0, 80.0, 500_000.0);
+        let ((min_lng, max_lng), (min_lat, max_lat)) = bounding_box(longitude, latitude, radius);
+        assert!(max_lng - min_lng < 360.0 && max_lat < 90.0);
+        for bearing in (0..360).step_by(5) {
+            let (lng, lat) = destination(longitude, latitude, bearing as f64, radius * 0.999);
+            assert!((min_lng..=max_lng).contains(&lng), "bearing {}", bearing);
+            assert!((min_lat..=max_lat).contains(&lat), "bearing {}", bearing);
+        }
+    }
+}
@@ -0,0 +1,166 @@
+// 实体id的强类型封装. 反序列化时校验格式, 非法id在接口层即被拒绝, 不会到达存储层;
+// 与ObjectId之间的转换都集中在这里
+use std::{
+    fmt::{self, Display},
+    ops::Deref,
+    str::FromStr,
+};
+
+use bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::error::Error;
+
+// 用户id的最大长度, 用户由认证服务签发, 不一定是ObjectId
+const MAX_USER_ID_LENGTH: usize = 64;
+
+pub fn parse_object_id(id: &str) -> Result<ObjectId, Error> {
+    ObjectId::parse_str(id).map_err(|e| Error::new(format!("invalid id: {id}")).with_cause(e))
+}
+
+pub fn parse_object_ids<S>(ids: &[S]) -> Result<Vec<ObjectId>, Error>
+where
+    S: AsRef<str>,
+{
+    ids.iter().map(|id| parse_object_id(id.as_ref())).collect()
+}
+
+macro_rules! string_id {
+    ($name:ident) => {
+        impl $name {
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = Error;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Self::try_from(s.to_owned())
+            }
+        }
+
+        impl Deref for $name {
+            type Target = str;
+
+            fn deref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(id: $name) -> Self {
+                id.0
+            }
+        }
+
+        impl PartialEq<str> for $name {
+            fn eq(&self, other: &str) -> bool {
+                self.0 == other
+            }
+        }
+
+        impl PartialEq<String> for $name {
+            fn eq(&self, other: &String) -> bool {
+                &self.0 == other
+            }
+        }
+
+        impl PartialEq<$name> for String {
+            fn eq(&self, other: &$name) -> bool {
+                self == &other.0
+            }
+        }
+    };
+}
+
+// 以ObjectId十六进制字符串为值的id, 统一转为小写
+macro_rules! object_id {
+    ($name:ident) => {
+        #[derive(
+            Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize, ToSchema,
+        )]
+        #[serde(try_from = "String", into = "String")]
+        pub struct $name(String);
+
+        string_id!($name);
+
+        impl $name {
+            pub fn new() -> Self {
+                Self(ObjectId::new().to_hex())
+            }
+
+            pub fn object_id(&self) -> ObjectId {
+                ObjectId::parse_str(&self.0).expect("id is validated on construction")
+            }
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl TryFrom<String> for $name {
+            type Error = Error;
+
+            fn try_from(id: String) -> Result<Self, Self::Error> {
+                parse_object_id(&id).map(Self::from)
+            }
+        }
+
+        impl From<ObjectId> for $name {
+            fn from(id: ObjectId) -> Self {
+                Self(id.to_hex())
+            }
+        }
+
+        impl From<$name> for ObjectId {
+            fn from(id: $name) -> Self {
+                id.object_id()
+            }
+        }
+    };
+}
+
+object_id!(DogId);
+object_id!(BreedId);
+object_id!(WalkRequestId);
+
+// 用户id由认证服务签发, 只校验非空、长度和字符集
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize, ToSchema)]
+#[serde(try_from = "String", into = "String")]
+pub struct UserId(String);
+
+string_id!(UserId);
+
+impl TryFrom<String> for UserId {
+    type Error = Error;
+
+    fn try_from(id: String) -> Result<Self, Self::Error> {
+        if id.is_empty() || id.len() > MAX_USER_ID_LENGTH {
+            return Err(Error::new(format!("invalid user id: {id}")));
+        }
+        if !id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(Error::new(format!("invalid user id: {id}")));
+        }
+        Ok(Self(id))
+    }
+}
@@ -0,0 +1,98 @@
+use std::io::Cursor;
+
+use bytes::Bytes;
+use image::{DynamicImage, ImageFormat, ImageOutputFormat};
+use img_parts::{
+    jpeg::{markers, Jpeg},
+    png::Png,
+    webp::{WebP, CHUNK_XMP},
+    DynImage, ImageEXIF,
+};
+
+use crate::error::Error;
+
+// 旋转后重新编码JPEG时使用的质量
+const REENCODE_QUALITY: u8 = 90;
+
+// PNG中保存文本、EXIF和修改时间的块
+const PNG_METADATA_CHUNKS: [[u8; 4]; 5] = [*b"eXIf", *b"tEXt", *b"zTXt", *b"iTXt", *b"tIME"];
+
+// 去除图片中的EXIF(含GPS位置)、XMP和注释等元数据. 带方向标记的JPEG和PNG按方向旋转后重新编码,
+// 其余图片无损地删除元数据. WebP无法重新编码, 方向标记随元数据一起删除. 非图片文件原样返回
+pub fn strip_metadata(content: Bytes) -> Result<Bytes, Error> {
+    let format = match image::guess_format(&content) {
+        Ok(format @ (ImageFormat::Jpeg | ImageFormat::Png | ImageFormat::WebP)) => format,
+        _ => return Ok(content),
+    };
+    let orientation = orientation(&content)?;
+    if orientation != 1 && format != ImageFormat::WebP {
+        let image = image::load_from_memory_with_format(&content, format)
+            .map_err(|e| Error::new("failed to decode image").with_cause(e))?;
+        let output = match format {
+            ImageFormat::Png => ImageOutputFormat::Png,
+            _ => ImageOutputFormat::Jpeg(REENCODE_QUALITY),
+        };
+        let mut out = Cursor::new(Vec::new());
+        apply_orientation(image, orientation)
+            .write_to(&mut out, output)
+            .map_err(|e| Error::new("failed to encode image").with_cause(e))?;
+        return Ok(Bytes::from(out.into_inner()));
+    }
+    match format {
+        ImageFormat::Jpeg => {
+            let mut jpeg = Jpeg::from_bytes(content)
+                .map_err(|e| Error::new("failed to parse image").with_cause(e))?;
+            jpeg.segments_mut().retain(|segment| {
+                !matches!(
+                    segment.marker(),
+                    markers::APP1 | markers::APP13 | markers::COM
+                )
+            });
+            Ok(jpeg.encoder().bytes())
+        }
+        ImageFormat::Png => {
+            let mut png = Png::from_bytes(content)
+                .map_err(|e| Error::new("failed to parse image").with_cause(e))?;
+            png.chunks_mut()
+                .retain(|chunk| !PNG_METADATA_CHUNKS.contains(&chunk.kind()));
+            Ok(png.encoder().bytes())
+        }
+        _ => {
+            let mut webp = WebP::from_bytes(content)
+                .map_err(|e| Error::new("failed to parse image").with_cause(e))?;
+            webp.remove_chunks_by_id(CHUNK_XMP);
+            // set_exif会同时更新VP8X中的元数据标记
+            webp.set_exif(None);
+            Ok(webp.encoder().bytes())
+        }
+    }
+}
+
+// EXIF中的方向标记, 没有EXIF或无法解析时视为正常方向
+fn orientation(content: &Bytes) -> Result<u32, Error> {
+    let exif = DynImage::from_bytes(content.clone())
+        .map_err(|e| Error::new("failed to parse image").with_cause(e))?
+        .and_then(|image| image.exif());
+    Ok(exif
+        .and_then(|exif| exif::Reader::new().read_raw(exif.to_vec()).ok())
+        .and_then(|exif| {
+            exif.get_field(exif::Tag::Orientation, exif::In::PRIMARY)
+                .and_then(|field| field.value.get_uint(0))
+        })
+        .filter(|orientation| (1..=8).contains(orientation))
+        .unwrap_or(1))
+}
+
+// 方向值定义见EXIF规范: 2~4为翻转和180度旋转, 5~8为转置和90度旋转
+fn apply_orientation(image: DynamicImage, orientation: u32) -> DynamicImage {
+    match orientation {
+        2 => image.fliph(),
+        3 => image.rotate180(),
+        4 => image.flipv(),
+        5 => image.rotate90().fliph(),
+        6 => image.rotate90(),
+        7 => image.rotate270().fliph(),
+        8 => image.rotate270(),
+        _ => image,
+    }
+}
@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+
+// 识别模型返回的候选品种, label需与breeds集合中的品种名一致才会被采用
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BreedCandidate {
+    pub label: String,
+    pub confidence: f64,
+}
+
+// 头像质量评估结果, 各项取值范围均为0-1
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortraitAssessment {
+    pub sharpness: f64,
+    pub brightness: f64,
+    pub dog_confidence: f64,
+}
+
+// 图像推理服务, 具体实现位于inference_providers
+pub trait InferenceProvider {
+    async fn recognize_breed(&self, image: Vec<u8>) -> Result<Vec<BreedCandidate>, Error>;
+    async fn assess_portrait(&self, image: Vec<u8>) -> Result<PortraitAssessment, Error>;
+}
@@ -0,0 +1,95 @@
+use std::fmt::{self, Display};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+use crate::error::Error;
+
+// 密钥用途, 每种用途有独立的一组密钥
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyPurpose {
+    Jwt,
+    FieldEncryption,
+    PayoutWebhook,
+}
+
+impl Display for KeyPurpose {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyPurpose::Jwt => write!(f, "jwt"),
+            KeyPurpose::FieldEncryption => write!(f, "field_encryption"),
+            KeyPurpose::PayoutWebhook => write!(f, "payout_webhook"),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Key {
+    pub id: String,
+    pub secret: Vec<u8>,
+}
+
+// 不输出密钥内容
+impl fmt::Debug for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Key").field("id", &self.id).finish()
+    }
+}
+
+// 同一用途下同时有效的一组密钥. 第一个为当前密钥, 用于签名和加密;
+// 其余密钥只用于验证和解密, 轮换时把新密钥放在最前, 旧密钥保留到不再需要为止
+#[derive(Debug, Clone, Default)]
+pub struct KeyRing {
+    keys: Vec<Key>,
+}
+
+impl KeyRing {
+    pub fn new(keys: Vec<Key>) -> Result<Self, Error> {
+        for (i, key) in keys.iter().enumerate() {
+            if key.id.is_empty() || key.id.contains(':') {
+                return Err(Error::new("invalid key id").with_cause(key.id.clone()));
+            }
+            if keys[..i].iter().any(|k| k.id == key.id) {
+                return Err(Error::new("duplicate key id").with_cause(key.id.clone()));
+            }
+        }
+        Ok(Self { keys })
+    }
+
+    // 解析"id:base64密钥,id:base64密钥"格式的配置
+    pub fn parse(s: &str) -> Result<Self, Error> {
+        Self::new(
+            s.split(',')
+                .map(str::trim)
+                .filter(|k| !k.is_empty())
+                .map(|k| {
+                    let (id, secret) = k
+                        .split_once(':')
+                        .ok_or(Error::new("invalid key").with_cause("missing key id"))?;
+                    Ok(Key {
+                        id: id.to_owned(),
+                        secret: STANDARD
+                            .decode(secret)
+                            .map_err(|e| Error::new("invalid key").with_cause(e))?,
+                    })
+                })
+                .collect::<Result<Vec<Key>, Error>>()?,
+        )
+    }
+
+    pub fn current(&self) -> Option<&Key> {
+        self.keys.first()
+    }
+
+    pub fn keys(&self) -> &[Key] {
+        &self.keys
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+// 密钥来源, 具体实现位于key_providers. 没有配置密钥的用途返回空的KeyRing
+pub trait KeyProvider {
+    async fn key_ring(&self, purpose: KeyPurpose) -> Result<KeyRing, Error>;
+}
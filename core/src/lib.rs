@@ -0,0 +1,25 @@
+#![allow(async_fn_in_trait)] // trait只在本项目中使用, 需要Send的调用方按具体实现类型spawn
+
+pub mod cache;
+pub mod chat;
+pub mod email;
+pub mod entities;
+pub mod error;
+pub mod export;
+pub mod geo;
+pub mod ids;
+pub mod image_metadata;
+pub mod inference;
+pub mod keys;
+#[cfg(any(test, feature = "memory"))]
+pub mod memory;
+pub mod oauth;
+pub mod object_store;
+pub mod payout;
+pub mod push;
+pub mod repository;
+pub mod route;
+pub mod service;
+pub mod sms;
+pub mod thumbnail;
+pub mod translation;
@@ -0,0 +1,4112 @@
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use bson::oid::ObjectId;
+use chrono::{DateTime, Utc};
+
+use crate::entities::AuditLog;
+use crate::entities::NeighborhoodStats;
+use crate::entities::PhoneBinding;
+use crate::entities::Poi;
+use crate::entities::ShortLink;
+use crate::entities::{AccountDeletion, AccountMerge, MergedReference};
+use crate::entities::{Achievement, AchievementKind, AchievementProgress};
+use crate::entities::{ActionToken, SensitiveAction};
+use crate::entities::{Block, Favorite, Owner, RankedWalker, Report, ReportStatus, Review};
+use crate::entities::{Breed, BreedCareTips, Dog, Gender, Otp, OtpPurpose, RefreshToken};
+use crate::entities::{CancellationPenalty, ImageSize, UploadVariant};
+use crate::entities::{ChatMessage, ChatReadMarker, MeetAndGreet, MeetAndGreetStatus};
+use crate::entities::{DeviceToken, Notification, Ticket, TicketMessage, TicketStatus};
+use crate::entities::{DirectUpload, DirectUploadStatus};
+use crate::entities::{Email, NotificationSettings, Translation};
+use crate::entities::{HelpArticle, PasswordResetToken, Session};
+use crate::entities::{IntegrityIssue, IntegrityIssueKind};
+use crate::entities::{Invite, InviteConversion};
+use crate::entities::{LedgerEntry, LedgerEntryKind, Withdrawal, WithdrawalStatus};
+use crate::entities::{LocationAccess, OperationsSnapshot, PaymentAttemptStats, PurgedCounts};
+use crate::entities::{OAuthAccount, OAuthLinkToken, OAuthProviderKind};
+use crate::entities::{OwnerReputationStats, ReviewKind, ReviewState};
+use crate::entities::{Partner, PartnerApiKey, PartnerConsent, PartnerUsage};
+use crate::entities::{PayoutAccount, PayoutAccountStatus};
+use crate::entities::{RefundRequest, RefundStatus};
+use crate::entities::{VerificationStatus, WalkRequest, Walker, WalkerStats, WalkingLocation};
+use crate::error::Error;
+use crate::geo::haversine_distance;
+use crate::repository::PhoneBindingUpsert;
+use crate::repository::ShortLinkCreate;
+use crate::repository::TranslationUpsert;
+use crate::repository::UpdateField;
+use crate::repository::WalkRequestUpdate;
+use crate::repository::{
+    AccountDeletionCreate, AccountMergeCreate, OtpCreate, RefreshTokenCreate,
+    RevokedAccessTokenCreate,
+};
+use crate::repository::{ActionTokenCreate, NotificationCreate, PasswordResetTokenCreate};
+use crate::repository::{AuditLogCreate, AuditLogQuery};
+use crate::repository::{
+    AvailableWalkerQuery, NeighborhoodStatsQuery, PartnerApiKeyCreate, PartnerConsentQuery,
+    PartnerCreate, PartnerUpdate,
+};
+use crate::repository::{BlockQuery, FavoriteQuery, ReportCreate, ReportQuery, ReportUpdate};
+use crate::repository::{
+    BreedCareTipsUpsert, BreedCreate, BreedQuery, DogCreate, DogQuery, DogUpdate, Repository,
+};
+use crate::repository::{CancellationPenaltyCreate, UploadVariantCreate};
+use crate::repository::{
+    ChatMessageCreate, ChatReadMarkerUpsert, MeetAndGreetCreate, MeetAndGreetQuery,
+    MeetAndGreetUpdate,
+};
+use crate::repository::{Cursor, Order, Page, Pagination, SortBy, WalkingLocationCreate};
+use crate::repository::{
+    DeviceTokenQuery, DeviceTokenUpsert, EmailCreate, NotificationSettingsUpsert,
+};
+use crate::repository::{DirectUploadCreate, PaymentAttemptCreate, SessionQuery};
+use crate::repository::{HelpArticleCreate, HelpArticleQuery};
+use crate::repository::{InviteCreate, InviteQuery};
+use crate::repository::{LocationAccessCreate, LocationAccessQuery};
+use crate::repository::{OAuthAccountCreate, OAuthLinkTokenCreate, PayoutAccountUpsert};
+use crate::repository::{OwnerUpdate, QueryPlan, QueryTemplate, ReviewCreate, ReviewQuery};
+use crate::repository::{PoiCreate, PoiQuery};
+use crate::repository::{RefundRequestCreate, RefundRequestQuery, RefundReview};
+use crate::repository::{TicketCreate, TicketMessageCreate, TicketQuery, TicketUpdate};
+use crate::repository::{WalkRequestCreate, WalkRequestQuery};
+use crate::repository::{WalkerQuery, WalkerSearch, WalkerUpdate, WalkerVerificationSubmit};
+use crate::repository::{WithdrawalCreate, WithdrawalQuery, WithdrawalUpdate};
+use crate::repository::{
+    COMPLETED_WALKS_HALF_SCORE, COMPLETED_WALKS_WEIGHT, DISTANCE_WEIGHT, RATING_WEIGHT,
+    USER_REFERENCES,
+};
+use crate::translation::Language;
+
+#[derive(Clone)]
+struct PaymentAttempt {
+    user_id: String,
+    amount: i64,
+    succeeded: bool,
+    created_at: DateTime<Utc>,
+}
+
+// 各集合以id为键保存, 遛狗人和狗狗主人档案以user_id为键
+#[derive(Default, Clone)]
+struct Store {
+    breeds: HashMap<String, Breed>,
+    breed_care_tips: HashMap<(String, String), BreedCareTips>,
+    dogs: HashMap<String, Dog>,
+    deleted_dogs: HashMap<String, DateTime<Utc>>, // Dog实体中没有deleted_at, 软删除时间单独保存
+    walk_requests: HashMap<String, WalkRequest>,
+    walking_locations: Vec<WalkingLocation>,
+    walkers: HashMap<String, Walker>,
+    owners: HashMap<String, Owner>,
+    achievements: HashMap<String, Achievement>,
+    reviews: HashMap<String, Review>,
+    review_votes: Vec<(String, String)>, // 评价id和投票用户
+    blocks: HashMap<String, Block>,
+    favorites: HashMap<String, Favorite>,
+    reports: HashMap<String, Report>,
+    refresh_tokens: HashMap<String, RefreshToken>,
+    account_merges: HashMap<String, AccountMerge>,
+    account_deletions: HashMap<String, AccountDeletion>,
+    otps: HashMap<String, Otp>,
+    password_reset_tokens: HashMap<String, PasswordResetToken>,
+    tickets: HashMap<String, Ticket>,
+    help_articles: HashMap<String, HelpArticle>,
+    oauth_accounts: HashMap<String, OAuthAccount>,
+    oauth_link_tokens: HashMap<String, OAuthLinkToken>,
+    phone_bindings: HashMap<String, PhoneBinding>, // 按用户id保存
+    payout_accounts: HashMap<String, PayoutAccount>,
+    ledger_entries: HashMap<String, LedgerEntry>,
+    withdrawals: HashMap<String, Withdrawal>,
+    payment_attempts: HashMap<String, PaymentAttempt>,
+    upload_variants: Vec<UploadVariant>,
+    cancellation_penalties: HashMap<String, CancellationPenalty>,
+    refund_requests: HashMap<String, RefundRequest>,
+    meet_and_greets: HashMap<String, MeetAndGreet>,
+    chat_messages: HashMap<String, ChatMessage>,
+    chat_read_markers: HashMap<(String, String), ChatReadMarker>,
+    direct_uploads: HashMap<String, DirectUpload>,
+    location_accesses: HashMap<String, LocationAccess>,
+    token_versions: HashMap<String, i64>,
+    revoked_access_tokens: HashMap<String, DateTime<Utc>>,
+    notifications: HashMap<String, Notification>,
+    device_tokens: HashMap<String, DeviceToken>,
+    notification_settings: HashMap<String, NotificationSettings>,
+    emails: HashMap<String, Email>,
+    translations: HashMap<(String, Language), Translation>,
+    action_tokens: HashMap<String, ActionToken>,
+    audit_logs: Vec<AuditLog>,
+    partners: HashMap<String, Partner>,
+    partner_api_keys: HashMap<String, (String, PartnerApiKey)>, // 密钥哈希和密钥
+    partner_consents: HashMap<String, PartnerConsent>,
+    partner_usages: Vec<(String, String, DateTime<Utc>)>, // 合作方id、接口和调用时间
+    invites: HashMap<String, (String, Invite)>,           // 邀请码哈希和邀请
+    pois: HashMap<String, Poi>,
+    short_links: HashMap<String, ShortLink>,
+}
+
+// 基于内存的Repository实现, 用于在没有数据库的环境下测试core::service, 语义与MongoDB实现保持一致
+#[derive(Default, Clone)]
+pub struct InMemory {
+    store: Arc<RwLock<Store>>, // 克隆共享同一份数据
+}
+
+impl InMemory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn read(&self) -> Result<RwLockReadGuard<'_, Store>, Error> {
+        self.store
+            .read()
+            .map_err(|e| Error::new("failed to read in-memory store").with_cause(e.to_string()))
+    }
+
+    fn write(&self) -> Result<RwLockWriteGuard<'_, Store>, Error> {
+        self.store
+            .write()
+            .map_err(|e| Error::new("failed to write in-memory store").with_cause(e.to_string()))
+    }
+}
+
+impl Repository for InMemory {
+    // 执行前保存快照, f返回错误时恢复快照. 不隔离同时进行的其他写入
+    async fn with_transaction<T, F, Fut>(&self, f: F) -> Result<T, Error>
+    where
+        F: FnOnce(Self) -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        let snapshot = self.read()?.clone();
+        let result = f(self.clone()).await;
+        if result.is_err() {
+            *self.write()? = snapshot;
+        }
+        result
+    }
+
+    async fn create_breed(&self, breed: &BreedCreate) -> Result<String, Error> {
+        let mut store = self.write()?;
+        if store
+            .breeds
+            .values()
+            .any(|b| b.name == breed.name && b.category == breed.category)
+        {
+            return Err(Error::duplicate(&["name", "category"]));
+        }
+        let id = new_id();
+        store.breeds.insert(
+            id.clone(),
+            Breed {
+                id: id.parse()?,
+                category: breed.category.clone(),
+                name: breed.name.clone(),
+                updated_at: Some(Utc::now()),
+            },
+        );
+        Ok(id)
+    }
+
+    async fn delete_breed(&self, id: &str) -> Result<bool, Error> {
+        Ok(self.write()?.breeds.remove(id).is_some())
+    }
+
+    async fn query_breeds(&self, query: &BreedQuery) -> Result<(Vec<Breed>, i64), Error> {
+        let breeds = select(&self.read()?.breeds, |b| {
+            query.id.as_ref().map_or(true, |id| b.id.as_str() == id)
+                && query
+                    .category
+                    .as_ref()
+                    .map_or(true, |c| c.to_string() == b.category.to_string())
+        });
+        let mut breeds = breeds;
+        if let Some(sort_by) = &query.sort_by {
+            sort_breeds(&mut breeds, sort_by);
+        }
+        let total = breeds.len() as i64;
+        Ok((breeds, total))
+    }
+
+    async fn query_breed_care_tips(
+        &self,
+        breed_ids: &[String],
+        locale: &str,
+    ) -> Result<Vec<BreedCareTips>, Error> {
+        let store = self.read()?;
+        Ok(breed_ids
+            .iter()
+            .filter_map(|id| {
+                store
+                    .breed_care_tips
+                    .get(&(id.clone(), locale.to_owned()))
+                    .cloned()
+            })
+            .collect())
+    }
+
+    async fn upsert_breed_care_tips(&self, upsert: BreedCareTipsUpsert) -> Result<(), Error> {
+        let mut store = self.write()?;
+        let key = (upsert.breed_id.clone(), upsert.locale.clone());
+        let id = store
+            .breed_care_tips
+            .get(&key)
+            .map(|t| t.id.clone())
+            .unwrap_or_else(new_id);
+        store.breed_care_tips.insert(
+            key,
+            BreedCareTips {
+                id,
+                breed_id: upsert.breed_id,
+                locale: upsert.locale,
+                tips: upsert.tips,
+                updated_by: upsert.updated_by,
+                updated_at: Some(Utc::now()),
+            },
+        );
+        Ok(())
+    }
+
+    async fn delete_breed_care_tips(&self, breed_id: &str, locale: &str) -> Result<bool, Error> {
+        Ok(self
+            .write()?
+            .breed_care_tips
+            .remove(&(breed_id.to_owned(), locale.to_owned()))
+            .is_some())
+    }
+
+    async fn create_dog(&self, dog: &DogCreate) -> Result<Dog, Error> {
+        let mut store = self.write()?;
+        let breed = resolve_breed(&store, &dog.breed)
+            .ok_or(Error::new("failed to create dog").with_cause("breed not exists"))?;
+        let id = new_id();
+        let created = Dog {
+            id: id.parse()?,
+            name: dog.name.clone(),
+            gender: parse_gender(&dog.gender),
+            breed,
+            birthday: dog.birthday,
+            owner_id: dog.owner_id.clone(),
+            tags: dog.tags.clone(),
+            portrait_id: dog.portrait_id.clone(),
+            photos: Vec::new(),
+            medical_flags: dog.medical_flags.clone(),
+            version: 0,
+            updated_at: Some(Utc::now()),
+        };
+        store.dogs.insert(id, created.clone());
+        Ok(created)
+    }
+
+    async fn delete_dog(&self, id: &str) -> Result<bool, Error> {
+        let mut store = self.write()?;
+        if !store.dogs.contains_key(id) || store.deleted_dogs.contains_key(id) {
+            return Ok(false);
+        }
+        store.deleted_dogs.insert(id.to_owned(), Utc::now());
+        Ok(true)
+    }
+
+    async fn update_dog(&self, id: &str, dog: &DogUpdate) -> Result<bool, Error> {
+        let mut store = self.write()?;
+        let breed = match &dog.breed {
+            Some(query) => Some(
+                resolve_breed(&store, query)
+                    .ok_or(Error::new("failed to update dog").with_cause("breed not exists"))?,
+            ),
+            None => None,
+        };
+        let birthday = match &dog.birthday {
+            Some(birthday) => Some(
+                DateTime::parse_from_rfc3339(birthday)
+                    .map_err(|e| Error::new("failed to update dog").with_cause(e))?
+                    .with_timezone(&Utc),
+            ),
+            None => None,
+        };
+        if store.deleted_dogs.contains_key(id) {
+            return Ok(false);
+        }
+        let Some(existing) = store.dogs.get_mut(id) else {
+            return Ok(false);
+        };
+        if let (Some(edited_at), Some(updated_at)) = (dog.edited_at, existing.updated_at) {
+            if updated_at > edited_at {
+                return Ok(false);
+            }
+        }
+        if dog.expected_version.is_some_and(|v| v != existing.version) {
+            return Ok(false);
+        }
+        let mut updated = false;
+        if let Some(name) = &dog.name {
+            existing.name = name.clone();
+            updated = true;
+        }
+        if let Some(gender) = &dog.gender {
+            existing.gender = parse_gender(gender);
+            updated = true;
+        }
+        if let Some(breed) = breed {
+            existing.breed = breed;
+            updated = true;
+        }
+        if let Some(birthday) = birthday {
+            existing.birthday = birthday;
+            updated = true;
+        }
+        if let Some(owner_id) = &dog.owner_id {
+            existing.owner_id = owner_id.clone();
+            updated = true;
+        }
+        if let Some(tags) = &dog.tags {
+            existing.tags = tags.clone();
+            updated = true;
+        }
+        if apply_update_field(&mut existing.portrait_id, &dog.portrait_id) {
+            updated = true;
+        }
+        if let Some(photos) = &dog.photos {
+            existing.photos = photos.clone();
+            updated = true;
+        }
+        if let Some(photo) = &dog.add_to_photos {
+            existing.photos.push(photo.clone());
+            updated = true;
+        }
+        if let Some(photo) = &dog.remove_from_photos {
+            existing.photos.retain(|p| p != photo);
+            updated = true;
+        }
+        if let Some(medical_flags) = &dog.medical_flags {
+            existing.medical_flags = medical_flags.clone();
+            updated = true;
+        }
+        // is_sterilized和introduction不在Dog实体中, 仅有这两项时与MongoDB一样视为已更新
+        updated |= dog.is_sterilized.is_some() || !dog.introduction.is_keep();
+        if updated {
+            existing.updated_at = Some(Utc::now());
+            existing.version += 1;
+        }
+        Ok(updated)
+    }
+
+    async fn query_dogs(&self, query: &DogQuery) -> Result<Vec<Dog>, Error> {
+        let store = self.read()?;
+        let dogs = select(&store.dogs, |d| {
+            query.owner_id.as_ref().map_or(true, |o| &d.owner_id == o)
+                && query
+                    .id_in
+                    .as_ref()
+                    .map_or(true, |ids| ids.iter().any(|id| d.id == *id))
+                && dog_matches(d, query)
+                && dog_visible(&store, d, query)
+        });
+        let mut dogs = dogs;
+        if let Some(sort_by) = &query.sort_by {
+            sort_dogs(&mut dogs, sort_by);
+        }
+        Ok(paginate_page(
+            dogs,
+            query.pagination.as_ref(),
+            |d, after| d.id.as_str() > after.id.to_hex().as_str(),
+        ))
+    }
+
+    async fn exists_dog(&self, query: &DogQuery) -> Result<bool, Error> {
+        let store = self.read()?;
+        Ok(store.dogs.values().any(|d| {
+            query.id.as_ref().map_or(true, |id| &d.id == id)
+                && query.owner_id.as_ref().map_or(true, |o| &d.owner_id == o)
+                && dog_visible(&store, d, query)
+        }))
+    }
+
+    async fn create_walk_request(&self, request: WalkRequestCreate) -> Result<String, Error> {
+        let id = new_id();
+        let now = Utc::now();
+        let mut created = WalkRequest {
+            id: id.parse()?,
+            dogs: request.dogs,
+            should_start_after: request.should_start_after,
+            should_start_before: request.should_start_before,
+            should_end_after: request.should_end_after,
+            should_end_before: request.should_end_before,
+            latitude: request.latitude,
+            longitude: request.longitude,
+            priority_walkers: Some(request.priority_walkers),
+            priority_until: request.priority_until,
+            notify_walker_nearby: request.notify_walker_nearby,
+            route_preference: request.route_preference,
+            partner_id: request.partner_id,
+            created_by: request.created_by,
+            created_at: Some(now),
+            updated_at: Some(now),
+            ..Default::default()
+        };
+        created.status = walk_request_status(&created);
+        self.write()?.walk_requests.insert(id.clone(), created);
+        Ok(id)
+    }
+
+    async fn update_walk_request(
+        &self,
+        id: &str,
+        request: WalkRequestUpdate,
+    ) -> Result<WalkRequest, Error> {
+        let mut store = self.write()?;
+        let existing = store
+            .walk_requests
+            .get_mut(id)
+            .filter(|r| r.deleted_at.is_none())
+            .ok_or(Error::not_found("代遛请求不存在"))?;
+        check_walk_request_version(existing, &request)?;
+        apply_walk_request_update(existing, &request);
+        Ok(existing.clone())
+    }
+
+    async fn update_walk_request_by_query(
+        &self,
+        query: WalkRequestQuery,
+        update: WalkRequestUpdate,
+    ) -> Result<WalkRequest, Error> {
+        let mut store = self.write()?;
+        let id = matching_walk_requests(&store, &query)?
+            .into_iter()
+            .next()
+            .map(|r| r.id.to_string())
+            .ok_or(Error::not_found("代遛请求不存在"))?;
+        let existing = store
+            .walk_requests
+            .get_mut(&id)
+            .ok_or(Error::not_found("代遛请求不存在"))?;
+        check_walk_request_version(existing, &update)?;
+        apply_walk_request_update(existing, &update);
+        Ok(existing.clone())
+    }
+
+    async fn update_walk_requests_by_query(
+        &self,
+        query: WalkRequestQuery,
+        update: WalkRequestUpdate,
+    ) -> Result<u64, Error> {
+        let mut store = self.write()?;
+        let ids = matching_walk_requests(&store, &query)?
+            .into_iter()
+            .filter(|r| update.expected_version.map_or(true, |v| r.version == v))
+            .map(|r| r.id.to_string())
+            .collect::<Vec<String>>();
+        for id in &ids {
+            if let Some(existing) = store.walk_requests.get_mut(id) {
+                apply_walk_request_update(existing, &update);
+            }
+        }
+        Ok(ids.len() as u64)
+    }
+
+    async fn get_walk_request(&self, id: &str) -> Result<WalkRequest, Error> {
+        self.read()?
+            .walk_requests
+            .get(id)
+            .filter(|r| r.deleted_at.is_none())
+            .cloned()
+            .ok_or(Error::not_found("walk request not found"))
+    }
+
+    async fn query_walk_requests(
+        &self,
+        query: WalkRequestQuery,
+        sort_by: Option<SortBy>,
+        pagination: Option<Page>,
+    ) -> Result<(Vec<WalkRequest>, u64), Error> {
+        let mut requests = matching_walk_requests(&*self.read()?, &query)?;
+        if let Some(sort_by) = &sort_by {
+            sort_walk_requests(&mut requests, sort_by);
+        }
+        let total = requests.len() as u64;
+        let requests = paginate_page(requests, pagination.as_ref(), |r, after| {
+            let ordering = match &sort_by {
+                Some(sort_by) => {
+                    let ordering = walk_request_sort_key(r, &sort_by.field)
+                        .cmp(&after.sort_key)
+                        .then(r.id.as_str().cmp(after.id.to_hex().as_str()));
+                    if sort_by.order == Order::Asc {
+                        ordering
+                    } else {
+                        ordering.reverse()
+                    }
+                }
+                None => r.id.as_str().cmp(after.id.to_hex().as_str()),
+            };
+            ordering.is_gt()
+        });
+        Ok((requests, total))
+    }
+
+    async fn create_walking_location(
+        &self,
+        create: WalkingLocationCreate<'_>,
+    ) -> Result<String, Error> {
+        let id = new_id();
+        self.write()?.walking_locations.push(WalkingLocation {
+            id: id.clone(),
+            request_id: create.walk_request_id.to_owned(),
+            longitude: create.longitude,
+            latitude: create.latitude,
+            created_at: Some(Utc::now()),
+        });
+        Ok(id)
+    }
+
+    async fn query_walking_locations(
+        &self,
+        walk_request_id: &str,
+    ) -> Result<Vec<WalkingLocation>, Error> {
+        Ok(self
+            .read()?
+            .walking_locations
+            .iter()
+            .filter(|l| l.request_id == walk_request_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn create_poi(&self, create: PoiCreate) -> Result<String, Error> {
+        let id = new_id();
+        self.write()?.pois.insert(
+            id.clone(),
+            Poi {
+                id: id.clone(),
+                name: create.name,
+                longitude: create.longitude,
+                latitude: create.latitude,
+                radius: create.radius,
+                boundary: create.boundary,
+            },
+        );
+        Ok(id)
+    }
+
+    async fn query_pois(&self, query: PoiQuery) -> Result<Vec<Poi>, Error> {
+        Ok(select(&self.read()?.pois, |p| {
+            match (query.near, query.max_distance) {
+                (Some(near), Some(max_distance)) => {
+                    haversine_distance(near.longitude, near.latitude, p.longitude, p.latitude)
+                        <= max_distance
+                }
+                _ => true,
+            }
+        }))
+    }
+
+    async fn delete_poi(&self, id: &str) -> Result<bool, Error> {
+        Ok(self.write()?.pois.remove(id).is_some())
+    }
+
+    async fn get_walker(&self, user_id: &str) -> Result<Option<Walker>, Error> {
+        Ok(self.read()?.walkers.get(user_id).cloned())
+    }
+
+    async fn submit_walker_verification(
+        &self,
+        submit: WalkerVerificationSubmit<'_>,
+    ) -> Result<Walker, Error> {
+        let mut store = self.write()?;
+        let now = Utc::now();
+        let walker = walker_entry(&mut store, submit.user_id);
+        walker.verification_status = VerificationStatus::Pending;
+        walker.id_document_ids = submit.id_document_ids;
+        walker.submitted_at = Some(now);
+        walker.rejected_reason = None;
+        walker.reviewed_at = None;
+        walker.updated_at = Some(now);
+        Ok(walker.clone())
+    }
+
+    async fn update_walkers_by_query(
+        &self,
+        query: WalkerQuery,
+        update: WalkerUpdate,
+    ) -> Result<u64, Error> {
+        let mut store = self.write()?;
+        let mut updated = 0;
+        for walker in store.walkers.values_mut() {
+            if !walker_matches(walker, &query) {
+                continue;
+            }
+            if let Some(verification_status) = update.verification_status {
+                walker.verification_status = verification_status;
+            }
+            if let Some(rejected_reason) = &update.rejected_reason {
+                walker.rejected_reason = Some(rejected_reason.clone());
+            }
+            if let Some(reviewed_at) = update.reviewed_at {
+                walker.reviewed_at = Some(reviewed_at);
+            }
+            if update.unset_rejected_reason {
+                walker.rejected_reason = None;
+            }
+            walker.updated_at = Some(Utc::now());
+            updated += 1;
+        }
+        Ok(updated)
+    }
+
+    async fn query_walkers(
+        &self,
+        query: WalkerQuery,
+        pagination: Option<Pagination>,
+    ) -> Result<(Vec<Walker>, i64), Error> {
+        let mut walkers = select(&self.read()?.walkers, |w| walker_matches(w, &query));
+        walkers.sort_by_key(|w| w.submitted_at);
+        let total = walkers.len() as i64;
+        Ok((paginate(walkers, pagination.as_ref()), total))
+    }
+
+    // 内存实现没有执行计划, 只返回查询的集合
+    async fn explain(&self, template: QueryTemplate) -> Result<QueryPlan, Error> {
+        let collection = match template {
+            QueryTemplate::NearbyWalkRequests { .. } | QueryTemplate::MyWalkRequests { .. } => {
+                "walk_requests"
+            }
+            QueryTemplate::DogsByOwner { .. } => "dogs",
+            QueryTemplate::WalkerVerifications { .. } => "walkers",
+        };
+        Ok(QueryPlan {
+            collection: collection.to_owned(),
+            ..Default::default()
+        })
+    }
+
+    async fn update_walker_location(
+        &self,
+        user_id: &str,
+        longitude: f64,
+        latitude: f64,
+    ) -> Result<(), Error> {
+        let mut store = self.write()?;
+        let walker = walker_entry(&mut store, user_id);
+        walker.longitude = Some(longitude);
+        walker.latitude = Some(latitude);
+        walker.updated_at = Some(Utc::now());
+        Ok(())
+    }
+
+    async fn search_walkers(&self, search: WalkerSearch) -> Result<Vec<RankedWalker>, Error> {
+        let store = self.read()?;
+        let mut ranked = Vec::new();
+        for walker in store.walkers.values() {
+            let (Some(longitude), Some(latitude)) = (walker.longitude, walker.latitude) else {
+                continue;
+            };
+            if walker.verification_status != VerificationStatus::Verified {
+                continue;
+            }
+            let distance =
+                haversine_distance(search.longitude, search.latitude, longitude, latitude);
+            if distance > search.radius {
+                continue;
+            }
+            let ratings = store
+                .reviews
+                .values()
+                .filter(|r| {
+                    r.walker_id == walker.user_id
+                        && r.kind == ReviewKind::OwnerToWalker
+                        && r.state == ReviewState::Revealed
+                })
+                .map(|r| r.rating as f64)
+                .collect::<Vec<f64>>();
+            let average_rating = if ratings.is_empty() {
+                0.0
+            } else {
+                ratings.iter().sum::<f64>() / ratings.len() as f64
+            };
+            let completed_walks = store
+                .walk_requests
+                .values()
+                .filter(|r| r.accepted_by.as_deref() == Some(&walker.user_id) && is_finished(r))
+                .count() as i64;
+            let score = RATING_WEIGHT * (average_rating / 5.0)
+                + COMPLETED_WALKS_WEIGHT
+                    * (completed_walks as f64
+                        / (completed_walks + COMPLETED_WALKS_HALF_SCORE) as f64)
+                + DISTANCE_WEIGHT * (1.0 - distance / search.radius);
+            ranked.push(RankedWalker {
+                user_id: walker.user_id.clone(),
+                longitude,
+                latitude,
+                distance,
+                average_rating,
+                completed_walks,
+                score,
+                avatar_id: store
+                    .owners
+                    .get(&walker.user_id)
+                    .and_then(|o| o.avatar_id.clone()),
+            });
+        }
+        ranked.sort_by(|a, b| b.score.total_cmp(&a.score));
+        Ok(paginate(ranked, Some(&search.pagination)))
+    }
+
+    async fn walker_stats(&self, walker_id: &str) -> Result<WalkerStats, Error> {
+        let store = self.read()?;
+        let accepted = store
+            .walk_requests
+            .values()
+            .filter(|r| r.accepted_by.as_deref() == Some(walker_id))
+            .collect::<Vec<&WalkRequest>>();
+        let finished = accepted
+            .iter()
+            .filter(|r| is_finished(r))
+            .collect::<Vec<_>>();
+        let canceled = accepted.iter().filter(|r| r.canceled_at.is_some()).count();
+        let ratings = store
+            .reviews
+            .values()
+            .filter(|r| {
+                r.walker_id == walker_id
+                    && r.kind == ReviewKind::OwnerToWalker
+                    && r.state == ReviewState::Revealed
+            })
+            .map(|r| r.rating as f64)
+            .collect::<Vec<f64>>();
+        Ok(WalkerStats {
+            walker_id: walker_id.to_owned(),
+            completed_walks: finished.len() as i64,
+            total_distance: finished
+                .iter()
+                .map(|r| walk_distance(&store.walking_locations, &r.id))
+                .sum(),
+            cancellation_rate: if accepted.is_empty() {
+                0.0
+            } else {
+                canceled as f64 / accepted.len() as f64
+            },
+            average_rating: if ratings.is_empty() {
+                0.0
+            } else {
+                ratings.iter().sum::<f64>() / ratings.len() as f64
+            },
+            review_count: ratings.len() as i64,
+        })
+    }
+
+    async fn owner_reputation_stats(
+        &self,
+        owner_id: &str,
+        since: DateTime<Utc>,
+    ) -> Result<OwnerReputationStats, Error> {
+        let store = self.read()?;
+        let now = Utc::now();
+        let requests = store
+            .walk_requests
+            .values()
+            .filter(|r| {
+                r.created_by == owner_id
+                    && r.created_at.map_or(false, |t| t >= since)
+                    && r.deleted_at.is_none()
+            })
+            .collect::<Vec<&WalkRequest>>();
+        let accepted = requests
+            .iter()
+            .filter(|r| r.accepted_by.is_some())
+            .collect::<Vec<_>>();
+        let ratings = store
+            .reviews
+            .values()
+            .filter(|r| {
+                r.owner_id == owner_id
+                    && r.kind == ReviewKind::WalkerToOwner
+                    && r.state == ReviewState::Revealed
+            })
+            .map(|r| r.rating as f64)
+            .collect::<Vec<f64>>();
+        Ok(OwnerReputationStats {
+            requests: requests.len() as i64,
+            accepted: accepted.len() as i64,
+            canceled_after_acceptance: accepted.iter().filter(|r| r.canceled_at.is_some()).count()
+                as i64,
+            no_shows: accepted
+                .iter()
+                .filter(|r| {
+                    r.canceled_at.is_none()
+                        && r.started_at.is_none()
+                        && r.should_end_before
+                            .or(r.should_start_before)
+                            .map_or(false, |t| t < now)
+                })
+                .count() as i64,
+            average_rating: if ratings.is_empty() {
+                0.0
+            } else {
+                ratings.iter().sum::<f64>() / ratings.len() as f64
+            },
+            review_count: ratings.len() as i64,
+        })
+    }
+
+    async fn achievement_progress(&self) -> Result<Vec<AchievementProgress>, Error> {
+        let store = self.read()?;
+        let mut progress: HashMap<String, AchievementProgress> = HashMap::new();
+        for request in store.walk_requests.values().filter(|r| is_finished(r)) {
+            // 遛狗人: 完成次数和累计距离
+            if let Some(walker_id) = &request.accepted_by {
+                let entry = progress_entry(&mut progress, walker_id);
+                entry.finished_walks += 1;
+                entry.total_distance += walk_distance(&store.walking_locations, &request.id);
+            }
+            // 狗狗主人: 完成次数
+            progress_entry(&mut progress, &request.created_by).finished_walks += 1;
+        }
+        // 遛狗人: 五星评价数
+        for review in store.reviews.values().filter(|r| {
+            r.rating == 5 && r.kind == ReviewKind::OwnerToWalker && r.state == ReviewState::Revealed
+        }) {
+            progress_entry(&mut progress, &review.walker_id).five_star_reviews += 1;
+        }
+        Ok(progress.into_values().collect())
+    }
+
+    async fn grant_achievement(&self, user_id: &str, kind: AchievementKind) -> Result<bool, Error> {
+        let mut store = self.write()?;
+        if store
+            .achievements
+            .values()
+            .any(|a| a.user_id == user_id && a.kind == kind)
+        {
+            return Ok(false);
+        }
+        let id = new_id();
+        store.achievements.insert(
+            id.clone(),
+            Achievement {
+                id,
+                user_id: user_id.to_owned(),
+                kind,
+                achieved_at: Some(Utc::now()),
+            },
+        );
+        Ok(true)
+    }
+
+    async fn query_achievements(&self, user_id: &str) -> Result<Vec<Achievement>, Error> {
+        let mut achievements = select(&self.read()?.achievements, |a| a.user_id == user_id);
+        achievements.sort_by_key(|a| a.achieved_at);
+        Ok(achievements)
+    }
+
+    async fn create_review(&self, create: ReviewCreate<'_>) -> Result<String, Error> {
+        let id = new_id();
+        self.write()?.reviews.insert(
+            id.clone(),
+            Review {
+                id: id.clone(),
+                walk_request_id: create.walk_request_id.to_owned(),
+                walker_id: create.walker_id.to_owned(),
+                owner_id: create.owner_id.to_owned(),
+                kind: create.kind,
+                rating: create.rating,
+                content: create.content,
+                photo_ids: create.photo_ids,
+                helpful_count: 0,
+                state: ReviewState::Hidden,
+                revealed_at: None,
+                created_at: Some(Utc::now()),
+            },
+        );
+        Ok(id)
+    }
+
+    async fn query_reviews(
+        &self,
+        query: ReviewQuery,
+        pagination: Option<Pagination>,
+    ) -> Result<(Vec<Review>, i64), Error> {
+        let store = self.read()?;
+        let mut reviews = select(&store.reviews, |r| {
+            query.id.as_ref().map_or(true, |id| &r.id == id)
+                && query
+                    .walk_request_id
+                    .as_ref()
+                    .map_or(true, |id| &r.walk_request_id == id)
+                && query
+                    .walker_id
+                    .as_ref()
+                    .map_or(true, |id| &r.walker_id == id)
+                && query.owner_id.as_ref().map_or(true, |id| &r.owner_id == id)
+                && query.kind.map_or(true, |kind| r.kind == kind)
+                && query.state.map_or(true, |state| r.state == state)
+        });
+        for review in &mut reviews {
+            review.helpful_count = store
+                .review_votes
+                .iter()
+                .filter(|(id, _)| id == &review.id)
+                .count() as i64;
+        }
+        reviews.reverse();
+        if let Some(sort_by) = &query.sort_by {
+            // 稳定排序, 票数相同的保持创建时间倒序
+            reviews.sort_by(|a, b| {
+                let ordering = match sort_by.field.as_str() {
+                    "rating" => a.rating.cmp(&b.rating),
+                    _ => a.helpful_count.cmp(&b.helpful_count),
+                };
+                if sort_by.order == Order::Asc {
+                    ordering
+                } else {
+                    ordering.reverse()
+                }
+            });
+        }
+        let total = reviews.len() as i64;
+        Ok((paginate(reviews, pagination.as_ref()), total))
+    }
+
+    async fn create_review_vote(&self, review_id: &str, user_id: &str) -> Result<bool, Error> {
+        let mut store = self.write()?;
+        let vote = (review_id.to_owned(), user_id.to_owned());
+        if store.review_votes.contains(&vote) {
+            return Ok(false);
+        }
+        store.review_votes.push(vote);
+        Ok(true)
+    }
+
+    async fn reveal_reviews(&self, walk_request_id: &str) -> Result<u64, Error> {
+        let mut store = self.write()?;
+        Ok(reveal(&mut store, |r| r.walk_request_id == walk_request_id))
+    }
+
+    async fn reveal_expired_reviews(&self, created_before: DateTime<Utc>) -> Result<u64, Error> {
+        let mut store = self.write()?;
+        Ok(reveal(&mut store, |r| {
+            r.created_at.map_or(false, |t| t < created_before)
+        }))
+    }
+
+    async fn count_dogs(&self, query: &DogQuery) -> Result<i64, Error> {
+        let store = self.read()?;
+        Ok(store
+            .dogs
+            .values()
+            .filter(|d| query.owner_id.as_ref().map_or(true, |o| &d.owner_id == o))
+            .filter(|d| dog_visible(&store, d, query))
+            .count() as i64)
+    }
+
+    async fn get_owner(&self, user_id: &str) -> Result<Option<Owner>, Error> {
+        Ok(self.read()?.owners.get(user_id).cloned())
+    }
+
+    async fn upsert_owner(&self, user_id: &str, update: OwnerUpdate) -> Result<Owner, Error> {
+        let mut store = self.write()?;
+        let now = Utc::now();
+        let owner = store
+            .owners
+            .entry(user_id.to_owned())
+            .or_insert_with(|| Owner {
+                id: new_id(),
+                user_id: user_id.to_owned(),
+                created_at: Some(now),
+                ..Default::default()
+            });
+        if let Some(nickname) = update.nickname {
+            owner.nickname = Some(nickname);
+        }
+        if let Some(avatar_id) = update.avatar_id {
+            owner.avatar_id = Some(avatar_id);
+        }
+        if let Some(emergency_contacts) = update.emergency_contacts {
+            owner.emergency_contacts = emergency_contacts;
+        }
+        owner.updated_at = Some(now);
+        Ok(owner.clone())
+    }
+
+    async fn create_block(&self, owner_id: &str, walker_id: &str) -> Result<Block, Error> {
+        let mut store = self.write()?;
+        if let Some(block) = store
+            .blocks
+            .values()
+            .find(|b| b.owner_id == owner_id && b.walker_id == walker_id)
+        {
+            return Ok(block.clone());
+        }
+        let block = Block {
+            id: new_id(),
+            owner_id: owner_id.to_owned(),
+            walker_id: walker_id.to_owned(),
+            created_at: Some(Utc::now()),
+        };
+        store.blocks.insert(block.id.clone(), block.clone());
+        Ok(block)
+    }
+
+    async fn delete_block(&self, owner_id: &str, walker_id: &str) -> Result<bool, Error> {
+        let mut store = self.write()?;
+        let before = store.blocks.len();
+        store
+            .blocks
+            .retain(|_, b| !(b.owner_id == owner_id && b.walker_id == walker_id));
+        Ok(store.blocks.len() < before)
+    }
+
+    async fn query_blocks(&self, query: BlockQuery) -> Result<Vec<Block>, Error> {
+        let mut blocks = select(&self.read()?.blocks, |b| {
+            query.owner_id.as_ref().map_or(true, |id| &b.owner_id == id)
+                && query
+                    .walker_id
+                    .as_ref()
+                    .map_or(true, |id| &b.walker_id == id)
+        });
+        blocks.reverse();
+        Ok(blocks)
+    }
+
+    async fn create_favorite(&self, owner_id: &str, walker_id: &str) -> Result<Favorite, Error> {
+        let mut store = self.write()?;
+        if let Some(favorite) = store
+            .favorites
+            .values()
+            .find(|f| f.owner_id == owner_id && f.walker_id == walker_id)
+        {
+            return Ok(favorite.clone());
+        }
+        let favorite = Favorite {
+            id: new_id(),
+            owner_id: owner_id.to_owned(),
+            walker_id: walker_id.to_owned(),
+            created_at: Some(Utc::now()),
+        };
+        store
+            .favorites
+            .insert(favorite.id.clone(), favorite.clone());
+        Ok(favorite)
+    }
+
+    async fn delete_favorite(&self, owner_id: &str, walker_id: &str) -> Result<bool, Error> {
+        let mut store = self.write()?;
+        let before = store.favorites.len();
+        store
+            .favorites
+            .retain(|_, f| !(f.owner_id == owner_id && f.walker_id == walker_id));
+        Ok(store.favorites.len() < before)
+    }
+
+    async fn query_favorites(&self, query: FavoriteQuery) -> Result<Vec<Favorite>, Error> {
+        let mut favorites = select(&self.read()?.favorites, |f| {
+            query.owner_id.as_ref().map_or(true, |id| &f.owner_id == id)
+                && query
+                    .walker_id
+                    .as_ref()
+                    .map_or(true, |id| &f.walker_id == id)
+        });
+        favorites.reverse();
+        Ok(favorites)
+    }
+
+    async fn create_report(&self, create: ReportCreate) -> Result<String, Error> {
+        let id = new_id();
+        self.write()?.reports.insert(
+            id.clone(),
+            Report {
+                id: id.clone(),
+                reporter_id: create.reporter_id,
+                target_user_id: create.target_user_id,
+                walk_request_id: create.walk_request_id,
+                reason: create.reason,
+                description: create.description,
+                status: ReportStatus::Pending,
+                resolution_note: None,
+                reviewed_at: None,
+                created_at: Some(Utc::now()),
+            },
+        );
+        Ok(id)
+    }
+
+    async fn query_reports(
+        &self,
+        query: ReportQuery,
+        pagination: Option<Pagination>,
+    ) -> Result<(Vec<Report>, i64), Error> {
+        let reports = select(&self.read()?.reports, |r| report_matches(r, &query));
+        let total = reports.len() as i64;
+        Ok((paginate(reports, pagination.as_ref()), total))
+    }
+
+    async fn update_reports_by_query(
+        &self,
+        query: ReportQuery,
+        update: ReportUpdate,
+    ) -> Result<u64, Error> {
+        let mut store = self.write()?;
+        let mut updated = 0;
+        for report in store.reports.values_mut() {
+            if !report_matches(report, &query) {
+                continue;
+            }
+            if let Some(status) = update.status {
+                report.status = status;
+            }
+            if let Some(resolution_note) = &update.resolution_note {
+                report.resolution_note = Some(resolution_note.clone());
+            }
+            if let Some(reviewed_at) = update.reviewed_at {
+                report.reviewed_at = Some(reviewed_at);
+            }
+            updated += 1;
+        }
+        Ok(updated)
+    }
+
+    async fn create_refresh_token(&self, create: RefreshTokenCreate) -> Result<String, Error> {
+        let id = new_id();
+        self.write()?.refresh_tokens.insert(
+            id.clone(),
+            RefreshToken {
+                id: id.clone(),
+                family_id: create.family_id,
+                user_id: create.user_id,
+                phone: create.phone,
+                token_hash: create.token_hash,
+                used: false,
+                revoked: false,
+                device_id: create.device.device_id,
+                platform: create.device.platform,
+                signed_in_at: Some(create.signed_in_at),
+                expires_at: create.expires_at,
+                created_at: Some(Utc::now()),
+            },
+        );
+        Ok(id)
+    }
+
+    // 与MongoDB实现一样返回标记前的令牌
+    async fn consume_refresh_token(&self, token_hash: &str) -> Result<Option<RefreshToken>, Error> {
+        let mut store = self.write()?;
+        Ok(store
+            .refresh_tokens
+            .values_mut()
+            .find(|t| t.token_hash == token_hash && !t.used)
+            .map(|t| {
+                let consumed = t.clone();
+                t.used = true;
+                consumed
+            }))
+    }
+
+    async fn get_refresh_token(&self, token_hash: &str) -> Result<Option<RefreshToken>, Error> {
+        Ok(self
+            .read()?
+            .refresh_tokens
+            .values()
+            .find(|t| t.token_hash == token_hash)
+            .cloned())
+    }
+
+    async fn revoke_refresh_token_family(&self, family_id: &str) -> Result<u64, Error> {
+        Ok(revoke_refresh_tokens(&mut *self.write()?, |t| {
+            t.family_id == family_id
+        }))
+    }
+
+    async fn count_user_references(&self, user_id: &str) -> Result<Vec<MergedReference>, Error> {
+        let mut store = self.write()?;
+        Ok(USER_REFERENCES
+            .iter()
+            .map(|(collection, field, _)| MergedReference {
+                collection: collection.to_string(),
+                field: field.to_string(),
+                count: replace_user_reference(&mut store, collection, field, user_id, None),
+            })
+            .collect())
+    }
+
+    async fn merge_accounts(&self, merge: AccountMergeCreate) -> Result<AccountMerge, Error> {
+        let mut store = self.write()?;
+        let references = USER_REFERENCES
+            .iter()
+            .map(|(collection, field, _)| MergedReference {
+                collection: collection.to_string(),
+                field: field.to_string(),
+                count: replace_user_reference(
+                    &mut store,
+                    collection,
+                    field,
+                    &merge.from_user_id,
+                    Some(&merge.to_user_id),
+                ),
+            })
+            .collect();
+        revoke_refresh_tokens(&mut store, |t| t.user_id == merge.from_user_id);
+        let merged = AccountMerge {
+            id: new_id(),
+            from_user_id: merge.from_user_id,
+            to_user_id: merge.to_user_id,
+            operator_id: merge.operator_id,
+            references,
+            created_at: Some(Utc::now()),
+        };
+        store
+            .account_merges
+            .insert(merged.id.clone(), merged.clone());
+        Ok(merged)
+    }
+
+    // 上传文件由upload-service保存, 内存实现中没有待删除的上传文件
+    async fn delete_account(
+        &self,
+        deletion: AccountDeletionCreate,
+    ) -> Result<AccountDeletion, Error> {
+        let mut store = self.write()?;
+        let store = &mut *store;
+        let uid = deletion.user_id.as_str();
+        let now = Utc::now();
+        let dogs = match &deletion.reassign_dogs_to {
+            Some(to_user_id) => {
+                let mut reassigned = 0;
+                for dog in store.dogs.values_mut().filter(|d| d.owner_id == uid) {
+                    dog.owner_id = to_user_id.clone();
+                    dog.updated_at = Some(now);
+                    dog.version += 1;
+                    reassigned += 1;
+                }
+                reassigned
+            }
+            None => {
+                let before = store.dogs.len();
+                store.dogs.retain(|_, d| d.owner_id != uid);
+                (before - store.dogs.len()) as u64
+            }
+        };
+        let mut canceled_walk_requests = 0;
+        let mut removed_acceptances = 0;
+        for request in store.walk_requests.values_mut() {
+            let mut updated = false;
+            if request.created_by == uid
+                && request.canceled_at.is_none()
+                && request.finished_at.is_none()
+            {
+                request.canceled_at = Some(now);
+                canceled_walk_requests += 1;
+                updated = true;
+            }
+            if request.accepted_by.as_deref() == Some(uid)
+                && request.started_at.is_none()
+                && request.finished_at.is_none()
+                && request.canceled_at.is_none()
+            {
+                request.accepted_by = None;
+                request.accepted_at = None;
+                removed_acceptances += 1;
+                updated = true;
+            }
+            let listed = |users: &Option<Vec<String>>| {
+                users
+                    .as_ref()
+                    .map_or(false, |u| u.iter().any(|id| id == uid))
+            };
+            if listed(&request.acceptances) || listed(&request.priority_walkers) {
+                for users in [&mut request.acceptances, &mut request.priority_walkers]
+                    .into_iter()
+                    .flatten()
+                {
+                    users.retain(|id| id != uid);
+                }
+                removed_acceptances += 1;
+                updated = true;
+            }
+            if updated {
+                request.updated_at = Some(now);
+                request.version += 1;
+                request.status = walk_request_status(request);
+            }
+        }
+        store
+            .favorites
+            .retain(|_, f| f.owner_id != uid && f.walker_id != uid);
+        store
+            .blocks
+            .retain(|_, b| b.owner_id != uid && b.walker_id != uid);
+        store.partner_consents.retain(|_, c| c.owner_id != uid);
+        store.invites.retain(|_, (_, i)| i.inviter_id != uid);
+        store.short_links.retain(|_, l| l.created_by != uid);
+        store.payout_accounts.remove(uid);
+        store.notifications.retain(|_, n| n.user_id != uid);
+        store.device_tokens.retain(|_, t| t.user_id != uid);
+        store.notification_settings.remove(uid);
+        store.emails.retain(|_, e| e.user_id != uid);
+        store
+            .meet_and_greets
+            .retain(|_, m| m.owner_id != uid && m.walker_id != uid);
+        store.chat_messages.retain(|_, m| m.sender_id != uid);
+        store
+            .chat_read_markers
+            .retain(|(_, user_id), _| user_id != uid);
+        if let Some(owner) = store.owners.get_mut(uid) {
+            owner.nickname = None;
+            owner.avatar_id = None;
+            owner.updated_at = Some(now);
+        }
+        if let Some(walker) = store.walkers.get_mut(uid) {
+            walker.id_document_ids = Vec::new();
+            walker.longitude = None;
+            walker.latitude = None;
+            walker.updated_at = Some(now);
+        }
+        revoke_refresh_tokens(store, |t| t.user_id == uid);
+        let deleted = AccountDeletion {
+            id: new_id(),
+            user_id: deletion.user_id,
+            dogs_reassigned_to: deletion.reassign_dogs_to,
+            dogs,
+            canceled_walk_requests,
+            removed_acceptances,
+            scheduled_uploads: 0,
+            created_at: Some(now),
+        };
+        store
+            .account_deletions
+            .insert(deleted.id.clone(), deleted.clone());
+        Ok(deleted)
+    }
+
+    async fn create_otp(&self, create: OtpCreate) -> Result<String, Error> {
+        let id = new_id();
+        self.write()?.otps.insert(
+            id.clone(),
+            Otp {
+                id: id.clone(),
+                phone: create.phone,
+                purpose: create.purpose,
+                code_hash: create.code_hash,
+                used: false,
+                failed_attempts: 0,
+                expires_at: create.expires_at,
+                created_at: Some(Utc::now()),
+            },
+        );
+        Ok(id)
+    }
+
+    async fn count_otps(&self, phone: &str, since: DateTime<Utc>) -> Result<i64, Error> {
+        Ok(self
+            .read()?
+            .otps
+            .values()
+            .filter(|o| o.phone == phone && o.created_at.map_or(false, |t| t >= since))
+            .count() as i64)
+    }
+
+    async fn consume_otp(
+        &self,
+        phone: &str,
+        purpose: OtpPurpose,
+        code_hash: &str,
+        max_attempts: i64,
+    ) -> Result<Option<Otp>, Error> {
+        let now = Utc::now();
+        let mut store = self.write()?;
+        let mut active = store
+            .otps
+            .values_mut()
+            .filter(|o| o.phone == phone && o.purpose == purpose && !o.used && o.expires_at > now)
+            .collect::<Vec<&mut Otp>>();
+        if let Some(otp) = active
+            .iter_mut()
+            .find(|o| o.code_hash == code_hash && o.failed_attempts < max_attempts)
+        {
+            otp.used = true;
+            return Ok(Some(otp.clone()));
+        }
+        for otp in active {
+            otp.failed_attempts += 1;
+            otp.used = otp.failed_attempts >= max_attempts;
+        }
+        Ok(None)
+    }
+
+    async fn create_password_reset_token(
+        &self,
+        create: PasswordResetTokenCreate,
+    ) -> Result<String, Error> {
+        let id = new_id();
+        self.write()?.password_reset_tokens.insert(
+            id.clone(),
+            PasswordResetToken {
+                id: id.clone(),
+                phone: create.phone,
+                token_hash: create.token_hash,
+                used: false,
+                expires_at: create.expires_at,
+                created_at: Some(Utc::now()),
+            },
+        );
+        Ok(id)
+    }
+
+    async fn consume_password_reset_token(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<PasswordResetToken>, Error> {
+        let now = Utc::now();
+        Ok(self
+            .write()?
+            .password_reset_tokens
+            .values_mut()
+            .find(|t| t.token_hash == token_hash && !t.used && t.expires_at > now)
+            .map(|t| {
+                let consumed = t.clone();
+                t.used = true;
+                consumed
+            }))
+    }
+
+    // 创建工单时的描述作为第一条消息保存
+    async fn create_ticket(&self, create: TicketCreate) -> Result<String, Error> {
+        let id = new_id();
+        let now = Utc::now();
+        self.write()?.tickets.insert(
+            id.clone(),
+            Ticket {
+                id: id.clone(),
+                user_id: create.user_id.clone(),
+                category: create.category,
+                subject: create.subject,
+                status: TicketStatus::Open,
+                assignee_id: None,
+                messages: vec![TicketMessage {
+                    author_id: create.user_id,
+                    from_staff: false,
+                    content: create.content,
+                    attachment_ids: create.attachment_ids,
+                    created_at: Some(now),
+                }],
+                created_at: Some(now),
+                updated_at: Some(now),
+            },
+        );
+        Ok(id)
+    }
+
+    async fn query_tickets(
+        &self,
+        query: TicketQuery,
+        pagination: Option<Pagination>,
+    ) -> Result<(Vec<Ticket>, i64), Error> {
+        let mut tickets = select(&self.read()?.tickets, |t| ticket_matches(t, &query));
+        tickets.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        let total = tickets.len() as i64;
+        Ok((paginate(tickets, pagination.as_ref()), total))
+    }
+
+    async fn update_tickets_by_query(
+        &self,
+        query: TicketQuery,
+        update: TicketUpdate,
+    ) -> Result<u64, Error> {
+        let mut store = self.write()?;
+        let mut updated = 0;
+        for ticket in store.tickets.values_mut() {
+            if !ticket_matches(ticket, &query) {
+                continue;
+            }
+            if let Some(status) = update.status {
+                ticket.status = status;
+            }
+            if let Some(assignee_id) = &update.assignee_id {
+                ticket.assignee_id = Some(assignee_id.clone());
+            }
+            ticket.updated_at = Some(Utc::now());
+            updated += 1;
+        }
+        Ok(updated)
+    }
+
+    async fn add_ticket_message(
+        &self,
+        query: TicketQuery,
+        message: TicketMessageCreate,
+    ) -> Result<u64, Error> {
+        let mut store = self.write()?;
+        let now = Utc::now();
+        let mut updated = 0;
+        for ticket in store.tickets.values_mut() {
+            if !ticket_matches(ticket, &query)
+                || (query.status.is_none() && ticket.status == TicketStatus::Closed)
+            {
+                continue;
+            }
+            ticket.messages.push(TicketMessage {
+                author_id: message.author_id.clone(),
+                from_staff: message.from_staff,
+                content: message.content.clone(),
+                attachment_ids: message.attachment_ids.clone(),
+                created_at: Some(now),
+            });
+            ticket.updated_at = Some(now);
+            updated += 1;
+        }
+        Ok(updated)
+    }
+
+    // 版本号取当前最新版本加一
+    async fn create_help_article(&self, create: HelpArticleCreate) -> Result<HelpArticle, Error> {
+        let mut store = self.write()?;
+        let version = store
+            .help_articles
+            .values()
+            .filter(|a| a.slug == create.slug && a.locale == create.locale)
+            .map(|a| a.version)
+            .max()
+            .unwrap_or(0)
+            + 1;
+        let article = HelpArticle {
+            id: new_id(),
+            slug: create.slug,
+            locale: create.locale,
+            version,
+            title: create.title,
+            markdown: create.markdown,
+            author_id: create.author_id,
+            created_at: Some(Utc::now()),
+        };
+        store
+            .help_articles
+            .insert(article.id.clone(), article.clone());
+        Ok(article)
+    }
+
+    async fn query_help_articles(
+        &self,
+        query: HelpArticleQuery,
+        limit: Option<i64>,
+    ) -> Result<Vec<HelpArticle>, Error> {
+        let mut articles = select(&self.read()?.help_articles, |a| {
+            query.slug.as_ref().map_or(true, |s| &a.slug == s)
+                && query.locale.as_ref().map_or(true, |l| &a.locale == l)
+                && query.version.map_or(true, |v| a.version == v)
+        });
+        articles.sort_by(|a, b| b.version.cmp(&a.version));
+        Ok(paginate(
+            articles,
+            limit.map(|limit| Pagination { limit, skip: 0 }).as_ref(),
+        ))
+    }
+
+    async fn latest_help_articles(&self, locale: &str) -> Result<Vec<HelpArticle>, Error> {
+        let mut latest: HashMap<String, HelpArticle> = HashMap::new();
+        for article in self
+            .read()?
+            .help_articles
+            .values()
+            .filter(|a| a.locale == locale)
+        {
+            if latest
+                .get(&article.slug)
+                .map_or(true, |a| a.version < article.version)
+            {
+                latest.insert(article.slug.clone(), article.clone());
+            }
+        }
+        let mut articles = latest.into_values().collect::<Vec<HelpArticle>>();
+        articles.sort_by(|a, b| a.slug.cmp(&b.slug));
+        Ok(articles)
+    }
+
+    async fn get_oauth_account(
+        &self,
+        provider: OAuthProviderKind,
+        subject: &str,
+    ) -> Result<Option<OAuthAccount>, Error> {
+        Ok(self
+            .read()?
+            .oauth_accounts
+            .values()
+            .find(|a| a.provider == provider && a.subject == subject)
+            .cloned())
+    }
+
+    // 同一第三方账号重复绑定时保留最早的绑定关系
+    async fn create_oauth_account(&self, create: OAuthAccountCreate) -> Result<String, Error> {
+        let mut store = self.write()?;
+        if let Some(account) = store
+            .oauth_accounts
+            .values()
+            .find(|a| a.provider == create.provider && a.subject == create.subject)
+        {
+            if account.user_id != create.user_id {
+                return Err(Error::msg("该第三方账号已绑定其他手机号"));
+            }
+            return Ok(account.id.clone());
+        }
+        let id = new_id();
+        store.oauth_accounts.insert(
+            id.clone(),
+            OAuthAccount {
+                id: id.clone(),
+                provider: create.provider,
+                subject: create.subject,
+                user_id: create.user_id,
+                phone: create.phone,
+                created_at: Some(Utc::now()),
+            },
+        );
+        Ok(id)
+    }
+
+    async fn create_oauth_link_token(&self, create: OAuthLinkTokenCreate) -> Result<String, Error> {
+        let id = new_id();
+        self.write()?.oauth_link_tokens.insert(
+            id.clone(),
+            OAuthLinkToken {
+                id: id.clone(),
+                provider: create.provider,
+                subject: create.subject,
+                token_hash: create.token_hash,
+                used: false,
+                expires_at: create.expires_at,
+                created_at: Some(Utc::now()),
+            },
+        );
+        Ok(id)
+    }
+
+    async fn consume_oauth_link_token(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<OAuthLinkToken>, Error> {
+        let now = Utc::now();
+        Ok(self
+            .write()?
+            .oauth_link_tokens
+            .values_mut()
+            .find(|t| t.token_hash == token_hash && !t.used && t.expires_at > now)
+            .map(|t| {
+                let consumed = t.clone();
+                t.used = true;
+                consumed
+            }))
+    }
+
+    async fn get_phone_binding(&self, phone: &str) -> Result<Option<PhoneBinding>, Error> {
+        Ok(self
+            .read()?
+            .phone_bindings
+            .values()
+            .find(|b| b.phone == phone || b.auth_phone == phone)
+            .cloned())
+    }
+
+    async fn bind_phone(&self, upsert: PhoneBindingUpsert) -> Result<(), Error> {
+        let mut store = self.write()?;
+        if store
+            .phone_bindings
+            .values()
+            .any(|b| b.phone == upsert.phone && b.user_id != upsert.user_id)
+        {
+            return Err(Error::duplicate(&["phone"]));
+        }
+        let now = Utc::now();
+        let binding = store
+            .phone_bindings
+            .entry(upsert.user_id.clone())
+            .or_insert_with(|| PhoneBinding {
+                id: new_id(),
+                user_id: upsert.user_id.clone(),
+                phone: upsert.phone.clone(),
+                auth_phone: upsert.auth_phone,
+                created_at: Some(now),
+                updated_at: None,
+            });
+        binding.phone = upsert.phone.clone();
+        binding.updated_at = Some(now);
+        for account in store.oauth_accounts.values_mut() {
+            if account.user_id == upsert.user_id {
+                account.phone = upsert.phone.clone();
+            }
+        }
+        Ok(())
+    }
+
+    // 每个遛狗人只保留一个收款账户, 重新绑定时覆盖
+    async fn upsert_payout_account(
+        &self,
+        upsert: PayoutAccountUpsert,
+    ) -> Result<PayoutAccount, Error> {
+        let mut store = self.write()?;
+        let now = Utc::now();
+        let account = store
+            .payout_accounts
+            .entry(upsert.user_id.clone())
+            .or_insert_with(|| PayoutAccount {
+                id: new_id(),
+                user_id: upsert.user_id.clone(),
+                method: upsert.method,
+                masked_holder_name: String::new(),
+                masked_account_number: String::new(),
+                bank_name: None,
+                provider_reference: String::new(),
+                status: upsert.status,
+                failure_reason: None,
+                verified_at: None,
+                created_at: Some(now),
+                updated_at: None,
+            });
+        account.method = upsert.method;
+        account.masked_holder_name = upsert.masked_holder_name;
+        account.masked_account_number = upsert.masked_account_number;
+        account.bank_name = upsert.bank_name;
+        account.provider_reference = upsert.provider_reference;
+        set_payout_status(account, upsert.status, upsert.failure_reason);
+        Ok(account.clone())
+    }
+
+    async fn get_payout_account(&self, user_id: &str) -> Result<Option<PayoutAccount>, Error> {
+        Ok(self.read()?.payout_accounts.get(user_id).cloned())
+    }
+
+    async fn update_payout_account_status(
+        &self,
+        user_id: &str,
+        provider_reference: &str,
+        status: PayoutAccountStatus,
+        failure_reason: Option<String>,
+    ) -> Result<Option<PayoutAccount>, Error> {
+        let mut store = self.write()?;
+        Ok(store
+            .payout_accounts
+            .get_mut(user_id)
+            .filter(|a| a.provider_reference == provider_reference)
+            .map(|account| {
+                set_payout_status(account, status, failure_reason);
+                account.clone()
+            }))
+    }
+
+    async fn delete_payout_account(&self, user_id: &str) -> Result<bool, Error> {
+        Ok(self.write()?.payout_accounts.remove(user_id).is_some())
+    }
+
+    // 每个family中未使用且未过期的令牌即当前有效的会话
+    async fn query_sessions(&self, user_id: &str) -> Result<Vec<Session>, Error> {
+        let now = Utc::now();
+        let mut latest: HashMap<String, RefreshToken> = HashMap::new();
+        for token in select(&self.read()?.refresh_tokens, |t| {
+            t.user_id == user_id && !t.used && !t.revoked && t.expires_at > now
+        }) {
+            latest.insert(token.family_id.clone(), token);
+        }
+        let mut tokens = latest.into_values().collect::<Vec<RefreshToken>>();
+        tokens.sort_by(|a, b| b.id.cmp(&a.id));
+        Ok(tokens
+            .into_iter()
+            .map(|t| Session {
+                id: t.family_id,
+                device_id: t.device_id,
+                platform: t.platform,
+                signed_in_at: t.signed_in_at.or(t.created_at),
+                last_seen_at: t.created_at,
+            })
+            .collect())
+    }
+
+    async fn revoke_sessions(&self, query: SessionQuery) -> Result<u64, Error> {
+        Ok(revoke_refresh_tokens(&mut *self.write()?, |t| {
+            t.user_id == query.user_id
+                && query.id.as_ref().map_or(true, |id| &t.family_id == id)
+                && query
+                    .device_id
+                    .as_ref()
+                    .map_or(true, |id| t.device_id.as_ref() == Some(id))
+        }))
+    }
+
+    async fn ledger_balance(&self, user_id: &str) -> Result<i64, Error> {
+        Ok(balance(&*self.read()?, user_id))
+    }
+
+    async fn query_ledger_entries(
+        &self,
+        user_id: &str,
+        pagination: Option<Pagination>,
+    ) -> Result<(Vec<LedgerEntry>, i64), Error> {
+        let mut entries = select(&self.read()?.ledger_entries, |e| e.user_id == user_id);
+        entries.reverse();
+        let total = entries.len() as i64;
+        Ok((paginate(entries, pagination.as_ref()), total))
+    }
+
+    async fn credit_walk_earning(
+        &self,
+        user_id: &str,
+        walk_request_id: &str,
+        amount: i64,
+    ) -> Result<(), Error> {
+        let mut store = self.write()?;
+        if !store.ledger_entries.values().any(|e| {
+            e.kind == LedgerEntryKind::Earning
+                && e.walk_request_id.as_deref() == Some(walk_request_id)
+        }) {
+            insert_ledger_entry(
+                &mut store,
+                user_id,
+                LedgerEntryKind::Earning,
+                amount,
+                None,
+                Some(walk_request_id.to_owned()),
+            );
+        }
+        Ok(())
+    }
+
+    // 持有写锁期间检查余额并扣款, 同一用户并发提现不会超额
+    async fn create_withdrawal(&self, create: WithdrawalCreate) -> Result<Option<String>, Error> {
+        let mut store = self.write()?;
+        if balance(&store, &create.user_id) < create.amount {
+            return Ok(None);
+        }
+        let now = Utc::now();
+        let id = new_id();
+        store.withdrawals.insert(
+            id.clone(),
+            Withdrawal {
+                id: id.clone(),
+                user_id: create.user_id.clone(),
+                amount: create.amount,
+                status: WithdrawalStatus::Requested,
+                destination_reference: create.destination_reference,
+                payout_reference: None,
+                failure_reason: None,
+                reviewed_by: None,
+                reviewed_at: None,
+                paid_at: None,
+                created_at: Some(now),
+                updated_at: Some(now),
+            },
+        );
+        insert_ledger_entry(
+            &mut store,
+            &create.user_id,
+            LedgerEntryKind::Withdrawal,
+            -create.amount,
+            Some(id.clone()),
+            None,
+        );
+        Ok(Some(id))
+    }
+
+    async fn query_withdrawals(
+        &self,
+        query: WithdrawalQuery,
+        pagination: Option<Pagination>,
+    ) -> Result<(Vec<Withdrawal>, i64), Error> {
+        let mut withdrawals = select(&self.read()?.withdrawals, |w| withdrawal_matches(w, &query));
+        withdrawals.reverse();
+        let total = withdrawals.len() as i64;
+        Ok((paginate(withdrawals, pagination.as_ref()), total))
+    }
+
+    // 提现被拒绝或打款失败时写入退回流水
+    async fn update_withdrawals_by_query(
+        &self,
+        query: WithdrawalQuery,
+        update: WithdrawalUpdate,
+    ) -> Result<u64, Error> {
+        let mut store = self.write()?;
+        let now = Utc::now();
+        let mut refunds = Vec::new();
+        for withdrawal in store.withdrawals.values_mut() {
+            if !withdrawal_matches(withdrawal, &query) {
+                continue;
+            }
+            if let Some(status) = update.status {
+                withdrawal.status = status;
+                if status == WithdrawalStatus::Paid {
+                    withdrawal.paid_at = Some(now);
+                }
+                if status.is_refunded() {
+                    refunds.push((
+                        withdrawal.user_id.clone(),
+                        withdrawal.amount,
+                        withdrawal.id.clone(),
+                    ));
+                }
+            }
+            if let Some(payout_reference) = &update.payout_reference {
+                withdrawal.payout_reference = Some(payout_reference.clone());
+            }
+            if let Some(failure_reason) = &update.failure_reason {
+                withdrawal.failure_reason = Some(failure_reason.clone());
+            }
+            if let Some(reviewed_by) = &update.reviewed_by {
+                withdrawal.reviewed_by = Some(reviewed_by.clone());
+                withdrawal.reviewed_at = Some(now);
+            }
+            withdrawal.updated_at = Some(now);
+        }
+        let updated = store
+            .withdrawals
+            .values()
+            .filter(|w| w.updated_at == Some(now))
+            .count() as u64;
+        for (user_id, amount, withdrawal_id) in refunds {
+            insert_ledger_entry(
+                &mut store,
+                &user_id,
+                LedgerEntryKind::WithdrawalReversal,
+                amount,
+                Some(withdrawal_id),
+                None,
+            );
+        }
+        Ok(updated)
+    }
+
+    async fn claim_approved_withdrawal(&self) -> Result<Option<Withdrawal>, Error> {
+        let mut store = self.write()?;
+        Ok(store
+            .withdrawals
+            .values_mut()
+            .filter(|w| w.status == WithdrawalStatus::Approved)
+            .min_by(|a, b| a.reviewed_at.cmp(&b.reviewed_at).then(a.id.cmp(&b.id)))
+            .map(|withdrawal| {
+                withdrawal.status = WithdrawalStatus::Processing;
+                withdrawal.updated_at = Some(Utc::now());
+                withdrawal.clone()
+            }))
+    }
+
+    async fn create_payment_attempt(&self, create: PaymentAttemptCreate) -> Result<String, Error> {
+        let id = new_id();
+        self.write()?.payment_attempts.insert(
+            id.clone(),
+            PaymentAttempt {
+                user_id: create.user_id,
+                amount: create.amount,
+                succeeded: create.succeeded,
+                created_at: Utc::now(),
+            },
+        );
+        Ok(id)
+    }
+
+    async fn payment_attempt_stats(
+        &self,
+        user_id: &str,
+        since: DateTime<Utc>,
+    ) -> Result<PaymentAttemptStats, Error> {
+        let mut stats = PaymentAttemptStats::default();
+        for attempt in self
+            .read()?
+            .payment_attempts
+            .values()
+            .filter(|a| a.user_id == user_id && a.created_at >= since)
+        {
+            if attempt.succeeded {
+                stats.succeeded_amount += attempt.amount;
+            } else {
+                stats.failed += 1;
+            }
+        }
+        Ok(stats)
+    }
+
+    // 同一图片同一规格只保留一个缩略图
+    async fn create_upload_variant(&self, create: UploadVariantCreate) -> Result<(), Error> {
+        let mut store = self.write()?;
+        store
+            .upload_variants
+            .retain(|v| !(v.upload_id == create.upload_id && v.size == create.size));
+        store.upload_variants.push(UploadVariant {
+            upload_id: create.upload_id,
+            size: create.size,
+            variant_id: create.variant_id,
+        });
+        Ok(())
+    }
+
+    async fn get_upload_variant(
+        &self,
+        upload_id: &str,
+        size: ImageSize,
+    ) -> Result<Option<UploadVariant>, Error> {
+        Ok(self
+            .read()?
+            .upload_variants
+            .iter()
+            .find(|v| v.upload_id == upload_id && v.size == size)
+            .cloned())
+    }
+
+    async fn is_verification_document(&self, upload_id: &str) -> Result<bool, Error> {
+        let store = self.read()?;
+        let original = store
+            .upload_variants
+            .iter()
+            .find(|v| v.variant_id == upload_id)
+            .map_or(upload_id, |v| v.upload_id.as_str());
+        Ok(store
+            .walkers
+            .values()
+            .any(|w| w.id_document_ids.iter().any(|id| id == original)))
+    }
+
+    async fn create_cancellation_penalty(
+        &self,
+        create: CancellationPenaltyCreate,
+    ) -> Result<CancellationPenalty, Error> {
+        let mut store = self.write()?;
+        insert_ledger_entry(
+            &mut store,
+            &create.walker_id,
+            LedgerEntryKind::CancellationPenalty,
+            -create.amount,
+            None,
+            Some(create.walk_request_id.clone()),
+        );
+        let penalty = CancellationPenalty {
+            id: new_id(),
+            balance_after: balance(&store, &create.walker_id),
+            walk_request_id: create.walk_request_id,
+            walker_id: create.walker_id,
+            owner_id: create.owner_id,
+            tier: create.tier,
+            scheduled_start: Some(create.scheduled_start),
+            notice_minutes: create.notice_minutes,
+            amount: create.amount,
+            created_at: Some(Utc::now()),
+        };
+        store
+            .cancellation_penalties
+            .insert(penalty.id.clone(), penalty.clone());
+        Ok(penalty)
+    }
+
+    async fn create_refund_request(&self, create: RefundRequestCreate) -> Result<String, Error> {
+        let now = Utc::now();
+        let id = new_id();
+        let mut store = self.write()?;
+        if store.refund_requests.values().any(|r| {
+            r.walk_request_id == create.walk_request_id && r.status == RefundStatus::Requested
+        }) {
+            return Err(Error::duplicate(&["walk_request_id"]));
+        }
+        store.refund_requests.insert(
+            id.clone(),
+            RefundRequest {
+                id: id.clone(),
+                walk_request_id: create.walk_request_id,
+                owner_id: create.owner_id,
+                walker_id: create.walker_id,
+                amount: create.amount,
+                reason: create.reason,
+                evidence_ids: create.evidence_ids,
+                status: RefundStatus::Requested,
+                approved_amount: None,
+                walker_debit: None,
+                reviewed_by: None,
+                review_note: None,
+                reviewed_at: None,
+                created_at: Some(now),
+                updated_at: Some(now),
+            },
+        );
+        Ok(id)
+    }
+
+    async fn query_refund_requests(
+        &self,
+        query: RefundRequestQuery,
+        pagination: Option<Pagination>,
+    ) -> Result<(Vec<RefundRequest>, i64), Error> {
+        let mut requests = select(&self.read()?.refund_requests, |r| {
+            query.id.as_ref().map_or(true, |id| &r.id == id)
+                && query
+                    .walk_request_id
+                    .as_ref()
+                    .map_or(true, |id| &r.walk_request_id == id)
+                && query.owner_id.as_ref().map_or(true, |id| &r.owner_id == id)
+                && query.status.map_or(true, |s| r.status == s)
+        });
+        requests.reverse();
+        let total = requests.len() as i64;
+        Ok((paginate(requests, pagination.as_ref()), total))
+    }
+
+    async fn review_refund_request(
+        &self,
+        review: RefundReview,
+    ) -> Result<Option<RefundRequest>, Error> {
+        let mut store = self.write()?;
+        let Some(walk_request_id) = store
+            .refund_requests
+            .get(&review.id)
+            .filter(|r| r.status == RefundStatus::Requested)
+            .map(|r| r.walk_request_id.clone())
+        else {
+            return Ok(None);
+        };
+        let refunded = review.approved_amount.unwrap_or_default()
+            + store
+                .refund_requests
+                .values()
+                .filter(|r| r.walk_request_id == walk_request_id)
+                .filter(|r| r.status == RefundStatus::Approved)
+                .filter_map(|r| r.approved_amount)
+                .sum::<i64>();
+        if refunded > review.refund_limit {
+            return Err(Error::conflict("累计退款超出遛狗费用"));
+        }
+        let now = Utc::now();
+        let Some(request) = store.refund_requests.get_mut(&review.id) else {
+            return Ok(None);
+        };
+        request.status = review.status;
+        request.approved_amount = review.approved_amount;
+        request.walker_debit = review.walker_debit;
+        request.reviewed_by = Some(review.reviewed_by);
+        request.review_note = review.review_note;
+        request.reviewed_at = Some(now);
+        request.updated_at = Some(now);
+        let request = request.clone();
+        if let Some(walker_debit) = request.walker_debit {
+            insert_ledger_entry(
+                &mut store,
+                &request.walker_id,
+                LedgerEntryKind::Refund,
+                -walker_debit,
+                None,
+                Some(request.walk_request_id.clone()),
+            );
+        }
+        Ok(Some(request))
+    }
+
+    async fn query_cancellation_penalties(
+        &self,
+        walk_request_id: &str,
+    ) -> Result<Vec<CancellationPenalty>, Error> {
+        let mut penalties = select(&self.read()?.cancellation_penalties, |p| {
+            p.walk_request_id == walk_request_id
+        });
+        penalties.reverse();
+        Ok(penalties)
+    }
+
+    async fn create_meet_and_greet(&self, create: MeetAndGreetCreate) -> Result<String, Error> {
+        let now = Utc::now();
+        let meet_and_greet = MeetAndGreet {
+            id: new_id(),
+            walk_request_id: create.walk_request_id,
+            owner_id: create.owner_id,
+            walker_id: create.walker_id,
+            status: MeetAndGreetStatus::Proposed,
+            starts_at: create.starts_at,
+            ends_at: create.ends_at,
+            longitude: create.longitude,
+            latitude: create.latitude,
+            note: create.note,
+            accepted_at: None,
+            completed_at: None,
+            canceled_by: None,
+            canceled_at: None,
+            created_at: Some(now),
+            updated_at: Some(now),
+        };
+        let id = meet_and_greet.id.clone();
+        self.write()?
+            .meet_and_greets
+            .insert(id.clone(), meet_and_greet);
+        Ok(id)
+    }
+
+    async fn query_meet_and_greets(
+        &self,
+        query: MeetAndGreetQuery,
+    ) -> Result<Vec<MeetAndGreet>, Error> {
+        let mut meet_and_greets = select(&self.read()?.meet_and_greets, |m| {
+            meet_and_greet_matches(m, &query)
+        });
+        meet_and_greets.sort_by_key(|m| m.starts_at);
+        Ok(meet_and_greets)
+    }
+
+    async fn update_meet_and_greet_by_query(
+        &self,
+        query: MeetAndGreetQuery,
+        update: MeetAndGreetUpdate,
+    ) -> Result<Option<MeetAndGreet>, Error> {
+        let mut store = self.write()?;
+        let Some(meet_and_greet) = store
+            .meet_and_greets
+            .values_mut()
+            .find(|m| meet_and_greet_matches(m, &query))
+        else {
+            return Ok(None);
+        };
+        let now = Utc::now();
+        if let Some(status) = update.status {
+            meet_and_greet.status = status;
+            match status {
+                MeetAndGreetStatus::Accepted => meet_and_greet.accepted_at = Some(now),
+                MeetAndGreetStatus::Completed => meet_and_greet.completed_at = Some(now),
+                MeetAndGreetStatus::Declined | MeetAndGreetStatus::Canceled => {
+                    meet_and_greet.canceled_at = Some(now)
+                }
+                MeetAndGreetStatus::Proposed => {}
+            }
+        }
+        if let Some(canceled_by) = update.canceled_by {
+            meet_and_greet.canceled_by = Some(canceled_by);
+        }
+        meet_and_greet.updated_at = Some(now);
+        Ok(Some(meet_and_greet.clone()))
+    }
+
+    async fn create_chat_message(&self, create: ChatMessageCreate) -> Result<String, Error> {
+        let message = ChatMessage {
+            id: new_id(),
+            walk_request_id: create.walk_request_id,
+            sender_id: create.sender_id,
+            text: create.text,
+            image_ids: create.image_ids,
+            created_at: Some(Utc::now()),
+        };
+        let id = message.id.clone();
+        self.write()?.chat_messages.insert(id.clone(), message);
+        Ok(id)
+    }
+
+    async fn get_chat_message(&self, id: &str) -> Result<ChatMessage, Error> {
+        self.read()?
+            .chat_messages
+            .get(id)
+            .cloned()
+            .ok_or(Error::not_found("chat message not found"))
+    }
+
+    async fn query_chat_messages(
+        &self,
+        walk_request_id: &str,
+        page: Page,
+    ) -> Result<(Vec<ChatMessage>, u64), Error> {
+        let mut messages = select(&self.read()?.chat_messages, |m| {
+            m.walk_request_id == walk_request_id
+        });
+        messages.sort_by(|a, b| (b.created_at, &b.id).cmp(&(a.created_at, &a.id)));
+        let total = messages.len() as u64;
+        let messages = paginate_page(messages, Some(&page), |m, after| {
+            (m.created_at, m.id.as_str()) < (after.sort_key, after.id.to_hex().as_str())
+        });
+        Ok((messages, total))
+    }
+
+    async fn query_chat_read_markers(
+        &self,
+        walk_request_id: &str,
+    ) -> Result<Vec<ChatReadMarker>, Error> {
+        Ok(self
+            .read()?
+            .chat_read_markers
+            .values()
+            .filter(|m| m.walk_request_id == walk_request_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn upsert_chat_read_marker(&self, upsert: ChatReadMarkerUpsert) -> Result<(), Error> {
+        let key = (upsert.walk_request_id.clone(), upsert.user_id.clone());
+        self.write()?.chat_read_markers.insert(
+            key,
+            ChatReadMarker {
+                walk_request_id: upsert.walk_request_id,
+                user_id: upsert.user_id,
+                message_id: upsert.message_id,
+                message_created_at: Some(upsert.message_created_at),
+                updated_at: Some(Utc::now()),
+            },
+        );
+        Ok(())
+    }
+
+    async fn count_unread_chat_messages(
+        &self,
+        user_id: &str,
+        walk_request_ids: &[String],
+    ) -> Result<HashMap<String, u64>, Error> {
+        let store = self.read()?;
+        let mut counts = HashMap::new();
+        for message in store.chat_messages.values() {
+            if message.sender_id == user_id || !walk_request_ids.contains(&message.walk_request_id)
+            {
+                continue;
+            }
+            let read_until = store
+                .chat_read_markers
+                .get(&(message.walk_request_id.clone(), user_id.to_owned()))
+                .and_then(|m| m.message_created_at);
+            if read_until.map_or(true, |t| message.created_at > Some(t)) {
+                *counts.entry(message.walk_request_id.clone()).or_insert(0) += 1;
+            }
+        }
+        Ok(counts)
+    }
+
+    async fn operations_snapshot(&self, since: DateTime<Utc>) -> Result<OperationsSnapshot, Error> {
+        let store = self.read()?;
+        Ok(OperationsSnapshot {
+            active_walks: store
+                .walk_requests
+                .values()
+                .filter(|r| {
+                    r.started_at.is_some() && r.finished_at.is_none() && r.canceled_at.is_none()
+                })
+                .count() as i64,
+            failed_payouts: store
+                .withdrawals
+                .values()
+                .filter(|w| {
+                    w.status == WithdrawalStatus::Failed
+                        && w.updated_at.map_or(false, |t| t >= since)
+                })
+                .count() as i64,
+        })
+    }
+
+    async fn create_direct_upload(&self, create: DirectUploadCreate) -> Result<String, Error> {
+        let id = new_id();
+        self.write()?.direct_uploads.insert(
+            id.clone(),
+            DirectUpload {
+                id: id.clone(),
+                uploader: create.uploader,
+                key: create.key,
+                filename: create.filename,
+                content_type: create.content_type,
+                size: create.size,
+                status: DirectUploadStatus::Pending,
+                expires_at: Some(create.expires_at),
+                confirmed_at: None,
+                created_at: Some(Utc::now()),
+            },
+        );
+        Ok(id)
+    }
+
+    async fn get_direct_upload(&self, id: &str) -> Result<Option<DirectUpload>, Error> {
+        Ok(self.read()?.direct_uploads.get(id).cloned())
+    }
+
+    // 只确认待上传的记录, 返回是否确认成功
+    async fn confirm_direct_upload(&self, id: &str, size: i64) -> Result<bool, Error> {
+        let mut store = self.write()?;
+        let Some(upload) = store
+            .direct_uploads
+            .get_mut(id)
+            .filter(|u| u.status == DirectUploadStatus::Pending)
+        else {
+            return Ok(false);
+        };
+        upload.status = DirectUploadStatus::Confirmed;
+        upload.size = size;
+        upload.confirmed_at = Some(Utc::now());
+        Ok(true)
+    }
+
+    async fn create_location_access(&self, create: LocationAccessCreate) -> Result<String, Error> {
+        let id = new_id();
+        self.write()?.location_accesses.insert(
+            id.clone(),
+            LocationAccess {
+                id: id.clone(),
+                accessor_id: create.accessor_id,
+                kind: create.kind,
+                walk_request_id: create.walk_request_id,
+                subject_ids: create.subject_ids,
+                created_at: Some(Utc::now()),
+            },
+        );
+        Ok(id)
+    }
+
+    async fn query_location_accesses(
+        &self,
+        query: LocationAccessQuery,
+        pagination: Pagination,
+    ) -> Result<(Vec<LocationAccess>, i64), Error> {
+        let mut accesses = select(&self.read()?.location_accesses, |a| {
+            query
+                .accessor_id
+                .as_ref()
+                .map_or(true, |id| &a.accessor_id == id)
+                && query
+                    .subject_id
+                    .as_ref()
+                    .map_or(true, |id| a.subject_ids.contains(id))
+                && query
+                    .walk_request_id
+                    .as_ref()
+                    .map_or(true, |id| a.walk_request_id.as_ref() == Some(id))
+                && query.kind.map_or(true, |k| a.kind == k)
+                && query
+                    .created_after
+                    .map_or(true, |t| a.created_at.map_or(false, |c| c >= t))
+                && query
+                    .created_before
+                    .map_or(true, |t| a.created_at.map_or(false, |c| c < t))
+        });
+        accesses.reverse();
+        let total = accesses.len() as i64;
+        Ok((paginate(accesses, Some(&pagination)), total))
+    }
+
+    async fn count_upload_references(&self, upload_id: &str) -> Result<i64, Error> {
+        let store = self.read()?;
+        let is_upload = |id: &String| id == upload_id;
+        let count = store
+            .dogs
+            .values()
+            .map(|d| {
+                d.portrait_id.iter().filter(|id| is_upload(id)).count()
+                    + d.photos.iter().filter(|id| is_upload(id)).count()
+            })
+            .sum::<usize>()
+            + store
+                .owners
+                .values()
+                .filter(|o| o.avatar_id.iter().any(is_upload))
+                .count()
+            + store
+                .walkers
+                .values()
+                .filter(|w| w.id_document_ids.iter().any(is_upload))
+                .count()
+            + store
+                .tickets
+                .values()
+                .filter(|t| {
+                    t.messages
+                        .iter()
+                        .any(|m| m.attachment_ids.iter().any(is_upload))
+                })
+                .count()
+            + store
+                .refund_requests
+                .values()
+                .filter(|r| r.evidence_ids.iter().any(is_upload))
+                .count()
+            + store
+                .upload_variants
+                .iter()
+                .filter(|v| is_upload(&v.variant_id))
+                .count();
+        Ok(count as i64)
+    }
+
+    // 上传记录由upload-service保存, 内存实现中没有可清理的上传文件
+    async fn query_unreferenced_uploads(
+        &self,
+        _created_before: DateTime<Utc>,
+        _limit: i64,
+    ) -> Result<Vec<String>, Error> {
+        Ok(Vec::new())
+    }
+
+    // 内存实现不保存上传记录, 不检查上传引用
+    async fn query_integrity_issues(
+        &self,
+        kind: IntegrityIssueKind,
+        limit: i64,
+    ) -> Result<Vec<IntegrityIssue>, Error> {
+        let store = self.read()?;
+        let issue =
+            |collection: &str, record_id: &str, field: &str, reference_id: &str| IntegrityIssue {
+                kind,
+                collection: collection.to_owned(),
+                record_id: record_id.to_owned(),
+                field: field.to_owned(),
+                reference_id: reference_id.to_owned(),
+                fixed: false,
+            };
+        let issues = match kind {
+            IntegrityIssueKind::DogMissingBreed => select(&store.dogs, |d| {
+                !store.deleted_dogs.contains_key(d.id.as_str())
+                    && !store.breeds.contains_key(d.breed.id.as_str())
+            })
+            .into_iter()
+            .map(|d| issue("dogs", &d.id, "breed.id", &d.breed.id))
+            .collect::<Vec<IntegrityIssue>>(),
+            IntegrityIssueKind::WalkRequestDeletedDog => select(&store.walk_requests, |r| {
+                r.deleted_at.is_none() && r.canceled_at.is_none() && r.finished_at.is_none()
+            })
+            .into_iter()
+            .flat_map(|r| {
+                r.dogs
+                    .iter()
+                    .filter(|d| {
+                        !store.dogs.contains_key(d.id.as_str())
+                            || store.deleted_dogs.contains_key(d.id.as_str())
+                    })
+                    .map(|d| issue("walk_requests", &r.id, "dogs", &d.id))
+                    .collect::<Vec<IntegrityIssue>>()
+            })
+            .collect(),
+            IntegrityIssueKind::MissingUpload => Vec::new(),
+        };
+        Ok(issues.into_iter().take(limit.max(0) as usize).collect())
+    }
+
+    async fn repair_integrity_issue(&self, issue: &IntegrityIssue) -> Result<bool, Error> {
+        if issue.kind != IntegrityIssueKind::DogMissingBreed {
+            return Ok(false);
+        }
+        let mut store = self.write()?;
+        let Some(breed) = store.dogs.get(&issue.record_id).map(|d| d.breed.clone()) else {
+            return Ok(false);
+        };
+        let existing = store
+            .breeds
+            .values()
+            .find(|b| b.name == breed.name && b.category == breed.category)
+            .cloned();
+        match existing {
+            Some(existing) => {
+                let dog = store.dogs.get_mut(&issue.record_id).expect("dog exists");
+                dog.breed = Breed {
+                    updated_at: None,
+                    ..existing
+                };
+                dog.version += 1;
+                dog.updated_at = Some(Utc::now());
+            }
+            None => {
+                store.breeds.insert(
+                    breed.id.to_string(),
+                    Breed {
+                        updated_at: Some(Utc::now()),
+                        ..breed
+                    },
+                );
+            }
+        }
+        Ok(true)
+    }
+
+    async fn delete_uploads(&self, upload_ids: &[String]) -> Result<u64, Error> {
+        self.write()?
+            .upload_variants
+            .retain(|v| !upload_ids.contains(&v.upload_id));
+        Ok(0)
+    }
+
+    // 内存实现不加密字段
+    async fn reencrypt_fields(&self, _limit: i64) -> Result<u64, Error> {
+        Ok(0)
+    }
+
+    async fn get_token_version(&self, user_id: &str) -> Result<i64, Error> {
+        Ok(self
+            .read()?
+            .token_versions
+            .get(user_id)
+            .copied()
+            .unwrap_or(0))
+    }
+
+    async fn increment_token_version(&self, user_id: &str) -> Result<i64, Error> {
+        let mut store = self.write()?;
+        let version = store.token_versions.entry(user_id.to_owned()).or_insert(0);
+        *version += 1;
+        Ok(*version)
+    }
+
+    async fn revoke_access_token(&self, create: RevokedAccessTokenCreate) -> Result<(), Error> {
+        let mut store = self.write()?;
+        let now = Utc::now();
+        store
+            .revoked_access_tokens
+            .retain(|_, expires_at| *expires_at > now);
+        store
+            .revoked_access_tokens
+            .insert(create.token_id, create.expires_at);
+        Ok(())
+    }
+
+    async fn is_access_token_revoked(&self, token_id: &str) -> Result<bool, Error> {
+        Ok(self
+            .read()?
+            .revoked_access_tokens
+            .get(token_id)
+            .map_or(false, |expires_at| *expires_at > Utc::now()))
+    }
+
+    // 只有第一次标记成功, 同一请求只通知一次
+    async fn mark_walker_nearby_notified(&self, walk_request_id: &str) -> Result<bool, Error> {
+        let mut store = self.write()?;
+        let Some(request) = store
+            .walk_requests
+            .get_mut(walk_request_id)
+            .filter(|r| r.walker_nearby_notified_at.is_none())
+        else {
+            return Ok(false);
+        };
+        let now = Utc::now();
+        request.walker_nearby_notified_at = Some(now);
+        request.updated_at = Some(now);
+        request.version += 1;
+        Ok(true)
+    }
+
+    async fn create_notification(&self, create: NotificationCreate) -> Result<String, Error> {
+        let id = new_id();
+        self.write()?.notifications.insert(
+            id.clone(),
+            Notification {
+                id: id.clone(),
+                user_id: create.user_id,
+                kind: create.kind,
+                walk_request_id: create.walk_request_id,
+                content: create.content,
+                sms: create.sms,
+                pushed_at: None,
+                created_at: Some(Utc::now()),
+            },
+        );
+        Ok(id)
+    }
+
+    async fn query_notifications(
+        &self,
+        user_id: &str,
+        pagination: Pagination,
+    ) -> Result<Vec<Notification>, Error> {
+        let mut notifications = select(&self.read()?.notifications, |n| n.user_id == user_id);
+        notifications.reverse();
+        Ok(paginate(notifications, Some(&pagination)))
+    }
+
+    async fn claim_unpushed_notification(
+        &self,
+        created_after: DateTime<Utc>,
+    ) -> Result<Option<Notification>, Error> {
+        let mut store = self.write()?;
+        Ok(store
+            .notifications
+            .values_mut()
+            .filter(|n| n.pushed_at.is_none() && n.created_at.is_some_and(|t| t > created_after))
+            .min_by(|a, b| a.created_at.cmp(&b.created_at).then(a.id.cmp(&b.id)))
+            .map(|notification| {
+                notification.pushed_at = Some(Utc::now());
+                notification.clone()
+            }))
+    }
+
+    async fn upsert_device_token(&self, upsert: DeviceTokenUpsert) -> Result<(), Error> {
+        let mut store = self.write()?;
+        let now = Some(Utc::now());
+        match store
+            .device_tokens
+            .values_mut()
+            .find(|t| t.token == upsert.token)
+        {
+            Some(token) => {
+                token.user_id = upsert.user_id;
+                token.platform = upsert.platform;
+                token.updated_at = now;
+            }
+            None => {
+                let id = new_id();
+                store.device_tokens.insert(
+                    id.clone(),
+                    DeviceToken {
+                        id,
+                        user_id: upsert.user_id,
+                        platform: upsert.platform,
+                        token: upsert.token,
+                        updated_at: now,
+                    },
+                );
+            }
+        }
+        Ok(())
+    }
+
+    async fn query_device_tokens(&self, user_id: &str) -> Result<Vec<DeviceToken>, Error> {
+        Ok(select(&self.read()?.device_tokens, |t| {
+            t.user_id == user_id
+        }))
+    }
+
+    async fn delete_device_tokens(&self, query: DeviceTokenQuery) -> Result<u64, Error> {
+        let mut store = self.write()?;
+        let before = store.device_tokens.len();
+        store.device_tokens.retain(|_, t| {
+            !(query.user_id.as_ref().map_or(true, |u| &t.user_id == u)
+                && query.token.as_ref().map_or(true, |token| &t.token == token))
+        });
+        Ok((before - store.device_tokens.len()) as u64)
+    }
+
+    async fn get_notification_settings(
+        &self,
+        user_id: &str,
+    ) -> Result<Option<NotificationSettings>, Error> {
+        Ok(self.read()?.notification_settings.get(user_id).cloned())
+    }
+
+    async fn upsert_notification_settings(
+        &self,
+        upsert: NotificationSettingsUpsert,
+    ) -> Result<NotificationSettings, Error> {
+        let mut store = self.write()?;
+        let settings = store
+            .notification_settings
+            .entry(upsert.user_id.clone())
+            .or_insert_with(|| NotificationSettings {
+                user_id: upsert.user_id,
+                ..Default::default()
+            });
+        settings.sms_enabled = upsert.sms_enabled;
+        settings.sms_phone = upsert.sms_phone;
+        settings.email = upsert.email;
+        settings.email_receipts = upsert.email_receipts;
+        settings.email_weekly_summary = upsert.email_weekly_summary;
+        settings.email_verification_decisions = upsert.email_verification_decisions;
+        settings.updated_at = Some(Utc::now());
+        Ok(settings.clone())
+    }
+
+    async fn claim_weekly_summary_recipient(
+        &self,
+        week_start: DateTime<Utc>,
+    ) -> Result<Option<NotificationSettings>, Error> {
+        let mut store = self.write()?;
+        Ok(store
+            .notification_settings
+            .values_mut()
+            .find(|s| {
+                s.email_weekly_summary
+                    && s.email.is_some()
+                    && s.weekly_summary_sent_at.map_or(true, |t| t < week_start)
+            })
+            .map(|settings| {
+                settings.weekly_summary_sent_at = Some(Utc::now());
+                settings.clone()
+            }))
+    }
+
+    async fn create_email(&self, create: EmailCreate) -> Result<String, Error> {
+        let id = new_id();
+        self.write()?.emails.insert(
+            id.clone(),
+            Email {
+                id: id.clone(),
+                user_id: create.user_id,
+                kind: create.kind,
+                to: create.to,
+                subject: create.subject,
+                text: create.text,
+                html: create.html,
+                sent_at: None,
+                created_at: Some(Utc::now()),
+            },
+        );
+        Ok(id)
+    }
+
+    async fn claim_unsent_email(
+        &self,
+        created_after: DateTime<Utc>,
+    ) -> Result<Option<Email>, Error> {
+        let mut store = self.write()?;
+        Ok(store
+            .emails
+            .values_mut()
+            .filter(|e| e.sent_at.is_none() && e.created_at.is_some_and(|t| t > created_after))
+            .min_by(|a, b| a.created_at.cmp(&b.created_at).then(a.id.cmp(&b.id)))
+            .map(|email| {
+                email.sent_at = Some(Utc::now());
+                email.clone()
+            }))
+    }
+
+    async fn query_translations(
+        &self,
+        source_ids: &[String],
+        language: Language,
+    ) -> Result<Vec<Translation>, Error> {
+        let store = self.read()?;
+        Ok(source_ids
+            .iter()
+            .filter_map(|id| store.translations.get(&(id.clone(), language)).cloned())
+            .collect())
+    }
+
+    async fn upsert_translation(&self, upsert: TranslationUpsert) -> Result<(), Error> {
+        self.write()?.translations.insert(
+            (upsert.source_id.clone(), upsert.language),
+            Translation {
+                source_id: upsert.source_id,
+                language: upsert.language,
+                source_hash: upsert.source_hash,
+                text: upsert.text,
+                updated_at: Some(Utc::now()),
+            },
+        );
+        Ok(())
+    }
+
+    async fn create_action_token(&self, create: ActionTokenCreate) -> Result<String, Error> {
+        let id = new_id();
+        self.write()?.action_tokens.insert(
+            id.clone(),
+            ActionToken {
+                id: id.clone(),
+                user_id: create.user_id,
+                action: create.action,
+                token_hash: create.token_hash,
+                used: false,
+                expires_at: create.expires_at,
+                created_at: Some(Utc::now()),
+            },
+        );
+        Ok(id)
+    }
+
+    async fn consume_action_token(
+        &self,
+        user_id: &str,
+        action: SensitiveAction,
+        token_hash: &str,
+    ) -> Result<Option<ActionToken>, Error> {
+        let now = Utc::now();
+        Ok(self
+            .write()?
+            .action_tokens
+            .values_mut()
+            .find(|t| {
+                t.user_id == user_id
+                    && t.action == action
+                    && t.token_hash == token_hash
+                    && !t.used
+                    && t.expires_at > now
+            })
+            .map(|t| {
+                let consumed = t.clone();
+                t.used = true;
+                consumed
+            }))
+    }
+
+    async fn create_audit_log(&self, create: AuditLogCreate) -> Result<String, Error> {
+        let id = new_id();
+        self.write()?.audit_logs.push(AuditLog {
+            id: id.clone(),
+            walk_request_id: create.walk_request_id,
+            actor_id: create.actor_id,
+            action: create.action,
+            before: create.before,
+            after: create.after,
+            created_at: Some(Utc::now()),
+        });
+        Ok(id)
+    }
+
+    async fn query_audit_logs(
+        &self,
+        query: AuditLogQuery,
+        pagination: Pagination,
+    ) -> Result<(Vec<AuditLog>, i64), Error> {
+        let mut logs = self
+            .read()?
+            .audit_logs
+            .iter()
+            .filter(|l| {
+                query
+                    .walk_request_id
+                    .as_ref()
+                    .map_or(true, |id| &l.walk_request_id == id)
+                    && query
+                        .actor_id
+                        .as_ref()
+                        .map_or(true, |id| l.actor_id.as_ref() == Some(id))
+                    && query.action.map_or(true, |a| l.action == a)
+                    && query
+                        .created_after
+                        .map_or(true, |t| l.created_at.map_or(false, |c| c >= t))
+                    && query
+                        .created_before
+                        .map_or(true, |t| l.created_at.map_or(false, |c| c < t))
+            })
+            .cloned()
+            .collect::<Vec<AuditLog>>();
+        logs.reverse();
+        let total = logs.len() as i64;
+        Ok((paginate(logs, Some(&pagination)), total))
+    }
+
+    async fn purge_deleted(&self, deleted_before: DateTime<Utc>) -> Result<PurgedCounts, Error> {
+        let mut store = self.write()?;
+        let purged_dogs = store
+            .deleted_dogs
+            .iter()
+            .filter(|(_, deleted_at)| **deleted_at <= deleted_before)
+            .map(|(id, _)| id.clone())
+            .collect::<Vec<String>>();
+        for id in &purged_dogs {
+            store.dogs.remove(id);
+            store.deleted_dogs.remove(id);
+        }
+        let purged_requests = store
+            .walk_requests
+            .values()
+            .filter(|r| r.deleted_at.map_or(false, |t| t <= deleted_before))
+            .map(|r| r.id.to_string())
+            .collect::<Vec<String>>();
+        for id in &purged_requests {
+            store.walk_requests.remove(id);
+        }
+        let locations = store.walking_locations.len();
+        store
+            .walking_locations
+            .retain(|l| !purged_requests.contains(&l.request_id));
+        Ok(PurgedCounts {
+            dogs: purged_dogs.len() as u64,
+            walk_requests: purged_requests.len() as u64,
+            walking_locations: (locations - store.walking_locations.len()) as u64,
+        })
+    }
+
+    async fn create_partner(&self, create: PartnerCreate) -> Result<String, Error> {
+        let id = new_id();
+        let now = Utc::now();
+        self.write()?.partners.insert(
+            id.clone(),
+            Partner {
+                id: id.clone(),
+                name: create.name,
+                kind: create.kind,
+                scopes: create.scopes,
+                rate_limit: create.rate_limit,
+                disabled: false,
+                created_at: Some(now),
+                updated_at: Some(now),
+            },
+        );
+        Ok(id)
+    }
+
+    async fn get_partner(&self, id: &str) -> Result<Option<Partner>, Error> {
+        Ok(self.read()?.partners.get(id).cloned())
+    }
+
+    async fn query_partners(&self, pagination: Pagination) -> Result<(Vec<Partner>, i64), Error> {
+        let mut partners = select(&self.read()?.partners, |_| true);
+        partners.reverse();
+        let total = partners.len() as i64;
+        Ok((paginate(partners, Some(&pagination)), total))
+    }
+
+    async fn update_partner(
+        &self,
+        id: &str,
+        update: PartnerUpdate,
+    ) -> Result<Option<Partner>, Error> {
+        let mut store = self.write()?;
+        let Some(partner) = store.partners.get_mut(id) else {
+            return Ok(None);
+        };
+        if let Some(name) = update.name {
+            partner.name = name;
+        }
+        if let Some(scopes) = update.scopes {
+            partner.scopes = scopes;
+        }
+        if let Some(rate_limit) = update.rate_limit {
+            partner.rate_limit = rate_limit;
+        }
+        if let Some(disabled) = update.disabled {
+            partner.disabled = disabled;
+        }
+        partner.updated_at = Some(Utc::now());
+        Ok(Some(partner.clone()))
+    }
+
+    async fn create_partner_api_key(&self, create: PartnerApiKeyCreate) -> Result<String, Error> {
+        let id = new_id();
+        self.write()?.partner_api_keys.insert(
+            id.clone(),
+            (
+                create.key_hash,
+                PartnerApiKey {
+                    id: id.clone(),
+                    partner_id: create.partner_id,
+                    prefix: create.prefix,
+                    revoked_at: None,
+                    created_at: Some(Utc::now()),
+                },
+            ),
+        );
+        Ok(id)
+    }
+
+    async fn get_partner_api_key(&self, key_hash: &str) -> Result<Option<PartnerApiKey>, Error> {
+        Ok(self
+            .read()?
+            .partner_api_keys
+            .values()
+            .find(|(hash, k)| hash == key_hash && k.revoked_at.is_none())
+            .map(|(_, k)| k.clone()))
+    }
+
+    async fn query_partner_api_keys(&self, partner_id: &str) -> Result<Vec<PartnerApiKey>, Error> {
+        let mut keys = select(&self.read()?.partner_api_keys, |(_, k)| {
+            k.partner_id == partner_id
+        })
+        .into_iter()
+        .map(|(_, k)| k)
+        .collect::<Vec<PartnerApiKey>>();
+        keys.reverse();
+        Ok(keys)
+    }
+
+    async fn revoke_partner_api_key(&self, partner_id: &str, id: &str) -> Result<bool, Error> {
+        Ok(self
+            .write()?
+            .partner_api_keys
+            .get_mut(id)
+            .filter(|(_, k)| k.partner_id == partner_id && k.revoked_at.is_none())
+            .map(|(_, k)| k.revoked_at = Some(Utc::now()))
+            .is_some())
+    }
+
+    async fn create_partner_consent(
+        &self,
+        partner_id: &str,
+        owner_id: &str,
+    ) -> Result<PartnerConsent, Error> {
+        let mut store = self.write()?;
+        if let Some(consent) = store
+            .partner_consents
+            .values()
+            .find(|c| c.partner_id == partner_id && c.owner_id == owner_id)
+        {
+            return Ok(consent.clone());
+        }
+        let consent = PartnerConsent {
+            id: new_id(),
+            partner_id: partner_id.to_owned(),
+            owner_id: owner_id.to_owned(),
+            created_at: Some(Utc::now()),
+        };
+        store
+            .partner_consents
+            .insert(consent.id.clone(), consent.clone());
+        Ok(consent)
+    }
+
+    async fn delete_partner_consent(
+        &self,
+        partner_id: &str,
+        owner_id: &str,
+    ) -> Result<bool, Error> {
+        let mut store = self.write()?;
+        let before = store.partner_consents.len();
+        store
+            .partner_consents
+            .retain(|_, c| !(c.partner_id == partner_id && c.owner_id == owner_id));
+        Ok(store.partner_consents.len() < before)
+    }
+
+    async fn query_partner_consents(
+        &self,
+        query: PartnerConsentQuery,
+    ) -> Result<Vec<PartnerConsent>, Error> {
+        let mut consents = select(&self.read()?.partner_consents, |c| {
+            query
+                .partner_id
+                .as_ref()
+                .map_or(true, |id| &c.partner_id == id)
+                && query.owner_id.as_ref().map_or(true, |id| &c.owner_id == id)
+        });
+        consents.reverse();
+        Ok(consents)
+    }
+
+    async fn create_partner_usage(&self, partner_id: &str, endpoint: &str) -> Result<(), Error> {
+        self.write()?
+            .partner_usages
+            .push((partner_id.to_owned(), endpoint.to_owned(), Utc::now()));
+        Ok(())
+    }
+
+    async fn count_partner_usages(
+        &self,
+        partner_id: &str,
+        since: DateTime<Utc>,
+    ) -> Result<i64, Error> {
+        Ok(self
+            .read()?
+            .partner_usages
+            .iter()
+            .filter(|(id, _, at)| id == partner_id && *at >= since)
+            .count() as i64)
+    }
+
+    async fn partner_usage_report(
+        &self,
+        partner_id: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<PartnerUsage>, Error> {
+        let mut counts = BTreeMap::<(String, String), i64>::new();
+        for (_, endpoint, at) in self
+            .read()?
+            .partner_usages
+            .iter()
+            .filter(|(id, _, at)| id == partner_id && *at >= from && *at < to)
+        {
+            *counts
+                .entry((at.format("%Y-%m-%d").to_string(), endpoint.clone()))
+                .or_default() += 1;
+        }
+        Ok(counts
+            .into_iter()
+            .map(|((date, endpoint), count)| PartnerUsage {
+                date,
+                endpoint,
+                count,
+            })
+            .collect())
+    }
+
+    async fn available_walker_ids(
+        &self,
+        query: AvailableWalkerQuery,
+    ) -> Result<Vec<String>, Error> {
+        let store = self.read()?;
+        let busy = |walker_id: &str| {
+            store.walk_requests.values().any(|r| {
+                r.accepted_by.as_deref() == Some(walker_id)
+                    && r.canceled_at.is_none()
+                    && r.finished_at.is_none()
+                    && r.deleted_at.is_none()
+                    && r.should_start_after.map_or(true, |t| t < query.to)
+                    && r.should_end_before.map_or(true, |t| t > query.from)
+            })
+        };
+        Ok(store
+            .walkers
+            .values()
+            .filter(|w| w.verification_status == VerificationStatus::Verified)
+            .filter(|w| match (w.longitude, w.latitude) {
+                (Some(longitude), Some(latitude)) => {
+                    haversine_distance(query.longitude, query.latitude, longitude, latitude)
+                        <= query.radius
+                }
+                _ => false,
+            })
+            .filter(|w| !busy(&w.user_id))
+            .map(|w| w.user_id.clone())
+            .collect())
+    }
+
+    async fn walker_response_times(
+        &self,
+        walker_ids: Vec<String>,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<i64>, Error> {
+        Ok(self
+            .read()?
+            .walk_requests
+            .values()
+            .filter(|r| {
+                r.accepted_by
+                    .as_ref()
+                    .is_some_and(|id| walker_ids.contains(id))
+            })
+            .filter_map(|r| match (r.created_at, r.accepted_at) {
+                (Some(created_at), Some(accepted_at)) if accepted_at >= since => {
+                    Some((accepted_at - created_at).num_seconds())
+                }
+                _ => None,
+            })
+            .collect())
+    }
+
+    async fn neighborhood_stats(
+        &self,
+        query: NeighborhoodStatsQuery,
+    ) -> Result<Vec<NeighborhoodStats>, Error> {
+        let store = self.read()?;
+        let mut cells = BTreeMap::<(i64, i64), (i64, Vec<f64>)>::new();
+        for request in store.walk_requests.values() {
+            if request.canceled_at.is_some() || request.deleted_at.is_some() {
+                continue;
+            }
+            let distance = haversine_distance(
+                query.longitude,
+                query.latitude,
+                request.longitude,
+                request.latitude,
+            );
+            if distance > query.radius {
+                continue;
+            }
+            let cell = cells
+                .entry((
+                    (request.latitude / query.cell_size).floor() as i64,
+                    (request.longitude / query.cell_size).floor() as i64,
+                ))
+                .or_default();
+            if request.finished_at.is_none() {
+                cell.0 += 1;
+            }
+            let earnings = store
+                .ledger_entries
+                .values()
+                .filter(|e| {
+                    e.kind == LedgerEntryKind::Earning
+                        && e.walk_request_id.as_deref() == Some(request.id.as_str())
+                })
+                .map(|e| e.amount)
+                .collect::<Vec<i64>>();
+            if request.finished_at.is_some_and(|t| t >= query.priced_since) && !earnings.is_empty()
+            {
+                cell.1.push(earnings.iter().sum::<i64>() as f64);
+            }
+        }
+        Ok(cells
+            .into_iter()
+            .map(
+                |((latitude, longitude), (active_requests, prices))| NeighborhoodStats {
+                    latitude: (latitude as f64 + 0.5) * query.cell_size,
+                    longitude: (longitude as f64 + 0.5) * query.cell_size,
+                    active_requests,
+                    priced_walks: prices.len() as i64,
+                    average_price: (!prices.is_empty())
+                        .then(|| prices.iter().sum::<f64>() / prices.len() as f64),
+                },
+            )
+            .collect())
+    }
+
+    async fn create_invite(&self, create: InviteCreate) -> Result<String, Error> {
+        let id = new_id();
+        self.write()?.invites.insert(
+            id.clone(),
+            (
+                create.code_hash,
+                Invite {
+                    id: id.clone(),
+                    inviter_id: create.inviter_id,
+                    phone: create.phone,
+                    kind: create.kind,
+                    locale: create.locale,
+                    opened_at: None,
+                    converted_user_id: None,
+                    converted_at: None,
+                    created_at: Some(Utc::now()),
+                },
+            ),
+        );
+        Ok(id)
+    }
+
+    async fn count_invites(&self, query: InviteQuery) -> Result<i64, Error> {
+        Ok(self
+            .read()?
+            .invites
+            .values()
+            .filter(|(_, i)| {
+                query
+                    .inviter_id
+                    .as_ref()
+                    .map_or(true, |id| &i.inviter_id == id)
+                    && query.phone.as_ref().map_or(true, |p| &i.phone == p)
+                    && query
+                        .created_after
+                        .map_or(true, |t| i.created_at.is_some_and(|c| c >= t))
+            })
+            .count() as i64)
+    }
+
+    async fn query_invites(
+        &self,
+        inviter_id: &str,
+        pagination: Pagination,
+    ) -> Result<(Vec<Invite>, i64), Error> {
+        let mut invites = select(&self.read()?.invites, |(_, i)| i.inviter_id == inviter_id)
+            .into_iter()
+            .map(|(_, i)| i)
+            .collect::<Vec<Invite>>();
+        invites.reverse();
+        let total = invites.len() as i64;
+        Ok((paginate(invites, Some(&pagination)), total))
+    }
+
+    async fn open_invite(&self, code_hash: &str) -> Result<Option<Invite>, Error> {
+        Ok(self
+            .write()?
+            .invites
+            .values_mut()
+            .find(|(hash, _)| hash == code_hash)
+            .map(|(_, i)| {
+                i.opened_at.get_or_insert_with(Utc::now);
+                i.clone()
+            }))
+    }
+
+    async fn convert_invites(&self, phone: &str, user_id: &str) -> Result<u64, Error> {
+        let now = Utc::now();
+        Ok(self
+            .write()?
+            .invites
+            .values_mut()
+            .filter(|(_, i)| i.phone == phone && i.converted_at.is_none())
+            .map(|(_, i)| {
+                i.converted_user_id = Some(user_id.to_owned());
+                i.converted_at = Some(now);
+            })
+            .count() as u64)
+    }
+
+    async fn invite_conversion(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<InviteConversion>, Error> {
+        let mut conversions = BTreeMap::<String, InviteConversion>::new();
+        for (_, invite) in self.read()?.invites.values() {
+            if !invite.created_at.is_some_and(|t| t >= from && t < to) {
+                continue;
+            }
+            let conversion = conversions
+                .entry(invite.kind.to_string())
+                .or_insert_with(|| InviteConversion {
+                    kind: invite.kind,
+                    sent: 0,
+                    opened: 0,
+                    converted: 0,
+                });
+            conversion.sent += 1;
+            conversion.opened += invite.opened_at.is_some() as i64;
+            conversion.converted += invite.converted_at.is_some() as i64;
+        }
+        Ok(conversions.into_values().collect())
+    }
+
+    async fn create_short_link(&self, create: ShortLinkCreate) -> Result<String, Error> {
+        let mut store = self.write()?;
+        if store.short_links.values().any(|l| l.code == create.code) {
+            return Err(Error::duplicate(&["code"]));
+        }
+        let id = new_id();
+        store.short_links.insert(
+            id.clone(),
+            ShortLink {
+                id: id.clone(),
+                code: create.code,
+                kind: create.kind,
+                token: create.token,
+                created_by: create.created_by,
+                expires_at: create.expires_at,
+                created_at: Some(Utc::now()),
+            },
+        );
+        Ok(id)
+    }
+
+    async fn get_short_link(&self, code: &str) -> Result<Option<ShortLink>, Error> {
+        let now = Utc::now();
+        Ok(self
+            .read()?
+            .short_links
+            .values()
+            .find(|l| l.code == code && l.expires_at > now)
+            .cloned())
+    }
+
+    async fn self_check(&self) -> Result<(), Error> {
+        self.read().map(|_| ())
+    }
+}
+
+// 与MongoDB一样使用ObjectId作为id, 按id排序即按创建顺序排序
+fn new_id() -> String {
+    ObjectId::new().to_hex()
+}
+
+// 按创建顺序返回满足条件的记录
+fn select<T: Clone>(items: &HashMap<String, T>, filter: impl Fn(&T) -> bool) -> Vec<T> {
+    let mut selected = items
+        .iter()
+        .filter(|(_, item)| filter(item))
+        .collect::<Vec<(&String, &T)>>();
+    selected.sort_by(|a, b| a.0.cmp(b.0));
+    selected.into_iter().map(|(_, item)| item.clone()).collect()
+}
+
+// limit为0时与MongoDB一样不限制数量
+fn paginate<T>(items: Vec<T>, pagination: Option<&Pagination>) -> Vec<T> {
+    let Some(pagination) = pagination else {
+        return items;
+    };
+    let limit = match pagination.limit.unsigned_abs() as usize {
+        0 => usize::MAX,
+        limit => limit,
+    };
+    items
+        .into_iter()
+        .skip(pagination.skip.max(0) as usize)
+        .take(limit)
+        .collect()
+}
+
+// 游标分页时只保留排在游标之后的记录, items需已按游标的排序方式排好序
+fn paginate_page<T>(
+    items: Vec<T>,
+    page: Option<&Page>,
+    is_after: impl Fn(&T, &Cursor) -> bool,
+) -> Vec<T> {
+    match page {
+        None => items,
+        Some(Page::Offset(pagination)) => paginate(items, Some(pagination)),
+        Some(Page::Cursor { after, limit }) => {
+            let limit = match limit.unsigned_abs() as usize {
+                0 => usize::MAX,
+                limit => limit,
+            };
+            items
+                .into_iter()
+                .filter(|item| after.as_ref().map_or(true, |after| is_after(item, after)))
+                .take(limit)
+                .collect()
+        }
+    }
+}
+
+fn parse_gender(gender: &str) -> Gender {
+    match gender {
+        "Male" => Gender::Male,
+        "Female" => Gender::Female,
+        _ => Gender::Other,
+    }
+}
+
+// 按id查找已有品种, 找不到时用查询中的品种信息
+fn resolve_breed(store: &Store, query: &BreedQuery) -> Option<Breed> {
+    if let Some(breed) = query.id.as_ref().and_then(|id| store.breeds.get(id)) {
+        return Some(breed.clone());
+    }
+    query.category.as_ref().map(|category| Breed {
+        id: query
+            .id
+            .as_deref()
+            .and_then(|id| id.parse().ok())
+            .unwrap_or_default(),
+        category: category.clone(),
+        name: query.name.clone().unwrap_or_default(),
+        updated_at: None,
+    })
+}
+
+// 与MongoDB投影中的status计算保持一致
+fn walk_request_status(request: &WalkRequest) -> String {
+    if request.canceled_at.is_some() {
+        "Canceled"
+    } else if request.accepted_at.is_some() {
+        "Accepted"
+    } else if request.started_at.is_some() {
+        "Started"
+    } else if request.finished_at.is_some() {
+        "Finished"
+    } else {
+        "Waiting"
+    }
+    .to_owned()
+}
+
+fn is_finished(request: &WalkRequest) -> bool {
+    request.finished_at.is_some() && request.canceled_at.is_none()
+}
+
+fn walk_request_matches(request: &WalkRequest, query: &WalkRequestQuery) -> bool {
+    let dog_ids = request
+        .dogs
+        .iter()
+        .map(|d| d.id.as_str())
+        .collect::<Vec<&str>>();
+    let acceptances = request.acceptances.as_deref().unwrap_or_default();
+    query.id.as_ref().map_or(true, |id| &request.id == id)
+        && query.dog_ids_includes_any.as_ref().map_or(true, |ids| {
+            ids.iter().any(|id| dog_ids.contains(&id.as_str()))
+        })
+        && query.dog_ids_includes_all.as_ref().map_or(true, |ids| {
+            ids.iter().all(|id| dog_ids.contains(&id.as_str()))
+        })
+        && query
+            .accepted_by
+            .as_ref()
+            .map_or(true, |id| request.accepted_by.as_ref() == Some(id))
+        && query
+            .accepted_by_neq
+            .as_ref()
+            .map_or(true, |id| request.accepted_by.as_ref() != Some(id))
+        && query
+            .accepted_by_is_null
+            .map_or(true, |is_null| request.accepted_by.is_none() == is_null)
+        && query
+            .acceptances_includes_all
+            .as_ref()
+            .map_or(true, |ids| ids.iter().all(|id| acceptances.contains(id)))
+        && query
+            .acceptances_includes_any
+            .as_ref()
+            .map_or(true, |ids| ids.iter().any(|id| acceptances.contains(id)))
+        && query
+            .created_by
+            .as_ref()
+            .map_or(true, |id| &request.created_by == id)
+        && query
+            .created_by_nin
+            .as_ref()
+            .map_or(true, |ids| !ids.contains(&request.created_by))
+        && query
+            .started_at_is_null
+            .map_or(true, |is_null| request.started_at.is_none() == is_null)
+        && query
+            .finished_at_is_null
+            .map_or(true, |is_null| request.finished_at.is_none() == is_null)
+        && query
+            .canceled_at_is_null
+            .map_or(true, |is_null| request.canceled_at.is_none() == is_null)
+        && query
+            .open_to
+            .as_ref()
+            .map_or(true, |id| request.is_open_to(id, Utc::now()))
+        && query.involves.as_ref().map_or(true, |id| {
+            &request.created_by == id
+                || request.accepted_by.as_ref() == Some(id)
+                || acceptances.contains(id)
+        })
+        && query.updated_after.map_or(true, |t| {
+            request
+                .updated_at
+                .map_or(false, |updated_at| updated_at > t)
+        })
+        && (query.include_deleted || request.deleted_at.is_none())
+}
+
+// 公开满足条件的隐藏评价, 返回公开的数量
+fn reveal(store: &mut Store, matches: impl Fn(&Review) -> bool) -> u64 {
+    let now = Utc::now();
+    let mut revealed = 0;
+    for review in store.reviews.values_mut() {
+        if review.state == ReviewState::Hidden && matches(review) {
+            review.state = ReviewState::Revealed;
+            review.revealed_at = Some(now);
+            revealed += 1;
+        }
+    }
+    revealed
+}
+
+fn dog_matches(dog: &Dog, query: &DogQuery) -> bool {
+    query.breed_id.as_ref().map_or(true, |b| dog.breed.id == *b)
+        && query
+            .tags_include_any
+            .as_ref()
+            .map_or(true, |tags| tags.iter().any(|t| dog.tags.contains(t)))
+        && query.gender.as_ref().map_or(true, |g| dog.gender == *g)
+        && query.birthday_before.map_or(true, |b| dog.birthday < b)
+        && query.birthday_after.map_or(true, |a| dog.birthday > a)
+}
+
+fn dog_visible(store: &Store, dog: &Dog, query: &DogQuery) -> bool {
+    query.include_deleted || !store.deleted_dogs.contains_key(dog.id.as_str())
+}
+
+// 附近查询按距离由近到远排序并填写distance
+fn matching_walk_requests(
+    store: &Store,
+    query: &WalkRequestQuery,
+) -> Result<Vec<WalkRequest>, Error> {
+    let mut requests = select(&store.walk_requests, |r| walk_request_matches(r, query));
+    if let Some(nearby) = &query.nearby {
+        let [longitude, latitude, radius] = nearby[..] else {
+            return Err(Error::new("Invalid nearby query, expect [f64;3]"));
+        };
+        for request in &mut requests {
+            request.distance = Some(haversine_distance(
+                longitude,
+                latitude,
+                request.longitude,
+                request.latitude,
+            ));
+        }
+        requests.retain(|r| r.distance.map_or(false, |d| d <= radius));
+        requests.sort_by(|a, b| {
+            a.distance
+                .unwrap_or_default()
+                .total_cmp(&b.distance.unwrap_or_default())
+        });
+    }
+    Ok(requests)
+}
+
+// 可作为游标的时间类排序键, 其他字段按创建时间
+fn walk_request_sort_key(request: &WalkRequest, field: &str) -> Option<DateTime<Utc>> {
+    match field {
+        "updated_at" => request.updated_at,
+        "should_start_after" => request.should_start_after,
+        "should_start_before" => request.should_start_before,
+        _ => request.created_at,
+    }
+}
+
+fn sort_walk_requests(requests: &mut [WalkRequest], sort_by: &SortBy) {
+    requests.sort_by(|a, b| {
+        let ordering = match sort_by.field.as_str() {
+            "updated_at" => a.updated_at.cmp(&b.updated_at),
+            "should_start_after" => a.should_start_after.cmp(&b.should_start_after),
+            "should_start_before" => a.should_start_before.cmp(&b.should_start_before),
+            "distance" => a
+                .distance
+                .unwrap_or_default()
+                .total_cmp(&b.distance.unwrap_or_default()),
+            _ => a.created_at.cmp(&b.created_at),
+        }
+        .then(a.id.cmp(&b.id));
+        if sort_by.order == Order::Asc {
+            ordering
+        } else {
+            ordering.reverse()
+        }
+    });
+}
+
+fn sort_dogs(dogs: &mut [Dog], sort_by: &SortBy) {
+    dogs.sort_by(|a, b| {
+        let ordering = match sort_by.field.as_str() {
+            "name" => a.name.cmp(&b.name),
+            "gender" => format!("{:?}", a.gender).cmp(&format!("{:?}", b.gender)),
+            "birthday" => a.birthday.cmp(&b.birthday),
+            "owner_id" => a.owner_id.cmp(&b.owner_id),
+            _ => a.updated_at.cmp(&b.updated_at),
+        }
+        .then(a.id.cmp(&b.id));
+        if sort_by.order == Order::Asc {
+            ordering
+        } else {
+            ordering.reverse()
+        }
+    });
+}
+
+fn sort_breeds(breeds: &mut [Breed], sort_by: &SortBy) {
+    breeds.sort_by(|a, b| {
+        let ordering = match sort_by.field.as_str() {
+            "name" => a.name.cmp(&b.name),
+            "category" => a.category.to_string().cmp(&b.category.to_string()),
+            _ => a.updated_at.cmp(&b.updated_at),
+        }
+        .then(a.id.as_str().cmp(b.id.as_str()));
+        if sort_by.order == Order::Asc {
+            ordering
+        } else {
+            ordering.reverse()
+        }
+    });
+}
+
+fn check_walk_request_version(
+    request: &WalkRequest,
+    update: &WalkRequestUpdate,
+) -> Result<(), Error> {
+    match update.expected_version {
+        Some(version) if version != request.version => {
+            Err(Error::conflict("代遛请求已被修改, 请刷新后重试"))
+        }
+        _ => Ok(()),
+    }
+}
+
+// 返回字段是否被修改
+fn apply_update_field<T: Clone>(target: &mut Option<T>, field: &UpdateField<T>) -> bool {
+    match field {
+        UpdateField::Keep => return false,
+        UpdateField::Set(value) => *target = Some(value.clone()),
+        UpdateField::Unset => *target = None,
+    }
+    true
+}
+
+fn apply_walk_request_update(request: &mut WalkRequest, update: &WalkRequestUpdate) {
+    if let Some(dogs) = &update.dogs {
+        request.dogs = dogs.clone();
+    }
+    if let Some(should_start_after) = update.should_start_after {
+        request.should_start_after = Some(should_start_after);
+    }
+    if let Some(should_start_before) = update.should_start_before {
+        request.should_start_before = Some(should_start_before);
+    }
+    if let Some(should_end_before) = update.should_end_before {
+        request.should_end_before = Some(should_end_before);
+    }
+    if let Some(should_end_after) = update.should_end_after {
+        request.should_end_after = Some(should_end_after);
+    }
+    if let Some(latitude) = update.latitude {
+        request.latitude = latitude;
+    }
+    if let Some(longitude) = update.longitude {
+        request.longitude = longitude;
+    }
+    apply_update_field(&mut request.accepted_by, &update.accepted_by);
+    apply_update_field(&mut request.accepted_at, &update.accepted_at);
+    if let Some(canceled_at) = update.canceled_at {
+        request.canceled_at = Some(canceled_at);
+    }
+    if let Some(deleted_at) = update.deleted_at {
+        request.deleted_at = Some(deleted_at);
+    }
+    if let Some(started_at) = update.started_at {
+        request.started_at = Some(started_at);
+    }
+    if let Some(finished_at) = update.finished_at {
+        request.finished_at = Some(finished_at);
+    }
+    if let Some(walker_id) = &update.add_to_acceptances {
+        let acceptances = request.acceptances.get_or_insert_with(Vec::new);
+        if !acceptances.contains(walker_id) {
+            acceptances.push(walker_id.clone());
+        }
+    }
+    if let (Some(walker_id), Some(acceptances)) =
+        (&update.remove_from_acceptances, &mut request.acceptances)
+    {
+        acceptances.retain(|id| id != walker_id);
+    }
+    apply_update_field(&mut request.route_preference, &update.route_preference);
+    if let Some(route_assessment) = &update.route_assessment {
+        request.route_assessment = Some(route_assessment.clone());
+    }
+    request.updated_at = Some(Utc::now());
+    request.version += 1;
+    request.status = walk_request_status(request);
+}
+
+// 按轨迹点的先后顺序累加相邻点距离
+fn walk_distance(locations: &[WalkingLocation], walk_request_id: &str) -> f64 {
+    let points = locations
+        .iter()
+        .filter(|l| l.request_id == walk_request_id)
+        .collect::<Vec<&WalkingLocation>>();
+    points
+        .windows(2)
+        .map(|p| haversine_distance(p[0].longitude, p[0].latitude, p[1].longitude, p[1].latitude))
+        .sum()
+}
+
+fn progress_entry<'a>(
+    progress: &'a mut HashMap<String, AchievementProgress>,
+    user_id: &str,
+) -> &'a mut AchievementProgress {
+    progress
+        .entry(user_id.to_owned())
+        .or_insert_with(|| AchievementProgress {
+            user_id: user_id.to_owned(),
+            ..Default::default()
+        })
+}
+
+fn walker_entry<'a>(store: &'a mut Store, user_id: &str) -> &'a mut Walker {
+    store
+        .walkers
+        .entry(user_id.to_owned())
+        .or_insert_with(|| Walker {
+            id: new_id(),
+            user_id: user_id.to_owned(),
+            created_at: Some(Utc::now()),
+            ..Default::default()
+        })
+}
+
+fn walker_matches(walker: &Walker, query: &WalkerQuery) -> bool {
+    query
+        .user_id
+        .as_ref()
+        .map_or(true, |id| &walker.user_id == id)
+        && query
+            .verification_status
+            .map_or(true, |s| walker.verification_status == s)
+}
+
+fn report_matches(report: &Report, query: &ReportQuery) -> bool {
+    query.id.as_ref().map_or(true, |id| &report.id == id)
+        && query
+            .reporter_id
+            .as_ref()
+            .map_or(true, |id| &report.reporter_id == id)
+        && query.status.map_or(true, |s| report.status == s)
+}
+
+fn ticket_matches(ticket: &Ticket, query: &TicketQuery) -> bool {
+    query.id.as_ref().map_or(true, |id| &ticket.id == id)
+        && query
+            .user_id
+            .as_ref()
+            .map_or(true, |id| &ticket.user_id == id)
+        && query.status.map_or(true, |s| ticket.status == s)
+        && query
+            .assignee_id
+            .as_ref()
+            .map_or(true, |id| ticket.assignee_id.as_ref() == Some(id))
+}
+
+fn meet_and_greet_matches(meet_and_greet: &MeetAndGreet, query: &MeetAndGreetQuery) -> bool {
+    query
+        .id
+        .as_ref()
+        .map_or(true, |id| &meet_and_greet.id == id)
+        && query
+            .walk_request_id
+            .as_ref()
+            .map_or(true, |id| &meet_and_greet.walk_request_id == id)
+        && query
+            .walker_id
+            .as_ref()
+            .map_or(true, |id| &meet_and_greet.walker_id == id)
+        && query.participants_any.as_ref().map_or(true, |ids| {
+            ids.contains(&meet_and_greet.owner_id) || ids.contains(&meet_and_greet.walker_id)
+        })
+        && query
+            .status_in
+            .as_ref()
+            .map_or(true, |s| s.contains(&meet_and_greet.status))
+        && query
+            .starts_before
+            .map_or(true, |t| meet_and_greet.starts_at < t)
+        && query
+            .ends_after
+            .map_or(true, |t| meet_and_greet.ends_at > t)
+}
+
+fn withdrawal_matches(withdrawal: &Withdrawal, query: &WithdrawalQuery) -> bool {
+    query.id.as_ref().map_or(true, |id| &withdrawal.id == id)
+        && query
+            .ids
+            .as_ref()
+            .map_or(true, |ids| ids.contains(&withdrawal.id))
+        && query
+            .user_id
+            .as_ref()
+            .map_or(true, |id| &withdrawal.user_id == id)
+        && query.status.map_or(true, |s| withdrawal.status == s)
+        && query
+            .payout_reference
+            .as_ref()
+            .map_or(true, |r| withdrawal.payout_reference.as_ref() == Some(r))
+}
+
+// 核验通过时记录核验时间, 其他状态清除核验时间
+fn set_payout_status(
+    account: &mut PayoutAccount,
+    status: PayoutAccountStatus,
+    failure_reason: Option<String>,
+) {
+    let now = Utc::now();
+    account.status = status;
+    account.failure_reason = failure_reason;
+    account.verified_at = (status == PayoutAccountStatus::Verified).then_some(now);
+    account.updated_at = Some(now);
+}
+
+fn balance(store: &Store, user_id: &str) -> i64 {
+    store
+        .ledger_entries
+        .values()
+        .filter(|e| e.user_id == user_id)
+        .map(|e| e.amount)
+        .sum()
+}
+
+fn insert_ledger_entry(
+    store: &mut Store,
+    user_id: &str,
+    kind: LedgerEntryKind,
+    amount: i64,
+    withdrawal_id: Option<String>,
+    walk_request_id: Option<String>,
+) {
+    let id = new_id();
+    store.ledger_entries.insert(
+        id.clone(),
+        LedgerEntry {
+            id,
+            user_id: user_id.to_owned(),
+            kind,
+            amount,
+            withdrawal_id,
+            walk_request_id,
+            created_at: Some(Utc::now()),
+        },
+    );
+}
+
+// 作废满足条件且未作废的刷新令牌, 返回作废的数量
+fn revoke_refresh_tokens(store: &mut Store, filter: impl Fn(&RefreshToken) -> bool) -> u64 {
+    let mut revoked = 0;
+    for token in store
+        .refresh_tokens
+        .values_mut()
+        .filter(|t| !t.revoked && filter(t))
+    {
+        token.revoked = true;
+        revoked += 1;
+    }
+    revoked
+}
+
+// 对应USER_REFERENCES中的一个集合字段, to为空时只计数, 否则把from改写为to; 返回涉及的记录数.
+// uploads由upload-service保存, 内存实现中没有记录
+fn replace_user_reference(
+    store: &mut Store,
+    collection: &str,
+    field: &str,
+    from: &str,
+    to: Option<&str>,
+) -> u64 {
+    let replace = |value: &mut String| {
+        if value != from {
+            return false;
+        }
+        if let Some(to) = to {
+            *value = to.to_owned();
+        }
+        true
+    };
+    let replace_all = |values: &mut Vec<String>| {
+        let mut replaced = false;
+        for value in values.iter_mut() {
+            replaced |= replace(value);
+        }
+        replaced
+    };
+    let count = match (collection, field) {
+        ("dogs", "owner_id") => store
+            .dogs
+            .values_mut()
+            .map(|d| replace(&mut d.owner_id))
+            .filter(|replaced| *replaced)
+            .count(),
+        ("walk_requests", "created_by") => store
+            .walk_requests
+            .values_mut()
+            .map(|r| replace(&mut r.created_by))
+            .filter(|replaced| *replaced)
+            .count(),
+        ("walk_requests", "accepted_by") => store
+            .walk_requests
+            .values_mut()
+            .map(|r| r.accepted_by.as_mut().map_or(false, replace))
+            .filter(|replaced| *replaced)
+            .count(),
+        ("walk_requests", "acceptances") => store
+            .walk_requests
+            .values_mut()
+            .map(|r| r.acceptances.as_mut().map_or(false, replace_all))
+            .filter(|replaced| *replaced)
+            .count(),
+        ("walk_requests", "priority_walkers") => store
+            .walk_requests
+            .values_mut()
+            .map(|r| r.priority_walkers.as_mut().map_or(false, replace_all))
+            .filter(|replaced| *replaced)
+            .count(),
+        ("reviews", "owner_id") => store
+            .reviews
+            .values_mut()
+            .map(|r| replace(&mut r.owner_id))
+            .filter(|replaced| *replaced)
+            .count(),
+        ("reviews", "walker_id") => store
+            .reviews
+            .values_mut()
+            .map(|r| replace(&mut r.walker_id))
+            .filter(|replaced| *replaced)
+            .count(),
+        ("reports", "reporter_id") => store
+            .reports
+            .values_mut()
+            .map(|r| replace(&mut r.reporter_id))
+            .filter(|replaced| *replaced)
+            .count(),
+        ("reports", "target_user_id") => store
+            .reports
+            .values_mut()
+            .map(|r| replace(&mut r.target_user_id))
+            .filter(|replaced| *replaced)
+            .count(),
+        ("blocks", "owner_id") => store
+            .blocks
+            .values_mut()
+            .map(|b| replace(&mut b.owner_id))
+            .filter(|replaced| *replaced)
+            .count(),
+        ("blocks", "walker_id") => store
+            .blocks
+            .values_mut()
+            .map(|b| replace(&mut b.walker_id))
+            .filter(|replaced| *replaced)
+            .count(),
+        ("favorites", "owner_id") => store
+            .favorites
+            .values_mut()
+            .map(|f| replace(&mut f.owner_id))
+            .filter(|replaced| *replaced)
+            .count(),
+        ("favorites", "walker_id") => store
+            .favorites
+            .values_mut()
+            .map(|f| replace(&mut f.walker_id))
+            .filter(|replaced| *replaced)
+            .count(),
+        ("partner_consents", "owner_id") => store
+            .partner_consents
+            .values_mut()
+            .map(|c| replace(&mut c.owner_id))
+            .filter(|replaced| *replaced)
+            .count(),
+        ("invites", "inviter_id") => store
+            .invites
+            .values_mut()
+            .map(|(_, i)| replace(&mut i.inviter_id))
+            .filter(|replaced| *replaced)
+            .count(),
+        ("short_links", "created_by") => store
+            .short_links
+            .values_mut()
+            .map(|l| replace(&mut l.created_by))
+            .filter(|replaced| *replaced)
+            .count(),
+        ("refund_requests", "owner_id") => store
+            .refund_requests
+            .values_mut()
+            .map(|r| replace(&mut r.owner_id))
+            .filter(|replaced| *replaced)
+            .count(),
+        ("refund_requests", "walker_id") => store
+            .refund_requests
+            .values_mut()
+            .map(|r| replace(&mut r.walker_id))
+            .filter(|replaced| *replaced)
+            .count(),
+        _ => 0,
+    };
+    count as u64
+}
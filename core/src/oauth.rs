@@ -0,0 +1,12 @@
+use crate::error::Error;
+
+// 第三方登录换取的用户标识, subject在同一渠道内唯一
+#[derive(Debug, Clone)]
+pub struct OAuthIdentity {
+    pub subject: String,
+}
+
+// 第三方登录渠道, 具体实现位于oauth_providers
+pub trait OAuthProvider {
+    async fn exchange_code(&self, code: &str) -> Result<OAuthIdentity, Error>;
+}
@@ -0,0 +1,17 @@
+use std::time::Duration;
+
+use crate::error::Error;
+
+// 对象存储中已存在对象的元数据
+#[derive(Debug, Clone)]
+pub struct StoredObject {
+    pub size: i64,
+    pub content_type: Option<String>,
+}
+
+// 支持预签名直传的对象存储, 客户端直接上传和下载, 字节不经过本服务. 具体实现位于object_stores
+pub trait ObjectStore {
+    fn presign_put(&self, key: &str, expires_in: Duration) -> Result<String, Error>;
+    fn presign_get(&self, key: &str, expires_in: Duration) -> Result<String, Error>;
+    async fn head(&self, key: &str) -> Result<Option<StoredObject>, Error>;
+}
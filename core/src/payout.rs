@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    entities::{PayoutAccountStatus, PayoutMethod, WithdrawalStatus},
+    error::Error,
+};
+
+// 提交给打款服务登记的收款信息, 完整账号只用于登记, 不落库
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PayoutDestination {
+    pub method: PayoutMethod,
+    pub holder_name: String,
+    pub account_number: String,
+    pub bank_name: Option<String>,
+}
+
+// 打款服务的登记结果, reference用于后续查询核验状态和发起打款
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PayoutRegistration {
+    pub reference: String,
+    pub status: PayoutAccountStatus,
+    pub failure_reason: Option<String>,
+}
+
+// 提交给打款服务的打款指令, withdrawal_id同时作为幂等键, 重复提交不会重复打款
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PayoutOrder {
+    pub withdrawal_id: String,
+    pub destination_reference: String,
+    pub amount: i64,
+}
+
+// 打款服务的受理结果, 异步打款时status为Processing, 最终结果通过回调通知
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PayoutTransfer {
+    pub reference: String,
+    pub status: WithdrawalStatus,
+    pub failure_reason: Option<String>,
+}
+
+// 打款服务, 具体实现位于payout_providers
+pub trait PayoutProvider {
+    async fn register_destination(
+        &self,
+        destination: &PayoutDestination,
+    ) -> Result<PayoutRegistration, Error>;
+    async fn destination_status(&self, reference: &str) -> Result<PayoutRegistration, Error>;
+    async fn execute_payout(&self, order: &PayoutOrder) -> Result<PayoutTransfer, Error>;
+}
@@ -0,0 +1,34 @@
+use crate::{
+    entities::{DeviceToken, NotificationKind},
+    error::Error,
+};
+
+// 推送到设备的通知内容, kind和walk_request_id随推送下发, 供App跳转到对应页面
+#[derive(Debug, Clone)]
+pub struct PushMessage {
+    pub kind: NotificationKind,
+    pub title: String,
+    pub body: String,
+    pub walk_request_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PushOutcome {
+    Delivered,
+    Unregistered, // 令牌已失效, 如App已卸载, 需删除该令牌
+}
+
+// 推送通道, 具体实现位于notifiers
+pub trait Notifier {
+    async fn push(&self, device: &DeviceToken, message: &PushMessage)
+        -> Result<PushOutcome, Error>;
+}
+
+// 一轮推送的结果, failures为推送和短信发送失败的原因, 由调用方记录
+#[derive(Debug, Default)]
+pub struct PushSummary {
+    pub delivered: u64,
+    pub unregistered: u64,
+    pub sms_sent: u64,
+    pub failures: Vec<String>,
+}
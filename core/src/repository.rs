@@ -0,0 +1,1510 @@
+use crate::entities::DirectUpload;
+use crate::entities::EmergencyContact;
+use crate::entities::Gender;
+use crate::entities::NeighborhoodStats;
+use crate::entities::OperationsSnapshot;
+use crate::entities::PartnerUsage;
+use crate::entities::PhoneBinding;
+use crate::entities::PurgedCounts;
+use crate::entities::Translation;
+use crate::entities::{AccountDeletion, AccountMerge, MergedReference};
+use crate::entities::{Achievement, AchievementKind, AchievementProgress};
+use crate::entities::{ActionToken, SensitiveAction};
+use crate::entities::{AuditLog, WalkRequestAuditAction};
+use crate::entities::{Block, Favorite, Owner, RankedWalker, Report, ReportStatus, Review};
+use crate::entities::{Breed, BreedCareTips, Category, Dog, Otp, OtpPurpose, RefreshToken};
+use crate::entities::{CancellationPenalty, CancellationPenaltyTier};
+use crate::entities::{ChatMessage, ChatReadMarker, MeetAndGreet, MeetAndGreetStatus};
+use crate::entities::{Device, Session};
+use crate::entities::{DeviceToken, PushPlatform};
+use crate::entities::{Email, EmailKind};
+use crate::entities::{GeoPoint, Poi};
+use crate::entities::{HelpArticle, PasswordResetToken};
+use crate::entities::{ImageSize, UploadVariant};
+use crate::entities::{IntegrityIssue, IntegrityIssueKind};
+use crate::entities::{Invite, InviteConversion, InviteKind};
+use crate::entities::{LedgerEntry, PaymentAttemptStats, Withdrawal, WithdrawalStatus};
+use crate::entities::{LocationAccess, LocationAccessKind};
+use crate::entities::{Notification, NotificationKind, NotificationSettings};
+use crate::entities::{OAuthAccount, OAuthLinkToken, OAuthProviderKind};
+use crate::entities::{OwnerReputationStats, ReviewKind, ReviewState};
+use crate::entities::{Partner, PartnerApiKey, PartnerConsent, PartnerKind, PartnerScope};
+use crate::entities::{PayoutAccount, PayoutAccountStatus, PayoutMethod};
+use crate::entities::{RefundRequest, RefundStatus};
+use crate::entities::{RouteAssessment, RoutePreference, WalkingLocation};
+use crate::entities::{ShortLink, ShortLinkKind};
+use crate::entities::{Ticket, TicketCategory, TicketStatus};
+use crate::entities::{VerificationStatus, WalkRequest, Walker, WalkerStats};
+use crate::error::Error;
+use crate::translation::Language;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use bson::oid::ObjectId;
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+use std::future::Future;
+use std::str::FromStr;
+use utoipa::{IntoParams, ToSchema};
+
+#[derive(Debug, Serialize, Deserialize, Clone, IntoParams, ToSchema)]
+#[into_params(parameter_in = Query)]
+pub struct Pagination {
+    pub limit: i64,
+    pub skip: i64,
+}
+
+// 翻页游标, 由上一页最后一条记录的排序键和_id组成, 对客户端不透明.
+// 与偏移分页不同, 两页之间插入新记录不会导致重复或遗漏
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cursor {
+    pub sort_key: Option<DateTime<Utc>>, // 只按_id排序时为空
+    pub id: ObjectId,
+}
+
+impl Cursor {
+    // 格式为base64url("{排序键毫秒}.{_id}")
+    pub fn encode(&self) -> String {
+        let key = self
+            .sort_key
+            .map(|key| key.timestamp_millis().to_string())
+            .unwrap_or_default();
+        URL_SAFE_NO_PAD.encode(format!("{}.{}", key, self.id.to_hex()))
+    }
+
+    // 排在该时间的所有记录之后的游标, 按升序翻页时等价于"晚于time"
+    pub fn after_time(time: DateTime<Utc>) -> Self {
+        Self {
+            sort_key: Some(time),
+            id: ObjectId::from_bytes([u8::MAX; 12]),
+        }
+    }
+}
+
+impl FromStr for Cursor {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::msg("无效的游标");
+        let decoded = URL_SAFE_NO_PAD
+            .decode(s)
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .ok_or_else(invalid)?;
+        let (key, id) = decoded.split_once('.').ok_or_else(invalid)?;
+        let sort_key = match key {
+            "" => None,
+            key => Some(
+                key.parse::<i64>()
+                    .ok()
+                    .and_then(|millis| Utc.timestamp_millis_opt(millis).single())
+                    .ok_or_else(invalid)?,
+            ),
+        };
+        Ok(Self {
+            sort_key,
+            id: ObjectId::parse_str(id).map_err(|_| invalid())?,
+        })
+    }
+}
+
+// 列表查询的分页方式. 游标分页从after之后取limit条, after为空时从第一条开始
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Page {
+    Offset(Pagination),
+    Cursor { after: Option<Cursor>, limit: i64 },
+}
+
+impl Page {
+    pub fn limit(&self) -> i64 {
+        match self {
+            Page::Offset(pagination) => pagination.limit,
+            Page::Cursor { limit, .. } => *limit,
+        }
+    }
+
+    // 本页已满时用最后一条记录生成下一页的游标, 偏移分页的结果也可以据此切换到游标分页
+    pub fn next_cursor<T>(&self, items: &[T], cursor_of: impl Fn(&T) -> Cursor) -> Option<Cursor> {
+        match self.limit() {
+            limit if limit > 0 && items.len() as i64 >= limit => items.last().map(cursor_of),
+            _ => None,
+        }
+    }
+}
+
+impl From<Pagination> for Page {
+    fn from(pagination: Pagination) -> Self {
+        Page::Offset(pagination)
+    }
+}
+
+// 列表接口允许选择的字段, 与响应字段一一对应, 使用snake_case
+pub const DOG_FIELDS: &[&str] = &[
+    "name",
+    "gender",
+    "breed",
+    "birthday",
+    "owner_id",
+    "tags",
+    "portrait_id",
+    "photos",
+    "medical_flags",
+    "version",
+    "updated_at",
+];
+
+pub const WALK_REQUEST_FIELDS: &[&str] = &[
+    "dogs",
+    "should_start_after",
+    "should_start_before",
+    "should_end_after",
+    "should_end_before",
+    "latitude",
+    "longitude",
+    "distance",
+    "canceled_at",
+    "accepted_by",
+    "accepted_at",
+    "started_at",
+    "finished_at",
+    "status",
+    "acceptances",
+    "priority_until",
+    "notify_walker_nearby",
+    "route_preference",
+    "route_deviation_score",
+    "version",
+    "created_by",
+    "created_at",
+    "updated_at",
+    "unread_count", // 由聊天已读标记计算, 只在我的请求列表中返回
+];
+
+// 列表查询选择返回的字段, id总是返回. 存储层据此裁剪投影, 未选择的字段在实体中可能为默认值
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldSet(Vec<String>);
+
+impl FieldSet {
+    // 逗号分隔, 不在白名单中的字段返回错误
+    pub fn parse(raw: &str, allowed: &[&str]) -> Result<Self, Error> {
+        let mut fields = Vec::new();
+        for field in raw.split(',').map(str::trim).filter(|f| !f.is_empty()) {
+            if !allowed.contains(&field) {
+                return Err(Error::msg(&format!("不支持的字段: {field}")));
+            }
+            if !fields.iter().any(|f| f == field) {
+                fields.push(field.to_owned());
+            }
+        }
+        if fields.is_empty() {
+            return Err(Error::msg("字段列表不能为空"));
+        }
+        Ok(Self(fields))
+    }
+
+    pub fn contains(&self, field: &str) -> bool {
+        field == "id" || self.0.iter().any(|f| f == field)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(String::as_str)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BreedCreate {
+    pub category: Category,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BreedUpdate {
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct BreedQuery {
+    pub id: Option<String>,
+    pub category: Option<Category>,
+    pub name: Option<String>,
+    pub sort_by: Option<SortBy>, // 为空时按存储顺序返回
+}
+
+// 按品种和语言覆盖已有的建议
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BreedCareTipsUpsert {
+    pub breed_id: String,
+    pub locale: String,
+    pub tips: Vec<String>,
+    pub updated_by: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DogCreate {
+    pub owner_id: String,
+    pub name: String,
+    pub gender: String,
+    pub breed: BreedQuery,       // 品种
+    pub birthday: DateTime<Utc>, // 生日
+    // pub is_sterilized: bool,     // 是否绝育
+    // pub introduction: String,
+    pub tags: Vec<String>,
+    pub portrait_id: Option<String>,
+    pub medical_flags: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DogUpdate {
+    pub name: Option<String>,
+    pub gender: Option<String>,
+    pub breed: Option<BreedQuery>,   // 品种
+    pub birthday: Option<String>,    // 生日
+    pub is_sterilized: Option<bool>, // 是否绝育
+    #[serde(default, skip_serializing_if = "UpdateField::is_keep")]
+    pub introduction: UpdateField<String>,
+    pub owner_id: Option<String>,
+    pub tags: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "UpdateField::is_keep")]
+    pub portrait_id: UpdateField<String>,
+    pub photos: Option<Vec<String>>, // 整体替换相册, 用于调整顺序
+    pub add_to_photos: Option<String>,
+    pub remove_from_photos: Option<String>,
+    pub medical_flags: Option<Vec<String>>,
+    pub edited_at: Option<DateTime<Utc>>, // 客户端编辑时间, 服务端在此之后有更新则不覆盖
+    pub expected_version: Option<i64>,    // 客户端读取时的版本, 不一致时不更新
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DogQuery {
+    pub id: Option<String>,
+    pub id_in: Option<Vec<String>>,
+    pub owner_id: Option<String>,
+    pub pagination: Option<Page>, // 游标分页时按_id升序
+    pub include_deleted: bool,    // 默认不包含已软删除的狗狗
+    pub fields: Option<FieldSet>, // 为空时返回全部字段
+    pub sort_by: Option<SortBy>,  // 指定时以_id作为次序, 不支持游标分页
+    pub breed_id: Option<String>,
+    pub tags_include_any: Option<Vec<String>>, // 包含其中任一标签
+    pub gender: Option<Gender>,
+    pub birthday_before: Option<DateTime<Utc>>,
+    pub birthday_after: Option<DateTime<Utc>>,
+}
+
+pub trait Repository {
+    // 在同一个事务中执行f, f收到绑定该事务的仓储, 返回错误时回滚
+    async fn with_transaction<T, F, Fut>(&self, f: F) -> Result<T, Error>
+    where
+        Self: Sized,
+        F: FnOnce(Self) -> Fut,
+        Fut: Future<Output = Result<T, Error>>;
+    async fn create_breed(&self, breed: &BreedCreate) -> Result<String, Error>;
+    async fn delete_breed(&self, id: &str) -> Result<bool, Error>;
+    async fn query_breeds(&self, query: &BreedQuery) -> Result<(Vec<Breed>, i64), Error>;
+    async fn query_breed_care_tips(
+        &self,
+        breed_ids: &[String],
+        locale: &str,
+    ) -> Result<Vec<BreedCareTips>, Error>;
+    async fn upsert_breed_care_tips(&self, upsert: BreedCareTipsUpsert) -> Result<(), Error>;
+    async fn delete_breed_care_tips(&self, breed_id: &str, locale: &str) -> Result<bool, Error>;
+    async fn create_dog(&self, dog: &DogCreate) -> Result<Dog, Error>;
+    // 软删除, 写入deleted_at, 由purge_deleted物理删除
+    async fn delete_dog(&self, id: &str) -> Result<bool, Error>;
+    async fn update_dog(&self, id: &str, dog: &DogUpdate) -> Result<bool, Error>;
+    async fn query_dogs(&self, query: &DogQuery) -> Result<Vec<Dog>, Error>;
+    async fn exists_dog(&self, query: &DogQuery) -> Result<bool, Error>;
+    async fn create_walk_request(&self, request: WalkRequestCreate) -> Result<String, Error>;
+    async fn update_walk_request(
+        &self,
+        id: &str,
+        request: WalkRequestUpdate,
+    ) -> Result<WalkRequest, Error>;
+    async fn update_walk_request_by_query(
+        &self,
+        query: WalkRequestQuery,
+        update: WalkRequestUpdate,
+    ) -> Result<WalkRequest, Error>;
+    async fn update_walk_requests_by_query(
+        &self,
+        query: WalkRequestQuery,
+        update: WalkRequestUpdate,
+    ) -> Result<u64, Error>;
+    async fn get_walk_request(&self, id: &str) -> Result<WalkRequest, Error>;
+    // 返回当前页和满足条件的总数. 游标分页时以_id作为排序键相同时的次序
+    async fn query_walk_requests(
+        &self,
+        query: WalkRequestQuery,
+        sort_by: Option<SortBy>,
+        pagination: Option<Page>,
+    ) -> Result<(Vec<WalkRequest>, u64), Error>;
+    async fn create_walking_location(&self, create: WalkingLocationCreate)
+        -> Result<String, Error>;
+    // 按记录的先后顺序返回
+    async fn query_walking_locations(
+        &self,
+        walk_request_id: &str,
+    ) -> Result<Vec<WalkingLocation>, Error>;
+    async fn create_poi(&self, create: PoiCreate) -> Result<String, Error>;
+    async fn query_pois(&self, query: PoiQuery) -> Result<Vec<Poi>, Error>;
+    async fn delete_poi(&self, id: &str) -> Result<bool, Error>;
+    async fn get_walker(&self, user_id: &str) -> Result<Option<Walker>, Error>;
+    async fn submit_walker_verification(
+        &self,
+        submit: WalkerVerificationSubmit,
+    ) -> Result<Walker, Error>;
+    async fn update_walkers_by_query(
+        &self,
+        query: WalkerQuery,
+        update: WalkerUpdate,
+    ) -> Result<u64, Error>;
+    async fn query_walkers(
+        &self,
+        query: WalkerQuery,
+        pagination: Option<Pagination>,
+    ) -> Result<(Vec<Walker>, i64), Error>;
+    async fn explain(&self, template: QueryTemplate) -> Result<QueryPlan, Error>;
+    async fn update_walker_location(
+        &self,
+        user_id: &str,
+        longitude: f64,
+        latitude: f64,
+    ) -> Result<(), Error>;
+    async fn search_walkers(&self, search: WalkerSearch) -> Result<Vec<RankedWalker>, Error>;
+    async fn walker_stats(&self, walker_id: &str) -> Result<WalkerStats, Error>;
+    // 统计since之后创建的请求, 评价不限时间
+    async fn owner_reputation_stats(
+        &self,
+        owner_id: &str,
+        since: DateTime<Utc>,
+    ) -> Result<OwnerReputationStats, Error>;
+    async fn achievement_progress(&self) -> Result<Vec<AchievementProgress>, Error>;
+    async fn grant_achievement(&self, user_id: &str, kind: AchievementKind) -> Result<bool, Error>;
+    async fn query_achievements(&self, user_id: &str) -> Result<Vec<Achievement>, Error>;
+    async fn create_review(&self, create: ReviewCreate) -> Result<String, Error>;
+    async fn query_reviews(
+        &self,
+        query: ReviewQuery,
+        pagination: Option<Pagination>,
+    ) -> Result<(Vec<Review>, i64), Error>;
+    // 每个用户对同一评价只计一票, 返回是否为新投票
+    async fn create_review_vote(&self, review_id: &str, user_id: &str) -> Result<bool, Error>;
+    // 公开该次遛狗的所有隐藏评价, 返回公开的数量
+    async fn reveal_reviews(&self, walk_request_id: &str) -> Result<u64, Error>;
+    // 公开创建时间早于created_before的隐藏评价, 返回公开的数量
+    async fn reveal_expired_reviews(&self, created_before: DateTime<Utc>) -> Result<u64, Error>;
+    async fn count_dogs(&self, query: &DogQuery) -> Result<i64, Error>;
+    async fn get_owner(&self, user_id: &str) -> Result<Option<Owner>, Error>;
+    async fn upsert_owner(&self, user_id: &str, update: OwnerUpdate) -> Result<Owner, Error>;
+    async fn create_block(&self, owner_id: &str, walker_id: &str) -> Result<Block, Error>;
+    async fn delete_block(&self, owner_id: &str, walker_id: &str) -> Result<bool, Error>;
+    async fn query_blocks(&self, query: BlockQuery) -> Result<Vec<Block>, Error>;
+    async fn create_favorite(&self, owner_id: &str, walker_id: &str) -> Result<Favorite, Error>;
+    async fn delete_favorite(&self, owner_id: &str, walker_id: &str) -> Result<bool, Error>;
+    async fn query_favorites(&self, query: FavoriteQuery) -> Result<Vec<Favorite>, Error>;
+    async fn create_report(&self, create: ReportCreate) -> Result<String, Error>;
+    async fn query_reports(
+        &self,
+        query: ReportQuery,
+        pagination: Option<Pagination>,
+    ) -> Result<(Vec<Report>, i64), Error>;
+    async fn update_reports_by_query(
+        &self,
+        query: ReportQuery,
+        update: ReportUpdate,
+    ) -> Result<u64, Error>;
+    async fn create_refresh_token(&self, create: RefreshTokenCreate) -> Result<String, Error>;
+    async fn consume_refresh_token(&self, token_hash: &str) -> Result<Option<RefreshToken>, Error>;
+    async fn get_refresh_token(&self, token_hash: &str) -> Result<Option<RefreshToken>, Error>;
+    async fn revoke_refresh_token_family(&self, family_id: &str) -> Result<u64, Error>;
+    // 吊销单个访问令牌, 记录保留到令牌过期
+    async fn revoke_access_token(&self, create: RevokedAccessTokenCreate) -> Result<(), Error>;
+    async fn is_access_token_revoked(&self, token_id: &str) -> Result<bool, Error>;
+    async fn count_user_references(&self, user_id: &str) -> Result<Vec<MergedReference>, Error>;
+    async fn merge_accounts(&self, merge: AccountMergeCreate) -> Result<AccountMerge, Error>;
+    async fn delete_account(
+        &self,
+        deletion: AccountDeletionCreate,
+    ) -> Result<AccountDeletion, Error>;
+    async fn create_otp(&self, create: OtpCreate) -> Result<String, Error>;
+    async fn count_otps(&self, phone: &str, since: DateTime<Utc>) -> Result<i64, Error>;
+    // 验证码不匹配时该手机号同一用途的有效验证码都记一次失败, 失败max_attempts次后作废
+    async fn consume_otp(
+        &self,
+        phone: &str,
+        purpose: OtpPurpose,
+        code_hash: &str,
+        max_attempts: i64,
+    ) -> Result<Option<Otp>, Error>;
+    async fn create_password_reset_token(
+        &self,
+        create: PasswordResetTokenCreate,
+    ) -> Result<String, Error>;
+    async fn consume_password_reset_token(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<PasswordResetToken>, Error>;
+    async fn create_ticket(&self, create: TicketCreate) -> Result<String, Error>;
+    async fn query_tickets(
+        &self,
+        query: TicketQuery,
+        pagination: Option<Pagination>,
+    ) -> Result<(Vec<Ticket>, i64), Error>;
+    async fn update_tickets_by_query(
+        &self,
+        query: TicketQuery,
+        update: TicketUpdate,
+    ) -> Result<u64, Error>;
+    async fn add_ticket_message(
+        &self,
+        query: TicketQuery,
+        message: TicketMessageCreate,
+    ) -> Result<u64, Error>;
+    async fn create_help_article(&self, create: HelpArticleCreate) -> Result<HelpArticle, Error>;
+    async fn query_help_articles(
+        &self,
+        query: HelpArticleQuery,
+        limit: Option<i64>,
+    ) -> Result<Vec<HelpArticle>, Error>;
+    async fn latest_help_articles(&self, locale: &str) -> Result<Vec<HelpArticle>, Error>;
+    async fn get_oauth_account(
+        &self,
+        provider: OAuthProviderKind,
+        subject: &str,
+    ) -> Result<Option<OAuthAccount>, Error>;
+    async fn create_oauth_account(&self, create: OAuthAccountCreate) -> Result<String, Error>;
+    async fn create_oauth_link_token(&self, create: OAuthLinkTokenCreate) -> Result<String, Error>;
+    // 按当前手机号或注册手机号查找
+    async fn get_phone_binding(&self, phone: &str) -> Result<Option<PhoneBinding>, Error>;
+    // 再次更换时只修改当前手机号, 同时更新该用户第三方账号绑定的手机号
+    async fn bind_phone(&self, upsert: PhoneBindingUpsert) -> Result<(), Error>;
+    async fn consume_oauth_link_token(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<OAuthLinkToken>, Error>;
+    async fn upsert_payout_account(
+        &self,
+        upsert: PayoutAccountUpsert,
+    ) -> Result<PayoutAccount, Error>;
+    async fn get_payout_account(&self, user_id: &str) -> Result<Option<PayoutAccount>, Error>;
+    async fn update_payout_account_status(
+        &self,
+        user_id: &str,
+        provider_reference: &str,
+        status: PayoutAccountStatus,
+        failure_reason: Option<String>,
+    ) -> Result<Option<PayoutAccount>, Error>;
+    async fn delete_payout_account(&self, user_id: &str) -> Result<bool, Error>;
+    async fn query_sessions(&self, user_id: &str) -> Result<Vec<Session>, Error>;
+    async fn revoke_sessions(&self, query: SessionQuery) -> Result<u64, Error>;
+    async fn ledger_balance(&self, user_id: &str) -> Result<i64, Error>;
+    async fn query_ledger_entries(
+        &self,
+        user_id: &str,
+        pagination: Option<Pagination>,
+    ) -> Result<(Vec<LedgerEntry>, i64), Error>;
+    // 为遛狗结束的请求记入遛狗人收入, 同一请求只记一次
+    async fn credit_walk_earning(
+        &self,
+        user_id: &str,
+        walk_request_id: &str,
+        amount: i64,
+    ) -> Result<(), Error>;
+    // 余额不足时返回None
+    async fn create_withdrawal(&self, create: WithdrawalCreate) -> Result<Option<String>, Error>;
+    async fn query_withdrawals(
+        &self,
+        query: WithdrawalQuery,
+        pagination: Option<Pagination>,
+    ) -> Result<(Vec<Withdrawal>, i64), Error>;
+    async fn update_withdrawals_by_query(
+        &self,
+        query: WithdrawalQuery,
+        update: WithdrawalUpdate,
+    ) -> Result<u64, Error>;
+    // 领取一笔已审核通过的提现并标记为打款中, 没有待打款的提现时返回None
+    async fn claim_approved_withdrawal(&self) -> Result<Option<Withdrawal>, Error>;
+    async fn create_payment_attempt(&self, create: PaymentAttemptCreate) -> Result<String, Error>;
+    async fn payment_attempt_stats(
+        &self,
+        user_id: &str,
+        since: DateTime<Utc>,
+    ) -> Result<PaymentAttemptStats, Error>;
+    async fn create_upload_variant(&self, create: UploadVariantCreate) -> Result<(), Error>;
+    async fn get_upload_variant(
+        &self,
+        upload_id: &str,
+        size: ImageSize,
+    ) -> Result<Option<UploadVariant>, Error>;
+    // 是否为遛狗人提交的身份证件或其缩略图
+    async fn is_verification_document(&self, upload_id: &str) -> Result<bool, Error>;
+    // 写入罚金明细和对应的账本流水
+    async fn create_cancellation_penalty(
+        &self,
+        create: CancellationPenaltyCreate,
+    ) -> Result<CancellationPenalty, Error>;
+    async fn query_cancellation_penalties(
+        &self,
+        walk_request_id: &str,
+    ) -> Result<Vec<CancellationPenalty>, Error>;
+    // 同一遛狗请求已有待审核的申请时返回walk_request_id重复的Conflict错误
+    async fn create_refund_request(&self, create: RefundRequestCreate) -> Result<String, Error>;
+    // 按创建时间倒序
+    async fn query_refund_requests(
+        &self,
+        query: RefundRequestQuery,
+        pagination: Option<Pagination>,
+    ) -> Result<(Vec<RefundRequest>, i64), Error>;
+    // 只处理待审核的申请, 已处理过时返回None. 批准时在同一事务内核对累计退款不超过refund_limit,
+    // 并写入扣回遛狗人收入的账本流水
+    async fn review_refund_request(
+        &self,
+        review: RefundReview,
+    ) -> Result<Option<RefundRequest>, Error>;
+    async fn create_meet_and_greet(&self, create: MeetAndGreetCreate) -> Result<String, Error>;
+    // 按开始时间升序
+    async fn query_meet_and_greets(
+        &self,
+        query: MeetAndGreetQuery,
+    ) -> Result<Vec<MeetAndGreet>, Error>;
+    // 返回更新后的见面, 没有符合条件的见面时返回None
+    async fn update_meet_and_greet_by_query(
+        &self,
+        query: MeetAndGreetQuery,
+        update: MeetAndGreetUpdate,
+    ) -> Result<Option<MeetAndGreet>, Error>;
+    async fn create_chat_message(&self, create: ChatMessageCreate) -> Result<String, Error>;
+    async fn get_chat_message(&self, id: &str) -> Result<ChatMessage, Error>;
+    // 按发送时间倒序, 游标分页时从游标之前的消息开始取
+    async fn query_chat_messages(
+        &self,
+        walk_request_id: &str,
+        page: Page,
+    ) -> Result<(Vec<ChatMessage>, u64), Error>;
+    async fn query_chat_read_markers(
+        &self,
+        walk_request_id: &str,
+    ) -> Result<Vec<ChatReadMarker>, Error>;
+    async fn upsert_chat_read_marker(&self, upsert: ChatReadMarkerUpsert) -> Result<(), Error>;
+    // 各遛狗请求中他人发送且晚于该用户已读标记的消息数, 没有未读消息的请求不在结果中
+    async fn count_unread_chat_messages(
+        &self,
+        user_id: &str,
+        walk_request_ids: &[String],
+    ) -> Result<HashMap<String, u64>, Error>;
+
+    async fn operations_snapshot(&self, since: DateTime<Utc>) -> Result<OperationsSnapshot, Error>;
+
+    async fn create_direct_upload(&self, create: DirectUploadCreate) -> Result<String, Error>;
+    async fn get_direct_upload(&self, id: &str) -> Result<Option<DirectUpload>, Error>;
+    async fn confirm_direct_upload(&self, id: &str, size: i64) -> Result<bool, Error>;
+
+    async fn create_location_access(&self, create: LocationAccessCreate) -> Result<String, Error>;
+    async fn query_location_accesses(
+        &self,
+        query: LocationAccessQuery,
+        pagination: Pagination,
+    ) -> Result<(Vec<LocationAccess>, i64), Error>;
+
+    async fn count_upload_references(&self, upload_id: &str) -> Result<i64, Error>;
+    async fn query_unreferenced_uploads(
+        &self,
+        created_before: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<String>, Error>;
+    async fn delete_uploads(&self, upload_ids: &[String]) -> Result<u64, Error>;
+    // 按类型查找悬空引用, 最多返回limit条
+    async fn query_integrity_issues(
+        &self,
+        kind: IntegrityIssueKind,
+        limit: i64,
+    ) -> Result<Vec<IntegrityIssue>, Error>;
+    // 修复狗狗品种和上传的悬空引用, 不能在存储层修复的问题返回false
+    async fn repair_integrity_issue(&self, issue: &IntegrityIssue) -> Result<bool, Error>;
+    async fn reencrypt_fields(&self, limit: i64) -> Result<u64, Error>;
+    async fn get_token_version(&self, user_id: &str) -> Result<i64, Error>;
+    async fn increment_token_version(&self, user_id: &str) -> Result<i64, Error>;
+    async fn mark_walker_nearby_notified(&self, walk_request_id: &str) -> Result<bool, Error>;
+    async fn create_notification(&self, create: NotificationCreate) -> Result<String, Error>;
+    async fn query_notifications(
+        &self,
+        user_id: &str,
+        pagination: Pagination,
+    ) -> Result<Vec<Notification>, Error>;
+    // 领取一条尚未推送的通知并标记为已推送, 多个实例同时推送时每条通知只推送一次
+    async fn claim_unpushed_notification(
+        &self,
+        created_after: DateTime<Utc>,
+    ) -> Result<Option<Notification>, Error>;
+    // 按令牌登记, 令牌已由其他用户登记时改归当前用户
+    async fn upsert_device_token(&self, upsert: DeviceTokenUpsert) -> Result<(), Error>;
+    async fn query_device_tokens(&self, user_id: &str) -> Result<Vec<DeviceToken>, Error>;
+    async fn delete_device_tokens(&self, query: DeviceTokenQuery) -> Result<u64, Error>;
+    async fn get_notification_settings(
+        &self,
+        user_id: &str,
+    ) -> Result<Option<NotificationSettings>, Error>;
+    async fn upsert_notification_settings(
+        &self,
+        upsert: NotificationSettingsUpsert,
+    ) -> Result<NotificationSettings, Error>;
+    // 领取一个开启了周报且本周尚未发送的用户, 并记录发送时间
+    async fn claim_weekly_summary_recipient(
+        &self,
+        week_start: DateTime<Utc>,
+    ) -> Result<Option<NotificationSettings>, Error>;
+    async fn create_email(&self, create: EmailCreate) -> Result<String, Error>;
+    // 领取一封尚未发送的邮件并标记为已发送, 多个实例同时发送时每封邮件只发送一次
+    async fn claim_unsent_email(
+        &self,
+        created_after: DateTime<Utc>,
+    ) -> Result<Option<Email>, Error>;
+    // 按原文id批量查询某一语言的译文
+    async fn query_translations(
+        &self,
+        source_ids: &[String],
+        language: Language,
+    ) -> Result<Vec<Translation>, Error>;
+    async fn upsert_translation(&self, upsert: TranslationUpsert) -> Result<(), Error>;
+    async fn create_action_token(&self, create: ActionTokenCreate) -> Result<String, Error>;
+    async fn consume_action_token(
+        &self,
+        user_id: &str,
+        action: SensitiveAction,
+        token_hash: &str,
+    ) -> Result<Option<ActionToken>, Error>;
+    async fn create_audit_log(&self, create: AuditLogCreate) -> Result<String, Error>;
+    async fn query_audit_logs(
+        &self,
+        query: AuditLogQuery,
+        pagination: Pagination,
+    ) -> Result<(Vec<AuditLog>, i64), Error>;
+    // 物理删除deleted_before之前软删除的狗狗和遛狗请求, 以及这些请求的轨迹点
+    async fn purge_deleted(&self, deleted_before: DateTime<Utc>) -> Result<PurgedCounts, Error>;
+    async fn create_partner(&self, create: PartnerCreate) -> Result<String, Error>;
+    async fn get_partner(&self, id: &str) -> Result<Option<Partner>, Error>;
+    async fn query_partners(&self, pagination: Pagination) -> Result<(Vec<Partner>, i64), Error>;
+    async fn update_partner(
+        &self,
+        id: &str,
+        update: PartnerUpdate,
+    ) -> Result<Option<Partner>, Error>;
+    async fn create_partner_api_key(&self, create: PartnerApiKeyCreate) -> Result<String, Error>;
+    // 按密钥哈希查找未吊销的密钥
+    async fn get_partner_api_key(&self, key_hash: &str) -> Result<Option<PartnerApiKey>, Error>;
+    async fn query_partner_api_keys(&self, partner_id: &str) -> Result<Vec<PartnerApiKey>, Error>;
+    async fn revoke_partner_api_key(&self, partner_id: &str, id: &str) -> Result<bool, Error>;
+    async fn create_partner_consent(
+        &self,
+        partner_id: &str,
+        owner_id: &str,
+    ) -> Result<PartnerConsent, Error>;
+    async fn delete_partner_consent(&self, partner_id: &str, owner_id: &str)
+        -> Result<bool, Error>;
+    async fn query_partner_consents(
+        &self,
+        query: PartnerConsentQuery,
+    ) -> Result<Vec<PartnerConsent>, Error>;
+    async fn create_partner_usage(&self, partner_id: &str, endpoint: &str) -> Result<(), Error>;
+    async fn count_partner_usages(
+        &self,
+        partner_id: &str,
+        since: DateTime<Utc>,
+    ) -> Result<i64, Error>;
+    // 按UTC日期和接口汇总[from, to)之间的调用次数
+    async fn partner_usage_report(
+        &self,
+        partner_id: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<PartnerUsage>, Error>;
+    // 区域内已认证且在时间窗口内没有未完成订单的遛狗人
+    async fn available_walker_ids(&self, query: AvailableWalkerQuery)
+        -> Result<Vec<String>, Error>;
+    // 遛狗人自since以来接单的响应时间(秒), 即接单时间减去请求创建时间
+    async fn walker_response_times(
+        &self,
+        walker_ids: Vec<String>,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<i64>, Error>;
+    // 按cell_size度的经纬度网格汇总区域内未完成的请求数和priced_since以来完成的遛狗价格
+    async fn neighborhood_stats(
+        &self,
+        query: NeighborhoodStatsQuery,
+    ) -> Result<Vec<NeighborhoodStats>, Error>;
+    async fn create_invite(&self, create: InviteCreate) -> Result<String, Error>;
+    async fn count_invites(&self, query: InviteQuery) -> Result<i64, Error>;
+    async fn query_invites(
+        &self,
+        inviter_id: &str,
+        pagination: Pagination,
+    ) -> Result<(Vec<Invite>, i64), Error>;
+    // 第一次打开时记录打开时间, 邀请码不存在时返回None
+    async fn open_invite(&self, code_hash: &str) -> Result<Option<Invite>, Error>;
+    // 把发往该手机号且尚未转化的邀请标记为由user_id转化, 返回标记的数量
+    async fn convert_invites(&self, phone: &str, user_id: &str) -> Result<u64, Error>;
+    // 汇总[from, to)之间发出的邀请
+    async fn invite_conversion(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<InviteConversion>, Error>;
+    async fn create_short_link(&self, create: ShortLinkCreate) -> Result<String, Error>;
+    // 已过期的短链接视为不存在
+    async fn get_short_link(&self, code: &str) -> Result<Option<ShortLink>, Error>;
+    // 检查存储是否可用, 供健康检查接口使用
+    async fn self_check(&self) -> Result<(), Error>;
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WalkRequestCreate {
+    pub dogs: Vec<Dog>,
+    pub should_start_after: Option<DateTime<Utc>>,
+    pub should_start_before: Option<DateTime<Utc>>,
+    pub should_end_before: Option<DateTime<Utc>>,
+    pub should_end_after: Option<DateTime<Utc>>,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub created_by: String,
+    pub notify_favorites: bool, // 优先推送给收藏的遛狗人
+    pub priority_walkers: Vec<String>,
+    pub priority_until: Option<DateTime<Utc>>,
+    pub notify_walker_nearby: bool,
+    pub route_preference: Option<RoutePreference>,
+    pub partner_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct WalkRequestUpdate {
+    pub dogs: Option<Vec<Dog>>,
+    pub should_start_after: Option<DateTime<Utc>>,
+    pub should_start_before: Option<DateTime<Utc>>,
+    pub should_end_before: Option<DateTime<Utc>>,
+    pub should_end_after: Option<DateTime<Utc>>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    #[serde(default, skip_serializing_if = "UpdateField::is_keep")]
+    pub accepted_by: UpdateField<String>,
+    #[serde(default, skip_serializing_if = "UpdateField::is_keep")]
+    pub accepted_at: UpdateField<DateTime<Utc>>,
+    pub canceled_at: Option<DateTime<Utc>>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub add_to_acceptances: Option<String>,
+    pub remove_from_acceptances: Option<String>,
+    #[serde(default, skip_serializing_if = "UpdateField::is_keep")]
+    pub route_preference: UpdateField<RoutePreference>,
+    pub route_assessment: Option<RouteAssessment>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub actor_id: Option<String>, // 操作人, 只用于审计记录, 不写入请求
+    pub expected_version: Option<i64>, // 客户端读取时的版本, 不一致时返回冲突错误
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct WalkRequestQuery {
+    pub id: Option<String>,
+    pub dog_ids_includes_all: Option<Vec<String>>,
+    pub dog_ids_includes_any: Option<Vec<String>>,
+    pub nearby: Option<Vec<f64>>,
+    pub accepted_by: Option<String>,
+    pub accepted_by_neq: Option<String>,
+    pub accepted_by_is_null: Option<bool>,
+    pub acceptances_includes_all: Option<Vec<String>>,
+    pub acceptances_includes_any: Option<Vec<String>>,
+    pub created_by: Option<String>,
+    pub created_by_nin: Option<Vec<String>>,
+    pub started_at_is_null: Option<bool>,
+    pub finished_at_is_null: Option<bool>,
+    pub canceled_at_is_null: Option<bool>,
+    pub open_to: Option<String>,  // 优先推送期内仅对收藏的遛狗人开放
+    pub involves: Option<String>, // 创建、接单或报名的用户
+    pub updated_after: Option<DateTime<Utc>>,
+    pub include_deleted: bool,    // 默认不包含已软删除的请求
+    pub fields: Option<FieldSet>, // 为空时返回全部字段
+}
+
+#[derive(Debug)]
+pub struct PoiCreate {
+    pub name: String,
+    pub longitude: f64,
+    pub latitude: f64,
+    pub radius: f64,
+    pub boundary: Vec<GeoPoint>,
+}
+
+// 按中心点与near的距离筛选兴趣点
+#[derive(Debug, Default)]
+pub struct PoiQuery {
+    pub near: Option<GeoPoint>,
+    pub max_distance: Option<f64>, // 米
+}
+
+pub struct WalkingLocationCreate<'a> {
+    pub walk_request_id: &'a str,
+    pub longitude: f64,
+    pub latitude: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Order {
+    Asc,
+    Desc,
+}
+
+impl FromStr for Order {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "asc" => Ok(Order::Asc),
+            "desc" => Ok(Order::Desc),
+            _ => Err(Error::msg("排序方向只能是asc或desc")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SortBy {
+    pub field: String,
+    pub order: Order,
+}
+
+impl SortBy {
+    // 排序字段必须是sortable中的实体字段名, 未指定方向时升序
+    pub fn parse(field: &str, order: Option<&str>, sortable: &[String]) -> Result<Self, Error> {
+        if !sortable.iter().any(|f| f == field) {
+            return Err(Error::msg(&format!("不支持按{field}排序")));
+        }
+        Ok(Self {
+            field: field.to_owned(),
+            order: order.map(str::parse).transpose()?.unwrap_or(Order::Asc),
+        })
+    }
+}
+
+// 可清空字段的更新方式, JSON中缺省为Keep, null为Unset
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum UpdateField<T> {
+    #[default]
+    Keep,
+    Set(T),
+    Unset,
+}
+
+impl<T> UpdateField<T> {
+    pub fn is_keep(&self) -> bool {
+        matches!(self, UpdateField::Keep)
+    }
+
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> UpdateField<U> {
+        match self {
+            UpdateField::Keep => UpdateField::Keep,
+            UpdateField::Set(value) => UpdateField::Set(f(value)),
+            UpdateField::Unset => UpdateField::Unset,
+        }
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for UpdateField<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match Option::<T>::deserialize(deserializer)? {
+            Some(value) => UpdateField::Set(value),
+            None => UpdateField::Unset,
+        })
+    }
+}
+
+impl<T: Serialize> Serialize for UpdateField<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            UpdateField::Set(value) => value.serialize(serializer),
+            UpdateField::Keep | UpdateField::Unset => serializer.serialize_none(),
+        }
+    }
+}
+
+pub struct WalkerVerificationSubmit<'a> {
+    pub user_id: &'a str,
+    pub id_document_ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct WalkerQuery {
+    pub user_id: Option<String>,
+    pub verification_status: Option<VerificationStatus>,
+}
+
+#[derive(Debug, Default)]
+pub struct WalkerUpdate {
+    pub verification_status: Option<VerificationStatus>,
+    pub rejected_reason: Option<String>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub unset_rejected_reason: bool,
+}
+
+// 用于分析执行计划的具名查询模板
+#[derive(Debug, Serialize, Deserialize)]
+pub enum QueryTemplate {
+    NearbyWalkRequests {
+        longitude: f64,
+        latitude: f64,
+        radius: f64,
+    },
+    MyWalkRequests {
+        user_id: String,
+    },
+    DogsByOwner {
+        owner_id: String,
+    },
+    WalkerVerifications {
+        verification_status: VerificationStatus,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct QueryPlan {
+    pub collection: String,
+    pub stages: Vec<String>, // 获胜执行计划的各阶段, 由外到内
+    pub indexes: Vec<String>,
+    pub returned: i64,
+    pub keys_examined: i64,
+    pub docs_examined: i64,
+    pub execution_time_millis: i64,
+}
+
+// 遛狗人搜索综合评分权重, 各存储实现按相同公式计算score
+pub const RATING_WEIGHT: f64 = 0.5;
+pub const COMPLETED_WALKS_WEIGHT: f64 = 0.2;
+pub const DISTANCE_WEIGHT: f64 = 0.3;
+// 完成次数达到该值时完成次数得分为0.5
+pub const COMPLETED_WALKS_HALF_SCORE: i64 = 10;
+
+// 引用用户id的集合字段, 各存储实现按此迁移和统计用户引用, 第三项表示该字段为数组. 用户档案(owners/walkers)和成就不迁移, 成就由定时任务重新计算
+pub const USER_REFERENCES: &[(&str, &str, bool)] = &[
+    ("dogs", "owner_id", false),
+    ("walk_requests", "created_by", false),
+    ("walk_requests", "accepted_by", false),
+    ("walk_requests", "acceptances", true),
+    ("walk_requests", "priority_walkers", true),
+    ("reviews", "owner_id", false),
+    ("reviews", "walker_id", false),
+    ("reports", "reporter_id", false),
+    ("reports", "target_user_id", false),
+    ("blocks", "owner_id", false),
+    ("blocks", "walker_id", false),
+    ("favorites", "owner_id", false),
+    ("favorites", "walker_id", false),
+    ("partner_consents", "owner_id", false),
+    ("invites", "inviter_id", false),
+    ("short_links", "created_by", false),
+    ("refund_requests", "owner_id", false),
+    ("refund_requests", "walker_id", false),
+    ("uploads", "owner_id", false), // upload-service写入的上传文件
+];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WalkerSearch {
+    pub longitude: f64,
+    pub latitude: f64,
+    pub radius: f64,
+    pub pagination: Pagination,
+}
+
+pub struct ReviewCreate<'a> {
+    pub walk_request_id: &'a str,
+    pub walker_id: &'a str,
+    pub owner_id: &'a str,
+    pub kind: ReviewKind,
+    pub rating: i32,
+    pub content: String,
+    pub photo_ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct ReviewQuery {
+    pub id: Option<String>,
+    pub walk_request_id: Option<String>,
+    pub walker_id: Option<String>,
+    pub owner_id: Option<String>,
+    pub kind: Option<ReviewKind>,
+    pub state: Option<ReviewState>,
+    pub sort_by: Option<SortBy>, // 以创建时间倒序作为次序, 为空时只按创建时间倒序
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct OwnerUpdate {
+    pub nickname: Option<String>,
+    pub avatar_id: Option<String>,
+    pub emergency_contacts: Option<Vec<EmergencyContact>>, // 整体替换
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct BlockQuery {
+    pub owner_id: Option<String>,
+    pub walker_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct FavoriteQuery {
+    pub owner_id: Option<String>,
+    pub walker_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReportCreate {
+    pub reporter_id: String,
+    pub target_user_id: String,
+    pub walk_request_id: Option<String>,
+    pub reason: String,
+    pub description: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct ReportQuery {
+    pub id: Option<String>,
+    pub reporter_id: Option<String>,
+    pub status: Option<ReportStatus>,
+}
+
+#[derive(Debug, Default)]
+pub struct ReportUpdate {
+    pub status: Option<ReportStatus>,
+    pub resolution_note: Option<String>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RefreshTokenCreate {
+    pub family_id: String,
+    pub user_id: String,
+    pub phone: String,
+    pub token_hash: String,
+    pub device: Device,
+    pub signed_in_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct SessionQuery {
+    pub user_id: String,
+    pub id: Option<String>,
+    pub device_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RevokedAccessTokenCreate {
+    pub token_id: String, // 访问令牌的jti
+    pub user_id: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AccountMergeCreate {
+    pub from_user_id: String,
+    pub to_user_id: String,
+    pub operator_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AccountDeletionCreate {
+    pub user_id: String,
+    pub reassign_dogs_to: Option<String>, // 为空时删除名下的狗狗
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OtpCreate {
+    pub phone: String,
+    pub purpose: OtpPurpose,
+    pub code_hash: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PasswordResetTokenCreate {
+    pub phone: String,
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TicketCreate {
+    pub user_id: String,
+    pub category: TicketCategory,
+    pub subject: String,
+    pub content: String,
+    pub attachment_ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct TicketQuery {
+    pub id: Option<String>,
+    pub user_id: Option<String>,
+    pub status: Option<TicketStatus>,
+    pub assignee_id: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct TicketUpdate {
+    pub status: Option<TicketStatus>,
+    pub assignee_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TicketMessageCreate {
+    pub author_id: String,
+    pub from_staff: bool,
+    pub content: String,
+    pub attachment_ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HelpArticleCreate {
+    pub slug: String,
+    pub locale: String,
+    pub title: String,
+    pub markdown: String,
+    pub author_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct HelpArticleQuery {
+    pub slug: Option<String>,
+    pub locale: Option<String>,
+    pub version: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OAuthAccountCreate {
+    pub provider: OAuthProviderKind,
+    pub subject: String,
+    pub user_id: String,
+    pub phone: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PhoneBindingUpsert {
+    pub user_id: String,
+    pub phone: String,
+    pub auth_phone: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OAuthLinkTokenCreate {
+    pub provider: OAuthProviderKind,
+    pub subject: String,
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PayoutAccountUpsert {
+    pub user_id: String,
+    pub method: PayoutMethod,
+    pub masked_holder_name: String,
+    pub masked_account_number: String,
+    pub bank_name: Option<String>,
+    pub provider_reference: String,
+    pub status: PayoutAccountStatus,
+    pub failure_reason: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WithdrawalCreate {
+    pub user_id: String,
+    pub amount: i64,
+    pub destination_reference: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct WithdrawalQuery {
+    pub id: Option<String>,
+    pub ids: Option<Vec<String>>,
+    pub user_id: Option<String>,
+    pub status: Option<WithdrawalStatus>,
+    pub payout_reference: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct WithdrawalUpdate {
+    pub status: Option<WithdrawalStatus>,
+    pub payout_reference: Option<String>,
+    pub failure_reason: Option<String>,
+    pub reviewed_by: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PaymentAttemptCreate {
+    pub user_id: String,
+    pub amount: i64,
+    pub succeeded: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RefundRequestCreate {
+    pub walk_request_id: String,
+    pub owner_id: String,
+    pub walker_id: String,
+    pub amount: i64,
+    pub reason: String,
+    pub evidence_ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct RefundRequestQuery {
+    pub id: Option<String>,
+    pub walk_request_id: Option<String>,
+    pub owner_id: Option<String>,
+    pub status: Option<RefundStatus>,
+}
+
+// 拒绝时approved_amount和walker_debit为None
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RefundReview {
+    pub id: String,
+    pub reviewed_by: String,
+    pub status: RefundStatus,
+    pub approved_amount: Option<i64>,
+    pub walker_debit: Option<i64>,
+    pub review_note: Option<String>,
+    pub refund_limit: i64, // 批准后该遛狗请求的累计退款不能超过此金额
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UploadVariantCreate {
+    pub upload_id: String,
+    pub size: ImageSize,
+    pub variant_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CancellationPenaltyCreate {
+    pub walk_request_id: String,
+    pub walker_id: String,
+    pub owner_id: String,
+    pub tier: CancellationPenaltyTier,
+    pub scheduled_start: DateTime<Utc>,
+    pub notice_minutes: i64,
+    pub amount: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MeetAndGreetCreate {
+    pub walk_request_id: String,
+    pub owner_id: String,
+    pub walker_id: String,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub longitude: f64,
+    pub latitude: f64,
+    pub note: Option<String>,
+}
+
+// starts_before和ends_after同时指定时查询与该时间段重叠的见面
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct MeetAndGreetQuery {
+    pub id: Option<String>,
+    pub walk_request_id: Option<String>,
+    pub walker_id: Option<String>,
+    pub participants_any: Option<Vec<String>>, // 狗狗主人或遛狗人在其中
+    pub status_in: Option<Vec<MeetAndGreetStatus>>,
+    pub starts_before: Option<DateTime<Utc>>,
+    pub ends_after: Option<DateTime<Utc>>,
+}
+
+// 状态变更时记录对应的时间
+#[derive(Debug, Default)]
+pub struct MeetAndGreetUpdate {
+    pub status: Option<MeetAndGreetStatus>,
+    pub canceled_by: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChatMessageCreate {
+    pub walk_request_id: String,
+    pub sender_id: String,
+    pub text: String,
+    pub image_ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChatReadMarkerUpsert {
+    pub walk_request_id: String,
+    pub user_id: String,
+    pub message_id: String,
+    pub message_created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DirectUploadCreate {
+    pub uploader: String,
+    pub key: String,
+    pub filename: String,
+    pub content_type: String,
+    pub size: i64,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LocationAccessCreate {
+    pub accessor_id: String,
+    pub kind: LocationAccessKind,
+    pub walk_request_id: Option<String>,
+    pub subject_ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct LocationAccessQuery {
+    pub accessor_id: Option<String>,
+    pub subject_id: Option<String>,
+    pub walk_request_id: Option<String>,
+    pub kind: Option<LocationAccessKind>,
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ActionTokenCreate {
+    pub user_id: String,
+    pub action: SensitiveAction,
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NotificationCreate {
+    pub user_id: String,
+    pub kind: NotificationKind,
+    pub walk_request_id: Option<String>,
+    pub content: String,
+    pub sms: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NotificationSettingsUpsert {
+    pub user_id: String,
+    pub sms_enabled: bool,
+    pub sms_phone: Option<String>,
+    pub email: Option<String>,
+    pub email_receipts: bool,
+    pub email_weekly_summary: bool,
+    pub email_verification_decisions: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EmailCreate {
+    pub user_id: String,
+    pub kind: EmailKind,
+    pub to: String,
+    pub subject: String,
+    pub text: String,
+    pub html: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TranslationUpsert {
+    pub source_id: String,
+    pub language: Language,
+    pub source_hash: String,
+    pub text: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeviceTokenUpsert {
+    pub user_id: String,
+    pub platform: PushPlatform,
+    pub token: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DeviceTokenQuery {
+    pub user_id: Option<String>,
+    pub token: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditLogCreate {
+    pub walk_request_id: String,
+    pub actor_id: Option<String>,
+    pub action: WalkRequestAuditAction,
+    pub before: Option<WalkRequest>,
+    pub after: WalkRequest,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct AuditLogQuery {
+    pub walk_request_id: Option<String>,
+    pub actor_id: Option<String>,
+    pub action: Option<WalkRequestAuditAction>,
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PartnerCreate {
+    pub name: String,
+    pub kind: PartnerKind,
+    pub scopes: Vec<PartnerScope>,
+    pub rate_limit: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct PartnerUpdate {
+    pub name: Option<String>,
+    pub scopes: Option<Vec<PartnerScope>>,
+    pub rate_limit: Option<i64>,
+    pub disabled: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PartnerApiKeyCreate {
+    pub partner_id: String,
+    pub key_hash: String,
+    pub prefix: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct PartnerConsentQuery {
+    pub partner_id: Option<String>,
+    pub owner_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AvailableWalkerQuery {
+    pub longitude: f64,
+    pub latitude: f64,
+    pub radius: f64,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NeighborhoodStatsQuery {
+    pub longitude: f64,
+    pub latitude: f64,
+    pub radius: f64,
+    pub cell_size: f64,
+    pub priced_since: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InviteCreate {
+    pub inviter_id: String,
+    pub phone: String,
+    pub kind: InviteKind,
+    pub locale: String,
+    pub code_hash: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct InviteQuery {
+    pub inviter_id: Option<String>,
+    pub phone: Option<String>,
+    pub created_after: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ShortLinkCreate {
+    pub code: String,
+    pub kind: ShortLinkKind,
+    pub token: String,
+    pub created_by: String,
+    pub expires_at: DateTime<Utc>,
+}
@@ -0,0 +1,347 @@
+use chrono::{Duration, Utc};
+
+use crate::entities::{
+    GeoPoint, Poi, PoiVisit, RouteAssessment, RouteDeviation, RouteDeviationKind, RoutePreference,
+    WalkingLocation,
+};
+use crate::geo::{distance_to_polyline, haversine_distance, point_in_polygon};
+
+// 轨迹点距期望路线超过此值(米)视为偏离路线
+pub const ROUTE_DEVIATION_TOLERANCE: f64 = 50.0;
+
+// 在兴趣点内停留不足此时长视为路过, 不计为到访
+pub const MIN_POI_VISIT_SECONDS: i64 = 60;
+
+// 单个轨迹点的偏离: 类型、所在禁行区域的下标和偏离距离(米)
+type PointDeviation = (RouteDeviationKind, Option<usize>, f64);
+
+// 禁行区域优先于偏离路线, 同时位于多个禁行区域时取深入最多的一个
+fn point_deviation(
+    point: &GeoPoint,
+    polyline: &[(f64, f64)],
+    route_preference: &RoutePreference,
+) -> Option<PointDeviation> {
+    let zone = route_preference
+        .no_go_zones
+        .iter()
+        .enumerate()
+        .map(|(i, z)| {
+            let depth = z.radius
+                - haversine_distance(point.longitude, point.latitude, z.longitude, z.latitude);
+            (i, depth)
+        })
+        .filter(|(_, depth)| *depth >= 0.0)
+        .max_by(|a, b| a.1.total_cmp(&b.1));
+    if let Some((i, depth)) = zone {
+        return Some((RouteDeviationKind::NoGoZone, Some(i), depth));
+    }
+    distance_to_polyline(point.longitude, point.latitude, polyline)
+        .filter(|d| *d > ROUTE_DEVIATION_TOLERANCE)
+        .map(|d| (RouteDeviationKind::OffRoute, None, d))
+}
+
+// 按顺序扫描轨迹点, 连续进入同一禁行区域或连续偏离期望路线的点合并为一段偏离
+pub fn route_deviations(
+    route: &[GeoPoint],
+    route_preference: &RoutePreference,
+) -> Vec<RouteDeviation> {
+    let polyline = preferred_polyline(route_preference);
+    let mut deviations: Vec<RouteDeviation> = Vec::new();
+    let mut previous = None;
+    for point in route {
+        let Some((kind, zone, distance)) = point_deviation(point, &polyline, route_preference)
+        else {
+            previous = None;
+            continue;
+        };
+        match deviations.last_mut() {
+            Some(deviation) if previous == Some((kind, zone)) => {
+                deviation.points += 1;
+                deviation.max_distance = deviation.max_distance.max(distance);
+            }
+            _ => deviations.push(RouteDeviation {
+                kind,
+                zone: zone.and_then(|i| route_preference.no_go_zones[i].label.clone()),
+                longitude: point.longitude,
+                latitude: point.latitude,
+                points: 1,
+                max_distance: distance,
+            }),
+        }
+        previous = Some((kind, zone));
+    }
+    deviations
+}
+
+// 偏离分数为偏离路线偏好的里程占实际里程的百分比(0~100). 每段轨迹按两端点是否偏离各计一半,
+// 轨迹不足两个点时按偏离点数占比计算
+pub fn deviation_score(route: &[GeoPoint], route_preference: &RoutePreference) -> f64 {
+    let polyline = preferred_polyline(route_preference);
+    let deviated = route
+        .iter()
+        .map(|p| point_deviation(p, &polyline, route_preference).is_some())
+        .collect::<Vec<bool>>();
+    if route.len() < 2 {
+        return match route.len() {
+            1 if deviated[0] => 100.0,
+            _ => 0.0,
+        };
+    }
+    let (total, off) = route.windows(2).zip(deviated.windows(2)).fold(
+        (0.0, 0.0),
+        |(total, off), (points, deviated)| {
+            let length = haversine_distance(
+                points[0].longitude,
+                points[0].latitude,
+                points[1].longitude,
+                points[1].latitude,
+            );
+            let weight = deviated.iter().filter(|d| **d).count() as f64 / 2.0;
+            (total + length, off + length * weight)
+        },
+    );
+    if total == 0.0 {
+        return 100.0 * deviated.iter().filter(|d| **d).count() as f64 / deviated.len() as f64;
+    }
+    100.0 * off / total
+}
+
+pub fn assess_route(route: &[GeoPoint], route_preference: &RoutePreference) -> RouteAssessment {
+    RouteAssessment {
+        score: deviation_score(route, route_preference),
+        deviations: route_deviations(route, route_preference),
+        assessed_at: Utc::now(),
+    }
+}
+
+fn preferred_polyline(route_preference: &RoutePreference) -> Vec<(f64, f64)> {
+    route_preference
+        .preferred_route
+        .iter()
+        .map(|p| (p.longitude, p.latitude))
+        .collect()
+}
+
+pub fn poi_contains(poi: &Poi, point: &GeoPoint) -> bool {
+    if poi.boundary.len() >= 3 {
+        let polygon = poi
+            .boundary
+            .iter()
+            .map(|p| (p.longitude, p.latitude))
+            .collect::<Vec<(f64, f64)>>();
+        return point_in_polygon(point.longitude, point.latitude, &polygon);
+    }
+    haversine_distance(point.longitude, point.latitude, poi.longitude, poi.latitude) <= poi.radius
+}
+
+// 按时间顺序扫描轨迹点, 连续落在同一兴趣点内的点合并为一次到访, 停留时长取首末两点的时间差.
+// 同时位于多个兴趣点时取中心最近的一个, 没有记录时间的轨迹点不参与判断
+pub fn poi_visits(locations: &[WalkingLocation], pois: &[Poi]) -> Vec<PoiVisit> {
+    let mut visits: Vec<PoiVisit> = Vec::new();
+    let mut previous = None;
+    for location in locations {
+        let Some(at) = location.created_at else {
+            continue;
+        };
+        let point = GeoPoint {
+            longitude: location.longitude,
+            latitude: location.latitude,
+        };
+        let poi = pois
+            .iter()
+            .filter(|poi| poi_contains(poi, &point))
+            .min_by(|a, b| {
+                let da =
+                    haversine_distance(point.longitude, point.latitude, a.longitude, a.latitude);
+                let db =
+                    haversine_distance(point.longitude, point.latitude, b.longitude, b.latitude);
+                da.total_cmp(&db)
+            });
+        match (poi, visits.last_mut()) {
+            (Some(poi), Some(visit)) if previous == Some(poi.id.as_str()) => visit.left_at = at,
+            (Some(poi), _) => visits.push(PoiVisit {
+                poi_id: poi.id.clone(),
+                name: poi.name.clone(),
+                entered_at: at,
+                left_at: at,
+            }),
+            (None, _) => {}
+        }
+        previous = poi.map(|poi| poi.id.as_str());
+    }
+    visits.retain(|v| v.left_at - v.entered_at >= Duration::seconds(MIN_POI_VISIT_SECONDS));
+    visits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::NoGoZone;
+
+    // 纬度每0.001度约111米
+    fn point(longitude: f64, latitude: f64) -> GeoPoint {
+        GeoPoint {
+            longitude,
+            latitude,
+        }
+    }
+
+    fn straight_north() -> RoutePreference {
+        RoutePreference {
+            preferred_route: vec![point(116.4, 39.9), point(116.4, 39.91)],
+            no_go_zones: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn distance_to_polyline_is_perpendicular_distance() {
+        let d = distance_to_polyline(116.401, 39.905, &[(116.4, 39.9), (116.4, 39.91)]).unwrap();
+        let expected = haversine_distance(116.401, 39.905, 116.4, 39.905);
+        assert!((d - expected).abs() < 1.0, "{d} != {expected}");
+    }
+
+    #[test]
+    fn distance_to_polyline_beyond_end_is_distance_to_endpoint() {
+        let d = distance_to_polyline(116.4, 39.92, &[(116.4, 39.9), (116.4, 39.91)]).unwrap();
+        let expected = haversine_distance(116.4, 39.92, 116.4, 39.91);
+        assert!((d - expected).abs() < 1.0, "{d} != {expected}");
+        assert!(distance_to_polyline(116.4, 39.92, &[]).is_none());
+    }
+
+    #[test]
+    fn route_on_preferred_route_has_no_deviation() {
+        let route = (0..=10)
+            .map(|i| point(116.4, 39.9 + i as f64 * 0.001))
+            .collect::<Vec<GeoPoint>>();
+        let assessment = assess_route(&route, &straight_north());
+        assert!(assessment.deviations.is_empty());
+        assert_eq!(assessment.score, 0.0);
+    }
+
+    #[test]
+    fn consecutive_off_route_points_are_grouped() {
+        let route = vec![
+            point(116.4, 39.9),
+            point(116.4, 39.901),
+            point(116.402, 39.902),
+            point(116.403, 39.903),
+            point(116.4, 39.904),
+            point(116.4, 39.905),
+        ];
+        let deviations = route_deviations(&route, &straight_north());
+        assert_eq!(deviations.len(), 1);
+        assert_eq!(deviations[0].kind, RouteDeviationKind::OffRoute);
+        assert_eq!(deviations[0].points, 2);
+        assert_eq!(deviations[0].latitude, 39.902);
+        let expected = haversine_distance(116.403, 39.903, 116.4, 39.903);
+        assert!((deviations[0].max_distance - expected).abs() < 1.0);
+        let score = deviation_score(&route, &straight_north());
+        assert!(score > 0.0 && score < 100.0, "{score}");
+    }
+
+    #[test]
+    fn no_go_zone_takes_priority_over_off_route() {
+        let mut preference = straight_north();
+        preference.no_go_zones.push(NoGoZone {
+            longitude: 116.4,
+            latitude: 39.905,
+            radius: 150.0,
+            label: Some("狗狗公园".to_owned()),
+        });
+        let route = (0..=10)
+            .map(|i| point(116.4, 39.9 + i as f64 * 0.001))
+            .collect::<Vec<GeoPoint>>();
+        let deviations = route_deviations(&route, &preference);
+        assert_eq!(deviations.len(), 1);
+        assert_eq!(deviations[0].kind, RouteDeviationKind::NoGoZone);
+        assert_eq!(deviations[0].zone.as_deref(), Some("狗狗公园"));
+        assert_eq!(deviations[0].points, 3);
+        assert!((deviations[0].max_distance - 150.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn score_is_share_of_deviating_distance() {
+        let preference = RoutePreference {
+            preferred_route: Vec::new(),
+            no_go_zones: vec![NoGoZone {
+                longitude: 116.4,
+                latitude: 39.91,
+                radius: 10.0,
+                label: None,
+            }],
+        };
+        // 两段等长轨迹, 只有最后一个点在禁行区域内
+        let route = vec![
+            point(116.4, 39.9),
+            point(116.4, 39.905),
+            point(116.4, 39.91),
+        ];
+        assert!((deviation_score(&route, &preference) - 25.0).abs() < 1e-6);
+        // 没有期望路线时不判断偏离路线
+        assert!(route_deviations(&route[..2], &preference).is_empty());
+    }
+
+    fn located(longitude: f64, latitude: f64, minute: i64) -> WalkingLocation {
+        WalkingLocation {
+            longitude,
+            latitude,
+            created_at: Some(
+                "2024-05-01T08:00:00Z"
+                    .parse::<chrono::DateTime<Utc>>()
+                    .unwrap()
+                    + Duration::minutes(minute),
+            ),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn consecutive_points_in_poi_are_one_visit() {
+        let pois = vec![
+            Poi {
+                id: "park".to_owned(),
+                name: "滨河狗狗公园".to_owned(),
+                longitude: 116.4,
+                latitude: 39.905,
+                radius: 150.0,
+                boundary: Vec::new(),
+            },
+            Poi {
+                id: "square".to_owned(),
+                name: "广场".to_owned(),
+                longitude: 0.0,
+                latitude: 0.0,
+                radius: 0.0,
+                boundary: vec![
+                    point(116.41, 39.9),
+                    point(116.42, 39.9),
+                    point(116.42, 39.91),
+                    point(116.41, 39.91),
+                ],
+            },
+        ];
+        let locations = vec![
+            located(116.4, 39.9, 0),
+            located(116.4, 39.904, 3),
+            located(116.4, 39.905, 10),
+            located(116.4, 39.906, 15),
+            located(116.405, 39.905, 16),
+            // 只经过一个轨迹点, 视为路过
+            located(116.415, 39.905, 18),
+            located(116.43, 39.905, 20),
+        ];
+        let visits = poi_visits(&locations, &pois);
+        assert_eq!(visits.len(), 1);
+        assert_eq!(visits[0].poi_id, "park");
+        assert_eq!(visits[0].minutes(), 12);
+    }
+
+    #[test]
+    fn score_of_empty_or_single_point_route() {
+        assert_eq!(deviation_score(&[], &straight_north()), 0.0);
+        assert_eq!(
+            deviation_score(&[point(116.41, 39.9)], &straight_north()),
+            100.0
+        );
+    }
+}
@@ -0,0 +1,5927 @@
+use std::{collections::HashMap, default, time::Duration};
+
+use crate::{
+    cache::{
+        BreedCareTipsCache, NearbyCache, NearbyCell, NeighborhoodStatsCache, WalkerStatsCache,
+    },
+    chat::ChatEvent,
+    email::{self, EmailSummary, Mailer, Template},
+    error::{Error, ErrorKind},
+    geo::haversine_distance,
+    inference::InferenceProvider,
+    oauth::OAuthIdentity,
+    object_store::ObjectStore,
+    payout::{PayoutDestination, PayoutOrder, PayoutProvider},
+    push::{Notifier, PushMessage, PushOutcome, PushSummary},
+    repository::{
+        BreedCareTipsUpsert, BreedCreate, BreedQuery, DogCreate, DogQuery, DogUpdate, Repository,
+    },
+    route::{assess_route, poi_visits},
+    sms::SmsSender,
+    translation::{Language, Translator},
+};
+
+use super::{
+    entities::{
+        Breed, BreedCareTips, BreedSuggestion, Dog, DogUpdateOutcome, PortraitCheckMode,
+        PortraitIssue, PortraitUpdateOutcome,
+    },
+    repository::{Cursor, FieldSet, Page, Pagination, UpdateField},
+};
+
+pub struct Service<R>
+where
+    R: Repository,
+{
+    repository: R,
+    nearby_cache: NearbyCache,
+    walker_stats_cache: WalkerStatsCache,
+    neighborhood_stats_cache: NeighborhoodStatsCache,
+    breed_care_tips_cache: BreedCareTipsCache,
+    refresh_token_ttl: Duration,
+    otp_ttl: Duration,
+    portrait_check_mode: PortraitCheckMode,
+    admin_user_ids: Vec<String>,
+    invite_link_base: String,
+    short_link_base: String,
+    deep_link_scheme: String,
+    integrity_policies: HashMap<IntegrityIssueKind, IntegrityPolicy>,
+    synthetic_walker_id: Option<String>,
+}
+
+const DEFAULT_NEARBY_CACHE_TTL: Duration = Duration::from_secs(30);
+const DEFAULT_WALKER_STATS_CACHE_TTL: Duration = Duration::from_secs(300);
+const DEFAULT_NEIGHBORHOOD_STATS_CACHE_TTL: Duration = Duration::from_secs(3600);
+const DEFAULT_BREED_CARE_TIPS_CACHE_TTL: Duration = Duration::from_secs(24 * 3600);
+const DEFAULT_REFRESH_TOKEN_TTL: Duration = Duration::from_secs(30 * 24 * 3600);
+const DEFAULT_OTP_TTL: Duration = Duration::from_secs(300);
+const DEFAULT_INVITE_LINK_BASE: &str = "https://littlewalk.app/invites/";
+const DEFAULT_SHORT_LINK_BASE: &str = "https://littlewalk.app/s/";
+const DEFAULT_DEEP_LINK_SCHEME: &str = "littlewalk://";
+const MAX_BREED_SUGGESTIONS: usize = 5;
+const MAX_DOG_PHOTOS: usize = 20; // 每只狗狗相册的最大照片数
+const MAX_REVIEW_PHOTOS: usize = 9; // 每条评价最多附带的照片数
+const REVIEW_REVEAL_WINDOW_DAYS: i64 = 7; // 对方未评价时, 评价在创建后隐藏的天数
+const MAX_CHAT_MESSAGE_CHARS: usize = 1000;
+const MAX_CHAT_MESSAGE_IMAGES: usize = 9;
+
+// 头像质量阈值, 低于阈值视为不合格
+const MIN_PORTRAIT_SHARPNESS: f64 = 0.3;
+const MIN_PORTRAIT_BRIGHTNESS: f64 = 0.25;
+const MIN_PORTRAIT_DOG_CONFIDENCE: f64 = 0.5;
+
+impl<R> Service<R>
+where
+    R: Repository,
+{
+    pub fn new(repository: R) -> Self {
+        Self {
+            repository,
+            nearby_cache: NearbyCache::new(DEFAULT_NEARBY_CACHE_TTL),
+            walker_stats_cache: WalkerStatsCache::new(DEFAULT_WALKER_STATS_CACHE_TTL),
+            neighborhood_stats_cache: NeighborhoodStatsCache::new(
+                DEFAULT_NEIGHBORHOOD_STATS_CACHE_TTL,
+            ),
+            breed_care_tips_cache: BreedCareTipsCache::new(DEFAULT_BREED_CARE_TIPS_CACHE_TTL),
+            refresh_token_ttl: DEFAULT_REFRESH_TOKEN_TTL,
+            otp_ttl: DEFAULT_OTP_TTL,
+            portrait_check_mode: PortraitCheckMode::Warn,
+            admin_user_ids: Vec::new(),
+            invite_link_base: DEFAULT_INVITE_LINK_BASE.to_owned(),
+            short_link_base: DEFAULT_SHORT_LINK_BASE.to_owned(),
+            deep_link_scheme: DEFAULT_DEEP_LINK_SCHEME.to_owned(),
+            integrity_policies: HashMap::new(),
+            synthetic_walker_id: None,
+        }
+    }
+
+    pub fn with_nearby_cache_ttl(self, ttl: Duration) -> Self {
+        Self {
+            nearby_cache: NearbyCache::new(ttl),
+            ..self
+        }
+    }
+
+    pub fn with_walker_stats_cache_ttl(self, ttl: Duration) -> Self {
+        Self {
+            walker_stats_cache: WalkerStatsCache::new(ttl),
+            ..self
+        }
+    }
+
+    pub fn with_neighborhood_stats_cache_ttl(self, ttl: Duration) -> Self {
+        Self {
+            neighborhood_stats_cache: NeighborhoodStatsCache::new(ttl),
+            ..self
+        }
+    }
+
+    pub fn with_breed_care_tips_cache_ttl(self, ttl: Duration) -> Self {
+        Self {
+            breed_care_tips_cache: BreedCareTipsCache::new(ttl),
+            ..self
+        }
+    }
+
+    pub fn with_refresh_token_ttl(self, ttl: Duration) -> Self {
+        Self {
+            refresh_token_ttl: ttl,
+            ..self
+        }
+    }
+
+    pub fn with_otp_ttl(self, ttl: Duration) -> Self {
+        Self {
+            otp_ttl: ttl,
+            ..self
+        }
+    }
+
+    pub fn with_portrait_check_mode(self, mode: PortraitCheckMode) -> Self {
+        Self {
+            portrait_check_mode: mode,
+            ..self
+        }
+    }
+
+    pub fn with_admin_user_ids(self, admin_user_ids: Vec<String>) -> Self {
+        Self {
+            admin_user_ids,
+            ..self
+        }
+    }
+
+    // 邀请短信中的链接为该前缀加邀请码, 由App的深度链接处理
+    pub fn with_invite_link_base(self, invite_link_base: String) -> Self {
+        Self {
+            invite_link_base,
+            ..self
+        }
+    }
+
+    // 定时完整性检查对各类问题的处理方式, 未配置的类型只报告
+    pub fn with_integrity_policies(
+        self,
+        integrity_policies: HashMap<IntegrityIssueKind, IntegrityPolicy>,
+    ) -> Self {
+        Self {
+            integrity_policies,
+            ..self
+        }
+    }
+
+    // 短链接为该前缀加短链接码, 访问时跳转到deep_link_scheme开头的App深度链接
+    pub fn with_short_links(self, short_link_base: String, deep_link_scheme: String) -> Self {
+        Self {
+            short_link_base,
+            deep_link_scheme,
+            ..self
+        }
+    }
+
+    // 合成监控使用的测试遛狗人, 该账号结束遛狗不记入收入, 也不能提现
+    pub fn with_synthetic_walker_id(self, synthetic_walker_id: Option<String>) -> Self {
+        Self {
+            synthetic_walker_id,
+            ..self
+        }
+    }
+
+    fn is_synthetic_walker(&self, user_id: &str) -> bool {
+        self.synthetic_walker_id.as_deref() == Some(user_id)
+    }
+
+    pub async fn create_breed(&self, breed: BreedCreate) -> Result<String, Error> {
+        self.repository.create_breed(&breed).await
+    }
+
+    pub async fn delete_breed(&self, id: &str) -> Result<bool, Error> {
+        self.repository.delete_breed(id).await
+    }
+
+    pub async fn query_breeds(&self, query: &BreedQuery) -> Result<(Vec<Breed>, i64), Error> {
+        self.repository.query_breeds(query).await
+    }
+
+    pub async fn breed(
+        &self,
+        id: &str,
+        locale: &str,
+    ) -> Result<(Breed, Option<BreedCareTips>), Error> {
+        let (mut breeds, _) = self
+            .repository
+            .query_breeds(&BreedQuery {
+                id: Some(id.to_owned()),
+                ..Default::default()
+            })
+            .await?;
+        let breed = breeds.pop().ok_or(Error::not_found("品种不存在"))?;
+        let tips = self.breed_care_tips(id, locale).await?;
+        Ok((breed, tips))
+    }
+
+    // 指定语言没有建议时退回默认语言
+    pub async fn breed_care_tips(
+        &self,
+        breed_id: &str,
+        locale: &str,
+    ) -> Result<Option<BreedCareTips>, Error> {
+        if let Some(tips) = self.breed_care_tips_cache.get(breed_id, locale) {
+            return Ok(tips);
+        }
+        let mut tips = None;
+        for lookup in [locale, DEFAULT_HELP_LOCALE] {
+            tips = self
+                .repository
+                .query_breed_care_tips(&[breed_id.to_owned()], lookup)
+                .await?
+                .pop();
+            if tips.is_some() {
+                break;
+            }
+        }
+        self.breed_care_tips_cache
+            .put(breed_id, locale, tips.clone());
+        Ok(tips)
+    }
+
+    // 覆盖该品种该语言的全部建议
+    pub async fn update_breed_care_tips(
+        &self,
+        breed_id: &str,
+        locale: &str,
+        tips: Vec<String>,
+        updated_by: &str,
+    ) -> Result<(), Error> {
+        if locale.trim().is_empty() {
+            return Err(Error::msg("请指定语言"));
+        }
+        let (breeds, _) = self
+            .repository
+            .query_breeds(&BreedQuery {
+                id: Some(breed_id.to_owned()),
+                ..Default::default()
+            })
+            .await?;
+        if breeds.is_empty() {
+            return Err(Error::not_found("品种不存在"));
+        }
+        self.repository
+            .upsert_breed_care_tips(BreedCareTipsUpsert {
+                breed_id: breed_id.to_owned(),
+                locale: locale.to_owned(),
+                tips: tips.into_iter().map(|tip| tip.trim().to_owned()).collect(),
+                updated_by: updated_by.to_owned(),
+            })
+            .await?;
+        self.breed_care_tips_cache.invalidate(breed_id);
+        Ok(())
+    }
+
+    pub async fn delete_breed_care_tips(&self, breed_id: &str, locale: &str) -> Result<(), Error> {
+        if !self
+            .repository
+            .delete_breed_care_tips(breed_id, locale)
+            .await?
+        {
+            return Err(Error::not_found("该品种没有此语言的照护建议"));
+        }
+        self.breed_care_tips_cache.invalidate(breed_id);
+        Ok(())
+    }
+
+    // 接单的遛狗人查看请求中各狗狗品种的照护建议, 狗狗主人也可以查看
+    pub async fn walk_request_care_tips(
+        &self,
+        request_id: &str,
+        user_id: &str,
+        locale: &str,
+    ) -> Result<Vec<(Dog, Option<BreedCareTips>)>, Error> {
+        let request = self.repository.get_walk_request(request_id).await?;
+        if request.created_by != user_id && request.accepted_by.as_deref() != Some(user_id) {
+            return Err(Error::forbidden(
+                "只有狗狗主人和接单的遛狗人可以查看照护建议",
+            ));
+        }
+        let mut dogs = Vec::with_capacity(request.dogs.len());
+        for dog in request.dogs {
+            let tips = self.breed_care_tips(dog.breed.id.as_str(), locale).await?;
+            dogs.push((dog, tips));
+        }
+        Ok(dogs)
+    }
+
+    // 识别结果按置信度排序, 只保留能对应到breeds集合的候选
+    pub async fn suggest_breeds<P>(
+        &self,
+        provider: &P,
+        image: Vec<u8>,
+    ) -> Result<Vec<BreedSuggestion>, Error>
+    where
+        P: InferenceProvider,
+    {
+        let mut candidates = provider.recognize_breed(image).await?;
+        candidates.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+        let (breeds, _) = self.repository.query_breeds(&BreedQuery::default()).await?;
+        let mut suggestions: Vec<BreedSuggestion> = Vec::new();
+        for candidate in candidates {
+            if let Some(breed) = breeds
+                .iter()
+                .find(|b| b.name.eq_ignore_ascii_case(candidate.label.trim()))
+            {
+                if suggestions.iter().any(|s| s.breed.id == breed.id) {
+                    continue;
+                }
+                suggestions.push(BreedSuggestion {
+                    breed: breed.clone(),
+                    confidence: candidate.confidence,
+                });
+            }
+            if suggestions.len() == MAX_BREED_SUGGESTIONS {
+                break;
+            }
+        }
+        Ok(suggestions)
+    }
+
+    pub async fn create_dog(&self, dog: &DogCreate) -> Result<Dog, Error> {
+        self.repository.create_dog(dog).await
+    }
+
+    // 更新前先检查头像质量, Enforce模式下拒绝不合格的头像, Warn模式下仅返回提示
+    pub async fn update_dog_portrait<P>(
+        &self,
+        provider: &P,
+        id: &str,
+        portrait_id: &str,
+        image: Vec<u8>,
+    ) -> Result<PortraitUpdateOutcome, Error>
+    where
+        P: InferenceProvider,
+    {
+        let assessment = provider.assess_portrait(image).await?;
+        let mut issues = Vec::new();
+        if assessment.sharpness < MIN_PORTRAIT_SHARPNESS {
+            issues.push(PortraitIssue::Blurry);
+        }
+        if assessment.brightness < MIN_PORTRAIT_BRIGHTNESS {
+            issues.push(PortraitIssue::TooDark);
+        }
+        if assessment.dog_confidence < MIN_PORTRAIT_DOG_CONFIDENCE {
+            issues.push(PortraitIssue::NoDogDetected);
+        }
+        if !issues.is_empty() && self.portrait_check_mode == PortraitCheckMode::Enforce {
+            return Ok(PortraitUpdateOutcome::Rejected(issues));
+        }
+        let has_updated = self
+            .repository
+            .update_dog(
+                id,
+                &DogUpdate {
+                    portrait_id: UpdateField::Set(portrait_id.to_owned()),
+                    ..default::Default::default()
+                },
+            )
+            .await?;
+        Ok(PortraitUpdateOutcome::Updated {
+            has_updated,
+            warnings: issues,
+        })
+    }
+
+    // 版本不一致或离线编辑早于服务端更新时都返回服务端当前版本
+    pub async fn update_dog(&self, id: &str, dog: &DogUpdate) -> Result<DogUpdateOutcome, Error> {
+        if self.repository.update_dog(id, dog).await? {
+            return Ok(DogUpdateOutcome::Updated);
+        }
+        if dog.edited_at.is_none() && dog.expected_version.is_none() {
+            return Ok(DogUpdateOutcome::Unchanged);
+        }
+        let current = self
+            .repository
+            .query_dogs(&DogQuery {
+                id: Some(id.to_owned()),
+                ..default::Default::default()
+            })
+            .await?
+            .pop()
+            .ok_or(Error::not_found("狗狗不存在"))?;
+        if dog.expected_version.is_some_and(|v| v != current.version) {
+            return Ok(DogUpdateOutcome::Conflict(current));
+        }
+        match (current.updated_at, dog.edited_at) {
+            (Some(updated_at), Some(edited_at)) if updated_at > edited_at => {
+                Ok(DogUpdateOutcome::Conflict(current))
+            }
+            _ => Ok(DogUpdateOutcome::Unchanged),
+        }
+    }
+
+    async fn dog(&self, id: &str) -> Result<Dog, Error> {
+        self.repository
+            .query_dogs(&DogQuery {
+                id_in: Some(vec![id.to_owned()]),
+                ..default::Default::default()
+            })
+            .await?
+            .pop()
+            .ok_or(Error::not_found("狗狗不存在"))
+    }
+
+    pub async fn add_dog_photo(&self, id: &str, photo_id: &str) -> Result<Dog, Error> {
+        let dog = self.dog(id).await?;
+        if dog.photos.iter().any(|p| p == photo_id) {
+            return Err(Error::msg("照片已在相册中"));
+        }
+        if dog.photos.len() >= MAX_DOG_PHOTOS {
+            return Err(Error::msg("相册照片数量已达上限"));
+        }
+        self.repository
+            .update_dog(
+                id,
+                &DogUpdate {
+                    add_to_photos: Some(photo_id.to_owned()),
+                    ..default::Default::default()
+                },
+            )
+            .await?;
+        self.dog(id).await
+    }
+
+    pub async fn remove_dog_photo(&self, id: &str, photo_id: &str) -> Result<Dog, Error> {
+        self.repository
+            .update_dog(
+                id,
+                &DogUpdate {
+                    remove_from_photos: Some(photo_id.to_owned()),
+                    ..default::Default::default()
+                },
+            )
+            .await?;
+        self.dog(id).await
+    }
+
+    // 新顺序必须恰好包含相册中现有的全部照片
+    pub async fn reorder_dog_photos(&self, id: &str, photos: Vec<String>) -> Result<Dog, Error> {
+        let dog = self.dog(id).await?;
+        let mut current = dog.photos.clone();
+        let mut ordered = photos.clone();
+        current.sort();
+        ordered.sort();
+        if current != ordered {
+            return Err(Error::msg("照片列表与相册不一致"));
+        }
+        self.repository
+            .update_dog(
+                id,
+                &DogUpdate {
+                    photos: Some(photos),
+                    ..default::Default::default()
+                },
+            )
+            .await?;
+        self.dog(id).await
+    }
+
+    pub async fn my_dogs(
+        &self,
+        owner_id: &str,
+        pagination: Option<Page>,
+        fields: Option<FieldSet>,
+    ) -> Result<(Vec<Dog>, Option<Cursor>), Error> {
+        self.page_dogs(&DogQuery {
+            owner_id: Some(owner_id.to_owned()),
+            pagination,
+            fields,
+            ..default::Default::default()
+        })
+        .await
+    }
+
+    pub async fn query_dogs(&self, query: &DogQuery) -> Result<Vec<Dog>, Error> {
+        self.repository.query_dogs(query).await
+    }
+
+    // 同时返回下一页的游标, 狗狗列表的游标只按_id排序, 指定排序字段时不返回游标
+    pub async fn page_dogs(&self, query: &DogQuery) -> Result<(Vec<Dog>, Option<Cursor>), Error> {
+        if query.sort_by.is_some() && matches!(query.pagination, Some(Page::Cursor { .. })) {
+            return Err(Error::msg("指定排序字段时不支持游标分页"));
+        }
+        let dogs = self.repository.query_dogs(query).await?;
+        let next = query
+            .pagination
+            .as_ref()
+            .filter(|_| query.sort_by.is_none())
+            .and_then(|page| {
+                page.next_cursor(&dogs, |d| Cursor {
+                    sort_key: None,
+                    id: d.id.object_id(),
+                })
+            });
+        Ok((dogs, next))
+    }
+
+    // 删除狗狗, 并从未开始的遛狗请求中移除该狗狗, 请求中没有其他狗狗时取消请求
+    pub async fn delete_dog(&self, owner_id: &str, dog_id: &str) -> Result<(), Error> {
+        let affected = self
+            .repository
+            .with_transaction(|repository| async move {
+                if !repository.delete_dog(dog_id).await? {
+                    return Err(Error::not_found("狗狗不存在"));
+                }
+                let (requests, _) = repository
+                    .query_walk_requests(
+                        WalkRequestQuery {
+                            dog_ids_includes_any: Some(vec![dog_id.to_owned()]),
+                            created_by: Some(owner_id.to_owned()),
+                            started_at_is_null: Some(true),
+                            ..Default::default()
+                        },
+                        None,
+                        None,
+                    )
+                    .await?;
+                let mut affected = Vec::new();
+                for request in requests {
+                    if request.canceled_at.is_some() {
+                        continue;
+                    }
+                    let update = without_dog(request.dogs, dog_id, Some(owner_id.to_owned()));
+                    affected.push(repository.update_walk_request(&request.id, update).await?);
+                }
+                Ok(affected)
+            })
+            .await?;
+        for request in affected {
+            self.nearby_cache
+                .invalidate(request.latitude, request.longitude);
+        }
+        Ok(())
+    }
+
+    pub async fn is_owner_of_the_dog(&self, owner_id: &str, dog_id: &str) -> Result<bool, Error> {
+        self.repository
+            .exists_dog(&DogQuery {
+                id: Some(dog_id.into()),
+                owner_id: Some(owner_id.into()),
+                ..Default::default()
+            })
+            .await
+    }
+
+    pub async fn create_walk_request(&self, request: WalkRequestCreate) -> Result<String, Error> {
+        // if request.should_start_after >= request.should_end_before {
+        //     return Err(Error::msg("开始时间范围起点不得大于等于终点"));
+        // }
+        // if request.should_end_after >= request.should_end_before {
+        //     return Err(Error::msg("结束时间范围起点不得大于等于终点"));
+        // }
+        // if request.should_start_after >= request.should_end_before {
+        //     return Err(Error::msg("结束时间不得早于开始时间"));
+        // }
+        let request = self.prepare_walk_request(request).await?;
+        let (latitude, longitude) = (request.latitude, request.longitude);
+        let id = self.repository.create_walk_request(request).await?;
+        self.nearby_cache.invalidate(latitude, longitude);
+        Ok(id)
+    }
+
+    // 执行与创建相同的检查但不保存, 返回将要创建的请求和费用报价, 供客户端确认
+    pub async fn validate_walk_request(
+        &self,
+        request: WalkRequestCreate,
+    ) -> Result<(WalkRequestCreate, WalkQuote), Error> {
+        let request = self.prepare_walk_request(request).await?;
+        let quote = walk_quote(request.dogs.len());
+        Ok((request, quote))
+    }
+
+    // 创建前的校验和补全, 不写入数据
+    async fn prepare_walk_request(
+        &self,
+        mut request: WalkRequestCreate,
+    ) -> Result<WalkRequestCreate, Error> {
+        if let Some(route_preference) = &request.route_preference {
+            validate_route_preference(route_preference)?;
+        }
+        request.route_preference = request.route_preference.filter(|p| !p.is_empty());
+        self.check_schedule_conflict(&request).await?;
+        if request.notify_favorites {
+            request.priority_walkers = self
+                .repository
+                .query_favorites(FavoriteQuery {
+                    owner_id: Some(request.created_by.clone()),
+                    ..Default::default()
+                })
+                .await?
+                .into_iter()
+                .map(|f| f.walker_id)
+                .collect();
+            if !request.priority_walkers.is_empty() {
+                request.priority_until =
+                    Some(Utc::now() + chrono::Duration::minutes(FAVORITES_PRIORITY_MINUTES));
+            }
+        }
+        Ok(request)
+    }
+
+    // 同一只狗在未开始的请求中预定时段重叠时视为冲突. 没有预定开始时间的请求不参与比较
+    async fn check_schedule_conflict(&self, request: &WalkRequestCreate) -> Result<(), Error> {
+        let Some(window) = schedule_window(
+            request.should_start_after,
+            request.should_start_before,
+            request.should_end_after,
+            request.should_end_before,
+        ) else {
+            return Ok(());
+        };
+        let (requests, _) = self
+            .repository
+            .query_walk_requests(
+                WalkRequestQuery {
+                    created_by: Some(request.created_by.clone()),
+                    dog_ids_includes_any: Some(
+                        request.dogs.iter().map(|d| d.id.to_string()).collect(),
+                    ),
+                    started_at_is_null: Some(true),
+                    ..Default::default()
+                },
+                None,
+                None,
+            )
+            .await?;
+        let conflict = requests.iter().any(|r| {
+            r.canceled_at.is_none()
+                && schedule_window(
+                    r.should_start_after,
+                    r.should_start_before,
+                    r.should_end_after,
+                    r.should_end_before,
+                )
+                .is_some_and(|(start, end)| start <= window.1 && window.0 <= end)
+        });
+        if conflict {
+            return Err(Error::conflict("狗狗在该时段已有遛狗请求"));
+        }
+        Ok(())
+    }
+
+    // 被屏蔽或不对当前用户开放的请求不计入总数
+    pub async fn nearby_walk_requests(
+        &self,
+        user_id: &str,
+        latitute: f64,
+        longitude: f64,
+        radius: f64,
+        pagination: Pagination,
+    ) -> Result<(Vec<WalkRequest>, u64), Error> {
+        let blockers = self.blockers_of(user_id).await?;
+        let cell = NearbyCell::new(latitute, longitude, radius);
+        let candidates = match self.nearby_cache.get(&cell) {
+            Some(cached) => cached,
+            None => {
+                let (requests, _) = self
+                    .repository
+                    .query_walk_requests(
+                        WalkRequestQuery {
+                            accepted_by_is_null: Some(true),
+                            nearby: Some(vec![
+                                cell.longitude(),
+                                cell.latitude(),
+                                cell.query_radius(),
+                            ]),
+                            ..Default::default()
+                        },
+                        None,
+                        Some(
+                            Pagination {
+                                limit: MAX_NEARBY_CANDIDATES,
+                                skip: 0,
+                            }
+                            .into(),
+                        ),
+                    )
+                    .await?;
+                self.nearby_cache.put(cell, requests.clone());
+                requests
+            }
+        };
+        // 候选请求的距离是到网格中心的距离, 按调用方的实际位置重新计算后筛选、排序, 再分页.
+        // 屏蔽关系每次重新读取, 屏蔽后立即生效而不必等缓存过期; 优先推送期按当前时间判断
+        let now = Utc::now();
+        let mut requests = candidates
+            .into_iter()
+            .filter(|r| !blockers.contains(&r.created_by) && r.is_open_to(user_id, now))
+            .map(|mut r| {
+                r.distance = Some(haversine_distance(
+                    longitude,
+                    latitute,
+                    r.longitude,
+                    r.latitude,
+                ));
+                r
+            })
+            .filter(|r| r.distance.map_or(false, |d| d <= radius))
+            .collect::<Vec<WalkRequest>>();
+        requests.sort_by(|a, b| {
+            a.distance
+                .unwrap_or_default()
+                .total_cmp(&b.distance.unwrap_or_default())
+        });
+        let total = requests.len() as u64;
+        let requests = requests
+            .into_iter()
+            .skip(pagination.skip.max(0) as usize)
+            .take(if pagination.limit > 0 {
+                pagination.limit as usize
+            } else {
+                usize::MAX
+            })
+            .collect();
+        Ok((requests, total))
+    }
+
+    pub async fn my_walk_requests(
+        &self,
+        user_id: &str,
+        page: Page,
+        fields: Option<FieldSet>,
+    ) -> Result<(Vec<WalkRequest>, u64, Option<Cursor>), Error> {
+        let (requests, total) = self
+            .repository
+            .query_walk_requests(
+                WalkRequestQuery {
+                    created_by: Some(user_id.to_owned()),
+                    fields,
+                    ..Default::default()
+                },
+                Some(SortBy {
+                    field: WalkRequest::created_at(),
+                    order: Order::Desc,
+                }),
+                Some(page.clone()),
+            )
+            .await?;
+        let next = page.next_cursor(&requests, |r| Cursor {
+            sort_key: r.created_at,
+            id: r.id.object_id(),
+        });
+        Ok((requests, total, next))
+    }
+
+    // 按(updated_at, _id)游标分页, 批量更新写入相同时间戳的请求分在两页时也不会遗漏
+    pub async fn walk_request_changes(
+        &self,
+        user_id: &str,
+        since: Cursor,
+    ) -> Result<WalkRequestChanges, Error> {
+        let (requests, _) = self
+            .repository
+            .query_walk_requests(
+                WalkRequestQuery {
+                    involves: Some(user_id.to_owned()),
+                    include_deleted: true,
+                    ..Default::default()
+                },
+                Some(SortBy {
+                    field: WalkRequest::updated_at(),
+                    order: Order::Asc,
+                }),
+                Some(Page::Cursor {
+                    after: Some(since.clone()),
+                    limit: MAX_CHANGES,
+                }),
+            )
+            .await?;
+        let mut changes = WalkRequestChanges {
+            created: Vec::new(),
+            updated: Vec::new(),
+            deleted: Vec::new(),
+            has_more: requests.len() as i64 == MAX_CHANGES,
+            checkpoint: requests
+                .last()
+                .map(|r| Cursor {
+                    sort_key: r.updated_at,
+                    id: r.id.object_id(),
+                })
+                .unwrap_or(since.clone()),
+        };
+        for request in requests {
+            if request.canceled_at.is_some() || request.deleted_at.is_some() {
+                changes.deleted.push(request.id.into());
+            } else if request.created_at > since.sort_key {
+                changes.created.push(request);
+            } else {
+                changes.updated.push(request);
+            }
+        }
+        Ok(changes)
+    }
+
+    pub async fn accept(&self, request_id: &str, user_id: &str) -> Result<WalkRequest, Error> {
+        if !self.is_verified_walker(user_id).await? {
+            return Err(Error::forbidden("只有通过身份认证的遛狗人才能接单"));
+        }
+        let blockers = self.blockers_of(user_id).await?;
+        let request = self
+            .repository
+            .update_walk_request_by_query(
+                WalkRequestQuery {
+                    id: Some(request_id.into()),
+                    accepted_by_is_null: Some(true),
+                    created_by_nin: Some(blockers),
+                    open_to: Some(user_id.to_owned()),
+                    ..Default::default()
+                },
+                WalkRequestUpdate {
+                    accepted_by: UpdateField::Set(user_id.to_owned()),
+                    accepted_at: UpdateField::Set(Utc::now()),
+                    actor_id: Some(user_id.to_owned()),
+                    ..Default::default()
+                },
+            )
+            .await?;
+        self.nearby_cache
+            .invalidate(request.latitude, request.longitude);
+        self.notify(
+            &request.created_by,
+            NotificationKind::RequestAccepted,
+            &request.id.to_string(),
+            "遛狗人已接单",
+            true,
+        )
+        .await?;
+        Ok(request)
+    }
+
+    pub async fn remove_acceptance(&self, request_id: &str, user_id: &str) -> Result<(), Error> {
+        self.repository
+            .update_walk_requests_by_query(
+                WalkRequestQuery {
+                    id: Some(request_id.to_owned()),
+                    accepted_by_neq: Some(user_id.to_owned()),
+                    ..Default::default()
+                },
+                WalkRequestUpdate {
+                    remove_from_acceptances: Some(user_id.to_owned()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .and_then(|n| {
+                if n == 1 {
+                    Ok(())
+                } else {
+                    Err(Error::not_found("请求不存在或狗狗主人已通过请求"))
+                }
+            })
+    }
+
+    // 只有请求的创建者可以从报名者中指定接单人
+    pub async fn assign_accepter(
+        &self,
+        owner_id: &str,
+        request_id: &str,
+        user_id: &str,
+    ) -> Result<(), Error> {
+        self.repository
+            .update_walk_requests_by_query(
+                WalkRequestQuery {
+                    id: Some(request_id.to_owned()),
+                    created_by: Some(owner_id.to_owned()),
+                    accepted_by_is_null: Some(true),
+                    acceptances_includes_all: Some(vec![user_id.to_owned()]),
+                    ..Default::default()
+                },
+                WalkRequestUpdate {
+                    accepted_by: UpdateField::Set(user_id.to_owned()),
+                    accepted_at: UpdateField::Set(Utc::now()),
+                    actor_id: Some(owner_id.to_owned()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .and_then(|n| {
+                if n == 1 {
+                    Ok(())
+                } else {
+                    Err(Error::not_found("请求不存在或该用户已取消报名"))
+                }
+            })?;
+        let request = self.repository.get_walk_request(request_id).await?;
+        self.nearby_cache
+            .invalidate(request.latitude, request.longitude);
+        self.notify(
+            user_id,
+            NotificationKind::RequestAccepted,
+            request_id,
+            "狗狗主人已选择你来遛狗",
+            true,
+        )
+        .await
+    }
+
+    pub async fn dismiss_accepter(&self, request_id: &str, user_id: &str) -> Result<(), Error> {
+        self.repository
+            .update_walk_requests_by_query(
+                WalkRequestQuery {
+                    id: Some(request_id.to_owned()),
+                    accepted_by: Some(user_id.to_owned()),
+                    ..Default::default()
+                },
+                WalkRequestUpdate {
+                    accepted_by: UpdateField::Unset,
+                    accepted_at: UpdateField::Unset,
+                    ..Default::default()
+                },
+            )
+            .await
+            .and_then(|n| {
+                if n == 1 {
+                    Ok(())
+                } else {
+                    Err(Error::not_found("请求不存在或该用户已取消报名"))
+                }
+            })
+    }
+
+    // 只能删除尚未有遛狗人接单的请求, 删除后可由管理员在清理前恢复
+    pub async fn delete_walk_request(&self, owner_id: &str, request_id: &str) -> Result<(), Error> {
+        let request = self
+            .repository
+            .update_walk_request_by_query(
+                WalkRequestQuery {
+                    id: Some(request_id.to_owned()),
+                    created_by: Some(owner_id.to_owned()),
+                    accepted_by_is_null: Some(true),
+                    ..Default::default()
+                },
+                WalkRequestUpdate {
+                    deleted_at: Some(Utc::now()),
+                    ..Default::default()
+                },
+            )
+            .await?;
+        self.nearby_cache
+            .invalidate(request.latitude, request.longitude);
+        Ok(())
+    }
+
+    // 导出给寄养合作方打印的遛狗请求摘要, 只有狗狗主人和接单的遛狗人可以导出
+    pub async fn export_walk_request(
+        &self,
+        request_id: &str,
+        user_id: &str,
+    ) -> Result<WalkRequestExport, Error> {
+        let request = self.repository.get_walk_request(request_id).await?;
+        if request.created_by != user_id && request.accepted_by.as_deref() != Some(user_id) {
+            return Err(Error::forbidden(
+                "只有狗狗主人和接单的遛狗人可以导出遛狗请求",
+            ));
+        }
+        let current = self
+            .repository
+            .query_dogs(&DogQuery {
+                id_in: Some(request.dogs.iter().map(|d| d.id.to_string()).collect()),
+                include_deleted: true,
+                ..Default::default()
+            })
+            .await?;
+        let dogs = request
+            .dogs
+            .iter()
+            .map(|snapshot| {
+                current
+                    .iter()
+                    .find(|d| d.id == snapshot.id)
+                    .unwrap_or(snapshot)
+                    .clone()
+            })
+            .collect();
+        let owner = self
+            .repository
+            .get_owner(&request.created_by)
+            .await?
+            .unwrap_or_default();
+        Ok(WalkRequestExport {
+            request,
+            dogs,
+            owner_nickname: owner.nickname,
+            emergency_contacts: owner.emergency_contacts,
+            generated_at: Utc::now(),
+        })
+    }
+
+    // 请求被接单后狗狗主人和接单的遛狗人可以聊天
+    pub async fn check_chat_access(&self, request_id: &str, user_id: &str) -> Result<(), Error> {
+        let request = self.repository.get_walk_request(request_id).await?;
+        let Some(walker_id) = request.accepted_by.as_deref() else {
+            return Err(Error::forbidden("请求被接单后才能聊天"));
+        };
+        if request.created_by != user_id && walker_id != user_id {
+            return Err(Error::forbidden("只有狗狗主人和接单的遛狗人可以聊天"));
+        }
+        Ok(())
+    }
+
+    // 每条消息都重新检查权限, 请求被改派或取消接单后原遛狗人不能继续发送. 保存成功后才推送
+    pub async fn send_chat_message(
+        &self,
+        request_id: &str,
+        sender_id: &str,
+        text: String,
+        mut image_ids: Vec<String>,
+    ) -> Result<ChatEvent, Error> {
+        let text = text.trim().to_owned();
+        let mut seen = std::collections::HashSet::new();
+        image_ids.retain(|id| seen.insert(id.clone()));
+        if text.is_empty() && image_ids.is_empty() {
+            return Err(Error::msg("消息内容不能为空"));
+        }
+        if text.chars().count() > MAX_CHAT_MESSAGE_CHARS {
+            return Err(Error::msg(&format!(
+                "每条消息最多{}个字",
+                MAX_CHAT_MESSAGE_CHARS
+            )));
+        }
+        if image_ids.len() > MAX_CHAT_MESSAGE_IMAGES {
+            return Err(Error::msg(&format!(
+                "每条消息最多附带{}张图片",
+                MAX_CHAT_MESSAGE_IMAGES
+            )));
+        }
+        self.check_chat_access(request_id, sender_id).await?;
+        let id = self
+            .repository
+            .create_chat_message(ChatMessageCreate {
+                walk_request_id: request_id.to_owned(),
+                sender_id: sender_id.to_owned(),
+                text,
+                image_ids,
+            })
+            .await?;
+        Ok(self.repository.get_chat_message(&id).await?.into())
+    }
+
+    // 聊天记录按发送时间倒序, 客户端向上翻页时用next_cursor继续获取更早的消息
+    pub async fn chat_messages(
+        &self,
+        request_id: &str,
+        user_id: &str,
+        page: Page,
+    ) -> Result<(Vec<ChatMessage>, u64, Option<Cursor>), Error> {
+        self.check_chat_access(request_id, user_id).await?;
+        let (messages, total) = self
+            .repository
+            .query_chat_messages(request_id, page.clone())
+            .await?;
+        let next = page.next_cursor(&messages, |m| Cursor {
+            sort_key: m.created_at,
+            id: m.id.parse().unwrap_or_default(),
+        });
+        Ok((messages, total, next))
+    }
+
+    // 已读标记只前进不后退, 标记的消息不晚于当前已读位置时不产生事件
+    pub async fn mark_chat_read(
+        &self,
+        request_id: &str,
+        user_id: &str,
+        message_id: &str,
+    ) -> Result<Option<ChatEvent>, Error> {
+        self.check_chat_access(request_id, user_id).await?;
+        let message = self.repository.get_chat_message(message_id).await?;
+        if message.walk_request_id != request_id {
+            return Err(Error::not_found("消息不存在"));
+        }
+        let Some(message_created_at) = message.created_at else {
+            return Ok(None);
+        };
+        let read_until = self
+            .repository
+            .query_chat_read_markers(request_id)
+            .await?
+            .into_iter()
+            .find(|m| m.user_id == user_id)
+            .and_then(|m| m.message_created_at);
+        if read_until.map_or(false, |t| t >= message_created_at) {
+            return Ok(None);
+        }
+        self.repository
+            .upsert_chat_read_marker(ChatReadMarkerUpsert {
+                walk_request_id: request_id.to_owned(),
+                user_id: user_id.to_owned(),
+                message_id: message.id.clone(),
+                message_created_at,
+            })
+            .await?;
+        Ok(Some(ChatEvent::Read {
+            walk_request_id: message.walk_request_id,
+            user_id: user_id.to_owned(),
+            message_id: message.id,
+            read_at: Some(Utc::now()),
+        }))
+    }
+
+    // 连接建立时推送双方当前的已读位置
+    pub async fn chat_read_markers(&self, request_id: &str) -> Result<Vec<ChatReadMarker>, Error> {
+        self.repository.query_chat_read_markers(request_id).await
+    }
+
+    // 只统计已被接单、可以聊天的请求
+    pub async fn unread_chat_counts(
+        &self,
+        user_id: &str,
+        requests: &[WalkRequest],
+    ) -> Result<HashMap<String, u64>, Error> {
+        let request_ids: Vec<String> = requests
+            .iter()
+            .filter(|r| r.accepted_by.is_some())
+            .map(|r| r.id.to_string())
+            .collect();
+        self.repository
+            .count_unread_chat_messages(user_id, &request_ids)
+            .await
+    }
+
+    pub async fn cancel_unaccepted_request(&self, request_id: &str) -> Result<(), Error> {
+        self.repository
+            .update_walk_requests_by_query(
+                WalkRequestQuery {
+                    id: Some(request_id.to_owned()),
+                    accepted_by_is_null: Some(true),
+                    ..Default::default()
+                },
+                WalkRequestUpdate {
+                    canceled_at: Some(Utc::now()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .and_then(|n| {
+                if n == 1 {
+                    Ok(())
+                } else {
+                    Err(Error::not_found("请求不存在"))
+                }
+            })
+    }
+
+    pub async fn cancel_accepted_request(
+        &self,
+        request_id: &str,
+        user_id: &str,
+    ) -> Result<(), Error> {
+        self.repository
+            .update_walk_requests_by_query(
+                WalkRequestQuery {
+                    id: Some(request_id.to_owned()),
+                    accepted_by: Some(user_id.to_owned()),
+                    ..Default::default()
+                },
+                WalkRequestUpdate {
+                    canceled_at: Some(Utc::now()),
+                    actor_id: Some(user_id.to_owned()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .and_then(|n| {
+                if n == 1 {
+                    Ok(())
+                } else {
+                    Err(Error::not_found("请求不存在"))
+                }
+            })?;
+        let request = self.repository.get_walk_request(request_id).await?;
+        self.notify(
+            &request.created_by,
+            NotificationKind::RequestCanceled,
+            request_id,
+            "遛狗人已取消本次遛狗",
+            starts_soon(&request),
+        )
+        .await
+    }
+
+    // 遛狗人放弃已被指定且未取消、未结束的请求. 临近预定开始时间放弃时按档位扣除罚金,
+    // 余额不足时账本可以为负, 之后的收入会先抵扣欠款
+    pub async fn resign_acceptance(
+        &self,
+        request_id: &str,
+        user_id: &str,
+    ) -> Result<Option<CancellationPenalty>, Error> {
+        let request = self.repository.get_walk_request(request_id).await?;
+        self.repository
+            .update_walk_requests_by_query(
+                WalkRequestQuery {
+                    id: Some(request_id.to_owned()),
+                    accepted_by: Some(user_id.to_owned()),
+                    finished_at_is_null: Some(true),
+                    canceled_at_is_null: Some(true),
+                    ..Default::default()
+                },
+                WalkRequestUpdate {
+                    accepted_by: UpdateField::Unset,
+                    accepted_at: UpdateField::Unset,
+                    remove_from_acceptances: Some(user_id.to_owned()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .and_then(|n| {
+                if n == 1 {
+                    Ok(())
+                } else {
+                    Err(Error::not_found("请求不存在、已取消或已结束"))
+                }
+            })?;
+        let scheduled_start = match request.should_start_after.or(request.should_start_before) {
+            Some(scheduled_start) => scheduled_start,
+            None => return Ok(None),
+        };
+        let notice = scheduled_start - Utc::now();
+        let tier = match cancellation_penalty_tier(notice) {
+            Some(tier) => tier,
+            None => return Ok(None),
+        };
+        self.repository
+            .create_cancellation_penalty(CancellationPenaltyCreate {
+                walk_request_id: request.id.into(),
+                walker_id: user_id.to_owned(),
+                owner_id: request.created_by,
+                tier,
+                scheduled_start,
+                notice_minutes: notice.num_minutes(),
+                amount: cancellation_penalty_amount(tier),
+            })
+            .await
+            .map(Some)
+    }
+
+    // 罚金明细只对被罚的遛狗人和请求的狗狗主人可见
+    pub async fn cancellation_penalties(
+        &self,
+        request_id: &str,
+        user_id: &str,
+    ) -> Result<Vec<CancellationPenalty>, Error> {
+        Ok(self
+            .repository
+            .query_cancellation_penalties(request_id)
+            .await?
+            .into_iter()
+            .filter(|p| p.walker_id == user_id || p.owner_id == user_id)
+            .collect())
+    }
+
+    // 狗狗主人只能对自己已结束的遛狗申请退款, 同一遛狗同时只能有一个待审核的申请,
+    // 申请金额与已批准的退款之和不超过遛狗价格
+    pub async fn request_refund(
+        &self,
+        request_id: &str,
+        owner_id: &str,
+        amount: i64,
+        reason: String,
+        evidence_ids: Vec<String>,
+    ) -> Result<String, Error> {
+        let request = self.repository.get_walk_request(request_id).await?;
+        if request.created_by != owner_id {
+            return Err(Error::not_found("请求不存在"));
+        }
+        let (Some(walker_id), Some(_)) = (request.accepted_by, request.finished_at) else {
+            return Err(Error::msg("遛狗结束后才能申请退款"));
+        };
+        let (refunds, _) = self
+            .repository
+            .query_refund_requests(
+                RefundRequestQuery {
+                    walk_request_id: Some(request_id.to_owned()),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await?;
+        if refunds.iter().any(|r| r.status == RefundStatus::Requested) {
+            return Err(Error::conflict("已有待处理的退款申请"));
+        }
+        let refunded = refunds
+            .iter()
+            .filter_map(|r| r.approved_amount)
+            .sum::<i64>();
+        if amount <= 0 || refunded + amount > walk_quote(request.dogs.len()).price {
+            return Err(Error::msg("退款金额超出可退范围"));
+        }
+        // 并发的申请都通过了上面的检查时, 由待审核申请的唯一约束拒绝后到的一个
+        self.repository
+            .create_refund_request(RefundRequestCreate {
+                walk_request_id: request_id.to_owned(),
+                owner_id: owner_id.to_owned(),
+                walker_id,
+                amount,
+                reason,
+                evidence_ids,
+            })
+            .await
+            .map_err(|e| {
+                if e.is_duplicate("walk_request_id") {
+                    Error::conflict("已有待处理的退款申请")
+                } else {
+                    e
+                }
+            })
+    }
+
+    // 退款申请只对申请的狗狗主人和被申请的遛狗人可见
+    pub async fn walk_request_refunds(
+        &self,
+        request_id: &str,
+        user_id: &str,
+    ) -> Result<Vec<RefundRequest>, Error> {
+        let (refunds, _) = self
+            .repository
+            .query_refund_requests(
+                RefundRequestQuery {
+                    walk_request_id: Some(request_id.to_owned()),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await?;
+        Ok(refunds
+            .into_iter()
+            .filter(|r| r.owner_id == user_id || r.walker_id == user_id)
+            .collect())
+    }
+
+    pub async fn refund_requests(
+        &self,
+        status: Option<RefundStatus>,
+        pagination: Pagination,
+    ) -> Result<(Vec<RefundRequest>, i64), Error> {
+        self.repository
+            .query_refund_requests(
+                RefundRequestQuery {
+                    status,
+                    ..Default::default()
+                },
+                Some(pagination),
+            )
+            .await
+    }
+
+    // 批准时可以只退部分金额, 默认全额. 退款按比例由遛狗人收入和平台服务费分担,
+    // 遛狗人承担的部分从账本扣回, 余额不足时可以为负
+    pub async fn review_refund(
+        &self,
+        id: &str,
+        reviewer_id: &str,
+        approved: bool,
+        amount: Option<i64>,
+        note: Option<String>,
+    ) -> Result<RefundRequest, Error> {
+        let (status, approved_amount, walker_debit, refund_limit) = if approved {
+            let (refunds, _) = self
+                .repository
+                .query_refund_requests(
+                    RefundRequestQuery {
+                        id: Some(id.to_owned()),
+                        status: Some(RefundStatus::Requested),
+                        ..Default::default()
+                    },
+                    None,
+                )
+                .await?;
+            let refund = refunds
+                .first()
+                .ok_or(Error::not_found("退款申请不存在或已处理"))?;
+            let amount = amount.unwrap_or(refund.amount);
+            if amount <= 0 || amount > refund.amount {
+                return Err(Error::msg("批准金额不能超过申请金额"));
+            }
+            // 申请时的可退金额检查与审核之间可能有其他退款获批, 由仓储在审核事务内按此上限重新核对
+            let request = self
+                .repository
+                .get_walk_request(&refund.walk_request_id)
+                .await?;
+            (
+                RefundStatus::Approved,
+                Some(amount),
+                Some(amount - platform_fee(amount)),
+                walk_quote(request.dogs.len()).price,
+            )
+        } else {
+            (RefundStatus::Rejected, None, None, i64::MAX)
+        };
+        self.repository
+            .review_refund_request(RefundReview {
+                id: id.to_owned(),
+                reviewed_by: reviewer_id.to_owned(),
+                status,
+                approved_amount,
+                walker_debit,
+                review_note: note,
+                refund_limit,
+            })
+            .await?
+            .ok_or(Error::not_found("退款申请不存在或已处理"))
+    }
+
+    // 狗狗主人邀请已报名或接单的遛狗人在遛狗前见面. 见面须在遛狗开始前结束,
+    // 同一遛狗人同时只能有一个待进行的见面, 且时间不能与双方的其他安排冲突
+    pub async fn propose_meet_and_greet(
+        &self,
+        create: MeetAndGreetCreate,
+    ) -> Result<MeetAndGreet, Error> {
+        let minutes = (create.ends_at - create.starts_at).num_minutes();
+        if !(MIN_MEET_AND_GREET_MINUTES..=MAX_MEET_AND_GREET_MINUTES).contains(&minutes) {
+            return Err(Error::msg(&format!(
+                "见面时长应在{}到{}分钟之间",
+                MIN_MEET_AND_GREET_MINUTES, MAX_MEET_AND_GREET_MINUTES
+            )));
+        }
+        if create.starts_at <= Utc::now() {
+            return Err(Error::msg("见面时间必须晚于当前时间"));
+        }
+        let request = self
+            .repository
+            .get_walk_request(&create.walk_request_id)
+            .await?;
+        if request.created_by != create.owner_id {
+            return Err(Error::forbidden("只有狗狗主人可以发起见面"));
+        }
+        if request.canceled_at.is_some() || request.started_at.is_some() {
+            return Err(Error::msg("遛狗请求已取消或已开始"));
+        }
+        let applied = request
+            .acceptances
+            .as_ref()
+            .is_some_and(|a| a.contains(&create.walker_id));
+        if !applied && request.accepted_by.as_deref() != Some(create.walker_id.as_str()) {
+            return Err(Error::msg("只能邀请已报名或接单的遛狗人见面"));
+        }
+        if request
+            .should_start_after
+            .or(request.should_start_before)
+            .is_some_and(|start| create.ends_at > start)
+        {
+            return Err(Error::msg("见面须在遛狗开始前结束"));
+        }
+        let pending = self
+            .repository
+            .query_meet_and_greets(MeetAndGreetQuery {
+                walk_request_id: Some(create.walk_request_id.clone()),
+                walker_id: Some(create.walker_id.clone()),
+                status_in: Some(vec![
+                    MeetAndGreetStatus::Proposed,
+                    MeetAndGreetStatus::Accepted,
+                ]),
+                ..Default::default()
+            })
+            .await?;
+        if !pending.is_empty() {
+            return Err(Error::conflict("已有与该遛狗人待进行的见面"));
+        }
+        self.check_meet_and_greet_conflicts(
+            &create.owner_id,
+            &create.walker_id,
+            create.starts_at,
+            create.ends_at,
+            None,
+        )
+        .await?;
+        let walker_id = create.walker_id.clone();
+        let id = self.repository.create_meet_and_greet(create).await?;
+        self.notify(
+            &walker_id,
+            NotificationKind::MeetAndGreetProposed,
+            &request.id.to_string(),
+            "狗狗主人邀请你在遛狗前见面",
+            false,
+        )
+        .await?;
+        self.meet_and_greet(&id, &walker_id).await
+    }
+
+    // 与双方待进行的见面重叠, 或与遛狗人已接单遛狗的时间窗口重叠时返回冲突错误
+    async fn check_meet_and_greet_conflicts(
+        &self,
+        owner_id: &str,
+        walker_id: &str,
+        starts_at: DateTime<Utc>,
+        ends_at: DateTime<Utc>,
+        exclude_id: Option<&str>,
+    ) -> Result<(), Error> {
+        let overlapping = self
+            .repository
+            .query_meet_and_greets(MeetAndGreetQuery {
+                participants_any: Some(vec![owner_id.to_owned(), walker_id.to_owned()]),
+                status_in: Some(vec![
+                    MeetAndGreetStatus::Proposed,
+                    MeetAndGreetStatus::Accepted,
+                ]),
+                starts_before: Some(ends_at),
+                ends_after: Some(starts_at),
+                ..Default::default()
+            })
+            .await?;
+        if overlapping
+            .iter()
+            .any(|m| Some(m.id.as_str()) != exclude_id)
+        {
+            return Err(Error::conflict("该时间段与已有的见面冲突"));
+        }
+        let (walks, _) = self
+            .repository
+            .query_walk_requests(
+                WalkRequestQuery {
+                    accepted_by: Some(walker_id.to_owned()),
+                    ..Default::default()
+                },
+                None,
+                None,
+            )
+            .await?;
+        let busy = walks.iter().any(|w| {
+            w.canceled_at.is_none()
+                && w.finished_at.is_none()
+                && match (
+                    w.should_start_after.or(w.should_start_before),
+                    w.should_end_before.or(w.should_end_after),
+                ) {
+                    (Some(start), Some(end)) => start < ends_at && end > starts_at,
+                    _ => false,
+                }
+        });
+        if busy {
+            return Err(Error::conflict("该时间段遛狗人已有遛狗安排"));
+        }
+        Ok(())
+    }
+
+    // 只有见面的双方可以查看
+    pub async fn meet_and_greet(&self, id: &str, user_id: &str) -> Result<MeetAndGreet, Error> {
+        self.repository
+            .query_meet_and_greets(MeetAndGreetQuery {
+                id: Some(id.to_owned()),
+                participants_any: Some(vec![user_id.to_owned()]),
+                ..Default::default()
+            })
+            .await?
+            .pop()
+            .ok_or(Error::not_found("见面不存在"))
+    }
+
+    // 狗狗主人可以看到请求的全部见面, 遛狗人只能看到自己的
+    pub async fn meet_and_greets(
+        &self,
+        request_id: &str,
+        user_id: &str,
+    ) -> Result<Vec<MeetAndGreet>, Error> {
+        self.repository
+            .query_meet_and_greets(MeetAndGreetQuery {
+                walk_request_id: Some(request_id.to_owned()),
+                participants_any: Some(vec![user_id.to_owned()]),
+                ..Default::default()
+            })
+            .await
+    }
+
+    // 确认前再次检查冲突, 遛狗人的安排可能在邀请后发生了变化
+    pub async fn accept_meet_and_greet(
+        &self,
+        id: &str,
+        walker_id: &str,
+    ) -> Result<MeetAndGreet, Error> {
+        let current = self.meet_and_greet(id, walker_id).await?;
+        if current.walker_id != walker_id || current.status != MeetAndGreetStatus::Proposed {
+            return Err(Error::not_found("见面不存在或状态已变更"));
+        }
+        if current.starts_at <= Utc::now() {
+            return Err(Error::msg("见面时间已过"));
+        }
+        self.check_meet_and_greet_conflicts(
+            &current.owner_id,
+            walker_id,
+            current.starts_at,
+            current.ends_at,
+            Some(id),
+        )
+        .await?;
+        let meet_and_greet = self
+            .transition_meet_and_greet(
+                MeetAndGreetQuery {
+                    id: Some(id.to_owned()),
+                    walker_id: Some(walker_id.to_owned()),
+                    status_in: Some(vec![MeetAndGreetStatus::Proposed]),
+                    ..Default::default()
+                },
+                MeetAndGreetUpdate {
+                    status: Some(MeetAndGreetStatus::Accepted),
+                    ..Default::default()
+                },
+            )
+            .await?;
+        self.notify(
+            &meet_and_greet.owner_id,
+            NotificationKind::MeetAndGreetAccepted,
+            &meet_and_greet.walk_request_id,
+            "遛狗人已确认见面",
+            false,
+        )
+        .await?;
+        Ok(meet_and_greet)
+    }
+
+    pub async fn decline_meet_and_greet(
+        &self,
+        id: &str,
+        walker_id: &str,
+    ) -> Result<MeetAndGreet, Error> {
+        let meet_and_greet = self
+            .transition_meet_and_greet(
+                MeetAndGreetQuery {
+                    id: Some(id.to_owned()),
+                    walker_id: Some(walker_id.to_owned()),
+                    status_in: Some(vec![MeetAndGreetStatus::Proposed]),
+                    ..Default::default()
+                },
+                MeetAndGreetUpdate {
+                    status: Some(MeetAndGreetStatus::Declined),
+                    canceled_by: Some(walker_id.to_owned()),
+                },
+            )
+            .await?;
+        self.notify(
+            &meet_and_greet.owner_id,
+            NotificationKind::MeetAndGreetCanceled,
+            &meet_and_greet.walk_request_id,
+            "遛狗人婉拒了见面邀请",
+            false,
+        )
+        .await?;
+        Ok(meet_and_greet)
+    }
+
+    // 任一方都可以取消待进行的见面, 并通知另一方
+    pub async fn cancel_meet_and_greet(
+        &self,
+        id: &str,
+        user_id: &str,
+    ) -> Result<MeetAndGreet, Error> {
+        let meet_and_greet = self
+            .transition_meet_and_greet(
+                MeetAndGreetQuery {
+                    id: Some(id.to_owned()),
+                    participants_any: Some(vec![user_id.to_owned()]),
+                    status_in: Some(vec![
+                        MeetAndGreetStatus::Proposed,
+                        MeetAndGreetStatus::Accepted,
+                    ]),
+                    ..Default::default()
+                },
+                MeetAndGreetUpdate {
+                    status: Some(MeetAndGreetStatus::Canceled),
+                    canceled_by: Some(user_id.to_owned()),
+                },
+            )
+            .await?;
+        let (other, content) = if meet_and_greet.owner_id == user_id {
+            (&meet_and_greet.walker_id, "狗狗主人已取消见面")
+        } else {
+            (&meet_and_greet.owner_id, "遛狗人已取消见面")
+        };
+        self.notify(
+            other,
+            NotificationKind::MeetAndGreetCanceled,
+            &meet_and_greet.walk_request_id,
+            content,
+            false,
+        )
+        .await?;
+        Ok(meet_and_greet)
+    }
+
+    // 已确认的见面到开始时间后, 任一方可以标记为已完成
+    pub async fn complete_meet_and_greet(
+        &self,
+        id: &str,
+        user_id: &str,
+    ) -> Result<MeetAndGreet, Error> {
+        self.transition_meet_and_greet(
+            MeetAndGreetQuery {
+                id: Some(id.to_owned()),
+                participants_any: Some(vec![user_id.to_owned()]),
+                status_in: Some(vec![MeetAndGreetStatus::Accepted]),
+                starts_before: Some(Utc::now()),
+                ..Default::default()
+            },
+            MeetAndGreetUpdate {
+                status: Some(MeetAndGreetStatus::Completed),
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    async fn transition_meet_and_greet(
+        &self,
+        query: MeetAndGreetQuery,
+        update: MeetAndGreetUpdate,
+    ) -> Result<MeetAndGreet, Error> {
+        self.repository
+            .update_meet_and_greet_by_query(query, update)
+            .await?
+            .ok_or(Error::not_found("见面不存在或状态已变更"))
+    }
+
+    pub async fn start_walk(&self, request_id: &str, user_id: &str) -> Result<WalkRequest, Error> {
+        let request = self
+            .repository
+            .update_walk_request_by_query(
+                WalkRequestQuery {
+                    id: Some(request_id.to_owned()),
+                    accepted_by: Some(user_id.to_owned()),
+                    ..Default::default()
+                },
+                WalkRequestUpdate {
+                    started_at: Some(Utc::now()),
+                    actor_id: Some(user_id.to_owned()),
+                    ..Default::default()
+                },
+            )
+            .await?;
+        self.notify(
+            &request.created_by,
+            NotificationKind::WalkStarted,
+            request_id,
+            "遛狗人已接到狗狗, 遛狗开始",
+            false,
+        )
+        .await?;
+        Ok(request)
+    }
+
+    pub async fn record_walking_location(
+        &self,
+        walk_request_id: &str,
+        longitude: f64,
+        latitute: f64,
+    ) -> Result<String, Error> {
+        self.repository
+            .create_walking_location(WalkingLocationCreate {
+                walk_request_id,
+                longitude,
+                latitude: latitute,
+            })
+            .await
+    }
+
+    // 设定了路线偏好的请求在结束时评估实际轨迹的偏离程度, 与结束时间一并保存. 只有已开始且未结束、未取消的遛狗
+    // 可以结束, 结束成功后为遛狗人记入收入, 合成监控的测试遛狗人除外
+    pub async fn finish_walk(&self, request_id: &str, user_id: &str) -> Result<WalkRequest, Error> {
+        let existing = self.repository.get_walk_request(request_id).await?;
+        if existing.accepted_by.as_deref() != Some(user_id) {
+            return Err(Error::not_found("代遛请求不存在"));
+        }
+        let route_assessment = match existing.route_preference {
+            Some(route_preference) => Some(assess_route(
+                &self.walked_route(request_id).await?,
+                &route_preference,
+            )),
+            None => None,
+        };
+        let request = self
+            .repository
+            .update_walk_request_by_query(
+                WalkRequestQuery {
+                    id: Some(request_id.to_owned()),
+                    accepted_by: Some(user_id.to_owned()),
+                    started_at_is_null: Some(false),
+                    finished_at_is_null: Some(true),
+                    canceled_at_is_null: Some(true),
+                    ..Default::default()
+                },
+                WalkRequestUpdate {
+                    finished_at: Some(Utc::now()),
+                    route_assessment,
+                    actor_id: Some(user_id.to_owned()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .map_err(|e| match e.kind() {
+                ErrorKind::NotFound => Error::conflict("遛狗未开始或已结束"),
+                _ => e,
+            })?;
+        if !self.is_synthetic_walker(user_id) {
+            self.repository
+                .credit_walk_earning(
+                    user_id,
+                    request_id,
+                    walk_quote(request.dogs.len()).walker_earning,
+                )
+                .await?;
+        }
+        self.walker_stats_cache.invalidate(user_id);
+        self.notify(
+            &request.created_by,
+            NotificationKind::WalkFinished,
+            request_id,
+            "遛狗已结束, 欢迎评价本次服务",
+            false,
+        )
+        .await?;
+        Ok(request)
+    }
+
+    // 遛狗结束前狗狗主人可以修改路线偏好, 偏好为空时清除. 指定了期望版本时, 请求已被修改返回冲突错误
+    pub async fn update_route_preference(
+        &self,
+        request_id: &str,
+        user_id: &str,
+        route_preference: RoutePreference,
+        expected_version: Option<i64>,
+    ) -> Result<WalkRequest, Error> {
+        validate_route_preference(&route_preference)?;
+        let request = self.repository.get_walk_request(request_id).await?;
+        if request.created_by != user_id {
+            return Err(Error::not_found("请求不存在"));
+        }
+        if request.finished_at.is_some() || request.canceled_at.is_some() {
+            return Err(Error::msg("遛狗已结束, 无法修改路线偏好"));
+        }
+        let update = if route_preference.is_empty() {
+            WalkRequestUpdate {
+                route_preference: UpdateField::Unset,
+                expected_version,
+                ..Default::default()
+            }
+        } else {
+            WalkRequestUpdate {
+                route_preference: UpdateField::Set(route_preference),
+                expected_version,
+                ..Default::default()
+            }
+        };
+        self.repository
+            .update_walk_request_by_query(
+                WalkRequestQuery {
+                    id: Some(request_id.to_owned()),
+                    created_by: Some(user_id.to_owned()),
+                    ..Default::default()
+                },
+                update,
+            )
+            .await
+    }
+
+    // 未被接单的请求对所有遛狗人公开, 接单后只有狗狗主人和接单的遛狗人可以查看路线偏好
+    pub async fn route_preference(
+        &self,
+        request_id: &str,
+        user_id: &str,
+    ) -> Result<Option<RoutePreference>, Error> {
+        let request = self.repository.get_walk_request(request_id).await?;
+        if request.created_by != user_id
+            && request
+                .accepted_by
+                .as_ref()
+                .is_some_and(|walker_id| walker_id != user_id)
+        {
+            return Err(Error::not_found("请求不存在"));
+        }
+        Ok(request.route_preference)
+    }
+
+    // 遛狗结束后对比实际轨迹与路线偏好, 狗狗主人和接单的遛狗人可以查看
+    pub async fn walk_route_report(
+        &self,
+        request_id: &str,
+        user_id: &str,
+    ) -> Result<WalkRouteReport, Error> {
+        let request = self.repository.get_walk_request(request_id).await?;
+        if request.created_by != user_id && request.accepted_by.as_deref() != Some(user_id) {
+            return Err(Error::not_found("请求不存在"));
+        }
+        if request.finished_at.is_none() {
+            return Err(Error::msg("遛狗尚未结束"));
+        }
+        let locations = self.repository.query_walking_locations(request_id).await?;
+        let route = locations
+            .iter()
+            .map(|l| GeoPoint {
+                longitude: l.longitude,
+                latitude: l.latitude,
+            })
+            .collect::<Vec<GeoPoint>>();
+        let pois = self.pois_near_route(&route).await?;
+        // 结束时没有保存评估的请求(如结束后才引入评估)即时计算
+        let route_assessment = request.route_assessment.or_else(|| {
+            request
+                .route_preference
+                .as_ref()
+                .map(|p| assess_route(&route, p))
+        });
+        Ok(WalkRouteReport {
+            walk_request_id: request.id.into(),
+            route,
+            route_preference: request.route_preference,
+            route_assessment,
+            poi_visits: poi_visits(&locations, &pois),
+        })
+    }
+
+    // 以轨迹外接矩形的中心查询兴趣点, 查询半径再放宽兴趣点范围的上限, 保证边缘经过的兴趣点也能查到
+    async fn pois_near_route(&self, route: &[GeoPoint]) -> Result<Vec<Poi>, Error> {
+        if route.is_empty() {
+            return Ok(Vec::new());
+        }
+        let (min_lng, max_lng, min_lat, max_lat) = route.iter().fold(
+            (f64::MAX, f64::MIN, f64::MAX, f64::MIN),
+            |(min_lng, max_lng, min_lat, max_lat), p| {
+                (
+                    min_lng.min(p.longitude),
+                    max_lng.max(p.longitude),
+                    min_lat.min(p.latitude),
+                    max_lat.max(p.latitude),
+                )
+            },
+        );
+        let center = GeoPoint {
+            longitude: (min_lng + max_lng) / 2.0,
+            latitude: (min_lat + max_lat) / 2.0,
+        };
+        let radius = route
+            .iter()
+            .map(|p| haversine_distance(center.longitude, center.latitude, p.longitude, p.latitude))
+            .fold(0.0, f64::max);
+        self.repository
+            .query_pois(PoiQuery {
+                near: Some(center),
+                max_distance: Some(radius + MAX_POI_RADIUS),
+            })
+            .await
+    }
+
+    // 兴趣点的范围不超过中心点MAX_POI_RADIUS米, 边界多边形至少三个顶点
+    pub async fn create_poi(&self, create: PoiCreate) -> Result<String, Error> {
+        if create.name.trim().is_empty() {
+            return Err(Error::msg("请填写地点名称"));
+        }
+        if !(-180.0..=180.0).contains(&create.longitude)
+            || !(-90.0..=90.0).contains(&create.latitude)
+        {
+            return Err(Error::msg("无效的坐标"));
+        }
+        if !create.boundary.is_empty() && create.boundary.len() < 3 {
+            return Err(Error::msg("地点边界至少需要三个顶点"));
+        }
+        let out_of_range = create.boundary.iter().any(|p| {
+            haversine_distance(create.longitude, create.latitude, p.longitude, p.latitude)
+                > MAX_POI_RADIUS
+        });
+        if out_of_range || create.radius < 0.0 || create.radius > MAX_POI_RADIUS {
+            return Err(Error::msg(&format!("地点范围不能超过{}米", MAX_POI_RADIUS)));
+        }
+        if create.boundary.is_empty() && create.radius == 0.0 {
+            return Err(Error::msg("请设置地点半径或边界"));
+        }
+        self.repository.create_poi(create).await
+    }
+
+    pub async fn pois(&self, query: PoiQuery) -> Result<Vec<Poi>, Error> {
+        self.repository.query_pois(query).await
+    }
+
+    pub async fn delete_poi(&self, id: &str) -> Result<(), Error> {
+        if !self.repository.delete_poi(id).await? {
+            return Err(Error::not_found("地点不存在"));
+        }
+        Ok(())
+    }
+
+    async fn walked_route(&self, request_id: &str) -> Result<Vec<GeoPoint>, Error> {
+        Ok(self
+            .repository
+            .query_walking_locations(request_id)
+            .await?
+            .into_iter()
+            .map(|l| GeoPoint {
+                longitude: l.longitude,
+                latitude: l.latitude,
+            })
+            .collect())
+    }
+
+    pub async fn walker(&self, user_id: &str) -> Result<Walker, Error> {
+        let mut walker = self
+            .repository
+            .get_walker(user_id)
+            .await?
+            .unwrap_or_else(|| Walker {
+                user_id: user_id.to_owned(),
+                ..Default::default()
+            });
+        walker.avatar_id = self
+            .repository
+            .get_owner(user_id)
+            .await?
+            .and_then(|owner| owner.avatar_id);
+        Ok(walker)
+    }
+
+    pub async fn is_verified_walker(&self, user_id: &str) -> Result<bool, Error> {
+        Ok(self
+            .repository
+            .get_walker(user_id)
+            .await?
+            .map(|w| w.verification_status == VerificationStatus::Verified)
+            .unwrap_or(false))
+    }
+
+    pub async fn submit_walker_verification(
+        &self,
+        user_id: &str,
+        id_document_ids: Vec<String>,
+    ) -> Result<Walker, Error> {
+        if id_document_ids.is_empty() {
+            return Err(Error::msg("请上传身份证件"));
+        }
+        if self.is_verified_walker(user_id).await? {
+            return Err(Error::msg("已通过身份认证"));
+        }
+        self.repository
+            .submit_walker_verification(WalkerVerificationSubmit {
+                user_id,
+                id_document_ids,
+            })
+            .await
+    }
+
+    pub async fn approve_walker_verification(&self, user_id: &str) -> Result<(), Error> {
+        self.repository
+            .update_walkers_by_query(
+                WalkerQuery {
+                    user_id: Some(user_id.to_owned()),
+                    verification_status: Some(VerificationStatus::Pending),
+                },
+                WalkerUpdate {
+                    verification_status: Some(VerificationStatus::Verified),
+                    reviewed_at: Some(Utc::now()),
+                    unset_rejected_reason: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .and_then(|n| {
+                if n == 1 {
+                    Ok(())
+                } else {
+                    Err(Error::not_found("认证申请不存在或已处理"))
+                }
+            })?;
+        self.email(
+            user_id,
+            EmailKind::VerificationDecision,
+            &email::VERIFICATION_APPROVED,
+            &[],
+        )
+        .await
+    }
+
+    pub async fn reject_walker_verification(
+        &self,
+        user_id: &str,
+        reason: &str,
+    ) -> Result<(), Error> {
+        self.repository
+            .update_walkers_by_query(
+                WalkerQuery {
+                    user_id: Some(user_id.to_owned()),
+                    verification_status: Some(VerificationStatus::Pending),
+                },
+                WalkerUpdate {
+                    verification_status: Some(VerificationStatus::Rejected),
+                    rejected_reason: Some(reason.to_owned()),
+                    reviewed_at: Some(Utc::now()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .and_then(|n| {
+                if n == 1 {
+                    Ok(())
+                } else {
+                    Err(Error::not_found("认证申请不存在或已处理"))
+                }
+            })?;
+        self.email(
+            user_id,
+            EmailKind::VerificationDecision,
+            &email::VERIFICATION_REJECTED,
+            &[("reason", reason)],
+        )
+        .await
+    }
+
+    pub async fn walker_verifications(
+        &self,
+        status: Option<VerificationStatus>,
+        pagination: Pagination,
+    ) -> Result<(Vec<Walker>, i64), Error> {
+        self.repository
+            .query_walkers(
+                WalkerQuery {
+                    verification_status: status,
+                    ..Default::default()
+                },
+                Some(pagination),
+            )
+            .await
+    }
+
+    pub async fn explain(&self, template: QueryTemplate) -> Result<QueryPlan, Error> {
+        self.repository.explain(template).await
+    }
+
+    pub async fn update_walker_location(
+        &self,
+        user_id: &str,
+        longitude: f64,
+        latitude: f64,
+    ) -> Result<(), Error> {
+        self.repository
+            .update_walker_location(user_id, longitude, latitude)
+            .await?;
+        self.notify_walker_nearby(user_id, longitude, latitude)
+            .await
+    }
+
+    // 已接单且尚未开始的请求, 狗狗主人开启了通知时, 遛狗人在开始时间之前进入接狗地点附近即通知一次
+    async fn notify_walker_nearby(
+        &self,
+        walker_id: &str,
+        longitude: f64,
+        latitude: f64,
+    ) -> Result<(), Error> {
+        let now = Utc::now();
+        let (requests, _) = self
+            .repository
+            .query_walk_requests(
+                WalkRequestQuery {
+                    accepted_by: Some(walker_id.to_owned()),
+                    started_at_is_null: Some(true),
+                    ..Default::default()
+                },
+                None,
+                None,
+            )
+            .await?;
+        for request in requests {
+            if !request.notify_walker_nearby
+                || request.walker_nearby_notified_at.is_some()
+                || request.canceled_at.is_some()
+                || request.should_start_before.is_some_and(|t| t <= now)
+            {
+                continue;
+            }
+            let distance =
+                haversine_distance(longitude, latitude, request.longitude, request.latitude);
+            if distance > WALKER_NEARBY_RADIUS {
+                continue;
+            }
+            if !self
+                .repository
+                .mark_walker_nearby_notified(&request.id)
+                .await?
+            {
+                continue;
+            }
+            self.repository
+                .create_notification(NotificationCreate {
+                    user_id: request.created_by,
+                    kind: NotificationKind::WalkerNearby,
+                    walk_request_id: Some(request.id.into()),
+                    content: format!("遛狗人已到达接狗地点附近(约{}米)", distance.round()),
+                    sms: false,
+                })
+                .await?;
+        }
+        Ok(())
+    }
+
+    // 记录站内通知, 由推送任务推送到用户登记的设备. sms为true的紧急通知同时发送短信
+    async fn notify(
+        &self,
+        user_id: &str,
+        kind: NotificationKind,
+        walk_request_id: &str,
+        content: &str,
+        sms: bool,
+    ) -> Result<(), Error> {
+        self.repository
+            .create_notification(NotificationCreate {
+                user_id: user_id.to_owned(),
+                kind,
+                walk_request_id: Some(walk_request_id.to_owned()),
+                content: content.to_owned(),
+                sms,
+            })
+            .await
+            .map(|_| ())
+    }
+
+    // 逐条领取最近未推送的通知推送到用户的所有设备, 紧急通知还发送短信给开启了短信通知的用户.
+    // 推送失败不重试, 用户仍可在站内通知中看到, 推送通道报告失效的令牌随即删除
+    pub async fn push_notifications<N, S>(
+        &self,
+        notifier: &N,
+        sms_sender: &S,
+    ) -> Result<PushSummary, Error>
+    where
+        N: Notifier,
+        S: SmsSender,
+    {
+        let created_after = Utc::now() - chrono::Duration::minutes(PUSH_MAX_DELAY_MINUTES);
+        let mut summary = PushSummary::default();
+        while let Some(notification) = self
+            .repository
+            .claim_unpushed_notification(created_after)
+            .await?
+        {
+            let message = PushMessage {
+                kind: notification.kind,
+                title: push_title(notification.kind).to_owned(),
+                body: notification.content,
+                walk_request_id: notification.walk_request_id,
+            };
+            for device in self
+                .repository
+                .query_device_tokens(&notification.user_id)
+                .await?
+            {
+                match notifier.push(&device, &message).await {
+                    Ok(PushOutcome::Delivered) => summary.delivered += 1,
+                    Ok(PushOutcome::Unregistered) => {
+                        summary.unregistered += self
+                            .repository
+                            .delete_device_tokens(DeviceTokenQuery {
+                                token: Some(device.token),
+                                ..Default::default()
+                            })
+                            .await?;
+                    }
+                    Err(e) => summary.failures.push(format!(
+                        "notification {} to {} device: {}",
+                        notification.id, device.platform, e
+                    )),
+                }
+            }
+            if !notification.sms {
+                continue;
+            }
+            let Some(phone) = self
+                .repository
+                .get_notification_settings(&notification.user_id)
+                .await?
+                .filter(|settings| settings.sms_enabled)
+                .and_then(|settings| settings.sms_phone)
+            else {
+                continue;
+            };
+            match sms_sender
+                .send(&phone, &format!("【{}】{}", message.title, message.body))
+                .await
+            {
+                Ok(()) => summary.sms_sent += 1,
+                Err(e) => summary
+                    .failures
+                    .push(format!("notification {} by sms: {}", notification.id, e)),
+            }
+        }
+        Ok(summary)
+    }
+
+    pub async fn notification_settings(
+        &self,
+        user_id: &str,
+    ) -> Result<NotificationSettings, Error> {
+        Ok(self
+            .repository
+            .get_notification_settings(user_id)
+            .await?
+            .unwrap_or(NotificationSettings {
+                user_id: user_id.to_owned(),
+                ..Default::default()
+            }))
+    }
+
+    // 开启短信通知需要手机号, 更换手机号时需先向新号码发送登录验证码, 确认号码属于本人.
+    // 手机号和邮箱为空时保留原来的设置
+    pub async fn update_notification_settings(
+        &self,
+        mut upsert: NotificationSettingsUpsert,
+        code: Option<&str>,
+    ) -> Result<NotificationSettings, Error> {
+        let current = self.notification_settings(&upsert.user_id).await?;
+        upsert.sms_phone = match upsert.sms_phone {
+            Some(phone) if current.sms_phone.as_deref() != Some(phone.as_str()) => {
+                let code = code.ok_or(Error::msg("更换手机号需要填写验证码"))?;
+                self.verify_otp(&phone, OtpPurpose::Login, code).await?;
+                Some(phone)
+            }
+            _ => current.sms_phone,
+        };
+        if upsert.sms_enabled && upsert.sms_phone.is_none() {
+            return Err(Error::msg("开启短信通知需要填写手机号"));
+        }
+        upsert.email = upsert.email.or(current.email);
+        if (upsert.email_receipts
+            || upsert.email_weekly_summary
+            || upsert.email_verification_decisions)
+            && upsert.email.is_none()
+        {
+            return Err(Error::msg("开启邮件通知需要填写邮箱"));
+        }
+        self.repository.upsert_notification_settings(upsert).await
+    }
+
+    // 用户开启了该类邮件时按模板渲染并入队, 由邮件任务发送
+    async fn email(
+        &self,
+        user_id: &str,
+        kind: EmailKind,
+        template: &Template,
+        values: &[(&str, &str)],
+    ) -> Result<(), Error> {
+        let settings = self.notification_settings(user_id).await?;
+        let Some(to) = settings.email_to(kind) else {
+            return Ok(());
+        };
+        let rendered = template.render(values);
+        self.repository
+            .create_email(EmailCreate {
+                user_id: user_id.to_owned(),
+                kind,
+                to: to.to_owned(),
+                subject: rendered.subject,
+                text: rendered.text,
+                html: rendered.html,
+            })
+            .await
+            .map(|_| ())
+    }
+
+    // 每周一(UTC)起为开启了周报的用户汇总上一周完成的遛狗, 每个用户每周只发送一次
+    pub async fn enqueue_weekly_summaries(&self) -> Result<u64, Error> {
+        let now = Utc::now();
+        let week_start = (now
+            - chrono::Duration::days(now.weekday().num_days_from_monday() as i64))
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .ok_or(Error::new("invalid week start"))?
+        .and_utc();
+        let from = week_start - chrono::Duration::weeks(1);
+        let mut enqueued = 0;
+        while let Some(settings) = self
+            .repository
+            .claim_weekly_summary_recipient(week_start)
+            .await?
+        {
+            let (walks, _) = self
+                .repository
+                .query_walk_requests(
+                    WalkRequestQuery {
+                        involves: Some(settings.user_id.clone()),
+                        updated_after: Some(from),
+                        ..Default::default()
+                    },
+                    None,
+                    None,
+                )
+                .await?;
+            let finished = walks
+                .iter()
+                .filter(|w| {
+                    w.canceled_at.is_none()
+                        && w.finished_at.is_some_and(|t| t >= from && t < week_start)
+                        && (w.created_by == settings.user_id
+                            || w.accepted_by.as_deref() == Some(&settings.user_id))
+                })
+                .collect::<Vec<&WalkRequest>>();
+            let owner_walks = finished
+                .iter()
+                .filter(|w| w.created_by == settings.user_id)
+                .count();
+            let minutes = finished
+                .iter()
+                .filter_map(|w| Some((w.finished_at? - w.started_at?).num_minutes()))
+                .sum::<i64>();
+            self.email(
+                &settings.user_id,
+                EmailKind::WeeklySummary,
+                &email::WEEKLY_SUMMARY,
+                &[
+                    ("from", &from.format("%Y-%m-%d").to_string()),
+                    (
+                        "to",
+                        &(week_start - chrono::Duration::days(1))
+                            .format("%Y-%m-%d")
+                            .to_string(),
+                    ),
+                    ("owner_walks", &owner_walks.to_string()),
+                    ("walker_walks", &(finished.len() - owner_walks).to_string()),
+                    ("minutes", &minutes.to_string()),
+                ],
+            )
+            .await?;
+            enqueued += 1;
+        }
+        Ok(enqueued)
+    }
+
+    // 逐封领取最近未发送的邮件发送. 发送失败不重试, 原因由调用方记录
+    pub async fn send_emails<M>(&self, mailer: &M) -> Result<EmailSummary, Error>
+    where
+        M: Mailer,
+    {
+        let created_after = Utc::now() - chrono::Duration::hours(EMAIL_MAX_DELAY_HOURS);
+        let mut summary = EmailSummary::default();
+        while let Some(email) = self.repository.claim_unsent_email(created_after).await? {
+            match mailer.send(&email).await {
+                Ok(()) => summary.sent += 1,
+                Err(e) => summary
+                    .failures
+                    .push(format!("{} email {}: {}", email.kind, email.id, e)),
+            }
+        }
+        Ok(summary)
+    }
+
+    pub async fn register_device_token(
+        &self,
+        user_id: &str,
+        platform: PushPlatform,
+        token: &str,
+    ) -> Result<(), Error> {
+        self.repository
+            .upsert_device_token(DeviceTokenUpsert {
+                user_id: user_id.to_owned(),
+                platform,
+                token: token.to_owned(),
+            })
+            .await
+    }
+
+    // 退出登录或关闭通知时由App注销, 只能注销自己登记的令牌
+    pub async fn unregister_device_token(&self, user_id: &str, token: &str) -> Result<(), Error> {
+        self.repository
+            .delete_device_tokens(DeviceTokenQuery {
+                user_id: Some(user_id.to_owned()),
+                token: Some(token.to_owned()),
+            })
+            .await
+            .and_then(|n| {
+                if n == 1 {
+                    Ok(())
+                } else {
+                    Err(Error::not_found("推送令牌不存在"))
+                }
+            })
+    }
+
+    pub async fn my_notifications(
+        &self,
+        user_id: &str,
+        pagination: Pagination,
+    ) -> Result<Vec<Notification>, Error> {
+        self.repository
+            .query_notifications(user_id, pagination)
+            .await
+    }
+
+    // 搜索结果包含遛狗人的实时位置, 返回前记录访问
+    pub async fn search_walkers(
+        &self,
+        accessor_id: &str,
+        search: WalkerSearch,
+    ) -> Result<Vec<RankedWalker>, Error> {
+        if search.radius <= 0.0 {
+            return Err(Error::msg("搜索半径必须大于0"));
+        }
+        // 分页参数直接用于聚合管道的$skip和$limit, 不合法的值会导致查询失败
+        if !(1..=MAX_WALKER_SEARCH_LIMIT).contains(&search.pagination.limit) {
+            return Err(Error::msg(&format!(
+                "每页数量应在1到{}之间",
+                MAX_WALKER_SEARCH_LIMIT
+            )));
+        }
+        if search.pagination.skip < 0 {
+            return Err(Error::msg("跳过数量不能为负数"));
+        }
+        let walkers = self.repository.search_walkers(search).await?;
+        if !walkers.is_empty() {
+            self.record_location_access(
+                accessor_id,
+                LocationAccessKind::LivePosition,
+                None,
+                walkers.iter().map(|w| w.user_id.clone()).collect(),
+            )
+            .await?;
+        }
+        Ok(walkers)
+    }
+
+    // 读取轨迹或实时位置前必须调用, 记录失败时不返回位置数据
+    pub async fn record_location_access(
+        &self,
+        accessor_id: &str,
+        kind: LocationAccessKind,
+        walk_request_id: Option<&str>,
+        subject_ids: Vec<String>,
+    ) -> Result<(), Error> {
+        self.repository
+            .create_location_access(LocationAccessCreate {
+                accessor_id: accessor_id.to_owned(),
+                kind,
+                walk_request_id: walk_request_id.map(|id| id.to_owned()),
+                subject_ids,
+            })
+            .await?;
+        Ok(())
+    }
+
+    // 仍被狗狗头像、用户头像、证件或工单引用的文件不能删除, 上传者校验由调用方完成
+    pub async fn delete_upload(&self, upload_id: &str) -> Result<(), Error> {
+        if self.repository.count_upload_references(upload_id).await? > 0 {
+            return Err(Error::msg("文件仍在使用中"));
+        }
+        self.repository
+            .delete_uploads(&[upload_id.to_owned()])
+            .await?;
+        Ok(())
+    }
+
+    // 清理超过宽限期仍未被引用的上传文件, 宽限期内客户端可能尚未提交引用该文件的数据
+    pub async fn collect_orphan_uploads(&self) -> Result<u64, Error> {
+        let ids = self
+            .repository
+            .query_unreferenced_uploads(
+                Utc::now() - chrono::Duration::hours(ORPHAN_UPLOAD_GRACE_HOURS),
+                ORPHAN_UPLOAD_BATCH,
+            )
+            .await?;
+        if ids.is_empty() {
+            return Ok(0);
+        }
+        self.repository.delete_uploads(&ids).await
+    }
+
+    // 检查各类悬空引用, apply_policies为true时按配置修复, 否则只报告
+    pub async fn check_integrity(&self, apply_policies: bool) -> Result<IntegrityReport, Error> {
+        let mut issues = Vec::new();
+        for kind in IntegrityIssueKind::ALL {
+            let mut found = self
+                .repository
+                .query_integrity_issues(kind, INTEGRITY_CHECK_LIMIT)
+                .await?;
+            let policy = self
+                .integrity_policies
+                .get(&kind)
+                .copied()
+                .unwrap_or_default();
+            if apply_policies && policy == IntegrityPolicy::Fix {
+                for issue in &mut found {
+                    issue.fixed = match kind {
+                        IntegrityIssueKind::WalkRequestDeletedDog => {
+                            self.remove_deleted_dog(&issue.record_id, &issue.reference_id)
+                                .await?
+                        }
+                        _ => self.repository.repair_integrity_issue(issue).await?,
+                    };
+                }
+            }
+            issues.extend(found);
+        }
+        Ok(IntegrityReport {
+            checked_at: Utc::now(),
+            issues,
+        })
+    }
+
+    // 与删除狗狗时的处理相同: 从未开始的请求中移除该狗狗, 没有其他狗狗时取消请求
+    async fn remove_deleted_dog(&self, request_id: &str, dog_id: &str) -> Result<bool, Error> {
+        let request = self.repository.get_walk_request(request_id).await?;
+        if request.started_at.is_some() || request.canceled_at.is_some() {
+            return Ok(false);
+        }
+        let request = self
+            .repository
+            .update_walk_request(request_id, without_dog(request.dogs, dog_id, None))
+            .await?;
+        self.nearby_cache
+            .invalidate(request.latitude, request.longitude);
+        Ok(true)
+    }
+
+    // 分批迁移尚未用当前密钥加密的字段, 直到某一批未满
+    pub async fn reencrypt_fields(&self) -> Result<u64, Error> {
+        let mut migrated = 0;
+        loop {
+            let n = self
+                .repository
+                .reencrypt_fields(FIELD_ENCRYPTION_BATCH)
+                .await?;
+            migrated += n;
+            if n < FIELD_ENCRYPTION_BATCH as u64 {
+                return Ok(migrated);
+            }
+        }
+    }
+
+    pub async fn location_accesses(
+        &self,
+        query: LocationAccessQuery,
+        pagination: Pagination,
+    ) -> Result<(Vec<LocationAccess>, i64), Error> {
+        self.repository
+            .query_location_accesses(query, pagination)
+            .await
+    }
+
+    pub async fn audit_logs(
+        &self,
+        query: AuditLogQuery,
+        pagination: Pagination,
+    ) -> Result<(Vec<AuditLog>, i64), Error> {
+        self.repository.query_audit_logs(query, pagination).await
+    }
+
+    // 狗狗主人评价遛狗人, 遛狗人评价狗狗主人, 每次遛狗每个方向只能评价一次
+    // 评价先隐藏, 双方都评价后一起公开, 见reveal_expired_reviews
+    pub async fn review_walk(
+        &self,
+        request_id: &str,
+        reviewer_id: &str,
+        rating: i32,
+        content: String,
+        mut photo_ids: Vec<String>,
+    ) -> Result<String, Error> {
+        if !(1..=5).contains(&rating) {
+            return Err(Error::msg("评分必须为1-5星"));
+        }
+        let mut seen = std::collections::HashSet::new();
+        photo_ids.retain(|id| seen.insert(id.clone()));
+        if photo_ids.len() > MAX_REVIEW_PHOTOS {
+            return Err(Error::msg(&format!(
+                "每条评价最多附带{}张照片",
+                MAX_REVIEW_PHOTOS
+            )));
+        }
+        let request = self.repository.get_walk_request(request_id).await?;
+        let owner_id = request.created_by.as_str();
+        let walker_id = match &request.accepted_by {
+            Some(walker_id) => walker_id.as_str(),
+            None if reviewer_id == owner_id => return Err(Error::msg("遛狗尚未完成")),
+            None => return Err(Error::forbidden("只有遛狗的双方可以评价")),
+        };
+        let kind = if reviewer_id == owner_id {
+            ReviewKind::OwnerToWalker
+        } else if reviewer_id == walker_id {
+            ReviewKind::WalkerToOwner
+        } else {
+            return Err(Error::forbidden("只有遛狗的双方可以评价"));
+        };
+        if request.finished_at.is_none() {
+            return Err(Error::msg("遛狗尚未完成"));
+        }
+        let (_, reviewed) = self
+            .repository
+            .query_reviews(
+                ReviewQuery {
+                    walk_request_id: Some(request_id.to_owned()),
+                    kind: Some(kind),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await?;
+        if reviewed > 0 {
+            return Err(Error::msg("已评价过该次遛狗"));
+        }
+        let id = self
+            .repository
+            .create_review(ReviewCreate {
+                walk_request_id: request_id,
+                walker_id,
+                owner_id,
+                kind,
+                rating,
+                content,
+                photo_ids,
+            })
+            .await?;
+        // 写入后再查对方的评价, 双方同时提交时至少有一方能看到另一方
+        let counterpart = match kind {
+            ReviewKind::OwnerToWalker => ReviewKind::WalkerToOwner,
+            ReviewKind::WalkerToOwner => ReviewKind::OwnerToWalker,
+        };
+        let (_, counterpart_reviews) = self
+            .repository
+            .query_reviews(
+                ReviewQuery {
+                    walk_request_id: Some(request_id.to_owned()),
+                    kind: Some(counterpart),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await?;
+        if counterpart_reviews > 0 && self.repository.reveal_reviews(request_id).await? > 0 {
+            self.walker_stats_cache.invalidate(walker_id);
+        }
+        Ok(id)
+    }
+
+    // 评价双方不能为该评价投票, 重复投票不报错, 返回是否计入新的一票
+    pub async fn vote_review_helpful(&self, review_id: &str, user_id: &str) -> Result<bool, Error> {
+        let (reviews, _) = self
+            .repository
+            .query_reviews(
+                ReviewQuery {
+                    id: Some(review_id.to_owned()),
+                    state: Some(ReviewState::Revealed),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await?;
+        let review = reviews
+            .into_iter()
+            .next()
+            .ok_or(Error::not_found("评价不存在"))?;
+        if review.owner_id == user_id || review.walker_id == user_id {
+            return Err(Error::forbidden("不能为自己参与的评价投票"));
+        }
+        self.repository.create_review_vote(review_id, user_id).await
+    }
+
+    // 公开超过REVIEW_REVEAL_WINDOW_DAYS天对方仍未评价的评价, 遛狗人统计缓存按TTL过期
+    pub async fn reveal_expired_reviews(&self) -> Result<u64, Error> {
+        self.repository
+            .reveal_expired_reviews(Utc::now() - chrono::Duration::days(REVIEW_REVEAL_WINDOW_DAYS))
+            .await
+    }
+
+    pub async fn achievements(&self, user_id: &str) -> Result<Vec<Achievement>, Error> {
+        self.repository.query_achievements(user_id).await
+    }
+
+    // 根据累计数据补发用户尚未获得的成就, 返回新获得的成就数量
+    pub async fn award_achievements(&self) -> Result<u64, Error> {
+        let mut granted = 0;
+        for progress in self.repository.achievement_progress().await? {
+            let mut kinds = Vec::new();
+            if progress.finished_walks >= 1 {
+                kinds.push(AchievementKind::FirstWalk);
+            }
+            if progress.total_distance >= HUNDRED_KILOMETERS {
+                kinds.push(AchievementKind::HundredKilometers);
+            }
+            if progress.five_star_reviews >= FIVE_STAR_REVIEWS_FOR_ACHIEVEMENT {
+                kinds.push(AchievementKind::FiftyFiveStarReviews);
+            }
+            for kind in kinds {
+                if self
+                    .repository
+                    .grant_achievement(&progress.user_id, kind)
+                    .await?
+                {
+                    granted += 1;
+                }
+            }
+        }
+        Ok(granted)
+    }
+
+    pub async fn walker_stats(&self, walker_id: &str) -> Result<WalkerStats, Error> {
+        if let Some(stats) = self.walker_stats_cache.get(walker_id) {
+            return Ok(stats);
+        }
+        let stats = self.repository.walker_stats(walker_id).await?;
+        self.walker_stats_cache.put(stats.clone());
+        Ok(stats)
+    }
+
+    // 遛狗人的公开评价按有用票数排序, 票数相同时新的在前
+    pub async fn walker_reviews(
+        &self,
+        walker_id: &str,
+        pagination: Pagination,
+    ) -> Result<(Vec<Review>, i64), Error> {
+        self.repository
+            .query_reviews(
+                ReviewQuery {
+                    walker_id: Some(walker_id.to_owned()),
+                    kind: Some(ReviewKind::OwnerToWalker),
+                    state: Some(ReviewState::Revealed),
+                    sort_by: Some(SortBy {
+                        field: Review::helpful_count(),
+                        order: Order::Desc,
+                    }),
+                    ..Default::default()
+                },
+                Some(pagination),
+            )
+            .await
+    }
+
+    pub async fn owner_reviews(
+        &self,
+        owner_id: &str,
+        pagination: Pagination,
+    ) -> Result<(Vec<Review>, i64), Error> {
+        self.repository
+            .query_reviews(
+                ReviewQuery {
+                    owner_id: Some(owner_id.to_owned()),
+                    kind: Some(ReviewKind::WalkerToOwner),
+                    state: Some(ReviewState::Revealed),
+                    ..Default::default()
+                },
+                Some(pagination),
+            )
+            .await
+    }
+
+    // 按来源id翻译文本, 返回来源id到译文的映射. 译文按来源id和目标语言缓存,
+    // 原文变化后哈希不一致重新翻译. 已是目标语言的文本不调用翻译服务
+    pub async fn translate<T>(
+        &self,
+        translator: &T,
+        texts: Vec<(String, String)>,
+        to: Language,
+    ) -> Result<HashMap<String, String>, Error>
+    where
+        T: Translator,
+    {
+        let source_ids = texts.iter().map(|(id, _)| id.clone()).collect::<Vec<_>>();
+        let cached = self
+            .repository
+            .query_translations(&source_ids, to)
+            .await?
+            .into_iter()
+            .map(|translation| (translation.source_id.clone(), translation))
+            .collect::<HashMap<_, _>>();
+        let mut translated = HashMap::new();
+        for (source_id, text) in texts {
+            if to.is_written_in(&text) {
+                translated.insert(source_id, text);
+                continue;
+            }
+            let source_hash = hash_token(&text);
+            if let Some(translation) = cached
+                .get(&source_id)
+                .filter(|translation| translation.source_hash == source_hash)
+            {
+                translated.insert(source_id, translation.text.clone());
+                continue;
+            }
+            let translation = translator.translate(&text, to).await?;
+            // 翻译服务未配置时原样返回, 不缓存
+            if translation != text {
+                self.repository
+                    .upsert_translation(TranslationUpsert {
+                        source_id: source_id.clone(),
+                        language: to,
+                        source_hash,
+                        text: translation.clone(),
+                    })
+                    .await?;
+            }
+            translated.insert(source_id, translation);
+        }
+        Ok(translated)
+    }
+
+    // 取消和爽约按最近REPUTATION_WINDOW_DAYS天内的请求统计, 打款失败为同期的失败次数
+    pub async fn owner_reputation(&self, owner_id: &str) -> Result<OwnerReputation, Error> {
+        let since = Utc::now() - chrono::Duration::days(REPUTATION_WINDOW_DAYS);
+        let stats = self
+            .repository
+            .owner_reputation_stats(owner_id, since)
+            .await?;
+        let payments = self
+            .repository
+            .payment_attempt_stats(owner_id, since)
+            .await?;
+        Ok(owner_reputation(owner_id, &stats, payments.failed))
+    }
+
+    pub async fn owner_profile(&self, user_id: &str) -> Result<OwnerProfile, Error> {
+        let owner = self.repository.get_owner(user_id).await?;
+        let dog_count = self
+            .repository
+            .count_dogs(&DogQuery {
+                owner_id: Some(user_id.to_owned()),
+                ..Default::default()
+            })
+            .await?;
+        let achievements = self.repository.query_achievements(user_id).await?;
+        Ok(match owner {
+            Some(owner) => OwnerProfile {
+                user_id: owner.user_id,
+                nickname: owner.nickname,
+                avatar_id: owner.avatar_id,
+                dog_count,
+                member_since: owner.created_at,
+                achievements,
+            },
+            None => OwnerProfile {
+                user_id: user_id.to_owned(),
+                dog_count,
+                achievements,
+                ..Default::default()
+            },
+        })
+    }
+
+    pub async fn update_owner_profile(
+        &self,
+        user_id: &str,
+        update: OwnerUpdate,
+    ) -> Result<OwnerProfile, Error> {
+        if let Some(nickname) = &update.nickname {
+            let len = nickname.trim().chars().count();
+            if len == 0 || len > MAX_NICKNAME_LENGTH {
+                return Err(Error::msg("昵称长度必须为1-20个字符"));
+            }
+        }
+        if let Some(contacts) = &update.emergency_contacts {
+            if contacts.len() > MAX_EMERGENCY_CONTACTS {
+                return Err(Error::msg("紧急联系人最多3个"));
+            }
+            if contacts
+                .iter()
+                .any(|c| c.name.trim().is_empty() || c.phone.trim().is_empty())
+            {
+                return Err(Error::msg("紧急联系人的姓名和手机号不能为空"));
+            }
+        }
+        self.repository.upsert_owner(user_id, update).await?;
+        self.owner_profile(user_id).await
+    }
+
+    pub async fn block_walker(&self, owner_id: &str, walker_id: &str) -> Result<Block, Error> {
+        if owner_id == walker_id {
+            return Err(Error::msg("不能屏蔽自己"));
+        }
+        let block = self.repository.create_block(owner_id, walker_id).await?;
+        // 移除该遛狗人在此狗狗主人尚未开始的请求上的报名和接单
+        self.repository
+            .update_walk_requests_by_query(
+                WalkRequestQuery {
+                    created_by: Some(owner_id.to_owned()),
+                    acceptances_includes_all: Some(vec![walker_id.to_owned()]),
+                    started_at_is_null: Some(true),
+                    ..Default::default()
+                },
+                WalkRequestUpdate {
+                    remove_from_acceptances: Some(walker_id.to_owned()),
+                    ..Default::default()
+                },
+            )
+            .await?;
+        self.repository
+            .update_walk_requests_by_query(
+                WalkRequestQuery {
+                    created_by: Some(owner_id.to_owned()),
+                    accepted_by: Some(walker_id.to_owned()),
+                    started_at_is_null: Some(true),
+                    ..Default::default()
+                },
+                WalkRequestUpdate {
+                    accepted_by: UpdateField::Unset,
+                    accepted_at: UpdateField::Unset,
+                    ..Default::default()
+                },
+            )
+            .await?;
+        Ok(block)
+    }
+
+    pub async fn unblock_walker(&self, owner_id: &str, walker_id: &str) -> Result<(), Error> {
+        self.repository
+            .delete_block(owner_id, walker_id)
+            .await
+            .and_then(|deleted| {
+                if deleted {
+                    Ok(())
+                } else {
+                    Err(Error::msg("未屏蔽该遛狗人"))
+                }
+            })
+    }
+
+    pub async fn blocked_walkers(&self, owner_id: &str) -> Result<Vec<Block>, Error> {
+        self.repository
+            .query_blocks(BlockQuery {
+                owner_id: Some(owner_id.to_owned()),
+                ..Default::default()
+            })
+            .await
+    }
+
+    pub async fn favorite_walker(
+        &self,
+        owner_id: &str,
+        walker_id: &str,
+    ) -> Result<Favorite, Error> {
+        if owner_id == walker_id {
+            return Err(Error::msg("不能收藏自己"));
+        }
+        if !self.is_verified_walker(walker_id).await? {
+            return Err(Error::msg("只能收藏通过身份认证的遛狗人"));
+        }
+        self.repository.create_favorite(owner_id, walker_id).await
+    }
+
+    pub async fn unfavorite_walker(&self, owner_id: &str, walker_id: &str) -> Result<(), Error> {
+        self.repository
+            .delete_favorite(owner_id, walker_id)
+            .await
+            .and_then(|deleted| {
+                if deleted {
+                    Ok(())
+                } else {
+                    Err(Error::msg("未收藏该遛狗人"))
+                }
+            })
+    }
+
+    pub async fn favorite_walkers(&self, owner_id: &str) -> Result<Vec<Favorite>, Error> {
+        self.repository
+            .query_favorites(FavoriteQuery {
+                owner_id: Some(owner_id.to_owned()),
+                ..Default::default()
+            })
+            .await
+    }
+
+    // 屏蔽了该遛狗人的狗狗主人
+    async fn blockers_of(&self, walker_id: &str) -> Result<Vec<String>, Error> {
+        Ok(self
+            .repository
+            .query_blocks(BlockQuery {
+                walker_id: Some(walker_id.to_owned()),
+                ..Default::default()
+            })
+            .await?
+            .into_iter()
+            .map(|b| b.owner_id)
+            .collect())
+    }
+
+    pub async fn report_user(&self, report: ReportCreate) -> Result<String, Error> {
+        if report.reporter_id == report.target_user_id {
+            return Err(Error::msg("不能举报自己"));
+        }
+        if report.reason.trim().is_empty() {
+            return Err(Error::msg("请填写举报原因"));
+        }
+        self.repository.create_report(report).await
+    }
+
+    pub async fn reports(
+        &self,
+        status: Option<ReportStatus>,
+        pagination: Pagination,
+    ) -> Result<(Vec<Report>, i64), Error> {
+        self.repository
+            .query_reports(
+                ReportQuery {
+                    status,
+                    ..Default::default()
+                },
+                Some(pagination),
+            )
+            .await
+    }
+
+    pub async fn review_report(
+        &self,
+        id: &str,
+        status: ReportStatus,
+        resolution_note: Option<String>,
+    ) -> Result<(), Error> {
+        if status == ReportStatus::Pending {
+            return Err(Error::msg("无效的处理结果"));
+        }
+        self.repository
+            .update_reports_by_query(
+                ReportQuery {
+                    id: Some(id.to_owned()),
+                    status: Some(ReportStatus::Pending),
+                    ..Default::default()
+                },
+                ReportUpdate {
+                    status: Some(status),
+                    resolution_note,
+                    reviewed_at: Some(Utc::now()),
+                },
+            )
+            .await
+            .and_then(|n| {
+                if n == 1 {
+                    Ok(())
+                } else {
+                    Err(Error::not_found("举报不存在或已处理"))
+                }
+            })
+    }
+
+    // 预览账号合并将改写的记录数, 不做任何修改
+    pub async fn preview_account_merge(
+        &self,
+        from_user_id: &str,
+        to_user_id: &str,
+    ) -> Result<Vec<MergedReference>, Error> {
+        if from_user_id == to_user_id {
+            return Err(Error::msg("不能合并同一个账号"));
+        }
+        self.repository.count_user_references(from_user_id).await
+    }
+
+    pub async fn merge_accounts(&self, merge: AccountMergeCreate) -> Result<AccountMerge, Error> {
+        if merge.from_user_id == merge.to_user_id {
+            return Err(Error::msg("不能合并同一个账号"));
+        }
+        let from_user_id = merge.from_user_id.clone();
+        let to_user_id = merge.to_user_id.clone();
+        let merged = self.repository.merge_accounts(merge).await?;
+        self.repository
+            .increment_token_version(&from_user_id)
+            .await?;
+        self.walker_stats_cache.invalidate(&from_user_id);
+        self.walker_stats_cache.invalidate(&to_user_id);
+        Ok(merged)
+    }
+
+    // 注销账号, 可以把名下的狗狗转给其他用户, 否则一并删除
+    pub async fn delete_account(
+        &self,
+        user_id: &str,
+        reassign_dogs_to: Option<String>,
+    ) -> Result<AccountDeletion, Error> {
+        if reassign_dogs_to.as_deref() == Some(user_id) {
+            return Err(Error::msg("不能把狗狗转给自己"));
+        }
+        let deleted = self
+            .repository
+            .delete_account(AccountDeletionCreate {
+                user_id: user_id.to_owned(),
+                reassign_dogs_to,
+            })
+            .await?;
+        self.repository.increment_token_version(user_id).await?;
+        self.walker_stats_cache.invalidate(user_id);
+        Ok(deleted)
+    }
+
+    pub async fn send_otp<S>(
+        &self,
+        sender: &S,
+        phone: &str,
+        purpose: OtpPurpose,
+    ) -> Result<(), Error>
+    where
+        S: SmsSender,
+    {
+        let now = Utc::now();
+        if self
+            .repository
+            .count_otps(
+                phone,
+                now - chrono::Duration::seconds(OTP_RESEND_INTERVAL_SECS),
+            )
+            .await?
+            > 0
+        {
+            return Err(Error::msg("验证码发送过于频繁, 请稍后再试"));
+        }
+        if self
+            .repository
+            .count_otps(phone, now - chrono::Duration::hours(1))
+            .await?
+            >= MAX_OTPS_PER_HOUR
+        {
+            return Err(Error::msg("验证码发送次数过多, 请一小时后再试"));
+        }
+        let code = format!("{:06}", rand::thread_rng().gen_range(0..1_000_000));
+        self.repository
+            .create_otp(OtpCreate {
+                phone: phone.to_owned(),
+                purpose,
+                code_hash: hash_token(&code),
+                expires_at: now + chrono::Duration::seconds(self.otp_ttl.as_secs() as i64),
+            })
+            .await?;
+        sender
+            .send(
+                phone,
+                &format!(
+                    "您{}的验证码是{}, {}分钟内有效",
+                    match purpose {
+                        OtpPurpose::Login => "登录",
+                        OtpPurpose::PasswordReset => "重置密码",
+                        OtpPurpose::PhoneChange => "更换手机号",
+                    },
+                    code,
+                    self.otp_ttl.as_secs() / 60
+                ),
+            )
+            .await
+    }
+
+    // 验证码只能使用一次, 输错MAX_OTP_ATTEMPTS次后作废, 需重新获取
+    pub async fn verify_otp(
+        &self,
+        phone: &str,
+        purpose: OtpPurpose,
+        code: &str,
+    ) -> Result<(), Error> {
+        self.repository
+            .consume_otp(phone, purpose, &hash_token(code), MAX_OTP_ATTEMPTS)
+            .await?
+            .map(|_| ())
+            .ok_or(Error::msg("验证码错误或已过期"))
+    }
+
+    // 手机号在auth_service中的注册手机号. 已被更换掉的注册手机号不能再用于登录
+    pub async fn auth_phone(&self, phone: &str) -> Result<String, Error> {
+        match self.repository.get_phone_binding(phone).await? {
+            Some(binding) if binding.phone == phone => Ok(binding.auth_phone),
+            Some(_) => Err(Error::not_found("该手机号已更换")),
+            None => Ok(phone.to_owned()),
+        }
+    }
+
+    // 更换前或更换后使用该手机号的用户, auth_service中查不到更换后的手机号
+    pub async fn phone_bound_to(&self, phone: &str) -> Result<Option<String>, Error> {
+        Ok(self
+            .repository
+            .get_phone_binding(phone)
+            .await?
+            .map(|binding| binding.user_id))
+    }
+
+    // 新手机号验证码校验通过后绑定到用户, 所有设备的刷新令牌作废
+    pub async fn change_phone(
+        &self,
+        user_id: &str,
+        auth_phone: &str,
+        new_phone: &str,
+        code: &str,
+    ) -> Result<(), Error> {
+        self.verify_otp(new_phone, OtpPurpose::PhoneChange, code)
+            .await?;
+        self.repository
+            .bind_phone(PhoneBindingUpsert {
+                user_id: user_id.to_owned(),
+                phone: new_phone.to_owned(),
+                auth_phone: auth_phone.to_owned(),
+            })
+            .await?;
+        self.revoke_all_sessions(user_id).await?;
+        Ok(())
+    }
+
+    // 重置密码验证码校验通过后签发重置令牌
+    pub async fn issue_password_reset_token(
+        &self,
+        phone: &str,
+        code: &str,
+    ) -> Result<String, Error> {
+        self.verify_otp(phone, OtpPurpose::PasswordReset, code)
+            .await?;
+        let token = random_token();
+        self.repository
+            .create_password_reset_token(PasswordResetTokenCreate {
+                phone: phone.to_owned(),
+                token_hash: hash_token(&token),
+                expires_at: Utc::now() + chrono::Duration::minutes(PASSWORD_RESET_TOKEN_MINUTES),
+            })
+            .await?;
+        Ok(token)
+    }
+
+    // 已绑定的第三方账号直接登录, 未绑定时签发绑定令牌, 由客户端验证手机号后完成绑定
+    pub async fn oauth_login(
+        &self,
+        provider: OAuthProviderKind,
+        identity: OAuthIdentity,
+    ) -> Result<OAuthLoginOutcome, Error> {
+        if let Some(account) = self
+            .repository
+            .get_oauth_account(provider, &identity.subject)
+            .await?
+        {
+            return Ok(OAuthLoginOutcome::LoggedIn(account));
+        }
+        let token = random_token();
+        self.repository
+            .create_oauth_link_token(OAuthLinkTokenCreate {
+                provider,
+                subject: identity.subject,
+                token_hash: hash_token(&token),
+                expires_at: Utc::now() + chrono::Duration::minutes(OAUTH_LINK_TOKEN_MINUTES),
+            })
+            .await?;
+        Ok(OAuthLoginOutcome::LinkRequired(token))
+    }
+
+    // 先校验验证码再消费绑定令牌, 验证码输错时绑定令牌仍可重试
+    pub async fn verify_oauth_link(
+        &self,
+        link_token: &str,
+        phone: &str,
+        code: &str,
+    ) -> Result<OAuthLinkToken, Error> {
+        self.verify_otp(phone, OtpPurpose::Login, code).await?;
+        self.repository
+            .consume_oauth_link_token(&hash_token(link_token))
+            .await?
+            .ok_or(Error::msg("绑定令牌无效或已过期"))
+    }
+
+    pub async fn link_oauth_account(
+        &self,
+        link: OAuthLinkToken,
+        user_id: &str,
+        phone: &str,
+    ) -> Result<String, Error> {
+        self.repository
+            .create_oauth_account(OAuthAccountCreate {
+                provider: link.provider,
+                subject: link.subject,
+                user_id: user_id.to_owned(),
+                phone: phone.to_owned(),
+            })
+            .await
+    }
+
+    // 消费重置令牌, 返回令牌对应的手机号
+    pub async fn consume_password_reset_token(&self, token: &str) -> Result<String, Error> {
+        self.repository
+            .consume_password_reset_token(&hash_token(token))
+            .await?
+            .map(|reset| reset.phone)
+            .ok_or(Error::msg("重置令牌无效或已过期"))
+    }
+
+    // 登录或注册时开启一个新的刷新令牌family
+    pub async fn issue_refresh_token(
+        &self,
+        user_id: &str,
+        phone: &str,
+        device: Device,
+    ) -> Result<String, Error> {
+        let family_id = random_token();
+        self.create_refresh_token(&family_id, user_id, phone, device, Utc::now())
+            .await
+    }
+
+    // 轮换刷新令牌, 返回被消费的令牌记录和新令牌. 已使用过的令牌再次出现视为泄露, 整个family作废
+    pub async fn rotate_refresh_token(&self, token: &str) -> Result<(RefreshToken, String), Error> {
+        let token_hash = hash_token(token);
+        let consumed = match self.repository.consume_refresh_token(&token_hash).await? {
+            Some(consumed) => consumed,
+            None => {
+                if let Some(reused) = self.repository.get_refresh_token(&token_hash).await? {
+                    self.repository
+                        .revoke_refresh_token_family(&reused.family_id)
+                        .await?;
+                    return Err(Error::msg("刷新令牌已被使用, 请重新登录"));
+                }
+                return Err(Error::msg("无效的刷新令牌"));
+            }
+        };
+        if consumed.revoked {
+            return Err(Error::msg("刷新令牌已失效, 请重新登录"));
+        }
+        if consumed.expires_at <= Utc::now() {
+            return Err(Error::msg("刷新令牌已过期, 请重新登录"));
+        }
+        let token = self
+            .create_refresh_token(
+                &consumed.family_id,
+                &consumed.user_id,
+                &consumed.phone,
+                Device {
+                    device_id: consumed.device_id.clone(),
+                    platform: consumed.platform.clone(),
+                },
+                consumed
+                    .signed_in_at
+                    .or(consumed.created_at)
+                    .unwrap_or_else(Utc::now),
+            )
+            .await?;
+        Ok((consumed, token))
+    }
+
+    // 退出登录时吊销当前访问令牌, 不影响同一用户的其他令牌
+    pub async fn revoke_access_token(
+        &self,
+        token_id: &str,
+        user_id: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), Error> {
+        self.repository
+            .revoke_access_token(RevokedAccessTokenCreate {
+                token_id: token_id.to_owned(),
+                user_id: user_id.to_owned(),
+                expires_at,
+            })
+            .await
+    }
+
+    pub async fn is_access_token_revoked(&self, token_id: &str) -> Result<bool, Error> {
+        self.repository.is_access_token_revoked(token_id).await
+    }
+
+    pub async fn sessions(&self, user_id: &str) -> Result<Vec<Session>, Error> {
+        self.repository.query_sessions(user_id).await
+    }
+
+    // 按会话或设备注销, 只作废刷新令牌, 已签发的访问令牌在过期前仍然有效
+    pub async fn revoke_sessions(&self, query: SessionQuery) -> Result<(), Error> {
+        if query.id.is_none() && query.device_id.is_none() {
+            return Err(Error::msg("请指定要注销的会话或设备"));
+        }
+        self.repository.revoke_sessions(query).await.and_then(|n| {
+            if n > 0 {
+                Ok(())
+            } else {
+                Err(Error::not_found("会话不存在或已注销"))
+            }
+        })
+    }
+
+    // 调用方需先重新验证用户身份, 返回的令牌只能用于指定的操作
+    pub async fn issue_action_token(
+        &self,
+        user_id: &str,
+        action: SensitiveAction,
+    ) -> Result<String, Error> {
+        let token = random_token();
+        self.repository
+            .create_action_token(ActionTokenCreate {
+                user_id: user_id.to_owned(),
+                action,
+                token_hash: hash_token(&token),
+                expires_at: Utc::now() + chrono::Duration::minutes(ACTION_TOKEN_MINUTES),
+            })
+            .await?;
+        Ok(token)
+    }
+
+    pub async fn consume_action_token(
+        &self,
+        user_id: &str,
+        action: SensitiveAction,
+        token: &str,
+    ) -> Result<(), Error> {
+        self.repository
+            .consume_action_token(user_id, action, &hash_token(token))
+            .await?
+            .map(|_| ())
+            .ok_or(Error::msg("操作确认令牌无效或已过期"))
+    }
+
+    // 修改密码后作废用户所有的刷新令牌, 其他设备需要重新登录
+    // 同时提升令牌版本, 已签发的访问令牌立即失效
+    pub async fn revoke_all_sessions(&self, user_id: &str) -> Result<u64, Error> {
+        let revoked = self
+            .repository
+            .revoke_sessions(SessionQuery {
+                user_id: user_id.to_owned(),
+                ..Default::default()
+            })
+            .await?;
+        self.repository.increment_token_version(user_id).await?;
+        Ok(revoked)
+    }
+
+    // 访问令牌中的版本低于当前版本时视为已吊销
+    pub async fn token_version(&self, user_id: &str) -> Result<i64, Error> {
+        self.repository.get_token_version(user_id).await
+    }
+
+    // 所有用户都是狗狗主人, 遛狗人档案通过身份认证后同时是遛狗人, 管理员由配置指定
+    pub async fn user_roles(&self, user_id: &str) -> Result<Vec<Role>, Error> {
+        let mut roles = vec![Role::Owner];
+        if self
+            .repository
+            .get_walker(user_id)
+            .await?
+            .is_some_and(|w| w.verification_status == VerificationStatus::Verified)
+        {
+            roles.push(Role::Walker);
+        }
+        if self.admin_user_ids.iter().any(|id| id == user_id) {
+            roles.push(Role::Admin);
+        }
+        Ok(roles)
+    }
+
+    async fn create_refresh_token(
+        &self,
+        family_id: &str,
+        user_id: &str,
+        phone: &str,
+        device: Device,
+        signed_in_at: DateTime<Utc>,
+    ) -> Result<String, Error> {
+        let token = random_token();
+        self.repository
+            .create_refresh_token(RefreshTokenCreate {
+                family_id: family_id.to_owned(),
+                user_id: user_id.to_owned(),
+                phone: phone.to_owned(),
+                token_hash: hash_token(&token),
+                device,
+                signed_in_at,
+                expires_at: Utc::now()
+                    + chrono::Duration::seconds(self.refresh_token_ttl.as_secs() as i64),
+            })
+            .await?;
+        Ok(token)
+    }
+
+    pub async fn create_ticket(&self, create: TicketCreate) -> Result<String, Error> {
+        if create.subject.trim().is_empty() {
+            return Err(Error::msg("请填写工单标题"));
+        }
+        if create.content.trim().is_empty() && create.attachment_ids.is_empty() {
+            return Err(Error::msg("请填写问题描述"));
+        }
+        self.repository.create_ticket(create).await
+    }
+
+    pub async fn my_tickets(
+        &self,
+        user_id: &str,
+        status: Option<TicketStatus>,
+        pagination: Pagination,
+    ) -> Result<(Vec<Ticket>, i64), Error> {
+        self.repository
+            .query_tickets(
+                TicketQuery {
+                    user_id: Some(user_id.to_owned()),
+                    status,
+                    ..Default::default()
+                },
+                Some(pagination),
+            )
+            .await
+    }
+
+    pub async fn tickets(
+        &self,
+        status: Option<TicketStatus>,
+        assignee_id: Option<String>,
+        pagination: Pagination,
+    ) -> Result<(Vec<Ticket>, i64), Error> {
+        self.repository
+            .query_tickets(
+                TicketQuery {
+                    status,
+                    assignee_id,
+                    ..Default::default()
+                },
+                Some(pagination),
+            )
+            .await
+    }
+
+    // user_id为None时不校验工单归属, 供客服使用
+    pub async fn ticket(&self, id: &str, user_id: Option<&str>) -> Result<Ticket, Error> {
+        let (mut tickets, _) = self
+            .repository
+            .query_tickets(
+                TicketQuery {
+                    id: Some(id.to_owned()),
+                    user_id: user_id.map(str::to_owned),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await?;
+        tickets.pop().ok_or(Error::not_found("工单不存在"))
+    }
+
+    // 用户回复已解决的工单时重新打开工单
+    pub async fn reply_ticket(
+        &self,
+        id: &str,
+        user_id: &str,
+        content: String,
+        attachment_ids: Vec<String>,
+    ) -> Result<(), Error> {
+        let ticket = self.ticket(id, Some(user_id)).await?;
+        self.add_ticket_message(&ticket, user_id, false, content, attachment_ids)
+            .await?;
+        if ticket.status == TicketStatus::Resolved {
+            self.repository
+                .update_tickets_by_query(
+                    TicketQuery {
+                        id: Some(id.to_owned()),
+                        status: Some(TicketStatus::Resolved),
+                        ..Default::default()
+                    },
+                    TicketUpdate {
+                        status: Some(TicketStatus::InProgress),
+                        ..Default::default()
+                    },
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    pub async fn reply_ticket_as_staff(
+        &self,
+        id: &str,
+        staff_id: &str,
+        content: String,
+        attachment_ids: Vec<String>,
+    ) -> Result<(), Error> {
+        let ticket = self.ticket(id, None).await?;
+        self.add_ticket_message(&ticket, staff_id, true, content, attachment_ids)
+            .await
+    }
+
+    async fn add_ticket_message(
+        &self,
+        ticket: &Ticket,
+        author_id: &str,
+        from_staff: bool,
+        content: String,
+        attachment_ids: Vec<String>,
+    ) -> Result<(), Error> {
+        if ticket.status == TicketStatus::Closed {
+            return Err(Error::msg("工单已关闭"));
+        }
+        if content.trim().is_empty() && attachment_ids.is_empty() {
+            return Err(Error::msg("回复内容不能为空"));
+        }
+        self.repository
+            .add_ticket_message(
+                TicketQuery {
+                    id: Some(ticket.id.clone()),
+                    ..Default::default()
+                },
+                TicketMessageCreate {
+                    author_id: author_id.to_owned(),
+                    from_staff,
+                    content,
+                    attachment_ids,
+                },
+            )
+            .await
+            .and_then(|n| {
+                if n == 1 {
+                    Ok(())
+                } else {
+                    Err(Error::msg("工单已关闭"))
+                }
+            })
+    }
+
+    // 分配给客服后, 未处理的工单进入处理中状态
+    pub async fn assign_ticket(&self, id: &str, assignee_id: &str) -> Result<(), Error> {
+        let ticket = self.ticket(id, None).await?;
+        if ticket.status == TicketStatus::Closed {
+            return Err(Error::msg("工单已关闭"));
+        }
+        let status = if ticket.status == TicketStatus::Open {
+            TicketStatus::InProgress
+        } else {
+            ticket.status
+        };
+        self.repository
+            .update_tickets_by_query(
+                TicketQuery {
+                    id: Some(id.to_owned()),
+                    status: Some(ticket.status),
+                    ..Default::default()
+                },
+                TicketUpdate {
+                    status: Some(status),
+                    assignee_id: Some(assignee_id.to_owned()),
+                },
+            )
+            .await
+            .and_then(|n| {
+                if n == 1 {
+                    Ok(())
+                } else {
+                    Err(Error::conflict("工单状态已变更, 请刷新后重试"))
+                }
+            })
+    }
+
+    pub async fn transition_ticket(
+        &self,
+        id: &str,
+        user_id: Option<&str>,
+        status: TicketStatus,
+    ) -> Result<(), Error> {
+        let ticket = self.ticket(id, user_id).await?;
+        if !ticket.status.can_transition_to(status) {
+            return Err(Error::msg("无效的工单状态变更"));
+        }
+        self.repository
+            .update_tickets_by_query(
+                TicketQuery {
+                    id: Some(id.to_owned()),
+                    status: Some(ticket.status),
+                    ..Default::default()
+                },
+                TicketUpdate {
+                    status: Some(status),
+                    ..Default::default()
+                },
+            )
+            .await
+            .and_then(|n| {
+                if n == 1 {
+                    Ok(())
+                } else {
+                    Err(Error::conflict("工单状态已变更, 请刷新后重试"))
+                }
+            })
+    }
+
+    // 只有通过认证的遛狗人可以绑定收款账户, 重新绑定会替换原账户
+    pub async fn link_payout_account<P>(
+        &self,
+        provider: &P,
+        user_id: &str,
+        destination: PayoutDestination,
+    ) -> Result<PayoutAccount, Error>
+    where
+        P: PayoutProvider,
+    {
+        if !self.is_verified_walker(user_id).await? {
+            return Err(Error::msg("请先完成遛狗人认证"));
+        }
+        if destination.holder_name.trim().is_empty() || destination.account_number.trim().is_empty()
+        {
+            return Err(Error::msg("请填写收款人和收款账号"));
+        }
+        if destination.method == PayoutMethod::Bank
+            && destination
+                .bank_name
+                .as_deref()
+                .map(|n| n.trim().is_empty())
+                .unwrap_or(true)
+        {
+            return Err(Error::msg("请填写开户银行"));
+        }
+        let registration = provider.register_destination(&destination).await?;
+        self.repository
+            .upsert_payout_account(PayoutAccountUpsert {
+                user_id: user_id.to_owned(),
+                method: destination.method,
+                masked_holder_name: mask_name(&destination.holder_name),
+                masked_account_number: mask_account_number(&destination.account_number),
+                bank_name: destination.bank_name,
+                provider_reference: registration.reference,
+                status: registration.status,
+                failure_reason: registration.failure_reason,
+            })
+            .await
+    }
+
+    // 核验中的账户在查询时向打款服务同步最新状态
+    pub async fn payout_account<P>(
+        &self,
+        provider: &P,
+        user_id: &str,
+    ) -> Result<Option<PayoutAccount>, Error>
+    where
+        P: PayoutProvider,
+    {
+        let account = match self.repository.get_payout_account(user_id).await? {
+            Some(account) if account.status == PayoutAccountStatus::Pending => account,
+            other => return Ok(other),
+        };
+        let registration = provider
+            .destination_status(&account.provider_reference)
+            .await?;
+        if registration.status == PayoutAccountStatus::Pending {
+            return Ok(Some(account));
+        }
+        self.repository
+            .update_payout_account_status(
+                user_id,
+                &account.provider_reference,
+                registration.status,
+                registration.failure_reason,
+            )
+            .await
+    }
+
+    pub async fn unlink_payout_account(&self, user_id: &str) -> Result<bool, Error> {
+        self.repository.delete_payout_account(user_id).await
+    }
+
+    pub async fn balance(&self, user_id: &str) -> Result<i64, Error> {
+        self.repository.ledger_balance(user_id).await
+    }
+
+    pub async fn ledger(
+        &self,
+        user_id: &str,
+        pagination: Pagination,
+    ) -> Result<(Vec<LedgerEntry>, i64), Error> {
+        self.repository
+            .query_ledger_entries(user_id, Some(pagination))
+            .await
+    }
+
+    // 申请时即从账本扣除金额, 提现打到当前已核验的收款账户.
+    // 每次尝试都会记录结果, 窗口内失败过多时进入冷却, 冷却期间的请求不再记录
+    pub async fn request_withdrawal(&self, user_id: &str, amount: i64) -> Result<String, Error> {
+        if self.is_synthetic_walker(user_id) {
+            return Err(Error::forbidden("测试账号不能提现"));
+        }
+        let now = Utc::now();
+        let recent = self
+            .repository
+            .payment_attempt_stats(
+                user_id,
+                now - chrono::Duration::minutes(PAYMENT_COOL_DOWN_MINUTES),
+            )
+            .await?;
+        if recent.failed >= MAX_FAILED_PAYMENT_ATTEMPTS {
+            return Err(Error::msg(&format!(
+                "提现失败次数过多, 请{}分钟后再试",
+                PAYMENT_COOL_DOWN_MINUTES
+            )));
+        }
+        let result = self.submit_withdrawal(user_id, amount).await;
+        self.repository
+            .create_payment_attempt(PaymentAttemptCreate {
+                user_id: user_id.to_owned(),
+                amount,
+                succeeded: result.is_ok(),
+            })
+            .await?;
+        result
+    }
+
+    async fn submit_withdrawal(&self, user_id: &str, amount: i64) -> Result<String, Error> {
+        if amount < MIN_WITHDRAWAL_AMOUNT {
+            return Err(Error::msg("提现金额低于最低限额"));
+        }
+        let daily = self
+            .repository
+            .payment_attempt_stats(user_id, Utc::now() - chrono::Duration::days(1))
+            .await?;
+        if daily.succeeded_amount + amount > MAX_DAILY_WITHDRAWAL_AMOUNT {
+            return Err(Error::msg("超过单日提现限额"));
+        }
+        let account = self
+            .repository
+            .get_payout_account(user_id)
+            .await?
+            .ok_or(Error::msg("请先绑定收款账户"))?;
+        if !account.can_withdraw() {
+            return Err(Error::msg("收款账户尚未通过核验"));
+        }
+        self.repository
+            .create_withdrawal(WithdrawalCreate {
+                user_id: user_id.to_owned(),
+                amount,
+                destination_reference: account.provider_reference,
+            })
+            .await?
+            .ok_or(Error::msg("可提现余额不足"))
+    }
+
+    pub async fn my_withdrawals(
+        &self,
+        user_id: &str,
+        status: Option<WithdrawalStatus>,
+        pagination: Pagination,
+    ) -> Result<(Vec<Withdrawal>, i64), Error> {
+        self.repository
+            .query_withdrawals(
+                WithdrawalQuery {
+                    user_id: Some(user_id.to_owned()),
+                    status,
+                    ..Default::default()
+                },
+                Some(pagination),
+            )
+            .await
+    }
+
+    pub async fn withdrawals(
+        &self,
+        status: Option<WithdrawalStatus>,
+        pagination: Pagination,
+    ) -> Result<(Vec<Withdrawal>, i64), Error> {
+        self.repository
+            .query_withdrawals(
+                WithdrawalQuery {
+                    status,
+                    ..Default::default()
+                },
+                Some(pagination),
+            )
+            .await
+    }
+
+    // 批量审核, 只处理仍在待审核状态的申请, 返回实际处理的数量
+    pub async fn review_withdrawals(
+        &self,
+        ids: Vec<String>,
+        reviewer_id: &str,
+        approved: bool,
+        reason: Option<String>,
+    ) -> Result<u64, Error> {
+        if ids.is_empty() || ids.len() > MAX_WITHDRAWAL_BATCH {
+            return Err(Error::msg("无效的批量审核数量"));
+        }
+        self.repository
+            .update_withdrawals_by_query(
+                WithdrawalQuery {
+                    ids: Some(ids),
+                    status: Some(WithdrawalStatus::Requested),
+                    ..Default::default()
+                },
+                WithdrawalUpdate {
+                    status: Some(if approved {
+                        WithdrawalStatus::Approved
+                    } else {
+                        WithdrawalStatus::Rejected
+                    }),
+                    failure_reason: reason,
+                    reviewed_by: Some(reviewer_id.to_owned()),
+                    ..Default::default()
+                },
+            )
+            .await
+    }
+
+    // 逐笔提交审核通过的提现. 打款服务调用失败时退回审核通过状态, 下一轮以同一幂等键重试
+    pub async fn process_withdrawals<P>(&self, provider: &P) -> Result<u64, Error>
+    where
+        P: PayoutProvider,
+    {
+        let mut submitted = 0;
+        while let Some(withdrawal) = self.repository.claim_approved_withdrawal().await? {
+            let query = WithdrawalQuery {
+                id: Some(withdrawal.id.clone()),
+                status: Some(WithdrawalStatus::Processing),
+                ..Default::default()
+            };
+            let order = PayoutOrder {
+                withdrawal_id: withdrawal.id,
+                destination_reference: withdrawal.destination_reference,
+                amount: withdrawal.amount,
+            };
+            match provider.execute_payout(&order).await {
+                Ok(transfer) => {
+                    self.repository
+                        .update_withdrawals_by_query(
+                            query,
+                            WithdrawalUpdate {
+                                status: Some(transfer.status),
+                                payout_reference: Some(transfer.reference),
+                                failure_reason: transfer.failure_reason,
+                                ..Default::default()
+                            },
+                        )
+                        .await?;
+                    submitted += 1;
+                }
+                Err(e) => {
+                    self.repository
+                        .update_withdrawals_by_query(
+                            query,
+                            WithdrawalUpdate {
+                                status: Some(WithdrawalStatus::Approved),
+                                ..Default::default()
+                            },
+                        )
+                        .await?;
+                    return Err(e);
+                }
+            }
+        }
+        Ok(submitted)
+    }
+
+    // 打款服务回调最终结果, 只更新打款中的提现, 重复回调不会重复退回, 也不会重复发送到账凭证
+    pub async fn settle_withdrawal(
+        &self,
+        payout_reference: &str,
+        status: WithdrawalStatus,
+        failure_reason: Option<String>,
+    ) -> Result<u64, Error> {
+        if !matches!(status, WithdrawalStatus::Paid | WithdrawalStatus::Failed) {
+            return Err(Error::msg("无效的打款状态"));
+        }
+        let settled = self
+            .repository
+            .update_withdrawals_by_query(
+                WithdrawalQuery {
+                    payout_reference: Some(payout_reference.to_owned()),
+                    status: Some(WithdrawalStatus::Processing),
+                    ..Default::default()
+                },
+                WithdrawalUpdate {
+                    status: Some(status),
+                    failure_reason,
+                    ..Default::default()
+                },
+            )
+            .await?;
+        if settled == 0 || status != WithdrawalStatus::Paid {
+            return Ok(settled);
+        }
+        let (withdrawals, _) = self
+            .repository
+            .query_withdrawals(
+                WithdrawalQuery {
+                    payout_reference: Some(payout_reference.to_owned()),
+                    status: Some(WithdrawalStatus::Paid),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await?;
+        for withdrawal in withdrawals {
+            self.email(
+                &withdrawal.user_id,
+                EmailKind::Receipt,
+                &email::RECEIPT,
+                &[
+                    ("withdrawal_id", &withdrawal.id),
+                    ("amount", &format_amount(withdrawal.amount)),
+                    (
+                        "paid_at",
+                        &withdrawal
+                            .paid_at
+                            .unwrap_or_else(Utc::now)
+                            .format("%Y-%m-%d %H:%M UTC")
+                            .to_string(),
+                    ),
+                ],
+            )
+            .await?;
+        }
+        Ok(settled)
+    }
+
+    pub async fn add_upload_variant(
+        &self,
+        upload_id: &str,
+        size: ImageSize,
+        variant_id: &str,
+    ) -> Result<(), Error> {
+        self.repository
+            .create_upload_variant(UploadVariantCreate {
+                upload_id: upload_id.to_owned(),
+                size,
+                variant_id: variant_id.to_owned(),
+            })
+            .await
+    }
+
+    // 没有对应规格的缩略图时(非图片或原图本身更小)返回None, 由调用方回退到原图
+    pub async fn upload_variant(
+        &self,
+        upload_id: &str,
+        size: ImageSize,
+    ) -> Result<Option<String>, Error> {
+        self.repository
+            .get_upload_variant(upload_id, size)
+            .await
+            .map(|variant| variant.map(|v| v.variant_id))
+    }
+
+    pub async fn is_verification_document(&self, upload_id: &str) -> Result<bool, Error> {
+        self.repository.is_verification_document(upload_id).await
+    }
+
+    pub async fn publish_help_article(
+        &self,
+        create: HelpArticleCreate,
+    ) -> Result<HelpArticle, Error> {
+        if create.slug.trim().is_empty() || create.locale.trim().is_empty() {
+            return Err(Error::msg("无效的文章标识"));
+        }
+        if create.title.trim().is_empty() {
+            return Err(Error::msg("请填写文章标题"));
+        }
+        self.repository.create_help_article(create).await
+    }
+
+    // 指定语言没有该文章时退回默认语言
+    pub async fn help_article(
+        &self,
+        slug: &str,
+        locale: &str,
+        version: Option<i64>,
+    ) -> Result<HelpArticle, Error> {
+        for locale in [locale, DEFAULT_HELP_LOCALE] {
+            if let Some(article) = self
+                .repository
+                .query_help_articles(
+                    HelpArticleQuery {
+                        slug: Some(slug.to_owned()),
+                        locale: Some(locale.to_owned()),
+                        version,
+                    },
+                    Some(1),
+                )
+                .await?
+                .pop()
+            {
+                return Ok(article);
+            }
+        }
+        Err(Error::not_found("文章不存在"))
+    }
+
+    pub async fn help_articles(&self, locale: &str) -> Result<Vec<HelpArticle>, Error> {
+        let articles = self.repository.latest_help_articles(locale).await?;
+        if articles.is_empty() && locale != DEFAULT_HELP_LOCALE {
+            return self
+                .repository
+                .latest_help_articles(DEFAULT_HELP_LOCALE)
+                .await;
+        }
+        Ok(articles)
+    }
+
+    pub async fn help_article_versions(
+        &self,
+        slug: &str,
+        locale: &str,
+    ) -> Result<Vec<HelpArticle>, Error> {
+        self.repository
+            .query_help_articles(
+                HelpArticleQuery {
+                    slug: Some(slug.to_owned()),
+                    locale: Some(locale.to_owned()),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await
+    }
+
+    pub async fn operations_snapshot(&self) -> Result<OperationsSnapshot, Error> {
+        self.repository
+            .operations_snapshot(Utc::now() - chrono::Duration::minutes(OPERATIONS_WINDOW_MINUTES))
+            .await
+    }
+
+    pub async fn purge_deleted(
+        &self,
+        deleted_before: DateTime<Utc>,
+    ) -> Result<PurgedCounts, Error> {
+        if deleted_before > Utc::now() {
+            return Err(Error::msg("清理时间不能晚于当前时间"));
+        }
+        self.repository.purge_deleted(deleted_before).await
+    }
+
+    // 大文件由客户端通过预签名地址直接上传到对象存储, 上传完成后调用确认接口登记
+    pub async fn presign_upload<S>(
+        &self,
+        store: &S,
+        uploader: &str,
+        filename: &str,
+        content_type: &str,
+        size: i64,
+    ) -> Result<(DirectUpload, String), Error>
+    where
+        S: ObjectStore,
+    {
+        if filename.trim().is_empty() {
+            return Err(Error::msg("请填写文件名"));
+        }
+        if !DIRECT_UPLOAD_CONTENT_TYPES
+            .iter()
+            .any(|prefix| content_type.starts_with(prefix))
+        {
+            return Err(Error::msg("不支持的文件类型"));
+        }
+        if size <= 0 || size > MAX_DIRECT_UPLOAD_BYTES {
+            return Err(Error::msg("文件大小超出限制"));
+        }
+        let key = format!("uploads/{}/{}", uploader, random_token());
+        let url = store.presign_put(
+            &key,
+            Duration::from_secs(PRESIGNED_UPLOAD_MINUTES as u64 * 60),
+        )?;
+        let id = self
+            .repository
+            .create_direct_upload(DirectUploadCreate {
+                uploader: uploader.to_owned(),
+                key,
+                filename: filename.trim().to_owned(),
+                content_type: content_type.to_owned(),
+                size,
+                expires_at: Utc::now() + chrono::Duration::minutes(PRESIGNED_UPLOAD_MINUTES),
+            })
+            .await?;
+        let upload = self
+            .repository
+            .get_direct_upload(&id)
+            .await?
+            .ok_or(Error::not_found("上传记录不存在"))?;
+        Ok((upload, url))
+    }
+
+    // 确认时向对象存储核对文件已上传, 且大小和类型与申请时一致. 重复确认直接返回记录
+    pub async fn confirm_direct_upload<S>(
+        &self,
+        store: &S,
+        uploader: &str,
+        id: &str,
+    ) -> Result<DirectUpload, Error>
+    where
+        S: ObjectStore,
+    {
+        let upload = self
+            .repository
+            .get_direct_upload(id)
+            .await?
+            .filter(|u| u.uploader == uploader)
+            .ok_or(Error::not_found("上传记录不存在"))?;
+        if upload.status == DirectUploadStatus::Confirmed {
+            return Ok(upload);
+        }
+        let object = store
+            .head(&upload.key)
+            .await?
+            .ok_or(Error::msg("文件尚未上传"))?;
+        if object.size > upload.size {
+            return Err(Error::msg("文件大小超出申请时的大小"));
+        }
+        if object.content_type.as_deref() != Some(upload.content_type.as_str()) {
+            return Err(Error::msg("文件类型与申请时不一致"));
+        }
+        self.repository
+            .confirm_direct_upload(id, object.size)
+            .await?;
+        self.repository
+            .get_direct_upload(id)
+            .await?
+            .ok_or(Error::not_found("上传记录不存在"))
+    }
+
+    // 已确认的文件返回短期有效的下载地址
+    pub async fn direct_upload_url<S>(&self, store: &S, id: &str) -> Result<String, Error>
+    where
+        S: ObjectStore,
+    {
+        let upload = self
+            .repository
+            .get_direct_upload(id)
+            .await?
+            .filter(|u| u.status == DirectUploadStatus::Confirmed)
+            .ok_or(Error::not_found("文件不存在"))?;
+        store.presign_get(
+            &upload.key,
+            Duration::from_secs(PRESIGNED_DOWNLOAD_MINUTES as u64 * 60),
+        )
+    }
+
+    // 使用专用的测试主人、遛狗人和狗狗走一遍完整的遛狗流程, 返回各步骤耗时.
+    // 请求位于(0, 0)且只对测试遛狗人开放, 真实用户看不到. 中途失败时取消请求
+    pub async fn run_synthetic_walk(
+        &self,
+        owner_id: &str,
+        walker_id: &str,
+        dog_id: &str,
+    ) -> Vec<SyntheticStep> {
+        let mut steps = Vec::new();
+        let mut request_id = None;
+        let mut accepted = false;
+        for name in [
+            "create_walk_request",
+            "accept",
+            "start_walk",
+            "record_walking_location",
+            "finish_walk",
+        ] {
+            let started = std::time::Instant::now();
+            let result = match (name, request_id.as_deref()) {
+                ("create_walk_request", _) => self
+                    .create_synthetic_walk_request(owner_id, walker_id, dog_id)
+                    .await
+                    .map(|id| request_id = Some(id)),
+                ("accept", Some(id)) => self.accept(id, walker_id).await.map(|_| accepted = true),
+                ("start_walk", Some(id)) => self.start_walk(id, walker_id).await.map(|_| ()),
+                ("record_walking_location", Some(id)) => self
+                    .record_walking_location(id, SYNTHETIC_LONGITUDE, SYNTHETIC_LATITUDE)
+                    .await
+                    .map(|_| ()),
+                ("finish_walk", Some(id)) => self.finish_walk(id, walker_id).await.map(|_| ()),
+                _ => Err(Error::msg("流程步骤无效")),
+            };
+            let failed = result.is_err();
+            steps.push(SyntheticStep {
+                name: name.to_owned(),
+                millis: started.elapsed().as_millis() as i64,
+                error: result.err().map(|e| e.to_string()),
+            });
+            if failed {
+                if let Some(id) = request_id.as_deref() {
+                    let started = std::time::Instant::now();
+                    let canceled = if accepted {
+                        self.cancel_accepted_request(id, walker_id).await
+                    } else {
+                        self.cancel_unaccepted_request(id).await
+                    };
+                    steps.push(SyntheticStep {
+                        name: "cancel_walk_request".to_owned(),
+                        millis: started.elapsed().as_millis() as i64,
+                        error: canceled.err().map(|e| e.to_string()),
+                    });
+                }
+                break;
+            }
+        }
+        steps
+    }
+
+    async fn create_synthetic_walk_request(
+        &self,
+        owner_id: &str,
+        walker_id: &str,
+        dog_id: &str,
+    ) -> Result<String, Error> {
+        let dogs = self
+            .repository
+            .query_dogs(&DogQuery {
+                id: Some(dog_id.to_owned()),
+                owner_id: Some(owner_id.to_owned()),
+                ..Default::default()
+            })
+            .await?;
+        if dogs.is_empty() {
+            return Err(Error::not_found("测试狗狗不存在"));
+        }
+        let now = Utc::now();
+        self.create_walk_request(WalkRequestCreate {
+            dogs,
+            should_start_after: Some(now),
+            should_start_before: Some(now + chrono::Duration::hours(1)),
+            should_end_after: Some(now),
+            should_end_before: Some(now + chrono::Duration::hours(2)),
+            latitude: SYNTHETIC_LATITUDE,
+            longitude: SYNTHETIC_LONGITUDE,
+            created_by: owner_id.to_owned(),
+            notify_favorites: false,
+            priority_walkers: vec![walker_id.to_owned()],
+            priority_until: Some(now + chrono::Duration::minutes(FAVORITES_PRIORITY_MINUTES)),
+            notify_walker_nearby: false,
+            route_preference: None,
+            partner_id: None,
+        })
+        .await
+    }
+
+    pub async fn create_partner(&self, create: PartnerCreate) -> Result<String, Error> {
+        if create.name.trim().is_empty() {
+            return Err(Error::msg("合作方名称不能为空"));
+        }
+        if create.rate_limit <= 0 {
+            return Err(Error::msg("请求上限必须大于0"));
+        }
+        self.repository.create_partner(create).await
+    }
+
+    pub async fn partners(&self, pagination: Pagination) -> Result<(Vec<Partner>, i64), Error> {
+        self.repository.query_partners(pagination).await
+    }
+
+    pub async fn update_partner(&self, id: &str, update: PartnerUpdate) -> Result<Partner, Error> {
+        if update.name.as_ref().is_some_and(|n| n.trim().is_empty()) {
+            return Err(Error::msg("合作方名称不能为空"));
+        }
+        if update.rate_limit.is_some_and(|l| l <= 0) {
+            return Err(Error::msg("请求上限必须大于0"));
+        }
+        self.repository
+            .update_partner(id, update)
+            .await?
+            .ok_or(Error::not_found("合作方不存在"))
+    }
+
+    // 签发API密钥, 明文只在签发时返回一次, 之后只能通过前缀辨认
+    pub async fn issue_partner_api_key(
+        &self,
+        partner_id: &str,
+    ) -> Result<(PartnerApiKey, String), Error> {
+        if self.repository.get_partner(partner_id).await?.is_none() {
+            return Err(Error::not_found("合作方不存在"));
+        }
+        let key = random_token();
+        let prefix = key[..PARTNER_API_KEY_PREFIX_LENGTH].to_owned();
+        let id = self
+            .repository
+            .create_partner_api_key(PartnerApiKeyCreate {
+                partner_id: partner_id.to_owned(),
+                key_hash: hash_token(&key),
+                prefix: prefix.clone(),
+            })
+            .await?;
+        Ok((
+            PartnerApiKey {
+                id,
+                partner_id: partner_id.to_owned(),
+                prefix,
+                revoked_at: None,
+                created_at: Some(Utc::now()),
+            },
+            key,
+        ))
+    }
+
+    pub async fn partner_api_keys(&self, partner_id: &str) -> Result<Vec<PartnerApiKey>, Error> {
+        self.repository.query_partner_api_keys(partner_id).await
+    }
+
+    pub async fn revoke_partner_api_key(&self, partner_id: &str, id: &str) -> Result<(), Error> {
+        if self
+            .repository
+            .revoke_partner_api_key(partner_id, id)
+            .await?
+        {
+            Ok(())
+        } else {
+            Err(Error::not_found("密钥不存在或已吊销"))
+        }
+    }
+
+    // 密钥已吊销或合作方已停用时认证失败
+    pub async fn authenticate_partner(&self, api_key: &str) -> Result<Partner, Error> {
+        let key = self
+            .repository
+            .get_partner_api_key(&hash_token(api_key))
+            .await?
+            .ok_or(Error::msg("API密钥无效"))?;
+        self.repository
+            .get_partner(&key.partner_id)
+            .await?
+            .filter(|p| !p.disabled)
+            .ok_or(Error::not_found("合作方不存在或已停用"))
+    }
+
+    // 按接口记录一次调用, 最近一分钟的调用次数达到合作方的请求上限时返回false, 且不计入用量
+    pub async fn record_partner_usage(
+        &self,
+        partner: &Partner,
+        endpoint: &str,
+    ) -> Result<bool, Error> {
+        let since = Utc::now() - chrono::Duration::minutes(1);
+        if self
+            .repository
+            .count_partner_usages(&partner.id, since)
+            .await?
+            >= partner.rate_limit
+        {
+            return Ok(false);
+        }
+        self.repository
+            .create_partner_usage(&partner.id, endpoint)
+            .await?;
+        Ok(true)
+    }
+
+    pub async fn partner_usage(
+        &self,
+        partner_id: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<PartnerUsage>, Error> {
+        if from >= to {
+            return Err(Error::msg("统计起始时间必须早于结束时间"));
+        }
+        if to - from > chrono::Duration::days(MAX_PARTNER_USAGE_DAYS) {
+            return Err(Error::msg("统计范围不能超过一年"));
+        }
+        self.repository
+            .partner_usage_report(partner_id, from, to)
+            .await
+    }
+
+    pub async fn grant_partner_consent(
+        &self,
+        owner_id: &str,
+        partner_id: &str,
+    ) -> Result<PartnerConsent, Error> {
+        if !self
+            .repository
+            .get_partner(partner_id)
+            .await?
+            .is_some_and(|p| !p.disabled)
+        {
+            return Err(Error::not_found("合作方不存在或已停用"));
+        }
+        self.repository
+            .create_partner_consent(partner_id, owner_id)
+            .await
+    }
+
+    // 撤销授权后合作方不能再代为创建请求, 也不能再查询此前代为创建的请求
+    pub async fn revoke_partner_consent(
+        &self,
+        owner_id: &str,
+        partner_id: &str,
+    ) -> Result<(), Error> {
+        if self
+            .repository
+            .delete_partner_consent(partner_id, owner_id)
+            .await?
+        {
+            Ok(())
+        } else {
+            Err(Error::forbidden("未授权该合作方"))
+        }
+    }
+
+    pub async fn partner_consents(&self, owner_id: &str) -> Result<Vec<PartnerConsent>, Error> {
+        self.repository
+            .query_partner_consents(PartnerConsentQuery {
+                owner_id: Some(owner_id.to_owned()),
+                ..Default::default()
+            })
+            .await
+    }
+
+    async fn ensure_partner_consent(&self, partner_id: &str, owner_id: &str) -> Result<(), Error> {
+        if self
+            .repository
+            .query_partner_consents(PartnerConsentQuery {
+                partner_id: Some(partner_id.to_owned()),
+                owner_id: Some(owner_id.to_owned()),
+            })
+            .await?
+            .is_empty()
+        {
+            return Err(Error::forbidden("狗狗主人未授权该合作方"));
+        }
+        Ok(())
+    }
+
+    // 合作方代已授权的狗狗主人创建请求, 狗狗必须属于该狗狗主人
+    pub async fn create_partner_walk_request(
+        &self,
+        partner_id: &str,
+        dog_ids: Vec<String>,
+        mut request: WalkRequestCreate,
+    ) -> Result<String, Error> {
+        self.ensure_partner_consent(partner_id, &request.created_by)
+            .await?;
+        if dog_ids.is_empty() {
+            return Err(Error::msg("至少选择一只狗狗"));
+        }
+        let dogs = self
+            .repository
+            .query_dogs(&DogQuery {
+                id_in: Some(dog_ids.clone()),
+                owner_id: Some(request.created_by.clone()),
+                ..Default::default()
+            })
+            .await?;
+        if dogs.len() != dog_ids.len() {
+            return Err(Error::not_found("狗狗不存在或不属于该狗狗主人"));
+        }
+        request.dogs = dogs;
+        request.partner_id = Some(partner_id.to_owned());
+        self.create_walk_request(request).await
+    }
+
+    pub async fn partner_walk_request(
+        &self,
+        partner_id: &str,
+        request_id: &str,
+    ) -> Result<WalkRequest, Error> {
+        let request = self.repository.get_walk_request(request_id).await?;
+        if request.partner_id.as_deref() != Some(partner_id) {
+            return Err(Error::not_found("请求不存在"));
+        }
+        self.ensure_partner_consent(partner_id, &request.created_by)
+            .await?;
+        Ok(request)
+    }
+
+    // 合作方排班用的汇总数据, 响应时间取区域内可用遛狗人近期接单耗时的中位数
+    pub async fn walker_availability(
+        &self,
+        query: AvailableWalkerQuery,
+    ) -> Result<WalkerAvailability, Error> {
+        if query.from >= query.to {
+            return Err(Error::msg("开始时间必须早于结束时间"));
+        }
+        if query.to - query.from > chrono::Duration::days(MAX_AVAILABILITY_WINDOW_DAYS) {
+            return Err(Error::msg("查询时间窗口不能超过一周"));
+        }
+        if !(query.radius > 0.0 && query.radius <= MAX_AVAILABILITY_RADIUS) {
+            return Err(Error::msg("查询半径必须大于0且不超过20公里"));
+        }
+        let walker_ids = self.repository.available_walker_ids(query).await?;
+        let available_walkers = walker_ids.len() as i64;
+        if walker_ids.len() < MIN_AVAILABILITY_WALKERS {
+            return Ok(WalkerAvailability {
+                available_walkers,
+                median_response_seconds: None,
+            });
+        }
+        let since = Utc::now() - chrono::Duration::days(RESPONSE_TIME_WINDOW_DAYS);
+        let mut seconds = self
+            .repository
+            .walker_response_times(walker_ids, since)
+            .await?;
+        seconds.sort_unstable();
+        Ok(WalkerAvailability {
+            available_walkers,
+            median_response_seconds: match seconds.len() {
+                0 => None,
+                n if n % 2 == 0 => Some((seconds[n / 2 - 1] + seconds[n / 2]) / 2),
+                n => Some(seconds[n / 2]),
+            },
+        })
+    }
+
+    // 营销网站城市页面使用的公开数据, 不需要登录. 查询按网格取整后缓存,
+    // 样本过少的网格不返回具体数值, 避免推断出单个用户
+    pub async fn public_neighborhood_stats(
+        &self,
+        latitude: f64,
+        longitude: f64,
+        radius: f64,
+    ) -> Result<Vec<NeighborhoodStats>, Error> {
+        if !(radius > 0.0 && radius <= MAX_PUBLIC_STATS_RADIUS) {
+            return Err(Error::msg("查询半径必须大于0且不超过50公里"));
+        }
+        let cell = NearbyCell::new(latitude, longitude, radius);
+        if let Some(stats) = self.neighborhood_stats_cache.get(&cell) {
+            return Ok(stats);
+        }
+        let stats = self
+            .repository
+            .neighborhood_stats(NeighborhoodStatsQuery {
+                longitude: cell.longitude(),
+                latitude: cell.latitude(),
+                radius: cell.radius(),
+                cell_size: NEIGHBORHOOD_CELL_SIZE,
+                priced_since: Utc::now() - chrono::Duration::days(NEIGHBORHOOD_PRICE_DAYS),
+            })
+            .await?
+            .into_iter()
+            .filter(|s| {
+                s.active_requests >= MIN_NEIGHBORHOOD_SAMPLES
+                    || s.priced_walks >= MIN_NEIGHBORHOOD_SAMPLES
+            })
+            .map(|s| NeighborhoodStats {
+                active_requests: match s.active_requests {
+                    n if n < MIN_NEIGHBORHOOD_SAMPLES => 0,
+                    n => n,
+                },
+                average_price: s
+                    .average_price
+                    .filter(|_| s.priced_walks >= MIN_NEIGHBORHOOD_SAMPLES)
+                    .map(f64::round),
+                ..s
+            })
+            .collect::<Vec<NeighborhoodStats>>();
+        self.neighborhood_stats_cache.put(cell, stats.clone());
+        Ok(stats)
+    }
+
+    // 邀请尚未注册的家庭成员或遛狗人. 调用方需先确认该手机号未注册
+    pub async fn send_invite<S>(
+        &self,
+        sender: &S,
+        inviter_id: &str,
+        phone: &str,
+        kind: InviteKind,
+        locale: &str,
+    ) -> Result<String, Error>
+    where
+        S: SmsSender,
+    {
+        if !is_e164_phone(phone) {
+            return Err(Error::msg(
+                "手机号格式不正确, 需包含国家代码, 如+8613800000000",
+            ));
+        }
+        let locale = if INVITE_LOCALES.contains(&locale) {
+            locale
+        } else {
+            DEFAULT_HELP_LOCALE
+        };
+        let now = Utc::now();
+        if self
+            .repository
+            .count_invites(InviteQuery {
+                inviter_id: Some(inviter_id.to_owned()),
+                created_after: Some(now - chrono::Duration::days(1)),
+                ..Default::default()
+            })
+            .await?
+            >= MAX_INVITES_PER_DAY
+        {
+            return Err(Error::msg("今天发送的邀请过多, 请明天再试"));
+        }
+        if self
+            .repository
+            .count_invites(InviteQuery {
+                phone: Some(phone.to_owned()),
+                created_after: Some(now - chrono::Duration::days(1)),
+                ..Default::default()
+            })
+            .await?
+            > 0
+        {
+            return Err(Error::msg("该手机号今天已收到过邀请"));
+        }
+        let code = random_token();
+        let id = self
+            .repository
+            .create_invite(InviteCreate {
+                inviter_id: inviter_id.to_owned(),
+                phone: phone.to_owned(),
+                kind,
+                locale: locale.to_owned(),
+                code_hash: hash_token(&code),
+            })
+            .await?;
+        let link = format!("{}{}", self.invite_link_base, code);
+        sender
+            .send(phone, &invite_message(kind, locale, &link))
+            .await?;
+        Ok(id)
+    }
+
+    pub async fn my_invites(
+        &self,
+        inviter_id: &str,
+        pagination: Pagination,
+    ) -> Result<(Vec<Invite>, i64), Error> {
+        self.repository.query_invites(inviter_id, pagination).await
+    }
+
+    // 被邀请人点开链接时调用, 用于统计打开率
+    pub async fn open_invite(&self, code: &str) -> Result<Option<Invite>, Error> {
+        self.repository.open_invite(&hash_token(code)).await
+    }
+
+    // 用户注册后调用, 发往该手机号的邀请都记为转化
+    pub async fn convert_invites(&self, phone: &str, user_id: &str) -> Result<u64, Error> {
+        self.repository.convert_invites(phone, user_id).await
+    }
+
+    pub async fn invite_conversion(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<InviteConversion>, Error> {
+        if from >= to {
+            return Err(Error::msg("开始时间必须早于结束时间"));
+        }
+        if to - from > chrono::Duration::days(MAX_INVITE_REPORT_DAYS) {
+            return Err(Error::msg("统计时间范围不能超过一年"));
+        }
+        self.repository.invite_conversion(from, to).await
+    }
+
+    // 为分享令牌、邀请码或二维码生成短链接, 返回短链接和过期时间
+    pub async fn create_short_link(
+        &self,
+        user_id: &str,
+        kind: ShortLinkKind,
+        token: &str,
+        ttl: Option<chrono::Duration>,
+    ) -> Result<(String, DateTime<Utc>), Error> {
+        if token.is_empty()
+            || token.len() > MAX_SHORT_LINK_TOKEN_LENGTH
+            || !token
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(Error::msg("无效的链接参数"));
+        }
+        let ttl = ttl.unwrap_or(chrono::Duration::days(DEFAULT_SHORT_LINK_TTL_DAYS));
+        if ttl <= chrono::Duration::zero() || ttl > chrono::Duration::days(MAX_SHORT_LINK_TTL_DAYS)
+        {
+            return Err(Error::msg("短链接有效期必须在30天以内"));
+        }
+        let expires_at = Utc::now() + ttl;
+        // 随机码与已有短链接重复时换一个重试
+        for _ in 0..SHORT_LINK_CODE_ATTEMPTS {
+            let code = rand::thread_rng()
+                .sample_iter(&Alphanumeric)
+                .take(SHORT_LINK_CODE_LENGTH)
+                .map(char::from)
+                .collect::<String>();
+            match self
+                .repository
+                .create_short_link(ShortLinkCreate {
+                    code: code.clone(),
+                    kind,
+                    token: token.to_owned(),
+                    created_by: user_id.to_owned(),
+                    expires_at,
+                })
+                .await
+            {
+                Ok(_) => return Ok((format!("{}{}", self.short_link_base, code), expires_at)),
+                Err(e) if e.is_duplicate("code") => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        Err(Error::new("failed to create short link").with_cause("no unique code available"))
+    }
+
+    // 返回短链接对应的App深度链接
+    pub async fn resolve_short_link(&self, code: &str) -> Result<String, Error> {
+        let link = self
+            .repository
+            .get_short_link(code)
+            .await?
+            .ok_or(Error::not_found("短链接不存在或已过期"))?;
+        Ok(format!(
+            "{}{}/{}",
+            self.deep_link_scheme,
+            link.kind.deep_link_path(),
+            link.token
+        ))
+    }
+
+    pub async fn self_check(&self) -> Result<(), Error> {
+        self.repository.self_check().await
+    }
+}
+
+const MAX_NICKNAME_LENGTH: usize = 20;
+const MAX_EMERGENCY_CONTACTS: usize = 3;
+
+const MAX_CHANGES: i64 = 200; // 单次增量同步返回的最大变化数
+const MAX_NEARBY_CANDIDATES: i64 = 1000; // 每个附近网格最多缓存的候选请求数
+const MAX_WALKER_SEARCH_LIMIT: i64 = 50; // 遛狗人搜索每页最多返回的数量
+
+const HUNDRED_KILOMETERS: f64 = 100_000.0;
+const FIVE_STAR_REVIEWS_FOR_ACHIEVEMENT: i64 = 50;
+// 狗狗主人信誉的统计窗口和各项权重, 权重合计为1
+const REPUTATION_WINDOW_DAYS: i64 = 180;
+const REPUTATION_CANCELLATION_WEIGHT: f64 = 0.3;
+const REPUTATION_NO_SHOW_WEIGHT: f64 = 0.3;
+const REPUTATION_PAYMENT_WEIGHT: f64 = 0.1;
+const REPUTATION_RATING_WEIGHT: f64 = 0.3;
+const OWNER_RATING_PRIOR: f64 = 4.0; // 平滑用的先验评分, 相当于OWNER_RATING_PRIOR_WEIGHT条该评分的评价
+const OWNER_RATING_PRIOR_WEIGHT: f64 = 3.0;
+
+// 开启notify_favorites时, 请求在此时长内仅对收藏的遛狗人可见
+const FAVORITES_PRIORITY_MINUTES: i64 = 10;
+
+// 同一手机号两次发送验证码的最小间隔, 以及每小时最多发送次数
+const OTP_RESEND_INTERVAL_SECS: i64 = 60;
+const MAX_OTPS_PER_HOUR: i64 = 5;
+const MAX_OTP_ATTEMPTS: i64 = 5; // 同一验证码最多输错的次数
+const PASSWORD_RESET_TOKEN_MINUTES: i64 = 15;
+const OAUTH_LINK_TOKEN_MINUTES: i64 = 10;
+const ACTION_TOKEN_MINUTES: i64 = 5;
+// 遛狗人与接狗地点的距离小于此值(米)时通知狗狗主人
+const WALKER_NEARBY_RADIUS: f64 = 300.0;
+// 超过此时长仍未推送的通知不再推送, 避免推送任务长时间停止后集中推送过时的通知
+const PUSH_MAX_DELAY_MINUTES: i64 = 30;
+// 超过此时长仍未发送的邮件不再发送
+const EMAIL_MAX_DELAY_HOURS: i64 = 24;
+// 距计划开始不到此时长时取消已接单的请求, 同时以短信通知狗狗主人
+const SMS_CANCEL_WINDOW_HOURS: i64 = 3;
+// 遛狗前见面的时长范围(分钟)
+const MIN_MEET_AND_GREET_MINUTES: i64 = 10;
+const MAX_MEET_AND_GREET_MINUTES: i64 = 60;
+const MAX_PREFERRED_ROUTE_POINTS: usize = 500;
+const MAX_NO_GO_ZONES: usize = 20;
+const MAX_NO_GO_ZONE_RADIUS: f64 = 2_000.0;
+// 兴趣点范围(半径或边界顶点到中心的距离)的上限(米)
+const MAX_POI_RADIUS: f64 = 2_000.0;
+// 提现金额以分为单位
+const MIN_WITHDRAWAL_AMOUNT: i64 = 100;
+const MAX_WITHDRAWAL_BATCH: usize = 100;
+const MAX_DAILY_WITHDRAWAL_AMOUNT: i64 = 500_000;
+// 冷却窗口内失败的提现尝试达到该次数后拒绝继续提现
+const MAX_FAILED_PAYMENT_ATTEMPTS: i64 = 3;
+const PAYMENT_COOL_DOWN_MINUTES: i64 = 30;
+// 遛狗人迟取消罚金, 以分为单位
+const LATE_CANCELLATION_PENALTY: i64 = 1_000;
+const VERY_LATE_CANCELLATION_PENALTY: i64 = 3_000;
+const NO_SHOW_CANCELLATION_PENALTY: i64 = 5_000;
+// 每只狗狗的遛狗价格(分), 平台按百分比抽取服务费
+const WALK_PRICE_PER_DOG: i64 = 3_000;
+const PLATFORM_FEE_PERCENT: i64 = 20;
+// 运营看板中失败打款的统计窗口
+const OPERATIONS_WINDOW_MINUTES: i64 = 60;
+// 预签名直传只用于大文件, 小文件仍通过上传接口
+const MAX_DIRECT_UPLOAD_BYTES: i64 = 2 * 1024 * 1024 * 1024;
+const DIRECT_UPLOAD_CONTENT_TYPES: [&str; 2] = ["video/", "image/"];
+const PRESIGNED_UPLOAD_MINUTES: i64 = 15;
+const PRESIGNED_DOWNLOAD_MINUTES: i64 = 10;
+// 上传后超过该时长仍未被引用的文件视为孤立文件, 每次最多清理的数量
+const ORPHAN_UPLOAD_GRACE_HOURS: i64 = 24;
+const ORPHAN_UPLOAD_BATCH: i64 = 500;
+const INTEGRITY_CHECK_LIMIT: i64 = 200; // 每类问题每次最多处理的数量
+const FIELD_ENCRYPTION_BATCH: i64 = 500;
+// 合作方API密钥中用于辨认的前缀长度, 以及用量报表的最大统计天数
+const PARTNER_API_KEY_PREFIX_LENGTH: usize = 8;
+const MAX_PARTNER_USAGE_DAYS: i64 = 366;
+// 遛狗人可用情况的查询范围; 可用人数少于MIN_AVAILABILITY_WALKERS时不返回响应时间
+const MAX_AVAILABILITY_RADIUS: f64 = 20_000.0;
+const MAX_AVAILABILITY_WINDOW_DAYS: i64 = 7;
+const MIN_AVAILABILITY_WALKERS: usize = 3;
+const RESPONSE_TIME_WINDOW_DAYS: i64 = 30;
+// 公开区域数据的网格大小(度, 约2公里)、价格统计天数, 以及网格内返回具体数值的最少样本数
+const MAX_PUBLIC_STATS_RADIUS: f64 = 50_000.0;
+const NEIGHBORHOOD_CELL_SIZE: f64 = 0.02;
+const NEIGHBORHOOD_PRICE_DAYS: i64 = 90;
+const MIN_NEIGHBORHOOD_SAMPLES: i64 = 3;
+
+const MAX_INVITES_PER_DAY: i64 = 20;
+const SHORT_LINK_CODE_LENGTH: usize = 8;
+const SHORT_LINK_CODE_ATTEMPTS: usize = 3;
+const MAX_SHORT_LINK_TOKEN_LENGTH: usize = 128;
+const DEFAULT_SHORT_LINK_TTL_DAYS: i64 = 7;
+const MAX_SHORT_LINK_TTL_DAYS: i64 = 30;
+const MAX_INVITE_REPORT_DAYS: i64 = 366;
+const INVITE_LOCALES: [&str; 2] = ["zh-CN", "en-US"];
+// 合成监控的遛狗请求位置, 远离真实用户
+const SYNTHETIC_LATITUDE: f64 = 0.0;
+const SYNTHETIC_LONGITUDE: f64 = 0.0;
+
+pub const DEFAULT_HELP_LOCALE: &str = "zh-CN";
+
+fn random_token() -> String {
+    rand::random::<[u8; 32]>()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+// 数据库只保存刷新令牌的哈希, 泄露的数据无法直接用于刷新
+fn hash_token(token: &str) -> String {
+    Sha256::digest(token.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+// 账号只保留后4位, 户名只保留最后一个字
+fn mask_account_number(account_number: &str) -> String {
+    let chars = account_number.trim().chars().collect::<Vec<char>>();
+    let visible = chars.len().min(4);
+    format!(
+        "{}{}",
+        "*".repeat(chars.len() - visible),
+        chars[chars.len() - visible..].iter().collect::<String>()
+    )
+}
+
+fn mask_name(name: &str) -> String {
+    let chars = name.trim().chars().collect::<Vec<char>>();
+    match chars.split_last() {
+        Some((last, rest)) => format!("{}{}", "*".repeat(rest.len()), last),
+        None => String::new(),
+    }
+}
+
+// 各项按权重合计后换算为0-100分. 评分以先验评分平滑, 评价很少时不会被单个评价左右;
+// 没有被接单过的狗狗主人取消率和爽约率按0计算
+fn owner_reputation(
+    owner_id: &str,
+    stats: &OwnerReputationStats,
+    payment_failures: i64,
+) -> OwnerReputation {
+    let rate = |n: i64| {
+        if stats.accepted > 0 {
+            n as f64 / stats.accepted as f64
+        } else {
+            0.0
+        }
+    };
+    let cancellation_rate = rate(stats.canceled_after_acceptance);
+    let no_show_rate = rate(stats.no_shows);
+    let smoothed_rating = (stats.average_rating * stats.review_count as f64
+        + OWNER_RATING_PRIOR * OWNER_RATING_PRIOR_WEIGHT)
+        / (stats.review_count as f64 + OWNER_RATING_PRIOR_WEIGHT);
+    let score = REPUTATION_CANCELLATION_WEIGHT * (1.0 - cancellation_rate)
+        + REPUTATION_NO_SHOW_WEIGHT * (1.0 - no_show_rate)
+        + REPUTATION_PAYMENT_WEIGHT / (1 + payment_failures) as f64
+        + REPUTATION_RATING_WEIGHT * smoothed_rating / 5.0;
+    OwnerReputation {
+        owner_id: owner_id.to_owned(),
+        requests: stats.requests,
+        cancellation_rate,
+        no_show_rate,
+        payment_failures,
+        average_rating: stats.average_rating,
+        review_count: stats.review_count,
+        score: (score * 100.0).round(),
+    }
+}
+
+// 距预定开始24小时以上放弃不罚
+fn cancellation_penalty_tier(notice: chrono::Duration) -> Option<CancellationPenaltyTier> {
+    if notice >= chrono::Duration::hours(24) {
+        None
+    } else if notice >= chrono::Duration::hours(2) {
+        Some(CancellationPenaltyTier::Late)
+    } else if notice >= chrono::Duration::zero() {
+        Some(CancellationPenaltyTier::VeryLate)
+    } else {
+        Some(CancellationPenaltyTier::NoShow)
+    }
+}
+
+fn cancellation_penalty_amount(tier: CancellationPenaltyTier) -> i64 {
+    match tier {
+        CancellationPenaltyTier::Late => LATE_CANCELLATION_PENALTY,
+        CancellationPenaltyTier::VeryLate => VERY_LATE_CANCELLATION_PENALTY,
+        CancellationPenaltyTier::NoShow => NO_SHOW_CANCELLATION_PENALTY,
+    }
+}
+
+// 预定时段, 开始取最早开始时间, 结束取最晚结束时间, 缺少结束时间时按开始时间计
+fn schedule_window(
+    start_after: Option<DateTime<Utc>>,
+    start_before: Option<DateTime<Utc>>,
+    end_after: Option<DateTime<Utc>>,
+    end_before: Option<DateTime<Utc>>,
+) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let start = start_after.or(start_before)?;
+    let end = end_before.or(end_after).unwrap_or(start).max(start);
+    Some((start, end))
+}
+
+fn walk_quote(dogs: usize) -> WalkQuote {
+    let price = WALK_PRICE_PER_DOG * dogs as i64;
+    let platform_fee = platform_fee(price);
+    WalkQuote {
+        price,
+        platform_fee,
+        walker_earning: price - platform_fee,
+    }
+}
+
+fn platform_fee(amount: i64) -> i64 {
+    amount * PLATFORM_FEE_PERCENT / 100
+}
+
+fn push_title(kind: NotificationKind) -> &'static str {
+    match kind {
+        NotificationKind::WalkerNearby => "遛狗人即将到达",
+        NotificationKind::RequestAccepted => "已接单",
+        NotificationKind::WalkStarted => "遛狗开始",
+        NotificationKind::WalkFinished => "遛狗结束",
+        NotificationKind::RequestCanceled => "遛狗已取消",
+        NotificationKind::MeetAndGreetProposed => "见面邀请",
+        NotificationKind::MeetAndGreetAccepted => "见面已确认",
+        NotificationKind::MeetAndGreetCanceled => "见面已取消",
+    }
+}
+
+// 金额以分为单位, 显示为元
+fn format_amount(amount: i64) -> String {
+    format!(
+        "{}{}.{:02}",
+        if amount < 0 { "-" } else { "" },
+        amount.abs() / 100,
+        amount.abs() % 100
+    )
+}
+
+fn starts_soon(request: &WalkRequest) -> bool {
+    request
+        .should_start_after
+        .is_some_and(|start| start - Utc::now() < chrono::Duration::hours(SMS_CANCEL_WINDOW_HOURS))
+}
+
+// 从请求中移除狗狗, 没有其他狗狗时改为取消请求
+fn without_dog(dogs: Vec<Dog>, dog_id: &str, actor_id: Option<String>) -> WalkRequestUpdate {
+    let dogs = dogs
+        .into_iter()
+        .filter(|d| d.id.as_str() != dog_id)
+        .collect::<Vec<Dog>>();
+    if dogs.is_empty() {
+        WalkRequestUpdate {
+            canceled_at: Some(Utc::now()),
+            actor_id,
+            ..Default::default()
+        }
+    } else {
+        WalkRequestUpdate {
+            dogs: Some(dogs),
+            ..Default::default()
+        }
+    }
+}
+
+// 期望路线至少需要两个点, 禁行区域半径需为正数
+// E.164格式: +国家代码和号码, 共8到15位数字
+fn is_e164_phone(phone: &str) -> bool {
+    phone.strip_prefix('+').is_some_and(|digits| {
+        (8..=15).contains(&digits.len())
+            && !digits.starts_with('0')
+            && digits.chars().all(|c| c.is_ascii_digit())
+    })
+}
+
+fn invite_message(kind: InviteKind, locale: &str, link: &str) -> String {
+    match (locale, kind) {
+        ("en-US", InviteKind::FamilyMember) => {
+            format!("Your family invited you to share dog walks on Little Walk: {link}")
+        }
+        ("en-US", InviteKind::PreferredWalker) => {
+            format!("A dog owner invited you to be their walker on Little Walk: {link}")
+        }
+        (_, InviteKind::FamilyMember) => format!("您的家人邀请您加入小遛, 一起照顾狗狗: {link}"),
+        (_, InviteKind::PreferredWalker) => {
+            format!("有狗狗主人邀请您成为TA的遛狗人, 点击链接加入小遛: {link}")
+        }
+    }
+}
+
+fn validate_route_preference(route_preference: &RoutePreference) -> Result<(), Error> {
+    if route_preference.preferred_route.len() == 1 {
+        return Err(Error::msg("期望路线至少需要两个点"));
+    }
+    if route_preference.preferred_route.len() > MAX_PREFERRED_ROUTE_POINTS {
+        return Err(Error::msg("期望路线的点数过多"));
+    }
+    if route_preference.no_go_zones.len() > MAX_NO_GO_ZONES {
+        return Err(Error::msg("禁行区域过多"));
+    }
+    let is_valid = |longitude: f64, latitude: f64| {
+        (-180.0..=180.0).contains(&longitude) && (-90.0..=90.0).contains(&latitude)
+    };
+    if route_preference
+        .preferred_route
+        .iter()
+        .any(|p| !is_valid(p.longitude, p.latitude))
+        || route_preference
+            .no_go_zones
+            .iter()
+            .any(|z| !is_valid(z.longitude, z.latitude))
+    {
+        return Err(Error::msg("坐标不合法"));
+    }
+    if route_preference
+        .no_go_zones
+        .iter()
+        .any(|z| !(z.radius > 0.0 && z.radius <= MAX_NO_GO_ZONE_RADIUS))
+    {
+        return Err(Error::msg("禁行区域半径不合法"));
+    }
+    Ok(())
+}
+
+use super::{
+    entities::{
+        AccountDeletion, AccountMerge, Achievement, AchievementKind, AuditLog, Block,
+        CancellationPenalty, CancellationPenaltyTier, ChatMessage, ChatReadMarker, Device,
+        DirectUpload, DirectUploadStatus, EmailKind, Favorite, GeoPoint, HelpArticle, ImageSize,
+        IntegrityIssueKind, IntegrityPolicy, IntegrityReport, Invite, InviteConversion, InviteKind,
+        LedgerEntry, LocationAccess, LocationAccessKind, MeetAndGreet, MeetAndGreetStatus,
+        MergedReference, NeighborhoodStats, Notification, NotificationKind, NotificationSettings,
+        OAuthLinkToken, OAuthLoginOutcome, OAuthProviderKind, OperationsSnapshot, OtpPurpose,
+        OwnerProfile, OwnerReputation, OwnerReputationStats, Partner, PartnerApiKey,
+        PartnerConsent, PartnerUsage, PayoutAccount, PayoutAccountStatus, PayoutMethod, Poi,
+        PurgedCounts, PushPlatform, RankedWalker, RefreshToken, RefundRequest, RefundStatus,
+        Report, ReportStatus, Review, ReviewKind, ReviewState, Role, RoutePreference,
+        SensitiveAction, Session, ShortLinkKind, SyntheticStep, Ticket, TicketStatus,
+        VerificationStatus, WalkQuote, WalkRequest, WalkRequestChanges, WalkRequestExport,
+        WalkRouteReport, Walker, WalkerAvailability, WalkerStats, Withdrawal, WithdrawalStatus,
+    },
+    repository::{
+        AccountDeletionCreate, AccountMergeCreate, ActionTokenCreate, AuditLogQuery,
+        AvailableWalkerQuery, BlockQuery, CancellationPenaltyCreate, ChatMessageCreate,
+        ChatReadMarkerUpsert, DeviceTokenQuery, DeviceTokenUpsert, DirectUploadCreate, EmailCreate,
+        FavoriteQuery, HelpArticleCreate, HelpArticleQuery, InviteCreate, InviteQuery,
+        LocationAccessCreate, LocationAccessQuery, MeetAndGreetCreate, MeetAndGreetQuery,
+        MeetAndGreetUpdate, NeighborhoodStatsQuery, NotificationCreate, NotificationSettingsUpsert,
+        OAuthAccountCreate, OAuthLinkTokenCreate, Order, OtpCreate, OwnerUpdate,
+        PartnerApiKeyCreate, PartnerConsentQuery, PartnerCreate, PartnerUpdate,
+        PasswordResetTokenCreate, PaymentAttemptCreate, PayoutAccountUpsert, PhoneBindingUpsert,
+        PoiCreate, PoiQuery, QueryPlan, QueryTemplate, RefreshTokenCreate, RefundRequestCreate,
+        RefundRequestQuery, RefundReview, ReportCreate, ReportQuery, ReportUpdate, ReviewCreate,
+        ReviewQuery, RevokedAccessTokenCreate, SessionQuery, ShortLinkCreate, SortBy, TicketCreate,
+        TicketMessageCreate, TicketQuery, TicketUpdate, TranslationUpsert, UploadVariantCreate,
+        WalkRequestCreate, WalkRequestQuery, WalkRequestUpdate, WalkerQuery, WalkerSearch,
+        WalkerUpdate, WalkerVerificationSubmit, WalkingLocationCreate, WithdrawalCreate,
+        WithdrawalQuery, WithdrawalUpdate,
+    },
+};
+use chrono::{DateTime, Datelike, Utc};
+use rand::{distributions::Alphanumeric, Rng};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+impl<R> Service<R> where R: Repository + Clone {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entities::{Category, Gender},
+        ids::{BreedId, DogId},
+        memory::InMemory,
+        repository::DISTANCE_WEIGHT,
+    };
+
+    const PHONE: &str = "13800138000";
+    const OWNER_ID: &str = "owner-1";
+    const WALKER_ID: &str = "walker-1";
+
+    async fn create_otp(service: &Service<InMemory>, code: &str) {
+        create_otp_for(service, PHONE, OtpPurpose::Login, code).await;
+    }
+
+    async fn create_otp_for(
+        service: &Service<InMemory>,
+        phone: &str,
+        purpose: OtpPurpose,
+        code: &str,
+    ) {
+        service
+            .repository
+            .create_otp(OtpCreate {
+                phone: phone.to_owned(),
+                purpose,
+                code_hash: hash_token(code),
+                expires_at: Utc::now() + chrono::Duration::minutes(5),
+            })
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn otp_is_accepted_after_fewer_failures_than_the_limit() {
+        let service = Service::new(InMemory::new());
+        create_otp(&service, "123456").await;
+        for _ in 0..MAX_OTP_ATTEMPTS - 1 {
+            assert!(service
+                .verify_otp(PHONE, OtpPurpose::Login, "000000")
+                .await
+                .is_err());
+        }
+        assert!(service
+            .verify_otp(PHONE, OtpPurpose::Login, "123456")
+            .await
+            .is_ok());
+        assert!(service
+            .verify_otp(PHONE, OtpPurpose::Login, "123456")
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn otp_is_invalidated_after_too_many_failures() {
+        let service = Service::new(InMemory::new());
+        create_otp(&service, "123456").await;
+        for _ in 0..MAX_OTP_ATTEMPTS {
+            assert!(service
+                .verify_otp(PHONE, OtpPurpose::Login, "000000")
+                .await
+                .is_err());
+        }
+        assert!(service
+            .verify_otp(PHONE, OtpPurpose::Login, "123456")
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn changed_phone_maps_to_the_registered_phone() {
+        let service = Service::new(InMemory::new());
+        let (new_phone, third_phone) = ("13900139000", "13700137000");
+        create_otp_for(&service, new_phone, OtpPurpose::PhoneChange, "123456").await;
+        assert!(service
+            .change_phone(OWNER_ID, PHONE, new_phone, "000000")
+            .await
+            .is_err());
+        assert_eq!(service.auth_phone(new_phone).await.unwrap(), new_phone);
+        service
+            .change_phone(OWNER_ID, PHONE, new_phone, "123456")
+            .await
+            .unwrap();
+        assert_eq!(service.auth_phone(new_phone).await.unwrap(), PHONE);
+        assert!(service.auth_phone(PHONE).await.is_err());
+        assert_eq!(
+            service.phone_bound_to(PHONE).await.unwrap().as_deref(),
+            Some(OWNER_ID)
+        );
+        create_otp_for(&service, third_phone, OtpPurpose::PhoneChange, "654321").await;
+        service
+            .change_phone(OWNER_ID, PHONE, third_phone, "654321")
+            .await
+            .unwrap();
+        assert_eq!(service.auth_phone(third_phone).await.unwrap(), PHONE);
+        assert_eq!(service.auth_phone(new_phone).await.unwrap(), new_phone);
+        assert_eq!(service.phone_bound_to(new_phone).await.unwrap(), None);
+    }
+
+    // 对应reset_password处理函数中重置令牌消费之后的步骤, 密码本身由auth_service更新
+    #[tokio::test]
+    async fn password_reset_rejects_refresh_tokens_issued_before() {
+        let service = Service::new(InMemory::new());
+        let device = Device {
+            device_id: Some("phone-1".to_owned()),
+            platform: None,
+        };
+        let refresh_token = service
+            .issue_refresh_token(OWNER_ID, PHONE, device)
+            .await
+            .unwrap();
+        let version = service.token_version(OWNER_ID).await.unwrap();
+        create_otp_for(&service, PHONE, OtpPurpose::PasswordReset, "123456").await;
+        let reset_token = service
+            .issue_password_reset_token(PHONE, "123456")
+            .await
+            .unwrap();
+        assert_eq!(
+            service
+                .consume_password_reset_token(&reset_token)
+                .await
+                .unwrap(),
+            PHONE
+        );
+        service.revoke_all_sessions(OWNER_ID).await.unwrap();
+        assert!(service.rotate_refresh_token(&refresh_token).await.is_err());
+        assert!(service.token_version(OWNER_ID).await.unwrap() > version);
+        assert!(service
+            .consume_password_reset_token(&reset_token)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn phone_bound_by_another_user_is_a_duplicate() {
+        let service = Service::new(InMemory::new());
+        let new_phone = "13900139000";
+        create_otp_for(&service, new_phone, OtpPurpose::PhoneChange, "123456").await;
+        service
+            .change_phone(OWNER_ID, PHONE, new_phone, "123456")
+            .await
+            .unwrap();
+        create_otp_for(&service, new_phone, OtpPurpose::PhoneChange, "654321").await;
+        let e = service
+            .change_phone(WALKER_ID, "13700137000", new_phone, "654321")
+            .await
+            .unwrap_err();
+        assert!(e.is_duplicate("phone"));
+        assert_eq!(
+            service.phone_bound_to(new_phone).await.unwrap().as_deref(),
+            Some(OWNER_ID)
+        );
+    }
+
+    fn dog() -> Dog {
+        Dog {
+            id: DogId::new(),
+            name: "旺财".to_owned(),
+            gender: Gender::Male,
+            breed: Breed {
+                id: BreedId::new(),
+                category: Category::Small,
+                name: "柯基".to_owned(),
+                updated_at: None,
+            },
+            birthday: Utc::now(),
+            owner_id: OWNER_ID.to_owned(),
+            tags: vec![],
+            portrait_id: None,
+            photos: vec![],
+            medical_flags: vec![],
+            version: 0,
+            updated_at: None,
+        }
+    }
+
+    async fn accepted_walk_request(service: &Service<InMemory>, dogs: Vec<Dog>) -> String {
+        let id = service
+            .repository
+            .create_walk_request(WalkRequestCreate {
+                dogs,
+                should_start_after: None,
+                should_start_before: None,
+                should_end_before: None,
+                should_end_after: None,
+                latitude: 39.9,
+                longitude: 116.4,
+                created_by: OWNER_ID.to_owned(),
+                notify_favorites: false,
+                priority_walkers: vec![],
+                priority_until: None,
+                notify_walker_nearby: false,
+                route_preference: None,
+                partner_id: None,
+            })
+            .await
+            .unwrap();
+        service
+            .repository
+            .update_walk_request_by_query(
+                WalkRequestQuery {
+                    id: Some(id.clone()),
+                    ..Default::default()
+                },
+                WalkRequestUpdate {
+                    accepted_by: UpdateField::Set(WALKER_ID.to_owned()),
+                    accepted_at: UpdateField::Set(Utc::now()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        id
+    }
+
+    #[tokio::test]
+    async fn finishing_a_walk_credits_the_walker_once() {
+        let service = Service::new(InMemory::new());
+        let id = accepted_walk_request(&service, vec![dog(), dog()]).await;
+        service.start_walk(&id, WALKER_ID).await.unwrap();
+        service.finish_walk(&id, WALKER_ID).await.unwrap();
+        let e = service.finish_walk(&id, WALKER_ID).await.unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::Conflict);
+        assert_eq!(
+            service.balance(WALKER_ID).await.unwrap(),
+            walk_quote(2).walker_earning
+        );
+    }
+
+    #[tokio::test]
+    async fn synthetic_walks_leave_the_walker_ledger_empty() {
+        let service =
+            Service::new(InMemory::new()).with_synthetic_walker_id(Some(WALKER_ID.to_owned()));
+        let breed_id = service
+            .create_breed(BreedCreate {
+                category: Category::Small,
+                name: "柯基".to_owned(),
+            })
+            .await
+            .unwrap();
+        let dog = service
+            .create_dog(&DogCreate {
+                owner_id: OWNER_ID.to_owned(),
+                name: "旺财".to_owned(),
+                gender: "Male".to_owned(),
+                breed: BreedQuery {
+                    id: Some(breed_id),
+                    ..Default::default()
+                },
+                birthday: Utc::now(),
+                tags: vec![],
+                portrait_id: None,
+                medical_flags: vec![],
+            })
+            .await
+            .unwrap();
+        service
+            .submit_walker_verification(WALKER_ID, vec!["id-card".to_owned()])
+            .await
+            .unwrap();
+        service
+            .approve_walker_verification(WALKER_ID)
+            .await
+            .unwrap();
+        let steps = service
+            .run_synthetic_walk(OWNER_ID, WALKER_ID, &dog.id.to_string())
+            .await;
+        assert!(steps.iter().all(|s| s.error.is_none()));
+        assert_eq!(steps.last().unwrap().name, "finish_walk");
+        assert_eq!(service.balance(WALKER_ID).await.unwrap(), 0);
+        let e = service
+            .request_withdrawal(WALKER_ID, MIN_WITHDRAWAL_AMOUNT)
+            .await
+            .unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::Forbidden);
+    }
+
+    #[tokio::test]
+    async fn only_started_walks_can_be_finished() {
+        let service = Service::new(InMemory::new());
+        let unstarted = accepted_walk_request(&service, vec![dog()]).await;
+        let canceled = accepted_walk_request(&service, vec![dog()]).await;
+        service.start_walk(&canceled, WALKER_ID).await.unwrap();
+        service
+            .repository
+            .update_walk_request_by_query(
+                WalkRequestQuery {
+                    id: Some(canceled.clone()),
+                    ..Default::default()
+                },
+                WalkRequestUpdate {
+                    canceled_at: Some(Utc::now()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        for id in [&unstarted, &canceled] {
+            let e = service.finish_walk(id, WALKER_ID).await.unwrap_err();
+            assert_eq!(e.kind(), ErrorKind::Conflict);
+        }
+        let (entries, total) = service
+            .ledger(WALKER_ID, Pagination { limit: 10, skip: 0 })
+            .await
+            .unwrap();
+        assert!(entries.is_empty());
+        assert_eq!(total, 0);
+    }
+
+    #[tokio::test]
+    async fn chat_access_ends_when_the_walker_resigns() {
+        let service = Service::new(InMemory::new());
+        let id = accepted_walk_request(&service, vec![dog()]).await;
+        service.check_chat_access(&id, WALKER_ID).await.unwrap();
+        service.check_chat_access(&id, OWNER_ID).await.unwrap();
+        service.resign_acceptance(&id, WALKER_ID).await.unwrap();
+        let e = service.check_chat_access(&id, WALKER_ID).await.unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::Forbidden);
+    }
+
+    #[tokio::test]
+    async fn approved_refund_debits_the_walker_share() {
+        let service = Service::new(InMemory::new());
+        let id = accepted_walk_request(&service, vec![dog()]).await;
+        let price = walk_quote(1).price;
+        assert!(service
+            .request_refund(&id, OWNER_ID, price, "迟到".to_owned(), vec![])
+            .await
+            .is_err());
+        service.start_walk(&id, WALKER_ID).await.unwrap();
+        service.finish_walk(&id, WALKER_ID).await.unwrap();
+        assert!(service
+            .request_refund(&id, OWNER_ID, price + 1, "迟到".to_owned(), vec![])
+            .await
+            .is_err());
+        let refund_id = service
+            .request_refund(&id, OWNER_ID, price, "迟到".to_owned(), vec![])
+            .await
+            .unwrap();
+        let refund = service
+            .review_refund(&refund_id, "admin-1", true, Some(1_000), None)
+            .await
+            .unwrap();
+        assert_eq!(refund.status, RefundStatus::Approved);
+        assert_eq!(refund.approved_amount, Some(1_000));
+        assert_eq!(refund.walker_debit, Some(1_000 - platform_fee(1_000)));
+        assert!(service
+            .review_refund(&refund_id, "admin-1", false, None, None)
+            .await
+            .is_err());
+        assert_eq!(
+            service.balance(WALKER_ID).await.unwrap(),
+            walk_quote(1).walker_earning - (1_000 - platform_fee(1_000))
+        );
+        // 累计退款不能超过遛狗费用
+        assert!(service
+            .request_refund(&id, OWNER_ID, price - 999, "迟到".to_owned(), vec![])
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn approved_refunds_for_one_walk_stay_within_the_price() {
+        let service = Service::new(InMemory::new());
+        let id = accepted_walk_request(&service, vec![dog()]).await;
+        service.start_walk(&id, WALKER_ID).await.unwrap();
+        service.finish_walk(&id, WALKER_ID).await.unwrap();
+        let price = walk_quote(1).price;
+        let first = service
+            .request_refund(&id, OWNER_ID, price / 2, "迟到".to_owned(), vec![])
+            .await
+            .unwrap();
+        let e = service
+            .request_refund(&id, OWNER_ID, 1, "迟到".to_owned(), vec![])
+            .await
+            .unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::Conflict);
+        service
+            .review_refund(&first, "admin-1", true, None, None)
+            .await
+            .unwrap();
+        let second = service
+            .request_refund(&id, OWNER_ID, price - price / 2, "迟到".to_owned(), vec![])
+            .await
+            .unwrap();
+        service
+            .review_refund(&second, "admin-1", true, None, None)
+            .await
+            .unwrap();
+        // 绕过申请时的检查写入的申请在审核时按累计退款重新核对
+        let extra = service
+            .repository
+            .create_refund_request(RefundRequestCreate {
+                walk_request_id: id.clone(),
+                owner_id: OWNER_ID.to_owned(),
+                walker_id: WALKER_ID.to_owned(),
+                amount: 1,
+                reason: "迟到".to_owned(),
+                evidence_ids: vec![],
+            })
+            .await
+            .unwrap();
+        let e = service
+            .review_refund(&extra, "admin-1", true, None, None)
+            .await
+            .unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::Conflict);
+        let debited = [price / 2, price - price / 2]
+            .into_iter()
+            .map(|amount| amount - platform_fee(amount))
+            .sum::<i64>();
+        assert_eq!(
+            service.balance(WALKER_ID).await.unwrap(),
+            walk_quote(1).walker_earning - debited
+        );
+    }
+
+    #[tokio::test]
+    async fn late_resignation_is_penalised_by_notice() {
+        let service = Service::new(InMemory::new());
+        for (hours, tier) in [
+            (48, None),
+            (6, Some(CancellationPenaltyTier::Late)),
+            (1, Some(CancellationPenaltyTier::VeryLate)),
+            (-1, Some(CancellationPenaltyTier::NoShow)),
+        ] {
+            let id = accepted_walk_request(&service, vec![dog()]).await;
+            service
+                .repository
+                .update_walk_request_by_query(
+                    WalkRequestQuery {
+                        id: Some(id.clone()),
+                        ..Default::default()
+                    },
+                    WalkRequestUpdate {
+                        should_start_after: Some(Utc::now() + chrono::Duration::hours(hours)),
+                        ..Default::default()
+                    },
+                )
+                .await
+                .unwrap();
+            let penalty = service.resign_acceptance(&id, WALKER_ID).await.unwrap();
+            assert_eq!(penalty.as_ref().map(|p| p.tier), tier);
+            assert!(service.resign_acceptance(&id, WALKER_ID).await.is_err());
+        }
+        assert_eq!(
+            service.balance(WALKER_ID).await.unwrap(),
+            -(LATE_CANCELLATION_PENALTY
+                + VERY_LATE_CANCELLATION_PENALTY
+                + NO_SHOW_CANCELLATION_PENALTY)
+        );
+    }
+
+    #[tokio::test]
+    async fn resigning_an_inactive_request_is_not_penalised() {
+        let service = Service::new(InMemory::new());
+        let canceled = accepted_walk_request(&service, vec![dog()]).await;
+        let finished = accepted_walk_request(&service, vec![dog()]).await;
+        for (id, update) in [
+            (
+                &canceled,
+                WalkRequestUpdate {
+                    should_start_after: Some(Utc::now() + chrono::Duration::hours(1)),
+                    canceled_at: Some(Utc::now()),
+                    ..Default::default()
+                },
+            ),
+            (
+                &finished,
+                WalkRequestUpdate {
+                    should_start_after: Some(Utc::now() - chrono::Duration::hours(1)),
+                    finished_at: Some(Utc::now()),
+                    ..Default::default()
+                },
+            ),
+        ] {
+            service
+                .repository
+                .update_walk_request_by_query(
+                    WalkRequestQuery {
+                        id: Some(id.clone()),
+                        ..Default::default()
+                    },
+                    update,
+                )
+                .await
+                .unwrap();
+            assert!(service.resign_acceptance(id, WALKER_ID).await.is_err());
+            assert!(service
+                .cancellation_penalties(id, WALKER_ID)
+                .await
+                .unwrap()
+                .is_empty());
+        }
+        assert_eq!(service.balance(WALKER_ID).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn walker_role_is_granted_only_after_verification() {
+        let service = Service::new(InMemory::new());
+        assert_eq!(service.user_roles(WALKER_ID).await.unwrap(), [Role::Owner]);
+        service
+            .submit_walker_verification(WALKER_ID, vec!["id-card".to_owned()])
+            .await
+            .unwrap();
+        assert_eq!(service.user_roles(WALKER_ID).await.unwrap(), [Role::Owner]);
+        service
+            .approve_walker_verification(WALKER_ID)
+            .await
+            .unwrap();
+        assert_eq!(
+            service.user_roles(WALKER_ID).await.unwrap(),
+            [Role::Owner, Role::Walker]
+        );
+    }
+
+    #[tokio::test]
+    async fn search_ranks_verified_walkers_by_distance() {
+        let service = Service::new(InMemory::new());
+        for (user_id, latitude) in [("far", 39.93), ("near", 39.91), ("unverified", 39.9)] {
+            service
+                .submit_walker_verification(user_id, vec!["id-card".to_owned()])
+                .await
+                .unwrap();
+            if user_id != "unverified" {
+                service.approve_walker_verification(user_id).await.unwrap();
+            }
+            service
+                .update_walker_location(user_id, 116.4, latitude)
+                .await
+                .unwrap();
+        }
+        let search = |limit, skip| WalkerSearch {
+            longitude: 116.4,
+            latitude: 39.9,
+            radius: 5_000.0,
+            pagination: Pagination { limit, skip },
+        };
+        for (limit, skip) in [(0, 0), (-1, 0), (MAX_WALKER_SEARCH_LIMIT + 1, 0), (10, -1)] {
+            let e = service
+                .search_walkers(OWNER_ID, search(limit, skip))
+                .await
+                .unwrap_err();
+            assert_eq!(e.kind(), ErrorKind::Invalid);
+        }
+        let walkers = service
+            .search_walkers(OWNER_ID, search(10, 0))
+            .await
+            .unwrap();
+        assert_eq!(
+            walkers
+                .iter()
+                .map(|w| w.user_id.as_str())
+                .collect::<Vec<&str>>(),
+            ["near", "far"]
+        );
+        // 没有评价和完成记录时只有距离得分
+        for walker in &walkers {
+            assert!(
+                (walker.score - DISTANCE_WEIGHT * (1.0 - walker.distance / 5_000.0)).abs() < 1e-9
+            );
+        }
+    }
+
+    async fn open_walk_request(
+        service: &Service<InMemory>,
+        owner_id: &str,
+        priority_walkers: Vec<String>,
+    ) -> String {
+        service
+            .repository
+            .create_walk_request(WalkRequestCreate {
+                dogs: vec![dog()],
+                should_start_after: None,
+                should_start_before: None,
+                should_end_before: None,
+                should_end_after: None,
+                latitude: 39.9,
+                longitude: 116.4,
+                created_by: owner_id.to_owned(),
+                notify_favorites: !priority_walkers.is_empty(),
+                priority_until: (!priority_walkers.is_empty())
+                    .then(|| Utc::now() + chrono::Duration::minutes(10)),
+                priority_walkers,
+                notify_walker_nearby: false,
+                route_preference: None,
+                partner_id: None,
+            })
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn nearby_cache_is_shared_and_filtered_per_caller() {
+        let service = Service::new(InMemory::new());
+        let nearby = |user_id: &'static str| {
+            let service = &service;
+            async move {
+                let (requests, total) = service
+                    .nearby_walk_requests(
+                        user_id,
+                        39.9,
+                        116.4,
+                        1_000.0,
+                        Pagination { limit: 10, skip: 0 },
+                    )
+                    .await
+                    .unwrap();
+                assert_eq!(requests.len() as u64, total);
+                requests
+                    .into_iter()
+                    .map(|r| r.id.to_string())
+                    .collect::<Vec<String>>()
+            }
+        };
+        let favorite_only =
+            open_walk_request(&service, OWNER_ID, vec!["favorite".to_owned()]).await;
+        let open = open_walk_request(&service, "owner-2", vec![]).await;
+        assert_eq!(nearby("favorite").await.len(), 2);
+        assert_eq!(nearby(WALKER_ID).await, [open.clone()]);
+        // 直接写入仓库的请求不会清除缓存, 其他调用方读到的是同一份候选列表
+        open_walk_request(&service, "owner-3", vec![]).await;
+        assert_eq!(nearby(WALKER_ID).await, [open]);
+        service.block_walker("owner-2", WALKER_ID).await.unwrap();
+        assert!(nearby(WALKER_ID).await.is_empty());
+        assert!(nearby("favorite").await.contains(&favorite_only));
+    }
+
+    fn scheduled_walk_request(dogs: Vec<Dog>, starts_in_hours: i64) -> WalkRequestCreate {
+        let start = Utc::now() + chrono::Duration::hours(starts_in_hours);
+        WalkRequestCreate {
+            dogs,
+            should_start_after: Some(start),
+            should_start_before: Some(start + chrono::Duration::minutes(30)),
+            should_end_before: Some(start + chrono::Duration::hours(1)),
+            should_end_after: None,
+            latitude: 39.9,
+            longitude: 116.4,
+            created_by: OWNER_ID.to_owned(),
+            notify_favorites: false,
+            priority_walkers: vec![],
+            priority_until: None,
+            notify_walker_nearby: false,
+            route_preference: None,
+            partner_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn dry_run_quotes_fees_and_detects_schedule_conflicts() {
+        let service = Service::new(InMemory::new());
+        let (rex, buddy) = (dog(), dog());
+        service
+            .create_walk_request(scheduled_walk_request(vec![rex.clone()], 2))
+            .await
+            .unwrap();
+        let e = service
+            .validate_walk_request(scheduled_walk_request(vec![rex.clone(), buddy.clone()], 2))
+            .await
+            .unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::Conflict);
+        assert!(service
+            .create_walk_request(scheduled_walk_request(vec![rex.clone()], 2))
+            .await
+            .is_err());
+        let (_, quote) = service
+            .validate_walk_request(scheduled_walk_request(vec![rex, buddy.clone()], 4))
+            .await
+            .unwrap();
+        assert_eq!(quote, walk_quote(2));
+        assert_eq!(quote.price, 2 * WALK_PRICE_PER_DOG);
+        assert_eq!(quote.platform_fee + quote.walker_earning, quote.price);
+        // 没有预定时间的请求不做冲突检查
+        let mut unscheduled = scheduled_walk_request(vec![buddy], 0);
+        unscheduled.should_start_after = None;
+        unscheduled.should_start_before = None;
+        assert!(service.validate_walk_request(unscheduled).await.is_ok());
+    }
+}
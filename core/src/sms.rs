@@ -0,0 +1,6 @@
+use crate::error::Error;
+
+// 短信发送通道, 具体实现位于sms_senders
+pub trait SmsSender {
+    async fn send(&self, phone: &str, content: &str) -> Result<(), Error>;
+}
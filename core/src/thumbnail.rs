@@ -0,0 +1,32 @@
+use std::io::Cursor;
+
+use image::{imageops::FilterType, DynamicImage, ImageOutputFormat};
+
+use crate::{entities::ImageSize, error::Error};
+
+// 缩略图统一编码为JPEG
+const THUMBNAIL_QUALITY: u8 = 85;
+
+// 生成各规格的缩略图. 非图片文件返回空列表, 原图不大于目标规格时跳过该规格
+pub fn render_thumbnails(content: &[u8]) -> Result<Vec<(ImageSize, Vec<u8>)>, Error> {
+    if image::guess_format(content).is_err() {
+        return Ok(Vec::new());
+    }
+    let image = image::load_from_memory(content)
+        .map_err(|e| Error::new("failed to decode image").with_cause(e))?;
+    let mut thumbnails = Vec::new();
+    for size in [ImageSize::Small, ImageSize::Medium, ImageSize::Large] {
+        let max = size.max_dimension();
+        if image.width() <= max && image.height() <= max {
+            continue;
+        }
+        let resized =
+            DynamicImage::ImageRgb8(image.resize(max, max, FilterType::Lanczos3).to_rgb8());
+        let mut out = Cursor::new(Vec::new());
+        resized
+            .write_to(&mut out, ImageOutputFormat::Jpeg(THUMBNAIL_QUALITY))
+            .map_err(|e| Error::new("failed to encode thumbnail").with_cause(e))?;
+        thumbnails.push((size, out.into_inner()));
+    }
+    Ok(thumbnails)
+}
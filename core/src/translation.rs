@@ -0,0 +1,45 @@
+use std::fmt::{self, Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::error::Error;
+
+// 支持翻译的目标语言, 取值即ISO 639-1代码
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    En,
+    Zh,
+}
+
+impl Display for Language {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Language::En => "en",
+                Language::Zh => "zh",
+            }
+        )
+    }
+}
+
+impl Language {
+    // 粗略判断文本是否已是该语言: 含汉字视为中文, 否则视为英文, 避免不必要的翻译调用
+    pub fn is_written_in(&self, text: &str) -> bool {
+        let has_han = text
+            .chars()
+            .any(|c| matches!(c, '\u{4E00}'..='\u{9FFF}' | '\u{3400}'..='\u{4DBF}'));
+        match self {
+            Language::Zh => has_han,
+            Language::En => !has_han,
+        }
+    }
+}
+
+// 文本翻译服务, 具体实现位于translation_providers
+pub trait Translator {
+    async fn translate(&self, text: &str, to: Language) -> Result<String, Error>;
+}
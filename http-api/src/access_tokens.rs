@@ -0,0 +1,124 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use jwt::{AlgorithmType, Header, SignWithKey, Token, VerifyWithKey};
+use rand::{distributions::Alphanumeric, Rng};
+use serde::{Deserialize, Serialize};
+use sha2::Sha384;
+
+use crate::core::{entities::Role, error::Error, keys::KeyRing};
+
+// 访问令牌声明. ver为签发时用户的令牌版本, 低于当前版本的令牌视为已吊销; did为登录设备id;
+// jti为令牌id, 用于单独吊销某个令牌, 增加jti之前签发的令牌没有该字段
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessClaims {
+    pub iss: String,
+    pub aud: String,
+    pub sub: String,
+    pub iat: i64,
+    pub exp: i64,
+    pub roles: Vec<Role>,
+    pub ver: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub did: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jti: Option<String>,
+}
+
+// 签发和校验访问令牌. 以当前密钥签名并在头部kid中记录密钥id, 校验时按kid选择密钥
+pub struct AccessTokens {
+    keys: Vec<(String, Hmac<Sha384>)>,
+    issuer: String,
+    audience: String,
+    ttl: Duration,
+}
+
+impl AccessTokens {
+    pub fn new(ring: &KeyRing, issuer: &str, audience: &str, ttl: Duration) -> Result<Self, Error> {
+        if ring.is_empty() {
+            return Err(Error::new("invalid jwt keys").with_cause("no key configured"));
+        }
+        let keys = ring
+            .keys()
+            .iter()
+            .map(|key| {
+                Hmac::new_from_slice(&key.secret)
+                    .map(|mac| (key.id.clone(), mac))
+                    .map_err(|e| Error::new("invalid jwt key").with_cause(e))
+            })
+            .collect::<Result<Vec<(String, Hmac<Sha384>)>, Error>>()?;
+        Ok(Self {
+            keys,
+            issuer: issuer.to_owned(),
+            audience: audience.to_owned(),
+            ttl,
+        })
+    }
+
+    pub fn issue(
+        &self,
+        user_id: &str,
+        roles: Vec<Role>,
+        version: i64,
+        device_id: Option<String>,
+    ) -> Result<String, Error> {
+        let (key_id, key) = &self.keys[0];
+        let now = Utc::now().timestamp();
+        let header = Header {
+            algorithm: AlgorithmType::Hs384,
+            key_id: Some(key_id.clone()),
+            ..Default::default()
+        };
+        let claims = AccessClaims {
+            iss: self.issuer.clone(),
+            aud: self.audience.clone(),
+            sub: user_id.to_owned(),
+            iat: now,
+            exp: now + self.ttl.as_secs() as i64,
+            roles,
+            ver: version,
+            did: device_id,
+            jti: Some(
+                rand::thread_rng()
+                    .sample_iter(&Alphanumeric)
+                    .take(32)
+                    .map(char::from)
+                    .collect(),
+            ),
+        };
+        Token::new(header, claims)
+            .sign_with_key(key)
+            .map(|token| token.as_str().to_owned())
+            .map_err(|e| Error::new("failed to sign access token").with_cause(e))
+    }
+
+    // 校验签名、签发者、受众和过期时间, 令牌版本由调用方与当前版本比较
+    pub fn verify(&self, token: &str) -> Result<AccessClaims, Error> {
+        let unverified = Token::<Header, AccessClaims, _>::parse_unverified(token)
+            .map_err(|e| Error::new("invalid access token").with_cause(e))?;
+        let key_id = unverified
+            .header()
+            .key_id
+            .clone()
+            .ok_or(Error::new("invalid access token").with_cause("missing key id"))?;
+        let (_, key) = self
+            .keys
+            .iter()
+            .find(|(id, _)| *id == key_id)
+            .ok_or(Error::new("invalid access token").with_cause("unknown key id"))?;
+        let verified: Token<Header, AccessClaims, _> = unverified
+            .verify_with_key(key)
+            .map_err(|e| Error::new("invalid access token").with_cause(e))?;
+        let claims = verified.claims().clone();
+        if claims.iss != self.issuer || claims.aud != self.audience {
+            return Err(
+                Error::new("invalid access token").with_cause("issuer or audience mismatch")
+            );
+        }
+        if claims.exp <= Utc::now().timestamp() {
+            return Err(Error::new("invalid access token").with_cause("token expired"));
+        }
+        Ok(claims)
+    }
+}
@@ -0,0 +1,97 @@
+use crate::{
+    core::{
+        repository::{OwnerUpdate, Repository},
+        service::Service,
+    },
+    handlers::{
+        common::{AuthUser, DeleteAccountAction, RequireActionToken},
+        error::{api_error, ApiError},
+        upload::{read_file, store_with_thumbnails, UploadLimits},
+    },
+};
+use actix_multipart::Multipart;
+use actix_web::{
+    web::{Data, Json},
+    Error,
+};
+use futures::StreamExt;
+use little_walk_types::{
+    account::DeleteAccountReq,
+    dto::{AccountDeletionResp, OwnerProfileResp},
+    upload::UploadPurpose,
+};
+use nb_serde_query::actix_web::Query;
+use upload_service::core::{
+    repository::Repository as UploadRepository, service::Service as UploadService, store::Store,
+};
+
+// 注销需要操作确认令牌, 注销后已签发的访问令牌和刷新令牌都会作废
+#[utoipa::path(
+    delete,
+    path = "/v1/accounts/me",
+    tag = "account",
+    params(DeleteAccountReq),
+    responses((status = 200, body = AccountDeletionResp)),
+    security(("bearer_auth" = [], "action_token" = []))
+)]
+pub async fn delete_me<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    _: RequireActionToken<DeleteAccountAction>,
+    Query(req): Query<DeleteAccountReq>,
+) -> Result<Json<AccountDeletionResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .delete_account(&uid, req.reassign_dogs_to)
+        .await
+        .map(|deletion| Json(deletion.into()))
+        .map_err(api_error)
+}
+
+// 上传并设置头像, 表单中只能有一个文件. 头像保存在狗狗主人资料中, 遛狗人资料也使用该头像
+#[utoipa::path(
+    put,
+    path = "/v1/accounts/me/avatar",
+    tag = "account",
+    request_body(content_type = "multipart/form-data", description = "头像图片"),
+    responses((status = 200, body = OwnerProfileResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn update_avatar<R, UR, S>(
+    service: Data<Service<R>>,
+    upload_service: Data<UploadService<UR, S>>,
+    limits: Data<UploadLimits>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    mut form: Multipart,
+) -> Result<Json<OwnerProfileResp>, Error>
+where
+    R: Repository,
+    UR: UploadRepository + Clone,
+    S: Store + Clone,
+{
+    let field = form
+        .next()
+        .await
+        .ok_or(ApiError::bad_request("avatar file is required"))?
+        .map_err(ApiError::internal)?;
+    let (filename, content) = read_file(&limits, UploadPurpose::Avatar, field).await?;
+    if form.next().await.is_some() {
+        return Err(ApiError::bad_request("only one avatar file allowed").into());
+    }
+    let avatar_id =
+        store_with_thumbnails(&upload_service, &service, content, &filename, &uid).await?;
+    service
+        .update_owner_profile(
+            &uid,
+            OwnerUpdate {
+                nickname: None,
+                avatar_id: Some(avatar_id),
+                emergency_contacts: None,
+            },
+        )
+        .await
+        .map(|profile| Json(profile.into()))
+        .map_err(api_error)
+}
@@ -0,0 +1,490 @@
+use std::{convert::Infallible, time::Duration};
+
+use crate::{
+    core::{
+        repository::{AuditLogQuery, LocationAccessQuery, Pagination, Repository},
+        service::Service,
+    },
+    handlers::{
+        common::{AdminRole, AuthUser, RequireRole},
+        error::api_error,
+    },
+    metrics::repository_operation_counts,
+};
+use actix_web::{
+    http::header::{CACHE_CONTROL, CONTENT_TYPE},
+    web::{Bytes, Data, Json, Path},
+    Error, HttpResponse,
+};
+use futures::stream;
+use little_walk_types::{
+    admin::{AuditLogsReq, CreatePartnerResp, LocationAccessesReq, RevokePartnerApiKeyResp},
+    common::ListResp,
+    dto::{
+        AccountMergeReq, AccountMergeResp, AuditLogResp, CreatePartnerReq, ExplainReq,
+        IntegrityReportResp, InviteConversionReq, InviteConversionResp, IssuedPartnerApiKeyResp,
+        LocationAccessResp, MergedReferenceResp, OperationsSnapshotResp, PartnerApiKeyResp,
+        PartnerResp, PartnerUsageReq, PartnerUsageResp, PurgeDeletedReq, PurgedCountsResp,
+        QueryPlanResp, UpdatePartnerReq,
+    },
+};
+use nb_serde_query::actix_web::Query;
+use tokio::time::interval;
+
+// 运营看板的推送间隔
+const OPERATIONS_FEED_INTERVAL: Duration = Duration::from_secs(5);
+
+#[utoipa::path(
+    post,
+    path = "/v1/admin/db/explain",
+    tag = "admin",
+    request_body = ExplainReq,
+    responses((status = 200, body = QueryPlanResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn explain<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<AdminRole>,
+    Json(template): Json<ExplainReq>,
+) -> Result<Json<QueryPlanResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .explain(template.into())
+        .await
+        .map(|plan| Json(plan.into()))
+        .map_err(api_error)
+}
+
+// 物理删除指定时间之前软删除的狗狗和遛狗请求, 清理后无法恢复
+#[utoipa::path(
+    post,
+    path = "/v1/admin/db/purge_deleted",
+    tag = "admin",
+    request_body = PurgeDeletedReq,
+    responses((status = 200, body = PurgedCountsResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn purge_deleted<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<AdminRole>,
+    Json(req): Json<PurgeDeletedReq>,
+) -> Result<Json<PurgedCountsResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .purge_deleted(req.deleted_before)
+        .await
+        .map(|counts| Json(counts.into()))
+        .map_err(api_error)
+}
+
+// 立即检查悬空引用并返回结果, 只报告不修复; 修复由定时任务按配置的策略执行
+#[utoipa::path(
+    get,
+    path = "/v1/admin/db/integrity",
+    tag = "admin",
+    responses((status = 200, body = IntegrityReportResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn integrity_report<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<AdminRole>,
+) -> Result<Json<IntegrityReportResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .check_integrity(false)
+        .await
+        .map(|report| Json(report.into()))
+        .map_err(api_error)
+}
+
+// 账号合并预览(dry run), 返回将被改写的各集合字段记录数
+#[utoipa::path(
+    post,
+    path = "/v1/admin/account_merges/preview",
+    tag = "admin",
+    request_body = AccountMergeReq,
+    responses((status = 200, body = Vec<MergedReferenceResp>)),
+    security(("bearer_auth" = []))
+)]
+pub async fn preview_account_merge<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<AdminRole>,
+    Json(req): Json<AccountMergeReq>,
+) -> Result<Json<Vec<MergedReferenceResp>>, Error>
+where
+    R: Repository,
+{
+    service
+        .preview_account_merge(&req.from_user_id, &req.to_user_id)
+        .await
+        .map(|references| {
+            Json(
+                references
+                    .into_iter()
+                    .map(MergedReferenceResp::from)
+                    .collect(),
+            )
+        })
+        .map_err(api_error)
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/admin/account_merges",
+    tag = "admin",
+    request_body = AccountMergeReq,
+    responses((status = 200, body = AccountMergeResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn merge_accounts<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<AdminRole>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    Json(req): Json<AccountMergeReq>,
+) -> Result<Json<AccountMergeResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .merge_accounts(req.into_create(uid.into()))
+        .await
+        .map(|merge| Json(merge.into()))
+        .map_err(api_error)
+}
+
+// 运营看板(SSE), 定时推送进行中的遛狗数、近期失败打款数和仓储操作错误率
+#[utoipa::path(
+    get,
+    path = "/v1/admin/operations/feed",
+    tag = "admin",
+    responses((status = 200, description = "运营数据推送流", content_type = "text/event-stream")),
+    security(("bearer_auth" = []))
+)]
+pub async fn operations_feed<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<AdminRole>,
+) -> Result<HttpResponse, Error>
+where
+    R: Repository + 'static,
+{
+    let state = (
+        service,
+        interval(OPERATIONS_FEED_INTERVAL),
+        repository_operation_counts(),
+    );
+    let events = stream::unfold(
+        state,
+        |(service, mut ticker, (last_total, last_failed))| async move {
+            ticker.tick().await;
+            let (total, failed) = repository_operation_counts();
+            let event = match service.operations_snapshot().await {
+                Ok(snapshot) => {
+                    let resp = OperationsSnapshotResp::new(
+                        snapshot,
+                        total.saturating_sub(last_total),
+                        failed.saturating_sub(last_failed),
+                    );
+                    match serde_json::to_string(&resp) {
+                        Ok(data) => format!("event: snapshot\ndata: {}\n\n", data),
+                        Err(e) => format!("event: error\ndata: {}\n\n", e),
+                    }
+                }
+                Err(e) => format!("event: error\ndata: {}\n\n", e),
+            };
+            Some((
+                Ok::<_, Infallible>(Bytes::from(event)),
+                (service, ticker, (total, failed)),
+            ))
+        },
+    );
+    Ok(HttpResponse::Ok()
+        .insert_header((CONTENT_TYPE, "text/event-stream"))
+        .insert_header((CACHE_CONTROL, "no-cache"))
+        .streaming(events))
+}
+
+// 位置数据访问记录, 可按访问者、被访问用户、遛狗请求和时间范围筛选
+#[utoipa::path(
+    get,
+    path = "/v1/admin/location_accesses",
+    tag = "admin",
+    params(LocationAccessesReq),
+    responses((status = 200, body = ListResp<LocationAccessResp>)),
+    security(("bearer_auth" = []))
+)]
+pub async fn location_accesses<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<AdminRole>,
+    Query(req): Query<LocationAccessesReq>,
+) -> Result<Json<ListResp<LocationAccessResp>>, Error>
+where
+    R: Repository,
+{
+    let (accesses, total) = service
+        .location_accesses(
+            LocationAccessQuery {
+                accessor_id: req.accessor_id,
+                subject_id: req.subject_id,
+                walk_request_id: req.walk_request_id,
+                kind: req.kind,
+                created_after: req.created_after,
+                created_before: req.created_before,
+            },
+            Pagination {
+                limit: req.limit,
+                skip: req.skip,
+            },
+        )
+        .await
+        .map_err(api_error)?;
+    Ok(Json(ListResp::new(
+        accesses.into_iter().map(LocationAccessResp::from).collect(),
+        total,
+    )))
+}
+
+// 遛狗请求变更的审计记录, 可按请求、操作人、动作和时间范围筛选
+#[utoipa::path(
+    get,
+    path = "/v1/admin/audit_logs",
+    tag = "admin",
+    params(AuditLogsReq),
+    responses((status = 200, body = ListResp<AuditLogResp>)),
+    security(("bearer_auth" = []))
+)]
+pub async fn audit_logs<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<AdminRole>,
+    Query(req): Query<AuditLogsReq>,
+) -> Result<Json<ListResp<AuditLogResp>>, Error>
+where
+    R: Repository,
+{
+    let (logs, total) = service
+        .audit_logs(
+            AuditLogQuery {
+                walk_request_id: req.walk_request_id,
+                actor_id: req.actor_id,
+                action: req.action,
+                created_after: req.created_after,
+                created_before: req.created_before,
+            },
+            Pagination {
+                limit: req.limit,
+                skip: req.skip,
+            },
+        )
+        .await
+        .map_err(api_error)?;
+    Ok(Json(ListResp::new(
+        logs.into_iter().map(AuditLogResp::from).collect(),
+        total,
+    )))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/admin/partners",
+    tag = "admin",
+    request_body = CreatePartnerReq,
+    responses((status = 200, body = CreatePartnerResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn create_partner<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<AdminRole>,
+    Json(req): Json<CreatePartnerReq>,
+) -> Result<Json<CreatePartnerResp>, Error>
+where
+    R: Repository,
+{
+    let id = service
+        .create_partner(req.into())
+        .await
+        .map_err(api_error)?;
+    Ok(Json(CreatePartnerResp { id }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/partners",
+    tag = "admin",
+    params(Pagination),
+    responses((status = 200, body = ListResp<PartnerResp>)),
+    security(("bearer_auth" = []))
+)]
+pub async fn partners<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<AdminRole>,
+    Query(pagination): Query<Pagination>,
+) -> Result<Json<ListResp<PartnerResp>>, Error>
+where
+    R: Repository,
+{
+    let (partners, total) = service.partners(pagination).await.map_err(api_error)?;
+    Ok(Json(ListResp::new(
+        partners.into_iter().map(PartnerResp::from).collect(),
+        total,
+    )))
+}
+
+// 修改合作方的权限范围、请求上限或停用合作方
+#[utoipa::path(
+    put,
+    path = "/v1/admin/partners/{id}",
+    tag = "admin",
+    params(("id" = String, Path)),
+    request_body = UpdatePartnerReq,
+    responses((status = 200, body = PartnerResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn update_partner<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<AdminRole>,
+    id: Path<(String,)>,
+    Json(req): Json<UpdatePartnerReq>,
+) -> Result<Json<PartnerResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .update_partner(&id.0, req.into())
+        .await
+        .map(|partner| Json(partner.into()))
+        .map_err(api_error)
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/admin/partners/{id}/api_keys",
+    tag = "admin",
+    params(("id" = String, Path)),
+    responses((status = 200, body = IssuedPartnerApiKeyResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn issue_partner_api_key<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<AdminRole>,
+    id: Path<(String,)>,
+) -> Result<Json<IssuedPartnerApiKeyResp>, Error>
+where
+    R: Repository,
+{
+    let (key, api_key) = service
+        .issue_partner_api_key(&id.0)
+        .await
+        .map_err(api_error)?;
+    Ok(Json(IssuedPartnerApiKeyResp {
+        key: key.into(),
+        api_key,
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/partners/{id}/api_keys",
+    tag = "admin",
+    params(("id" = String, Path)),
+    responses((status = 200, body = Vec<PartnerApiKeyResp>)),
+    security(("bearer_auth" = []))
+)]
+pub async fn partner_api_keys<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<AdminRole>,
+    id: Path<(String,)>,
+) -> Result<Json<Vec<PartnerApiKeyResp>>, Error>
+where
+    R: Repository,
+{
+    service
+        .partner_api_keys(&id.0)
+        .await
+        .map(|keys| Json(keys.into_iter().map(PartnerApiKeyResp::from).collect()))
+        .map_err(api_error)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/admin/partners/{id}/api_keys/{key_id}",
+    tag = "admin",
+    params(("id" = String, Path), ("key_id" = String, Path)),
+    responses((status = 200, body = RevokePartnerApiKeyResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn revoke_partner_api_key<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<AdminRole>,
+    path: Path<(String, String)>,
+) -> Result<Json<RevokePartnerApiKeyResp>, Error>
+where
+    R: Repository,
+{
+    let (partner_id, key_id) = path.into_inner();
+    service
+        .revoke_partner_api_key(&partner_id, &key_id)
+        .await
+        .map_err(api_error)?;
+    Ok(Json(RevokePartnerApiKeyResp { success: true }))
+}
+
+// 合作方按天和接口汇总的调用量
+#[utoipa::path(
+    get,
+    path = "/v1/admin/partners/{id}/usage",
+    tag = "admin",
+    params(("id" = String, Path), PartnerUsageReq),
+    responses((status = 200, body = Vec<PartnerUsageResp>)),
+    security(("bearer_auth" = []))
+)]
+pub async fn partner_usage<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<AdminRole>,
+    id: Path<(String,)>,
+    Query(req): Query<PartnerUsageReq>,
+) -> Result<Json<Vec<PartnerUsageResp>>, Error>
+where
+    R: Repository,
+{
+    service
+        .partner_usage(&id.0, req.from, req.to)
+        .await
+        .map(|usage| Json(usage.into_iter().map(PartnerUsageResp::from).collect()))
+        .map_err(api_error)
+}
+
+// 按邀请类型统计邀请短信的打开和注册转化
+#[utoipa::path(
+    get,
+    path = "/v1/admin/invites/conversion",
+    tag = "admin",
+    params(InviteConversionReq),
+    responses((status = 200, body = Vec<InviteConversionResp>)),
+    security(("bearer_auth" = []))
+)]
+pub async fn invite_conversion<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<AdminRole>,
+    Query(req): Query<InviteConversionReq>,
+) -> Result<Json<Vec<InviteConversionResp>>, Error>
+where
+    R: Repository,
+{
+    service
+        .invite_conversion(req.from, req.to)
+        .await
+        .map(|conversion| {
+            Json(
+                conversion
+                    .into_iter()
+                    .map(InviteConversionResp::from)
+                    .collect(),
+            )
+        })
+        .map_err(api_error)
+}
@@ -0,0 +1,865 @@
+use actix_web::{
+    web::{Data, Json, Path},
+    Error, HttpRequest,
+};
+use little_walk_types::auth::{
+    ChangePasswordParams, ChangePasswordResp, ChangePhoneParams, ChangePhoneResp, ExistsUserResp,
+    IssueActionTokenParams, IssueActionTokenResp, LinkOAuthAccountParams, LinkOAuthAccountResp,
+    LoginByOAuthParams, LoginByOAuthResp, LoginByOtpParams, LoginByOtpResp, LoginByPasswordParams,
+    LoginByPasswordResp, RefreshTokenParams, RefreshTokenResp, ResetPasswordParams,
+    ResetPasswordResp, RevokeTokenResp, SendOtpResp, SendPhoneChangeCodeParams, SignupParams,
+    SignupResp, VerifyPasswordResetCodeParams, VerifyPasswordResetCodeResp, VerifyTokenResp,
+};
+
+use auth_service::core::{
+    hasher::Hasher, repository::Repository, service::Service, token_manager::TokenManager,
+};
+
+use crate::{
+    access_tokens::AccessTokens,
+    core::{
+        entities::{Device, OAuthLoginOutcome, OAuthProviderKind, OtpPurpose},
+        repository::Repository as DogRepository,
+        service::Service as DogService,
+        sms::SmsSender,
+    },
+    handlers::{
+        common::{authenticate, bearer_token, verify_access_token, AuthUser, ClientDevice},
+        error::{api_error, ApiError},
+        validation::Valid,
+    },
+    oauth_providers::OAuthProviders,
+};
+
+use chrono::DateTime;
+use rand::{distributions::Alphanumeric, Rng};
+
+#[utoipa::path(
+    put,
+    path = "/v1/login",
+    tag = "auth",
+    request_body = LoginByPasswordParams,
+    responses((status = 200, body = LoginByPasswordResp))
+)]
+pub async fn login_by_password<R, H, T, DR>(
+    service: Data<Service<R, H, T>>,
+    dog_service: Data<DogService<DR>>,
+    tokens: Data<AccessTokens>,
+    ClientDevice(device): ClientDevice,
+    Valid(params): Valid<LoginByPasswordParams>,
+) -> Result<Json<LoginByPasswordResp>, Error>
+where
+    R: Repository + Clone,
+    H: Hasher + Clone,
+    T: TokenManager + Clone,
+    DR: DogRepository,
+{
+    let auth_phone = dog_service
+        .auth_phone(&params.phone)
+        .await
+        .map_err(api_error)?;
+    let auth_token = service
+        .login_by_password(&auth_phone, &params.password)
+        .await
+        .map_err(ApiError::internal)?;
+    let (token, refresh_token) = issue_tokens(
+        &service,
+        &dog_service,
+        &tokens,
+        &auth_token,
+        &params.phone,
+        device,
+    )
+    .await?;
+    Ok(Json(LoginByPasswordResp {
+        token,
+        refresh_token,
+    }))
+}
+
+// auth_service只负责验证身份, 访问令牌由本服务签发, 携带角色、令牌版本和设备id.
+// 刷新令牌记录用户id和手机号, 轮换时据此重新签发访问令牌
+async fn issue_tokens<R, H, T, DR>(
+    service: &Service<R, H, T>,
+    dog_service: &DogService<DR>,
+    tokens: &AccessTokens,
+    auth_token: &str,
+    phone: &str,
+    device: Device,
+) -> Result<(String, String), Error>
+where
+    R: Repository + Clone,
+    H: Hasher + Clone,
+    T: TokenManager + Clone,
+    DR: DogRepository,
+{
+    let user_id = service
+        .verify_token(auth_token)
+        .await
+        .map_err(ApiError::internal)?;
+    let token = issue_access_token(dog_service, tokens, &user_id, device.device_id.clone()).await?;
+    let refresh_token = dog_service
+        .issue_refresh_token(&user_id, phone, device)
+        .await
+        .map_err(api_error)?;
+    Ok((token, refresh_token))
+}
+
+// 新用户注册后把发往该手机号的邀请记为转化. 统计失败不影响注册
+async fn record_invite_conversion<DR>(dog_service: &DogService<DR>, phone: &str, user_id: &str)
+where
+    DR: DogRepository,
+{
+    if let Err(e) = dog_service.convert_invites(phone, user_id).await {
+        log::error!("failed to record invite conversion: {}", e);
+    }
+}
+
+async fn issue_access_token<DR>(
+    dog_service: &DogService<DR>,
+    tokens: &AccessTokens,
+    user_id: &str,
+    device_id: Option<String>,
+) -> Result<String, Error>
+where
+    DR: DogRepository,
+{
+    let roles = dog_service.user_roles(user_id).await.map_err(api_error)?;
+    let version = dog_service
+        .token_version(user_id)
+        .await
+        .map_err(api_error)?;
+    tokens
+        .issue(user_id, roles, version, device_id)
+        .map_err(api_error)
+}
+
+// 与AuthUser相同的校验, 供其他服务校验访问令牌
+#[utoipa::path(
+    get,
+    path = "/v1/tokens/{token}/verification",
+    tag = "auth",
+    params(("token" = String, Path)),
+    responses((status = 200, body = VerifyTokenResp))
+)]
+pub async fn verify_token<DR>(
+    dog_service: Data<DogService<DR>>,
+    tokens: Data<AccessTokens>,
+    token: Path<(String,)>,
+) -> Result<Json<VerifyTokenResp>, Error>
+where
+    DR: DogRepository,
+{
+    let user = authenticate(&tokens, &dog_service, &token.0).await?;
+    Ok(Json(VerifyTokenResp {
+        id: user.user_id.to_string(),
+    }))
+}
+
+// 退出登录, 当前访问令牌立即失效. 令牌已吊销时同样返回成功, 重复调用不会出错.
+// 刷新令牌需另行通过会话接口吊销
+#[utoipa::path(
+    delete,
+    path = "/v1/tokens/current",
+    tag = "auth",
+    responses((status = 200, body = RevokeTokenResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn revoke_current_token<DR>(
+    dog_service: Data<DogService<DR>>,
+    tokens: Data<AccessTokens>,
+    req: HttpRequest,
+) -> Result<Json<RevokeTokenResp>, Error>
+where
+    DR: DogRepository,
+{
+    let claims = verify_access_token(&tokens, &dog_service, &bearer_token(&req)?).await?;
+    let token_id = claims.jti.ok_or(ApiError::bad_request(
+        "access token cannot be revoked individually, please sign in again",
+    ))?;
+    let expires_at = DateTime::from_timestamp(claims.exp, 0)
+        .ok_or(ApiError::bad_request("invalid access token expiry"))?;
+    dog_service
+        .revoke_access_token(&token_id, &claims.sub, expires_at)
+        .await
+        .map_err(api_error)?;
+    Ok(Json(RevokeTokenResp { success: true }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/signup",
+    tag = "auth",
+    request_body = SignupParams,
+    responses((status = 200, body = SignupResp))
+)]
+pub async fn signup<R, H, T, DR>(
+    service: Data<Service<R, H, T>>,
+    dog_service: Data<DogService<DR>>,
+    tokens: Data<AccessTokens>,
+    ClientDevice(device): ClientDevice,
+    Valid(params): Valid<SignupParams>,
+) -> Result<Json<SignupResp>, Error>
+where
+    R: Repository + Clone,
+    H: Hasher + Clone,
+    T: TokenManager + Clone,
+    DR: DogRepository,
+{
+    if dog_service
+        .phone_bound_to(&params.phone)
+        .await
+        .map_err(api_error)?
+        .is_some()
+    {
+        return Err(ApiError::conflict("user already registered").into());
+    }
+    let auth_token = service
+        .signup(&params.phone, &params.password)
+        .await
+        .map_err(ApiError::internal)?;
+    let user_id = service
+        .verify_token(&auth_token)
+        .await
+        .map_err(ApiError::internal)?;
+    record_invite_conversion(&dog_service, &params.phone, &user_id).await;
+    let (token, refresh_token) = issue_tokens(
+        &service,
+        &dog_service,
+        &tokens,
+        &auth_token,
+        &params.phone,
+        device,
+    )
+    .await?;
+    Ok(Json(SignupResp {
+        token,
+        refresh_token,
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/phones/{phone}/exists",
+    tag = "auth",
+    params(("phone" = String, Path)),
+    responses((status = 200, body = ExistsUserResp))
+)]
+pub async fn exists_user<R, H, T, DR>(
+    service: Data<Service<R, H, T>>,
+    dog_service: Data<DogService<DR>>,
+    phone: Path<(String,)>,
+) -> Result<Json<ExistsUserResp>, Error>
+where
+    R: Repository + Clone,
+    H: Hasher + Clone,
+    T: TokenManager + Clone,
+    DR: DogRepository,
+{
+    let exists = dog_service
+        .phone_bound_to(&phone.0)
+        .await
+        .map_err(api_error)?
+        .is_some()
+        || service
+            .exists_user(&phone.0)
+            .await
+            .map_err(ApiError::internal)?;
+    Ok(Json(ExistsUserResp { exists }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/tokens/refresh",
+    tag = "auth",
+    request_body = RefreshTokenParams,
+    responses((status = 200, body = RefreshTokenResp))
+)]
+pub async fn refresh_token<DR>(
+    dog_service: Data<DogService<DR>>,
+    tokens: Data<AccessTokens>,
+    Json(params): Json<RefreshTokenParams>,
+) -> Result<Json<RefreshTokenResp>, Error>
+where
+    DR: DogRepository,
+{
+    let (consumed, refresh_token) = dog_service
+        .rotate_refresh_token(&params.refresh_token)
+        .await
+        .map_err(ApiError::unauthorized)?;
+    let token =
+        issue_access_token(&dog_service, &tokens, &consumed.user_id, consumed.device_id).await?;
+    Ok(Json(RefreshTokenResp {
+        token,
+        refresh_token,
+    }))
+}
+
+#[utoipa::path(
+    put,
+    path = "/v1/phones/{phone}/otp",
+    tag = "auth",
+    params(("phone" = String, Path)),
+    responses((status = 200, body = SendOtpResp))
+)]
+pub async fn send_otp<DR, S>(
+    dog_service: Data<DogService<DR>>,
+    sender: Data<S>,
+    phone: Path<(String,)>,
+) -> Result<Json<SendOtpResp>, Error>
+where
+    DR: DogRepository,
+    S: SmsSender,
+{
+    dog_service
+        .send_otp(sender.as_ref(), &phone.0, OtpPurpose::Login)
+        .await
+        .map_err(api_error)?;
+    Ok(Json(SendOtpResp { success: true }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/login/otp",
+    tag = "auth",
+    request_body = LoginByOtpParams,
+    responses((status = 200, body = LoginByOtpResp))
+)]
+pub async fn login_by_otp<R, H, T, DR>(
+    service: Data<Service<R, H, T>>,
+    dog_service: Data<DogService<DR>>,
+    tokens: Data<AccessTokens>,
+    ClientDevice(device): ClientDevice,
+    Valid(params): Valid<LoginByOtpParams>,
+) -> Result<Json<LoginByOtpResp>, Error>
+where
+    R: Repository + Clone,
+    H: Hasher + Clone,
+    T: TokenManager + Clone,
+    DR: DogRepository,
+{
+    dog_service
+        .verify_otp(&params.phone, OtpPurpose::Login, &params.code)
+        .await
+        .map_err(ApiError::unauthorized)?;
+    let auth_phone = dog_service
+        .auth_phone(&params.phone)
+        .await
+        .map_err(api_error)?;
+    let auth_token = service
+        .generate_token(&auth_phone)
+        .await
+        .map_err(ApiError::internal)?;
+    let (token, refresh_token) = issue_tokens(
+        &service,
+        &dog_service,
+        &tokens,
+        &auth_token,
+        &params.phone,
+        device,
+    )
+    .await?;
+    Ok(Json(LoginByOtpResp {
+        token,
+        refresh_token,
+    }))
+}
+
+#[utoipa::path(
+    put,
+    path = "/v1/phones/{phone}/password_reset_code",
+    tag = "auth",
+    params(("phone" = String, Path)),
+    responses((status = 200, body = SendOtpResp))
+)]
+pub async fn send_password_reset_code<R, H, T, DR, S>(
+    service: Data<Service<R, H, T>>,
+    dog_service: Data<DogService<DR>>,
+    sender: Data<S>,
+    phone: Path<(String,)>,
+) -> Result<Json<SendOtpResp>, Error>
+where
+    R: Repository + Clone,
+    H: Hasher + Clone,
+    T: TokenManager + Clone,
+    DR: DogRepository,
+    S: SmsSender,
+{
+    let auth_phone = dog_service.auth_phone(&phone.0).await.map_err(api_error)?;
+    if !service
+        .exists_user(&auth_phone)
+        .await
+        .map_err(ApiError::internal)?
+    {
+        return Err(ApiError::not_found("user not exists").into());
+    }
+    dog_service
+        .send_otp(sender.as_ref(), &phone.0, OtpPurpose::PasswordReset)
+        .await
+        .map_err(api_error)?;
+    Ok(Json(SendOtpResp { success: true }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/password_resets",
+    tag = "auth",
+    request_body = VerifyPasswordResetCodeParams,
+    responses((status = 200, body = VerifyPasswordResetCodeResp))
+)]
+pub async fn verify_password_reset_code<DR>(
+    dog_service: Data<DogService<DR>>,
+    Json(params): Json<VerifyPasswordResetCodeParams>,
+) -> Result<Json<VerifyPasswordResetCodeResp>, Error>
+where
+    DR: DogRepository,
+{
+    let reset_token = dog_service
+        .issue_password_reset_token(&params.phone, &params.code)
+        .await
+        .map_err(ApiError::unauthorized)?;
+    Ok(Json(VerifyPasswordResetCodeResp { reset_token }))
+}
+
+#[utoipa::path(
+    put,
+    path = "/v1/passwords",
+    tag = "auth",
+    request_body = ResetPasswordParams,
+    responses((status = 200, body = ResetPasswordResp))
+)]
+pub async fn reset_password<R, H, T, DR>(
+    service: Data<Service<R, H, T>>,
+    dog_service: Data<DogService<DR>>,
+    Json(params): Json<ResetPasswordParams>,
+) -> Result<Json<ResetPasswordResp>, Error>
+where
+    R: Repository + Clone,
+    H: Hasher + Clone,
+    T: TokenManager + Clone,
+    DR: DogRepository,
+{
+    let phone = dog_service
+        .consume_password_reset_token(&params.reset_token)
+        .await
+        .map_err(ApiError::unauthorized)?;
+    let auth_phone = dog_service.auth_phone(&phone).await.map_err(api_error)?;
+    let auth_token = service
+        .generate_token(&auth_phone)
+        .await
+        .map_err(ApiError::internal)?;
+    let user_id = service
+        .verify_token(&auth_token)
+        .await
+        .map_err(ApiError::internal)?;
+    service
+        .update_password(&auth_phone, &params.password)
+        .await
+        .map_err(ApiError::internal)?;
+    // 账号可能已被他人登录, 重置后所有设备的刷新令牌和访问令牌作废
+    dog_service
+        .revoke_all_sessions(&user_id)
+        .await
+        .map_err(api_error)?;
+    Ok(Json(ResetPasswordResp { success: true }))
+}
+
+// 用旧密码登录一次确认手机号属于当前用户, 修改成功后所有设备的刷新令牌作废
+#[utoipa::path(
+    put,
+    path = "/v1/accounts/me/password",
+    tag = "auth",
+    request_body = ChangePasswordParams,
+    responses((status = 200, body = ChangePasswordResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn change_password<R, H, T, DR>(
+    service: Data<Service<R, H, T>>,
+    dog_service: Data<DogService<DR>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    Json(params): Json<ChangePasswordParams>,
+) -> Result<Json<ChangePasswordResp>, Error>
+where
+    R: Repository + Clone,
+    H: Hasher + Clone,
+    T: TokenManager + Clone,
+    DR: DogRepository,
+{
+    let auth_phone = dog_service
+        .auth_phone(&params.phone)
+        .await
+        .map_err(api_error)?;
+    let token = service
+        .login_by_password(&auth_phone, &params.old_password)
+        .await
+        .map_err(ApiError::unauthorized)?;
+    let user_id = service
+        .verify_token(&token)
+        .await
+        .map_err(ApiError::internal)?;
+    if user_id != uid {
+        return Err(ApiError::forbidden("phone does not belong to current user").into());
+    }
+    service
+        .update_password(&auth_phone, &params.new_password)
+        .await
+        .map_err(ApiError::internal)?;
+    dog_service
+        .revoke_all_sessions(&uid)
+        .await
+        .map_err(api_error)?;
+    Ok(Json(ChangePasswordResp { success: true }))
+}
+
+// 新手机号不能已注册或被其他用户换绑, 换回自己原来的手机号除外
+async fn ensure_phone_available<R, H, T, DR>(
+    service: &Service<R, H, T>,
+    dog_service: &DogService<DR>,
+    user_id: &str,
+    phone: &str,
+) -> Result<(), Error>
+where
+    R: Repository + Clone,
+    H: Hasher + Clone,
+    T: TokenManager + Clone,
+    DR: DogRepository,
+{
+    let taken = match dog_service.phone_bound_to(phone).await.map_err(api_error)? {
+        Some(bound_to) => bound_to != user_id,
+        None => service
+            .exists_user(phone)
+            .await
+            .map_err(ApiError::internal)?,
+    };
+    if taken {
+        return Err(ApiError::conflict("phone already registered").into());
+    }
+    Ok(())
+}
+
+#[utoipa::path(
+    put,
+    path = "/v1/accounts/me/phone_change_code",
+    tag = "auth",
+    request_body = SendPhoneChangeCodeParams,
+    responses((status = 200, body = SendOtpResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn send_phone_change_code<R, H, T, DR, S>(
+    service: Data<Service<R, H, T>>,
+    dog_service: Data<DogService<DR>>,
+    sender: Data<S>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    Valid(params): Valid<SendPhoneChangeCodeParams>,
+) -> Result<Json<SendOtpResp>, Error>
+where
+    R: Repository + Clone,
+    H: Hasher + Clone,
+    T: TokenManager + Clone,
+    DR: DogRepository,
+    S: SmsSender,
+{
+    ensure_phone_available(&service, &dog_service, &uid, &params.new_phone).await?;
+    dog_service
+        .send_otp(sender.as_ref(), &params.new_phone, OtpPurpose::PhoneChange)
+        .await
+        .map_err(api_error)?;
+    Ok(Json(SendOtpResp { success: true }))
+}
+
+// 与修改密码相同, 先用当前手机号和密码确认是本人操作, 再校验发往新手机号的验证码.
+// 更换成功后所有设备的刷新令牌作废, 需要用新手机号重新登录
+#[utoipa::path(
+    put,
+    path = "/v1/accounts/me/phone",
+    tag = "auth",
+    request_body = ChangePhoneParams,
+    responses((status = 200, body = ChangePhoneResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn change_phone<R, H, T, DR>(
+    service: Data<Service<R, H, T>>,
+    dog_service: Data<DogService<DR>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    Valid(params): Valid<ChangePhoneParams>,
+) -> Result<Json<ChangePhoneResp>, Error>
+where
+    R: Repository + Clone,
+    H: Hasher + Clone,
+    T: TokenManager + Clone,
+    DR: DogRepository,
+{
+    let auth_phone = dog_service
+        .auth_phone(&params.phone)
+        .await
+        .map_err(api_error)?;
+    let token = service
+        .login_by_password(&auth_phone, &params.password)
+        .await
+        .map_err(ApiError::unauthorized)?;
+    let user_id = service
+        .verify_token(&token)
+        .await
+        .map_err(ApiError::internal)?;
+    if user_id != uid {
+        return Err(ApiError::forbidden("phone does not belong to current user").into());
+    }
+    ensure_phone_available(&service, &dog_service, &uid, &params.new_phone).await?;
+    dog_service
+        .change_phone(&uid, &auth_phone, &params.new_phone, &params.code)
+        .await
+        .map_err(api_error)?;
+    Ok(Json(ChangePhoneResp { success: true }))
+}
+
+// 与修改密码相同, 用密码登录一次确认是本人操作后签发一次性的操作确认令牌
+#[utoipa::path(
+    post,
+    path = "/v1/accounts/me/action_tokens",
+    tag = "auth",
+    request_body = IssueActionTokenParams,
+    responses((status = 200, body = IssueActionTokenResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn issue_action_token<R, H, T, DR>(
+    service: Data<Service<R, H, T>>,
+    dog_service: Data<DogService<DR>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    Json(params): Json<IssueActionTokenParams>,
+) -> Result<Json<IssueActionTokenResp>, Error>
+where
+    R: Repository + Clone,
+    H: Hasher + Clone,
+    T: TokenManager + Clone,
+    DR: DogRepository,
+{
+    let auth_phone = dog_service
+        .auth_phone(&params.phone)
+        .await
+        .map_err(api_error)?;
+    let token = service
+        .login_by_password(&auth_phone, &params.password)
+        .await
+        .map_err(ApiError::unauthorized)?;
+    let user_id = service
+        .verify_token(&token)
+        .await
+        .map_err(ApiError::internal)?;
+    if user_id != uid {
+        return Err(ApiError::forbidden("phone does not belong to current user").into());
+    }
+    let action_token = dog_service
+        .issue_action_token(&uid, params.action)
+        .await
+        .map_err(api_error)?;
+    Ok(Json(IssueActionTokenResp { action_token }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/login/oauth/{provider}",
+    tag = "auth",
+    params(("provider" = String, Path)),
+    request_body = LoginByOAuthParams,
+    responses((status = 200, body = LoginByOAuthResp))
+)]
+pub async fn login_by_oauth<R, H, T, DR>(
+    service: Data<Service<R, H, T>>,
+    dog_service: Data<DogService<DR>>,
+    tokens: Data<AccessTokens>,
+    ClientDevice(device): ClientDevice,
+    providers: Data<OAuthProviders>,
+    provider: Path<(String,)>,
+    Json(params): Json<LoginByOAuthParams>,
+) -> Result<Json<LoginByOAuthResp>, Error>
+where
+    R: Repository + Clone,
+    H: Hasher + Clone,
+    T: TokenManager + Clone,
+    DR: DogRepository,
+{
+    let kind = provider
+        .0
+        .parse::<OAuthProviderKind>()
+        .map_err(ApiError::not_found)?;
+    let identity = providers
+        .exchange_code(kind, &params.code)
+        .await
+        .map_err(ApiError::unauthorized)?;
+    match dog_service
+        .oauth_login(kind, identity)
+        .await
+        .map_err(api_error)?
+    {
+        OAuthLoginOutcome::LoggedIn(account) => {
+            let auth_phone = dog_service
+                .auth_phone(&account.phone)
+                .await
+                .map_err(api_error)?;
+            let auth_token = service
+                .generate_token(&auth_phone)
+                .await
+                .map_err(ApiError::internal)?;
+            let (token, refresh_token) = issue_tokens(
+                &service,
+                &dog_service,
+                &tokens,
+                &auth_token,
+                &account.phone,
+                device,
+            )
+            .await?;
+            Ok(Json(LoginByOAuthResp {
+                token: Some(token),
+                refresh_token: Some(refresh_token),
+                link_token: None,
+            }))
+        }
+        OAuthLoginOutcome::LinkRequired(link_token) => Ok(Json(LoginByOAuthResp {
+            token: None,
+            refresh_token: None,
+            link_token: Some(link_token),
+        })),
+    }
+}
+
+// 手机号未注册时以随机密码注册, 之后可通过重置密码设置登录密码
+#[utoipa::path(
+    post,
+    path = "/v1/oauth_accounts",
+    tag = "auth",
+    request_body = LinkOAuthAccountParams,
+    responses((status = 200, body = LinkOAuthAccountResp))
+)]
+pub async fn link_oauth_account<R, H, T, DR>(
+    service: Data<Service<R, H, T>>,
+    dog_service: Data<DogService<DR>>,
+    tokens: Data<AccessTokens>,
+    ClientDevice(device): ClientDevice,
+    Json(params): Json<LinkOAuthAccountParams>,
+) -> Result<Json<LinkOAuthAccountResp>, Error>
+where
+    R: Repository + Clone,
+    H: Hasher + Clone,
+    T: TokenManager + Clone,
+    DR: DogRepository,
+{
+    let link = dog_service
+        .verify_oauth_link(&params.link_token, &params.phone, &params.code)
+        .await
+        .map_err(ApiError::unauthorized)?;
+    let auth_phone = dog_service
+        .auth_phone(&params.phone)
+        .await
+        .map_err(api_error)?;
+    let exists = service
+        .exists_user(&auth_phone)
+        .await
+        .map_err(ApiError::internal)?;
+    let auth_token = if exists {
+        service.generate_token(&auth_phone).await
+    } else {
+        let password = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(32)
+            .map(char::from)
+            .collect::<String>();
+        service.signup(&params.phone, &password).await
+    }
+    .map_err(ApiError::internal)?;
+    let user_id = service
+        .verify_token(&auth_token)
+        .await
+        .map_err(ApiError::internal)?;
+    if !exists {
+        record_invite_conversion(&dog_service, &params.phone, &user_id).await;
+    }
+    dog_service
+        .link_oauth_account(link, &user_id, &params.phone)
+        .await
+        .map_err(api_error)?;
+    let (token, refresh_token) = issue_tokens(
+        &service,
+        &dog_service,
+        &tokens,
+        &auth_token,
+        &params.phone,
+        device,
+    )
+    .await?;
+    Ok(Json(LinkOAuthAccountResp {
+        token,
+        refresh_token,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use actix_web::{
+        http::{header::AUTHORIZATION, StatusCode},
+        test,
+        web::delete,
+        App,
+    };
+
+    use super::*;
+    use crate::core::{
+        entities::Role,
+        keys::{Key, KeyRing},
+        memory::InMemory,
+    };
+
+    const USER_ID: &str = "user-1";
+
+    fn access_tokens() -> AccessTokens {
+        let ring = KeyRing::new(vec![Key {
+            id: "test".to_owned(),
+            secret: b"secret".to_vec(),
+        }])
+        .unwrap();
+        AccessTokens::new(&ring, "issuer", "audience", Duration::from_secs(3600)).unwrap()
+    }
+
+    #[actix_web::test]
+    async fn revoked_token_is_rejected_while_other_sessions_still_work() {
+        let (tokens, service) = (access_tokens(), DogService::new(InMemory::new()));
+        let current = tokens.issue(USER_ID, vec![Role::Owner], 0, None).unwrap();
+        let other = tokens.issue(USER_ID, vec![Role::Owner], 0, None).unwrap();
+        let claims = tokens.verify(&current).unwrap();
+        service
+            .revoke_access_token(
+                claims.jti.as_deref().unwrap(),
+                USER_ID,
+                DateTime::from_timestamp(claims.exp, 0).unwrap(),
+            )
+            .await
+            .unwrap();
+        assert!(authenticate(&tokens, &service, &current).await.is_err());
+        assert!(authenticate(&tokens, &service, &other).await.is_ok());
+    }
+
+    #[actix_web::test]
+    async fn revoking_the_current_token_is_idempotent() {
+        let tokens = access_tokens();
+        let token = tokens.issue(USER_ID, vec![Role::Owner], 0, None).unwrap();
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::new(tokens))
+                .app_data(Data::new(DogService::new(InMemory::new())))
+                .route(
+                    "/tokens/current",
+                    delete().to(revoke_current_token::<InMemory>),
+                ),
+        )
+        .await;
+        for _ in 0..2 {
+            let req = test::TestRequest::delete()
+                .uri("/tokens/current")
+                .insert_header((AUTHORIZATION, format!("Bearer {}", token)))
+                .to_request();
+            assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use crate::{
+    core::{repository::Repository, service::Service},
+    handlers::common::AuthUser,
+    handlers::error::api_error,
+};
+use actix_web::{
+    web::{Data, Json, Path},
+    Error,
+};
+use little_walk_types::{block::UnblockResp, dto::BlockResp};
+
+#[utoipa::path(
+    put,
+    path = "/v1/blocks/{walker_id}",
+    tag = "block",
+    params(("walker_id" = String, Path)),
+    responses((status = 200, body = BlockResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn block<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    walker_id: Path<(String,)>,
+) -> Result<Json<BlockResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .block_walker(&uid, &walker_id.0)
+        .await
+        .map(|block| Json(block.into()))
+        .map_err(api_error)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/blocks/{walker_id}",
+    tag = "block",
+    params(("walker_id" = String, Path)),
+    responses((status = 200, body = UnblockResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn unblock<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    walker_id: Path<(String,)>,
+) -> Result<Json<UnblockResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .unblock_walker(&uid, &walker_id.0)
+        .await
+        .map_err(api_error)?;
+    Ok(Json(UnblockResp { success: true }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/blocks",
+    tag = "block",
+    responses((status = 200, body = Vec<BlockResp>)),
+    security(("bearer_auth" = []))
+)]
+pub async fn my_blocks<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+) -> Result<Json<Vec<BlockResp>>, Error>
+where
+    R: Repository,
+{
+    service
+        .blocked_walkers(&uid)
+        .await
+        .map(|blocks| Json(blocks.into_iter().map(BlockResp::from).collect()))
+        .map_err(api_error)
+}
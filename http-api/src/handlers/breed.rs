@@ -0,0 +1,126 @@
+use crate::{
+    core::{entities::Breed, ids::WalkRequestId, repository::{BreedQuery, Repository}, service::Service},
+    handlers::{
+        error::api_error,
+        cache::{cached_json, weak_etag, CachePolicies},
+        common::{AdminRole, AuthUser, RequireRole},
+        validation::Valid,
+    },
+};
+use actix_web::{
+    web::{Data, Json, Path, Query},
+    Error, HttpRequest, HttpResponse,
+};
+use little_walk_types::{
+    common::ListResp,
+    dto::{
+        BreedDetailResp, BreedQueryReq, BreedResp, CreateBreedReq, DogCareTipsResp, LocaleReq,
+        SortReq, UpdateBreedCareTipsReq,
+    },
+};
+
+#[utoipa::path(
+    post,
+    path = "/v1/breeds",
+    tag = "breed",
+    request_body = CreateBreedReq,
+    responses((status = 200, body = String)),
+    security(("bearer_auth" = []))
+)]
+pub(crate) async fn create_breed<R>(service: Data<Service<R>>, _: RequireRole<AdminRole>, Json(breed): Json<CreateBreedReq>) -> Result<String, Error>
+where
+    R: Repository,
+{
+    service.create_breed(breed.into()).await.map_err(api_error)
+}
+
+// 品种选择器频繁拉取完整列表, 品种未变化时返回304
+#[utoipa::path(
+    get,
+    path = "/v1/breeds",
+    tag = "breed",
+    params(BreedQueryReq, SortReq),
+    responses((status = 200, body = ListResp<BreedResp>), (status = 304, description = "品种列表未变化"))
+)]
+pub(crate) async fn breeds<R>(service: Data<Service<R>>, policies: Data<CachePolicies>, req: HttpRequest, Query(query): Query<BreedQueryReq>, Query(sort): Query<SortReq>) -> Result<HttpResponse, Error>
+where
+    R: Repository,
+{
+    let query = BreedQuery { sort_by: sort.parse(&Breed::sortable_fields()).map_err(api_error)?, ..query.into() };
+    let (breeds, total) = service.query_breeds(&query).await.map_err(api_error)?;
+    // 不同排序的响应顺序不同, 排序方式计入ETag
+    let order = query.sort_by.map(|sort_by| format!("{}:{:?}", sort_by.field, sort_by.order));
+    let etag = weak_etag(order.as_deref().map(|o| (o, None)).into_iter().chain(breeds.iter().map(|b| (b.id.as_str(), b.updated_at))));
+    Ok(cached_json(&req, &policies.breeds, etag, ListResp::new(breeds.into_iter().map(BreedResp::from).collect(), total)))
+}
+
+// 品种详情附带请求语言的照护建议, 建议很少变化, 按品种和建议的更新时间生成ETag
+#[utoipa::path(
+    get,
+    path = "/v1/breeds/{id}",
+    tag = "breed",
+    params(("id" = String, Path), LocaleReq),
+    responses((status = 200, body = BreedDetailResp), (status = 304, description = "品种和照护建议未变化"))
+)]
+pub(crate) async fn breed<R>(service: Data<Service<R>>, policies: Data<CachePolicies>, req: HttpRequest, id: Path<(String,)>, Query(query): Query<LocaleReq>) -> Result<HttpResponse, Error>
+where
+    R: Repository,
+{
+    let (breed, tips) = service.breed(&id.0, &query.locale).await.map_err(api_error)?;
+    let etag = weak_etag([(breed.id.as_str(), breed.updated_at)].into_iter().chain(tips.iter().map(|t| (t.locale.as_str(), t.updated_at))));
+    Ok(cached_json(&req, &policies.breeds, etag, BreedDetailResp::new(breed, tips)))
+}
+
+// 覆盖该品种该语言的全部照护建议
+#[utoipa::path(
+    put,
+    path = "/v1/admin/breeds/{id}/care_tips/{locale}",
+    tag = "breed",
+    params(("id" = String, Path), ("locale" = String, Path)),
+    request_body = UpdateBreedCareTipsReq,
+    responses((status = 200)),
+    security(("bearer_auth" = []))
+)]
+pub(crate) async fn update_care_tips<R>(service: Data<Service<R>>, _: RequireRole<AdminRole>, AuthUser { user_id: uid, .. }: AuthUser, path: Path<(String, String)>, Valid(req): Valid<UpdateBreedCareTipsReq>) -> Result<HttpResponse, Error>
+where
+    R: Repository,
+{
+    let (breed_id, locale) = path.into_inner();
+    service.update_breed_care_tips(&breed_id, &locale, req.tips, &uid).await.map_err(api_error)?;
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/admin/breeds/{id}/care_tips/{locale}",
+    tag = "breed",
+    params(("id" = String, Path), ("locale" = String, Path)),
+    responses((status = 200)),
+    security(("bearer_auth" = []))
+)]
+pub(crate) async fn delete_care_tips<R>(service: Data<Service<R>>, _: RequireRole<AdminRole>, path: Path<(String, String)>) -> Result<HttpResponse, Error>
+where
+    R: Repository,
+{
+    let (breed_id, locale) = path.into_inner();
+    service.delete_breed_care_tips(&breed_id, &locale).await.map_err(api_error)?;
+    Ok(HttpResponse::Ok().finish())
+}
+
+// 遛狗请求中各狗狗品种的照护建议, 供接单的遛狗人出发前查看
+#[utoipa::path(
+    get,
+    path = "/v1/walk_requests/{id}/care_tips",
+    tag = "breed",
+    params(("id" = String, Path), LocaleReq),
+    responses((status = 200, body = Vec<DogCareTipsResp>), (status = 304, description = "照护建议未变化")),
+    security(("bearer_auth" = []))
+)]
+pub(crate) async fn walk_request_care_tips<R>(service: Data<Service<R>>, policies: Data<CachePolicies>, req: HttpRequest, AuthUser { user_id: uid, .. }: AuthUser, request_id: Path<(WalkRequestId,)>, Query(query): Query<LocaleReq>) -> Result<HttpResponse, Error>
+where
+    R: Repository,
+{
+    let dogs = service.walk_request_care_tips(&request_id.0.to_string(), &uid, &query.locale).await.map_err(api_error)?;
+    let etag = weak_etag(dogs.iter().map(|(dog, tips)| (dog.breed.id.as_str(), tips.as_ref().and_then(|t| t.updated_at))));
+    Ok(cached_json(&req, &policies.dogs, etag, dogs.into_iter().map(|(dog, tips)| DogCareTipsResp::new(dog, tips)).collect::<Vec<_>>()))
+}
@@ -0,0 +1,89 @@
+// 只读接口的HTTP缓存: 按配置返回Cache-Control, 用记录的更新时间生成弱ETag并处理条件请求
+use actix_web::{
+    http::header::{CacheControl, CacheDirective, ETag, EntityTag, Header, IfNoneMatch},
+    HttpRequest, HttpResponse,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+// 由配置生成, 各接口按数据的敏感程度使用不同的策略
+pub struct CachePolicies {
+    pub breeds: CachePolicy,
+    pub dogs: CachePolicy,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CachePolicy {
+    pub max_age: u32, // 为0时客户端每次都需用ETag重新验证
+    pub public: bool, // 是否允许CDN等共享缓存保存
+}
+
+impl CachePolicy {
+    fn cache_control(&self) -> CacheControl {
+        CacheControl(vec![
+            if self.public {
+                CacheDirective::Public
+            } else {
+                CacheDirective::Private
+            },
+            if self.max_age == 0 {
+                CacheDirective::NoCache
+            } else {
+                CacheDirective::MaxAge(self.max_age)
+            },
+        ])
+    }
+}
+
+// 记录增删或任一记录更新后ETag都会变化
+pub fn weak_etag<'a>(
+    records: impl IntoIterator<Item = (&'a str, Option<DateTime<Utc>>)>,
+) -> EntityTag {
+    let mut hasher = Sha256::new();
+    for (id, updated_at) in records {
+        hasher.update(id.as_bytes());
+        hasher.update(
+            updated_at
+                .map(|t| t.timestamp_millis())
+                .unwrap_or_default()
+                .to_be_bytes(),
+        );
+    }
+    EntityTag::new_weak(
+        hasher.finalize()[..8]
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect(),
+    )
+}
+
+// If-None-Match与当前ETag匹配时返回304, 否则返回带缓存头的JSON
+pub fn cached_json<T>(
+    req: &HttpRequest,
+    policy: &CachePolicy,
+    etag: EntityTag,
+    body: T,
+) -> HttpResponse
+where
+    T: Serialize,
+{
+    let not_modified = match IfNoneMatch::parse(req) {
+        Ok(IfNoneMatch::Any) => true,
+        Ok(IfNoneMatch::Items(tags)) => tags.iter().any(|tag| tag.weak_eq(&etag)),
+        Err(_) => false,
+    };
+    let mut builder = if not_modified {
+        HttpResponse::NotModified()
+    } else {
+        HttpResponse::Ok()
+    };
+    builder
+        .insert_header(ETag(etag))
+        .insert_header(policy.cache_control());
+    if not_modified {
+        builder.finish()
+    } else {
+        builder.json(body)
+    }
+}
@@ -0,0 +1,199 @@
+use crate::{
+    core::{
+        chat::{ChatEvent, ChatHub},
+        ids::WalkRequestId,
+        repository::{Page, Repository},
+        service::Service,
+        translation::Translator,
+    },
+    handlers::{common::AuthUser, error::api_error},
+};
+use actix_web::{
+    rt,
+    web::{Data, Json, Path, Payload},
+    Error, HttpRequest, HttpResponse,
+};
+use actix_ws::{CloseCode, Message, Session};
+use futures::StreamExt;
+use little_walk_types::{
+    chat::ChatReq,
+    common::PageResp,
+    dto::{ChatMessageResp, PageReq, TranslateReq},
+};
+use nb_serde_query::actix_web::Query;
+
+// 建立聊天连接, 客户端发送{"type":"message","text":...,"imageIds":[...]}, 服务端向聊天室内的全部连接推送message事件,
+// 发送{"type":"read","messageId":...}标记已读, 已读位置前进时推送read事件. 连接建立时先推送双方当前的已读位置.
+// 发送失败时只向发送方推送error事件. 失去聊天权限的连接以policy状态码关闭
+#[utoipa::path(
+    get,
+    path = "/v1/walk_requests/{id}/chat",
+    tag = "chat",
+    params(("id" = String, Path)),
+    responses((status = 101, description = "切换为WebSocket连接")),
+    security(("bearer_auth" = []))
+)]
+pub async fn chat<R>(
+    req: HttpRequest,
+    body: Payload,
+    service: Data<Service<R>>,
+    hub: Data<ChatHub>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    request_id: Path<(WalkRequestId,)>,
+) -> Result<HttpResponse, Error>
+where
+    R: Repository + 'static,
+{
+    let request_id = request_id.0.to_string();
+    let user_id: String = uid.into();
+    service
+        .check_chat_access(&request_id, &user_id)
+        .await
+        .map_err(api_error)?;
+    let read_markers = service
+        .chat_read_markers(&request_id)
+        .await
+        .map_err(api_error)?;
+    let (response, session, mut stream) = actix_ws::handle(&req, body)?;
+    let (connection_id, mut events) = hub.join(&request_id);
+
+    // 推送前重新检查权限, 请求被改派或取消接单后原遛狗人的连接不再收到事件并被关闭
+    let mut outgoing = session.clone();
+    let (outgoing_service, outgoing_request_id, outgoing_user_id) =
+        (service.clone(), request_id.clone(), user_id.clone());
+    rt::spawn(async move {
+        for marker in read_markers {
+            if send_event(&mut outgoing, &marker.into()).await.is_err() {
+                return;
+            }
+        }
+        while let Some(event) = events.recv().await {
+            if outgoing_service
+                .check_chat_access(&outgoing_request_id, &outgoing_user_id)
+                .await
+                .is_err()
+            {
+                let _ = outgoing.close(Some(CloseCode::Policy.into())).await;
+                break;
+            }
+            if send_event(&mut outgoing, &event).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut session = session;
+    rt::spawn(async move {
+        let mut close_reason = None;
+        while let Some(Ok(message)) = stream.next().await {
+            match message {
+                Message::Text(text) => {
+                    let result = match serde_json::from_str::<ChatReq>(&text) {
+                        Ok(ChatReq::Message { text, image_ids }) => service
+                            .send_chat_message(&request_id, &user_id, text, image_ids)
+                            .await
+                            .map(Some)
+                            .map_err(|e| e.to_string()),
+                        Ok(ChatReq::Read { message_id }) => service
+                            .mark_chat_read(&request_id, &user_id, &message_id)
+                            .await
+                            .map_err(|e| e.to_string()),
+                        Err(e) => Err(format!("无法解析的消息: {}", e)),
+                    };
+                    match result {
+                        Ok(Some(event)) => hub.publish(&request_id, event),
+                        Ok(None) => {}
+                        Err(message) => {
+                            if send_event(&mut session, &ChatEvent::Error { message })
+                                .await
+                                .is_err()
+                            {
+                                break;
+                            }
+                        }
+                    }
+                }
+                Message::Ping(bytes) => {
+                    if session.pong(&bytes).await.is_err() {
+                        break;
+                    }
+                }
+                Message::Close(reason) => {
+                    close_reason = reason;
+                    break;
+                }
+                _ => {}
+            }
+        }
+        hub.leave(&request_id, connection_id);
+        let _ = session.close(close_reason).await;
+    });
+
+    Ok(response)
+}
+
+// 聊天记录按发送时间倒序, 传translate时附带消息文本的译文
+#[utoipa::path(
+    get,
+    path = "/v1/walk_requests/{id}/messages",
+    tag = "chat",
+    params(("id" = String, Path), PageReq, TranslateReq),
+    responses((status = 200, body = PageResp<ChatMessageResp>)),
+    security(("bearer_auth" = []))
+)]
+pub async fn messages<R, T>(
+    service: Data<Service<R>>,
+    translator: Data<T>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    request_id: Path<(WalkRequestId,)>,
+    Query(req): Query<PageReq>,
+    Query(translate): Query<TranslateReq>,
+) -> Result<Json<PageResp<ChatMessageResp>>, Error>
+where
+    R: Repository,
+    T: Translator,
+{
+    let page = Page::try_from(&req).map_err(api_error)?;
+    let (messages, total, next) = service
+        .chat_messages(&request_id.0.to_string(), &uid, page)
+        .await
+        .map_err(api_error)?;
+    let mut translated = match translate.translate {
+        Some(language) => service
+            .translate(
+                translator.get_ref(),
+                messages
+                    .iter()
+                    .filter(|m| !m.text.is_empty())
+                    .map(|m| (m.id.clone(), m.text.clone()))
+                    .collect(),
+                language,
+            )
+            .await
+            .map_err(api_error)?,
+        None => Default::default(),
+    };
+    let list = messages
+        .into_iter()
+        .map(|message| {
+            let translated_text = translated.remove(&message.id);
+            ChatMessageResp {
+                translated_text,
+                ..message.into()
+            }
+        })
+        .collect();
+    Ok(Json(
+        PageResp::new(list, total, &req.pagination()).with_next_cursor(next),
+    ))
+}
+
+async fn send_event(session: &mut Session, event: &ChatEvent) -> Result<(), actix_ws::Closed> {
+    match serde_json::to_string(event) {
+        Ok(text) => session.text(text).await,
+        Err(e) => {
+            log::error!("failed to serialize chat event: {}", e);
+            Ok(())
+        }
+    }
+}
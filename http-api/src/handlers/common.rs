@@ -0,0 +1,350 @@
+use std::marker::PhantomData;
+
+use actix_web::{
+    http::header::AUTHORIZATION, web::Data, Error, FromRequest, HttpMessage, HttpRequest,
+};
+use futures::future::{ready, LocalBoxFuture, Ready};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    access_tokens::{AccessClaims, AccessTokens},
+    core::{
+        entities::{Device, Partner, PartnerScope, Role, SensitiveAction},
+        ids::UserId,
+        repository::{FieldSet, Repository},
+        service::Service,
+    },
+    handlers::error::{api_error, ApiError},
+    repositories::audited::AuditedMongoDB,
+    routes::API_PREFIX,
+};
+
+// 当前登录用户. 校验Authorization中访问令牌的签名、签发者、受众、过期时间、令牌版本和是否已单独吊销,
+// 结果缓存在请求扩展中, 同一请求内多次提取只校验一次
+#[derive(Debug, Clone)]
+pub struct AuthUser {
+    pub user_id: UserId,
+    pub roles: Vec<Role>,
+}
+
+pub fn bearer_token(req: &HttpRequest) -> Result<String, Error> {
+    req.headers()
+        .get(AUTHORIZATION)
+        .and_then(|hv| hv.to_str().ok())
+        .and_then(|s| s.strip_prefix("Bearer "))
+        .map(str::to_owned)
+        .ok_or(ApiError::unauthorized("no access token").into())
+}
+
+// 校验访问令牌的签名、签发者、受众、过期时间和令牌版本, 不检查是否已单独吊销
+pub async fn verify_access_token<R>(
+    tokens: &AccessTokens,
+    service: &Service<R>,
+    token: &str,
+) -> Result<AccessClaims, Error>
+where
+    R: Repository,
+{
+    let claims = tokens.verify(token).map_err(ApiError::unauthorized)?;
+    let version = service
+        .token_version(&claims.sub)
+        .await
+        .map_err(api_error)?;
+    if claims.ver < version {
+        return Err(ApiError::unauthorized("access token revoked").into());
+    }
+    Ok(claims)
+}
+
+// AuthUser的完整校验
+pub async fn authenticate<R>(
+    tokens: &AccessTokens,
+    service: &Service<R>,
+    token: &str,
+) -> Result<AuthUser, Error>
+where
+    R: Repository,
+{
+    let claims = verify_access_token(tokens, service, token).await?;
+    if let Some(token_id) = &claims.jti {
+        if service
+            .is_access_token_revoked(token_id)
+            .await
+            .map_err(api_error)?
+        {
+            return Err(ApiError::unauthorized("access token revoked").into());
+        }
+    }
+    Ok(AuthUser {
+        user_id: UserId::try_from(claims.sub).map_err(ApiError::unauthorized)?,
+        roles: claims.roles,
+    })
+}
+
+impl FromRequest for AuthUser {
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+        if let Some(user) = req.extensions().get::<AuthUser>() {
+            let user = user.clone();
+            return Box::pin(async move { Ok(user) });
+        }
+        let req = req.clone();
+        Box::pin(async move {
+            let token = bearer_token(&req)?;
+            let tokens = req
+                .app_data::<Data<AccessTokens>>()
+                .ok_or(ApiError::internal("access tokens not configured"))?;
+            let service = req
+                .app_data::<Data<Service<AuditedMongoDB>>>()
+                .ok_or(ApiError::internal("service not configured"))?;
+            let user = authenticate(tokens, service, &token).await?;
+            req.extensions_mut().insert(user.clone());
+            Ok(user)
+        })
+    }
+}
+
+// 请求头中的客户端设备信息, 缺少时对应字段为空
+pub struct ClientDevice(pub Device);
+
+impl FromRequest for ClientDevice {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+        let header = |name: &str| {
+            req.headers()
+                .get(name)
+                .and_then(|hv| hv.to_str().ok())
+                .filter(|s| !s.is_empty())
+                .map(str::to_owned)
+        };
+        ready(Ok(ClientDevice(Device {
+            device_id: header("X-Device-ID"),
+            platform: header("X-Platform"),
+        })))
+    }
+}
+
+pub trait RoleRequirement {
+    const ROLE: Role;
+}
+
+pub struct OwnerRole;
+pub struct AdminRole;
+pub struct WalkerRole;
+
+impl RoleRequirement for OwnerRole {
+    const ROLE: Role = Role::Owner;
+}
+
+impl RoleRequirement for AdminRole {
+    const ROLE: Role = Role::Admin;
+}
+
+impl RoleRequirement for WalkerRole {
+    const ROLE: Role = Role::Walker;
+}
+
+// 要求当前用户带有指定角色, 缺少令牌返回401, 角色不符返回403
+pub struct RequireRole<T>(PhantomData<T>)
+where
+    T: RoleRequirement;
+
+impl<T> FromRequest for RequireRole<T>
+where
+    T: RoleRequirement + 'static,
+{
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+        let user = AuthUser::extract(req);
+        Box::pin(async move {
+            if !user.await?.roles.contains(&T::ROLE) {
+                return Err(ApiError::forbidden("permission denied").into());
+            }
+            Ok(RequireRole(PhantomData))
+        })
+    }
+}
+
+// 调用合作方接口的合作方. 校验请求头X-Api-Key中的API密钥, 缺少或无效时返回401;
+// 最近一分钟的调用达到合作方的请求上限时返回429, 否则按接口计入用量. 结果缓存在请求扩展中
+#[derive(Debug, Clone)]
+pub struct AuthPartner {
+    pub partner: Partner,
+}
+
+impl FromRequest for AuthPartner {
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+        if let Some(partner) = req.extensions().get::<AuthPartner>() {
+            let partner = partner.clone();
+            return Box::pin(async move { Ok(partner) });
+        }
+        let req = req.clone();
+        Box::pin(async move {
+            let api_key = req
+                .headers()
+                .get("X-Api-Key")
+                .and_then(|hv| hv.to_str().ok())
+                .ok_or(ApiError::unauthorized("no api key"))?
+                .to_owned();
+            let service = req
+                .app_data::<Data<Service<AuditedMongoDB>>>()
+                .ok_or(ApiError::internal("service not configured"))?;
+            let partner = service
+                .authenticate_partner(&api_key)
+                .await
+                .map_err(ApiError::unauthorized)?;
+            let endpoint = req.match_pattern().unwrap_or_else(|| req.path().to_owned());
+            // 旧路径与/v1路径的调用量合并统计和限流
+            let endpoint = endpoint.strip_prefix(API_PREFIX).unwrap_or(&endpoint);
+            if !service
+                .record_partner_usage(&partner, endpoint)
+                .await
+                .map_err(api_error)?
+            {
+                return Err(ApiError::too_many_requests("rate limit exceeded").into());
+            }
+            let partner = AuthPartner { partner };
+            req.extensions_mut().insert(partner.clone());
+            Ok(partner)
+        })
+    }
+}
+
+pub trait ScopeRequirement {
+    const SCOPE: PartnerScope;
+}
+
+pub struct CreateWalkRequestsScope;
+pub struct ReadWalkRequestsScope;
+pub struct ReadWalkerAvailabilityScope;
+
+impl ScopeRequirement for CreateWalkRequestsScope {
+    const SCOPE: PartnerScope = PartnerScope::CreateWalkRequests;
+}
+
+impl ScopeRequirement for ReadWalkRequestsScope {
+    const SCOPE: PartnerScope = PartnerScope::ReadWalkRequests;
+}
+
+impl ScopeRequirement for ReadWalkerAvailabilityScope {
+    const SCOPE: PartnerScope = PartnerScope::ReadWalkerAvailability;
+}
+
+// 要求合作方的API密钥带有指定权限范围, 权限不符返回403
+pub struct RequireScope<T>(PhantomData<T>)
+where
+    T: ScopeRequirement;
+
+impl<T> FromRequest for RequireScope<T>
+where
+    T: ScopeRequirement + 'static,
+{
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+        let partner = AuthPartner::extract(req);
+        Box::pin(async move {
+            if !partner.await?.partner.scopes.contains(&T::SCOPE) {
+                return Err(ApiError::forbidden("permission denied").into());
+            }
+            Ok(RequireScope(PhantomData))
+        })
+    }
+}
+
+pub trait ActionRequirement {
+    const ACTION: SensitiveAction;
+}
+
+pub struct DeleteAccountAction;
+pub struct RequestWithdrawalAction;
+pub struct ChangePayoutAccountAction;
+
+impl ActionRequirement for DeleteAccountAction {
+    const ACTION: SensitiveAction = SensitiveAction::DeleteAccount;
+}
+
+impl ActionRequirement for RequestWithdrawalAction {
+    const ACTION: SensitiveAction = SensitiveAction::RequestWithdrawal;
+}
+
+impl ActionRequirement for ChangePayoutAccountAction {
+    const ACTION: SensitiveAction = SensitiveAction::ChangePayoutAccount;
+}
+
+// 要求请求头X-Action-Token中带有当前用户为指定操作签发的确认令牌, 令牌校验后即作废,
+// 仅凭访问令牌无法完成敏感操作, 截获的确认令牌也不能重放
+pub struct RequireActionToken<T>(PhantomData<T>)
+where
+    T: ActionRequirement;
+
+impl<T> FromRequest for RequireActionToken<T>
+where
+    T: ActionRequirement + 'static,
+{
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+        let user = AuthUser::extract(req);
+        let req = req.clone();
+        Box::pin(async move {
+            let user = user.await?;
+            let token = req
+                .headers()
+                .get("X-Action-Token")
+                .and_then(|hv| hv.to_str().ok())
+                .ok_or(ApiError::forbidden("action token required"))?
+                .to_owned();
+            let service = req
+                .app_data::<Data<Service<AuditedMongoDB>>>()
+                .ok_or(ApiError::internal("service not configured"))?;
+            service
+                .consume_action_token(&user.user_id, T::ACTION, &token)
+                .await
+                .map_err(ApiError::forbidden)?;
+            Ok(RequireActionToken(PhantomData))
+        })
+    }
+}
+
+// 按字段选择裁剪列表项, 选择的字段为snake_case, 对应响应中的camelCase字段
+pub fn select_fields<T>(items: Vec<T>, fields: Option<&FieldSet>) -> Result<Vec<Value>, ApiError>
+where
+    T: Serialize,
+{
+    items
+        .into_iter()
+        .map(|item| {
+            let mut value = serde_json::to_value(item).map_err(ApiError::internal)?;
+            if let (Some(fields), Value::Object(object)) = (fields, &mut value) {
+                object.retain(|key, _| fields.contains(&snake_case(key)));
+            }
+            Ok(value)
+        })
+        .collect()
+}
+
+fn snake_case(key: &str) -> String {
+    let mut snake = String::with_capacity(key.len() + 4);
+    for c in key.chars() {
+        if c.is_ascii_uppercase() {
+            snake.push('_');
+            snake.push(c.to_ascii_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
@@ -0,0 +1,140 @@
+use crate::{
+    core::{repository::Repository, service::Service},
+    handlers::{
+        common::AuthUser,
+        error::{api_error, ApiError},
+    },
+};
+use actix_web::{
+    http::header::LOCATION,
+    web::{Data, Json, Path},
+    Error, HttpResponse,
+};
+use little_walk_types::dto::{CreateShortLinkReq, ShortLinkResp};
+use serde_json::{json, Value};
+
+// 由App处理的网页链接路径, 安装App后打开这些链接会直接进入App
+const APP_LINK_PATHS: [&str; 2] = ["/s/*", "/invites/*"];
+
+// 由配置生成的App关联文件, 对应平台未配置时返回404
+pub struct AppAssociation {
+    apple: Option<Value>,
+    android: Option<Value>,
+}
+
+impl AppAssociation {
+    pub fn new(
+        apple_app_ids: Vec<String>,
+        android_package_name: String,
+        android_cert_fingerprints: Vec<String>,
+    ) -> Self {
+        let apple = (!apple_app_ids.is_empty()).then(|| {
+            json!({
+                "applinks": {
+                    "details": [{
+                        "appIDs": apple_app_ids,
+                        "components": APP_LINK_PATHS
+                            .iter()
+                            .map(|path| json!({"/": path}))
+                            .collect::<Vec<Value>>(),
+                    }],
+                },
+            })
+        });
+        let android = (!android_package_name.is_empty()).then(|| {
+            json!([{
+                "relation": ["delegate_permission/common.handle_all_urls"],
+                "target": {
+                    "namespace": "android_app",
+                    "package_name": android_package_name,
+                    "sha256_cert_fingerprints": android_cert_fingerprints,
+                },
+            }])
+        });
+        Self { apple, android }
+    }
+}
+
+// iOS通用链接的关联文件
+#[utoipa::path(
+    get,
+    path = "/.well-known/apple-app-site-association",
+    tag = "deep_link",
+    responses((status = 200, description = "apple-app-site-association内容"))
+)]
+pub async fn apple_app_site_association(
+    association: Data<AppAssociation>,
+) -> Result<Json<Value>, Error> {
+    association
+        .apple
+        .clone()
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found("apple app site association not configured").into())
+}
+
+// Android App Links的关联文件
+#[utoipa::path(
+    get,
+    path = "/.well-known/assetlinks.json",
+    tag = "deep_link",
+    responses((status = 200, description = "assetlinks.json内容"))
+)]
+pub async fn asset_links(association: Data<AppAssociation>) -> Result<Json<Value>, Error> {
+    association
+        .android
+        .clone()
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found("asset links not configured").into())
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/short_links",
+    tag = "deep_link",
+    request_body = CreateShortLinkReq,
+    responses((status = 200, body = ShortLinkResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn create_short_link<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    Json(req): Json<CreateShortLinkReq>,
+) -> Result<Json<ShortLinkResp>, Error>
+where
+    R: Repository,
+{
+    let (url, expires_at) = service
+        .create_short_link(
+            &uid,
+            req.kind,
+            &req.token,
+            req.ttl_days.map(chrono::Duration::days),
+        )
+        .await
+        .map_err(api_error)?;
+    Ok(Json(ShortLinkResp { url, expires_at }))
+}
+
+// 未安装App时浏览器打开短链接, 跳转到App深度链接
+#[utoipa::path(
+    get,
+    path = "/s/{code}",
+    tag = "deep_link",
+    params(("code" = String, Path)),
+    responses((status = 302, description = "跳转到App深度链接"))
+)]
+pub async fn resolve_short_link<R>(
+    service: Data<Service<R>>,
+    code: Path<(String,)>,
+) -> Result<HttpResponse, Error>
+where
+    R: Repository,
+{
+    let link = service
+        .resolve_short_link(&code.0)
+        .await
+        .map_err(api_error)?;
+    Ok(HttpResponse::Found()
+        .insert_header((LOCATION, link))
+        .finish())
+}
@@ -0,0 +1,95 @@
+use crate::{
+    core::{object_store::ObjectStore, repository::Repository, service::Service},
+    handlers::{common::AuthUser, error::api_error},
+};
+use actix_web::{
+    http::header::LOCATION,
+    web::{Data, Json, Path},
+    Error, HttpResponse,
+};
+use little_walk_types::dto::{DirectUploadResp, PresignUploadReq, PresignUploadResp};
+
+#[utoipa::path(
+    post,
+    path = "/v1/uploads/presign",
+    tag = "direct_upload",
+    request_body = PresignUploadReq,
+    responses((status = 200, body = PresignUploadResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn presign<R, S>(
+    service: Data<Service<R>>,
+    store: Data<S>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    Json(req): Json<PresignUploadReq>,
+) -> Result<Json<PresignUploadResp>, Error>
+where
+    R: Repository,
+    S: ObjectStore + 'static,
+{
+    let (upload, url) = service
+        .presign_upload(
+            store.as_ref(),
+            &uid,
+            &req.filename,
+            &req.content_type,
+            req.size,
+        )
+        .await
+        .map_err(api_error)?;
+    Ok(Json(PresignUploadResp {
+        upload: upload.into(),
+        url,
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/uploads/presign/{id}/confirmation",
+    tag = "direct_upload",
+    params(("id" = String, Path)),
+    responses((status = 200, body = DirectUploadResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn confirm<R, S>(
+    service: Data<Service<R>>,
+    store: Data<S>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    id: Path<(String,)>,
+) -> Result<Json<DirectUploadResp>, Error>
+where
+    R: Repository,
+    S: ObjectStore + 'static,
+{
+    service
+        .confirm_direct_upload(store.as_ref(), &uid, &id.0)
+        .await
+        .map(|upload| Json(upload.into()))
+        .map_err(api_error)
+}
+
+// 重定向到对象存储的预签名下载地址
+#[utoipa::path(
+    get,
+    path = "/v1/uploads/direct/{id}",
+    tag = "direct_upload",
+    params(("id" = String, Path)),
+    responses((status = 302, description = "重定向到对象存储的预签名下载地址"))
+)]
+pub async fn download<R, S>(
+    service: Data<Service<R>>,
+    store: Data<S>,
+    id: Path<(String,)>,
+) -> Result<HttpResponse, Error>
+where
+    R: Repository,
+    S: ObjectStore + 'static,
+{
+    let url = service
+        .direct_upload_url(store.as_ref(), &id.0)
+        .await
+        .map_err(api_error)?;
+    Ok(HttpResponse::Found()
+        .insert_header((LOCATION, url))
+        .finish())
+}
@@ -0,0 +1,290 @@
+use crate::core::{
+    entities::{Dog, DogUpdateOutcome, PortraitUpdateOutcome},
+    ids::DogId,
+    inference::InferenceProvider,
+    repository::{Cursor, DogQuery, FieldSet, Page, Repository, SortBy, DOG_FIELDS},
+    service::Service,
+};
+use actix_web::{
+    http::header::{HeaderName, HeaderValue},
+    web::{Data, Json, Path},
+    Error, HttpRequest, HttpResponse,
+};
+use futures::TryStreamExt;
+use upload_service::core::{repository::Repository as UploadRepository, service::Service as UploadService, store::Store};
+
+use super::cache::{cached_json, weak_etag, CachePolicies};
+use super::common::{select_fields, AuthUser};
+use super::error::{api_error, ApiError};
+use super::validation::Valid;
+use little_walk_types::{
+    dog::{
+        AddDogPhotoReq, BreedSuggestionsReq, IsOwnerOfTheDogReq, IsOwnerOfTheDogResp,
+        MergeRequiredResp, PortraitRejectedResp, ReorderDogPhotosReq, UpdateDogPortraitReq,
+        UpdateDogPortraitResp, UpdateDogResult,
+    },
+    dto::{
+        BreedSuggestionResp, CreateDogReq, DogResp, DogsReq, FieldsReq, PageReq, SortReq,
+        UpdateDogReq,
+    },
+};
+use nb_serde_query::actix_web::Query;
+
+#[utoipa::path(
+    post,
+    path = "/v1/dogs",
+    tag = "dog",
+    request_body = CreateDogReq,
+    responses((status = 200, body = DogResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn create_dog<R>(serive: Data<Service<R>>, AuthUser { user_id: uid, .. }: AuthUser, Valid(dog): Valid<CreateDogReq>) -> Result<Json<DogResp>, Error>
+where
+    R: Repository,
+{
+    serive.create_dog(&dog.into_create(uid.into())).await.map(|dog| Json(dog.into())).map_err(api_error)
+}
+
+// 只有狗狗主人可以修改狗狗信息
+async fn ensure_dog_owner<R>(service: &Service<R>, uid: &str, dog_id: &str) -> Result<(), Error>
+where
+    R: Repository,
+{
+    if !service.is_owner_of_the_dog(uid, dog_id).await.map_err(api_error)? {
+        return Err(ApiError::forbidden("not the owner of the dog").into());
+    }
+    Ok(())
+}
+
+#[utoipa::path(
+    put,
+    path = "/v1/dogs/{id}",
+    tag = "dog",
+    params(("id" = String, Path)),
+    request_body = UpdateDogReq,
+    responses((status = 200, body = UpdateDogResult), (status = 409, body = MergeRequiredResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn update_dog<R>(service: Data<Service<R>>, AuthUser { user_id: uid, .. }: AuthUser, id: Path<(DogId,)>, Valid(dog): Valid<UpdateDogReq>) -> Result<HttpResponse, Error>
+where
+    R: Repository,
+{
+    ensure_dog_owner(&service, &uid, &id.0).await?;
+    match service.update_dog(&id.0, &dog.into()).await.map_err(api_error)? {
+        DogUpdateOutcome::Updated => Ok(HttpResponse::Ok().json(UpdateDogResult { updated: true })),
+        DogUpdateOutcome::Unchanged => Ok(HttpResponse::Ok().json(UpdateDogResult { updated: false })),
+        DogUpdateOutcome::Conflict(dog) => Ok(HttpResponse::Conflict().json(MergeRequiredResp { merge_required: true, server: dog.into() })),
+    }
+}
+
+// 删除狗狗时一并处理包含该狗狗的未开始的遛狗请求
+#[utoipa::path(
+    delete,
+    path = "/v1/dogs/{id}",
+    tag = "dog",
+    params(("id" = String, Path)),
+    responses((status = 200)),
+    security(("bearer_auth" = []))
+)]
+pub async fn delete_dog<R>(service: Data<Service<R>>, AuthUser { user_id: uid, .. }: AuthUser, id: Path<(DogId,)>) -> Result<HttpResponse, Error>
+where
+    R: Repository,
+{
+    ensure_dog_owner(&service, &uid, &id.0).await?;
+    service.delete_dog(&uid, &id.0).await.map_err(api_error)?;
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/dogs/mine",
+    tag = "dog",
+    params(PageReq, FieldsReq),
+    responses((status = 200, body = Vec<DogResp>, headers(("X-Next-Cursor" = String, description = "下一页的游标, 本页未满时不返回"))), (status = 304, description = "狗狗信息未变化")),
+    security(("bearer_auth" = []))
+)]
+pub async fn my_dogs<R>(service: Data<Service<R>>, policies: Data<CachePolicies>, req: HttpRequest, AuthUser { user_id: uid, .. }: AuthUser, Query(page): Query<PageReq>, Query(fields): Query<FieldsReq>) -> Result<HttpResponse, Error>
+where
+    R: Repository,
+{
+    let page = Page::try_from(&page).map_err(api_error)?;
+    let fields = fields.parse(DOG_FIELDS).map_err(api_error)?;
+    let (dogs, next) = service.my_dogs(&uid, Some(page), fields.clone()).await.map_err(api_error)?;
+    dogs_response(&req, &policies, dogs, fields.as_ref(), None, next)
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/dogs",
+    tag = "dog",
+    params(DogsReq, FieldsReq, SortReq),
+    responses((status = 200, body = Vec<DogResp>, headers(("X-Next-Cursor" = String, description = "下一页的游标, 本页未满时不返回"))), (status = 304, description = "狗狗信息未变化")),
+    security(("bearer_auth" = []))
+)]
+pub async fn dogs<R>(service: Data<Service<R>>, policies: Data<CachePolicies>, req: HttpRequest, _: AuthUser, Query(query): Query<DogsReq>, Query(fields): Query<FieldsReq>, Query(sort): Query<SortReq>) -> Result<HttpResponse, Error>
+where
+    R: Repository,
+{
+    let mut query = DogQuery::try_from(query).map_err(api_error)?;
+    query.fields = fields.parse(DOG_FIELDS).map_err(api_error)?;
+    query.sort_by = sort.parse(&Dog::sortable_fields()).map_err(api_error)?;
+    let (dogs, next) = service.page_dogs(&query).await.map_err(api_error)?;
+    dogs_response(&req, &policies, dogs, query.fields.as_ref(), query.sort_by.as_ref(), next)
+}
+
+// 选择的字段和排序方式计入ETag, 不同字段或顺序的响应不会互相命中缓存
+fn dogs_response(req: &HttpRequest, policies: &CachePolicies, dogs: Vec<Dog>, fields: Option<&FieldSet>, sort_by: Option<&SortBy>, next: Option<Cursor>) -> Result<HttpResponse, Error> {
+    let selected = fields.map(|fields| fields.iter().collect::<Vec<&str>>().join(","));
+    let order = sort_by.map(|sort_by| format!("{}:{:?}", sort_by.field, sort_by.order));
+    let variants = selected.as_deref().into_iter().chain(order.as_deref()).map(|v| (v, None));
+    let etag = weak_etag(variants.chain(dogs.iter().map(|d| (d.id.as_str(), d.updated_at))));
+    let body = select_fields(dogs.into_iter().map(DogResp::from).collect(), fields)?;
+    Ok(with_next_cursor(cached_json(req, &policies.dogs, etag, body), next))
+}
+
+// 狗狗列表的响应体是数组, 为兼容已有客户端, 下一页的游标通过X-Next-Cursor响应头返回
+fn with_next_cursor(mut resp: HttpResponse, cursor: Option<Cursor>) -> HttpResponse {
+    if let Some(cursor) = cursor {
+        resp.headers_mut().insert(HeaderName::from_static("x-next-cursor"), HeaderValue::from_str(&cursor.encode()).expect("cursor is base64url"));
+    }
+    resp
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/dogs/exists",
+    tag = "dog",
+    params(IsOwnerOfTheDogReq),
+    responses((status = 200, body = IsOwnerOfTheDogResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn is_owner_of_the_dog<R>(service: Data<Service<R>>, _: AuthUser, Query(query): Query<IsOwnerOfTheDogReq>) -> Result<Json<IsOwnerOfTheDogResp>, Error>
+where
+    R: Repository,
+{
+    let is_owner = service.is_owner_of_the_dog(&query.owner_id, &query.id).await.map_err(api_error)?;
+    Ok(Json(IsOwnerOfTheDogResp { is_owner }))
+}
+
+#[utoipa::path(
+    put,
+    path = "/v1/dogs/{id}/portrait",
+    tag = "dog",
+    params(("id" = String, Path)),
+    request_body = UpdateDogPortraitReq,
+    responses((status = 200, body = UpdateDogPortraitResp), (status = 422, body = PortraitRejectedResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn update_dog_portrait<R, UR, S, P>(service: Data<Service<R>>, upload_service: Data<UploadService<UR, S>>, provider: Data<P>, AuthUser { user_id: uid, .. }: AuthUser, dog_id: Path<(DogId,)>, Json(query): Json<UpdateDogPortraitReq>) -> Result<HttpResponse, Error>
+where
+    R: Repository,
+    UR: UploadRepository + Clone,
+    S: Store + Clone,
+    P: InferenceProvider,
+{
+    ensure_dog_owner(&service, &uid, &dog_id.0).await?;
+    let image = read_image(&upload_service, &query.portrait_id).await?;
+    match service.update_dog_portrait(provider.as_ref(), &dog_id.as_ref().0, &query.portrait_id, image).await.map_err(api_error)? {
+        PortraitUpdateOutcome::Updated { has_updated, warnings } => Ok(HttpResponse::Ok().json(UpdateDogPortraitResp { has_updated, warnings })),
+        PortraitUpdateOutcome::Rejected(issues) => Ok(HttpResponse::UnprocessableEntity().json(PortraitRejectedResp { issues })),
+    }
+}
+
+// 读取上传的图片内容
+async fn read_image<UR, S>(upload_service: &UploadService<UR, S>, upload_id: &str) -> Result<Vec<u8>, Error>
+where
+    UR: UploadRepository + Clone,
+    S: Store + Clone,
+{
+    let file_info = upload_service.get_uploaded_file(upload_id).await.map_err(ApiError::internal)?.ok_or(ApiError::bad_request(format!("upload {} not exists", upload_id)))?;
+    if !file_info.mime_type.starts_with("image/") {
+        return Err(ApiError::bad_request("upload is not an image").into());
+    }
+    let image = upload_service
+        .download(upload_id)
+        .await
+        .map_err(ApiError::internal)?
+        .try_fold(Vec::new(), |mut image, chunk| async move {
+            image.extend_from_slice(&chunk);
+            Ok(image)
+        })
+        .await
+        .map_err(ApiError::internal)?;
+    Ok(image)
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/dogs/breed_suggestions",
+    tag = "dog",
+    request_body = BreedSuggestionsReq,
+    responses((status = 200, body = Vec<BreedSuggestionResp>)),
+    security(("bearer_auth" = []))
+)]
+pub async fn breed_suggestions<R, UR, S, P>(service: Data<Service<R>>, upload_service: Data<UploadService<UR, S>>, provider: Data<P>, _: AuthUser, Json(req): Json<BreedSuggestionsReq>) -> Result<Json<Vec<BreedSuggestionResp>>, Error>
+where
+    R: Repository,
+    UR: UploadRepository + Clone,
+    S: Store + Clone,
+    P: InferenceProvider,
+{
+    let image = read_image(&upload_service, &req.upload_id).await?;
+    service.suggest_breeds(provider.as_ref(), image).await.map_err(api_error).map(|suggestions| Json(suggestions.into_iter().map(BreedSuggestionResp::from).collect()))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/dogs/{id}/photos",
+    tag = "dog",
+    params(("id" = String, Path)),
+    request_body = AddDogPhotoReq,
+    responses((status = 200, body = DogResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn add_dog_photo<R, UR, S>(service: Data<Service<R>>, upload_service: Data<UploadService<UR, S>>, AuthUser { user_id: uid, .. }: AuthUser, dog_id: Path<(DogId,)>, Json(req): Json<AddDogPhotoReq>) -> Result<Json<DogResp>, Error>
+where
+    R: Repository,
+    UR: UploadRepository + Clone,
+    S: Store + Clone,
+{
+    ensure_dog_owner(&service, &uid, &dog_id.0).await?;
+    let file_info = upload_service.get_uploaded_file(&req.photo_id).await.map_err(ApiError::internal)?.ok_or(ApiError::bad_request(format!("upload {} not exists", req.photo_id)))?;
+    if !file_info.mime_type.starts_with("image/") {
+        return Err(ApiError::bad_request("upload is not an image").into());
+    }
+    service.add_dog_photo(&dog_id.0, &req.photo_id).await.map(|dog| Json(dog.into())).map_err(api_error)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/dogs/{id}/photos/{photo_id}",
+    tag = "dog",
+    params(("id" = String, Path), ("photo_id" = String, Path)),
+    responses((status = 200, body = DogResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn remove_dog_photo<R>(service: Data<Service<R>>, AuthUser { user_id: uid, .. }: AuthUser, path: Path<(DogId, String)>) -> Result<Json<DogResp>, Error>
+where
+    R: Repository,
+{
+    let (dog_id, photo_id) = path.into_inner();
+    ensure_dog_owner(&service, &uid, &dog_id).await?;
+    service.remove_dog_photo(&dog_id, &photo_id).await.map(|dog| Json(dog.into())).map_err(api_error)
+}
+
+#[utoipa::path(
+    put,
+    path = "/v1/dogs/{id}/photos",
+    tag = "dog",
+    params(("id" = String, Path)),
+    request_body = ReorderDogPhotosReq,
+    responses((status = 200, body = DogResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn reorder_dog_photos<R>(service: Data<Service<R>>, AuthUser { user_id: uid, .. }: AuthUser, dog_id: Path<(DogId,)>, Json(req): Json<ReorderDogPhotosReq>) -> Result<Json<DogResp>, Error>
+where
+    R: Repository,
+{
+    ensure_dog_owner(&service, &uid, &dog_id.0).await?;
+    service.reorder_dog_photos(&dog_id.0, req.photos).await.map(|dog| Json(dog.into())).map_err(api_error)
+}
@@ -0,0 +1,148 @@
+use std::fmt::{self, Display};
+
+use actix_web::{http::StatusCode, HttpResponse, ResponseError};
+use little_walk_types::error::{ApiErrorResp, FieldError};
+
+use crate::core::error::{Error as CoreError, ErrorKind};
+
+// 内部错误不向客户端暴露细节, 只记录日志
+const INTERNAL_ERROR_MESSAGE: &str = "服务器内部错误, 请稍后重试";
+
+// 接口统一的错误响应. code供客户端判断错误类型, message可直接展示给用户
+#[derive(Debug)]
+pub struct ApiError {
+    status: StatusCode,
+    code: &'static str,
+    message: String,
+    fields: Vec<String>,
+    errors: Vec<FieldError>,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, code: &'static str, message: impl Display) -> Self {
+        Self {
+            status,
+            code,
+            message: message.to_string(),
+            fields: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn bad_request(message: impl Display) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, "invalid_request", message)
+    }
+
+    pub fn unauthorized(message: impl Display) -> Self {
+        Self::new(StatusCode::UNAUTHORIZED, "unauthorized", message)
+    }
+
+    pub fn forbidden(message: impl Display) -> Self {
+        Self::new(StatusCode::FORBIDDEN, "forbidden", message)
+    }
+
+    pub fn not_found(message: impl Display) -> Self {
+        Self::new(StatusCode::NOT_FOUND, "not_found", message)
+    }
+
+    pub fn conflict(message: impl Display) -> Self {
+        Self::new(StatusCode::CONFLICT, "conflict", message)
+    }
+
+    // 与已有记录重复, 与并发修改的conflict区分, 客户端不应刷新重试
+    pub fn duplicate(message: impl Display, fields: Vec<String>) -> Self {
+        Self {
+            fields,
+            ..Self::new(StatusCode::CONFLICT, "duplicate", message)
+        }
+    }
+
+    pub fn unprocessable(errors: Vec<FieldError>) -> Self {
+        Self {
+            errors,
+            ..Self::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "validation_failed",
+                "请求参数不合法",
+            )
+        }
+    }
+
+    pub fn too_many_requests(message: impl Display) -> Self {
+        Self::new(StatusCode::TOO_MANY_REQUESTS, "too_many_requests", message)
+    }
+
+    pub fn internal(err: impl Display) -> Self {
+        log::error!("internal error: {}", err);
+        Self::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "internal_error",
+            INTERNAL_ERROR_MESSAGE,
+        )
+    }
+}
+
+impl Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.code, self.message)
+    }
+}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        self.status
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status).json(ApiErrorResp {
+            code: self.code.to_owned(),
+            message: self.message.clone(),
+            fields: self.fields.clone(),
+            errors: self.errors.clone(),
+        })
+    }
+}
+
+impl From<CoreError> for ApiError {
+    fn from(err: CoreError) -> Self {
+        match err.kind() {
+            ErrorKind::Internal => Self::internal(err),
+            ErrorKind::Invalid => Self::bad_request(err.message()),
+            ErrorKind::NotFound => Self::not_found(err.message()),
+            ErrorKind::Forbidden => Self::forbidden(err.message()),
+            ErrorKind::Conflict if !err.fields().is_empty() => {
+                Self::duplicate(err.message(), err.fields().to_vec())
+            }
+            ErrorKind::Conflict => Self::conflict(err.message()),
+        }
+    }
+}
+
+// 用于map_err, 把服务层错误转换为接口错误
+pub fn api_error(err: CoreError) -> actix_web::Error {
+    ApiError::from(err).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[actix_web::test]
+    async fn duplicate_errors_are_conflicts_with_fields() {
+        let response = api_error(CoreError::duplicate(&["phone"])).error_response();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let body = actix_web::body::to_bytes(response.into_body())
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["code"], "duplicate");
+        assert_eq!(body["fields"], serde_json::json!(["phone"]));
+        let response = api_error(CoreError::conflict("请求已被修改")).error_response();
+        let body = actix_web::body::to_bytes(response.into_body())
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["code"], "conflict");
+        assert!(body.get("fields").is_none());
+    }
+}
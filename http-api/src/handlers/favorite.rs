@@ -0,0 +1,77 @@
+use crate::{
+    core::{repository::Repository, service::Service},
+    handlers::common::AuthUser,
+    handlers::error::api_error,
+};
+use actix_web::{
+    web::{Data, Json, Path},
+    Error,
+};
+use little_walk_types::{dto::FavoriteResp, favorite::UnfavoriteResp};
+
+#[utoipa::path(
+    put,
+    path = "/v1/favorites/{walker_id}",
+    tag = "favorite",
+    params(("walker_id" = String, Path)),
+    responses((status = 200, body = FavoriteResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn favorite<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    walker_id: Path<(String,)>,
+) -> Result<Json<FavoriteResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .favorite_walker(&uid, &walker_id.0)
+        .await
+        .map(|favorite| Json(favorite.into()))
+        .map_err(api_error)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/favorites/{walker_id}",
+    tag = "favorite",
+    params(("walker_id" = String, Path)),
+    responses((status = 200, body = UnfavoriteResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn unfavorite<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    walker_id: Path<(String,)>,
+) -> Result<Json<UnfavoriteResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .unfavorite_walker(&uid, &walker_id.0)
+        .await
+        .map_err(api_error)?;
+    Ok(Json(UnfavoriteResp { success: true }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/favorites",
+    tag = "favorite",
+    responses((status = 200, body = Vec<FavoriteResp>)),
+    security(("bearer_auth" = []))
+)]
+pub async fn my_favorites<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+) -> Result<Json<Vec<FavoriteResp>>, Error>
+where
+    R: Repository,
+{
+    service
+        .favorite_walkers(&uid)
+        .await
+        .map(|favorites| Json(favorites.into_iter().map(FavoriteResp::from).collect()))
+        .map_err(api_error)
+}
@@ -0,0 +1,34 @@
+use std::time::Instant;
+
+use actix_web::{web::Data, Error, HttpResponse};
+use little_walk_types::health::HealthResp;
+
+use crate::core::{repository::Repository, service::Service};
+
+// 供负载均衡和编排系统探测, 存储不可用时返回503
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "health",
+    responses((status = 200, body = HealthResp), (status = 503, body = HealthResp))
+)]
+pub async fn health<R>(service: Data<Service<R>>) -> Result<HttpResponse, Error>
+where
+    R: Repository,
+{
+    let started = Instant::now();
+    let result = service.self_check().await;
+    let latency_millis = started.elapsed().as_millis();
+    Ok(match result {
+        Ok(()) => HttpResponse::Ok().json(HealthResp {
+            healthy: true,
+            latency_millis,
+            error: None,
+        }),
+        Err(e) => HttpResponse::ServiceUnavailable().json(HealthResp {
+            healthy: false,
+            latency_millis,
+            error: Some(e.to_string()),
+        }),
+    })
+}
@@ -0,0 +1,172 @@
+use crate::{
+    core::{entities::HelpArticle, repository::Repository, service::Service},
+    handlers::{
+        common::{AdminRole, AuthUser, RequireRole},
+        error::api_error,
+    },
+};
+use actix_web::{
+    http::header::{CACHE_CONTROL, ETAG, IF_NONE_MATCH},
+    web::{Data, Json, Path},
+    Error, HttpRequest, HttpResponse,
+};
+use little_walk_types::{
+    dto::{HelpArticleResp, HelpArticleSummaryResp, PublishHelpArticleReq},
+    help::{HelpArticleFormat, HelpArticleReq, HelpArticlesReq},
+};
+use nb_serde_query::actix_web::Query;
+use pulldown_cmark::{html, Options, Parser};
+
+// 文章每个版本的内容不变, 客户端可以按ETag缓存
+const HELP_CACHE_CONTROL: &str = "public, max-age=300";
+
+fn render_markdown(markdown: &str) -> String {
+    let mut out = String::new();
+    html::push_html(
+        &mut out,
+        Parser::new_ext(
+            markdown,
+            Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH,
+        ),
+    );
+    out
+}
+
+fn etag(article: &HelpArticle, format: HelpArticleFormat) -> String {
+    format!("\"{}-{:?}\"", article.id, format)
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/help/articles",
+    tag = "help",
+    params(HelpArticlesReq),
+    responses((status = 200, body = Vec<HelpArticleSummaryResp>))
+)]
+pub async fn articles<R>(
+    service: Data<Service<R>>,
+    Query(req): Query<HelpArticlesReq>,
+) -> Result<HttpResponse, Error>
+where
+    R: Repository,
+{
+    let articles = service
+        .help_articles(&req.locale)
+        .await
+        .map_err(api_error)?;
+    Ok(HttpResponse::Ok()
+        .insert_header((CACHE_CONTROL, HELP_CACHE_CONTROL))
+        .json(
+            articles
+                .into_iter()
+                .map(HelpArticleSummaryResp::from)
+                .collect::<Vec<_>>(),
+        ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/help/articles/{slug}",
+    tag = "help",
+    params(("slug" = String, Path), HelpArticleReq),
+    responses((status = 200, body = HelpArticleResp), (status = 304, description = "文章未变化"))
+)]
+pub async fn article<R>(
+    service: Data<Service<R>>,
+    req: HttpRequest,
+    slug: Path<(String,)>,
+    Query(query): Query<HelpArticleReq>,
+) -> Result<HttpResponse, Error>
+where
+    R: Repository,
+{
+    let article = service
+        .help_article(&slug.0, &query.locale, query.version)
+        .await
+        .map_err(api_error)?;
+    let etag = etag(&article, query.format);
+    if req
+        .headers()
+        .get(IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.split(',').any(|t| t.trim() == etag))
+        .unwrap_or(false)
+    {
+        return Ok(HttpResponse::NotModified()
+            .insert_header((ETAG, etag))
+            .insert_header((CACHE_CONTROL, HELP_CACHE_CONTROL))
+            .finish());
+    }
+    let content = match query.format {
+        HelpArticleFormat::Html => render_markdown(&article.markdown),
+        HelpArticleFormat::Markdown => article.markdown,
+    };
+    Ok(HttpResponse::Ok()
+        .insert_header((ETAG, etag))
+        .insert_header((CACHE_CONTROL, HELP_CACHE_CONTROL))
+        .json(HelpArticleResp {
+            slug: article.slug,
+            locale: article.locale,
+            version: article.version,
+            title: article.title,
+            content,
+            created_at: article.created_at,
+        }))
+}
+
+#[utoipa::path(
+    put,
+    path = "/v1/admin/help/articles/{slug}",
+    tag = "help",
+    params(("slug" = String, Path)),
+    request_body = PublishHelpArticleReq,
+    responses((status = 200, body = HelpArticleSummaryResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn publish<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<AdminRole>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    slug: Path<(String,)>,
+    Json(req): Json<PublishHelpArticleReq>,
+) -> Result<Json<HelpArticleSummaryResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .publish_help_article(req.into_create(slug.into_inner().0, uid.into()))
+        .await
+        .map(|article| Json(article.into()))
+        .map_err(api_error)
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/help/articles/{slug}/versions",
+    tag = "help",
+    params(("slug" = String, Path), HelpArticlesReq),
+    responses((status = 200, body = Vec<HelpArticleSummaryResp>)),
+    security(("bearer_auth" = []))
+)]
+pub async fn versions<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<AdminRole>,
+    slug: Path<(String,)>,
+    Query(req): Query<HelpArticlesReq>,
+) -> Result<Json<Vec<HelpArticleSummaryResp>>, Error>
+where
+    R: Repository,
+{
+    service
+        .help_article_versions(&slug.0, &req.locale)
+        .await
+        .map(|articles| {
+            Json(
+                articles
+                    .into_iter()
+                    .map(HelpArticleSummaryResp::from)
+                    .collect(),
+            )
+        })
+        .map_err(api_error)
+}
@@ -0,0 +1,125 @@
+use crate::{
+    core::{
+        repository::{Pagination, Repository},
+        service::{Service, DEFAULT_HELP_LOCALE},
+        sms::SmsSender,
+    },
+    handlers::{
+        common::{AuthUser, OwnerRole, RequireRole},
+        error::{api_error, ApiError},
+        validation::Valid,
+    },
+};
+use actix_web::{
+    web::{Data, Json, Path},
+    Error,
+};
+use auth_service::core::{
+    hasher::Hasher, repository::Repository as AuthRepository, service::Service as AuthService,
+    token_manager::TokenManager,
+};
+use little_walk_types::{
+    common::PageResp,
+    dto::{InviteResp, OpenedInviteResp, SendInviteReq},
+    invite::SendInviteResp,
+};
+use nb_serde_query::actix_web::Query;
+
+// 狗狗主人通过短信邀请尚未注册的家庭成员或遛狗人
+#[utoipa::path(
+    post,
+    path = "/v1/invites",
+    tag = "invite",
+    request_body = SendInviteReq,
+    responses((status = 200, body = SendInviteResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn send_invite<R, AR, H, T, S>(
+    service: Data<Service<R>>,
+    auth_service: Data<AuthService<AR, H, T>>,
+    sender: Data<S>,
+    _: RequireRole<OwnerRole>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    Valid(req): Valid<SendInviteReq>,
+) -> Result<Json<SendInviteResp>, Error>
+where
+    R: Repository,
+    AR: AuthRepository + Clone,
+    H: Hasher + Clone,
+    T: TokenManager + Clone,
+    S: SmsSender,
+{
+    if service
+        .phone_bound_to(&req.phone)
+        .await
+        .map_err(api_error)?
+        .is_some()
+        || auth_service
+            .exists_user(&req.phone)
+            .await
+            .map_err(ApiError::internal)?
+    {
+        return Err(ApiError::conflict("user already registered").into());
+    }
+    let id = service
+        .send_invite(
+            sender.as_ref(),
+            &uid,
+            &req.phone,
+            req.kind,
+            req.locale.as_deref().unwrap_or(DEFAULT_HELP_LOCALE),
+        )
+        .await
+        .map_err(api_error)?;
+    Ok(Json(SendInviteResp { id }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/invites",
+    tag = "invite",
+    params(Pagination),
+    responses((status = 200, body = PageResp<InviteResp>)),
+    security(("bearer_auth" = []))
+)]
+pub async fn my_invites<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    Query(pagination): Query<Pagination>,
+) -> Result<Json<PageResp<InviteResp>>, Error>
+where
+    R: Repository,
+{
+    let (invites, total) = service
+        .my_invites(&uid, pagination.clone())
+        .await
+        .map_err(api_error)?;
+    Ok(Json(PageResp::new(
+        invites.into_iter().map(InviteResp::from).collect(),
+        total as u64,
+        &pagination,
+    )))
+}
+
+// 被邀请人打开短信链接时调用, 不需要登录
+#[utoipa::path(
+    post,
+    path = "/v1/invites/{code}/opened",
+    tag = "invite",
+    params(("code" = String, Path)),
+    responses((status = 200, body = OpenedInviteResp))
+)]
+pub async fn open_invite<R>(
+    service: Data<Service<R>>,
+    code: Path<(String,)>,
+) -> Result<Json<OpenedInviteResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .open_invite(&code.0)
+        .await
+        .map_err(api_error)?
+        .map(|invite| Json(invite.into()))
+        .ok_or_else(|| ApiError::not_found("invite not exists").into())
+}
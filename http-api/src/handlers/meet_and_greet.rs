@@ -0,0 +1,167 @@
+use crate::{
+    core::{ids::WalkRequestId, repository::Repository, service::Service},
+    handlers::{
+        common::{AuthUser, OwnerRole, RequireRole, WalkerRole},
+        error::api_error,
+        validation::Valid,
+    },
+};
+use actix_web::{
+    web::{Data, Json, Path},
+    Error,
+};
+use little_walk_types::dto::{MeetAndGreetResp, ProposeMeetAndGreetReq};
+
+// 只能邀请已报名或接单的遛狗人, 时间与双方已有的见面或遛狗人已接的遛狗冲突时返回409
+#[utoipa::path(
+    post,
+    path = "/v1/walk_requests/{id}/meet_and_greets",
+    tag = "meet_and_greet",
+    params(("id" = String, Path)),
+    request_body = ProposeMeetAndGreetReq,
+    responses((status = 200, body = MeetAndGreetResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn propose<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<OwnerRole>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    request_id: Path<(WalkRequestId,)>,
+    Valid(req): Valid<ProposeMeetAndGreetReq>,
+) -> Result<Json<MeetAndGreetResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .propose_meet_and_greet(req.into_create(request_id.0.to_string(), uid.into()))
+        .await
+        .map(|meet_and_greet| Json(meet_and_greet.into()))
+        .map_err(api_error)
+}
+
+// 狗狗主人返回请求的全部见面, 遛狗人只返回自己的, 按开始时间升序
+#[utoipa::path(
+    get,
+    path = "/v1/walk_requests/{id}/meet_and_greets",
+    tag = "meet_and_greet",
+    params(("id" = String, Path)),
+    responses((status = 200, body = Vec<MeetAndGreetResp>)),
+    security(("bearer_auth" = []))
+)]
+pub async fn meet_and_greets<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    request_id: Path<(WalkRequestId,)>,
+) -> Result<Json<Vec<MeetAndGreetResp>>, Error>
+where
+    R: Repository,
+{
+    service
+        .meet_and_greets(&request_id.0, &uid)
+        .await
+        .map(|meet_and_greets| {
+            Json(
+                meet_and_greets
+                    .into_iter()
+                    .map(MeetAndGreetResp::from)
+                    .collect(),
+            )
+        })
+        .map_err(api_error)
+}
+
+#[utoipa::path(
+    put,
+    path = "/v1/meet_and_greets/{id}/accept",
+    tag = "meet_and_greet",
+    params(("id" = String, Path)),
+    responses((status = 200, body = MeetAndGreetResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn accept<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<WalkerRole>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    id: Path<(String,)>,
+) -> Result<Json<MeetAndGreetResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .accept_meet_and_greet(&id.0, &uid)
+        .await
+        .map(|meet_and_greet| Json(meet_and_greet.into()))
+        .map_err(api_error)
+}
+
+#[utoipa::path(
+    put,
+    path = "/v1/meet_and_greets/{id}/decline",
+    tag = "meet_and_greet",
+    params(("id" = String, Path)),
+    responses((status = 200, body = MeetAndGreetResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn decline<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<WalkerRole>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    id: Path<(String,)>,
+) -> Result<Json<MeetAndGreetResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .decline_meet_and_greet(&id.0, &uid)
+        .await
+        .map(|meet_and_greet| Json(meet_and_greet.into()))
+        .map_err(api_error)
+}
+
+// 双方都可以取消尚未完成的见面
+#[utoipa::path(
+    put,
+    path = "/v1/meet_and_greets/{id}/cancel",
+    tag = "meet_and_greet",
+    params(("id" = String, Path)),
+    responses((status = 200, body = MeetAndGreetResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn cancel<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    id: Path<(String,)>,
+) -> Result<Json<MeetAndGreetResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .cancel_meet_and_greet(&id.0, &uid)
+        .await
+        .map(|meet_and_greet| Json(meet_and_greet.into()))
+        .map_err(api_error)
+}
+
+// 见面开始后双方都可以标记为已完成
+#[utoipa::path(
+    put,
+    path = "/v1/meet_and_greets/{id}/complete",
+    tag = "meet_and_greet",
+    params(("id" = String, Path)),
+    responses((status = 200, body = MeetAndGreetResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn complete<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    id: Path<(String,)>,
+) -> Result<Json<MeetAndGreetResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .complete_meet_and_greet(&id.0, &uid)
+        .await
+        .map(|meet_and_greet| Json(meet_and_greet.into()))
+        .map_err(api_error)
+}
@@ -0,0 +1,16 @@
+use actix_web::{Error, HttpResponse};
+
+use crate::{handlers::error::ApiError, metrics};
+
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "metrics",
+    responses((status = 200, description = "Prometheus指标", content_type = "text/plain"))
+)]
+pub async fn metrics() -> Result<HttpResponse, Error> {
+    let (content_type, body) = metrics::encode().map_err(ApiError::internal)?;
+    Ok(HttpResponse::Ok()
+        .insert_header(("Content-Type", content_type))
+        .body(body))
+}
@@ -0,0 +1,37 @@
+// 对外JSON统一使用camelCase, 请求字段保留snake_case别名以兼容迁移期间的旧客户端
+pub(crate) mod account;
+pub(crate) mod admin;
+pub(crate) mod auth;
+pub(crate) mod block;
+pub(crate) mod breed;
+pub(crate) mod cache;
+pub(crate) mod chat;
+pub(crate) mod common;
+pub(crate) mod deep_link;
+pub(crate) mod direct_upload;
+pub(crate) mod dog;
+pub(crate) mod error;
+pub(crate) mod favorite;
+pub(crate) mod health;
+pub(crate) mod help;
+pub(crate) mod invite;
+pub(crate) mod meet_and_greet;
+pub(crate) mod metrics;
+pub(crate) mod notification;
+pub(crate) mod openapi;
+pub(crate) mod owner;
+pub(crate) mod partner;
+pub(crate) mod payout;
+pub(crate) mod poi;
+pub(crate) mod public;
+pub(crate) mod refund;
+pub(crate) mod report;
+pub(crate) mod review;
+pub(crate) mod session;
+pub(crate) mod synthetic;
+pub(crate) mod ticket;
+pub(crate) mod upload;
+pub(crate) mod validation;
+pub(crate) mod walk_request;
+pub(crate) mod walker;
+pub(crate) mod withdrawal;
@@ -0,0 +1,152 @@
+use crate::{
+    core::{
+        repository::{NotificationSettingsUpsert, Pagination, Repository},
+        service::Service,
+    },
+    handlers::error::api_error,
+    handlers::{common::AuthUser, validation::Valid},
+};
+use actix_web::{
+    web::{Data, Json, Path},
+    Error,
+};
+use little_walk_types::{
+    dto::{
+        NotificationResp, NotificationSettingsReq, NotificationSettingsResp, RegisterPushTokenReq,
+    },
+    notification::PushTokenResp,
+};
+use nb_serde_query::actix_web::Query;
+
+// 最新的通知在前
+#[utoipa::path(
+    get,
+    path = "/v1/notifications/mine",
+    tag = "notification",
+    params(Pagination),
+    responses((status = 200, body = Vec<NotificationResp>)),
+    security(("bearer_auth" = []))
+)]
+pub async fn my_notifications<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    Query(pagination): Query<Pagination>,
+) -> Result<Json<Vec<NotificationResp>>, Error>
+where
+    R: Repository,
+{
+    service
+        .my_notifications(&uid, pagination)
+        .await
+        .map(|notifications| {
+            Json(
+                notifications
+                    .into_iter()
+                    .map(NotificationResp::from)
+                    .collect(),
+            )
+        })
+        .map_err(api_error)
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/notification_settings/mine",
+    tag = "notification",
+    responses((status = 200, body = NotificationSettingsResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn my_notification_settings<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+) -> Result<Json<NotificationSettingsResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .notification_settings(&uid)
+        .await
+        .map(|settings| Json(settings.into()))
+        .map_err(api_error)
+}
+
+#[utoipa::path(
+    put,
+    path = "/v1/notification_settings/mine",
+    tag = "notification",
+    request_body = NotificationSettingsReq,
+    responses((status = 200, body = NotificationSettingsResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn update_notification_settings<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    Valid(req): Valid<NotificationSettingsReq>,
+) -> Result<Json<NotificationSettingsResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .update_notification_settings(
+            NotificationSettingsUpsert {
+                user_id: uid.into(),
+                sms_enabled: req.sms_enabled,
+                sms_phone: req.sms_phone,
+                email: req.email,
+                email_receipts: req.email_receipts,
+                email_weekly_summary: req.email_weekly_summary,
+                email_verification_decisions: req.email_verification_decisions,
+            },
+            req.code.as_deref(),
+        )
+        .await
+        .map(|settings| Json(settings.into()))
+        .map_err(api_error)
+}
+
+// App启动或令牌刷新时登记, 新的通知会推送到该设备
+#[utoipa::path(
+    put,
+    path = "/v1/push_tokens",
+    tag = "notification",
+    request_body = RegisterPushTokenReq,
+    responses((status = 200, body = PushTokenResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn register_push_token<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    Valid(req): Valid<RegisterPushTokenReq>,
+) -> Result<Json<PushTokenResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .register_device_token(&uid, req.platform, &req.token)
+        .await
+        .map_err(api_error)?;
+    Ok(Json(PushTokenResp { success: true }))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/push_tokens/{token}",
+    tag = "notification",
+    params(("token" = String, Path)),
+    responses((status = 200, body = PushTokenResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn unregister_push_token<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    token: Path<(String,)>,
+) -> Result<Json<PushTokenResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .unregister_device_token(&uid, &token.0)
+        .await
+        .map_err(api_error)?;
+    Ok(Json(PushTokenResp { success: true }))
+}
@@ -0,0 +1,301 @@
+// 接口的OpenAPI描述, 由各handler上的utoipa::path标注汇总生成, 供移动端生成客户端代码
+use little_walk_types::{
+    error::{ApiErrorResp, FieldError},
+    help::HelpArticleFormat,
+    upload::UploadPurpose,
+};
+use utoipa::{
+    openapi::{
+        security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme},
+        RefOr, Response, ResponseBuilder,
+    },
+    Modify, OpenApi, PartialSchema, ToSchema,
+};
+
+use crate::core::{
+    entities::ImageSize, export::ExportFormat, repository::Pagination, translation::Language,
+};
+
+use super::{
+    account, admin, auth, block, breed, chat, deep_link, direct_upload, dog, favorite, health,
+    help, invite, meet_and_greet, metrics, notification, owner, partner, payout, poi, public,
+    refund, report, review, session, synthetic, ticket, upload, walk_request, walker, withdrawal,
+};
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Little Walk API"),
+    paths(
+        metrics::metrics,
+        health::health,
+        synthetic::walk,
+        withdrawal::payout_webhook,
+        deep_link::apple_app_site_association,
+        deep_link::asset_links,
+        deep_link::resolve_short_link,
+        auth::login_by_password,
+        auth::login_by_otp,
+        auth::login_by_oauth,
+        auth::link_oauth_account,
+        auth::send_otp,
+        auth::send_password_reset_code,
+        auth::verify_password_reset_code,
+        auth::reset_password,
+        auth::signup,
+        auth::refresh_token,
+        auth::verify_token,
+        auth::revoke_current_token,
+        auth::exists_user,
+        upload::get,
+        upload::delete,
+        upload::upload,
+        direct_upload::presign,
+        direct_upload::confirm,
+        direct_upload::download,
+        breed::create_breed,
+        breed::breeds,
+        breed::breed,
+        breed::update_care_tips,
+        breed::delete_care_tips,
+        breed::walk_request_care_tips,
+        dog::create_dog,
+        dog::dogs,
+        dog::update_dog,
+        dog::my_dogs,
+        dog::breed_suggestions,
+        dog::is_owner_of_the_dog,
+        dog::update_dog_portrait,
+        dog::add_dog_photo,
+        dog::reorder_dog_photos,
+        dog::remove_dog_photo,
+        dog::delete_dog,
+        walker::search,
+        walker::update_location,
+        walker::my_verification,
+        payout::my_payout_account,
+        payout::link_payout_account,
+        payout::unlink_payout_account,
+        withdrawal::my_balance,
+        withdrawal::my_ledger,
+        withdrawal::my_withdrawals,
+        withdrawal::request_withdrawal,
+        walker::submit_verification,
+        review::walker_reviews,
+        review::vote_helpful,
+        walker::stats,
+        walker::achievements,
+        block::my_blocks,
+        block::block,
+        block::unblock,
+        favorite::my_favorites,
+        favorite::favorite,
+        favorite::unfavorite,
+        partner::my_consents,
+        partner::grant_consent,
+        partner::revoke_consent,
+        report::create_report,
+        help::articles,
+        help::article,
+        public::neighborhoods,
+        invite::send_invite,
+        invite::my_invites,
+        invite::open_invite,
+        deep_link::create_short_link,
+        session::sessions,
+        session::revoke_session,
+        notification::my_notifications,
+        notification::register_push_token,
+        notification::unregister_push_token,
+        notification::my_notification_settings,
+        notification::update_notification_settings,
+        session::revoke_device,
+        account::delete_me,
+        account::update_avatar,
+        auth::issue_action_token,
+        auth::change_password,
+        auth::send_phone_change_code,
+        auth::change_phone,
+        ticket::create_ticket,
+        ticket::my_tickets,
+        ticket::my_ticket,
+        ticket::reply,
+        ticket::close,
+        owner::update_my_profile,
+        owner::owner_profile,
+        owner::owner_reputation,
+        owner::owner_reviews,
+        walk_request::create_walk_request,
+        walk_request::validate_walk_request,
+        walk_request::changes,
+        walk_request::my_walk_requests,
+        walk_request::nearby_walk_requests,
+        walk_request::delete_walk_request,
+        walk_request::assign_accepter,
+        walk_request::resign_acceptance,
+        walk_request::cancellation_penalties,
+        refund::request_refund,
+        refund::walk_request_refunds,
+        walk_request::update_route_preference,
+        walk_request::route_preference,
+        walk_request::export,
+        walk_request::route_report,
+        meet_and_greet::propose,
+        meet_and_greet::meet_and_greets,
+        meet_and_greet::accept,
+        meet_and_greet::decline,
+        meet_and_greet::cancel,
+        meet_and_greet::complete,
+        chat::chat,
+        chat::messages,
+        review::create_review,
+        admin::invite_conversion,
+        report::reports,
+        report::review_report,
+        poi::create_poi,
+        poi::pois,
+        poi::delete_poi,
+        help::publish,
+        help::versions,
+        ticket::tickets,
+        ticket::ticket,
+        ticket::staff_reply,
+        ticket::assign,
+        ticket::update_status,
+        withdrawal::withdrawals,
+        withdrawal::approve,
+        withdrawal::reject,
+        refund::refund_requests,
+        refund::approve_refund,
+        refund::reject_refund,
+        admin::explain,
+        admin::purge_deleted,
+        admin::integrity_report,
+        admin::location_accesses,
+        admin::audit_logs,
+        admin::operations_feed,
+        admin::merge_accounts,
+        admin::preview_account_merge,
+        walker::verifications,
+        walker::approve_verification,
+        walker::reject_verification,
+        admin::create_partner,
+        admin::partners,
+        admin::update_partner,
+        admin::issue_partner_api_key,
+        admin::partner_api_keys,
+        admin::revoke_partner_api_key,
+        admin::partner_usage,
+        partner::create_walk_request,
+        partner::walk_request,
+        partner::walker_availability,
+        partner::usage,
+    ),
+    // 只在查询参数中引用的类型不会被自动收集
+    components(schemas(
+        ExportFormat,
+        HelpArticleFormat,
+        ImageSize,
+        Language,
+        Pagination,
+        UploadPurpose
+    )),
+    modifiers(&SecuritySchemes, &ErrorResponses)
+)]
+pub struct ApiDoc;
+
+// bearer_auth为登录后获得的访问令牌, action_token为敏感操作前签发的一次性令牌, api_key为合作方密钥
+struct SecuritySchemes;
+
+impl Modify for SecuritySchemes {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+        components.add_security_scheme(
+            "action_token",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("X-Action-Token"))),
+        );
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("X-Api-Key"))),
+        );
+    }
+}
+
+// 所有接口出错时都返回ApiError, 统一补充为默认响应, 不在每个handler上重复标注
+struct ErrorResponses;
+
+impl Modify for ErrorResponses {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let schemas = &mut openapi
+            .components
+            .get_or_insert_with(Default::default)
+            .schemas;
+        schemas.insert(ApiErrorResp::name().into_owned(), ApiErrorResp::schema());
+        schemas.insert(FieldError::name().into_owned(), FieldError::schema());
+        let response: RefOr<Response> = ResponseBuilder::new()
+            .description("错误信息")
+            .content(
+                "application/json",
+                utoipa::openapi::ContentBuilder::new()
+                    .schema(Some(utoipa::openapi::Ref::from_schema_name(
+                        ApiErrorResp::name(),
+                    )))
+                    .build(),
+            )
+            .build()
+            .into();
+        for item in openapi.paths.paths.values_mut() {
+            for operation in [
+                &mut item.get,
+                &mut item.put,
+                &mut item.post,
+                &mut item.delete,
+                &mut item.patch,
+            ]
+            .into_iter()
+            .flatten()
+            {
+                operation
+                    .responses
+                    .responses
+                    .entry("default".to_owned())
+                    .or_insert_with(|| response.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collect_refs<'a>(value: &'a serde_json::Value, refs: &mut Vec<&'a str>) {
+        match value {
+            serde_json::Value::Object(map) => {
+                if let Some(serde_json::Value::String(r)) = map.get("$ref") {
+                    refs.push(r);
+                }
+                map.values().for_each(|v| collect_refs(v, refs));
+            }
+            serde_json::Value::Array(values) => values.iter().for_each(|v| collect_refs(v, refs)),
+            _ => {}
+        }
+    }
+
+    // 客户端生成器遇到未定义的schema引用会失败, 新增接口时须把引用的类型加入components
+    #[test]
+    fn every_schema_reference_is_defined() {
+        let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        let mut refs = Vec::new();
+        collect_refs(&spec, &mut refs);
+        let missing = refs
+            .into_iter()
+            .filter_map(|r| r.strip_prefix("#/components/schemas/"))
+            .filter(|name| spec["components"]["schemas"].get(name).is_none())
+            .collect::<std::collections::BTreeSet<&str>>();
+        assert!(missing.is_empty(), "undefined schemas: {:?}", missing);
+    }
+}
@@ -0,0 +1,135 @@
+use crate::{
+    core::{
+        repository::{Pagination, Repository},
+        service::Service,
+        translation::Translator,
+    },
+    handlers::{
+        common::{AuthUser, RequireRole, WalkerRole},
+        error::{api_error, ApiError},
+        review::review_resps,
+        validation::Valid,
+    },
+};
+use actix_web::{
+    web::{Data, Json, Path},
+    Error,
+};
+use little_walk_types::{
+    common::ListResp,
+    dto::{OwnerProfileResp, OwnerReputationResp, ReviewResp, TranslateReq, UpdateOwnerReq},
+};
+use nb_serde_query::actix_web::Query;
+use upload_service::core::{
+    repository::Repository as UploadRepository, service::Service as UploadService, store::Store,
+};
+
+#[utoipa::path(
+    get,
+    path = "/v1/owners/{id}",
+    tag = "owner",
+    params(("id" = String, Path)),
+    responses((status = 200, body = OwnerProfileResp))
+)]
+pub async fn owner_profile<R>(
+    service: Data<Service<R>>,
+    user_id: Path<(String,)>,
+) -> Result<Json<OwnerProfileResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .owner_profile(&user_id.0)
+        .await
+        .map(|profile| Json(profile.into()))
+        .map_err(api_error)
+}
+
+#[utoipa::path(
+    put,
+    path = "/v1/owners/me",
+    tag = "owner",
+    request_body = UpdateOwnerReq,
+    responses((status = 200, body = OwnerProfileResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn update_my_profile<R, UR, S>(
+    service: Data<Service<R>>,
+    upload_service: Data<UploadService<UR, S>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    Valid(update): Valid<UpdateOwnerReq>,
+) -> Result<Json<OwnerProfileResp>, Error>
+where
+    R: Repository,
+    UR: UploadRepository + Clone,
+    S: Store + Clone,
+{
+    if let Some(avatar_id) = &update.avatar_id {
+        if upload_service
+            .get_uploaded_file(avatar_id)
+            .await
+            .map_err(ApiError::internal)?
+            .is_none()
+        {
+            return Err(ApiError::bad_request(format!("upload {} not exists", avatar_id)).into());
+        }
+    }
+    service
+        .update_owner_profile(&uid, update.into())
+        .await
+        .map(|profile| Json(profile.into()))
+        .map_err(api_error)
+}
+
+// 遛狗人接单前查看狗狗主人的信誉
+#[utoipa::path(
+    get,
+    path = "/v1/owners/{id}/reputation",
+    tag = "owner",
+    params(("id" = String, Path)),
+    responses((status = 200, body = OwnerReputationResp), (status = 403)),
+    security(("bearer_auth" = []))
+)]
+pub async fn owner_reputation<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<WalkerRole>,
+    owner_id: Path<(String,)>,
+) -> Result<Json<OwnerReputationResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .owner_reputation(&owner_id.0)
+        .await
+        .map(|reputation| Json(reputation.into()))
+        .map_err(api_error)
+}
+
+// 遛狗人对狗狗主人的评价, 传translate时附带评价内容的译文
+#[utoipa::path(
+    get,
+    path = "/v1/owners/{id}/reviews",
+    tag = "owner",
+    params(("id" = String, Path), Pagination, TranslateReq),
+    responses((status = 200, body = ListResp<ReviewResp>))
+)]
+pub async fn owner_reviews<R, T>(
+    service: Data<Service<R>>,
+    translator: Data<T>,
+    owner_id: Path<(String,)>,
+    Query(pagination): Query<Pagination>,
+    Query(translate): Query<TranslateReq>,
+) -> Result<Json<ListResp<ReviewResp>>, Error>
+where
+    R: Repository,
+    T: Translator,
+{
+    let (reviews, total) = service
+        .owner_reviews(&owner_id.0, pagination)
+        .await
+        .map_err(api_error)?;
+    Ok(Json(ListResp::new(
+        review_resps(&service, translator.get_ref(), reviews, translate.translate).await?,
+        total,
+    )))
+}
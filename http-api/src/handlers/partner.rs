@@ -0,0 +1,190 @@
+use crate::{
+    core::{ids::WalkRequestId, repository::Repository, service::Service},
+    handlers::{
+        common::{
+            AuthPartner, AuthUser, CreateWalkRequestsScope, OwnerRole, ReadWalkRequestsScope,
+            ReadWalkerAvailabilityScope, RequireRole, RequireScope,
+        },
+        error::api_error,
+        validation::Valid,
+    },
+};
+use actix_web::{
+    web::{Data, Json, Path},
+    Error,
+};
+use little_walk_types::{
+    dto::{
+        CreatePartnerWalkRequestReq, PartnerConsentResp, PartnerUsageReq, PartnerUsageResp,
+        PartnerWalkRequestResp, WalkerAvailabilityReq, WalkerAvailabilityResp,
+    },
+    partner::{CreatePartnerWalkRequestResp, RevokeConsentResp},
+};
+use nb_serde_query::actix_web::Query;
+
+// 合作方代已授权的狗狗主人创建遛狗请求
+#[utoipa::path(
+    post,
+    path = "/v1/partner/walk_requests",
+    operation_id = "partner_create_walk_request",
+    tag = "partner",
+    request_body = CreatePartnerWalkRequestReq,
+    responses((status = 200, body = CreatePartnerWalkRequestResp)),
+    security(("api_key" = []))
+)]
+pub async fn create_walk_request<R>(
+    service: Data<Service<R>>,
+    AuthPartner { partner }: AuthPartner,
+    _: RequireScope<CreateWalkRequestsScope>,
+    Valid(req): Valid<CreatePartnerWalkRequestReq>,
+) -> Result<Json<CreatePartnerWalkRequestResp>, Error>
+where
+    R: Repository,
+{
+    let (dog_ids, create) = req.into_create();
+    let id = service
+        .create_partner_walk_request(&partner.id, dog_ids, create)
+        .await
+        .map_err(api_error)?;
+    Ok(Json(CreatePartnerWalkRequestResp { id }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/partner/walk_requests/{id}",
+    tag = "partner",
+    params(("id" = String, Path)),
+    responses((status = 200, body = PartnerWalkRequestResp)),
+    security(("api_key" = []))
+)]
+pub async fn walk_request<R>(
+    service: Data<Service<R>>,
+    AuthPartner { partner }: AuthPartner,
+    _: RequireScope<ReadWalkRequestsScope>,
+    id: Path<(WalkRequestId,)>,
+) -> Result<Json<PartnerWalkRequestResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .partner_walk_request(&partner.id, &id.0)
+        .await
+        .map(|request| Json(request.into()))
+        .map_err(api_error)
+}
+
+// 合作方查询自己的调用量
+#[utoipa::path(
+    get,
+    path = "/v1/partner/usage",
+    tag = "partner",
+    params(PartnerUsageReq),
+    responses((status = 200, body = Vec<PartnerUsageResp>)),
+    security(("api_key" = []))
+)]
+pub async fn usage<R>(
+    service: Data<Service<R>>,
+    AuthPartner { partner }: AuthPartner,
+    Query(req): Query<PartnerUsageReq>,
+) -> Result<Json<Vec<PartnerUsageResp>>, Error>
+where
+    R: Repository,
+{
+    service
+        .partner_usage(&partner.id, req.from, req.to)
+        .await
+        .map(|usage| Json(usage.into_iter().map(PartnerUsageResp::from).collect()))
+        .map_err(api_error)
+}
+
+// 区域和时间窗口内可用遛狗人的汇总数据, 供合作方排班参考
+#[utoipa::path(
+    get,
+    path = "/v1/partner/walker_availability",
+    tag = "partner",
+    params(WalkerAvailabilityReq),
+    responses((status = 200, body = WalkerAvailabilityResp)),
+    security(("api_key" = []))
+)]
+pub async fn walker_availability<R>(
+    service: Data<Service<R>>,
+    _: RequireScope<ReadWalkerAvailabilityScope>,
+    Query(req): Query<WalkerAvailabilityReq>,
+) -> Result<Json<WalkerAvailabilityResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .walker_availability(req.into())
+        .await
+        .map(|availability| Json(availability.into()))
+        .map_err(api_error)
+}
+
+#[utoipa::path(
+    put,
+    path = "/v1/partner_consents/{partner_id}",
+    tag = "partner",
+    params(("partner_id" = String, Path)),
+    responses((status = 200, body = PartnerConsentResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn grant_consent<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<OwnerRole>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    partner_id: Path<(String,)>,
+) -> Result<Json<PartnerConsentResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .grant_partner_consent(&uid, &partner_id.0)
+        .await
+        .map(|consent| Json(consent.into()))
+        .map_err(api_error)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/partner_consents/{partner_id}",
+    tag = "partner",
+    params(("partner_id" = String, Path)),
+    responses((status = 200, body = RevokeConsentResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn revoke_consent<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    partner_id: Path<(String,)>,
+) -> Result<Json<RevokeConsentResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .revoke_partner_consent(&uid, &partner_id.0)
+        .await
+        .map_err(api_error)?;
+    Ok(Json(RevokeConsentResp { success: true }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/partner_consents",
+    tag = "partner",
+    responses((status = 200, body = Vec<PartnerConsentResp>)),
+    security(("bearer_auth" = []))
+)]
+pub async fn my_consents<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+) -> Result<Json<Vec<PartnerConsentResp>>, Error>
+where
+    R: Repository,
+{
+    service
+        .partner_consents(&uid)
+        .await
+        .map(|consents| Json(consents.into_iter().map(PartnerConsentResp::from).collect()))
+        .map_err(api_error)
+}
@@ -0,0 +1,87 @@
+use crate::{
+    core::{payout::PayoutProvider, repository::Repository, service::Service},
+    handlers::{
+        common::{AuthUser, ChangePayoutAccountAction, RequireActionToken},
+        error::{api_error, ApiError},
+    },
+};
+use actix_web::{
+    web::{Data, Json},
+    Error,
+};
+use little_walk_types::{
+    dto::{LinkPayoutAccountReq, PayoutAccountResp},
+    payout::UnlinkPayoutAccountResp,
+};
+
+#[utoipa::path(
+    get,
+    path = "/v1/walkers/me/payout_account",
+    tag = "payout",
+    responses((status = 200, body = PayoutAccountResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn my_payout_account<R, P>(
+    service: Data<Service<R>>,
+    provider: Data<P>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+) -> Result<Json<PayoutAccountResp>, Error>
+where
+    R: Repository,
+    P: PayoutProvider,
+{
+    service
+        .payout_account(provider.as_ref(), &uid)
+        .await
+        .map_err(api_error)?
+        .map(|account| Json(account.into()))
+        .ok_or_else(|| ApiError::not_found("payout account not exists").into())
+}
+
+#[utoipa::path(
+    put,
+    path = "/v1/walkers/me/payout_account",
+    tag = "payout",
+    request_body = LinkPayoutAccountReq,
+    responses((status = 200, body = PayoutAccountResp)),
+    security(("bearer_auth" = [], "action_token" = []))
+)]
+pub async fn link_payout_account<R, P>(
+    service: Data<Service<R>>,
+    provider: Data<P>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    _: RequireActionToken<ChangePayoutAccountAction>,
+    Json(req): Json<LinkPayoutAccountReq>,
+) -> Result<Json<PayoutAccountResp>, Error>
+where
+    R: Repository,
+    P: PayoutProvider,
+{
+    service
+        .link_payout_account(provider.as_ref(), &uid, req.into())
+        .await
+        .map(|account| Json(account.into()))
+        .map_err(api_error)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/walkers/me/payout_account",
+    tag = "payout",
+    responses((status = 200, body = UnlinkPayoutAccountResp)),
+    security(("bearer_auth" = [], "action_token" = []))
+)]
+pub async fn unlink_payout_account<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    _: RequireActionToken<ChangePayoutAccountAction>,
+) -> Result<Json<UnlinkPayoutAccountResp>, Error>
+where
+    R: Repository,
+{
+    let success = service
+        .unlink_payout_account(&uid)
+        .await
+        .map_err(api_error)?;
+    Ok(Json(UnlinkPayoutAccountResp { success }))
+}
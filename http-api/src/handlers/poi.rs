@@ -0,0 +1,81 @@
+use crate::{
+    core::{repository::Repository, service::Service},
+    handlers::{
+        common::{AdminRole, RequireRole},
+        error::api_error,
+        validation::Valid,
+    },
+};
+use actix_web::{
+    web::{Data, Json, Path},
+    Error,
+};
+use little_walk_types::{
+    dto::{CreatePoiReq, PoiResp, PoisReq},
+    poi::{CreatePoiResp, DeletePoiResp},
+};
+use nb_serde_query::actix_web::Query;
+
+// 维护兴趣点目录, 遛狗报告据此判断途经的狗狗公园等地点
+#[utoipa::path(
+    post,
+    path = "/v1/admin/pois",
+    tag = "poi",
+    request_body = CreatePoiReq,
+    responses((status = 200, body = CreatePoiResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn create_poi<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<AdminRole>,
+    Valid(req): Valid<CreatePoiReq>,
+) -> Result<Json<CreatePoiResp>, Error>
+where
+    R: Repository,
+{
+    let id = service.create_poi(req.into()).await.map_err(api_error)?;
+    Ok(Json(CreatePoiResp { id }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/pois",
+    tag = "poi",
+    params(PoisReq),
+    responses((status = 200, body = Vec<PoiResp>)),
+    security(("bearer_auth" = []))
+)]
+pub async fn pois<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<AdminRole>,
+    Query(req): Query<PoisReq>,
+) -> Result<Json<Vec<PoiResp>>, Error>
+where
+    R: Repository,
+{
+    service
+        .pois(req.into())
+        .await
+        .map(|pois| Json(pois.into_iter().map(PoiResp::from).collect()))
+        .map_err(api_error)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/admin/pois/{id}",
+    tag = "poi",
+    params(("id" = String, Path)),
+    responses((status = 200, body = DeletePoiResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn delete_poi<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<AdminRole>,
+    id: Path<(String,)>,
+) -> Result<Json<DeletePoiResp>, Error>
+where
+    R: Repository,
+{
+    service.delete_poi(&id.0).await.map_err(api_error)?;
+    Ok(Json(DeletePoiResp { success: true }))
+}
@@ -0,0 +1,39 @@
+use crate::{
+    core::{repository::Repository, service::Service},
+    handlers::error::api_error,
+};
+use actix_web::{http::header::CACHE_CONTROL, web::Data, Error, HttpResponse};
+use little_walk_types::{dto::NeighborhoodStatsResp, public::NeighborhoodsReq};
+use nb_serde_query::actix_web::Query;
+
+// 数据本身已在服务端缓存一小时, CDN和浏览器也可以缓存
+const PUBLIC_CACHE_CONTROL: &str = "public, max-age=3600";
+
+// 营销网站城市页面的区域数据, 不需要登录
+#[utoipa::path(
+    get,
+    path = "/v1/public/neighborhoods",
+    tag = "public",
+    params(NeighborhoodsReq),
+    responses((status = 200, body = Vec<NeighborhoodStatsResp>))
+)]
+pub async fn neighborhoods<R>(
+    service: Data<Service<R>>,
+    Query(req): Query<NeighborhoodsReq>,
+) -> Result<HttpResponse, Error>
+where
+    R: Repository,
+{
+    let stats = service
+        .public_neighborhood_stats(req.latitude, req.longitude, req.radius)
+        .await
+        .map_err(api_error)?;
+    Ok(HttpResponse::Ok()
+        .insert_header((CACHE_CONTROL, PUBLIC_CACHE_CONTROL))
+        .json(
+            stats
+                .into_iter()
+                .map(NeighborhoodStatsResp::from)
+                .collect::<Vec<_>>(),
+        ))
+}
@@ -0,0 +1,182 @@
+use crate::{
+    core::{
+        ids::WalkRequestId,
+        repository::{Pagination, Repository},
+        service::Service,
+    },
+    handlers::{
+        common::{AdminRole, AuthUser, RequireRole},
+        error::{api_error, ApiError},
+    },
+};
+use actix_web::{
+    web::{Data, Json, Path},
+    Error,
+};
+use little_walk_types::{
+    common::ListResp,
+    dto::RefundRequestResp,
+    refund::{
+        ApproveRefundReq, RefundRequestsReq, RejectRefundReq, RequestRefundReq, RequestRefundResp,
+    },
+};
+use nb_serde_query::actix_web::Query;
+use upload_service::core::{
+    repository::Repository as UploadRepository, service::Service as UploadService, store::Store,
+};
+
+// 只能对已完成的遛狗申请退款, 证据图片须是申请人自己上传的
+#[utoipa::path(
+    post,
+    path = "/v1/walk_requests/{id}/refund_requests",
+    tag = "refund",
+    params(("id" = String, Path)),
+    request_body = RequestRefundReq,
+    responses((status = 200, body = RequestRefundResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn request_refund<R, UR, S>(
+    service: Data<Service<R>>,
+    upload_service: Data<UploadService<UR, S>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    request_id: Path<(WalkRequestId,)>,
+    Json(req): Json<RequestRefundReq>,
+) -> Result<Json<RequestRefundResp>, Error>
+where
+    R: Repository,
+    UR: UploadRepository + Clone,
+    S: Store + Clone,
+{
+    if req.reason.trim().is_empty() {
+        return Err(ApiError::bad_request("reason is required").into());
+    }
+    for id in &req.evidence_ids {
+        if upload_service
+            .get_uploaded_file(id)
+            .await
+            .map_err(ApiError::internal)?
+            .filter(|file| file.owner_id == uid)
+            .is_none()
+        {
+            return Err(ApiError::bad_request(format!("upload {} not exists", id)).into());
+        }
+    }
+    let id = service
+        .request_refund(
+            &request_id.0,
+            &uid,
+            req.amount,
+            req.reason,
+            req.evidence_ids,
+        )
+        .await
+        .map_err(api_error)?;
+    Ok(Json(RequestRefundResp { id }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/walk_requests/{id}/refund_requests",
+    tag = "refund",
+    params(("id" = String, Path)),
+    responses((status = 200, body = Vec<RefundRequestResp>)),
+    security(("bearer_auth" = []))
+)]
+pub async fn walk_request_refunds<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    request_id: Path<(WalkRequestId,)>,
+) -> Result<Json<Vec<RefundRequestResp>>, Error>
+where
+    R: Repository,
+{
+    service
+        .walk_request_refunds(&request_id.0, &uid)
+        .await
+        .map(|refunds| Json(refunds.into_iter().map(RefundRequestResp::from).collect()))
+        .map_err(api_error)
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/refund_requests",
+    tag = "refund",
+    params(RefundRequestsReq),
+    responses((status = 200, body = ListResp<RefundRequestResp>)),
+    security(("bearer_auth" = []))
+)]
+pub async fn refund_requests<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<AdminRole>,
+    Query(req): Query<RefundRequestsReq>,
+) -> Result<Json<ListResp<RefundRequestResp>>, Error>
+where
+    R: Repository,
+{
+    let (refunds, total) = service
+        .refund_requests(
+            req.status,
+            Pagination {
+                limit: req.limit,
+                skip: req.skip,
+            },
+        )
+        .await
+        .map_err(api_error)?;
+    Ok(Json(ListResp::new(
+        refunds.into_iter().map(RefundRequestResp::from).collect(),
+        total,
+    )))
+}
+
+#[utoipa::path(
+    put,
+    path = "/v1/admin/refund_requests/{id}/approval",
+    tag = "refund",
+    params(("id" = String, Path)),
+    request_body = ApproveRefundReq,
+    responses((status = 200, body = RefundRequestResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn approve_refund<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<AdminRole>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    refund_id: Path<(String,)>,
+    Json(req): Json<ApproveRefundReq>,
+) -> Result<Json<RefundRequestResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .review_refund(&refund_id.0, &uid, true, req.amount, req.note)
+        .await
+        .map(|refund| Json(refund.into()))
+        .map_err(api_error)
+}
+
+#[utoipa::path(
+    put,
+    path = "/v1/admin/refund_requests/{id}/rejection",
+    tag = "refund",
+    params(("id" = String, Path)),
+    request_body = RejectRefundReq,
+    responses((status = 200, body = RefundRequestResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn reject_refund<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<AdminRole>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    refund_id: Path<(String,)>,
+    Json(req): Json<RejectRefundReq>,
+) -> Result<Json<RefundRequestResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .review_refund(&refund_id.0, &uid, false, None, req.note)
+        .await
+        .map(|refund| Json(refund.into()))
+        .map_err(api_error)
+}
@@ -0,0 +1,100 @@
+use crate::{
+    core::{
+        repository::{Pagination, Repository},
+        service::Service,
+    },
+    handlers::{
+        common::{AdminRole, AuthUser, RequireRole},
+        error::api_error,
+    },
+};
+use actix_web::{
+    web::{Data, Json, Path},
+    Error,
+};
+use little_walk_types::{
+    common::ListResp,
+    dto::{CreateReportReq, ReportResp},
+    report::{CreateReportResp, ReportsReq, ReviewReportReq, ReviewReportResp},
+};
+use nb_serde_query::actix_web::Query;
+
+#[utoipa::path(
+    post,
+    path = "/v1/reports",
+    tag = "report",
+    request_body = CreateReportReq,
+    responses((status = 200, body = CreateReportResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn create_report<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    Json(report): Json<CreateReportReq>,
+) -> Result<Json<CreateReportResp>, Error>
+where
+    R: Repository,
+{
+    let id = service
+        .report_user(report.into_create(uid.into()))
+        .await
+        .map_err(api_error)?;
+    Ok(Json(CreateReportResp { id }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/reports",
+    tag = "report",
+    params(ReportsReq),
+    responses((status = 200, body = ListResp<ReportResp>)),
+    security(("bearer_auth" = []))
+)]
+pub async fn reports<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<AdminRole>,
+    Query(req): Query<ReportsReq>,
+) -> Result<Json<ListResp<ReportResp>>, Error>
+where
+    R: Repository,
+{
+    let (reports, total) = service
+        .reports(
+            req.status,
+            Pagination {
+                limit: req.limit,
+                skip: req.skip,
+            },
+        )
+        .await
+        .map_err(api_error)?;
+    Ok(Json(ListResp::new(
+        reports.into_iter().map(ReportResp::from).collect(),
+        total,
+    )))
+}
+
+#[utoipa::path(
+    put,
+    path = "/v1/admin/reports/{id}",
+    tag = "report",
+    params(("id" = String, Path)),
+    request_body = ReviewReportReq,
+    responses((status = 200, body = ReviewReportResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn review_report<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<AdminRole>,
+    id: Path<(String,)>,
+    Json(req): Json<ReviewReportReq>,
+) -> Result<Json<ReviewReportResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .review_report(&id.0, req.status, req.resolution_note)
+        .await
+        .map_err(api_error)?;
+    Ok(Json(ReviewReportResp { success: true }))
+}
@@ -0,0 +1,134 @@
+use crate::{
+    core::{
+        entities::Review,
+        repository::{Pagination, Repository},
+        service::Service,
+        translation::{Language, Translator},
+    },
+    handlers::{common::AuthUser, error::api_error},
+};
+use actix_web::{
+    web::{Data, Json, Path},
+    Error,
+};
+use little_walk_types::{
+    common::ListResp,
+    dto::{ReviewResp, TranslateReq},
+    review::{CreateReviewReq, CreateReviewResp, HelpfulVoteResp},
+};
+use nb_serde_query::actix_web::Query;
+
+// 狗狗主人评价遛狗人, 接单的遛狗人评价狗狗主人, 每方各一次
+#[utoipa::path(
+    post,
+    path = "/v1/walk_requests/{id}/reviews",
+    tag = "review",
+    params(("id" = String, Path)),
+    request_body = CreateReviewReq,
+    responses((status = 200, body = CreateReviewResp), (status = 403)),
+    security(("bearer_auth" = []))
+)]
+pub async fn create_review<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    request_id: Path<(String,)>,
+    Json(req): Json<CreateReviewReq>,
+) -> Result<Json<CreateReviewResp>, Error>
+where
+    R: Repository,
+{
+    let id = service
+        .review_walk(&request_id.0, &uid, req.rating, req.content, req.photo_ids)
+        .await
+        .map_err(api_error)?;
+    Ok(Json(CreateReviewResp { id }))
+}
+
+#[utoipa::path(
+    put,
+    path = "/v1/reviews/{id}/helpful",
+    tag = "review",
+    params(("id" = String, Path)),
+    responses((status = 200, body = HelpfulVoteResp), (status = 403), (status = 404)),
+    security(("bearer_auth" = []))
+)]
+pub async fn vote_helpful<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    review_id: Path<(String,)>,
+) -> Result<Json<HelpfulVoteResp>, Error>
+where
+    R: Repository,
+{
+    let counted = service
+        .vote_review_helpful(&review_id.0, &uid)
+        .await
+        .map_err(api_error)?;
+    Ok(Json(HelpfulVoteResp { counted }))
+}
+
+// 按有用票数排序, 传translate时附带评价内容的译文
+#[utoipa::path(
+    get,
+    path = "/v1/walkers/{id}/reviews",
+    tag = "review",
+    params(("id" = String, Path), Pagination, TranslateReq),
+    responses((status = 200, body = ListResp<ReviewResp>))
+)]
+pub async fn walker_reviews<R, T>(
+    service: Data<Service<R>>,
+    translator: Data<T>,
+    walker_id: Path<(String,)>,
+    Query(pagination): Query<Pagination>,
+    Query(translate): Query<TranslateReq>,
+) -> Result<Json<ListResp<ReviewResp>>, Error>
+where
+    R: Repository,
+    T: Translator,
+{
+    let (reviews, total) = service
+        .walker_reviews(&walker_id.0, pagination)
+        .await
+        .map_err(api_error)?;
+    Ok(Json(ListResp::new(
+        review_resps(&service, translator.get_ref(), reviews, translate.translate).await?,
+        total,
+    )))
+}
+
+pub(crate) async fn review_resps<R, T>(
+    service: &Service<R>,
+    translator: &T,
+    reviews: Vec<Review>,
+    translate: Option<Language>,
+) -> Result<Vec<ReviewResp>, Error>
+where
+    R: Repository,
+    T: Translator,
+{
+    let Some(language) = translate else {
+        return Ok(reviews.into_iter().map(ReviewResp::from).collect());
+    };
+    let mut translated = service
+        .translate(
+            translator,
+            reviews
+                .iter()
+                .filter(|review| !review.content.is_empty())
+                .map(|review| (review.id.clone(), review.content.clone()))
+                .collect(),
+            language,
+        )
+        .await
+        .map_err(api_error)?;
+    Ok(reviews
+        .into_iter()
+        .map(|review| {
+            let translated_content = translated.remove(&review.id);
+            ReviewResp {
+                translated_content,
+                ..review.into()
+            }
+        })
+        .collect())
+}
@@ -0,0 +1,99 @@
+use crate::{
+    core::{
+        repository::{Repository, SessionQuery},
+        service::Service,
+    },
+    handlers::{
+        common::{AuthUser, ClientDevice},
+        error::api_error,
+    },
+};
+use actix_web::{
+    web::{Data, Json, Path},
+    Error,
+};
+use little_walk_types::{dto::SessionResp, session::RevokeSessionResp};
+
+// 请求头中的设备ID与会话一致时标记为当前设备
+#[utoipa::path(
+    get,
+    path = "/v1/sessions",
+    tag = "session",
+    responses((status = 200, body = Vec<SessionResp>)),
+    security(("bearer_auth" = []))
+)]
+pub async fn sessions<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    ClientDevice(device): ClientDevice,
+) -> Result<Json<Vec<SessionResp>>, Error>
+where
+    R: Repository,
+{
+    service
+        .sessions(&uid)
+        .await
+        .map(|sessions| {
+            Json(
+                sessions
+                    .into_iter()
+                    .map(|s| SessionResp::new(s, device.device_id.as_deref()))
+                    .collect(),
+            )
+        })
+        .map_err(api_error)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/sessions/{id}",
+    tag = "session",
+    params(("id" = String, Path)),
+    responses((status = 200, body = RevokeSessionResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn revoke_session<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    id: Path<(String,)>,
+) -> Result<Json<RevokeSessionResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .revoke_sessions(SessionQuery {
+            user_id: uid.into(),
+            id: Some(id.into_inner().0),
+            ..Default::default()
+        })
+        .await
+        .map_err(api_error)?;
+    Ok(Json(RevokeSessionResp { success: true }))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/devices/{device_id}",
+    tag = "session",
+    params(("device_id" = String, Path)),
+    responses((status = 200, body = RevokeSessionResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn revoke_device<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    device_id: Path<(String,)>,
+) -> Result<Json<RevokeSessionResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .revoke_sessions(SessionQuery {
+            user_id: uid.into(),
+            device_id: Some(device_id.into_inner().0),
+            ..Default::default()
+        })
+        .await
+        .map_err(api_error)?;
+    Ok(Json(RevokeSessionResp { success: true }))
+}
@@ -0,0 +1,65 @@
+use crate::{
+    core::{repository::Repository, service::Service},
+    handlers::error::ApiError,
+};
+use actix_web::{web::Data, Error, HttpRequest, HttpResponse};
+use hmac::{digest::CtOutput, Hmac, Mac};
+use little_walk_types::synthetic::{SyntheticStepResp, SyntheticWalkResp};
+use sha2::Sha256;
+
+// 合成监控配置, token为空时不开放接口. 测试用户需预先创建, 遛狗人需通过认证
+pub struct SyntheticMonitor {
+    pub token: String,
+    pub owner_id: String,
+    pub walker_id: String,
+    pub dog_id: String,
+}
+
+// 比较前先做HMAC, 避免逐字节比较泄露token
+fn digest(key: &[u8], value: &[u8]) -> Option<CtOutput<Hmac<Sha256>>> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).ok()?;
+    mac.update(value);
+    Some(mac.finalize())
+}
+
+// 供外部拨测调用, 请求头X-Synthetic-Token需与配置一致. 任一步骤失败时返回503
+#[utoipa::path(
+    post,
+    path = "/internal/synthetic/walk",
+    tag = "synthetic",
+    responses((status = 200, body = SyntheticWalkResp), (status = 503, body = SyntheticWalkResp))
+)]
+pub async fn walk<R>(
+    service: Data<Service<R>>,
+    monitor: Data<SyntheticMonitor>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error>
+where
+    R: Repository,
+{
+    if monitor.token.is_empty() {
+        return Err(ApiError::not_found("synthetic monitoring not configured").into());
+    }
+    let token = req
+        .headers()
+        .get("X-Synthetic-Token")
+        .and_then(|v| v.to_str().ok())
+        .ok_or(ApiError::unauthorized("missing synthetic token"))?;
+    let expected = digest(monitor.token.as_bytes(), monitor.token.as_bytes());
+    if expected.is_none() || expected != digest(monitor.token.as_bytes(), token.as_bytes()) {
+        return Err(ApiError::unauthorized("invalid synthetic token").into());
+    }
+    let steps = service
+        .run_synthetic_walk(&monitor.owner_id, &monitor.walker_id, &monitor.dog_id)
+        .await;
+    let resp = SyntheticWalkResp {
+        success: steps.iter().all(|s| s.error.is_none()),
+        total_millis: steps.iter().map(|s| s.millis).sum(),
+        steps: steps.into_iter().map(SyntheticStepResp::from).collect(),
+    };
+    Ok(if resp.success {
+        HttpResponse::Ok().json(resp)
+    } else {
+        HttpResponse::ServiceUnavailable().json(resp)
+    })
+}
@@ -0,0 +1,316 @@
+use crate::{
+    core::{
+        entities::TicketStatus,
+        repository::{Pagination, Repository},
+        service::Service,
+    },
+    handlers::{
+        common::{AdminRole, AuthUser, RequireRole},
+        error::{api_error, ApiError},
+    },
+};
+use actix_web::{
+    web::{Data, Json, Path},
+    Error,
+};
+use little_walk_types::{
+    common::ListResp,
+    dto::{CreateTicketReq, ReplyTicketReq, TicketResp},
+    ticket::{CreateTicketResp, MyTicketsReq, TicketsReq, UpdateTicketResp, UpdateTicketStatusReq},
+};
+use nb_serde_query::actix_web::Query;
+use upload_service::core::{
+    repository::Repository as UploadRepository, service::Service as UploadService, store::Store,
+};
+
+// 附件需要先通过上传接口上传, 这里只校验上传记录存在
+async fn ensure_attachments_exist<UR, S>(
+    upload_service: &UploadService<UR, S>,
+    attachment_ids: &[String],
+) -> Result<(), Error>
+where
+    UR: UploadRepository + Clone,
+    S: Store + Clone,
+{
+    for id in attachment_ids {
+        if upload_service
+            .get_uploaded_file(id)
+            .await
+            .map_err(ApiError::internal)?
+            .is_none()
+        {
+            return Err(ApiError::bad_request(format!("upload {} not exists", id)).into());
+        }
+    }
+    Ok(())
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/support/tickets",
+    tag = "ticket",
+    request_body = CreateTicketReq,
+    responses((status = 200, body = CreateTicketResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn create_ticket<R, UR, S>(
+    service: Data<Service<R>>,
+    upload_service: Data<UploadService<UR, S>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    Json(req): Json<CreateTicketReq>,
+) -> Result<Json<CreateTicketResp>, Error>
+where
+    R: Repository,
+    UR: UploadRepository + Clone,
+    S: Store + Clone,
+{
+    ensure_attachments_exist(&upload_service, &req.attachment_ids).await?;
+    let id = service
+        .create_ticket(req.into_create(uid.into()))
+        .await
+        .map_err(api_error)?;
+    Ok(Json(CreateTicketResp { id }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/support/tickets",
+    tag = "ticket",
+    params(MyTicketsReq),
+    responses((status = 200, body = ListResp<TicketResp>)),
+    security(("bearer_auth" = []))
+)]
+pub async fn my_tickets<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    Query(req): Query<MyTicketsReq>,
+) -> Result<Json<ListResp<TicketResp>>, Error>
+where
+    R: Repository,
+{
+    let (tickets, total) = service
+        .my_tickets(
+            &uid,
+            req.status,
+            Pagination {
+                limit: req.limit,
+                skip: req.skip,
+            },
+        )
+        .await
+        .map_err(api_error)?;
+    Ok(Json(ListResp::new(
+        tickets.into_iter().map(TicketResp::from).collect(),
+        total,
+    )))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/support/tickets/{id}",
+    tag = "ticket",
+    params(("id" = String, Path)),
+    responses((status = 200, body = TicketResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn my_ticket<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    id: Path<(String,)>,
+) -> Result<Json<TicketResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .ticket(&id.0, Some(&uid))
+        .await
+        .map(|ticket| Json(ticket.into()))
+        .map_err(api_error)
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/support/tickets/{id}/messages",
+    tag = "ticket",
+    params(("id" = String, Path)),
+    request_body = ReplyTicketReq,
+    responses((status = 200, body = UpdateTicketResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn reply<R, UR, S>(
+    service: Data<Service<R>>,
+    upload_service: Data<UploadService<UR, S>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    id: Path<(String,)>,
+    Json(req): Json<ReplyTicketReq>,
+) -> Result<Json<UpdateTicketResp>, Error>
+where
+    R: Repository,
+    UR: UploadRepository + Clone,
+    S: Store + Clone,
+{
+    ensure_attachments_exist(&upload_service, &req.attachment_ids).await?;
+    service
+        .reply_ticket(&id.0, &uid, req.content, req.attachment_ids)
+        .await
+        .map_err(api_error)?;
+    Ok(Json(UpdateTicketResp { success: true }))
+}
+
+#[utoipa::path(
+    put,
+    path = "/v1/support/tickets/{id}/closure",
+    tag = "ticket",
+    params(("id" = String, Path)),
+    responses((status = 200, body = UpdateTicketResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn close<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    id: Path<(String,)>,
+) -> Result<Json<UpdateTicketResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .transition_ticket(&id.0, Some(&uid), TicketStatus::Closed)
+        .await
+        .map_err(api_error)?;
+    Ok(Json(UpdateTicketResp { success: true }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/support/tickets",
+    tag = "ticket",
+    params(TicketsReq),
+    responses((status = 200, body = ListResp<TicketResp>)),
+    security(("bearer_auth" = []))
+)]
+pub async fn tickets<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<AdminRole>,
+    Query(req): Query<TicketsReq>,
+) -> Result<Json<ListResp<TicketResp>>, Error>
+where
+    R: Repository,
+{
+    let (tickets, total) = service
+        .tickets(
+            req.status,
+            req.assignee_id,
+            Pagination {
+                limit: req.limit,
+                skip: req.skip,
+            },
+        )
+        .await
+        .map_err(api_error)?;
+    Ok(Json(ListResp::new(
+        tickets.into_iter().map(TicketResp::from).collect(),
+        total,
+    )))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/support/tickets/{id}",
+    tag = "ticket",
+    params(("id" = String, Path)),
+    responses((status = 200, body = TicketResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn ticket<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<AdminRole>,
+    id: Path<(String,)>,
+) -> Result<Json<TicketResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .ticket(&id.0, None)
+        .await
+        .map(|ticket| Json(ticket.into()))
+        .map_err(api_error)
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/admin/support/tickets/{id}/messages",
+    tag = "ticket",
+    params(("id" = String, Path)),
+    request_body = ReplyTicketReq,
+    responses((status = 200, body = UpdateTicketResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn staff_reply<R, UR, S>(
+    service: Data<Service<R>>,
+    upload_service: Data<UploadService<UR, S>>,
+    _: RequireRole<AdminRole>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    id: Path<(String,)>,
+    Json(req): Json<ReplyTicketReq>,
+) -> Result<Json<UpdateTicketResp>, Error>
+where
+    R: Repository,
+    UR: UploadRepository + Clone,
+    S: Store + Clone,
+{
+    ensure_attachments_exist(&upload_service, &req.attachment_ids).await?;
+    service
+        .reply_ticket_as_staff(&id.0, &uid, req.content, req.attachment_ids)
+        .await
+        .map_err(api_error)?;
+    Ok(Json(UpdateTicketResp { success: true }))
+}
+
+#[utoipa::path(
+    put,
+    path = "/v1/admin/support/tickets/{id}/assignee/{user_id}",
+    tag = "ticket",
+    params(("id" = String, Path), ("user_id" = String, Path)),
+    responses((status = 200, body = UpdateTicketResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn assign<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<AdminRole>,
+    path: Path<(String, String)>,
+) -> Result<Json<UpdateTicketResp>, Error>
+where
+    R: Repository,
+{
+    let (id, assignee_id) = path.into_inner();
+    service
+        .assign_ticket(&id, &assignee_id)
+        .await
+        .map_err(api_error)?;
+    Ok(Json(UpdateTicketResp { success: true }))
+}
+
+#[utoipa::path(
+    put,
+    path = "/v1/admin/support/tickets/{id}/status",
+    tag = "ticket",
+    params(("id" = String, Path)),
+    request_body = UpdateTicketStatusReq,
+    responses((status = 200, body = UpdateTicketResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn update_status<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<AdminRole>,
+    id: Path<(String,)>,
+    Json(req): Json<UpdateTicketStatusReq>,
+) -> Result<Json<UpdateTicketResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .transition_ticket(&id.0, None, req.status)
+        .await
+        .map_err(api_error)?;
+    Ok(Json(UpdateTicketResp { success: true }))
+}
@@ -0,0 +1,410 @@
+use actix_multipart::{Field, Multipart};
+use little_walk_types::upload::{
+    DeleteResult, DownloadReq, UploadPurpose, UploadReq, UploadResult,
+};
+use std::io::Cursor;
+
+use actix_web::{
+    error::Result,
+    http::{
+        header::{ACCEPT_RANGES, CONTENT_RANGE, RANGE},
+        StatusCode,
+    },
+    web::{self, Bytes, BytesMut, Data, Json, Path},
+    HttpRequest, HttpResponse,
+};
+use futures::{
+    future::{self, try_join_all},
+    stream, StreamExt, TryStreamExt,
+};
+use nb_serde_query::actix_web::Query;
+use upload_service::core::{repository::Repository, service::Service, store::Store};
+
+use crate::core::{
+    entities::Role, image_metadata::strip_metadata, repository::Repository as DogRepository,
+    service::Service as DogService, thumbnail::render_thumbnails,
+};
+
+use super::common::AuthUser;
+use super::error::{api_error, ApiError};
+
+// 上传限制, 在写入存储之前检查. 文件类型按内容的magic bytes识别, 不信任文件名和Content-Type
+#[derive(Debug, Clone)]
+pub struct UploadLimits {
+    pub max_files: usize, // 单次请求最多上传的文件数
+    pub max_bytes: usize,
+    pub allowed_mime_types: Vec<String>,
+    pub max_image_width: u32,
+    pub max_image_height: u32,
+    pub keep_metadata_purposes: Vec<UploadPurpose>, // 这些用途上传的图片保留原始元数据, 其余用途去除EXIF并按方向旋转
+}
+
+// 上传校验失败使用接口统一的错误响应, code区分具体原因
+fn reject(status: StatusCode, code: &'static str, message: String) -> actix_web::Error {
+    ApiError::new(status, code, message).into()
+}
+
+fn validate(limits: &UploadLimits, content: &[u8]) -> Result<()> {
+    let mime_type = infer::get(content).map(|t| t.mime_type()).ok_or_else(|| {
+        reject(
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "unknown_media_type",
+            "unable to detect file type".to_owned(),
+        )
+    })?;
+    if !limits.allowed_mime_types.iter().any(|t| t == mime_type) {
+        return Err(reject(
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "unsupported_media_type",
+            format!("{} is not allowed", mime_type),
+        ));
+    }
+    // 只读取图片头部获取尺寸, 避免解码超大图片
+    if mime_type.starts_with("image/") {
+        let (width, height) = image::io::Reader::new(Cursor::new(content))
+            .with_guessed_format()
+            .map_err(ApiError::internal)?
+            .into_dimensions()
+            .map_err(|e| {
+                reject(
+                    StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                    "invalid_image",
+                    e.to_string(),
+                )
+            })?;
+        if width > limits.max_image_width || height > limits.max_image_height {
+            return Err(reject(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "image_dimensions_too_large",
+                format!(
+                    "image is {}x{}, at most {}x{} allowed",
+                    width, height, limits.max_image_width, limits.max_image_height
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+async fn store<R, S>(
+    service: &Service<R, S>,
+    content: Bytes,
+    filename: &str,
+    uploader: &str,
+) -> Result<String>
+where
+    R: Repository + Clone,
+    S: Store + Clone,
+{
+    let size = content.len();
+    let id = service
+        .upload(
+            stream::iter(vec![Ok(content)]),
+            filename,
+            uploader,
+            Some(size),
+        )
+        .await
+        .map_err(ApiError::internal)?;
+    Ok(id)
+}
+
+// 保存原图, 图片同时生成并保存各规格缩略图
+pub(crate) async fn store_with_thumbnails<R, S, DR>(
+    service: &Service<R, S>,
+    dog_service: &DogService<DR>,
+    content: Bytes,
+    filename: &str,
+    uploader: &str,
+) -> Result<String>
+where
+    R: Repository + Clone,
+    S: Store + Clone,
+    DR: DogRepository,
+{
+    let thumbnails = {
+        let content = content.clone();
+        web::block(move || render_thumbnails(&content))
+            .await
+            .map_err(ApiError::internal)?
+            .map_err(ApiError::bad_request)?
+    };
+    let id = store(service, content, filename, uploader).await?;
+    for (size, thumbnail) in thumbnails {
+        let variant_id = store(
+            service,
+            Bytes::from(thumbnail),
+            &format!("{}.{}.jpg", filename, size),
+            uploader,
+        )
+        .await?;
+        dog_service
+            .add_upload_variant(&id, size, &variant_id)
+            .await
+            .map_err(api_error)?;
+    }
+    Ok(id)
+}
+
+// 读取表单中的一个文件并通过限制检查, 该用途不保留元数据时去除元数据, 返回文件名和内容
+pub(crate) async fn read_file(
+    limits: &UploadLimits,
+    purpose: UploadPurpose,
+    mut field: Field,
+) -> Result<(String, Bytes)> {
+    let filename = field
+        .content_disposition()
+        .get_filename()
+        .ok_or(ApiError::bad_request("failed to get filename"))?
+        .to_owned();
+    let mut content = BytesMut::new();
+    while let Some(chunk) = field.try_next().await.map_err(ApiError::internal)? {
+        if content.len() + chunk.len() > limits.max_bytes {
+            return Err(reject(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "file_too_large",
+                format!("{} exceeds {} bytes", filename, limits.max_bytes),
+            ));
+        }
+        content.extend_from_slice(&chunk);
+    }
+    let content = content.freeze();
+    validate(limits, &content)?;
+    if limits.keep_metadata_purposes.contains(&purpose) {
+        return Ok((filename, content));
+    }
+    let content = web::block(move || strip_metadata(content))
+        .await
+        .map_err(ApiError::internal)?
+        .map_err(ApiError::bad_request)?;
+    Ok((filename, content))
+}
+
+// 一次请求可以上传多个文件. 所有文件先读入内存并通过限制检查, 再并发保存, 返回的id与文件顺序一致
+#[utoipa::path(
+    post,
+    path = "/v1/uploads",
+    tag = "upload",
+    params(UploadReq),
+    request_body(content_type = "multipart/form-data", description = "上传的文件"),
+    responses((status = 200, body = UploadResult)),
+    security(("bearer_auth" = []))
+)]
+pub(crate) async fn upload<R, S, DR>(
+    service: Data<Service<R, S>>,
+    dog_service: Data<DogService<DR>>,
+    limits: Data<UploadLimits>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    Query(req): Query<UploadReq>,
+    mut form: Multipart,
+) -> Result<Json<UploadResult>>
+where
+    R: Repository + Clone,
+    S: Store + Clone,
+    DR: DogRepository,
+{
+    let purpose = req.purpose.unwrap_or(UploadPurpose::Other);
+    let mut files = Vec::new();
+    while let Some(field) = form.next().await {
+        let field = field.map_err(ApiError::internal)?;
+        if files.len() >= limits.max_files {
+            return Err(reject(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "too_many_files",
+                format!("at most {} files allowed", limits.max_files),
+            ));
+        }
+        files.push(read_file(&limits, purpose, field).await?);
+    }
+    let ids = try_join_all(files.into_iter().map(|(filename, content)| {
+        let (service, dog_service, uid) = (&service, &dog_service, &uid);
+        async move { store_with_thumbnails(service, dog_service, content, &filename, uid).await }
+    }))
+    .await?;
+    Ok(Json(UploadResult { ids }))
+}
+
+// 解析单个字节区间的Range头, 返回闭区间[start, end].
+// 没有Range头或格式不支持(如多个区间)时返回None, 按完整文件响应; 区间超出文件大小时返回Some(Err)
+fn parse_range(header: &str, size: u64) -> Option<Result<(u64, u64), ()>> {
+    let spec = header.trim().strip_prefix("bytes=")?;
+    if spec.contains(',') {
+        return None;
+    }
+    let (start, end) = spec.split_once('-')?;
+    let (start, end) = match (start.trim(), end.trim()) {
+        ("", suffix) => {
+            let suffix = suffix.parse::<u64>().ok()?;
+            if suffix == 0 {
+                return Some(Err(()));
+            }
+            (size.saturating_sub(suffix), size.checked_sub(1)?)
+        }
+        (start, "") => (start.parse::<u64>().ok()?, size.checked_sub(1)?),
+        (start, end) => {
+            let (start, end) = (start.parse::<u64>().ok()?, end.parse::<u64>().ok()?);
+            if end < start {
+                return None;
+            }
+            (start, end.min(size.checked_sub(1)?))
+        }
+    };
+    if start >= size {
+        return Some(Err(()));
+    }
+    Some(Ok((start, end)))
+}
+
+// 指定size时返回对应规格的缩略图, 没有该规格时返回原图.
+// 支持单个区间的Range请求, 便于客户端断点续传和拖动播放视频, 文件内容始终以流的形式返回.
+// 遛狗人提交的身份证件只对上传者和管理员可见, 其他人按文件不存在处理
+#[utoipa::path(
+    get,
+    path = "/v1/uploads/{id}",
+    tag = "upload",
+    params(("id" = String, Path), DownloadReq),
+    responses((status = 200, description = "文件内容", content_type = "application/octet-stream"), (status = 206, description = "Range请求的部分内容", content_type = "application/octet-stream")),
+    security((), ("bearer_auth" = []))
+)]
+pub(crate) async fn get<R, S, DR>(
+    service: Data<Service<R, S>>,
+    dog_service: Data<DogService<DR>>,
+    user: Option<AuthUser>,
+    id: Path<(String,)>,
+    Query(req): Query<DownloadReq>,
+    http_req: HttpRequest,
+) -> Result<HttpResponse>
+where
+    R: Repository + Clone,
+    S: Store + Clone,
+    DR: DogRepository,
+{
+    let id = match req.size {
+        Some(size) => dog_service
+            .upload_variant(&id.0, size)
+            .await
+            .map_err(api_error)?
+            .unwrap_or(id.into_inner().0),
+        None => id.into_inner().0,
+    };
+    let file_info = service
+        .get_uploaded_file(&id)
+        .await
+        .map_err(ApiError::internal)?
+        .ok_or(ApiError::not_found("file not found"))?;
+    if dog_service
+        .is_verification_document(&id)
+        .await
+        .map_err(api_error)?
+        && !user.map_or(false, |user| {
+            user.user_id == file_info.owner_id || user.roles.contains(&Role::Admin)
+        })
+    {
+        return Err(ApiError::not_found("file not found").into());
+    }
+    let size = file_info.size.max(0) as u64;
+    let range = http_req
+        .headers()
+        .get(RANGE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| parse_range(v, size));
+    let (start, end) = match range {
+        Some(Err(())) => {
+            return Ok(HttpResponse::build(StatusCode::RANGE_NOT_SATISFIABLE)
+                .insert_header((CONTENT_RANGE, format!("bytes */{}", size)))
+                .finish())
+        }
+        Some(Ok(range)) => range,
+        None => {
+            let stream = service.download(&id).await.map_err(ApiError::internal)?;
+            return Ok(HttpResponse::build(StatusCode::OK)
+                .insert_header(("Content-Type", file_info.mime_type))
+                .insert_header((ACCEPT_RANGES, "bytes"))
+                .no_chunking(size)
+                .streaming(stream));
+        }
+    };
+    // 存储只能从头读取, 跳过区间之前的数据并在区间结束后停止读取
+    let stream = service
+        .download(&id)
+        .await
+        .map_err(ApiError::internal)?
+        .scan((start, end - start + 1), |(skip, remaining), chunk| {
+            let chunk = match chunk {
+                Err(e) => Some(Err(e)),
+                Ok(_) if *remaining == 0 => None,
+                Ok(chunk) => {
+                    let offset = (*skip).min(chunk.len() as u64);
+                    *skip -= offset;
+                    let len = (*remaining).min(chunk.len() as u64 - offset);
+                    *remaining -= len;
+                    Some(Ok(chunk.slice(offset as usize..(offset + len) as usize)))
+                }
+            };
+            future::ready(chunk)
+        })
+        .try_filter(|chunk| future::ready(!chunk.is_empty()));
+    Ok(HttpResponse::build(StatusCode::PARTIAL_CONTENT)
+        .insert_header(("Content-Type", file_info.mime_type))
+        .insert_header((ACCEPT_RANGES, "bytes"))
+        .insert_header((CONTENT_RANGE, format!("bytes {}-{}/{}", start, end, size)))
+        .no_chunking(end - start + 1)
+        .streaming(stream))
+}
+
+// 只有上传者可以删除, 缩略图随原图一起删除
+#[utoipa::path(
+    delete,
+    path = "/v1/uploads/{id}",
+    tag = "upload",
+    params(("id" = String, Path)),
+    responses((status = 200, body = DeleteResult)),
+    security(("bearer_auth" = []))
+)]
+pub(crate) async fn delete<R, S, DR>(
+    service: Data<Service<R, S>>,
+    dog_service: Data<DogService<DR>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    id: Path<(String,)>,
+) -> Result<Json<DeleteResult>>
+where
+    R: Repository + Clone,
+    S: Store + Clone,
+    DR: DogRepository,
+{
+    service
+        .get_uploaded_file(&id.0)
+        .await
+        .map_err(ApiError::internal)?
+        .filter(|file| file.owner_id == uid)
+        .ok_or(ApiError::not_found("file not found"))?;
+    dog_service.delete_upload(&id.0).await.map_err(api_error)?;
+    Ok(Json(DeleteResult { success: true }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[actix_web::test]
+    async fn rejections_use_the_api_error_shape() {
+        let limits = UploadLimits {
+            max_files: 1,
+            max_bytes: 1024,
+            allowed_mime_types: vec!["image/png".to_owned()],
+            max_image_width: 100,
+            max_image_height: 100,
+            keep_metadata_purposes: vec![],
+        };
+        let response = validate(&limits, b"plain text")
+            .unwrap_err()
+            .error_response();
+        assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        let body = actix_web::body::to_bytes(response.into_body())
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["code"], "unknown_media_type");
+        assert_eq!(body["message"], "unable to detect file type");
+    }
+}
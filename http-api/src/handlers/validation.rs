@@ -0,0 +1,28 @@
+use actix_web::{dev::Payload, web::Json, Error, FromRequest, HttpRequest};
+use futures::future::LocalBoxFuture;
+use little_walk_types::validation::{FieldErrors, Validate};
+use serde::de::DeserializeOwned;
+
+use crate::handlers::error::ApiError;
+
+// 解析JSON请求体并校验, 校验失败返回422和字段错误列表
+pub struct Valid<T>(pub T);
+
+impl<T> FromRequest for Valid<T>
+where
+    T: DeserializeOwned + Validate + 'static,
+{
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let body = Json::<T>::from_request(req, payload);
+        Box::pin(async move {
+            let body = body.await?.into_inner();
+            let mut errors = FieldErrors::default();
+            body.validate(&mut errors);
+            errors.into_result().map_err(ApiError::unprocessable)?;
+            Ok(Valid(body))
+        })
+    }
+}
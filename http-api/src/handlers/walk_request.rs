@@ -0,0 +1,428 @@
+use crate::{
+    core::{
+        export::Renderer,
+        ids::{UserId, WalkRequestId},
+        repository::{Cursor, Page, Pagination, Repository, WALK_REQUEST_FIELDS},
+        service::Service,
+    },
+    handlers::{
+        common::{select_fields, AuthUser, OwnerRole, RequireRole},
+        error::{api_error, ApiError},
+        validation::Valid,
+    },
+};
+use actix_web::{
+    http::header::{CONTENT_DISPOSITION, CONTENT_TYPE},
+    web::{Data, Json, Path},
+    Error, HttpResponse,
+};
+use chrono::{DateTime, TimeZone, Utc};
+use little_walk_types::{
+    common::PageResp,
+    dto::{
+        CancellationPenaltyResp, CreateWalkRequestReq, FieldsReq, PageReq, RoutePreferenceReq,
+        RoutePreferenceResp, WalkRequestChangesResp, WalkRequestResp, WalkRouteReportResp,
+    },
+    walk_request::{
+        AssignAccepterResp, ChangesReq, CreateWalkRequestResp, DeleteWalkRequestResp, ExportReq,
+        NearbyReq, ResignAcceptanceResp, UpdateRoutePreferenceResp, ValidateWalkRequestResp,
+    },
+};
+use nb_serde_query::actix_web::Query;
+
+#[utoipa::path(
+    post,
+    path = "/v1/walk_requests",
+    tag = "walk_request",
+    request_body = CreateWalkRequestReq,
+    responses((status = 200, body = CreateWalkRequestResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn create_walk_request<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    Valid(request): Valid<CreateWalkRequestReq>,
+) -> Result<Json<CreateWalkRequestResp>, Error>
+where
+    R: Repository,
+{
+    let id = service
+        .create_walk_request(request.into_create(uid.into()))
+        .await
+        .map_err(api_error)?;
+    Ok(Json(CreateWalkRequestResp { id }))
+}
+
+// 试运行创建: 执行全部检查(包括同一狗狗的时段冲突)并计算费用但不保存, 校验失败时返回与创建相同的错误
+#[utoipa::path(
+    post,
+    path = "/v1/walk_requests/validate",
+    tag = "walk_request",
+    request_body = CreateWalkRequestReq,
+    responses((status = 200, body = ValidateWalkRequestResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn validate_walk_request<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    Valid(request): Valid<CreateWalkRequestReq>,
+) -> Result<Json<ValidateWalkRequestResp>, Error>
+where
+    R: Repository,
+{
+    let (request, quote) = service
+        .validate_walk_request(request.into_create(uid.into()))
+        .await
+        .map_err(api_error)?;
+    Ok(Json(ValidateWalkRequestResp {
+        priority_walker_count: request.priority_walkers.len(),
+        priority_until: request.priority_until,
+        route_preference: request.route_preference.map(RoutePreferenceResp::from),
+        price: quote.price,
+        platform_fee: quote.platform_fee,
+        walker_earning: quote.walker_earning,
+    }))
+}
+
+fn parse_checkpoint(since: &str) -> Option<Cursor> {
+    let time = match since.parse::<i64>() {
+        Ok(millis) => Utc.timestamp_millis_opt(millis).single(),
+        Err(_) => DateTime::parse_from_rfc3339(since)
+            .ok()
+            .map(|t| t.with_timezone(&Utc)),
+    };
+    match time {
+        Some(time) => Some(Cursor::after_time(time)),
+        None => since.parse::<Cursor>().ok(),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/walk_requests/changes",
+    tag = "walk_request",
+    params(ChangesReq),
+    responses((status = 200, body = WalkRequestChangesResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn changes<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    Query(req): Query<ChangesReq>,
+) -> Result<Json<WalkRequestChangesResp>, Error>
+where
+    R: Repository,
+{
+    let since = parse_checkpoint(&req.since).ok_or(ApiError::bad_request("invalid since"))?;
+    service
+        .walk_request_changes(&uid, since)
+        .await
+        .map(|changes| Json(changes.into()))
+        .map_err(api_error)
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/walk_requests/mine",
+    tag = "walk_request",
+    params(PageReq, FieldsReq),
+    responses((status = 200, body = PageResp<WalkRequestResp>)),
+    security(("bearer_auth" = []))
+)]
+pub async fn my_walk_requests<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    Query(req): Query<PageReq>,
+    Query(fields): Query<FieldsReq>,
+) -> Result<Json<PageResp<serde_json::Value>>, Error>
+where
+    R: Repository,
+{
+    let page = Page::try_from(&req).map_err(api_error)?;
+    let fields = fields.parse(WALK_REQUEST_FIELDS).map_err(api_error)?;
+    let (requests, total, next) = service
+        .my_walk_requests(&uid, page, fields.clone())
+        .await
+        .map_err(api_error)?;
+    let mut unread = if fields.as_ref().map_or(true, |f| f.contains("unread_count")) {
+        service
+            .unread_chat_counts(&uid, &requests)
+            .await
+            .map_err(api_error)?
+    } else {
+        Default::default()
+    };
+    let list = select_fields(
+        requests
+            .into_iter()
+            .map(|request| {
+                // 未被接单的请求没有聊天, 不返回未读数
+                let unread_count = request
+                    .accepted_by
+                    .is_some()
+                    .then(|| unread.remove(&request.id.to_string()).unwrap_or(0));
+                WalkRequestResp {
+                    unread_count,
+                    ..request.into()
+                }
+            })
+            .collect(),
+        fields.as_ref(),
+    )?;
+    Ok(Json(
+        PageResp::new(list, total, &req.pagination()).with_next_cursor(next),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/walk_requests/nearby",
+    tag = "walk_request",
+    params(NearbyReq),
+    responses((status = 200, body = PageResp<WalkRequestResp>)),
+    security(("bearer_auth" = []))
+)]
+pub async fn nearby_walk_requests<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    Query(req): Query<NearbyReq>,
+) -> Result<Json<PageResp<WalkRequestResp>>, Error>
+where
+    R: Repository,
+{
+    let pagination = Pagination {
+        limit: req.limit,
+        skip: req.skip,
+    };
+    let (requests, total) = service
+        .nearby_walk_requests(
+            &uid,
+            req.latitude,
+            req.longitude,
+            req.radius,
+            pagination.clone(),
+        )
+        .await
+        .map_err(api_error)?;
+    Ok(Json(PageResp::new(
+        requests.into_iter().map(WalkRequestResp::from).collect(),
+        total,
+        &pagination,
+    )))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/walk_requests/{id}",
+    tag = "walk_request",
+    params(("id" = String, Path)),
+    responses((status = 200, body = DeleteWalkRequestResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn delete_walk_request<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<OwnerRole>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    request_id: Path<(WalkRequestId,)>,
+) -> Result<Json<DeleteWalkRequestResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .delete_walk_request(&uid, &request_id.0)
+        .await
+        .map_err(api_error)?;
+    Ok(Json(DeleteWalkRequestResp { success: true }))
+}
+
+#[utoipa::path(
+    put,
+    path = "/v1/walk_requests/{id}/accepter/{user_id}",
+    tag = "walk_request",
+    params(("id" = String, Path), ("user_id" = String, Path)),
+    responses((status = 200, body = AssignAccepterResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn assign_accepter<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<OwnerRole>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    path: Path<(WalkRequestId, UserId)>,
+) -> Result<Json<AssignAccepterResp>, Error>
+where
+    R: Repository,
+{
+    let (request_id, accepter_id) = path.into_inner();
+    service
+        .assign_accepter(&uid, &request_id, &accepter_id)
+        .await
+        .map_err(api_error)?;
+    Ok(Json(AssignAccepterResp { success: true }))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/walk_requests/{id}/acceptance",
+    tag = "walk_request",
+    params(("id" = String, Path)),
+    responses((status = 200, body = ResignAcceptanceResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn resign_acceptance<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    request_id: Path<(WalkRequestId,)>,
+) -> Result<Json<ResignAcceptanceResp>, Error>
+where
+    R: Repository,
+{
+    let penalty = service
+        .resign_acceptance(&request_id.0, &uid)
+        .await
+        .map_err(api_error)?;
+    Ok(Json(ResignAcceptanceResp {
+        penalty: penalty.map(CancellationPenaltyResp::from),
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/walk_requests/{id}/cancellation_penalties",
+    tag = "walk_request",
+    params(("id" = String, Path)),
+    responses((status = 200, body = Vec<CancellationPenaltyResp>)),
+    security(("bearer_auth" = []))
+)]
+pub async fn cancellation_penalties<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    request_id: Path<(WalkRequestId,)>,
+) -> Result<Json<Vec<CancellationPenaltyResp>>, Error>
+where
+    R: Repository,
+{
+    service
+        .cancellation_penalties(&request_id.0, &uid)
+        .await
+        .map(|penalties| {
+            Json(
+                penalties
+                    .into_iter()
+                    .map(CancellationPenaltyResp::from)
+                    .collect(),
+            )
+        })
+        .map_err(api_error)
+}
+
+// 期望路线和禁行区域都为空时清除路线偏好
+#[utoipa::path(
+    put,
+    path = "/v1/walk_requests/{id}/route_preference",
+    tag = "walk_request",
+    params(("id" = String, Path)),
+    request_body = RoutePreferenceReq,
+    responses((status = 200, body = UpdateRoutePreferenceResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn update_route_preference<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<OwnerRole>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    request_id: Path<(WalkRequestId,)>,
+    Json(req): Json<RoutePreferenceReq>,
+) -> Result<Json<UpdateRoutePreferenceResp>, Error>
+where
+    R: Repository,
+{
+    let expected_version = req.version;
+    service
+        .update_route_preference(&request_id.0, &uid, req.into(), expected_version)
+        .await
+        .map_err(api_error)?;
+    Ok(Json(UpdateRoutePreferenceResp { success: true }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/walk_requests/{id}/route_preference",
+    tag = "walk_request",
+    params(("id" = String, Path)),
+    responses((status = 200, body = Option<RoutePreferenceResp>)),
+    security(("bearer_auth" = []))
+)]
+pub async fn route_preference<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    request_id: Path<(WalkRequestId,)>,
+) -> Result<Json<Option<RoutePreferenceResp>>, Error>
+where
+    R: Repository,
+{
+    service
+        .route_preference(&request_id.0, &uid)
+        .await
+        .map(|route_preference| Json(route_preference.map(RoutePreferenceResp::from)))
+        .map_err(api_error)
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/walk_requests/{id}/route_report",
+    tag = "walk_request",
+    params(("id" = String, Path)),
+    responses((status = 200, body = WalkRouteReportResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn route_report<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    request_id: Path<(WalkRequestId,)>,
+) -> Result<Json<WalkRouteReportResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .walk_route_report(&request_id.0, &uid)
+        .await
+        .map(|report| Json(report.into()))
+        .map_err(api_error)
+}
+
+// 导出可打印的遛狗请求摘要, 供寄养合作方使用
+#[utoipa::path(
+    get,
+    path = "/v1/walk_requests/{id}/export",
+    tag = "walk_request",
+    params(("id" = String, Path), ExportReq),
+    responses((status = 200, description = "遛狗请求摘要文件", content_type = "application/pdf")),
+    security(("bearer_auth" = []))
+)]
+pub async fn export<R, P>(
+    service: Data<Service<R>>,
+    renderer: Data<P>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    request_id: Path<(WalkRequestId,)>,
+    Query(req): Query<ExportReq>,
+) -> Result<HttpResponse, Error>
+where
+    R: Repository,
+    P: Renderer + 'static,
+{
+    let export = service
+        .export_walk_request(&request_id.0, &uid)
+        .await
+        .map_err(api_error)?;
+    let body = renderer.render(req.format, &export).map_err(api_error)?;
+    Ok(HttpResponse::Ok()
+        .insert_header((CONTENT_TYPE, req.format.content_type()))
+        .insert_header((
+            CONTENT_DISPOSITION,
+            format!(
+                "attachment; filename=\"walk-request-{}.{}\"",
+                request_id.0,
+                req.format.extension()
+            ),
+        ))
+        .body(body))
+}
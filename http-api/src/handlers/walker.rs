@@ -0,0 +1,272 @@
+use crate::{
+    core::{
+        repository::{Pagination, Repository, WalkerSearch},
+        service::Service,
+    },
+    handlers::{
+        common::{AdminRole, AuthUser, RequireRole},
+        error::{api_error, ApiError},
+        validation::Valid,
+    },
+};
+use actix_web::{
+    web::{Data, Json, Path},
+    Error,
+};
+use little_walk_types::{
+    common::ListResp,
+    dto::{AchievementResp, RankedWalkerResp, WalkerResp, WalkerStatsResp},
+    walker::{
+        RejectVerificationReq, ReviewVerificationResp, SearchReq, SubmitVerificationReq,
+        UpdateLocationReq, UpdateLocationResp, VerificationsReq,
+    },
+};
+use nb_serde_query::actix_web::Query;
+use upload_service::core::{
+    repository::Repository as UploadRepository, service::Service as UploadService, store::Store,
+};
+
+#[utoipa::path(
+    get,
+    path = "/v1/walkers/me/verification",
+    tag = "walker",
+    responses((status = 200, body = WalkerResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn my_verification<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+) -> Result<Json<WalkerResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .walker(&uid)
+        .await
+        .map(|walker| Json(walker.into()))
+        .map_err(api_error)
+}
+
+#[utoipa::path(
+    put,
+    path = "/v1/walkers/me/verification",
+    tag = "walker",
+    request_body = SubmitVerificationReq,
+    responses((status = 200, body = WalkerResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn submit_verification<R, UR, S>(
+    service: Data<Service<R>>,
+    upload_service: Data<UploadService<UR, S>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    Json(req): Json<SubmitVerificationReq>,
+) -> Result<Json<WalkerResp>, Error>
+where
+    R: Repository,
+    UR: UploadRepository + Clone,
+    S: Store + Clone,
+{
+    for id in &req.id_document_ids {
+        if upload_service
+            .get_uploaded_file(id)
+            .await
+            .map_err(ApiError::internal)?
+            .filter(|file| file.owner_id == uid)
+            .is_none()
+        {
+            return Err(ApiError::bad_request(format!("upload {} not exists", id)).into());
+        }
+    }
+    service
+        .submit_walker_verification(&uid, req.id_document_ids)
+        .await
+        .map(|walker| Json(walker.into()))
+        .map_err(api_error)
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/walkers/verifications",
+    tag = "walker",
+    params(VerificationsReq),
+    responses((status = 200, body = ListResp<WalkerResp>)),
+    security(("bearer_auth" = []))
+)]
+pub async fn verifications<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<AdminRole>,
+    Query(req): Query<VerificationsReq>,
+) -> Result<Json<ListResp<WalkerResp>>, Error>
+where
+    R: Repository,
+{
+    let (walkers, total) = service
+        .walker_verifications(
+            req.status,
+            Pagination {
+                limit: req.limit,
+                skip: req.skip,
+            },
+        )
+        .await
+        .map_err(api_error)?;
+    Ok(Json(ListResp::new(
+        walkers.into_iter().map(WalkerResp::from).collect(),
+        total,
+    )))
+}
+
+#[utoipa::path(
+    put,
+    path = "/v1/admin/walkers/{id}/verification/approval",
+    tag = "walker",
+    params(("id" = String, Path)),
+    responses((status = 200, body = ReviewVerificationResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn approve_verification<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<AdminRole>,
+    user_id: Path<(String,)>,
+) -> Result<Json<ReviewVerificationResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .approve_walker_verification(&user_id.0)
+        .await
+        .map_err(api_error)?;
+    Ok(Json(ReviewVerificationResp { success: true }))
+}
+
+#[utoipa::path(
+    put,
+    path = "/v1/admin/walkers/{id}/verification/rejection",
+    tag = "walker",
+    params(("id" = String, Path)),
+    request_body = RejectVerificationReq,
+    responses((status = 200, body = ReviewVerificationResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn reject_verification<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<AdminRole>,
+    user_id: Path<(String,)>,
+    Json(req): Json<RejectVerificationReq>,
+) -> Result<Json<ReviewVerificationResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .reject_walker_verification(&user_id.0, &req.reason)
+        .await
+        .map_err(api_error)?;
+    Ok(Json(ReviewVerificationResp { success: true }))
+}
+
+#[utoipa::path(
+    put,
+    path = "/v1/walkers/me/location",
+    tag = "walker",
+    request_body = UpdateLocationReq,
+    responses((status = 200, body = UpdateLocationResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn update_location<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    Valid(req): Valid<UpdateLocationReq>,
+) -> Result<Json<UpdateLocationResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .update_walker_location(&uid, req.longitude, req.latitude)
+        .await
+        .map_err(api_error)?;
+    Ok(Json(UpdateLocationResp { success: true }))
+}
+
+// 结果包含遛狗人实时位置, 需要登录并记录访问
+#[utoipa::path(
+    get,
+    path = "/v1/walkers/search",
+    tag = "walker",
+    params(SearchReq),
+    responses((status = 200, body = Vec<RankedWalkerResp>)),
+    security(("bearer_auth" = []))
+)]
+pub async fn search<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    Query(req): Query<SearchReq>,
+) -> Result<Json<Vec<RankedWalkerResp>>, Error>
+where
+    R: Repository,
+{
+    service
+        .search_walkers(
+            &uid,
+            WalkerSearch {
+                longitude: req.longitude,
+                latitude: req.latitude,
+                radius: req.radius,
+                pagination: Pagination {
+                    limit: req.limit,
+                    skip: req.skip,
+                },
+            },
+        )
+        .await
+        .map(|walkers| Json(walkers.into_iter().map(RankedWalkerResp::from).collect()))
+        .map_err(api_error)
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/walkers/{id}/stats",
+    tag = "walker",
+    params(("id" = String, Path)),
+    responses((status = 200, body = WalkerStatsResp))
+)]
+pub async fn stats<R>(
+    service: Data<Service<R>>,
+    walker_id: Path<(String,)>,
+) -> Result<Json<WalkerStatsResp>, Error>
+where
+    R: Repository,
+{
+    service
+        .walker_stats(&walker_id.0)
+        .await
+        .map(|stats| Json(stats.into()))
+        .map_err(api_error)
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/walkers/{id}/achievements",
+    tag = "walker",
+    params(("id" = String, Path)),
+    responses((status = 200, body = Vec<AchievementResp>))
+)]
+pub async fn achievements<R>(
+    service: Data<Service<R>>,
+    walker_id: Path<(String,)>,
+) -> Result<Json<Vec<AchievementResp>>, Error>
+where
+    R: Repository,
+{
+    service
+        .achievements(&walker_id.0)
+        .await
+        .map(|achievements| {
+            Json(
+                achievements
+                    .into_iter()
+                    .map(AchievementResp::from)
+                    .collect(),
+            )
+        })
+        .map_err(api_error)
+}
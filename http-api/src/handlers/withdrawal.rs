@@ -0,0 +1,268 @@
+use crate::{
+    core::{
+        repository::{Pagination, Repository},
+        service::Service,
+    },
+    handlers::{
+        common::{AdminRole, AuthUser, RequestWithdrawalAction, RequireActionToken, RequireRole},
+        error::{api_error, ApiError},
+    },
+};
+use actix_web::{
+    web::{Bytes, Data, Json},
+    Error, HttpRequest,
+};
+use hmac::{Hmac, Mac};
+use little_walk_types::{
+    common::ListResp,
+    dto::{LedgerEntryResp, WithdrawalResp},
+    withdrawal::{
+        BalanceResp, LedgerReq, PayoutWebhookReq, PayoutWebhookResp, RequestWithdrawalReq,
+        RequestWithdrawalResp, ReviewWithdrawalsReq, ReviewWithdrawalsResp, WithdrawalsReq,
+    },
+};
+use nb_serde_query::actix_web::Query;
+use sha2::Sha256;
+
+// 打款回调签名密钥, 未配置时不接受回调
+pub struct PayoutWebhookKey(pub Vec<Hmac<Sha256>>);
+
+#[utoipa::path(
+    get,
+    path = "/v1/walkers/me/balance",
+    tag = "withdrawal",
+    responses((status = 200, body = BalanceResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn my_balance<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+) -> Result<Json<BalanceResp>, Error>
+where
+    R: Repository,
+{
+    let balance = service.balance(&uid).await.map_err(api_error)?;
+    Ok(Json(BalanceResp { balance }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/walkers/me/ledger",
+    tag = "withdrawal",
+    params(LedgerReq),
+    responses((status = 200, body = ListResp<LedgerEntryResp>)),
+    security(("bearer_auth" = []))
+)]
+pub async fn my_ledger<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    Query(req): Query<LedgerReq>,
+) -> Result<Json<ListResp<LedgerEntryResp>>, Error>
+where
+    R: Repository,
+{
+    let (entries, total) = service
+        .ledger(
+            &uid,
+            Pagination {
+                limit: req.limit,
+                skip: req.skip,
+            },
+        )
+        .await
+        .map_err(api_error)?;
+    Ok(Json(ListResp::new(
+        entries.into_iter().map(LedgerEntryResp::from).collect(),
+        total,
+    )))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/walkers/me/withdrawals",
+    tag = "withdrawal",
+    request_body = RequestWithdrawalReq,
+    responses((status = 200, body = RequestWithdrawalResp)),
+    security(("bearer_auth" = [], "action_token" = []))
+)]
+pub async fn request_withdrawal<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    _: RequireActionToken<RequestWithdrawalAction>,
+    Json(req): Json<RequestWithdrawalReq>,
+) -> Result<Json<RequestWithdrawalResp>, Error>
+where
+    R: Repository,
+{
+    let id = service
+        .request_withdrawal(&uid, req.amount)
+        .await
+        .map_err(api_error)?;
+    Ok(Json(RequestWithdrawalResp { id }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/walkers/me/withdrawals",
+    tag = "withdrawal",
+    params(WithdrawalsReq),
+    responses((status = 200, body = ListResp<WithdrawalResp>)),
+    security(("bearer_auth" = []))
+)]
+pub async fn my_withdrawals<R>(
+    service: Data<Service<R>>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    Query(req): Query<WithdrawalsReq>,
+) -> Result<Json<ListResp<WithdrawalResp>>, Error>
+where
+    R: Repository,
+{
+    let (withdrawals, total) = service
+        .my_withdrawals(
+            &uid,
+            req.status,
+            Pagination {
+                limit: req.limit,
+                skip: req.skip,
+            },
+        )
+        .await
+        .map_err(api_error)?;
+    Ok(Json(ListResp::new(
+        withdrawals.into_iter().map(WithdrawalResp::from).collect(),
+        total,
+    )))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/withdrawals",
+    tag = "withdrawal",
+    params(WithdrawalsReq),
+    responses((status = 200, body = ListResp<WithdrawalResp>)),
+    security(("bearer_auth" = []))
+)]
+pub async fn withdrawals<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<AdminRole>,
+    Query(req): Query<WithdrawalsReq>,
+) -> Result<Json<ListResp<WithdrawalResp>>, Error>
+where
+    R: Repository,
+{
+    let (withdrawals, total) = service
+        .withdrawals(
+            req.status,
+            Pagination {
+                limit: req.limit,
+                skip: req.skip,
+            },
+        )
+        .await
+        .map_err(api_error)?;
+    Ok(Json(ListResp::new(
+        withdrawals.into_iter().map(WithdrawalResp::from).collect(),
+        total,
+    )))
+}
+
+#[utoipa::path(
+    put,
+    path = "/v1/admin/withdrawals/approval",
+    tag = "withdrawal",
+    request_body = ReviewWithdrawalsReq,
+    responses((status = 200, body = ReviewWithdrawalsResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn approve<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<AdminRole>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    Json(req): Json<ReviewWithdrawalsReq>,
+) -> Result<Json<ReviewWithdrawalsResp>, Error>
+where
+    R: Repository,
+{
+    let reviewed = service
+        .review_withdrawals(req.ids, &uid, true, None)
+        .await
+        .map_err(api_error)?;
+    Ok(Json(ReviewWithdrawalsResp { reviewed }))
+}
+
+#[utoipa::path(
+    put,
+    path = "/v1/admin/withdrawals/rejection",
+    tag = "withdrawal",
+    request_body = ReviewWithdrawalsReq,
+    responses((status = 200, body = ReviewWithdrawalsResp)),
+    security(("bearer_auth" = []))
+)]
+pub async fn reject<R>(
+    service: Data<Service<R>>,
+    _: RequireRole<AdminRole>,
+    AuthUser { user_id: uid, .. }: AuthUser,
+    Json(req): Json<ReviewWithdrawalsReq>,
+) -> Result<Json<ReviewWithdrawalsResp>, Error>
+where
+    R: Repository,
+{
+    let reviewed = service
+        .review_withdrawals(req.ids, &uid, false, req.reason)
+        .await
+        .map_err(api_error)?;
+    Ok(Json(ReviewWithdrawalsResp { reviewed }))
+}
+
+fn decode_hex(s: &str) -> Option<Vec<u8>> {
+    if s.len() % 2 != 0 {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+// 打款服务以X-Payout-Signature头携带请求体的HMAC-SHA256签名(hex)
+#[utoipa::path(
+    post,
+    path = "/webhooks/payouts",
+    tag = "withdrawal",
+    request_body(content = String, content_type = "application/json", description = "打款渠道签名的回调内容"),
+    responses((status = 200, body = PayoutWebhookResp))
+)]
+pub async fn payout_webhook<R>(
+    service: Data<Service<R>>,
+    key: Data<PayoutWebhookKey>,
+    req: HttpRequest,
+    body: Bytes,
+) -> Result<Json<PayoutWebhookResp>, Error>
+where
+    R: Repository,
+{
+    if key.0.is_empty() {
+        return Err(ApiError::not_found("payout webhook not configured").into());
+    }
+    let signature = req
+        .headers()
+        .get("X-Payout-Signature")
+        .and_then(|v| v.to_str().ok())
+        .and_then(decode_hex)
+        .ok_or(ApiError::unauthorized("missing payout signature"))?;
+    // 密钥轮换期间新旧密钥的签名都接受
+    let verified = key.0.iter().any(|mac| {
+        let mut mac = mac.clone();
+        mac.update(&body);
+        mac.verify_slice(&signature).is_ok()
+    });
+    if !verified {
+        return Err(ApiError::unauthorized("invalid payout signature").into());
+    }
+    let event = serde_json::from_slice::<PayoutWebhookReq>(&body).map_err(ApiError::bad_request)?;
+    service
+        .settle_withdrawal(&event.reference, event.status, event.failure_reason)
+        .await
+        .map_err(api_error)?;
+    Ok(Json(PayoutWebhookResp { success: true }))
+}
@@ -0,0 +1,32 @@
+pub mod remote;
+pub mod stub;
+
+use crate::core::{
+    error::Error,
+    inference::{BreedCandidate, InferenceProvider, PortraitAssessment},
+};
+
+use self::{remote::RemoteInferenceProvider, stub::StubInferenceProvider};
+
+// 根据配置在远程推理服务和桩实现之间选择
+#[derive(Debug, Clone)]
+pub enum InferenceProviders {
+    Remote(RemoteInferenceProvider),
+    Stub(StubInferenceProvider),
+}
+
+impl InferenceProvider for InferenceProviders {
+    async fn recognize_breed(&self, image: Vec<u8>) -> Result<Vec<BreedCandidate>, Error> {
+        match self {
+            InferenceProviders::Remote(provider) => provider.recognize_breed(image).await,
+            InferenceProviders::Stub(provider) => provider.recognize_breed(image).await,
+        }
+    }
+
+    async fn assess_portrait(&self, image: Vec<u8>) -> Result<PortraitAssessment, Error> {
+        match self {
+            InferenceProviders::Remote(provider) => provider.assess_portrait(image).await,
+            InferenceProviders::Stub(provider) => provider.assess_portrait(image).await,
+        }
+    }
+}
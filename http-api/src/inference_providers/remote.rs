@@ -0,0 +1,57 @@
+use crate::core::{
+    error::Error,
+    inference::{BreedCandidate, InferenceProvider, PortraitAssessment},
+};
+
+// 调用远程识别接口, 请求体均为原始图片. {api_url}/breeds返回[{label, confidence}],
+// {api_url}/portrait_quality返回{sharpness, brightness, dog_confidence}
+#[derive(Debug, Clone)]
+pub struct RemoteInferenceProvider {
+    client: reqwest::Client,
+    api_url: String,
+    api_key: String,
+}
+
+impl RemoteInferenceProvider {
+    pub fn new(api_url: &str, api_key: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            api_url: api_url.to_owned(),
+            api_key: api_key.to_owned(),
+        }
+    }
+
+    async fn post_image(&self, path: &str, image: Vec<u8>) -> reqwest::Result<reqwest::Response> {
+        self.client
+            .post(format!("{}/{}", self.api_url.trim_end_matches('/'), path))
+            .bearer_auth(&self.api_key)
+            .header("Content-Type", "application/octet-stream")
+            .body(image)
+            .send()
+            .await
+    }
+}
+
+impl InferenceProvider for RemoteInferenceProvider {
+    async fn recognize_breed(&self, image: Vec<u8>) -> Result<Vec<BreedCandidate>, Error> {
+        self.post_image("breeds", image)
+            .await
+            .map_err(|e| Error::new("failed to recognize breed").with_cause(e))?
+            .error_for_status()
+            .map_err(|e| Error::new("failed to recognize breed").with_cause(e))?
+            .json::<Vec<BreedCandidate>>()
+            .await
+            .map_err(|e| Error::new("failed to parse breed candidates").with_cause(e))
+    }
+
+    async fn assess_portrait(&self, image: Vec<u8>) -> Result<PortraitAssessment, Error> {
+        self.post_image("portrait_quality", image)
+            .await
+            .map_err(|e| Error::new("failed to assess portrait").with_cause(e))?
+            .error_for_status()
+            .map_err(|e| Error::new("failed to assess portrait").with_cause(e))?
+            .json::<PortraitAssessment>()
+            .await
+            .map_err(|e| Error::new("failed to parse portrait assessment").with_cause(e))
+    }
+}
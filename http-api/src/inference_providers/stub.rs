@@ -0,0 +1,22 @@
+use crate::core::{
+    error::Error,
+    inference::{BreedCandidate, InferenceProvider, PortraitAssessment},
+};
+
+// 未配置识别服务时使用, 不返回任何候选, 头像一律视为合格
+#[derive(Debug, Clone, Default)]
+pub struct StubInferenceProvider;
+
+impl InferenceProvider for StubInferenceProvider {
+    async fn recognize_breed(&self, _image: Vec<u8>) -> Result<Vec<BreedCandidate>, Error> {
+        Ok(Vec::new())
+    }
+
+    async fn assess_portrait(&self, _image: Vec<u8>) -> Result<PortraitAssessment, Error> {
+        Ok(PortraitAssessment {
+            sharpness: 1.0,
+            brightness: 1.0,
+            dog_confidence: 1.0,
+        })
+    }
+}
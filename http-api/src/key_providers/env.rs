@@ -0,0 +1,32 @@
+use crate::core::{
+    error::Error,
+    keys::{KeyProvider, KeyPurpose, KeyRing},
+};
+
+// 从环境变量配置中读取的密钥, 启动时解析
+#[derive(Debug, Clone)]
+pub struct EnvKeyProvider {
+    jwt: KeyRing,
+    field_encryption: KeyRing,
+    payout_webhook: KeyRing,
+}
+
+impl EnvKeyProvider {
+    pub fn new(jwt: KeyRing, field_encryption: KeyRing, payout_webhook: KeyRing) -> Self {
+        Self {
+            jwt,
+            field_encryption,
+            payout_webhook,
+        }
+    }
+}
+
+impl KeyProvider for EnvKeyProvider {
+    async fn key_ring(&self, purpose: KeyPurpose) -> Result<KeyRing, Error> {
+        Ok(match purpose {
+            KeyPurpose::Jwt => self.jwt.clone(),
+            KeyPurpose::FieldEncryption => self.field_encryption.clone(),
+            KeyPurpose::PayoutWebhook => self.payout_webhook.clone(),
+        })
+    }
+}
@@ -0,0 +1,34 @@
+use std::collections::HashMap;
+
+use crate::core::{
+    error::Error,
+    keys::{KeyProvider, KeyPurpose, KeyRing},
+};
+
+use super::{key_ring, KeyEntry};
+
+// JSON格式的密钥文件, 以用途为键, 如{"jwt": [{"id": "2", "secret": "base64"}, {"id": "1", "secret": "base64"}]}.
+// 每次读取时重新加载文件
+#[derive(Debug, Clone)]
+pub struct FileKeyProvider {
+    path: String,
+}
+
+impl FileKeyProvider {
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: path.to_owned(),
+        }
+    }
+}
+
+impl KeyProvider for FileKeyProvider {
+    async fn key_ring(&self, purpose: KeyPurpose) -> Result<KeyRing, Error> {
+        let content = tokio::fs::read(&self.path)
+            .await
+            .map_err(|e| Error::new("failed to read key file").with_cause(e))?;
+        let mut keys = serde_json::from_slice::<HashMap<String, Vec<KeyEntry>>>(&content)
+            .map_err(|e| Error::new("failed to parse key file").with_cause(e))?;
+        key_ring(keys.remove(&purpose.to_string()).unwrap_or_default())
+    }
+}
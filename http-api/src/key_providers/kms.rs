@@ -0,0 +1,49 @@
+use serde::Deserialize;
+
+use crate::core::{
+    error::Error,
+    keys::{KeyProvider, KeyPurpose, KeyRing},
+};
+
+use super::{key_ring, KeyEntry};
+
+#[derive(Debug, Deserialize)]
+struct KeysResp {
+    keys: Vec<KeyEntry>,
+}
+
+// 通过KMS获取密钥, GET {endpoint}/keys/{purpose}返回{keys: [{id, secret}]}, 当前密钥排在最前
+#[derive(Debug, Clone)]
+pub struct KmsKeyProvider {
+    client: reqwest::Client,
+    endpoint: String,
+    token: String,
+}
+
+impl KmsKeyProvider {
+    pub fn new(endpoint: &str, token: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            endpoint: endpoint.trim_end_matches('/').to_owned(),
+            token: token.to_owned(),
+        }
+    }
+}
+
+impl KeyProvider for KmsKeyProvider {
+    async fn key_ring(&self, purpose: KeyPurpose) -> Result<KeyRing, Error> {
+        let resp = self
+            .client
+            .get(format!("{}/keys/{}", self.endpoint, purpose))
+            .bearer_auth(&self.token)
+            .send()
+            .await
+            .map_err(|e| Error::new("failed to fetch keys").with_cause(e))?
+            .error_for_status()
+            .map_err(|e| Error::new("failed to fetch keys").with_cause(e))?
+            .json::<KeysResp>()
+            .await
+            .map_err(|e| Error::new("failed to parse keys").with_cause(e))?;
+        key_ring(resp.keys)
+    }
+}
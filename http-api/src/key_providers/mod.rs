@@ -0,0 +1,54 @@
+pub mod env;
+pub mod file;
+pub mod kms;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::Deserialize;
+
+use crate::core::{
+    error::Error,
+    keys::{Key, KeyProvider, KeyPurpose, KeyRing},
+};
+
+use self::{env::EnvKeyProvider, file::FileKeyProvider, kms::KmsKeyProvider};
+
+// 根据配置在环境变量、密钥文件和KMS之间选择
+#[derive(Debug, Clone)]
+pub enum KeyProviders {
+    Env(EnvKeyProvider),
+    File(FileKeyProvider),
+    Kms(KmsKeyProvider),
+}
+
+impl KeyProvider for KeyProviders {
+    async fn key_ring(&self, purpose: KeyPurpose) -> Result<KeyRing, Error> {
+        match self {
+            KeyProviders::Env(provider) => provider.key_ring(purpose).await,
+            KeyProviders::File(provider) => provider.key_ring(purpose).await,
+            KeyProviders::Kms(provider) => provider.key_ring(purpose).await,
+        }
+    }
+}
+
+// 密钥文件和KMS返回的密钥, secret为base64编码, 当前密钥排在最前
+#[derive(Debug, Deserialize)]
+struct KeyEntry {
+    id: String,
+    secret: String,
+}
+
+fn key_ring(entries: Vec<KeyEntry>) -> Result<KeyRing, Error> {
+    KeyRing::new(
+        entries
+            .into_iter()
+            .map(|entry| {
+                Ok(Key {
+                    secret: STANDARD
+                        .decode(&entry.secret)
+                        .map_err(|e| Error::new("invalid key").with_cause(e))?,
+                    id: entry.id,
+                })
+            })
+            .collect::<Result<Vec<Key>, Error>>()?,
+    )
+}
@@ -0,0 +1,295 @@
+use std::str::FromStr;
+
+use actix_web::{dev::ServiceRequest, middleware::Logger};
+
+const REDACTED: &str = "[REDACTED]";
+
+// 字段名按下划线、连字符和驼峰拆分成单词, 任一单词命中即视为敏感字段
+const SENSITIVE_WORDS: [&str; 25] = [
+    "phone",
+    "mobile",
+    "token",
+    "password",
+    "secret",
+    "otp",
+    "code",
+    "signature",
+    "authorization",
+    "cookie",
+    "latitude",
+    "longitude",
+    "lat",
+    "lng",
+    "lon",
+    "location",
+    "note",
+    "notes",
+    "description",
+    "message",
+    "content",
+    "comment",
+    "email",
+    "address",
+    "contact",
+];
+
+// 路径中直接携带凭证的路由, *所在的段在两种模式下都会脱敏
+const SECRET_PATHS: [&[&str]; 4] = [
+    &["v1", "tokens", "*", "verification"],
+    &["v1", "invites", "*", "opened"],
+    &["v1", "push_tokens", "*"],
+    &["s", "*"],
+];
+
+// standard按字段名脱敏, strict脱敏全部查询参数和请求/响应头, 用于生产环境
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedactionMode {
+    Standard,
+    Strict,
+}
+
+impl FromStr for RedactionMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "standard" => Ok(RedactionMode::Standard),
+            "strict" => Ok(RedactionMode::Strict),
+            _ => Err(format!("invalid redaction mode: {}", s)),
+        }
+    }
+}
+
+// 访问日志脱敏. 路径中的手机号和凭证在两种模式下都会脱敏
+#[derive(Debug, Clone)]
+pub struct Redactor {
+    mode: RedactionMode,
+    words: Vec<String>,
+}
+
+impl Redactor {
+    pub fn new(mode: RedactionMode, extra_words: &[String]) -> Self {
+        Self {
+            mode,
+            words: SENSITIVE_WORDS
+                .iter()
+                .map(|w| w.to_string())
+                .chain(extra_words.iter().map(|w| w.to_lowercase()))
+                .collect(),
+        }
+    }
+
+    pub fn is_sensitive(&self, name: &str) -> bool {
+        self.mode == RedactionMode::Strict
+            || split_words(name).iter().any(|w| self.words.contains(w))
+    }
+
+    pub fn redact_query(&self, query: &str) -> String {
+        query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| match pair.split_once('=') {
+                Some((name, _)) if self.is_sensitive(name) => format!("{}={}", name, REDACTED),
+                _ => pair.to_owned(),
+            })
+            .collect::<Vec<String>>()
+            .join("&")
+    }
+
+    pub fn redact_path(&self, path: &str) -> String {
+        let segments = path.split('/').collect::<Vec<&str>>();
+        let secret = SECRET_PATHS.iter().find(|pattern| {
+            segments.len() == pattern.len() + 1
+                && segments[0].is_empty()
+                && segments[1..]
+                    .iter()
+                    .zip(pattern.iter())
+                    .all(|(segment, p)| *p == "*" || segment == p)
+        });
+        segments
+            .iter()
+            .enumerate()
+            .map(|(i, segment)| {
+                let is_secret = i > 0 && secret.is_some_and(|pattern| pattern[i - 1] == "*");
+                if is_secret || looks_like_phone(segment) {
+                    REDACTED
+                } else {
+                    segment
+                }
+            })
+            .collect::<Vec<&str>>()
+            .join("/")
+    }
+
+    pub fn request_line(&self, req: &ServiceRequest) -> String {
+        let query = req.query_string();
+        format!(
+            "{} {}{} {:?}",
+            req.method(),
+            self.redact_path(req.path()),
+            if query.is_empty() {
+                "".to_owned()
+            } else {
+                format!("?{}", self.redact_query(query))
+            },
+            req.version()
+        )
+    }
+
+    // 把格式中输出原始请求行(%r)、路径(%U)和敏感头(%{name}i, %{name}o)的占位符替换为脱敏版本
+    pub fn logger(&self, format: &str) -> Logger {
+        let request_redactor = self.clone();
+        let path_redactor = self.clone();
+        Logger::new(&self.rewrite_format(format))
+            .custom_request_replace("redacted_request", move |req| {
+                request_redactor.request_line(req)
+            })
+            .custom_request_replace("redacted_path", move |req| {
+                path_redactor.redact_path(req.path())
+            })
+    }
+
+    fn rewrite_format(&self, format: &str) -> String {
+        let mut out = String::new();
+        let mut rest = format;
+        while let Some(i) = rest.find('%') {
+            out.push_str(&rest[..i]);
+            rest = &rest[i..];
+            if rest.starts_with("%%") {
+                out.push_str("%%");
+                rest = &rest[2..];
+            } else if rest.starts_with("%r") {
+                out.push_str("%{redacted_request}xi");
+                rest = &rest[2..];
+            } else if rest.starts_with("%U") {
+                out.push_str("%{redacted_path}xi");
+                rest = &rest[2..];
+            } else if let Some((name, kind, len)) = header_directive(rest) {
+                if (kind == 'i' || kind == 'o') && self.is_sensitive(name) {
+                    out.push_str(REDACTED);
+                } else {
+                    out.push_str(&rest[..len]);
+                }
+                rest = &rest[len..];
+            } else {
+                out.push('%');
+                rest = &rest[1..];
+            }
+        }
+        out.push_str(rest);
+        out
+    }
+}
+
+// 解析%{name}x形式的占位符, 返回名称、类型字符和占位符长度
+fn header_directive(s: &str) -> Option<(&str, char, usize)> {
+    let inner = s.strip_prefix("%{")?;
+    let end = inner.find('}')?;
+    let kind = inner[end + 1..].chars().next()?;
+    Some((&inner[..end], kind, 2 + end + 1 + kind.len_utf8()))
+}
+
+fn split_words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut prev_lower = false;
+    for c in name.chars() {
+        if !c.is_alphanumeric() {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            prev_lower = false;
+            continue;
+        }
+        if c.is_uppercase() && prev_lower && !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+        prev_lower = c.is_lowercase() || c.is_ascii_digit();
+        word.extend(c.to_lowercase());
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+// 可选的+号(或编码后的%2B)加7到15位数字
+fn looks_like_phone(segment: &str) -> bool {
+    let digits = segment
+        .strip_prefix('+')
+        .or_else(|| segment.strip_prefix("%2B"))
+        .unwrap_or(segment);
+    (7..=15).contains(&digits.len()) && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn standard() -> Redactor {
+        Redactor::new(RedactionMode::Standard, &["nickname".to_owned()])
+    }
+
+    #[test]
+    fn path_secrets_and_phones() {
+        let redactor = standard();
+        assert_eq!(
+            redactor.redact_path("/v1/tokens/abc.def/verification"),
+            "/v1/tokens/[REDACTED]/verification"
+        );
+        assert_eq!(redactor.redact_path("/s/Xy12ab"), "/s/[REDACTED]");
+        assert_eq!(
+            redactor.redact_path("/v1/invites/k3j9/opened"),
+            "/v1/invites/[REDACTED]/opened"
+        );
+        assert_eq!(
+            redactor.redact_path("/v1/push_tokens/device-token"),
+            "/v1/push_tokens/[REDACTED]"
+        );
+        assert_eq!(
+            redactor.redact_path("/v1/phones/%2B8613800138000/exists"),
+            "/v1/phones/[REDACTED]/exists"
+        );
+        assert_eq!(
+            redactor.redact_path("/v1/tokens/refresh"),
+            "/v1/tokens/refresh"
+        );
+        assert_eq!(redactor.redact_path("/v1/invites"), "/v1/invites");
+        assert_eq!(
+            redactor.redact_path("/v1/walk_requests/665f1c2e/accepter"),
+            "/v1/walk_requests/665f1c2e/accepter"
+        );
+    }
+
+    #[test]
+    fn query_by_field_name() {
+        assert_eq!(
+            standard().redact_query("phone=138&limit=10&userNickname=a&&refresh_token=x"),
+            "phone=[REDACTED]&limit=10&userNickname=[REDACTED]&refresh_token=[REDACTED]"
+        );
+        assert_eq!(
+            Redactor::new(RedactionMode::Strict, &[]).redact_query("limit=10&skip"),
+            "limit=[REDACTED]&skip"
+        );
+    }
+
+    #[test]
+    fn split_words_by_case_and_separators() {
+        assert_eq!(split_words("refreshToken"), ["refresh", "token"]);
+        assert_eq!(split_words("X-Api-KEY"), ["x", "api", "key"]);
+        assert_eq!(split_words("phone_number2"), ["phone", "number2"]);
+        assert_eq!(split_words("__"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn rewrite_format_directives() {
+        assert_eq!(
+            standard().rewrite_format(r#"%a "%r" %U %s %{Authorization}i %{User-Agent}i 100%%"#),
+            r#"%a "%{redacted_request}xi" %{redacted_path}xi %s [REDACTED] %{User-Agent}i 100%%"#
+        );
+        assert_eq!(
+            Redactor::new(RedactionMode::Strict, &[]).rewrite_format("%{User-Agent}i %{x}e %T"),
+            "[REDACTED] %{x}e %T"
+        );
+    }
+}
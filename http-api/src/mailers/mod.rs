@@ -0,0 +1,33 @@
+pub mod ses;
+pub mod smtp;
+
+use crate::core::{email::Mailer, entities::Email, error::Error};
+
+use self::{ses::SesMailer, smtp::SmtpMailer};
+
+// 已配置的邮件通道. 未配置时只记录日志, 便于本地开发
+#[derive(Debug)]
+pub enum Mailers {
+    Smtp(SmtpMailer),
+    Ses(SesMailer),
+    Log,
+}
+
+impl Mailer for Mailers {
+    async fn send(&self, email: &Email) -> Result<(), Error> {
+        match self {
+            Mailers::Smtp(smtp) => smtp.send(email).await,
+            Mailers::Ses(ses) => ses.send(email).await,
+            Mailers::Log => {
+                log::info!(
+                    "email {} to {} of {}: {}",
+                    email.kind,
+                    email.to,
+                    email.user_id,
+                    email.subject
+                );
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+use crate::core::{email::Mailer, entities::Email, error::Error};
+
+const PATH: &str = "/v2/email/outbound-emails";
+const SIGNED_HEADERS: &str = "content-type;host;x-amz-date";
+
+// Amazon SES v2 SendEmail接口, 请求用访问密钥按Signature Version 4签名
+#[derive(Debug)]
+pub struct SesMailer {
+    client: reqwest::Client,
+    host: String,
+    region: String,
+    access_key_id: String,
+    secret_access_key: String,
+    from: String,
+}
+
+impl SesMailer {
+    pub fn new(region: &str, access_key_id: &str, secret_access_key: &str, from: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            host: format!("email.{}.amazonaws.com", region),
+            region: region.to_owned(),
+            access_key_id: access_key_id.to_owned(),
+            secret_access_key: secret_access_key.to_owned(),
+            from: from.to_owned(),
+        }
+    }
+
+    fn authorization(&self, amz_date: &str, body: &[u8]) -> Result<String, Error> {
+        let date = &amz_date[..8];
+        let scope = format!("{}/{}/ses/aws4_request", date, self.region);
+        let canonical_request = format!(
+            "POST\n{}\n\ncontent-type:application/json\nhost:{}\nx-amz-date:{}\n\n{}\n{}",
+            PATH,
+            self.host,
+            amz_date,
+            SIGNED_HEADERS,
+            hex(&Sha256::digest(body))
+        );
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex(&Sha256::digest(canonical_request.as_bytes()))
+        );
+        let key = [date, self.region.as_str(), "ses", "aws4_request"]
+            .into_iter()
+            .try_fold(
+                format!("AWS4{}", self.secret_access_key).into_bytes(),
+                |key, part| hmac_sha256(&key, part.as_bytes()),
+            )?;
+        Ok(format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.access_key_id,
+            scope,
+            SIGNED_HEADERS,
+            hex(&hmac_sha256(&key, string_to_sign.as_bytes())?)
+        ))
+    }
+}
+
+impl Mailer for SesMailer {
+    async fn send(&self, email: &Email) -> Result<(), Error> {
+        let body = json!({
+            "FromEmailAddress": self.from,
+            "Destination": {"ToAddresses": [email.to]},
+            "Content": {
+                "Simple": {
+                    "Subject": {"Data": email.subject, "Charset": "UTF-8"},
+                    "Body": {
+                        "Text": {"Data": email.text, "Charset": "UTF-8"},
+                        "Html": {"Data": email.html, "Charset": "UTF-8"},
+                    },
+                }
+            },
+        })
+        .to_string()
+        .into_bytes();
+        let amz_date = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+        self.client
+            .post(format!("https://{}{}", self.host, PATH))
+            .header("content-type", "application/json")
+            .header("x-amz-date", &amz_date)
+            .header("authorization", self.authorization(&amz_date, &body)?)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| Error::new("failed to send email by ses").with_cause(e))?
+            .error_for_status()
+            .map_err(|e| Error::new("failed to send email by ses").with_cause(e))?;
+        Ok(())
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key)
+        .map_err(|e| Error::new("invalid ses signing key").with_cause(e))?;
+    mac.update(data);
+    Ok(mac.finalize().into_bytes().to_vec())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -0,0 +1,65 @@
+use lettre::{
+    message::{Mailbox, MultiPart},
+    transport::smtp::authentication::Credentials,
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+
+use crate::core::{email::Mailer, entities::Email, error::Error};
+
+// 通过SMTP中继发送, 使用STARTTLS或隐式TLS由端口决定(465为隐式TLS)
+#[derive(Debug)]
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    pub fn new(
+        host: &str,
+        port: u16,
+        username: &str,
+        password: &str,
+        from: &str,
+    ) -> Result<Self, Error> {
+        let builder = if port == 465 {
+            AsyncSmtpTransport::<Tokio1Executor>::relay(host)
+        } else {
+            AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)
+        }
+        .map_err(|e| Error::new("invalid smtp host").with_cause(e))?
+        .port(port);
+        let builder = if username.is_empty() {
+            builder
+        } else {
+            builder.credentials(Credentials::new(username.to_owned(), password.to_owned()))
+        };
+        Ok(Self {
+            transport: builder.build(),
+            from: from
+                .parse()
+                .map_err(|e| Error::new("invalid mail from address").with_cause(e))?,
+        })
+    }
+}
+
+impl Mailer for SmtpMailer {
+    async fn send(&self, email: &Email) -> Result<(), Error> {
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(email
+                .to
+                .parse()
+                .map_err(|e| Error::new("invalid email address").with_cause(e))?)
+            .subject(&email.subject)
+            .multipart(MultiPart::alternative_plain_html(
+                email.text.clone(),
+                email.html.clone(),
+            ))
+            .map_err(|e| Error::new("failed to build email").with_cause(e))?;
+        self.transport
+            .send(message)
+            .await
+            .map_err(|e| Error::new("failed to send email by smtp").with_cause(e))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,923 @@
+mod access_tokens;
+mod handlers;
+mod inference_providers;
+mod key_providers;
+mod log_redaction;
+mod mailers;
+mod metrics;
+mod middlewares;
+mod notifiers;
+mod object_stores;
+mod oauth_providers;
+mod payout_providers;
+mod renderers;
+mod routes;
+mod sms_senders;
+mod translation_providers;
+
+// 领域层、存储层和后台任务在各自的crate中, 以原来的模块名引入, 其余模块仍通过crate::core等路径使用
+use little_walk_core as core;
+use little_walk_jobs as jobs;
+use little_walk_mongo_repo as repositories;
+
+use std::{
+    fmt::Debug,
+    io::{self, Write},
+    num::NonZeroUsize,
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
+
+use access_tokens::AccessTokens;
+use actix_web::{
+    web::{scope, Data},
+    App, HttpServer,
+};
+use auth_service::{
+    core::service::Service, hashers::sha::ShaHasher, repositories::mongo::MongodbRepository,
+    token_managers::jwt::JWTTokenManager,
+};
+use core::{
+    chat::ChatHub,
+    keys::{Key, KeyProvider, KeyPurpose, KeyRing},
+    service::Service as DogService,
+};
+use handlers::{
+    cache::{CachePolicies, CachePolicy},
+    deep_link::AppAssociation,
+    openapi::ApiDoc,
+    synthetic::SyntheticMonitor,
+    upload::UploadLimits,
+    withdrawal::PayoutWebhookKey,
+};
+use hmac::{Hmac, Mac};
+use inference_providers::{
+    remote::RemoteInferenceProvider, stub::StubInferenceProvider, InferenceProviders,
+};
+use key_providers::{
+    env::EnvKeyProvider, file::FileKeyProvider, kms::KmsKeyProvider, KeyProviders,
+};
+use log_redaction::{RedactionMode, Redactor};
+use mailers::{ses::SesMailer, smtp::SmtpMailer, Mailers};
+use middlewares::{concurrency_limit::ConcurrencyLimits, response_encoding::ResponseEncoding};
+use mongodb::{
+    options::{ClientOptions, ReadPreference},
+    Client,
+};
+use nb_from_env::{FromEnv, FromEnvDerive};
+use notifiers::{apns::ApnsNotifier, fcm::FcmNotifier, Notifiers};
+use oauth_providers::{apple::AppleOAuthProvider, wechat::WeChatOAuthProvider, OAuthProviders};
+use object_stores::{s3::S3ObjectStore, ObjectStores};
+use payout_providers::{http::HttpPayoutProvider, stub::StubPayoutProvider, PayoutProviders};
+use renderers::Renderers;
+use repositories::{
+    audited::Audited, field_cipher::FieldCipher, metrics::CommandMetrics, mongodb::MongoDB,
+};
+use sha2::{Sha256, Sha384};
+use sms_senders::http::HttpSmsSender;
+use translation_providers::{
+    remote::RemoteTranslationProvider, stub::StubTranslationProvider, TranslationProviders,
+};
+use upload_service::{
+    core::service::Service as UploadService, repositories::mongo::Mongo,
+    stores::local_fs::LocalFSStore,
+};
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
+
+#[derive(Debug, FromEnvDerive)]
+pub struct Config {
+    server_address: String,
+    db_uri: String,
+    #[env_default("")]
+    db_max_pool_size: String, // 连接池大小上限, 为空时使用db_uri中的设置或驱动默认值
+    #[env_default("")]
+    db_min_pool_size: String,
+    #[env_default("")]
+    db_connect_timeout: String, // 建立连接超时(毫秒)
+    #[env_default("")]
+    db_server_selection_timeout: String, // 选择可用服务器超时(毫秒)
+    #[env_default("")]
+    db_read_preference: String, // 列表和搜索的读偏好: primary, primaryPreferred, secondary, secondaryPreferred或nearest
+    #[env_default("")]
+    db_max_connecting: String, // 同时建立中的连接数上限, 为空时使用驱动默认值
+    #[env_default("")]
+    db_max_idle_time: String, // 空闲连接关闭前的最长时间(毫秒), 为空时不关闭
+    #[env_default("")]
+    http_workers: String, // HTTP工作线程数, 须大于0, 为空时等于可用CPU数
+    #[env_default("")]
+    http_worker_max_blocking_threads: String, // 每个HTTP工作线程的阻塞线程池大小, 为空时使用actix默认值
+    #[env_default("")]
+    runtime_worker_threads: String, // 后台任务运行时的线程数, 为空时等于可用CPU数
+    #[env_default("512")]
+    runtime_max_blocking_threads: String, // 后台任务运行时的阻塞线程池大小
+    #[env_default("")]
+    secret: String, // 未配置jwt_keys时作为唯一的JWT密钥
+    store_path: String,
+    #[env_default("info")]
+    log_level: String,
+    #[env_default("%t %s %r %D")]
+    log_format: String, // %r、%U和敏感请求头会被替换为脱敏后的内容
+    #[env_default("standard")]
+    log_redaction: String, // 日志脱敏模式: standard按字段名脱敏, strict脱敏全部查询参数和请求头
+    #[env_default("")]
+    log_redaction_fields: String, // 额外需要脱敏的字段名, 逗号分隔
+    #[env_default("30")]
+    nearby_cache_ttl: String, // 附近代遛请求缓存时长(秒)
+    #[env_default("300")]
+    walker_stats_cache_ttl: String, // 遛狗人统计缓存时长(秒)
+    #[env_default("3600")]
+    neighborhood_stats_cache_ttl: String, // 公开区域数据缓存时长(秒)
+    #[env_default("86400")]
+    breed_care_tips_cache_ttl: String, // 品种照护建议缓存时长(秒)
+    #[env_default("3600")]
+    achievement_job_interval: String, // 成就计算任务间隔(秒)
+    #[env_default("3600")]
+    review_reveal_job_interval: String, // 到期评价公开任务间隔(秒)
+    #[env_default("2592000")]
+    refresh_token_ttl: String, // 刷新令牌有效期(秒)
+    #[env_default("900")]
+    access_token_ttl: String, // 访问令牌有效期(秒)
+    #[env_default("little-walk")]
+    jwt_issuer: String,
+    #[env_default("little-walk-app")]
+    jwt_audience: String,
+    #[env_default("")]
+    admin_user_ids: String, // 管理员用户id, 逗号分隔
+    #[env_default("300")]
+    otp_ttl: String, // 短信验证码有效期(秒)
+    #[env_default("")]
+    sms_gateway_url: String, // 为空时验证码只写日志
+    #[env_default("")]
+    sms_api_key: String,
+    #[env_default("https://littlewalk.app/invites/")]
+    invite_link_base: String, // 邀请短信中的链接前缀, 后接邀请码
+    #[env_default("https://littlewalk.app/s/")]
+    short_link_base: String, // 短链接前缀, 后接短链接码
+    #[env_default("littlewalk://")]
+    deep_link_scheme: String, // 短链接跳转的App深度链接前缀
+    #[env_default("")]
+    apple_app_ids: String, // 逗号分隔的TEAMID.BundleID, 为空时不提供apple-app-site-association
+    #[env_default("")]
+    android_package_name: String, // 为空时不提供assetlinks.json
+    #[env_default("")]
+    android_cert_fingerprints: String, // 逗号分隔的App签名证书SHA256指纹
+    #[env_default("3600")]
+    breed_cache_max_age: String, // 品种列表的缓存时间(秒), 允许共享缓存
+    #[env_default("60")]
+    dog_cache_max_age: String, // 狗狗信息的缓存时间(秒), 只允许客户端缓存
+    #[env_default("true")]
+    legacy_routes: String, // 是否保留/v1之前的旧路径
+    #[env_default("")]
+    legacy_routes_sunset: String, // 旧路径停用的HTTP日期, 通过Sunset头告知客户端
+    #[env_default("")]
+    inference_api_url: String, // 品种识别服务地址, 为空时不返回建议
+    #[env_default("")]
+    inference_api_key: String,
+    #[env_default("")]
+    translation_api_url: String, // 翻译服务地址, 为空时原样返回原文
+    #[env_default("")]
+    translation_api_key: String,
+    #[env_default("warn")]
+    portrait_check_mode: String, // 头像质量检查模式: warn仅提示, enforce拒绝不合格头像
+    #[env_default("")]
+    payout_api_url: String, // 打款服务地址, 为空时收款账户登记即视为核验通过
+    #[env_default("")]
+    payout_api_key: String,
+    #[env_default("")]
+    payout_webhook_secret: String, // 未配置payout_webhook_keys时作为唯一的回调签名密钥, 都为空时不接受回调
+    #[env_default("60")]
+    payout_job_interval: String, // 提现打款任务间隔(秒)
+    #[env_default("10")]
+    upload_max_files: String, // 单次请求最多上传的文件数
+    #[env_default("3600")]
+    upload_gc_interval: String, // 孤立上传文件清理任务间隔(秒)
+    #[env_default("86400")]
+    integrity_job_interval: String, // 悬空引用检查任务间隔(秒)
+    #[env_default("")]
+    integrity_policies: String, // 按问题类型配置的处理方式, 如dog_missing_breed=fix,missing_upload=report, 默认只报告
+    #[env_default("1048576")]
+    upload_max_bytes: String, // 单个上传文件大小上限(字节)
+    #[env_default("image/jpeg,image/png,image/webp,image/gif")]
+    upload_allowed_mime_types: String, // 允许上传的文件类型, 逗号分隔
+    #[env_default("8000")]
+    upload_max_image_dimension: String, // 上传图片宽高上限(像素)
+    #[env_default("")]
+    upload_keep_metadata_purposes: String, // 保留图片EXIF等元数据的上传用途, 逗号分隔, 默认全部去除
+    #[env_default("")]
+    s3_endpoint: String, // 对象存储地址, 为空时不开放预签名直传
+    #[env_default("")]
+    s3_bucket: String,
+    #[env_default("us-east-1")]
+    s3_region: String,
+    #[env_default("")]
+    s3_access_key: String,
+    #[env_default("")]
+    s3_secret_key: String,
+    #[env_default("")]
+    synthetic_token: String, // 合成监控接口的访问令牌, 为空时不开放
+    #[env_default("")]
+    synthetic_owner_id: String, // 合成监控使用的测试主人、遛狗人和狗狗
+    #[env_default("")]
+    synthetic_walker_id: String,
+    #[env_default("")]
+    synthetic_dog_id: String,
+    #[env_default("env")]
+    key_provider: String, // 密钥来源: env, file或kms
+    #[env_default("")]
+    jwt_keys: String, // env来源的密钥, 格式为"id:base64密钥", 逗号分隔, 第一个为当前密钥
+    #[env_default("")]
+    field_encryption_keys: String, // 为空时手机号等字段明文保存
+    #[env_default("")]
+    payout_webhook_keys: String,
+    #[env_default("")]
+    key_file: String, // file来源的JSON密钥文件路径
+    #[env_default("")]
+    kms_endpoint: String,
+    #[env_default("")]
+    kms_token: String,
+    #[env_default("3600")]
+    field_encryption_job_interval: String, // 字段加密迁移任务间隔(秒)
+    #[env_default("")]
+    wechat_app_id: String, // 为空时不开放微信登录
+    #[env_default("")]
+    wechat_app_secret: String,
+    #[env_default("")]
+    apple_client_id: String, // 为空时不开放Apple登录
+    #[env_default("")]
+    apple_client_secret: String,
+    #[env_default("nearby=32,export=4,heatmap=8")]
+    concurrency_limits: String, // 按接口分组的并发上限, 分组有nearby、export和heatmap, 未列出的分组不限制
+    #[env_default("64")]
+    concurrency_queue_size: String, // 每组超出并发上限时最多排队的请求数, 排满后直接返回429
+    #[env_default("2000")]
+    concurrency_queue_timeout: String, // 排队等待的最长时间(毫秒), 超时返回429
+    #[env_default("10")]
+    push_job_interval: String, // 通知推送任务间隔(秒)
+    #[env_default("")]
+    fcm_service_account_file: String, // Firebase服务账号JSON文件路径, 为空时Android设备的推送只写日志
+    #[env_default("")]
+    apns_key_file: String, // APNs的.p8密钥文件路径, 为空时iOS设备的推送只写日志
+    #[env_default("")]
+    apns_key_id: String,
+    #[env_default("")]
+    apns_team_id: String,
+    #[env_default("")]
+    apns_topic: String, // App的Bundle ID
+    #[env_default("false")]
+    apns_sandbox: String, // 是否使用APNs开发环境, 开发版App的令牌只能在开发环境推送
+    #[env_default("")]
+    mail_provider: String, // smtp或ses, 为空时邮件只写日志
+    #[env_default("no-reply@littlewalk.app")]
+    mail_from: String, // 发件人, 可带显示名称, 如"Little Walk <no-reply@littlewalk.app>"
+    #[env_default("")]
+    smtp_host: String,
+    #[env_default("587")]
+    smtp_port: String, // 465使用隐式TLS, 其他端口使用STARTTLS
+    #[env_default("")]
+    smtp_username: String,
+    #[env_default("")]
+    smtp_password: String,
+    #[env_default("")]
+    ses_region: String,
+    #[env_default("")]
+    ses_access_key_id: String,
+    #[env_default("")]
+    ses_secret_access_key: String,
+    #[env_default("60")]
+    email_job_interval: String, // 邮件发送任务间隔(秒), 同时负责每周一生成周报
+}
+
+// HTTP请求由actix的工作线程处理, 这里的运行时只运行启动流程和后台任务
+fn main() -> io::Result<()> {
+    // 输出接口描述后退出, 不读取配置, 供clients/generate.sh生成Rust/Dart客户端
+    if std::env::args().nth(1).as_deref() == Some("openapi") {
+        let openapi = ApiDoc::openapi()
+            .to_pretty_json()
+            .expect("failed to serialize openapi");
+        return writeln!(io::stdout(), "{}", openapi);
+    }
+    dotenv::dotenv().ok();
+    let config = Config::from_env();
+    env_logger::init_from_env(
+        env_logger::Env::default().default_filter_or(config.log_level.as_str()),
+    );
+    let worker_threads = parse_optional(
+        &config.runtime_worker_threads,
+        "invalid runtime worker threads",
+    )
+    .unwrap_or_else(available_parallelism);
+    let max_blocking_threads = config
+        .runtime_max_blocking_threads
+        .parse()
+        .expect("invalid runtime max blocking threads");
+    log::info!(
+        "runtime: {} worker threads, {} max blocking threads",
+        worker_threads,
+        max_blocking_threads
+    );
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(worker_threads)
+        .max_blocking_threads(max_blocking_threads)
+        .enable_all()
+        .build()?
+        .block_on(serve(config))
+}
+
+async fn serve(config: Config) -> io::Result<()> {
+    let mut client_options = ClientOptions::parse(&config.db_uri)
+        .await
+        .expect("invalid mongodb uri");
+    client_options.command_event_handler = Some(Arc::new(CommandMetrics::default()));
+    if !config.db_max_pool_size.is_empty() {
+        client_options.max_pool_size = Some(
+            config
+                .db_max_pool_size
+                .parse()
+                .expect("invalid db max pool size"),
+        );
+    }
+    if !config.db_min_pool_size.is_empty() {
+        client_options.min_pool_size = Some(
+            config
+                .db_min_pool_size
+                .parse()
+                .expect("invalid db min pool size"),
+        );
+    }
+    if !config.db_connect_timeout.is_empty() {
+        client_options.connect_timeout = Some(
+            config
+                .db_connect_timeout
+                .parse()
+                .map(Duration::from_millis)
+                .expect("invalid db connect timeout"),
+        );
+    }
+    if !config.db_server_selection_timeout.is_empty() {
+        client_options.server_selection_timeout = Some(
+            config
+                .db_server_selection_timeout
+                .parse()
+                .map(Duration::from_millis)
+                .expect("invalid db server selection timeout"),
+        );
+    }
+    if let Some(max_connecting) =
+        parse_optional(&config.db_max_connecting, "invalid db max connecting")
+    {
+        client_options.max_connecting = Some(max_connecting);
+    }
+    if let Some(max_idle_time) =
+        parse_optional(&config.db_max_idle_time, "invalid db max idle time")
+    {
+        client_options.max_idle_time = Some(Duration::from_millis(max_idle_time));
+    }
+    // 未配置的项显示驱动默认值
+    log::info!(
+        "mongodb pool: max size {}, min size {}, max connecting {}, max idle time {:?}",
+        client_options.max_pool_size.unwrap_or(10),
+        client_options.min_pool_size.unwrap_or(0),
+        client_options.max_connecting.unwrap_or(2),
+        client_options.max_idle_time,
+    );
+    let client = Client::with_options(client_options).expect("failed to connect to mongodb");
+    let db = client.database("little-walk-auth");
+
+    let key_provider = match config.key_provider.as_str() {
+        "env" => KeyProviders::Env(EnvKeyProvider::new(
+            legacy_key_ring(&config.jwt_keys, &config.secret).expect("invalid jwt keys"),
+            KeyRing::parse(&config.field_encryption_keys).expect("invalid field encryption keys"),
+            legacy_key_ring(&config.payout_webhook_keys, &config.payout_webhook_secret)
+                .expect("invalid payout webhook keys"),
+        )),
+        "file" => KeyProviders::File(FileKeyProvider::new(&config.key_file)),
+        "kms" => KeyProviders::Kms(KmsKeyProvider::new(&config.kms_endpoint, &config.kms_token)),
+        _ => panic!("invalid key provider: {}", config.key_provider),
+    };
+    let jwt_keys = key_provider
+        .key_ring(KeyPurpose::Jwt)
+        .await
+        .expect("failed to load jwt keys");
+    let field_encryption_keys = key_provider
+        .key_ring(KeyPurpose::FieldEncryption)
+        .await
+        .expect("failed to load field encryption keys");
+    let payout_webhook_keys = key_provider
+        .key_ring(KeyPurpose::PayoutWebhook)
+        .await
+        .expect("failed to load payout webhook keys");
+
+    let service = Data::new(Service::<
+        MongodbRepository,
+        ShaHasher,
+        JWTTokenManager<Hmac<Sha384>>,
+    >::new(
+        MongodbRepository::new(db.clone()),
+        ShaHasher,
+        JWTTokenManager::new(
+            Hmac::new_from_slice(&jwt_keys.current().expect("missing jwt key").secret)
+                .expect("failed to create jwt signing key"),
+        ),
+    ));
+
+    let upload_service = Data::new(UploadService::<Mongo, LocalFSStore>::new(
+        Mongo::new(db.clone()),
+        LocalFSStore::new(&config.store_path),
+    ));
+
+    let nearby_cache_ttl = config
+        .nearby_cache_ttl
+        .parse()
+        .map(Duration::from_secs)
+        .expect("invalid nearby cache ttl");
+    let walker_stats_cache_ttl = config
+        .walker_stats_cache_ttl
+        .parse()
+        .map(Duration::from_secs)
+        .expect("invalid walker stats cache ttl");
+    let neighborhood_stats_cache_ttl = config
+        .neighborhood_stats_cache_ttl
+        .parse()
+        .map(Duration::from_secs)
+        .expect("invalid neighborhood stats cache ttl");
+    let breed_care_tips_cache_ttl = config
+        .breed_care_tips_cache_ttl
+        .parse()
+        .map(Duration::from_secs)
+        .expect("invalid breed care tips cache ttl");
+    let refresh_token_ttl = config
+        .refresh_token_ttl
+        .parse()
+        .map(Duration::from_secs)
+        .expect("invalid refresh token ttl");
+    let otp_ttl = config
+        .otp_ttl
+        .parse()
+        .map(Duration::from_secs)
+        .expect("invalid otp ttl");
+    let repository = if field_encryption_keys.is_empty() {
+        MongoDB::new(client, db)
+    } else {
+        MongoDB::new(client, db).with_field_cipher(
+            FieldCipher::new(&field_encryption_keys).expect("invalid field encryption keys"),
+        )
+    };
+    // 客户端始终读主节点, 配置的读偏好只用于列表、搜索等只读查询, 事务不受影响
+    let repository = if config.db_read_preference.is_empty() {
+        repository
+    } else {
+        repository.with_listing_read_preference(
+            parse_read_preference(&config.db_read_preference).expect("invalid db read preference"),
+        )
+    };
+    // 已有重复数据时索引创建失败, 记录错误后继续启动, 清理数据后重启即可
+    if let Err(e) = repository.ensure_indexes().await {
+        log::error!("failed to ensure indexes: {}", e);
+    }
+    let dog_service = Data::new(
+        DogService::new(Audited::new(repository))
+            .with_nearby_cache_ttl(nearby_cache_ttl)
+            .with_walker_stats_cache_ttl(walker_stats_cache_ttl)
+            .with_neighborhood_stats_cache_ttl(neighborhood_stats_cache_ttl)
+            .with_breed_care_tips_cache_ttl(breed_care_tips_cache_ttl)
+            .with_refresh_token_ttl(refresh_token_ttl)
+            .with_otp_ttl(otp_ttl)
+            .with_portrait_check_mode(
+                config
+                    .portrait_check_mode
+                    .parse()
+                    .expect("invalid portrait check mode"),
+            )
+            .with_admin_user_ids(
+                config
+                    .admin_user_ids
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|id| !id.is_empty())
+                    .map(str::to_owned)
+                    .collect(),
+            )
+            .with_integrity_policies(
+                config
+                    .integrity_policies
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|policy| !policy.is_empty())
+                    .map(|policy| {
+                        let (kind, policy) =
+                            policy.split_once('=').expect("invalid integrity policy");
+                        (
+                            kind.trim().parse().expect("invalid integrity issue kind"),
+                            policy.trim().parse().expect("invalid integrity policy"),
+                        )
+                    })
+                    .collect(),
+            )
+            .with_invite_link_base(config.invite_link_base.clone())
+            .with_short_links(
+                config.short_link_base.clone(),
+                config.deep_link_scheme.clone(),
+            )
+            .with_synthetic_walker_id(
+                Some(config.synthetic_walker_id.clone()).filter(|id| !id.is_empty()),
+            ),
+    );
+    tokio::spawn(jobs::run_achievement_job(
+        dog_service.clone().into_inner(),
+        config
+            .achievement_job_interval
+            .parse()
+            .map(Duration::from_secs)
+            .expect("invalid achievement job interval"),
+    ));
+    tokio::spawn(jobs::run_review_reveal_job(
+        dog_service.clone().into_inner(),
+        config
+            .review_reveal_job_interval
+            .parse()
+            .map(Duration::from_secs)
+            .expect("invalid review reveal job interval"),
+    ));
+    tokio::spawn(jobs::run_upload_gc_job(
+        dog_service.clone().into_inner(),
+        config
+            .upload_gc_interval
+            .parse()
+            .map(Duration::from_secs)
+            .expect("invalid upload gc interval"),
+    ));
+    tokio::spawn(jobs::run_integrity_job(
+        dog_service.clone().into_inner(),
+        config
+            .integrity_job_interval
+            .parse()
+            .map(Duration::from_secs)
+            .expect("invalid integrity job interval"),
+    ));
+    if !field_encryption_keys.is_empty() {
+        tokio::spawn(jobs::run_field_encryption_job(
+            dog_service.clone().into_inner(),
+            config
+                .field_encryption_job_interval
+                .parse()
+                .map(Duration::from_secs)
+                .expect("invalid field encryption job interval"),
+        ));
+    }
+
+    let access_tokens = Data::new(
+        AccessTokens::new(
+            &jwt_keys,
+            &config.jwt_issuer,
+            &config.jwt_audience,
+            config
+                .access_token_ttl
+                .parse()
+                .map(Duration::from_secs)
+                .expect("invalid access token ttl"),
+        )
+        .expect("invalid jwt keys"),
+    );
+
+    let sms_sender = Data::new(HttpSmsSender::new(
+        &config.sms_gateway_url,
+        &config.sms_api_key,
+    ));
+
+    let inference_provider = Data::new(if config.inference_api_url.is_empty() {
+        InferenceProviders::Stub(StubInferenceProvider)
+    } else {
+        InferenceProviders::Remote(RemoteInferenceProvider::new(
+            &config.inference_api_url,
+            &config.inference_api_key,
+        ))
+    });
+
+    let translation_provider = Data::new(if config.translation_api_url.is_empty() {
+        TranslationProviders::Stub(StubTranslationProvider)
+    } else {
+        TranslationProviders::Remote(RemoteTranslationProvider::new(
+            &config.translation_api_url,
+            &config.translation_api_key,
+        ))
+    });
+
+    let payout_provider = Data::new(if config.payout_api_url.is_empty() {
+        PayoutProviders::Stub(StubPayoutProvider)
+    } else {
+        PayoutProviders::Http(HttpPayoutProvider::new(
+            &config.payout_api_url,
+            &config.payout_api_key,
+        ))
+    });
+    tokio::spawn(jobs::run_payout_job(
+        dog_service.clone().into_inner(),
+        payout_provider.clone().into_inner(),
+        config
+            .payout_job_interval
+            .parse()
+            .map(Duration::from_secs)
+            .expect("invalid payout job interval"),
+    ));
+
+    let notifiers = Arc::new(Notifiers {
+        fcm: (!config.fcm_service_account_file.is_empty()).then(|| {
+            FcmNotifier::new(
+                &std::fs::read_to_string(&config.fcm_service_account_file)
+                    .expect("failed to read fcm service account file"),
+            )
+            .expect("invalid fcm service account")
+        }),
+        apns: (!config.apns_key_file.is_empty()).then(|| {
+            ApnsNotifier::new(
+                &std::fs::read_to_string(&config.apns_key_file)
+                    .expect("failed to read apns key file"),
+                &config.apns_key_id,
+                &config.apns_team_id,
+                &config.apns_topic,
+                config.apns_sandbox.parse().expect("invalid apns sandbox"),
+            )
+            .expect("invalid apns key")
+        }),
+    });
+    tokio::spawn(jobs::run_push_job(
+        dog_service.clone().into_inner(),
+        notifiers,
+        sms_sender.clone().into_inner(),
+        config
+            .push_job_interval
+            .parse()
+            .map(Duration::from_secs)
+            .expect("invalid push job interval"),
+    ));
+
+    let mailer = Arc::new(match config.mail_provider.as_str() {
+        "smtp" => Mailers::Smtp(
+            SmtpMailer::new(
+                &config.smtp_host,
+                config.smtp_port.parse().expect("invalid smtp port"),
+                &config.smtp_username,
+                &config.smtp_password,
+                &config.mail_from,
+            )
+            .expect("invalid smtp config"),
+        ),
+        "ses" => Mailers::Ses(SesMailer::new(
+            &config.ses_region,
+            &config.ses_access_key_id,
+            &config.ses_secret_access_key,
+            &config.mail_from,
+        )),
+        "" => Mailers::Log,
+        _ => panic!("invalid mail provider"),
+    });
+    tokio::spawn(jobs::run_email_job(
+        dog_service.clone().into_inner(),
+        mailer,
+        config
+            .email_job_interval
+            .parse()
+            .map(Duration::from_secs)
+            .expect("invalid email job interval"),
+    ));
+
+    let payout_webhook_key = Data::new(PayoutWebhookKey(
+        payout_webhook_keys
+            .keys()
+            .iter()
+            .map(|key| {
+                Hmac::<Sha256>::new_from_slice(&key.secret)
+                    .expect("failed to create payout webhook key")
+            })
+            .collect(),
+    ));
+
+    let app_association = Data::new(AppAssociation::new(
+        config
+            .apple_app_ids
+            .split(',')
+            .map(|id| id.trim().to_owned())
+            .filter(|id| !id.is_empty())
+            .collect(),
+        config.android_package_name.clone(),
+        config
+            .android_cert_fingerprints
+            .split(',')
+            .map(|f| f.trim().to_owned())
+            .filter(|f| !f.is_empty())
+            .collect(),
+    ));
+
+    let cache_policies = Data::new(CachePolicies {
+        breeds: CachePolicy {
+            max_age: config
+                .breed_cache_max_age
+                .parse()
+                .expect("invalid breed cache max age"),
+            public: true,
+        },
+        dogs: CachePolicy {
+            max_age: config
+                .dog_cache_max_age
+                .parse()
+                .expect("invalid dog cache max age"),
+            public: false,
+        },
+    });
+
+    let upload_max_image_dimension = config
+        .upload_max_image_dimension
+        .parse()
+        .expect("invalid upload max image dimension");
+    let upload_limits = Data::new(UploadLimits {
+        max_files: config
+            .upload_max_files
+            .parse()
+            .expect("invalid upload max files"),
+        max_bytes: config
+            .upload_max_bytes
+            .parse()
+            .expect("invalid upload max bytes"),
+        allowed_mime_types: config
+            .upload_allowed_mime_types
+            .split(',')
+            .map(|t| t.trim().to_owned())
+            .filter(|t| !t.is_empty())
+            .collect(),
+        max_image_width: upload_max_image_dimension,
+        max_image_height: upload_max_image_dimension,
+        keep_metadata_purposes: config
+            .upload_keep_metadata_purposes
+            .split(',')
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .map(|p| p.parse().expect("invalid upload purpose"))
+            .collect(),
+    });
+
+    let object_store = Data::new(if config.s3_endpoint.is_empty() {
+        ObjectStores::Disabled
+    } else {
+        ObjectStores::S3(
+            S3ObjectStore::new(
+                &config.s3_endpoint,
+                &config.s3_bucket,
+                &config.s3_region,
+                &config.s3_access_key,
+                &config.s3_secret_key,
+            )
+            .expect("invalid object store config"),
+        )
+    });
+
+    let synthetic_monitor = Data::new(SyntheticMonitor {
+        token: config.synthetic_token.clone(),
+        owner_id: config.synthetic_owner_id.clone(),
+        walker_id: config.synthetic_walker_id.clone(),
+        dog_id: config.synthetic_dog_id.clone(),
+    });
+
+    let renderers = Data::new(Renderers::default());
+    let chat_hub = Data::new(ChatHub::default());
+    let concurrency_limits = Data::new(
+        ConcurrencyLimits::new(
+            &config.concurrency_limits,
+            config
+                .concurrency_queue_size
+                .parse()
+                .expect("invalid concurrency queue size"),
+            config
+                .concurrency_queue_timeout
+                .parse()
+                .map(Duration::from_millis)
+                .expect("invalid concurrency queue timeout"),
+        )
+        .expect("invalid concurrency limits"),
+    );
+    let oauth_providers = Data::new(OAuthProviders {
+        wechat: (!config.wechat_app_id.is_empty())
+            .then(|| WeChatOAuthProvider::new(&config.wechat_app_id, &config.wechat_app_secret)),
+        apple: (!config.apple_client_id.is_empty())
+            .then(|| AppleOAuthProvider::new(&config.apple_client_id, &config.apple_client_secret)),
+    });
+
+    let redactor = Redactor::new(
+        config
+            .log_redaction
+            .parse::<RedactionMode>()
+            .expect("invalid log redaction mode"),
+        &config
+            .log_redaction_fields
+            .split(',')
+            .map(|f| f.trim().to_owned())
+            .filter(|f| !f.is_empty())
+            .collect::<Vec<String>>(),
+    );
+
+    let openapi = ApiDoc::openapi();
+    let legacy_routes = config
+        .legacy_routes
+        .parse::<bool>()
+        .expect("invalid legacy routes");
+    let http_workers = parse_optional(
+        &config.http_workers,
+        "invalid http workers, expect a positive integer",
+    )
+    .map_or_else(available_parallelism, NonZeroUsize::get);
+    let http_worker_max_blocking_threads = parse_optional(
+        &config.http_worker_max_blocking_threads,
+        "invalid http worker max blocking threads",
+    );
+    // actix默认把512个阻塞线程平分给各工作线程
+    log::info!(
+        "http: {} workers, {} max blocking threads per worker",
+        http_workers,
+        http_worker_max_blocking_threads.unwrap_or(512 / http_workers)
+    );
+    let server_address = config.server_address.clone();
+
+    let server = HttpServer::new(move || {
+        let logger = redactor.logger(&config.log_format);
+        App::new()
+            .wrap(ResponseEncoding)
+            .wrap(logger)
+            .app_data(service.clone())
+            .app_data(upload_service.clone())
+            .app_data(dog_service.clone())
+            .app_data(sms_sender.clone())
+            .app_data(access_tokens.clone())
+            .app_data(inference_provider.clone())
+            .app_data(translation_provider.clone())
+            .app_data(oauth_providers.clone())
+            .app_data(payout_provider.clone())
+            .app_data(payout_webhook_key.clone())
+            .app_data(app_association.clone())
+            .app_data(cache_policies.clone())
+            .app_data(upload_limits.clone())
+            .app_data(object_store.clone())
+            .app_data(synthetic_monitor.clone())
+            .app_data(renderers.clone())
+            .app_data(chat_hub.clone())
+            .app_data(concurrency_limits.clone())
+            .configure(routes::system::configure)
+            // 需在/v1作用域之前注册, 否则会被作用域匹配
+            .service(
+                SwaggerUi::new("/v1/swagger-ui/{_:.*}").url("/v1/openapi.json", openapi.clone()),
+            )
+            .service(scope(routes::API_PREFIX).configure(routes::configure))
+            .configure(|cfg| {
+                if legacy_routes {
+                    routes::configure_legacy(cfg, &config.legacy_routes_sunset);
+                }
+            })
+    })
+    .workers(http_workers);
+    match http_worker_max_blocking_threads {
+        Some(threads) => server.worker_max_blocking_threads(threads),
+        None => server,
+    }
+    .bind(server_address)?
+    .run()
+    .await
+}
+
+fn available_parallelism() -> usize {
+    std::thread::available_parallelism().map_or(2, NonZeroUsize::get)
+}
+
+// 为空时返回None, 使用默认值
+fn parse_optional<T>(value: &str, message: &str) -> Option<T>
+where
+    T: FromStr,
+    T::Err: Debug,
+{
+    (!value.is_empty()).then(|| value.parse().expect(message))
+}
+
+// 未配置密钥列表时使用旧的单个密钥配置, 密钥id为default
+fn legacy_key_ring(keys: &str, secret: &str) -> Result<KeyRing, core::error::Error> {
+    if keys.is_empty() && !secret.is_empty() {
+        return KeyRing::new(vec![Key {
+            id: "default".to_owned(),
+            secret: secret.as_bytes().to_vec(),
+        }]);
+    }
+    KeyRing::parse(keys)
+}
+
+// 与连接串中readPreference的取值一致
+fn parse_read_preference(mode: &str) -> Result<ReadPreference, core::error::Error> {
+    let options = Default::default();
+    match mode {
+        "primary" => Ok(ReadPreference::Primary),
+        "primaryPreferred" => Ok(ReadPreference::PrimaryPreferred { options }),
+        "secondary" => Ok(ReadPreference::Secondary { options }),
+        "secondaryPreferred" => Ok(ReadPreference::SecondaryPreferred { options }),
+        "nearest" => Ok(ReadPreference::Nearest { options }),
+        _ => Err(core::error::Error::new(format!(
+            "unknown read preference: {mode}"
+        ))),
+    }
+}
@@ -0,0 +1,42 @@
+use lazy_static::lazy_static;
+use prometheus::{core::Collector, register_int_counter_vec, Encoder, IntCounterVec, TextEncoder};
+
+use crate::repositories::metrics::REPOSITORY_OPERATIONS;
+
+lazy_static! {
+    pub static ref CONCURRENCY_LIMIT_REJECTIONS: IntCounterVec = register_int_counter_vec!(
+        "concurrency_limit_rejections_total",
+        "Number of requests rejected by the per-route concurrency limit by group and reason",
+        &["group", "reason"]
+    )
+    .expect("failed to register concurrency_limit_rejections_total");
+}
+
+pub fn encode() -> Result<(String, String), prometheus::Error> {
+    let encoder = TextEncoder::new();
+    let mut buffer = Vec::new();
+    encoder.encode(&prometheus::gather(), &mut buffer)?;
+    Ok((
+        encoder.format_type().to_owned(),
+        String::from_utf8_lossy(&buffer).into_owned(),
+    ))
+}
+
+// 汇总所有集合和操作的仓储操作次数, 返回(总次数, 失败次数)
+pub fn repository_operation_counts() -> (u64, u64) {
+    REPOSITORY_OPERATIONS
+        .collect()
+        .iter()
+        .flat_map(|family| family.get_metric())
+        .fold((0, 0), |(total, failed), metric| {
+            let count = metric.get_counter().get_value() as u64;
+            let is_failure = metric
+                .get_label()
+                .iter()
+                .any(|label| label.get_name() == "result" && label.get_value() == "failure");
+            (
+                total + count,
+                if is_failure { failed + count } else { failed },
+            )
+        })
+}
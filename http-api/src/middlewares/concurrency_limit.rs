@@ -0,0 +1,162 @@
+use std::{
+    collections::HashMap,
+    future::{ready, Future, Ready},
+    pin::Pin,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use actix_web::{
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    web::Data,
+    Error,
+};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::{core::error::Error as CoreError, handlers::error::ApiError, metrics};
+
+// 接口分组, 同组接口共享同一个并发上限
+pub const NEARBY: &str = "nearby"; // 使用$geoNear的附近请求和遛狗人搜索
+pub const EXPORT: &str = "export"; // 遛狗请求导出
+pub const HEATMAP: &str = "heatmap"; // 公开的区域热度统计
+
+const GROUPS: [&str; 3] = [NEARBY, EXPORT, HEATMAP];
+
+// 单个分组的并发控制. 超出上限的请求排队等待, 排队已满或等待超时返回429
+struct Limiter {
+    permits: Arc<Semaphore>,
+    queued: AtomicUsize,
+    max_queued: usize,
+    queue_timeout: Duration,
+}
+
+impl Limiter {
+    async fn acquire(&self, group: &'static str) -> Result<OwnedSemaphorePermit, ApiError> {
+        if let Ok(permit) = self.permits.clone().try_acquire_owned() {
+            return Ok(permit);
+        }
+        if self.queued.fetch_add(1, Ordering::SeqCst) >= self.max_queued {
+            self.queued.fetch_sub(1, Ordering::SeqCst);
+            return Err(reject(group, "queue_full"));
+        }
+        let permit =
+            tokio::time::timeout(self.queue_timeout, self.permits.clone().acquire_owned()).await;
+        self.queued.fetch_sub(1, Ordering::SeqCst);
+        match permit {
+            Ok(Ok(permit)) => Ok(permit),
+            Ok(Err(e)) => Err(ApiError::internal(e)),
+            Err(_) => Err(reject(group, "timeout")),
+        }
+    }
+}
+
+fn reject(group: &'static str, reason: &str) -> ApiError {
+    metrics::CONCURRENCY_LIMIT_REJECTIONS
+        .with_label_values(&[group, reason])
+        .inc();
+    ApiError::too_many_requests("服务繁忙, 请稍后重试")
+}
+
+// 各分组的并发上限, 通过app_data在所有工作线程间共享. 未配置的分组不限制
+#[derive(Default)]
+pub struct ConcurrencyLimits(HashMap<&'static str, Limiter>);
+
+impl ConcurrencyLimits {
+    // limits形如"nearby=32,export=4", 每组最多max_queued个请求排队, 最长等待queue_timeout
+    pub fn new(
+        limits: &str,
+        max_queued: usize,
+        queue_timeout: Duration,
+    ) -> Result<Self, CoreError> {
+        let mut groups = HashMap::new();
+        for limit in limits.split(',').map(str::trim).filter(|l| !l.is_empty()) {
+            let (group, permits) = limit
+                .split_once('=')
+                .ok_or(CoreError::msg("并发上限的格式应为分组=数量"))?;
+            let group = GROUPS
+                .into_iter()
+                .find(|g| *g == group.trim())
+                .ok_or(CoreError::msg("未知的接口分组"))?;
+            let permits = permits
+                .trim()
+                .parse::<usize>()
+                .ok()
+                .filter(|p| *p > 0)
+                .ok_or(CoreError::msg("并发上限必须为正整数"))?;
+            groups.insert(
+                group,
+                Limiter {
+                    permits: Arc::new(Semaphore::new(permits)),
+                    queued: AtomicUsize::new(0),
+                    max_queued,
+                    queue_timeout,
+                },
+            );
+        }
+        Ok(Self(groups))
+    }
+}
+
+// 包装耗费数据库资源的接口, 按分组限制同时执行的请求数
+pub struct ConcurrencyLimit(pub &'static str);
+
+impl<S, B> Transform<S, ServiceRequest> for ConcurrencyLimit
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = ConcurrencyLimitService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ConcurrencyLimitService {
+            group: self.0,
+            next: Rc::new(service),
+        }))
+    }
+}
+
+pub struct ConcurrencyLimitService<S> {
+    group: &'static str,
+    next: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for ConcurrencyLimitService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    fn poll_ready(
+        &self,
+        ctx: &mut core::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.next.poll_ready(ctx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let next = self.next.clone();
+        let group = self.group;
+        Box::pin(async move {
+            let limits = req.app_data::<Data<ConcurrencyLimits>>().cloned();
+            // 许可在响应返回前一直持有
+            let _permit = match limits.as_ref().and_then(|limits| limits.0.get(group)) {
+                Some(limiter) => Some(limiter.acquire(group).await?),
+                None => None,
+            };
+            next.call(req).await
+        })
+    }
+}
@@ -1 +1,2 @@
+pub mod concurrency_limit;
 pub mod response_encoding;
@@ -1,5 +1,6 @@
 use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
-use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::http::header::{HeaderName, HeaderValue, CONTENT_TYPE};
+use actix_web::http::StatusCode;
 use std::future::{ready, Ready};
 use std::pin::Pin;
 use std::str::FromStr;
@@ -46,8 +47,16 @@ where
         let future = self.next.call(req);
         Box::pin(async move {
             let mut res = future.await?;
-            res.headers_mut()
-                .insert(HeaderName::from_str("Content-Type").unwrap(), HeaderValue::from_str("application/json; charset=utf-8").unwrap());
+            // 只覆盖默认的json和文本类型, 保留文件下载、指标等显式设置的类型, WebSocket握手响应没有响应体
+            let overridable = match res.headers().get(CONTENT_TYPE).map(|v| v.to_str()) {
+                None => res.status() != StatusCode::SWITCHING_PROTOCOLS,
+                Some(Ok(content_type)) => content_type == "application/json" || content_type == "text/plain; charset=utf-8",
+                Some(Err(_)) => false,
+            };
+            if overridable {
+                res.headers_mut()
+                    .insert(HeaderName::from_str("Content-Type").unwrap(), HeaderValue::from_str("application/json; charset=utf-8").unwrap());
+            }
             Ok(res)
         })
     }
@@ -0,0 +1,135 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use reqwest::StatusCode;
+use ring::{
+    rand::SystemRandom,
+    signature::{EcdsaKeyPair, ECDSA_P256_SHA256_FIXED_SIGNING},
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::core::{
+    entities::DeviceToken,
+    error::Error,
+    push::{Notifier, PushMessage, PushOutcome},
+};
+
+use super::{pkcs8_der, push_data, sign_jwt, CachedToken};
+
+const PRODUCTION_URL: &str = "https://api.push.apple.com";
+const SANDBOX_URL: &str = "https://api.sandbox.push.apple.com";
+// 鉴权令牌需在1小时内更换, 且不能过于频繁地重新生成
+const PROVIDER_TOKEN_TTL: Duration = Duration::from_secs(50 * 60);
+
+#[derive(Debug, Deserialize)]
+struct ErrorResp {
+    reason: String,
+}
+
+// Apple Push Notification service. 用开发者账号下载的.p8密钥签名ES256令牌鉴权, 只支持HTTP/2
+#[derive(Debug)]
+pub struct ApnsNotifier {
+    client: reqwest::Client,
+    base_url: &'static str,
+    key_id: String,
+    team_id: String,
+    topic: String, // App的Bundle ID
+    key: EcdsaKeyPair,
+    provider_token: CachedToken,
+}
+
+impl ApnsNotifier {
+    pub fn new(
+        key: &str,
+        key_id: &str,
+        team_id: &str,
+        topic: &str,
+        sandbox: bool,
+    ) -> Result<Self, Error> {
+        let key = EcdsaKeyPair::from_pkcs8(
+            &ECDSA_P256_SHA256_FIXED_SIGNING,
+            &pkcs8_der(key)?,
+            &SystemRandom::new(),
+        )
+        .map_err(|e| Error::new("invalid apns key").with_cause(e))?;
+        Ok(Self {
+            client: reqwest::Client::builder()
+                .http2_prior_knowledge()
+                .build()
+                .map_err(|e| Error::new("failed to build apns client").with_cause(e))?,
+            base_url: if sandbox { SANDBOX_URL } else { PRODUCTION_URL },
+            key_id: key_id.to_owned(),
+            team_id: team_id.to_owned(),
+            topic: topic.to_owned(),
+            key,
+            provider_token: CachedToken::default(),
+        })
+    }
+
+    fn provider_token(&self) -> Result<String, Error> {
+        if let Some(token) = self.provider_token.get() {
+            return Ok(token);
+        }
+        let token = sign_jwt(
+            &json!({"alg": "ES256", "kid": self.key_id}),
+            &json!({"iss": self.team_id, "iat": Utc::now().timestamp()}),
+            |input| {
+                self.key
+                    .sign(&SystemRandom::new(), input)
+                    .map(|signature| signature.as_ref().to_vec())
+                    .map_err(|e| Error::new("failed to sign apns token").with_cause(e))
+            },
+        )?;
+        self.provider_token.set(&token, PROVIDER_TOKEN_TTL);
+        Ok(token)
+    }
+}
+
+impl Notifier for ApnsNotifier {
+    async fn push(
+        &self,
+        device: &DeviceToken,
+        message: &PushMessage,
+    ) -> Result<PushOutcome, Error> {
+        let mut payload = push_data(message);
+        payload.insert(
+            "aps".to_owned(),
+            json!({
+                "alert": {"title": message.title, "body": message.body},
+                "sound": "default",
+            }),
+        );
+        let resp = self
+            .client
+            .post(format!("{}/3/device/{}", self.base_url, device.token))
+            .header(
+                "authorization",
+                format!("bearer {}", self.provider_token()?),
+            )
+            .header("apns-topic", &self.topic)
+            .header("apns-push-type", "alert")
+            .json(&Value::Object(payload))
+            .send()
+            .await
+            .map_err(|e| Error::new("failed to send apns notification").with_cause(e))?;
+        match resp.status() {
+            // 410表示App已卸载或关闭了通知, 400 BadDeviceToken表示令牌与环境不符或格式错误
+            StatusCode::GONE => Ok(PushOutcome::Unregistered),
+            StatusCode::BAD_REQUEST => {
+                let reason = resp
+                    .json::<ErrorResp>()
+                    .await
+                    .map(|e| e.reason)
+                    .unwrap_or_default();
+                if reason == "BadDeviceToken" {
+                    Ok(PushOutcome::Unregistered)
+                } else {
+                    Err(Error::new("failed to send apns notification").with_cause(reason))
+                }
+            }
+            status if status.is_success() => Ok(PushOutcome::Delivered),
+            status => Err(Error::new("failed to send apns notification").with_cause(status)),
+        }
+    }
+}
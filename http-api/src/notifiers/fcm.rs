@@ -0,0 +1,145 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use reqwest::StatusCode;
+use ring::{
+    rand::SystemRandom,
+    signature::{RsaKeyPair, RSA_PKCS1_SHA256},
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::core::{
+    entities::DeviceToken,
+    error::Error,
+    push::{Notifier, PushMessage, PushOutcome},
+};
+
+use super::{pkcs8_der, push_data, sign_jwt, CachedToken};
+
+const SEND_URL: &str = "https://fcm.googleapis.com/v1/projects";
+const SCOPE: &str = "https://www.googleapis.com/auth/firebase.messaging";
+const ASSERTION_SECS: i64 = 3600;
+const TOKEN_EXPIRY_MARGIN_SECS: u64 = 60; // 访问令牌提前失效的时间, 避免请求途中过期
+
+// Firebase控制台下载的服务账号JSON中用到的字段
+#[derive(Debug, Deserialize)]
+struct ServiceAccount {
+    project_id: String,
+    client_email: String,
+    private_key: String,
+    token_uri: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResp {
+    access_token: String,
+    expires_in: u64,
+}
+
+// Firebase Cloud Messaging HTTP v1接口. 用服务账号私钥签名的JWT换取访问令牌, 令牌在有效期内复用
+#[derive(Debug)]
+pub struct FcmNotifier {
+    client: reqwest::Client,
+    project_id: String,
+    client_email: String,
+    token_uri: String,
+    key: RsaKeyPair,
+    access_token: CachedToken,
+}
+
+impl FcmNotifier {
+    pub fn new(service_account: &str) -> Result<Self, Error> {
+        let account = serde_json::from_str::<ServiceAccount>(service_account)
+            .map_err(|e| Error::new("failed to parse fcm service account").with_cause(e))?;
+        let key = RsaKeyPair::from_pkcs8(&pkcs8_der(&account.private_key)?)
+            .map_err(|e| Error::new("invalid fcm private key").with_cause(e))?;
+        Ok(Self {
+            client: reqwest::Client::new(),
+            project_id: account.project_id,
+            client_email: account.client_email,
+            token_uri: account.token_uri,
+            key,
+            access_token: CachedToken::default(),
+        })
+    }
+
+    async fn access_token(&self) -> Result<String, Error> {
+        if let Some(token) = self.access_token.get() {
+            return Ok(token);
+        }
+        let now = Utc::now().timestamp();
+        let assertion = sign_jwt(
+            &json!({"alg": "RS256", "typ": "JWT"}),
+            &json!({
+                "iss": self.client_email,
+                "scope": SCOPE,
+                "aud": self.token_uri,
+                "iat": now,
+                "exp": now + ASSERTION_SECS,
+            }),
+            |input| {
+                let mut signature = vec![0; self.key.public().modulus_len()];
+                self.key
+                    .sign(
+                        &RSA_PKCS1_SHA256,
+                        &SystemRandom::new(),
+                        input,
+                        &mut signature,
+                    )
+                    .map_err(|e| Error::new("failed to sign fcm assertion").with_cause(e))?;
+                Ok(signature)
+            },
+        )?;
+        let resp = self
+            .client
+            .post(&self.token_uri)
+            .form(&[
+                ("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"),
+                ("assertion", assertion.as_str()),
+            ])
+            .send()
+            .await
+            .map_err(|e| Error::new("failed to request fcm access token").with_cause(e))?
+            .error_for_status()
+            .map_err(|e| Error::new("failed to request fcm access token").with_cause(e))?
+            .json::<TokenResp>()
+            .await
+            .map_err(|e| Error::new("failed to parse fcm access token").with_cause(e))?;
+        self.access_token.set(
+            &resp.access_token,
+            Duration::from_secs(resp.expires_in.saturating_sub(TOKEN_EXPIRY_MARGIN_SECS)),
+        );
+        Ok(resp.access_token)
+    }
+}
+
+impl Notifier for FcmNotifier {
+    async fn push(
+        &self,
+        device: &DeviceToken,
+        message: &PushMessage,
+    ) -> Result<PushOutcome, Error> {
+        let resp = self
+            .client
+            .post(format!("{}/{}/messages:send", SEND_URL, self.project_id))
+            .bearer_auth(self.access_token().await?)
+            .json(&json!({
+                "message": {
+                    "token": device.token,
+                    "notification": {"title": message.title, "body": message.body},
+                    "data": push_data(message),
+                }
+            }))
+            .send()
+            .await
+            .map_err(|e| Error::new("failed to send fcm message").with_cause(e))?;
+        // 令牌已注销时返回404 UNREGISTERED
+        if resp.status() == StatusCode::NOT_FOUND {
+            return Ok(PushOutcome::Unregistered);
+        }
+        resp.error_for_status()
+            .map_err(|e| Error::new("failed to send fcm message").with_cause(e))?;
+        Ok(PushOutcome::Delivered)
+    }
+}
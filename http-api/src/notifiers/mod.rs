@@ -0,0 +1,105 @@
+pub mod apns;
+pub mod fcm;
+
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use base64::{engine::general_purpose::STANDARD, engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde_json::{json, Map, Value};
+
+use crate::core::{
+    entities::{DeviceToken, PushPlatform},
+    error::Error,
+    push::{Notifier, PushMessage, PushOutcome},
+};
+
+use self::{apns::ApnsNotifier, fcm::FcmNotifier};
+
+// 已配置的推送通道, 按设备平台选择. 未配置的平台只记录日志, 便于本地开发
+#[derive(Debug, Default)]
+pub struct Notifiers {
+    pub fcm: Option<FcmNotifier>,
+    pub apns: Option<ApnsNotifier>,
+}
+
+impl Notifier for Notifiers {
+    async fn push(
+        &self,
+        device: &DeviceToken,
+        message: &PushMessage,
+    ) -> Result<PushOutcome, Error> {
+        match (device.platform, &self.fcm, &self.apns) {
+            (PushPlatform::Android, Some(fcm), _) => fcm.push(device, message).await,
+            (PushPlatform::Ios, _, Some(apns)) => apns.push(device, message).await,
+            _ => {
+                log::info!(
+                    "push to {} device of {}: {}",
+                    device.platform,
+                    device.user_id,
+                    message.body
+                );
+                Ok(PushOutcome::Delivered)
+            }
+        }
+    }
+}
+
+// 随推送下发给App的自定义字段, FCM要求值均为字符串
+fn push_data(message: &PushMessage) -> Map<String, Value> {
+    let mut data = Map::new();
+    data.insert("kind".to_owned(), json!(message.kind.to_string()));
+    if let Some(walk_request_id) = &message.walk_request_id {
+        data.insert("walkRequestId".to_owned(), json!(walk_request_id));
+    }
+    data
+}
+
+// PEM格式的PKCS#8私钥解码为DER
+fn pkcs8_der(pem: &str) -> Result<Vec<u8>, Error> {
+    let body = pem
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with("-----"))
+        .collect::<String>();
+    STANDARD
+        .decode(body)
+        .map_err(|e| Error::new("failed to decode private key").with_cause(e))
+}
+
+// 按JWS紧凑格式签名, sign对"header.claims"签名并返回原始签名字节
+fn sign_jwt(
+    header: &Value,
+    claims: &Value,
+    sign: impl FnOnce(&[u8]) -> Result<Vec<u8>, Error>,
+) -> Result<String, Error> {
+    let input = format!(
+        "{}.{}",
+        URL_SAFE_NO_PAD.encode(header.to_string()),
+        URL_SAFE_NO_PAD.encode(claims.to_string())
+    );
+    let signature = sign(input.as_bytes())?;
+    Ok(format!("{}.{}", input, URL_SAFE_NO_PAD.encode(signature)))
+}
+
+// 推送通道的鉴权令牌, 到期前复用
+#[derive(Debug, Default)]
+struct CachedToken(Mutex<Option<(String, Instant)>>);
+
+impl CachedToken {
+    fn get(&self) -> Option<String> {
+        self.0
+            .lock()
+            .ok()?
+            .as_ref()
+            .filter(|(_, expires_at)| *expires_at > Instant::now())
+            .map(|(token, _)| token.clone())
+    }
+
+    fn set(&self, token: &str, ttl: Duration) {
+        if let Ok(mut cached) = self.0.lock() {
+            *cached = Some((token.to_owned(), Instant::now() + ttl));
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use jwt::{Header, Token, Unverified};
+use serde::Deserialize;
+
+use crate::core::{
+    error::Error,
+    oauth::{OAuthIdentity, OAuthProvider},
+};
+
+const TOKEN_URL: &str = "https://appleid.apple.com/auth/token";
+
+// Sign in with Apple. client_secret是用Apple私钥签名的ES256 JWT, 由运维生成后通过配置传入,
+// 最长有效期6个月
+#[derive(Debug, Clone)]
+pub struct AppleOAuthProvider {
+    client: reqwest::Client,
+    client_id: String,
+    client_secret: String,
+}
+
+impl AppleOAuthProvider {
+    pub fn new(client_id: &str, client_secret: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            client_id: client_id.to_owned(),
+            client_secret: client_secret.to_owned(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResp {
+    id_token: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct IdTokenClaims {
+    iss: String,
+    aud: String,
+    sub: String,
+}
+
+impl OAuthProvider for AppleOAuthProvider {
+    async fn exchange_code(&self, code: &str) -> Result<OAuthIdentity, Error> {
+        let resp = self
+            .client
+            .post(TOKEN_URL)
+            .form(&[
+                ("client_id", self.client_id.as_str()),
+                ("client_secret", self.client_secret.as_str()),
+                ("code", code),
+                ("grant_type", "authorization_code"),
+            ])
+            .send()
+            .await
+            .map_err(|e| Error::new("failed to exchange apple code").with_cause(e))?
+            .error_for_status()
+            .map_err(|e| Error::new("failed to exchange apple code").with_cause(e))?
+            .json::<TokenResp>()
+            .await
+            .map_err(|e| Error::new("failed to parse apple token").with_cause(e))?;
+        // id_token直接通过TLS从Apple令牌接口取得, 按OIDC规范可不再校验签名, 只核对签发方和受众
+        let token: Token<Header, IdTokenClaims, Unverified> =
+            Token::parse_unverified(&resp.id_token)
+                .map_err(|e| Error::new("failed to parse apple id token").with_cause(e))?;
+        let claims = token.claims();
+        if claims.iss != "https://appleid.apple.com" || claims.aud != self.client_id {
+            return Err(Error::new("invalid apple id token"));
+        }
+        Ok(OAuthIdentity {
+            subject: claims.sub.clone(),
+        })
+    }
+}
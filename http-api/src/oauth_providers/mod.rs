@@ -0,0 +1,36 @@
+pub mod apple;
+pub mod wechat;
+
+use crate::core::{
+    entities::OAuthProviderKind,
+    error::Error,
+    oauth::{OAuthIdentity, OAuthProvider},
+};
+
+use self::{apple::AppleOAuthProvider, wechat::WeChatOAuthProvider};
+
+// 已配置的第三方登录渠道, 未配置的渠道不可用
+#[derive(Debug, Clone, Default)]
+pub struct OAuthProviders {
+    pub wechat: Option<WeChatOAuthProvider>,
+    pub apple: Option<AppleOAuthProvider>,
+}
+
+impl OAuthProviders {
+    pub async fn exchange_code(
+        &self,
+        kind: OAuthProviderKind,
+        code: &str,
+    ) -> Result<OAuthIdentity, Error> {
+        match kind {
+            OAuthProviderKind::WeChat => match &self.wechat {
+                Some(provider) => provider.exchange_code(code).await,
+                None => Err(Error::msg("未开通微信登录")),
+            },
+            OAuthProviderKind::Apple => match &self.apple {
+                Some(provider) => provider.exchange_code(code).await,
+                None => Err(Error::msg("未开通Apple登录")),
+            },
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use serde::Deserialize;
+
+use crate::core::{
+    error::Error,
+    oauth::{OAuthIdentity, OAuthProvider},
+};
+
+const ACCESS_TOKEN_URL: &str = "https://api.weixin.qq.com/sns/oauth2/access_token";
+
+// 微信开放平台移动应用登录, 有unionid时优先使用unionid以便多个应用共享账号
+#[derive(Debug, Clone)]
+pub struct WeChatOAuthProvider {
+    client: reqwest::Client,
+    app_id: String,
+    app_secret: String,
+}
+
+impl WeChatOAuthProvider {
+    pub fn new(app_id: &str, app_secret: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            app_id: app_id.to_owned(),
+            app_secret: app_secret.to_owned(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct AccessTokenResp {
+    openid: Option<String>,
+    unionid: Option<String>,
+    errcode: Option<i64>,
+    errmsg: Option<String>,
+}
+
+impl OAuthProvider for WeChatOAuthProvider {
+    async fn exchange_code(&self, code: &str) -> Result<OAuthIdentity, Error> {
+        let resp = self
+            .client
+            .get(ACCESS_TOKEN_URL)
+            .query(&[
+                ("appid", self.app_id.as_str()),
+                ("secret", self.app_secret.as_str()),
+                ("code", code),
+                ("grant_type", "authorization_code"),
+            ])
+            .send()
+            .await
+            .map_err(|e| Error::new("failed to exchange wechat code").with_cause(e))?
+            .error_for_status()
+            .map_err(|e| Error::new("failed to exchange wechat code").with_cause(e))?
+            .json::<AccessTokenResp>()
+            .await
+            .map_err(|e| Error::new("failed to parse wechat access token").with_cause(e))?;
+        // 微信接口出错时同样返回200, 错误码在响应体中
+        if let Some(errcode) = resp.errcode.filter(|c| *c != 0) {
+            return Err(
+                Error::new("failed to exchange wechat code").with_cause(format!(
+                    "{}: {}",
+                    errcode,
+                    resp.errmsg.unwrap_or_default()
+                )),
+            );
+        }
+        resp.unionid
+            .or(resp.openid)
+            .map(|subject| OAuthIdentity { subject })
+            .ok_or(Error::new("failed to exchange wechat code").with_cause("missing openid"))
+    }
+}
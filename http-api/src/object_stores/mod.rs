@@ -0,0 +1,40 @@
+pub mod s3;
+
+use std::time::Duration;
+
+use crate::core::{
+    error::Error,
+    object_store::{ObjectStore, StoredObject},
+};
+
+use self::s3::S3ObjectStore;
+
+// 未配置对象存储时不开放直传
+#[derive(Debug, Clone)]
+pub enum ObjectStores {
+    S3(S3ObjectStore),
+    Disabled,
+}
+
+impl ObjectStore for ObjectStores {
+    fn presign_put(&self, key: &str, expires_in: Duration) -> Result<String, Error> {
+        match self {
+            ObjectStores::S3(store) => store.presign_put(key, expires_in),
+            ObjectStores::Disabled => Err(Error::new("object store not configured")),
+        }
+    }
+
+    fn presign_get(&self, key: &str, expires_in: Duration) -> Result<String, Error> {
+        match self {
+            ObjectStores::S3(store) => store.presign_get(key, expires_in),
+            ObjectStores::Disabled => Err(Error::new("object store not configured")),
+        }
+    }
+
+    async fn head(&self, key: &str) -> Result<Option<StoredObject>, Error> {
+        match self {
+            ObjectStores::S3(store) => store.head(key).await,
+            ObjectStores::Disabled => Err(Error::new("object store not configured")),
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use reqwest::{header::CONTENT_TYPE, StatusCode, Url};
+use sha2::{Digest, Sha256};
+
+use crate::core::{
+    error::Error,
+    object_store::{ObjectStore, StoredObject},
+};
+
+// S3兼容的对象存储, 使用path-style地址{endpoint}/{bucket}/{key}和SigV4查询参数签名.
+// 只签名host头, 不签名请求体(UNSIGNED-PAYLOAD), 上传的大小和类型在确认时通过HEAD核对
+#[derive(Debug, Clone)]
+pub struct S3ObjectStore {
+    client: reqwest::Client,
+    endpoint: Url,
+    bucket: String,
+    region: String,
+    access_key: String,
+    secret_key: String,
+}
+
+impl S3ObjectStore {
+    pub fn new(
+        endpoint: &str,
+        bucket: &str,
+        region: &str,
+        access_key: &str,
+        secret_key: &str,
+    ) -> Result<Self, Error> {
+        let endpoint = Url::parse(endpoint)
+            .map_err(|e| Error::new("invalid object store endpoint").with_cause(e))?;
+        if endpoint.host_str().is_none() {
+            return Err(Error::new("invalid object store endpoint").with_cause("missing host"));
+        }
+        Ok(Self {
+            client: reqwest::Client::new(),
+            endpoint,
+            bucket: bucket.to_owned(),
+            region: region.to_owned(),
+            access_key: access_key.to_owned(),
+            secret_key: secret_key.to_owned(),
+        })
+    }
+
+    fn presign(&self, method: &str, key: &str, expires_in: Duration) -> Result<String, Error> {
+        let now = Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let host = match (self.endpoint.host_str(), self.endpoint.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_owned(),
+            (None, _) => return Err(Error::new("invalid object store endpoint")),
+        };
+        let path = format!(
+            "{}/{}/{}",
+            self.endpoint.path().trim_end_matches('/'),
+            uri_encode(&self.bucket, false),
+            uri_encode(key, true)
+        );
+        // 查询参数需按名称排序, 此处已按字典序排列
+        let query = [
+            ("X-Amz-Algorithm", "AWS4-HMAC-SHA256".to_owned()),
+            ("X-Amz-Credential", format!("{}/{}", self.access_key, scope)),
+            ("X-Amz-Date", amz_date.clone()),
+            ("X-Amz-Expires", expires_in.as_secs().to_string()),
+            ("X-Amz-SignedHeaders", "host".to_owned()),
+        ]
+        .iter()
+        .map(|(k, v)| format!("{}={}", k, uri_encode(v, false)))
+        .collect::<Vec<String>>()
+        .join("&");
+        let canonical_request = format!(
+            "{}\n{}\n{}\nhost:{}\n\nhost\nUNSIGNED-PAYLOAD",
+            method, path, query, host
+        );
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex(&Sha256::digest(canonical_request.as_bytes()))
+        );
+        let signing_key = [self.region.as_str(), "s3", "aws4_request"]
+            .iter()
+            .try_fold(
+                hmac_sha256(
+                    format!("AWS4{}", self.secret_key).as_bytes(),
+                    date.as_bytes(),
+                )?,
+                |key, part| hmac_sha256(&key, part.as_bytes()),
+            )?;
+        let signature = hex(&hmac_sha256(&signing_key, string_to_sign.as_bytes())?);
+        Ok(format!(
+            "{}://{}{}?{}&X-Amz-Signature={}",
+            self.endpoint.scheme(),
+            host,
+            path,
+            query,
+            signature
+        ))
+    }
+}
+
+impl ObjectStore for S3ObjectStore {
+    fn presign_put(&self, key: &str, expires_in: Duration) -> Result<String, Error> {
+        self.presign("PUT", key, expires_in)
+    }
+
+    fn presign_get(&self, key: &str, expires_in: Duration) -> Result<String, Error> {
+        self.presign("GET", key, expires_in)
+    }
+
+    async fn head(&self, key: &str) -> Result<Option<StoredObject>, Error> {
+        let url = self.presign("HEAD", key, Duration::from_secs(60))?;
+        let resp = self
+            .client
+            .head(url)
+            .send()
+            .await
+            .map_err(|e| Error::new("failed to head object").with_cause(e))?;
+        if resp.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let resp = resp
+            .error_for_status()
+            .map_err(|e| Error::new("failed to head object").with_cause(e))?;
+        let size = resp
+            .content_length()
+            .ok_or(Error::new("failed to head object").with_cause("missing content length"))?;
+        Ok(Some(StoredObject {
+            size: size as i64,
+            content_type: resp
+                .headers()
+                .get(CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.to_owned()),
+        }))
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key)
+        .map_err(|e| Error::new("failed to create signing key").with_cause(e))?;
+    mac.update(data);
+    Ok(mac.finalize().into_bytes().to_vec())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// SigV4要求的URI编码, 只保留unreserved字符, 对象键中的'/'不编码
+fn uri_encode(s: &str, keep_slash: bool) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            b'/' if keep_slash => "/".to_owned(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
@@ -0,0 +1,74 @@
+use crate::core::{
+    error::Error,
+    payout::{PayoutDestination, PayoutOrder, PayoutProvider, PayoutRegistration, PayoutTransfer},
+};
+
+// 通过打款服务登记收款账户, 支付宝、微信和银行卡由打款服务对接各渠道完成核验.
+// POST {api_url}/destinations登记, GET {api_url}/destinations/{reference}查询, 均返回{reference, status, failure_reason}.
+// POST {api_url}/payouts发起打款, 以withdrawal_id作为Idempotency-Key, 最终结果回调/webhooks/payouts
+#[derive(Debug, Clone)]
+pub struct HttpPayoutProvider {
+    client: reqwest::Client,
+    api_url: String,
+    api_key: String,
+}
+
+impl HttpPayoutProvider {
+    pub fn new(api_url: &str, api_key: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            api_url: api_url.trim_end_matches('/').to_owned(),
+            api_key: api_key.to_owned(),
+        }
+    }
+}
+
+impl PayoutProvider for HttpPayoutProvider {
+    async fn register_destination(
+        &self,
+        destination: &PayoutDestination,
+    ) -> Result<PayoutRegistration, Error> {
+        self.client
+            .post(format!("{}/destinations", self.api_url))
+            .bearer_auth(&self.api_key)
+            .json(destination)
+            .send()
+            .await
+            .map_err(|e| Error::new("failed to register payout destination").with_cause(e))?
+            .error_for_status()
+            .map_err(|e| Error::new("failed to register payout destination").with_cause(e))?
+            .json::<PayoutRegistration>()
+            .await
+            .map_err(|e| Error::new("failed to parse payout registration").with_cause(e))
+    }
+
+    async fn destination_status(&self, reference: &str) -> Result<PayoutRegistration, Error> {
+        self.client
+            .get(format!("{}/destinations/{}", self.api_url, reference))
+            .bearer_auth(&self.api_key)
+            .send()
+            .await
+            .map_err(|e| Error::new("failed to query payout destination").with_cause(e))?
+            .error_for_status()
+            .map_err(|e| Error::new("failed to query payout destination").with_cause(e))?
+            .json::<PayoutRegistration>()
+            .await
+            .map_err(|e| Error::new("failed to parse payout registration").with_cause(e))
+    }
+
+    async fn execute_payout(&self, order: &PayoutOrder) -> Result<PayoutTransfer, Error> {
+        self.client
+            .post(format!("{}/payouts", self.api_url))
+            .bearer_auth(&self.api_key)
+            .header("Idempotency-Key", &order.withdrawal_id)
+            .json(order)
+            .send()
+            .await
+            .map_err(|e| Error::new("failed to execute payout").with_cause(e))?
+            .error_for_status()
+            .map_err(|e| Error::new("failed to execute payout").with_cause(e))?
+            .json::<PayoutTransfer>()
+            .await
+            .map_err(|e| Error::new("failed to parse payout transfer").with_cause(e))
+    }
+}
@@ -0,0 +1,42 @@
+pub mod http;
+pub mod stub;
+
+use crate::core::{
+    error::Error,
+    payout::{PayoutDestination, PayoutOrder, PayoutProvider, PayoutRegistration, PayoutTransfer},
+};
+
+use self::{http::HttpPayoutProvider, stub::StubPayoutProvider};
+
+// 根据配置在打款服务和桩实现之间选择
+#[derive(Debug, Clone)]
+pub enum PayoutProviders {
+    Http(HttpPayoutProvider),
+    Stub(StubPayoutProvider),
+}
+
+impl PayoutProvider for PayoutProviders {
+    async fn register_destination(
+        &self,
+        destination: &PayoutDestination,
+    ) -> Result<PayoutRegistration, Error> {
+        match self {
+            PayoutProviders::Http(provider) => provider.register_destination(destination).await,
+            PayoutProviders::Stub(provider) => provider.register_destination(destination).await,
+        }
+    }
+
+    async fn destination_status(&self, reference: &str) -> Result<PayoutRegistration, Error> {
+        match self {
+            PayoutProviders::Http(provider) => provider.destination_status(reference).await,
+            PayoutProviders::Stub(provider) => provider.destination_status(reference).await,
+        }
+    }
+
+    async fn execute_payout(&self, order: &PayoutOrder) -> Result<PayoutTransfer, Error> {
+        match self {
+            PayoutProviders::Http(provider) => provider.execute_payout(order).await,
+            PayoutProviders::Stub(provider) => provider.execute_payout(order).await,
+        }
+    }
+}
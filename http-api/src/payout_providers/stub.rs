@@ -0,0 +1,40 @@
+use chrono::Utc;
+
+use crate::core::{
+    entities::{PayoutAccountStatus, WithdrawalStatus},
+    error::Error,
+    payout::{PayoutDestination, PayoutOrder, PayoutProvider, PayoutRegistration, PayoutTransfer},
+};
+
+// 未配置打款服务时使用, 登记即视为核验通过, 打款立即成功, 仅用于本地开发
+#[derive(Debug, Clone, Default)]
+pub struct StubPayoutProvider;
+
+impl PayoutProvider for StubPayoutProvider {
+    async fn register_destination(
+        &self,
+        _destination: &PayoutDestination,
+    ) -> Result<PayoutRegistration, Error> {
+        Ok(PayoutRegistration {
+            reference: format!("stub-{}", Utc::now().timestamp_millis()),
+            status: PayoutAccountStatus::Verified,
+            failure_reason: None,
+        })
+    }
+
+    async fn destination_status(&self, reference: &str) -> Result<PayoutRegistration, Error> {
+        Ok(PayoutRegistration {
+            reference: reference.to_owned(),
+            status: PayoutAccountStatus::Verified,
+            failure_reason: None,
+        })
+    }
+
+    async fn execute_payout(&self, order: &PayoutOrder) -> Result<PayoutTransfer, Error> {
+        Ok(PayoutTransfer {
+            reference: format!("stub-{}", order.withdrawal_id),
+            status: WithdrawalStatus::Paid,
+            failure_reason: None,
+        })
+    }
+}
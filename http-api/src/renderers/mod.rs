@@ -0,0 +1,23 @@
+pub mod pdf;
+
+use crate::core::{
+    entities::WalkRequestExport,
+    error::Error,
+    export::{ExportFormat, Renderer},
+};
+
+use self::pdf::PdfRenderer;
+
+// 按导出格式选择渲染器
+#[derive(Debug, Clone, Default)]
+pub struct Renderers {
+    pdf: PdfRenderer,
+}
+
+impl Renderer for Renderers {
+    fn render(&self, format: ExportFormat, export: &WalkRequestExport) -> Result<Vec<u8>, Error> {
+        match format {
+            ExportFormat::Pdf => Ok(self.pdf.render(export)),
+        }
+    }
+}
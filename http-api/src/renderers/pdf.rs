@@ -0,0 +1,253 @@
+use std::mem;
+
+use chrono::{DateTime, Utc};
+
+use crate::core::entities::{Gender, WalkRequestExport};
+
+// A4纸面, 单位为点(1/72英寸)
+const PAGE_WIDTH: f64 = 595.0;
+const PAGE_HEIGHT: f64 = 842.0;
+const MARGIN: f64 = 50.0;
+const TITLE_SIZE: f64 = 18.0;
+const HEADING_SIZE: f64 = 14.0;
+const BODY_SIZE: f64 = 11.0;
+const LINE_SPACING: f64 = 1.6;
+
+// 使用阅读器内置的宋体(STSong-Light)而不嵌入字体文件, ASCII字符按半角宽度排版
+#[derive(Debug, Clone, Default)]
+pub struct PdfRenderer;
+
+struct Line {
+    size: f64,
+    text: String,
+}
+
+impl Line {
+    fn new(size: f64, text: impl Into<String>) -> Self {
+        Self {
+            size,
+            text: text.into(),
+        }
+    }
+}
+
+impl PdfRenderer {
+    pub fn render(&self, export: &WalkRequestExport) -> Vec<u8> {
+        let lines = summary_lines(export)
+            .into_iter()
+            .flat_map(wrap)
+            .collect::<Vec<Line>>();
+        document(&page_contents(lines))
+    }
+}
+
+fn summary_lines(export: &WalkRequestExport) -> Vec<Line> {
+    let request = &export.request;
+    let mut lines = vec![
+        Line::new(TITLE_SIZE, "遛狗请求摘要"),
+        Line::new(BODY_SIZE, format!("请求编号: {}", request.id)),
+        Line::new(
+            BODY_SIZE,
+            format!("生成时间: {}", format_time(Some(export.generated_at))),
+        ),
+        Line::new(HEADING_SIZE, "日程"),
+        Line::new(
+            BODY_SIZE,
+            format!(
+                "开始时间: {} 至 {}",
+                format_time(request.should_start_after),
+                format_time(request.should_start_before)
+            ),
+        ),
+        Line::new(
+            BODY_SIZE,
+            format!(
+                "结束时间: {} 至 {}",
+                format_time(request.should_end_after),
+                format_time(request.should_end_before)
+            ),
+        ),
+        Line::new(
+            BODY_SIZE,
+            format!(
+                "接狗地点: 纬度 {:.6}, 经度 {:.6}",
+                request.latitude, request.longitude
+            ),
+        ),
+        Line::new(HEADING_SIZE, "狗狗"),
+    ];
+    for dog in &export.dogs {
+        let gender = match dog.gender {
+            Gender::Male => "公",
+            Gender::Female => "母",
+            Gender::Other => "未知",
+        };
+        lines.push(Line::new(
+            BODY_SIZE,
+            format!(
+                "{} ({}, {}, {}出生)",
+                dog.name,
+                gender,
+                dog.breed.name,
+                dog.birthday.format("%Y-%m-%d")
+            ),
+        ));
+        if !dog.tags.is_empty() {
+            lines.push(Line::new(
+                BODY_SIZE,
+                format!("    标签: {}", dog.tags.join("、")),
+            ));
+        }
+        let medical_flags = if dog.medical_flags.is_empty() {
+            "无".to_owned()
+        } else {
+            dog.medical_flags.join("、")
+        };
+        lines.push(Line::new(
+            BODY_SIZE,
+            format!("    健康状况: {}", medical_flags),
+        ));
+    }
+    lines.push(Line::new(HEADING_SIZE, "紧急联系人"));
+    lines.push(Line::new(
+        BODY_SIZE,
+        format!(
+            "狗狗主人: {}",
+            export.owner_nickname.as_deref().unwrap_or("未填写")
+        ),
+    ));
+    if export.emergency_contacts.is_empty() {
+        lines.push(Line::new(BODY_SIZE, "未填写紧急联系人"));
+    }
+    for contact in &export.emergency_contacts {
+        let relationship = contact
+            .relationship
+            .as_deref()
+            .map(|r| format!(" ({})", r))
+            .unwrap_or_default();
+        lines.push(Line::new(
+            BODY_SIZE,
+            format!("{}{}: {}", contact.name, relationship, contact.phone),
+        ));
+    }
+    lines
+}
+
+fn format_time(time: Option<DateTime<Utc>>) -> String {
+    time.map(|t| t.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or("未指定".to_owned())
+}
+
+// 与字体的/W设置一致: ASCII为半角, 其余字符为全角
+fn char_width(c: char) -> f64 {
+    if c.is_ascii() {
+        0.5
+    } else {
+        1.0
+    }
+}
+
+// 超出版心宽度时按字符折行
+fn wrap(line: Line) -> Vec<Line> {
+    let max_width = PAGE_WIDTH - 2.0 * MARGIN;
+    let mut wrapped = Vec::new();
+    let mut current = String::new();
+    let mut width = 0.0;
+    for c in line.text.chars() {
+        let w = char_width(c) * line.size;
+        if width + w > max_width && !current.is_empty() {
+            wrapped.push(Line::new(line.size, mem::take(&mut current)));
+            width = 0.0;
+        }
+        current.push(c);
+        width += w;
+    }
+    wrapped.push(Line::new(line.size, current));
+    wrapped
+}
+
+// 每页一个内容流, 文本以UCS-2编码的十六进制字符串写入
+fn page_contents(lines: Vec<Line>) -> Vec<String> {
+    let mut pages = Vec::new();
+    let mut content = String::new();
+    let mut y = PAGE_HEIGHT - MARGIN;
+    for line in lines {
+        let height = line.size * LINE_SPACING;
+        if y - height < MARGIN && !content.is_empty() {
+            pages.push(mem::take(&mut content));
+            y = PAGE_HEIGHT - MARGIN;
+        }
+        y -= height;
+        content.push_str(&format!(
+            "BT /F1 {:.2} Tf {:.2} {:.2} Td <{}> Tj ET\n",
+            line.size,
+            MARGIN,
+            y,
+            encode(&line.text)
+        ));
+    }
+    pages.push(content);
+    pages
+}
+
+// UniGB-UCS2-H只覆盖基本多文种平面, 其余字符以问号代替
+fn encode(text: &str) -> String {
+    text.chars()
+        .map(|c| {
+            if (c as u32) <= 0xFFFF {
+                c as u32
+            } else {
+                '?' as u32
+            }
+        })
+        .map(|u| format!("{:04X}", u))
+        .collect()
+}
+
+// 对象1为目录, 2为页面树, 3-5为字体, 之后每页依次为页面对象和内容流
+fn document(pages: &[String]) -> Vec<u8> {
+    let kids = (0..pages.len())
+        .map(|i| format!("{} 0 R", 6 + i * 2))
+        .collect::<Vec<String>>()
+        .join(" ");
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_owned(),
+        format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids, pages.len()),
+        "<< /Type /Font /Subtype /Type0 /BaseFont /STSong-Light /Encoding /UniGB-UCS2-H /DescendantFonts [4 0 R] >>".to_owned(),
+        "<< /Type /Font /Subtype /CIDFontType0 /BaseFont /STSong-Light /CIDSystemInfo << /Registry (Adobe) /Ordering (GB1) /Supplement 4 >> /FontDescriptor 5 0 R /DW 1000 /W [1 95 500] >>".to_owned(),
+        "<< /Type /FontDescriptor /FontName /STSong-Light /Flags 6 /FontBBox [-25 -254 1000 880] /ItalicAngle 0 /Ascent 880 /Descent -120 /CapHeight 880 /StemV 93 >>".to_owned(),
+    ];
+    for (i, content) in pages.iter().enumerate() {
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+            PAGE_WIDTH,
+            PAGE_HEIGHT,
+            7 + i * 2
+        ));
+        objects.push(format!(
+            "<< /Length {} >>\nstream\n{}endstream",
+            content.len(),
+            content
+        ));
+    }
+    let mut pdf = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::new();
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend(format!("{} 0 obj\n{}\nendobj\n", i + 1, object).as_bytes());
+    }
+    let xref = pdf.len();
+    pdf.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes());
+    for offset in offsets {
+        pdf.extend(format!("{:010} 00000 n \n", offset).as_bytes());
+    }
+    pdf.extend(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref
+        )
+        .as_bytes(),
+    );
+    pdf
+}
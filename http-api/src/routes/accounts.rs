@@ -0,0 +1,129 @@
+use actix_web::web::{delete, get, post, put, scope, ServiceConfig};
+use auth_service::{
+    hashers::sha::ShaHasher, repositories::mongo::MongodbRepository,
+    token_managers::jwt::JWTTokenManager,
+};
+use hmac::Hmac;
+use sha2::Sha384;
+use upload_service::{repositories::mongo::Mongo, stores::local_fs::LocalFSStore};
+
+use crate::{
+    handlers::{account, auth, notification, owner, partner, session},
+    repositories::audited::AuditedMongoDB,
+    sms_senders::http::HttpSmsSender,
+    translation_providers::TranslationProviders,
+};
+
+pub fn configure(cfg: &mut ServiceConfig) {
+    cfg.service(
+        scope("accounts")
+            .route("me", delete().to(account::delete_me::<AuditedMongoDB>))
+            .route(
+                "me/avatar",
+                put().to(account::update_avatar::<AuditedMongoDB, Mongo, LocalFSStore>),
+            )
+            .route(
+                "me/action_tokens",
+                post().to(auth::issue_action_token::<
+                    MongodbRepository,
+                    ShaHasher,
+                    JWTTokenManager<Hmac<Sha384>>,
+                    AuditedMongoDB,
+                >),
+            )
+            .route(
+                "me/password",
+                put().to(auth::change_password::<
+                    MongodbRepository,
+                    ShaHasher,
+                    JWTTokenManager<Hmac<Sha384>>,
+                    AuditedMongoDB,
+                >),
+            )
+            .route(
+                "me/phone_change_code",
+                put().to(auth::send_phone_change_code::<
+                    MongodbRepository,
+                    ShaHasher,
+                    JWTTokenManager<Hmac<Sha384>>,
+                    AuditedMongoDB,
+                    HttpSmsSender,
+                >),
+            )
+            .route(
+                "me/phone",
+                put().to(auth::change_phone::<
+                    MongodbRepository,
+                    ShaHasher,
+                    JWTTokenManager<Hmac<Sha384>>,
+                    AuditedMongoDB,
+                >),
+            ),
+    )
+    .service(
+        scope("sessions")
+            .route("", get().to(session::sessions::<AuditedMongoDB>))
+            .route(
+                "{id}",
+                delete().to(session::revoke_session::<AuditedMongoDB>),
+            ),
+    )
+    .service(scope("devices").route(
+        "{device_id}",
+        delete().to(session::revoke_device::<AuditedMongoDB>),
+    ))
+    .service(scope("notifications").route(
+        "mine",
+        get().to(notification::my_notifications::<AuditedMongoDB>),
+    ))
+    .service(
+        scope("notification_settings")
+            .route(
+                "mine",
+                get().to(notification::my_notification_settings::<AuditedMongoDB>),
+            )
+            .route(
+                "mine",
+                put().to(notification::update_notification_settings::<AuditedMongoDB>),
+            ),
+    )
+    .service(
+        scope("push_tokens")
+            .route(
+                "",
+                put().to(notification::register_push_token::<AuditedMongoDB>),
+            )
+            .route(
+                "{token}",
+                delete().to(notification::unregister_push_token::<AuditedMongoDB>),
+            ),
+    )
+    .service(
+        scope("owners")
+            .route(
+                "me",
+                put().to(owner::update_my_profile::<AuditedMongoDB, Mongo, LocalFSStore>),
+            )
+            .route("{id}", get().to(owner::owner_profile::<AuditedMongoDB>))
+            .route(
+                "{id}/reputation",
+                get().to(owner::owner_reputation::<AuditedMongoDB>),
+            )
+            .route(
+                "{id}/reviews",
+                get().to(owner::owner_reviews::<AuditedMongoDB, TranslationProviders>),
+            ),
+    )
+    .service(
+        scope("partner_consents")
+            .route("", get().to(partner::my_consents::<AuditedMongoDB>))
+            .route(
+                "{partner_id}",
+                put().to(partner::grant_consent::<AuditedMongoDB>),
+            )
+            .route(
+                "{partner_id}",
+                delete().to(partner::revoke_consent::<AuditedMongoDB>),
+            ),
+    );
+}
@@ -0,0 +1,145 @@
+use actix_web::web::{delete, get, post, put, scope, ServiceConfig};
+use upload_service::{repositories::mongo::Mongo, stores::local_fs::LocalFSStore};
+
+use crate::{
+    handlers::{admin, breed, help, poi, refund, report, ticket, walker, withdrawal},
+    repositories::audited::AuditedMongoDB,
+};
+
+pub fn configure(cfg: &mut ServiceConfig) {
+    cfg.route(
+        "invites/conversion",
+        get().to(admin::invite_conversion::<AuditedMongoDB>),
+    )
+    .service(
+        scope("reports")
+            .route("", get().to(report::reports::<AuditedMongoDB>))
+            .route("{id}", put().to(report::review_report::<AuditedMongoDB>)),
+    )
+    .service(
+        scope("pois")
+            .route("", post().to(poi::create_poi::<AuditedMongoDB>))
+            .route("", get().to(poi::pois::<AuditedMongoDB>))
+            .route("{id}", delete().to(poi::delete_poi::<AuditedMongoDB>)),
+    )
+    .service(
+        scope("help/articles")
+            .route("{slug}", put().to(help::publish::<AuditedMongoDB>))
+            .route(
+                "{slug}/versions",
+                get().to(help::versions::<AuditedMongoDB>),
+            ),
+    )
+    .service(
+        scope("breeds")
+            .route(
+                "{id}/care_tips/{locale}",
+                put().to(breed::update_care_tips::<AuditedMongoDB>),
+            )
+            .route(
+                "{id}/care_tips/{locale}",
+                delete().to(breed::delete_care_tips::<AuditedMongoDB>),
+            ),
+    )
+    .service(
+        scope("support/tickets")
+            .route("", get().to(ticket::tickets::<AuditedMongoDB>))
+            .route("{id}", get().to(ticket::ticket::<AuditedMongoDB>))
+            .route(
+                "{id}/messages",
+                post().to(ticket::staff_reply::<AuditedMongoDB, Mongo, LocalFSStore>),
+            )
+            .route(
+                "{id}/assignee/{user_id}",
+                put().to(ticket::assign::<AuditedMongoDB>),
+            )
+            .route(
+                "{id}/status",
+                put().to(ticket::update_status::<AuditedMongoDB>),
+            ),
+    )
+    .service(
+        scope("withdrawals")
+            .route("", get().to(withdrawal::withdrawals::<AuditedMongoDB>))
+            .route("approval", put().to(withdrawal::approve::<AuditedMongoDB>))
+            .route("rejection", put().to(withdrawal::reject::<AuditedMongoDB>)),
+    )
+    .service(
+        scope("refund_requests")
+            .route("", get().to(refund::refund_requests::<AuditedMongoDB>))
+            .route(
+                "{id}/approval",
+                put().to(refund::approve_refund::<AuditedMongoDB>),
+            )
+            .route(
+                "{id}/rejection",
+                put().to(refund::reject_refund::<AuditedMongoDB>),
+            ),
+    )
+    .service(
+        scope("db")
+            .route("explain", post().to(admin::explain::<AuditedMongoDB>))
+            .route(
+                "purge_deleted",
+                post().to(admin::purge_deleted::<AuditedMongoDB>),
+            )
+            .route(
+                "integrity",
+                get().to(admin::integrity_report::<AuditedMongoDB>),
+            ),
+    )
+    .route(
+        "location_accesses",
+        get().to(admin::location_accesses::<AuditedMongoDB>),
+    )
+    .route("audit_logs", get().to(admin::audit_logs::<AuditedMongoDB>))
+    .route(
+        "operations/feed",
+        get().to(admin::operations_feed::<AuditedMongoDB>),
+    )
+    .service(
+        scope("account_merges")
+            .route("", post().to(admin::merge_accounts::<AuditedMongoDB>))
+            .route(
+                "preview",
+                post().to(admin::preview_account_merge::<AuditedMongoDB>),
+            ),
+    )
+    .service(
+        scope("walkers")
+            .route(
+                "verifications",
+                get().to(walker::verifications::<AuditedMongoDB>),
+            )
+            .route(
+                "{id}/verification/approval",
+                put().to(walker::approve_verification::<AuditedMongoDB>),
+            )
+            .route(
+                "{id}/verification/rejection",
+                put().to(walker::reject_verification::<AuditedMongoDB>),
+            ),
+    )
+    .service(
+        scope("partners")
+            .route("", post().to(admin::create_partner::<AuditedMongoDB>))
+            .route("", get().to(admin::partners::<AuditedMongoDB>))
+            .route("{id}", put().to(admin::update_partner::<AuditedMongoDB>))
+            .route(
+                "{id}/api_keys",
+                post().to(admin::issue_partner_api_key::<AuditedMongoDB>),
+            )
+            .route(
+                "{id}/api_keys",
+                get().to(admin::partner_api_keys::<AuditedMongoDB>),
+            )
+            .route(
+                "{id}/api_keys/{key_id}",
+                delete().to(admin::revoke_partner_api_key::<AuditedMongoDB>),
+            )
+            .route(
+                "{id}/usage",
+                get().to(admin::partner_usage::<AuditedMongoDB>),
+            ),
+    );
+}
@@ -0,0 +1,125 @@
+use actix_web::web::{delete, get, post, put, ServiceConfig};
+use auth_service::{
+    hashers::sha::ShaHasher, repositories::mongo::MongodbRepository,
+    token_managers::jwt::JWTTokenManager,
+};
+use hmac::Hmac;
+use sha2::Sha384;
+
+use crate::{
+    handlers::auth, repositories::audited::AuditedMongoDB, sms_senders::http::HttpSmsSender,
+};
+
+pub fn configure(cfg: &mut ServiceConfig) {
+    cfg.route(
+        "/login",
+        put().to(auth::login_by_password::<
+            MongodbRepository,
+            ShaHasher,
+            JWTTokenManager<Hmac<Sha384>>,
+            AuditedMongoDB,
+        >),
+    )
+    .route(
+        "/login/otp",
+        post().to(auth::login_by_otp::<
+            MongodbRepository,
+            ShaHasher,
+            JWTTokenManager<Hmac<Sha384>>,
+            AuditedMongoDB,
+        >),
+    )
+    .route(
+        "/login/oauth/{provider}",
+        post().to(auth::login_by_oauth::<
+            MongodbRepository,
+            ShaHasher,
+            JWTTokenManager<Hmac<Sha384>>,
+            AuditedMongoDB,
+        >),
+    )
+    .route(
+        "/oauth_accounts",
+        post().to(auth::link_oauth_account::<
+            MongodbRepository,
+            ShaHasher,
+            JWTTokenManager<Hmac<Sha384>>,
+            AuditedMongoDB,
+        >),
+    )
+    .route(
+        "/phones/{phone}/otp",
+        put().to(auth::send_otp::<AuditedMongoDB, HttpSmsSender>),
+    )
+    .route(
+        "/phones/{phone}/password_reset_code",
+        put().to(auth::send_password_reset_code::<
+            MongodbRepository,
+            ShaHasher,
+            JWTTokenManager<Hmac<Sha384>>,
+            AuditedMongoDB,
+            HttpSmsSender,
+        >),
+    )
+    .route(
+        "/password_resets",
+        post().to(auth::verify_password_reset_code::<AuditedMongoDB>),
+    )
+    .route(
+        "/passwords",
+        put().to(auth::reset_password::<
+            MongodbRepository,
+            ShaHasher,
+            JWTTokenManager<Hmac<Sha384>>,
+            AuditedMongoDB,
+        >),
+    )
+    .route(
+        "/signup",
+        post().to(auth::signup::<
+            MongodbRepository,
+            ShaHasher,
+            JWTTokenManager<Hmac<Sha384>>,
+            AuditedMongoDB,
+        >),
+    )
+    .route(
+        "/tokens/refresh",
+        post().to(auth::refresh_token::<AuditedMongoDB>),
+    )
+    .route(
+        "/tokens/current",
+        delete().to(auth::revoke_current_token::<AuditedMongoDB>),
+    )
+    .route(
+        "/tokens/{token}/verification",
+        get().to(auth::verify_token::<AuditedMongoDB>),
+    )
+    .route(
+        "/phones/{phone}/exists",
+        get().to(auth::exists_user::<
+            MongodbRepository,
+            ShaHasher,
+            JWTTokenManager<Hmac<Sha384>>,
+            AuditedMongoDB,
+        >),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http::StatusCode, test, App};
+
+    // 访问令牌只能通过密码、验证码、第三方登录或刷新令牌获取, 仅凭手机号不能换取令牌
+    #[actix_web::test]
+    async fn phone_number_alone_cannot_obtain_a_token() {
+        let app = test::init_service(App::new().configure(super::configure)).await;
+        let req = test::TestRequest::put()
+            .uri("/phones/13800138000/tokens")
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::NOT_FOUND
+        );
+    }
+}
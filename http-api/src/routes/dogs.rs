@@ -0,0 +1,60 @@
+use actix_web::web::{delete, get, post, put, resource, scope, ServiceConfig};
+use upload_service::{repositories::mongo::Mongo, stores::local_fs::LocalFSStore};
+
+use crate::{
+    handlers::{breed, dog},
+    inference_providers::InferenceProviders,
+    repositories::audited::AuditedMongoDB,
+};
+
+pub fn configure(cfg: &mut ServiceConfig) {
+    cfg.service(
+        resource("breeds")
+            .post(breed::create_breed::<AuditedMongoDB>)
+            .get(breed::breeds::<AuditedMongoDB>),
+    )
+    .route("breeds/{id}", get().to(breed::breed::<AuditedMongoDB>))
+    .service(
+        scope("dogs")
+            .route("", post().to(dog::create_dog::<AuditedMongoDB>))
+            .route("", get().to(dog::dogs::<AuditedMongoDB>))
+            .route("", put().to(dog::update_dog::<AuditedMongoDB>))
+            .route("mine", get().to(dog::my_dogs::<AuditedMongoDB>))
+            .route(
+                "breed_suggestions",
+                post().to(dog::breed_suggestions::<
+                    AuditedMongoDB,
+                    Mongo,
+                    LocalFSStore,
+                    InferenceProviders,
+                >),
+            )
+            .route(
+                "exists",
+                get().to(dog::is_owner_of_the_dog::<AuditedMongoDB>),
+            )
+            .route(
+                "{id}/portrait",
+                put().to(dog::update_dog_portrait::<
+                    AuditedMongoDB,
+                    Mongo,
+                    LocalFSStore,
+                    InferenceProviders,
+                >),
+            )
+            .route(
+                "{id}/photos",
+                post().to(dog::add_dog_photo::<AuditedMongoDB, Mongo, LocalFSStore>),
+            )
+            .route(
+                "{id}/photos",
+                put().to(dog::reorder_dog_photos::<AuditedMongoDB>),
+            )
+            .route(
+                "{id}/photos/{photo_id}",
+                delete().to(dog::remove_dog_photo::<AuditedMongoDB>),
+            )
+            .route("{id}", put().to(dog::update_dog::<AuditedMongoDB>))
+            .route("{id}", delete().to(dog::delete_dog::<AuditedMongoDB>)),
+    );
+}
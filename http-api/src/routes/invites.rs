@@ -0,0 +1,38 @@
+use actix_web::web::{get, post, scope, ServiceConfig};
+use auth_service::{
+    hashers::sha::ShaHasher, repositories::mongo::MongodbRepository,
+    token_managers::jwt::JWTTokenManager,
+};
+use hmac::Hmac;
+use sha2::Sha384;
+
+use crate::{
+    handlers::{deep_link, invite},
+    repositories::audited::AuditedMongoDB,
+    sms_senders::http::HttpSmsSender,
+};
+
+pub fn configure(cfg: &mut ServiceConfig) {
+    cfg.service(
+        scope("invites")
+            .route(
+                "",
+                post().to(invite::send_invite::<
+                    AuditedMongoDB,
+                    MongodbRepository,
+                    ShaHasher,
+                    JWTTokenManager<Hmac<Sha384>>,
+                    HttpSmsSender,
+                >),
+            )
+            .route("", get().to(invite::my_invites::<AuditedMongoDB>))
+            .route(
+                "{code}/opened",
+                post().to(invite::open_invite::<AuditedMongoDB>),
+            ),
+    )
+    .route(
+        "short_links",
+        post().to(deep_link::create_short_link::<AuditedMongoDB>),
+    );
+}
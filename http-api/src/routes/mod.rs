@@ -0,0 +1,65 @@
+// 路由按业务领域拆分, 各模块的configure注册相对路径. 新接口挂载在/v1下,
+// 旧路径在弃用期间作为别名保留, 响应带Deprecation头提示客户端迁移
+pub mod accounts;
+pub mod admin;
+pub mod auth;
+pub mod dogs;
+pub mod invites;
+pub mod partner;
+pub mod support;
+pub mod system;
+pub mod uploads;
+pub mod walk_requests;
+pub mod walkers;
+
+use actix_web::{
+    middleware::DefaultHeaders,
+    web::{scope, ServiceConfig},
+};
+
+pub const API_PREFIX: &str = "/v1";
+
+// 用户接口, 旧路径下位于/apis
+fn configure_apis(cfg: &mut ServiceConfig) {
+    cfg.configure(uploads::configure)
+        .configure(dogs::configure)
+        .configure(walkers::configure)
+        .configure(walk_requests::configure)
+        .configure(accounts::configure)
+        .configure(invites::configure)
+        .configure(support::configure);
+}
+
+// 挂载在API_PREFIX下的全部版本化接口
+pub fn configure(cfg: &mut ServiceConfig) {
+    cfg.configure(auth::configure)
+        .configure(configure_apis)
+        .service(scope("admin").configure(admin::configure))
+        .service(scope("partner").configure(partner::configure));
+}
+
+// 未版本化的旧路径. sunset为空时不返回Sunset头
+pub fn configure_legacy(cfg: &mut ServiceConfig, sunset: &str) {
+    let deprecation = || {
+        let headers = DefaultHeaders::new().add(("Deprecation", "true"));
+        if sunset.is_empty() {
+            headers
+        } else {
+            headers.add(("Sunset", sunset))
+        }
+    };
+    cfg.service(scope("/apis").wrap(deprecation()).configure(configure_apis))
+        .service(
+            scope("/admin")
+                .wrap(deprecation())
+                .configure(admin::configure),
+        )
+        .service(
+            scope("/partner")
+                .wrap(deprecation())
+                .configure(partner::configure),
+        )
+        // 旧的认证接口直接位于根路径, 需最后注册, 否则空前缀的作用域会匹配所有请求;
+        // 因此未匹配任何路由的404响应也会带Deprecation头
+        .service(scope("").wrap(deprecation()).configure(auth::configure));
+}
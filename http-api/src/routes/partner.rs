@@ -0,0 +1,19 @@
+use actix_web::web::{get, post, ServiceConfig};
+
+use crate::{handlers::partner, repositories::audited::AuditedMongoDB};
+
+pub fn configure(cfg: &mut ServiceConfig) {
+    cfg.route(
+        "walk_requests",
+        post().to(partner::create_walk_request::<AuditedMongoDB>),
+    )
+    .route(
+        "walk_requests/{id}",
+        get().to(partner::walk_request::<AuditedMongoDB>),
+    )
+    .route(
+        "walker_availability",
+        get().to(partner::walker_availability::<AuditedMongoDB>),
+    )
+    .route("usage", get().to(partner::usage::<AuditedMongoDB>));
+}
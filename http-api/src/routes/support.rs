@@ -0,0 +1,38 @@
+use actix_web::web::{get, post, put, resource, scope, ServiceConfig};
+use upload_service::{repositories::mongo::Mongo, stores::local_fs::LocalFSStore};
+
+use crate::{
+    handlers::{help, public, report, ticket},
+    middlewares::concurrency_limit::{ConcurrencyLimit, HEATMAP},
+    repositories::audited::AuditedMongoDB,
+};
+
+pub fn configure(cfg: &mut ServiceConfig) {
+    cfg.service(scope("reports").route("", post().to(report::create_report::<AuditedMongoDB>)))
+        .service(
+            scope("help/articles")
+                .route("", get().to(help::articles::<AuditedMongoDB>))
+                .route("{slug}", get().to(help::article::<AuditedMongoDB>)),
+        )
+        .service(
+            scope("public").service(
+                resource("neighborhoods")
+                    .wrap(ConcurrencyLimit(HEATMAP))
+                    .route(get().to(public::neighborhoods::<AuditedMongoDB>)),
+            ),
+        )
+        .service(
+            scope("support/tickets")
+                .route(
+                    "",
+                    post().to(ticket::create_ticket::<AuditedMongoDB, Mongo, LocalFSStore>),
+                )
+                .route("", get().to(ticket::my_tickets::<AuditedMongoDB>))
+                .route("{id}", get().to(ticket::my_ticket::<AuditedMongoDB>))
+                .route(
+                    "{id}/messages",
+                    post().to(ticket::reply::<AuditedMongoDB, Mongo, LocalFSStore>),
+                )
+                .route("{id}/closure", put().to(ticket::close::<AuditedMongoDB>)),
+        );
+}
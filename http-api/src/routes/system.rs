@@ -0,0 +1,32 @@
+// 不随API版本变化的接口: 监控、App关联文件、短链接和外部回调
+use actix_web::web::{get, post, ServiceConfig};
+
+use crate::{handlers, repositories::audited::AuditedMongoDB};
+
+pub fn configure(cfg: &mut ServiceConfig) {
+    cfg.route("/metrics", get().to(handlers::metrics::metrics))
+        .route(
+            "/health",
+            get().to(handlers::health::health::<AuditedMongoDB>),
+        )
+        .route(
+            "/.well-known/apple-app-site-association",
+            get().to(handlers::deep_link::apple_app_site_association),
+        )
+        .route(
+            "/.well-known/assetlinks.json",
+            get().to(handlers::deep_link::asset_links),
+        )
+        .route(
+            "/s/{code}",
+            get().to(handlers::deep_link::resolve_short_link::<AuditedMongoDB>),
+        )
+        .route(
+            "/internal/synthetic/walk",
+            post().to(handlers::synthetic::walk::<AuditedMongoDB>),
+        )
+        .route(
+            "/webhooks/payouts",
+            post().to(handlers::withdrawal::payout_webhook::<AuditedMongoDB>),
+        );
+}
@@ -0,0 +1,38 @@
+use actix_web::web::{delete, get, post, scope, ServiceConfig};
+use upload_service::{repositories::mongo::Mongo, stores::local_fs::LocalFSStore};
+
+use crate::{
+    handlers::{direct_upload, upload},
+    object_stores::ObjectStores,
+    repositories::audited::AuditedMongoDB,
+};
+
+pub fn configure(cfg: &mut ServiceConfig) {
+    cfg.service(
+        scope("/uploads")
+            .route(
+                "/{id}",
+                get().to(upload::get::<Mongo, LocalFSStore, AuditedMongoDB>),
+            )
+            .route(
+                "/{id}",
+                delete().to(upload::delete::<Mongo, LocalFSStore, AuditedMongoDB>),
+            )
+            .route(
+                "",
+                post().to(upload::upload::<Mongo, LocalFSStore, AuditedMongoDB>),
+            )
+            .route(
+                "/presign",
+                post().to(direct_upload::presign::<AuditedMongoDB, ObjectStores>),
+            )
+            .route(
+                "/presign/{id}/confirmation",
+                post().to(direct_upload::confirm::<AuditedMongoDB, ObjectStores>),
+            )
+            .route(
+                "/direct/{id}",
+                get().to(direct_upload::download::<AuditedMongoDB, ObjectStores>),
+            ),
+    );
+}
@@ -0,0 +1,115 @@
+use actix_web::web::{delete, get, post, put, resource, scope, ServiceConfig};
+use upload_service::{repositories::mongo::Mongo, stores::local_fs::LocalFSStore};
+
+use crate::{
+    handlers::{breed, chat, meet_and_greet, refund, review, walk_request},
+    middlewares::concurrency_limit::{ConcurrencyLimit, EXPORT, NEARBY},
+    renderers::Renderers,
+    repositories::audited::AuditedMongoDB,
+    translation_providers::TranslationProviders,
+};
+
+pub fn configure(cfg: &mut ServiceConfig) {
+    cfg.service(
+        scope("walk_requests")
+            .route(
+                "",
+                post().to(walk_request::create_walk_request::<AuditedMongoDB>),
+            )
+            .route(
+                "validate",
+                post().to(walk_request::validate_walk_request::<AuditedMongoDB>),
+            )
+            .route("changes", get().to(walk_request::changes::<AuditedMongoDB>))
+            .route(
+                "mine",
+                get().to(walk_request::my_walk_requests::<AuditedMongoDB>),
+            )
+            .service(
+                resource("nearby")
+                    .wrap(ConcurrencyLimit(NEARBY))
+                    .route(get().to(walk_request::nearby_walk_requests::<AuditedMongoDB>)),
+            )
+            .route(
+                "{id}",
+                delete().to(walk_request::delete_walk_request::<AuditedMongoDB>),
+            )
+            .route(
+                "{id}/accepter/{user_id}",
+                put().to(walk_request::assign_accepter::<AuditedMongoDB>),
+            )
+            .route(
+                "{id}/acceptance",
+                delete().to(walk_request::resign_acceptance::<AuditedMongoDB>),
+            )
+            .route(
+                "{id}/cancellation_penalties",
+                get().to(walk_request::cancellation_penalties::<AuditedMongoDB>),
+            )
+            .route(
+                "{id}/refund_requests",
+                post().to(refund::request_refund::<AuditedMongoDB, Mongo, LocalFSStore>),
+            )
+            .route(
+                "{id}/refund_requests",
+                get().to(refund::walk_request_refunds::<AuditedMongoDB>),
+            )
+            .route(
+                "{id}/route_preference",
+                put().to(walk_request::update_route_preference::<AuditedMongoDB>),
+            )
+            .route(
+                "{id}/route_preference",
+                get().to(walk_request::route_preference::<AuditedMongoDB>),
+            )
+            .service(
+                resource("{id}/export")
+                    .wrap(ConcurrencyLimit(EXPORT))
+                    .route(get().to(walk_request::export::<AuditedMongoDB, Renderers>)),
+            )
+            .route(
+                "{id}/route_report",
+                get().to(walk_request::route_report::<AuditedMongoDB>),
+            )
+            .route(
+                "{id}/reviews",
+                post().to(review::create_review::<AuditedMongoDB>),
+            )
+            .route(
+                "{id}/meet_and_greets",
+                post().to(meet_and_greet::propose::<AuditedMongoDB>),
+            )
+            .route("{id}/chat", get().to(chat::chat::<AuditedMongoDB>))
+            .route(
+                "{id}/care_tips",
+                get().to(breed::walk_request_care_tips::<AuditedMongoDB>),
+            )
+            .route(
+                "{id}/messages",
+                get().to(chat::messages::<AuditedMongoDB, TranslationProviders>),
+            )
+            .route(
+                "{id}/meet_and_greets",
+                get().to(meet_and_greet::meet_and_greets::<AuditedMongoDB>),
+            ),
+    )
+    .service(
+        scope("meet_and_greets")
+            .route(
+                "{id}/accept",
+                put().to(meet_and_greet::accept::<AuditedMongoDB>),
+            )
+            .route(
+                "{id}/decline",
+                put().to(meet_and_greet::decline::<AuditedMongoDB>),
+            )
+            .route(
+                "{id}/cancel",
+                put().to(meet_and_greet::cancel::<AuditedMongoDB>),
+            )
+            .route(
+                "{id}/complete",
+                put().to(meet_and_greet::complete::<AuditedMongoDB>),
+            ),
+    );
+}
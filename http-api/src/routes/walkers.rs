@@ -0,0 +1,92 @@
+use actix_web::web::{delete, get, post, put, resource, scope, ServiceConfig};
+use upload_service::{repositories::mongo::Mongo, stores::local_fs::LocalFSStore};
+
+use crate::{
+    handlers::{block, favorite, payout, review, walker, withdrawal},
+    middlewares::concurrency_limit::{ConcurrencyLimit, NEARBY},
+    payout_providers::PayoutProviders,
+    repositories::audited::AuditedMongoDB,
+    translation_providers::TranslationProviders,
+};
+
+pub fn configure(cfg: &mut ServiceConfig) {
+    cfg.service(
+        scope("walkers")
+            .service(
+                resource("search")
+                    .wrap(ConcurrencyLimit(NEARBY))
+                    .route(get().to(walker::search::<AuditedMongoDB>)),
+            )
+            .route(
+                "me/location",
+                put().to(walker::update_location::<AuditedMongoDB>),
+            )
+            .route(
+                "me/verification",
+                get().to(walker::my_verification::<AuditedMongoDB>),
+            )
+            .route(
+                "me/payout_account",
+                get().to(payout::my_payout_account::<AuditedMongoDB, PayoutProviders>),
+            )
+            .route(
+                "me/payout_account",
+                put().to(payout::link_payout_account::<AuditedMongoDB, PayoutProviders>),
+            )
+            .route(
+                "me/payout_account",
+                delete().to(payout::unlink_payout_account::<AuditedMongoDB>),
+            )
+            .route(
+                "me/balance",
+                get().to(withdrawal::my_balance::<AuditedMongoDB>),
+            )
+            .route(
+                "me/ledger",
+                get().to(withdrawal::my_ledger::<AuditedMongoDB>),
+            )
+            .route(
+                "me/withdrawals",
+                get().to(withdrawal::my_withdrawals::<AuditedMongoDB>),
+            )
+            .route(
+                "me/withdrawals",
+                post().to(withdrawal::request_withdrawal::<AuditedMongoDB>),
+            )
+            .route(
+                "me/verification",
+                put().to(walker::submit_verification::<AuditedMongoDB, Mongo, LocalFSStore>),
+            )
+            .route(
+                "{id}/reviews",
+                get().to(review::walker_reviews::<AuditedMongoDB, TranslationProviders>),
+            )
+            .route("{id}/stats", get().to(walker::stats::<AuditedMongoDB>))
+            .route(
+                "{id}/achievements",
+                get().to(walker::achievements::<AuditedMongoDB>),
+            ),
+    )
+    .service(scope("reviews").route(
+        "{id}/helpful",
+        put().to(review::vote_helpful::<AuditedMongoDB>),
+    ))
+    .service(
+        scope("blocks")
+            .route("", get().to(block::my_blocks::<AuditedMongoDB>))
+            .route("{walker_id}", put().to(block::block::<AuditedMongoDB>))
+            .route("{walker_id}", delete().to(block::unblock::<AuditedMongoDB>)),
+    )
+    .service(
+        scope("favorites")
+            .route("", get().to(favorite::my_favorites::<AuditedMongoDB>))
+            .route(
+                "{walker_id}",
+                put().to(favorite::favorite::<AuditedMongoDB>),
+            )
+            .route(
+                "{walker_id}",
+                delete().to(favorite::unfavorite::<AuditedMongoDB>),
+            ),
+    );
+}
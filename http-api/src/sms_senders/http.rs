@@ -0,0 +1,38 @@
+use crate::core::{error::Error, sms::SmsSender};
+
+// 通过HTTP短信网关发送, 未配置网关地址时仅记录日志, 便于本地开发
+#[derive(Debug, Clone)]
+pub struct HttpSmsSender {
+    client: reqwest::Client,
+    gateway_url: String,
+    api_key: String,
+}
+
+impl HttpSmsSender {
+    pub fn new(gateway_url: &str, api_key: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            gateway_url: gateway_url.to_owned(),
+            api_key: api_key.to_owned(),
+        }
+    }
+}
+
+impl SmsSender for HttpSmsSender {
+    async fn send(&self, phone: &str, content: &str) -> Result<(), Error> {
+        if self.gateway_url.is_empty() {
+            log::info!("sms to {}: {}", phone, content);
+            return Ok(());
+        }
+        self.client
+            .post(&self.gateway_url)
+            .bearer_auth(&self.api_key)
+            .form(&[("phone", phone), ("content", content)])
+            .send()
+            .await
+            .map_err(|e| Error::new("failed to send sms").with_cause(e))?
+            .error_for_status()
+            .map_err(|e| Error::new("failed to send sms").with_cause(e))?;
+        Ok(())
+    }
+}
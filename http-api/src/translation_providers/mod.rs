@@ -0,0 +1,25 @@
+pub mod remote;
+pub mod stub;
+
+use crate::core::{
+    error::Error,
+    translation::{Language, Translator},
+};
+
+use self::{remote::RemoteTranslationProvider, stub::StubTranslationProvider};
+
+// 根据配置在远程翻译服务和桩实现之间选择
+#[derive(Debug, Clone)]
+pub enum TranslationProviders {
+    Remote(RemoteTranslationProvider),
+    Stub(StubTranslationProvider),
+}
+
+impl Translator for TranslationProviders {
+    async fn translate(&self, text: &str, to: Language) -> Result<String, Error> {
+        match self {
+            TranslationProviders::Remote(provider) => provider.translate(text, to).await,
+            TranslationProviders::Stub(provider) => provider.translate(text, to).await,
+        }
+    }
+}
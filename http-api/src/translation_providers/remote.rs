@@ -0,0 +1,48 @@
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::core::{
+    error::Error,
+    translation::{Language, Translator},
+};
+
+#[derive(Debug, Deserialize)]
+struct TranslateResp {
+    text: String,
+}
+
+// 调用远程翻译接口, POST {api_url}/translate, 请求体为{text, target}, 返回{text}
+#[derive(Debug, Clone)]
+pub struct RemoteTranslationProvider {
+    client: reqwest::Client,
+    api_url: String,
+    api_key: String,
+}
+
+impl RemoteTranslationProvider {
+    pub fn new(api_url: &str, api_key: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            api_url: api_url.to_owned(),
+            api_key: api_key.to_owned(),
+        }
+    }
+}
+
+impl Translator for RemoteTranslationProvider {
+    async fn translate(&self, text: &str, to: Language) -> Result<String, Error> {
+        self.client
+            .post(format!("{}/translate", self.api_url.trim_end_matches('/')))
+            .bearer_auth(&self.api_key)
+            .json(&json!({"text": text, "target": to.to_string()}))
+            .send()
+            .await
+            .map_err(|e| Error::new("failed to translate text").with_cause(e))?
+            .error_for_status()
+            .map_err(|e| Error::new("failed to translate text").with_cause(e))?
+            .json::<TranslateResp>()
+            .await
+            .map(|resp| resp.text)
+            .map_err(|e| Error::new("failed to parse translation").with_cause(e))
+    }
+}
@@ -0,0 +1,14 @@
+use crate::core::{
+    error::Error,
+    translation::{Language, Translator},
+};
+
+// 未配置翻译服务时使用, 原样返回文本
+#[derive(Debug, Clone, Default)]
+pub struct StubTranslationProvider;
+
+impl Translator for StubTranslationProvider {
+    async fn translate(&self, text: &str, _to: Language) -> Result<String, Error> {
+        Ok(text.to_owned())
+    }
+}
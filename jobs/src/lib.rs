@@ -0,0 +1,188 @@
+// 后台定时任务. 每个任务都是不会结束的future, 由调用方在tokio运行时上spawn,
+// 仓储和外部服务的具体实现也由调用方决定, 因此任务只依赖core, 不依赖actix
+use std::{sync::Arc, time::Duration};
+
+use little_walk_core::{
+    email::Mailer, payout::PayoutProvider, push::Notifier, repository::Repository,
+    service::Service, sms::SmsSender,
+};
+use tokio::runtime::Handle;
+
+// 定时根据遛狗记录和评价补发成就
+pub async fn run_achievement_job<R>(service: Arc<Service<R>>, interval: Duration)
+where
+    R: Repository,
+{
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        match service.award_achievements().await {
+            Ok(granted) => log::info!("achievement job granted {} achievements", granted),
+            Err(e) => log::error!("achievement job failed: {}", e),
+        }
+    }
+}
+
+// 定时公开超过期限对方仍未评价的评价
+pub async fn run_review_reveal_job<R>(service: Arc<Service<R>>, interval: Duration)
+where
+    R: Repository,
+{
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        match service.reveal_expired_reviews().await {
+            Ok(revealed) => log::info!("review reveal job revealed {} reviews", revealed),
+            Err(e) => log::error!("review reveal job failed: {}", e),
+        }
+    }
+}
+
+// 定时把审核通过的提现提交给打款服务
+pub async fn run_payout_job<R, P>(service: Arc<Service<R>>, provider: Arc<P>, interval: Duration)
+where
+    R: Repository,
+    P: PayoutProvider,
+{
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        match service.process_withdrawals(provider.as_ref()).await {
+            Ok(submitted) => log::info!("payout job submitted {} withdrawals", submitted),
+            Err(e) => log::error!("payout job failed: {}", e),
+        }
+    }
+}
+
+// 定时把新的站内通知推送到用户的设备, 紧急通知同时发送短信
+pub async fn run_push_job<R, N, S>(
+    service: Arc<Service<R>>,
+    notifiers: Arc<N>,
+    sms_sender: Arc<S>,
+    interval: Duration,
+) where
+    R: Repository,
+    N: Notifier,
+    S: SmsSender,
+{
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        match service
+            .push_notifications(notifiers.as_ref(), sms_sender.as_ref())
+            .await
+        {
+            Ok(summary) => {
+                for failure in &summary.failures {
+                    log::warn!("push job failed to push {}", failure);
+                }
+                log::debug!(
+                    "push job delivered {} notifications, sent {} sms, removed {} unregistered tokens, {} failed",
+                    summary.delivered,
+                    summary.sms_sent,
+                    summary.unregistered,
+                    summary.failures.len()
+                );
+            }
+            Err(e) => log::error!("push job failed: {}", e),
+        }
+    }
+}
+
+// 定时生成每周的遛狗周报, 并发送待发送的邮件
+pub async fn run_email_job<R, M>(service: Arc<Service<R>>, mailer: Arc<M>, interval: Duration)
+where
+    R: Repository,
+    M: Mailer,
+{
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        match service.enqueue_weekly_summaries().await {
+            Ok(0) => {}
+            Ok(enqueued) => log::info!("email job enqueued {} weekly summaries", enqueued),
+            Err(e) => log::error!("email job failed to enqueue weekly summaries: {}", e),
+        }
+        match service.send_emails(mailer.as_ref()).await {
+            Ok(summary) => {
+                for failure in &summary.failures {
+                    log::warn!("email job failed to send {}", failure);
+                }
+                log::debug!(
+                    "email job sent {} emails, {} failed",
+                    summary.sent,
+                    summary.failures.len()
+                );
+            }
+            Err(e) => log::error!("email job failed: {}", e),
+        }
+    }
+}
+
+// 定时清理未被引用的上传文件
+pub async fn run_upload_gc_job<R>(service: Arc<Service<R>>, interval: Duration)
+where
+    R: Repository,
+{
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        match service.collect_orphan_uploads().await {
+            Ok(deleted) => log::info!("upload gc job deleted {} uploads", deleted),
+            Err(e) => log::error!("upload gc job failed: {}", e),
+        }
+    }
+}
+
+// 定时把明文或旧密钥加密的手机号改用当前密钥加密, 轮换密钥后旧密钥需保留到迁移完成
+pub async fn run_field_encryption_job<R>(service: Arc<Service<R>>, interval: Duration)
+where
+    R: Repository,
+{
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        match service.reencrypt_fields().await {
+            Ok(migrated) => log::info!("field encryption job migrated {} fields", migrated),
+            Err(e) => log::error!("field encryption job failed: {}", e),
+        }
+    }
+}
+
+// 定时检查悬空引用, 按配置的策略修复, 未修复的问题记录到日志
+pub async fn run_integrity_job<R>(service: Arc<Service<R>>, interval: Duration)
+where
+    R: Repository + Send + Sync + 'static,
+{
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        // 修复遛狗请求时经由审计仓储的事务, 其future不满足Send, 在阻塞线程上用block_on执行
+        let service = service.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            Handle::current().block_on(service.check_integrity(true))
+        })
+        .await;
+        match result {
+            Ok(Ok(report)) => {
+                for issue in report.issues.iter().filter(|issue| !issue.fixed) {
+                    log::warn!(
+                        "integrity job found {:?}: {}.{} of {} references {}",
+                        issue.kind,
+                        issue.collection,
+                        issue.field,
+                        issue.record_id,
+                        issue.reference_id
+                    );
+                }
+                log::info!(
+                    "integrity job found {} issues, fixed {}",
+                    report.issues.len(),
+                    report.issues.iter().filter(|issue| issue.fixed).count()
+                );
+            }
+            Ok(Err(e)) => log::error!("integrity job failed: {}", e),
+            Err(e) => log::error!("integrity job panicked: {}", e),
+        }
+    }
+}
@@ -0,0 +1,1127 @@
+use std::{collections::HashMap, future::Future};
+
+use chrono::{DateTime, Utc};
+
+use little_walk_core::{
+    entities::{
+        AccountDeletion, AccountMerge, Achievement, AchievementKind, AchievementProgress,
+        ActionToken, AuditLog, Block, Breed, BreedCareTips, CancellationPenalty, ChatMessage,
+        ChatReadMarker, DeviceToken, DirectUpload, Dog, Email, Favorite, HelpArticle, ImageSize,
+        IntegrityIssue, IntegrityIssueKind, Invite, InviteConversion, LedgerEntry, LocationAccess,
+        MeetAndGreet, MergedReference, NeighborhoodStats, Notification, NotificationSettings,
+        OAuthAccount, OAuthLinkToken, OAuthProviderKind, OperationsSnapshot, Otp, OtpPurpose,
+        Owner, OwnerReputationStats, Partner, PartnerApiKey, PartnerConsent, PartnerUsage,
+        PasswordResetToken, PaymentAttemptStats, PayoutAccount, PayoutAccountStatus, PhoneBinding,
+        Poi, PurgedCounts, RankedWalker, RefreshToken, RefundRequest, Report, Review,
+        SensitiveAction, Session, ShortLink, Ticket, Translation, UploadVariant, WalkRequest,
+        WalkRequestAuditAction, Walker, WalkerStats, WalkingLocation, Withdrawal,
+    },
+    error::Error,
+    repository::{
+        AccountDeletionCreate, AccountMergeCreate, ActionTokenCreate, AuditLogCreate,
+        AuditLogQuery, AvailableWalkerQuery, BlockQuery, BreedCareTipsUpsert, BreedCreate,
+        BreedQuery, CancellationPenaltyCreate, ChatMessageCreate, ChatReadMarkerUpsert,
+        DeviceTokenQuery, DeviceTokenUpsert, DirectUploadCreate, DogCreate, DogQuery, DogUpdate,
+        EmailCreate, FavoriteQuery, HelpArticleCreate, HelpArticleQuery, InviteCreate, InviteQuery,
+        LocationAccessCreate, LocationAccessQuery, MeetAndGreetCreate, MeetAndGreetQuery,
+        MeetAndGreetUpdate, NeighborhoodStatsQuery, NotificationCreate, NotificationSettingsUpsert,
+        OAuthAccountCreate, OAuthLinkTokenCreate, OtpCreate, OwnerUpdate, Page, Pagination,
+        PartnerApiKeyCreate, PartnerConsentQuery, PartnerCreate, PartnerUpdate,
+        PasswordResetTokenCreate, PaymentAttemptCreate, PayoutAccountUpsert, PhoneBindingUpsert,
+        PoiCreate, PoiQuery, QueryPlan, QueryTemplate, RefreshTokenCreate, RefundRequestCreate,
+        RefundRequestQuery, RefundReview, ReportCreate, ReportQuery, ReportUpdate, Repository,
+        ReviewCreate, ReviewQuery, RevokedAccessTokenCreate, SessionQuery, ShortLinkCreate, SortBy,
+        TicketCreate, TicketMessageCreate, TicketQuery, TicketUpdate, TranslationUpsert,
+        UpdateField, UploadVariantCreate, WalkRequestCreate, WalkRequestQuery, WalkRequestUpdate,
+        WalkerQuery, WalkerSearch, WalkerUpdate, WalkerVerificationSubmit, WalkingLocationCreate,
+        WithdrawalCreate, WithdrawalQuery, WithdrawalUpdate,
+    },
+    translation::Language,
+};
+
+use crate::mongodb::MongoDB;
+
+// 审计遛狗请求状态变更的仓储装饰器. 接单、指定、取消、开始和结束时记录操作人和变更前后的请求快照,
+// 变更与审计记录在同一个事务中提交; 其余操作直接转发给被装饰的仓储
+#[derive(Debug, Clone)]
+pub struct Audited<R> {
+    inner: R,
+}
+
+impl<R> Audited<R> {
+    pub fn new(inner: R) -> Self {
+        Self { inner }
+    }
+}
+
+// 服务使用的仓储: 带审计的MongoDB
+pub type AuditedMongoDB = Audited<MongoDB>;
+
+// 根据更新内容判断需要审计的动作, 一次更新只记录一个动作
+fn audit_action(update: &WalkRequestUpdate) -> Option<WalkRequestAuditAction> {
+    if update.canceled_at.is_some() {
+        Some(WalkRequestAuditAction::Canceled)
+    } else if update.finished_at.is_some() {
+        Some(WalkRequestAuditAction::Finished)
+    } else if update.started_at.is_some() {
+        Some(WalkRequestAuditAction::Started)
+    } else if let UpdateField::Set(accepted_by) = &update.accepted_by {
+        if update.actor_id.as_ref() == Some(accepted_by) {
+            Some(WalkRequestAuditAction::Accepted)
+        } else {
+            Some(WalkRequestAuditAction::Assigned)
+        }
+    } else {
+        None
+    }
+}
+
+// 批量更新时只审计实际被更新的请求
+fn changed(action: WalkRequestAuditAction, before: &WalkRequest, after: &WalkRequest) -> bool {
+    match action {
+        WalkRequestAuditAction::Accepted | WalkRequestAuditAction::Assigned => {
+            before.accepted_by != after.accepted_by || before.accepted_at != after.accepted_at
+        }
+        WalkRequestAuditAction::Canceled => before.canceled_at != after.canceled_at,
+        WalkRequestAuditAction::Started => before.started_at != after.started_at,
+        WalkRequestAuditAction::Finished => before.finished_at != after.finished_at,
+    }
+}
+
+async fn record<R>(
+    repository: &R,
+    action: WalkRequestAuditAction,
+    actor_id: Option<String>,
+    before: Option<WalkRequest>,
+    after: &WalkRequest,
+) -> Result<(), Error>
+where
+    R: Repository,
+{
+    repository
+        .create_audit_log(AuditLogCreate {
+            walk_request_id: after.id.to_string(),
+            actor_id,
+            action,
+            before,
+            after: after.clone(),
+        })
+        .await
+        .map(|_| ())
+}
+
+impl<R> Repository for Audited<R>
+where
+    R: Repository,
+{
+    async fn with_transaction<T, F, Fut>(&self, f: F) -> Result<T, Error>
+    where
+        Self: Sized,
+        F: FnOnce(Self) -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        self.inner
+            .with_transaction(|inner| f(Audited { inner }))
+            .await
+    }
+
+    async fn update_walk_request(
+        &self,
+        id: &str,
+        request: WalkRequestUpdate,
+    ) -> Result<WalkRequest, Error> {
+        let Some(action) = audit_action(&request) else {
+            return self.inner.update_walk_request(id, request).await;
+        };
+        let actor_id = request.actor_id.clone();
+        self.inner
+            .with_transaction(|inner| async move {
+                let before = inner.get_walk_request(id).await.ok();
+                let after = inner.update_walk_request(id, request).await?;
+                record(&inner, action, actor_id, before, &after).await?;
+                Ok(after)
+            })
+            .await
+    }
+
+    async fn update_walk_request_by_query(
+        &self,
+        query: WalkRequestQuery,
+        update: WalkRequestUpdate,
+    ) -> Result<WalkRequest, Error> {
+        let Some(action) = audit_action(&update) else {
+            return self.inner.update_walk_request_by_query(query, update).await;
+        };
+        let actor_id = update.actor_id.clone();
+        self.inner
+            .with_transaction(|inner| async move {
+                let before = inner
+                    .query_walk_requests(
+                        query.clone(),
+                        None,
+                        Some(Pagination { limit: 1, skip: 0 }.into()),
+                    )
+                    .await?
+                    .0
+                    .pop();
+                let after = inner.update_walk_request_by_query(query, update).await?;
+                let before = before.filter(|b| b.id == after.id);
+                record(&inner, action, actor_id, before, &after).await?;
+                Ok(after)
+            })
+            .await
+    }
+
+    async fn update_walk_requests_by_query(
+        &self,
+        query: WalkRequestQuery,
+        update: WalkRequestUpdate,
+    ) -> Result<u64, Error> {
+        let Some(action) = audit_action(&update) else {
+            return self
+                .inner
+                .update_walk_requests_by_query(query, update)
+                .await;
+        };
+        let actor_id = update.actor_id.clone();
+        self.inner
+            .with_transaction(|inner| async move {
+                let (befores, _) = inner.query_walk_requests(query.clone(), None, None).await?;
+                let updated = inner.update_walk_requests_by_query(query, update).await?;
+                for before in befores {
+                    let after = inner.get_walk_request(&before.id).await?;
+                    if changed(action, &before, &after) {
+                        record(&inner, action, actor_id.clone(), Some(before), &after).await?;
+                    }
+                }
+                Ok(updated)
+            })
+            .await
+    }
+
+    async fn create_breed(&self, breed: &BreedCreate) -> Result<String, Error> {
+        self.inner.create_breed(breed).await
+    }
+
+    async fn delete_breed(&self, id: &str) -> Result<bool, Error> {
+        self.inner.delete_breed(id).await
+    }
+
+    async fn query_breeds(&self, query: &BreedQuery) -> Result<(Vec<Breed>, i64), Error> {
+        self.inner.query_breeds(query).await
+    }
+
+    async fn query_breed_care_tips(
+        &self,
+        breed_ids: &[String],
+        locale: &str,
+    ) -> Result<Vec<BreedCareTips>, Error> {
+        self.inner.query_breed_care_tips(breed_ids, locale).await
+    }
+
+    async fn upsert_breed_care_tips(&self, upsert: BreedCareTipsUpsert) -> Result<(), Error> {
+        self.inner.upsert_breed_care_tips(upsert).await
+    }
+
+    async fn delete_breed_care_tips(&self, breed_id: &str, locale: &str) -> Result<bool, Error> {
+        self.inner.delete_breed_care_tips(breed_id, locale).await
+    }
+
+    async fn create_dog(&self, dog: &DogCreate) -> Result<Dog, Error> {
+        self.inner.create_dog(dog).await
+    }
+
+    async fn delete_dog(&self, id: &str) -> Result<bool, Error> {
+        self.inner.delete_dog(id).await
+    }
+
+    async fn update_dog(&self, id: &str, dog: &DogUpdate) -> Result<bool, Error> {
+        self.inner.update_dog(id, dog).await
+    }
+
+    async fn query_dogs(&self, query: &DogQuery) -> Result<Vec<Dog>, Error> {
+        self.inner.query_dogs(query).await
+    }
+
+    async fn exists_dog(&self, query: &DogQuery) -> Result<bool, Error> {
+        self.inner.exists_dog(query).await
+    }
+
+    async fn create_walk_request(&self, request: WalkRequestCreate) -> Result<String, Error> {
+        self.inner.create_walk_request(request).await
+    }
+
+    async fn get_walk_request(&self, id: &str) -> Result<WalkRequest, Error> {
+        self.inner.get_walk_request(id).await
+    }
+
+    async fn query_walk_requests(
+        &self,
+        query: WalkRequestQuery,
+        sort_by: Option<SortBy>,
+        pagination: Option<Page>,
+    ) -> Result<(Vec<WalkRequest>, u64), Error> {
+        self.inner
+            .query_walk_requests(query, sort_by, pagination)
+            .await
+    }
+
+    async fn create_walking_location<'a>(
+        &self,
+        create: WalkingLocationCreate<'a>,
+    ) -> Result<String, Error> {
+        self.inner.create_walking_location(create).await
+    }
+
+    async fn query_walking_locations(
+        &self,
+        walk_request_id: &str,
+    ) -> Result<Vec<WalkingLocation>, Error> {
+        self.inner.query_walking_locations(walk_request_id).await
+    }
+
+    async fn create_poi(&self, create: PoiCreate) -> Result<String, Error> {
+        self.inner.create_poi(create).await
+    }
+
+    async fn query_pois(&self, query: PoiQuery) -> Result<Vec<Poi>, Error> {
+        self.inner.query_pois(query).await
+    }
+
+    async fn delete_poi(&self, id: &str) -> Result<bool, Error> {
+        self.inner.delete_poi(id).await
+    }
+
+    async fn get_walker(&self, user_id: &str) -> Result<Option<Walker>, Error> {
+        self.inner.get_walker(user_id).await
+    }
+
+    async fn submit_walker_verification<'a>(
+        &self,
+        submit: WalkerVerificationSubmit<'a>,
+    ) -> Result<Walker, Error> {
+        self.inner.submit_walker_verification(submit).await
+    }
+
+    async fn update_walkers_by_query(
+        &self,
+        query: WalkerQuery,
+        update: WalkerUpdate,
+    ) -> Result<u64, Error> {
+        self.inner.update_walkers_by_query(query, update).await
+    }
+
+    async fn query_walkers(
+        &self,
+        query: WalkerQuery,
+        pagination: Option<Pagination>,
+    ) -> Result<(Vec<Walker>, i64), Error> {
+        self.inner.query_walkers(query, pagination).await
+    }
+
+    async fn explain(&self, template: QueryTemplate) -> Result<QueryPlan, Error> {
+        self.inner.explain(template).await
+    }
+
+    async fn update_walker_location(
+        &self,
+        user_id: &str,
+        longitude: f64,
+        latitude: f64,
+    ) -> Result<(), Error> {
+        self.inner
+            .update_walker_location(user_id, longitude, latitude)
+            .await
+    }
+
+    async fn search_walkers(&self, search: WalkerSearch) -> Result<Vec<RankedWalker>, Error> {
+        self.inner.search_walkers(search).await
+    }
+
+    async fn walker_stats(&self, walker_id: &str) -> Result<WalkerStats, Error> {
+        self.inner.walker_stats(walker_id).await
+    }
+
+    async fn owner_reputation_stats(
+        &self,
+        owner_id: &str,
+        since: DateTime<Utc>,
+    ) -> Result<OwnerReputationStats, Error> {
+        self.inner.owner_reputation_stats(owner_id, since).await
+    }
+
+    async fn achievement_progress(&self) -> Result<Vec<AchievementProgress>, Error> {
+        self.inner.achievement_progress().await
+    }
+
+    async fn grant_achievement(&self, user_id: &str, kind: AchievementKind) -> Result<bool, Error> {
+        self.inner.grant_achievement(user_id, kind).await
+    }
+
+    async fn query_achievements(&self, user_id: &str) -> Result<Vec<Achievement>, Error> {
+        self.inner.query_achievements(user_id).await
+    }
+
+    async fn create_review<'a>(&self, create: ReviewCreate<'a>) -> Result<String, Error> {
+        self.inner.create_review(create).await
+    }
+
+    async fn query_reviews(
+        &self,
+        query: ReviewQuery,
+        pagination: Option<Pagination>,
+    ) -> Result<(Vec<Review>, i64), Error> {
+        self.inner.query_reviews(query, pagination).await
+    }
+
+    async fn create_review_vote(&self, review_id: &str, user_id: &str) -> Result<bool, Error> {
+        self.inner.create_review_vote(review_id, user_id).await
+    }
+
+    async fn reveal_reviews(&self, walk_request_id: &str) -> Result<u64, Error> {
+        self.inner.reveal_reviews(walk_request_id).await
+    }
+
+    async fn reveal_expired_reviews(&self, created_before: DateTime<Utc>) -> Result<u64, Error> {
+        self.inner.reveal_expired_reviews(created_before).await
+    }
+
+    async fn count_dogs(&self, query: &DogQuery) -> Result<i64, Error> {
+        self.inner.count_dogs(query).await
+    }
+
+    async fn get_owner(&self, user_id: &str) -> Result<Option<Owner>, Error> {
+        self.inner.get_owner(user_id).await
+    }
+
+    async fn upsert_owner(&self, user_id: &str, update: OwnerUpdate) -> Result<Owner, Error> {
+        self.inner.upsert_owner(user_id, update).await
+    }
+
+    async fn create_block(&self, owner_id: &str, walker_id: &str) -> Result<Block, Error> {
+        self.inner.create_block(owner_id, walker_id).await
+    }
+
+    async fn delete_block(&self, owner_id: &str, walker_id: &str) -> Result<bool, Error> {
+        self.inner.delete_block(owner_id, walker_id).await
+    }
+
+    async fn query_blocks(&self, query: BlockQuery) -> Result<Vec<Block>, Error> {
+        self.inner.query_blocks(query).await
+    }
+
+    async fn create_favorite(&self, owner_id: &str, walker_id: &str) -> Result<Favorite, Error> {
+        self.inner.create_favorite(owner_id, walker_id).await
+    }
+
+    async fn delete_favorite(&self, owner_id: &str, walker_id: &str) -> Result<bool, Error> {
+        self.inner.delete_favorite(owner_id, walker_id).await
+    }
+
+    async fn query_favorites(&self, query: FavoriteQuery) -> Result<Vec<Favorite>, Error> {
+        self.inner.query_favorites(query).await
+    }
+
+    async fn create_report(&self, create: ReportCreate) -> Result<String, Error> {
+        self.inner.create_report(create).await
+    }
+
+    async fn query_reports(
+        &self,
+        query: ReportQuery,
+        pagination: Option<Pagination>,
+    ) -> Result<(Vec<Report>, i64), Error> {
+        self.inner.query_reports(query, pagination).await
+    }
+
+    async fn update_reports_by_query(
+        &self,
+        query: ReportQuery,
+        update: ReportUpdate,
+    ) -> Result<u64, Error> {
+        self.inner.update_reports_by_query(query, update).await
+    }
+
+    async fn create_refresh_token(&self, create: RefreshTokenCreate) -> Result<String, Error> {
+        self.inner.create_refresh_token(create).await
+    }
+
+    async fn consume_refresh_token(&self, token_hash: &str) -> Result<Option<RefreshToken>, Error> {
+        self.inner.consume_refresh_token(token_hash).await
+    }
+
+    async fn get_refresh_token(&self, token_hash: &str) -> Result<Option<RefreshToken>, Error> {
+        self.inner.get_refresh_token(token_hash).await
+    }
+
+    async fn revoke_refresh_token_family(&self, family_id: &str) -> Result<u64, Error> {
+        self.inner.revoke_refresh_token_family(family_id).await
+    }
+
+    async fn count_user_references(&self, user_id: &str) -> Result<Vec<MergedReference>, Error> {
+        self.inner.count_user_references(user_id).await
+    }
+
+    async fn merge_accounts(&self, merge: AccountMergeCreate) -> Result<AccountMerge, Error> {
+        self.inner.merge_accounts(merge).await
+    }
+
+    async fn delete_account(
+        &self,
+        deletion: AccountDeletionCreate,
+    ) -> Result<AccountDeletion, Error> {
+        self.inner.delete_account(deletion).await
+    }
+
+    async fn create_otp(&self, create: OtpCreate) -> Result<String, Error> {
+        self.inner.create_otp(create).await
+    }
+
+    async fn count_otps(&self, phone: &str, since: DateTime<Utc>) -> Result<i64, Error> {
+        self.inner.count_otps(phone, since).await
+    }
+
+    async fn consume_otp(
+        &self,
+        phone: &str,
+        purpose: OtpPurpose,
+        code_hash: &str,
+        max_attempts: i64,
+    ) -> Result<Option<Otp>, Error> {
+        self.inner
+            .consume_otp(phone, purpose, code_hash, max_attempts)
+            .await
+    }
+
+    async fn create_password_reset_token(
+        &self,
+        create: PasswordResetTokenCreate,
+    ) -> Result<String, Error> {
+        self.inner.create_password_reset_token(create).await
+    }
+
+    async fn consume_password_reset_token(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<PasswordResetToken>, Error> {
+        self.inner.consume_password_reset_token(token_hash).await
+    }
+
+    async fn create_ticket(&self, create: TicketCreate) -> Result<String, Error> {
+        self.inner.create_ticket(create).await
+    }
+
+    async fn query_tickets(
+        &self,
+        query: TicketQuery,
+        pagination: Option<Pagination>,
+    ) -> Result<(Vec<Ticket>, i64), Error> {
+        self.inner.query_tickets(query, pagination).await
+    }
+
+    async fn update_tickets_by_query(
+        &self,
+        query: TicketQuery,
+        update: TicketUpdate,
+    ) -> Result<u64, Error> {
+        self.inner.update_tickets_by_query(query, update).await
+    }
+
+    async fn add_ticket_message(
+        &self,
+        query: TicketQuery,
+        message: TicketMessageCreate,
+    ) -> Result<u64, Error> {
+        self.inner.add_ticket_message(query, message).await
+    }
+
+    async fn create_help_article(&self, create: HelpArticleCreate) -> Result<HelpArticle, Error> {
+        self.inner.create_help_article(create).await
+    }
+
+    async fn query_help_articles(
+        &self,
+        query: HelpArticleQuery,
+        limit: Option<i64>,
+    ) -> Result<Vec<HelpArticle>, Error> {
+        self.inner.query_help_articles(query, limit).await
+    }
+
+    async fn latest_help_articles(&self, locale: &str) -> Result<Vec<HelpArticle>, Error> {
+        self.inner.latest_help_articles(locale).await
+    }
+
+    async fn get_oauth_account(
+        &self,
+        provider: OAuthProviderKind,
+        subject: &str,
+    ) -> Result<Option<OAuthAccount>, Error> {
+        self.inner.get_oauth_account(provider, subject).await
+    }
+
+    async fn create_oauth_account(&self, create: OAuthAccountCreate) -> Result<String, Error> {
+        self.inner.create_oauth_account(create).await
+    }
+
+    async fn create_oauth_link_token(&self, create: OAuthLinkTokenCreate) -> Result<String, Error> {
+        self.inner.create_oauth_link_token(create).await
+    }
+
+    async fn consume_oauth_link_token(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<OAuthLinkToken>, Error> {
+        self.inner.consume_oauth_link_token(token_hash).await
+    }
+
+    async fn get_phone_binding(&self, phone: &str) -> Result<Option<PhoneBinding>, Error> {
+        self.inner.get_phone_binding(phone).await
+    }
+
+    async fn bind_phone(&self, upsert: PhoneBindingUpsert) -> Result<(), Error> {
+        self.inner.bind_phone(upsert).await
+    }
+
+    async fn upsert_payout_account(
+        &self,
+        upsert: PayoutAccountUpsert,
+    ) -> Result<PayoutAccount, Error> {
+        self.inner.upsert_payout_account(upsert).await
+    }
+
+    async fn get_payout_account(&self, user_id: &str) -> Result<Option<PayoutAccount>, Error> {
+        self.inner.get_payout_account(user_id).await
+    }
+
+    async fn update_payout_account_status(
+        &self,
+        user_id: &str,
+        provider_reference: &str,
+        status: PayoutAccountStatus,
+        failure_reason: Option<String>,
+    ) -> Result<Option<PayoutAccount>, Error> {
+        self.inner
+            .update_payout_account_status(user_id, provider_reference, status, failure_reason)
+            .await
+    }
+
+    async fn delete_payout_account(&self, user_id: &str) -> Result<bool, Error> {
+        self.inner.delete_payout_account(user_id).await
+    }
+
+    async fn query_sessions(&self, user_id: &str) -> Result<Vec<Session>, Error> {
+        self.inner.query_sessions(user_id).await
+    }
+
+    async fn revoke_sessions(&self, query: SessionQuery) -> Result<u64, Error> {
+        self.inner.revoke_sessions(query).await
+    }
+
+    async fn ledger_balance(&self, user_id: &str) -> Result<i64, Error> {
+        self.inner.ledger_balance(user_id).await
+    }
+
+    async fn query_ledger_entries(
+        &self,
+        user_id: &str,
+        pagination: Option<Pagination>,
+    ) -> Result<(Vec<LedgerEntry>, i64), Error> {
+        self.inner.query_ledger_entries(user_id, pagination).await
+    }
+
+    async fn credit_walk_earning(
+        &self,
+        user_id: &str,
+        walk_request_id: &str,
+        amount: i64,
+    ) -> Result<(), Error> {
+        self.inner
+            .credit_walk_earning(user_id, walk_request_id, amount)
+            .await
+    }
+
+    async fn create_withdrawal(&self, create: WithdrawalCreate) -> Result<Option<String>, Error> {
+        self.inner.create_withdrawal(create).await
+    }
+
+    async fn query_withdrawals(
+        &self,
+        query: WithdrawalQuery,
+        pagination: Option<Pagination>,
+    ) -> Result<(Vec<Withdrawal>, i64), Error> {
+        self.inner.query_withdrawals(query, pagination).await
+    }
+
+    async fn update_withdrawals_by_query(
+        &self,
+        query: WithdrawalQuery,
+        update: WithdrawalUpdate,
+    ) -> Result<u64, Error> {
+        self.inner.update_withdrawals_by_query(query, update).await
+    }
+
+    async fn claim_approved_withdrawal(&self) -> Result<Option<Withdrawal>, Error> {
+        self.inner.claim_approved_withdrawal().await
+    }
+
+    async fn create_payment_attempt(&self, create: PaymentAttemptCreate) -> Result<String, Error> {
+        self.inner.create_payment_attempt(create).await
+    }
+
+    async fn payment_attempt_stats(
+        &self,
+        user_id: &str,
+        since: DateTime<Utc>,
+    ) -> Result<PaymentAttemptStats, Error> {
+        self.inner.payment_attempt_stats(user_id, since).await
+    }
+
+    async fn create_upload_variant(&self, create: UploadVariantCreate) -> Result<(), Error> {
+        self.inner.create_upload_variant(create).await
+    }
+
+    async fn get_upload_variant(
+        &self,
+        upload_id: &str,
+        size: ImageSize,
+    ) -> Result<Option<UploadVariant>, Error> {
+        self.inner.get_upload_variant(upload_id, size).await
+    }
+
+    async fn is_verification_document(&self, upload_id: &str) -> Result<bool, Error> {
+        self.inner.is_verification_document(upload_id).await
+    }
+
+    async fn create_cancellation_penalty(
+        &self,
+        create: CancellationPenaltyCreate,
+    ) -> Result<CancellationPenalty, Error> {
+        self.inner.create_cancellation_penalty(create).await
+    }
+
+    async fn create_refund_request(&self, create: RefundRequestCreate) -> Result<String, Error> {
+        self.inner.create_refund_request(create).await
+    }
+
+    async fn query_refund_requests(
+        &self,
+        query: RefundRequestQuery,
+        pagination: Option<Pagination>,
+    ) -> Result<(Vec<RefundRequest>, i64), Error> {
+        self.inner.query_refund_requests(query, pagination).await
+    }
+
+    async fn review_refund_request(
+        &self,
+        review: RefundReview,
+    ) -> Result<Option<RefundRequest>, Error> {
+        self.inner.review_refund_request(review).await
+    }
+
+    async fn query_cancellation_penalties(
+        &self,
+        walk_request_id: &str,
+    ) -> Result<Vec<CancellationPenalty>, Error> {
+        self.inner
+            .query_cancellation_penalties(walk_request_id)
+            .await
+    }
+
+    async fn create_meet_and_greet(&self, create: MeetAndGreetCreate) -> Result<String, Error> {
+        self.inner.create_meet_and_greet(create).await
+    }
+
+    async fn query_meet_and_greets(
+        &self,
+        query: MeetAndGreetQuery,
+    ) -> Result<Vec<MeetAndGreet>, Error> {
+        self.inner.query_meet_and_greets(query).await
+    }
+
+    async fn update_meet_and_greet_by_query(
+        &self,
+        query: MeetAndGreetQuery,
+        update: MeetAndGreetUpdate,
+    ) -> Result<Option<MeetAndGreet>, Error> {
+        self.inner
+            .update_meet_and_greet_by_query(query, update)
+            .await
+    }
+
+    async fn create_chat_message(&self, create: ChatMessageCreate) -> Result<String, Error> {
+        self.inner.create_chat_message(create).await
+    }
+
+    async fn get_chat_message(&self, id: &str) -> Result<ChatMessage, Error> {
+        self.inner.get_chat_message(id).await
+    }
+
+    async fn query_chat_messages(
+        &self,
+        walk_request_id: &str,
+        page: Page,
+    ) -> Result<(Vec<ChatMessage>, u64), Error> {
+        self.inner.query_chat_messages(walk_request_id, page).await
+    }
+
+    async fn query_chat_read_markers(
+        &self,
+        walk_request_id: &str,
+    ) -> Result<Vec<ChatReadMarker>, Error> {
+        self.inner.query_chat_read_markers(walk_request_id).await
+    }
+
+    async fn upsert_chat_read_marker(&self, upsert: ChatReadMarkerUpsert) -> Result<(), Error> {
+        self.inner.upsert_chat_read_marker(upsert).await
+    }
+
+    async fn count_unread_chat_messages(
+        &self,
+        user_id: &str,
+        walk_request_ids: &[String],
+    ) -> Result<HashMap<String, u64>, Error> {
+        self.inner
+            .count_unread_chat_messages(user_id, walk_request_ids)
+            .await
+    }
+
+    async fn operations_snapshot(&self, since: DateTime<Utc>) -> Result<OperationsSnapshot, Error> {
+        self.inner.operations_snapshot(since).await
+    }
+
+    async fn create_direct_upload(&self, create: DirectUploadCreate) -> Result<String, Error> {
+        self.inner.create_direct_upload(create).await
+    }
+
+    async fn get_direct_upload(&self, id: &str) -> Result<Option<DirectUpload>, Error> {
+        self.inner.get_direct_upload(id).await
+    }
+
+    async fn confirm_direct_upload(&self, id: &str, size: i64) -> Result<bool, Error> {
+        self.inner.confirm_direct_upload(id, size).await
+    }
+
+    async fn create_location_access(&self, create: LocationAccessCreate) -> Result<String, Error> {
+        self.inner.create_location_access(create).await
+    }
+
+    async fn query_location_accesses(
+        &self,
+        query: LocationAccessQuery,
+        pagination: Pagination,
+    ) -> Result<(Vec<LocationAccess>, i64), Error> {
+        self.inner.query_location_accesses(query, pagination).await
+    }
+
+    async fn count_upload_references(&self, upload_id: &str) -> Result<i64, Error> {
+        self.inner.count_upload_references(upload_id).await
+    }
+
+    async fn query_unreferenced_uploads(
+        &self,
+        created_before: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<String>, Error> {
+        self.inner
+            .query_unreferenced_uploads(created_before, limit)
+            .await
+    }
+
+    async fn delete_uploads(&self, upload_ids: &[String]) -> Result<u64, Error> {
+        self.inner.delete_uploads(upload_ids).await
+    }
+
+    async fn query_integrity_issues(
+        &self,
+        kind: IntegrityIssueKind,
+        limit: i64,
+    ) -> Result<Vec<IntegrityIssue>, Error> {
+        self.inner.query_integrity_issues(kind, limit).await
+    }
+
+    async fn repair_integrity_issue(&self, issue: &IntegrityIssue) -> Result<bool, Error> {
+        self.inner.repair_integrity_issue(issue).await
+    }
+
+    async fn reencrypt_fields(&self, limit: i64) -> Result<u64, Error> {
+        self.inner.reencrypt_fields(limit).await
+    }
+
+    async fn get_token_version(&self, user_id: &str) -> Result<i64, Error> {
+        self.inner.get_token_version(user_id).await
+    }
+
+    async fn increment_token_version(&self, user_id: &str) -> Result<i64, Error> {
+        self.inner.increment_token_version(user_id).await
+    }
+
+    async fn revoke_access_token(&self, create: RevokedAccessTokenCreate) -> Result<(), Error> {
+        self.inner.revoke_access_token(create).await
+    }
+
+    async fn is_access_token_revoked(&self, token_id: &str) -> Result<bool, Error> {
+        self.inner.is_access_token_revoked(token_id).await
+    }
+
+    async fn mark_walker_nearby_notified(&self, walk_request_id: &str) -> Result<bool, Error> {
+        self.inner
+            .mark_walker_nearby_notified(walk_request_id)
+            .await
+    }
+
+    async fn create_notification(&self, create: NotificationCreate) -> Result<String, Error> {
+        self.inner.create_notification(create).await
+    }
+
+    async fn query_notifications(
+        &self,
+        user_id: &str,
+        pagination: Pagination,
+    ) -> Result<Vec<Notification>, Error> {
+        self.inner.query_notifications(user_id, pagination).await
+    }
+
+    async fn claim_unpushed_notification(
+        &self,
+        created_after: DateTime<Utc>,
+    ) -> Result<Option<Notification>, Error> {
+        self.inner.claim_unpushed_notification(created_after).await
+    }
+
+    async fn upsert_device_token(&self, upsert: DeviceTokenUpsert) -> Result<(), Error> {
+        self.inner.upsert_device_token(upsert).await
+    }
+
+    async fn query_device_tokens(&self, user_id: &str) -> Result<Vec<DeviceToken>, Error> {
+        self.inner.query_device_tokens(user_id).await
+    }
+
+    async fn delete_device_tokens(&self, query: DeviceTokenQuery) -> Result<u64, Error> {
+        self.inner.delete_device_tokens(query).await
+    }
+
+    async fn get_notification_settings(
+        &self,
+        user_id: &str,
+    ) -> Result<Option<NotificationSettings>, Error> {
+        self.inner.get_notification_settings(user_id).await
+    }
+
+    async fn upsert_notification_settings(
+        &self,
+        upsert: NotificationSettingsUpsert,
+    ) -> Result<NotificationSettings, Error> {
+        self.inner.upsert_notification_settings(upsert).await
+    }
+
+    async fn claim_weekly_summary_recipient(
+        &self,
+        week_start: DateTime<Utc>,
+    ) -> Result<Option<NotificationSettings>, Error> {
+        self.inner.claim_weekly_summary_recipient(week_start).await
+    }
+
+    async fn create_email(&self, create: EmailCreate) -> Result<String, Error> {
+        self.inner.create_email(create).await
+    }
+
+    async fn claim_unsent_email(
+        &self,
+        created_after: DateTime<Utc>,
+    ) -> Result<Option<Email>, Error> {
+        self.inner.claim_unsent_email(created_after).await
+    }
+
+    async fn query_translations(
+        &self,
+        source_ids: &[String],
+        language: Language,
+    ) -> Result<Vec<Translation>, Error> {
+        self.inner.query_translations(source_ids, language).await
+    }
+
+    async fn upsert_translation(&self, upsert: TranslationUpsert) -> Result<(), Error> {
+        self.inner.upsert_translation(upsert).await
+    }
+
+    async fn create_action_token(&self, create: ActionTokenCreate) -> Result<String, Error> {
+        self.inner.create_action_token(create).await
+    }
+
+    async fn consume_action_token(
+        &self,
+        user_id: &str,
+        action: SensitiveAction,
+        token_hash: &str,
+    ) -> Result<Option<ActionToken>, Error> {
+        self.inner
+            .consume_action_token(user_id, action, token_hash)
+            .await
+    }
+
+    async fn create_audit_log(&self, create: AuditLogCreate) -> Result<String, Error> {
+        self.inner.create_audit_log(create).await
+    }
+
+    async fn query_audit_logs(
+        &self,
+        query: AuditLogQuery,
+        pagination: Pagination,
+    ) -> Result<(Vec<AuditLog>, i64), Error> {
+        self.inner.query_audit_logs(query, pagination).await
+    }
+
+    async fn purge_deleted(&self, deleted_before: DateTime<Utc>) -> Result<PurgedCounts, Error> {
+        self.inner.purge_deleted(deleted_before).await
+    }
+
+    async fn create_partner(&self, create: PartnerCreate) -> Result<String, Error> {
+        self.inner.create_partner(create).await
+    }
+
+    async fn get_partner(&self, id: &str) -> Result<Option<Partner>, Error> {
+        self.inner.get_partner(id).await
+    }
+
+    async fn query_partners(&self, pagination: Pagination) -> Result<(Vec<Partner>, i64), Error> {
+        self.inner.query_partners(pagination).await
+    }
+
+    async fn update_partner(
+        &self,
+        id: &str,
+        update: PartnerUpdate,
+    ) -> Result<Option<Partner>, Error> {
+        self.inner.update_partner(id, update).await
+    }
+
+    async fn create_partner_api_key(&self, create: PartnerApiKeyCreate) -> Result<String, Error> {
+        self.inner.create_partner_api_key(create).await
+    }
+
+    async fn get_partner_api_key(&self, key_hash: &str) -> Result<Option<PartnerApiKey>, Error> {
+        self.inner.get_partner_api_key(key_hash).await
+    }
+
+    async fn query_partner_api_keys(&self, partner_id: &str) -> Result<Vec<PartnerApiKey>, Error> {
+        self.inner.query_partner_api_keys(partner_id).await
+    }
+
+    async fn revoke_partner_api_key(&self, partner_id: &str, id: &str) -> Result<bool, Error> {
+        self.inner.revoke_partner_api_key(partner_id, id).await
+    }
+
+    async fn create_partner_consent(
+        &self,
+        partner_id: &str,
+        owner_id: &str,
+    ) -> Result<PartnerConsent, Error> {
+        self.inner
+            .create_partner_consent(partner_id, owner_id)
+            .await
+    }
+
+    async fn delete_partner_consent(
+        &self,
+        partner_id: &str,
+        owner_id: &str,
+    ) -> Result<bool, Error> {
+        self.inner
+            .delete_partner_consent(partner_id, owner_id)
+            .await
+    }
+
+    async fn query_partner_consents(
+        &self,
+        query: PartnerConsentQuery,
+    ) -> Result<Vec<PartnerConsent>, Error> {
+        self.inner.query_partner_consents(query).await
+    }
+
+    async fn create_partner_usage(&self, partner_id: &str, endpoint: &str) -> Result<(), Error> {
+        self.inner.create_partner_usage(partner_id, endpoint).await
+    }
+
+    async fn count_partner_usages(
+        &self,
+        partner_id: &str,
+        since: DateTime<Utc>,
+    ) -> Result<i64, Error> {
+        self.inner.count_partner_usages(partner_id, since).await
+    }
+
+    async fn partner_usage_report(
+        &self,
+        partner_id: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<PartnerUsage>, Error> {
+        self.inner.partner_usage_report(partner_id, from, to).await
+    }
+
+    async fn available_walker_ids(
+        &self,
+        query: AvailableWalkerQuery,
+    ) -> Result<Vec<String>, Error> {
+        self.inner.available_walker_ids(query).await
+    }
+
+    async fn walker_response_times(
+        &self,
+        walker_ids: Vec<String>,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<i64>, Error> {
+        self.inner.walker_response_times(walker_ids, since).await
+    }
+
+    async fn neighborhood_stats(
+        &self,
+        query: NeighborhoodStatsQuery,
+    ) -> Result<Vec<NeighborhoodStats>, Error> {
+        self.inner.neighborhood_stats(query).await
+    }
+
+    async fn create_invite(&self, create: InviteCreate) -> Result<String, Error> {
+        self.inner.create_invite(create).await
+    }
+
+    async fn count_invites(&self, query: InviteQuery) -> Result<i64, Error> {
+        self.inner.count_invites(query).await
+    }
+
+    async fn query_invites(
+        &self,
+        inviter_id: &str,
+        pagination: Pagination,
+    ) -> Result<(Vec<Invite>, i64), Error> {
+        self.inner.query_invites(inviter_id, pagination).await
+    }
+
+    async fn open_invite(&self, code_hash: &str) -> Result<Option<Invite>, Error> {
+        self.inner.open_invite(code_hash).await
+    }
+
+    async fn convert_invites(&self, phone: &str, user_id: &str) -> Result<u64, Error> {
+        self.inner.convert_invites(phone, user_id).await
+    }
+
+    async fn invite_conversion(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<InviteConversion>, Error> {
+        self.inner.invite_conversion(from, to).await
+    }
+
+    async fn create_short_link(&self, create: ShortLinkCreate) -> Result<String, Error> {
+        self.inner.create_short_link(create).await
+    }
+
+    async fn get_short_link(&self, code: &str) -> Result<Option<ShortLink>, Error> {
+        self.inner.get_short_link(code).await
+    }
+
+    async fn self_check(&self) -> Result<(), Error> {
+        self.inner.self_check().await
+    }
+}
@@ -0,0 +1,116 @@
+use aes_gcm_siv::{
+    aead::{Aead, KeyInit},
+    Aes256GcmSiv, Nonce,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use little_walk_core::{error::Error, keys::KeyRing};
+
+const PREFIX: &str = "enc";
+
+// 字段级加密, 密文格式为enc:v{密钥id}:{base64(nonce||密文)}.
+// nonce由明文的HMAC派生, 同一密钥下相同明文的密文相同, 可直接按密文等值查询.
+// 当前密钥用于加密, KeyRing中的所有密钥都可用于解密, 不带前缀的值视为尚未迁移的明文
+#[derive(Clone)]
+pub struct FieldCipher {
+    keys: Vec<(String, [u8; 32])>,
+    current: String,
+}
+
+impl FieldCipher {
+    // 密钥长度为32字节
+    pub fn new(ring: &KeyRing) -> Result<Self, Error> {
+        let keys = ring
+            .keys()
+            .iter()
+            .map(|key| {
+                let secret: [u8; 32] = key.secret.clone().try_into().map_err(|_| {
+                    Error::new("invalid field encryption key").with_cause("key must be 32 bytes")
+                })?;
+                Ok((key.id.clone(), secret))
+            })
+            .collect::<Result<Vec<(String, [u8; 32])>, Error>>()?;
+        let current = ring
+            .current()
+            .ok_or(Error::new("invalid field encryption key").with_cause("no key configured"))?
+            .id
+            .clone();
+        Ok(Self { keys, current })
+    }
+
+    // 当前密钥密文的前缀, 不以此开头的值需要迁移
+    pub fn current_prefix(&self) -> String {
+        format!("{}:v{}:", PREFIX, self.current)
+    }
+
+    pub fn encrypt(&self, plaintext: &str) -> Result<String, Error> {
+        self.encrypt_with(&self.current, plaintext)
+    }
+
+    pub fn decrypt(&self, value: &str) -> Result<String, Error> {
+        let Some(rest) = value
+            .strip_prefix(PREFIX)
+            .and_then(|v| v.strip_prefix(":v"))
+        else {
+            return Ok(value.to_owned());
+        };
+        let (key_id, data) = rest
+            .split_once(':')
+            .ok_or(Error::new("failed to decrypt field").with_cause("invalid ciphertext"))?;
+        let data = STANDARD
+            .decode(data)
+            .map_err(|e| Error::new("failed to decrypt field").with_cause(e))?;
+        if data.len() < 12 {
+            return Err(Error::new("failed to decrypt field").with_cause("invalid ciphertext"));
+        }
+        let (nonce, ciphertext) = data.split_at(12);
+        let plaintext = self
+            .cipher(key_id)?
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|e| Error::new("failed to decrypt field").with_cause(e))?;
+        String::from_utf8(plaintext)
+            .map_err(|e| Error::new("failed to decrypt field").with_cause(e))
+    }
+
+    // 按明文查询时需匹配所有密钥版本的密文以及尚未迁移的明文
+    pub fn lookup_values(&self, plaintext: &str) -> Result<Vec<String>, Error> {
+        let mut values = self
+            .keys
+            .iter()
+            .map(|(id, _)| self.encrypt_with(id, plaintext))
+            .collect::<Result<Vec<String>, Error>>()?;
+        values.push(plaintext.to_owned());
+        Ok(values)
+    }
+
+    fn encrypt_with(&self, key_id: &str, plaintext: &str) -> Result<String, Error> {
+        let nonce = self.derive(key_id, b"nonce", plaintext.as_bytes())?;
+        let ciphertext = self
+            .cipher(key_id)?
+            .encrypt(Nonce::from_slice(&nonce[..12]), plaintext.as_bytes())
+            .map_err(|e| Error::new("failed to encrypt field").with_cause(e))?;
+        let mut data = nonce[..12].to_vec();
+        data.extend(ciphertext);
+        Ok(format!("{}:v{}:{}", PREFIX, key_id, STANDARD.encode(data)))
+    }
+
+    fn cipher(&self, key_id: &str) -> Result<Aes256GcmSiv, Error> {
+        let key = self.derive(key_id, b"encryption", &[])?;
+        Aes256GcmSiv::new_from_slice(&key)
+            .map_err(|e| Error::new("failed to create field cipher").with_cause(e))
+    }
+
+    // 加密密钥和nonce密钥都从配置的主密钥派生, 避免同一密钥用于两种用途
+    fn derive(&self, key_id: &str, purpose: &[u8], data: &[u8]) -> Result<Vec<u8>, Error> {
+        let (_, key) = self.keys.iter().find(|(id, _)| id == key_id).ok_or(
+            Error::new("unknown field encryption key key_id").with_cause(key_id.to_owned()),
+        )?;
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key)
+            .map_err(|e| Error::new("failed to create field cipher").with_cause(e))?;
+        mac.update(purpose);
+        mac.update(data);
+        Ok(mac.finalize().into_bytes().to_vec())
+    }
+}
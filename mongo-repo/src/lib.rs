@@ -0,0 +1,4 @@
+pub mod audited;
+pub mod field_cipher;
+pub mod metrics;
+pub mod mongodb;
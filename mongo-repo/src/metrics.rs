@@ -0,0 +1,82 @@
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+
+use lazy_static::lazy_static;
+use mongodb::event::command::{
+    CommandEventHandler, CommandFailedEvent, CommandStartedEvent, CommandSucceededEvent,
+};
+use prometheus::{register_histogram_vec, register_int_counter_vec, HistogramVec, IntCounterVec};
+
+lazy_static! {
+    pub static ref REPOSITORY_OPERATIONS: IntCounterVec = register_int_counter_vec!(
+        "repository_operations_total",
+        "Number of repository operations by collection, operation and result",
+        &["collection", "operation", "result"]
+    )
+    .expect("failed to register repository_operations_total");
+    pub static ref REPOSITORY_OPERATION_DURATION: HistogramVec = register_histogram_vec!(
+        "repository_operation_duration_seconds",
+        "Latency of repository operations by collection and operation",
+        &["collection", "operation"]
+    )
+    .expect("failed to register repository_operation_duration_seconds");
+    pub static ref GEO_NEAR_FALLBACKS: IntCounterVec = register_int_counter_vec!(
+        "geo_near_fallbacks_total",
+        "Number of nearby queries answered without a geo index by collection",
+        &["collection"]
+    )
+    .expect("failed to register geo_near_fallbacks_total");
+}
+
+// 通过驱动的命令监控按集合和操作统计次数与耗时
+#[derive(Default)]
+pub struct CommandMetrics {
+    collections: Mutex<HashMap<i32, String>>,
+}
+
+impl CommandMetrics {
+    fn observe(&self, request_id: i32, operation: &str, result: &str, duration: Duration) {
+        let collection = match self.collections.lock() {
+            Ok(mut collections) => collections.remove(&request_id),
+            Err(_) => None,
+        };
+        if let Some(collection) = collection {
+            REPOSITORY_OPERATIONS
+                .with_label_values(&[&collection, operation, result])
+                .inc();
+            REPOSITORY_OPERATION_DURATION
+                .with_label_values(&[&collection, operation])
+                .observe(duration.as_secs_f64());
+        }
+    }
+}
+
+impl CommandEventHandler for CommandMetrics {
+    fn handle_command_started_event(&self, event: CommandStartedEvent) {
+        // getMore等命令的首个字段不是集合名, 集合名在collection字段中
+        let collection = event
+            .command
+            .get_str(&event.command_name)
+            .or_else(|_| event.command.get_str("collection"));
+        if let (Ok(collection), Ok(mut collections)) = (collection, self.collections.lock()) {
+            collections.insert(event.request_id, collection.to_owned());
+        }
+    }
+
+    fn handle_command_succeeded_event(&self, event: CommandSucceededEvent) {
+        self.observe(
+            event.request_id,
+            &event.command_name,
+            "success",
+            event.duration,
+        );
+    }
+
+    fn handle_command_failed_event(&self, event: CommandFailedEvent) {
+        self.observe(
+            event.request_id,
+            &event.command_name,
+            "failure",
+            event.duration,
+        );
+    }
+}
@@ -0,0 +1,7391 @@
+use mongodb::{
+    bson::{doc, from_document, oid::ObjectId, to_document, Bson, Document},
+    options::{DatabaseOptions, FindOneOptions, ReadPreference, SelectionCriteria},
+    Client, Database,
+};
+
+use little_walk_core::{
+    entities::{Breed, BreedCareTips, Dog},
+    error::Error,
+    ids::{parse_object_id, parse_object_ids},
+    repository::{
+        BreedCareTipsUpsert, BreedCreate, BreedQuery, DogCreate, DogQuery, DogUpdate, FieldSet,
+        Repository, USER_REFERENCES,
+    },
+};
+
+use mongodb::options::FindOptions;
+
+use futures::TryStreamExt;
+
+use chrono::{DateTime, Utc};
+
+// 实体和查询参数定义在core中, 孤儿规则不允许在这里为它们实现From和固有方法,
+// 与bson之间的转换和查询投影通过以下本地trait实现
+trait FromModel<T> {
+    fn from_model(value: T) -> Self;
+}
+
+trait TryFromModel<T>: Sized {
+    type Error;
+    fn try_from_model(value: T) -> Result<Self, Self::Error>;
+}
+
+trait Projection {
+    fn projection() -> Document;
+}
+
+impl TryFromModel<&DogCreate> for Document {
+    type Error = Error;
+    fn try_from_model(dog: &DogCreate) -> Result<Self, Self::Error> {
+        to_document(&dog)
+            .map_err(|e| Error::new("failed to convert DogCreate to Document").with_cause(e))
+    }
+}
+
+impl Projection for Dog {
+    fn projection() -> Document {
+        doc! {
+            "id": {"$toString": "$_id"},
+            "name": 1,
+            "gender": 1,
+            "breed": 1,
+            "birthday": 1,
+            "owner_id": 1,
+            "tags": 1,
+            "portrait_id": 1,
+            "photos": {"$ifNull": ["$photos", []]},
+            "medical_flags": {"$ifNull": ["$medical_flags", []]},
+            "version": 1,
+            "updated_at": {"$dateToString": {"date":"$updated_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+        }
+    }
+}
+
+// 反序列化狗狗和计算列表ETag所需的字段, 按字段选择查询时也总是投影
+const DOG_REQUIRED_FIELDS: &[&str] = &["name", "gender", "breed", "birthday", "owner_id", "updated_at"];
+
+// 只保留选择的字段和required中的字段, 响应字段与投影字段不同名的按别名匹配
+fn sparse_projection(projection: Document, fields: Option<&FieldSet>, required: &[&str]) -> Document {
+    let Some(fields) = fields else {
+        return projection;
+    };
+    projection
+        .into_iter()
+        .filter(|(key, _)| {
+            let alias = match key.as_str() {
+                "route_assessment" => "route_deviation_score",
+                key => key,
+            };
+            fields.contains(alias) || required.contains(&key.as_str())
+        })
+        .collect()
+}
+
+impl FromModel<Dog> for Bson {
+    fn from_model(value: Dog) -> Self {
+        let mut d = to_document(&value).unwrap();
+        d.insert("_id", value.id.object_id());
+        d.remove("id");
+        d.remove("version");
+        d.remove("updated_at");
+        Bson::Document(d)
+    }
+}
+
+#[derive(Clone)]
+pub struct MongoDB {
+    client: Client, // 事务需要通过client开启session
+    db: Database,
+    field_cipher: Option<FieldCipher>, // 为空时手机号等字段以明文保存
+    session: Option<Arc<Mutex<ClientSession>>>, // with_transaction中绑定的session, 写入钩子经由该session写入
+    listing_db: Option<Database>, // 带有配置读偏好的数据库句柄, 为空时只读查询也读主节点
+}
+
+impl MongoDB {
+    pub fn new(client: Client, db: Database) -> Self {
+        Self {
+            client,
+            db,
+            field_cipher: None,
+            session: None,
+            listing_db: None,
+        }
+    }
+
+    pub fn with_field_cipher(self, field_cipher: FieldCipher) -> Self {
+        Self {
+            field_cipher: Some(field_cipher),
+            ..self
+        }
+    }
+
+    // 列表、搜索和附近的请求等只读查询按该读偏好读取, 其余读写和事务始终使用主节点
+    pub fn with_listing_read_preference(self, read_preference: ReadPreference) -> Self {
+        let listing_db = self.client.database_with_options(
+            self.db.name(),
+            DatabaseOptions::builder()
+                .selection_criteria(SelectionCriteria::ReadPreference(read_preference))
+                .build(),
+        );
+        Self {
+            listing_db: Some(listing_db),
+            ..self
+        }
+    }
+
+    // 只读查询使用的副本. 绑定了session时仍在事务中读主节点
+    fn listing(&self) -> Self {
+        match (&self.listing_db, &self.session) {
+            (Some(listing_db), None) => Self {
+                db: listing_db.clone(),
+                ..self.clone()
+            },
+            _ => self.clone(),
+        }
+    }
+
+    // 启动时创建唯一索引, 已存在同名索引时不做改动
+    pub async fn ensure_indexes(&self) -> Result<(), Error> {
+        for index in &UNIQUE_INDEXES {
+            let keys = index.keys.iter().fold(doc! {}, |mut keys, key| {
+                keys.insert(*key, 1);
+                keys
+            });
+            self.db
+                .collection::<Document>(index.collection)
+                .create_index(
+                    IndexModel::builder()
+                        .keys(keys)
+                        .options(
+                            IndexOptions::builder()
+                                .name(index.name.to_owned())
+                                .unique(true)
+                                .partial_filter_expression(
+                                    index.partial.map(|(field, value)| doc! {field: value}),
+                                )
+                                .build(),
+                        )
+                        .build(),
+                    None,
+                )
+                .await
+                .map_err(|e| {
+                    Error::new(format!("failed to create index {}", index.name)).with_cause(e)
+                })?;
+        }
+        // 吊销记录在令牌过期后由TTL索引清理
+        self.db
+            .collection::<Document>("revoked_access_tokens")
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! {"expires_at": 1})
+                    .options(
+                        IndexOptions::builder()
+                            .name("revoked_access_tokens_expires_at".to_owned())
+                            .expire_after(Duration::ZERO)
+                            .build(),
+                    )
+                    .build(),
+                None,
+            )
+            .await
+            .map_err(|e| {
+                Error::new("failed to create index revoked_access_tokens_expires_at").with_cause(e)
+            })?;
+        Ok(())
+    }
+
+    fn encrypt_field(&self, value: &str) -> Result<String, Error> {
+        match &self.field_cipher {
+            Some(cipher) => cipher.encrypt(value),
+            None => Ok(value.to_owned()),
+        }
+    }
+
+    fn decrypt_field(&self, value: &mut String) -> Result<(), Error> {
+        if let Some(cipher) = &self.field_cipher {
+            *value = cipher.decrypt(value)?;
+        }
+        Ok(())
+    }
+
+    fn decrypt_emergency_contacts(&self, owner: &mut Owner) -> Result<(), Error> {
+        for contact in &mut owner.emergency_contacts {
+            self.decrypt_field(&mut contact.phone)?;
+        }
+        Ok(())
+    }
+
+    // 按加密字段等值查询的条件
+    fn encrypted_field_filter(&self, value: &str) -> Result<Bson, Error> {
+        match &self.field_cipher {
+            Some(cipher) => Ok(doc! {"$in": cipher.lookup_values(value)?}.into()),
+            None => Ok(value.into()),
+        }
+    }
+
+    async fn aggregate_all(
+        &self,
+        collection: &str,
+        pipeline: Vec<Document>,
+    ) -> Result<Vec<Document>, Error> {
+        self.try_aggregate(collection, pipeline)
+            .await
+            .map_err(|e| Error::new("failed to aggregate").with_cause(e))
+    }
+
+    async fn try_aggregate(
+        &self,
+        collection: &str,
+        pipeline: Vec<Document>,
+    ) -> Result<Vec<Document>, mongodb::error::Error> {
+        let collection = self.db.collection::<Document>(collection);
+        match &self.session {
+            Some(session) => {
+                let mut session = session.lock().await;
+                collection
+                    .aggregate_with_session(pipeline, None, &mut session)
+                    .await?
+                    .stream(&mut session)
+                    .try_collect::<Vec<Document>>()
+                    .await
+            }
+            None => {
+                collection
+                    .aggregate(pipeline, None)
+                    .await?
+                    .try_collect::<Vec<Document>>()
+                    .await
+            }
+        }
+    }
+
+    // 首个阶段为$geoNear的聚合, geo_field为GeoJSON点所在的字段. 集合缺少2dsphere索引(开发环境、单机测试库或
+    // 索引损坏的集群)时, 改用外接矩形查询候选文档并在进程内按球面距离过滤, 结果与$geoNear一致, 但无法利用索引
+    async fn aggregate_near(
+        &self,
+        collection: &str,
+        geo_field: &str,
+        mut pipeline: Vec<Document>,
+    ) -> Result<Vec<Document>, Error> {
+        // $geoNear和回退查询使用同一个字段
+        if let Some(near) = pipeline
+            .first_mut()
+            .and_then(|stage| stage.get_document_mut("$geoNear").ok())
+        {
+            near.insert("key", geo_field);
+        }
+        match self.try_aggregate(collection, pipeline.clone()).await {
+            Err(e) if is_geo_index_error(&e) => {
+                log::warn!(
+                    "no usable 2dsphere index on {}.{}, falling back to bounding box query: {}",
+                    collection,
+                    geo_field,
+                    e
+                );
+                GEO_NEAR_FALLBACKS.with_label_values(&[collection]).inc();
+                let near = pipeline
+                    .first()
+                    .and_then(|stage| stage.get_document("$geoNear").ok())
+                    .ok_or(Error::new("failed to aggregate").with_cause(e))?;
+                let mut stages = self.geo_near_fallback(collection, geo_field, near).await?;
+                stages.extend(pipeline.into_iter().skip(1));
+                self.aggregate_all(collection, stages).await
+            }
+            result => result.map_err(|e| Error::new("failed to aggregate").with_cause(e)),
+        }
+    }
+
+    // 替代$geoNear的阶段: 只保留距离内的候选文档, 写入距离字段并按距离升序排列
+    async fn geo_near_fallback(
+        &self,
+        collection: &str,
+        geo_field: &str,
+        near: &Document,
+    ) -> Result<Vec<Document>, Error> {
+        let point = near
+            .get_document("near")
+            .and_then(|near| near.get_array("coordinates"))
+            .map(|coordinates| {
+                coordinates
+                    .iter()
+                    .filter_map(Bson::as_f64)
+                    .collect::<Vec<f64>>()
+            })
+            .unwrap_or_default();
+        let (&[longitude, latitude], Ok(radius)) = (point.as_slice(), near.get_f64("maxDistance"))
+        else {
+            return Err(Error::new("failed to aggregate").with_cause("invalid $geoNear stage"));
+        };
+        let distance_field = near.get_str("distanceField").unwrap_or("distance");
+        let ((min_longitude, max_longitude), (min_latitude, max_latitude)) =
+            bounding_box(longitude, latitude, radius);
+        let mut filter = near.get_document("query").cloned().unwrap_or_default();
+        filter.insert(
+            format!("{}.coordinates.0", geo_field),
+            doc! {"$gte": min_longitude, "$lte": max_longitude},
+        );
+        filter.insert(
+            format!("{}.coordinates.1", geo_field),
+            doc! {"$gte": min_latitude, "$lte": max_latitude},
+        );
+        let candidates = self
+            .db
+            .collection::<Document>(collection)
+            .find(
+                filter,
+                FindOptions::builder()
+                    .projection(doc! {format!("{}.coordinates", geo_field): 1})
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to query geo candidates").with_cause(e))?
+            .try_collect::<Vec<Document>>()
+            .await
+            .map_err(|e| Error::new("failed to query geo candidates").with_cause(e))?;
+        let mut matched = candidates
+            .into_iter()
+            .filter_map(|d| {
+                let coordinates = d
+                    .get_document(geo_field)
+                    .and_then(|l| l.get_array("coordinates"))
+                    .ok()?;
+                let (lng, lat) = (
+                    coordinates.first()?.as_f64()?,
+                    coordinates.get(1)?.as_f64()?,
+                );
+                let distance = haversine_distance(longitude, latitude, lng, lat);
+                (distance <= radius).then_some((d.get("_id")?.clone(), distance))
+            })
+            .collect::<Vec<(Bson, f64)>>();
+        matched.sort_by(|a, b| a.1.total_cmp(&b.1));
+        let (ids, distances): (Vec<Bson>, Vec<f64>) = matched.into_iter().unzip();
+        Ok(vec![
+            doc! { "$match": { "_id": { "$in": &ids } } },
+            doc! { "$addFields": {
+                distance_field: { "$arrayElemAt": [distances, { "$indexOfArray": [ids, "$_id"] }] },
+            } },
+            doc! { "$sort": { distance_field: 1 } },
+        ])
+    }
+
+    // 指定了期望版本时, 请求存在但版本不一致返回冲突错误
+    async fn update_versioned_walk_request(
+        &self,
+        filter: Document,
+        update: WalkRequestUpdate,
+    ) -> Result<WalkRequest, Error> {
+        let expected_version = update.expected_version;
+        let mut versioned_filter = filter.clone();
+        if let Some(version) = expected_version {
+            versioned_filter.insert("version", version_filter(version));
+        }
+        if let Some(request) = self
+            .find_one_and_update(
+                "walk_requests",
+                versioned_filter,
+                Document::from_model(update),
+                FindOneAndUpdateOptions::builder()
+                    .return_document(Some(mongodb::options::ReturnDocument::After))
+                    .projection(WalkRequest::projection())
+                    .build(),
+            )
+            .await
+            .map_err(Error::from_error)?
+        {
+            return Ok(request);
+        }
+        if expected_version.is_some()
+            && self
+                .db
+                .collection::<Document>("walk_requests")
+                .count_documents(filter, None)
+                .await
+                .map_err(Error::from_error)?
+                > 0
+        {
+            return Err(Error::conflict("代遛请求已被修改, 请刷新后重试"));
+        }
+        Err(Error::not_found("代遛请求不存在"))
+    }
+}
+
+// 写入钩子: 所有集合的插入和更新都经由以下方法, 统一注入UTC时间戳和版本号
+impl MongoDB {
+    async fn insert_one(
+        &self,
+        collection: &str,
+        mut doc: Document,
+    ) -> mongodb::error::Result<InsertOneResult> {
+        let now = Utc::now();
+        doc.insert("created_at", now);
+        doc.insert("updated_at", now);
+        if VERSIONED_COLLECTIONS.contains(&collection) {
+            doc.insert("version", 0_i64);
+        }
+        let collection = self.db.collection::<Document>(collection);
+        match &self.session {
+            Some(session) => {
+                collection
+                    .insert_one_with_session(doc, None, &mut *session.lock().await)
+                    .await
+            }
+            None => collection.insert_one(doc, None).await,
+        }
+    }
+
+    async fn update_one(
+        &self,
+        collection: &str,
+        filter: Document,
+        update: Document,
+        options: Option<UpdateOptions>,
+    ) -> mongodb::error::Result<UpdateResult> {
+        let upsert = options.as_ref().and_then(|o| o.upsert).unwrap_or(false);
+        let update = stamp_update(collection, update, upsert);
+        let collection = self.db.collection::<Document>(collection);
+        match &self.session {
+            Some(session) => {
+                collection
+                    .update_one_with_session(filter, update, options, &mut *session.lock().await)
+                    .await
+            }
+            None => collection.update_one(filter, update, options).await,
+        }
+    }
+
+    async fn update_many(
+        &self,
+        collection: &str,
+        filter: Document,
+        update: Document,
+    ) -> mongodb::error::Result<UpdateResult> {
+        let update = stamp_update(collection, update, false);
+        let collection = self.db.collection::<Document>(collection);
+        match &self.session {
+            Some(session) => {
+                collection
+                    .update_many_with_session(filter, update, None, &mut *session.lock().await)
+                    .await
+            }
+            None => collection.update_many(filter, update, None).await,
+        }
+    }
+
+    async fn delete_many(
+        &self,
+        collection: &str,
+        filter: Document,
+    ) -> mongodb::error::Result<DeleteResult> {
+        let collection = self.db.collection::<Document>(collection);
+        match &self.session {
+            Some(session) => {
+                collection
+                    .delete_many_with_session(filter, None, &mut *session.lock().await)
+                    .await
+            }
+            None => collection.delete_many(filter, None).await,
+        }
+    }
+
+    async fn insert_one_with_session(
+        &self,
+        session: &mut ClientSession,
+        collection: &str,
+        mut doc: Document,
+    ) -> mongodb::error::Result<InsertOneResult> {
+        let now = Utc::now();
+        doc.insert("created_at", now);
+        doc.insert("updated_at", now);
+        if VERSIONED_COLLECTIONS.contains(&collection) {
+            doc.insert("version", 0_i64);
+        }
+        self.db
+            .collection::<Document>(collection)
+            .insert_one_with_session(doc, None, session)
+            .await
+    }
+
+    async fn update_many_with_session(
+        &self,
+        session: &mut ClientSession,
+        collection: &str,
+        filter: Document,
+        update: Document,
+        options: Option<UpdateOptions>,
+    ) -> mongodb::error::Result<UpdateResult> {
+        self.db
+            .collection::<Document>(collection)
+            .update_many_with_session(filter, stamp_update(collection, update, false), options, session)
+            .await
+    }
+
+    async fn find_one_and_update<T>(
+        &self,
+        collection: &str,
+        filter: Document,
+        update: Document,
+        options: FindOneAndUpdateOptions,
+    ) -> mongodb::error::Result<Option<T>>
+    where
+        T: DeserializeOwned + Send + Sync,
+    {
+        let upsert = options.upsert.unwrap_or(false);
+        let update = stamp_update(collection, update, upsert);
+        let collection = self.db.collection::<T>(collection);
+        match &self.session {
+            Some(session) => {
+                collection
+                    .find_one_and_update_with_session(
+                        filter,
+                        update,
+                        options,
+                        &mut *session.lock().await,
+                    )
+                    .await
+            }
+            None => collection.find_one_and_update(filter, update, options).await,
+        }
+    }
+
+    async fn find_one<T>(
+        &self,
+        collection: &str,
+        filter: Document,
+        options: FindOneOptions,
+    ) -> mongodb::error::Result<Option<T>>
+    where
+        T: DeserializeOwned + Unpin + Send + Sync,
+    {
+        let collection = self.db.collection::<T>(collection);
+        match &self.session {
+            Some(session) => {
+                collection
+                    .find_one_with_session(filter, options, &mut *session.lock().await)
+                    .await
+            }
+            None => collection.find_one(filter, options).await,
+        }
+    }
+}
+
+// 保存手机号的集合, 手机号字段在这些集合中加密保存
+const ENCRYPTED_PHONE_COLLECTIONS: [&str; 6] = [
+    "otps",
+    "refresh_tokens",
+    "password_reset_tokens",
+    "oauth_accounts",
+    "invites",
+    "phone_bindings",
+];
+
+// 引用上传文件id的集合字段, 缩略图由原图的upload_variants记录引用, 随原图一起删除
+const UPLOAD_REFERENCES: &[(&str, &str)] = &[
+    ("dogs", "portrait_id"),
+    ("dogs", "photos"),
+    ("owners", "avatar_id"),
+    ("walkers", "id_document_ids"),
+    ("tickets", "messages.attachment_ids"),
+    ("reviews", "photo_ids"),
+    ("refund_requests", "evidence_ids"),
+    ("upload_variants", "variant_id"),
+];
+
+// 完整性检查的聚合管道, 在引用方集合上执行. 结果中reference为引用值, 被引用的记录不存在时referenced为空
+fn integrity_pipelines(kind: IntegrityIssueKind) -> Vec<(&'static str, &'static str, Vec<Document>)> {
+    let dangling = doc! {"$match": {"referenced": {"$size": 0}}};
+    match kind {
+        IntegrityIssueKind::DogMissingBreed => vec![(
+            "dogs",
+            "breed.id",
+            vec![
+                doc! {"$match": {"deleted_at": null, "breed.id": {"$type": "string"}}},
+                doc! {"$addFields": {
+                    "reference": "$breed.id",
+                    "reference_oid": {"$convert": {"input": "$breed.id", "to": "objectId", "onError": null}},
+                }},
+                doc! {"$lookup": {
+                    "from": "breeds",
+                    "localField": "reference_oid",
+                    "foreignField": "_id",
+                    "as": "referenced",
+                }},
+                dangling,
+            ],
+        )],
+        // 已结束或已取消的请求保留删除前的狗狗作为历史记录
+        IntegrityIssueKind::WalkRequestDeletedDog => vec![(
+            "walk_requests",
+            "dogs",
+            vec![
+                doc! {"$match": {"deleted_at": null, "canceled_at": null, "finished_at": null}},
+                doc! {"$unwind": "$dogs"},
+                doc! {"$addFields": {"reference": "$dogs._id"}},
+                doc! {"$lookup": {
+                    "from": "dogs",
+                    "let": {"dog_id": "$reference"},
+                    "pipeline": [{"$match": {"$expr": {"$eq": ["$_id", "$$dog_id"]}, "deleted_at": null}}],
+                    "as": "referenced",
+                }},
+                dangling,
+            ],
+        )],
+        IntegrityIssueKind::MissingUpload => UPLOAD_REFERENCES
+            .iter()
+            .map(|(collection, field)| {
+                let mut pipeline = vec![doc! {"$match": {*field: {"$exists": true, "$ne": null}}}];
+                // 逐层展开数组, 标量字段展开后不变
+                let mut path = String::new();
+                for segment in field.split('.') {
+                    if !path.is_empty() {
+                        path.push('.');
+                    }
+                    path.push_str(segment);
+                    pipeline.push(doc! {"$unwind": format!("${}", path)});
+                }
+                let reference = format!("${}", field);
+                pipeline.push(doc! {"$addFields": {
+                    "reference": &reference,
+                    "reference_oid": {"$convert": {"input": &reference, "to": "objectId", "onError": null}},
+                }});
+                pipeline.push(doc! {"$lookup": {
+                    "from": "uploads",
+                    "localField": "reference_oid",
+                    "foreignField": "_id",
+                    "as": "referenced",
+                }});
+                pipeline.push(dangling.clone());
+                (*collection, *field, pipeline)
+            })
+            .collect(),
+    }
+}
+
+impl MongoDB {
+    // 用狗狗中内嵌的品种信息恢复品种记录, 已有同名同类的品种时改为引用该品种
+    async fn restore_dog_breed(&self, dog_id: ObjectId, breed_id: &str) -> Result<bool, Error> {
+        let dog = self
+            .db
+            .collection::<Document>("dogs")
+            .find_one(
+                doc! {"_id": dog_id},
+                FindOneOptions::builder().projection(doc! {"breed": 1}).build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to get dog").with_cause(e))?;
+        let Some(breed) = dog.as_ref().and_then(|d| d.get_document("breed").ok()) else {
+            return Ok(false);
+        };
+        let (Ok(name), Some(category)) = (breed.get_str("name"), breed.get("category")) else {
+            return Ok(false);
+        };
+        let restored = self
+            .insert_one(
+                "breeds",
+                doc! {"_id": parse_object_id(breed_id)?, "name": name, "category": category.clone()},
+            )
+            .await
+            .map_err(|e| write_error(e, "failed to restore breed"));
+        match restored {
+            Ok(_) => Ok(true),
+            Err(e) if e.is_duplicate("name") => {
+                let Some(existing) = self
+                    .db
+                    .collection::<Document>("breeds")
+                    .find_one(doc! {"name": name, "category": category.clone()}, None)
+                    .await
+                    .map_err(|e| Error::new("failed to get breed").with_cause(e))?
+                    .and_then(|d| d.get_object_id("_id").ok())
+                else {
+                    return Ok(false);
+                };
+                Ok(self
+                    .update_one(
+                        "dogs",
+                        doc! {"_id": dog_id, "breed.id": breed_id},
+                        doc! {"$set": {"breed.id": existing.to_hex()}},
+                        None,
+                    )
+                    .await
+                    .map_err(|e| Error::new("failed to update dog breed").with_cause(e))?
+                    .modified_count
+                    > 0)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    // 从引用方移除不存在的上传: 数组中删除该元素, 标量字段置空; 缩略图记录本身没有意义, 直接删除
+    async fn remove_upload_reference(
+        &self,
+        collection: &str,
+        field: &str,
+        record_id: ObjectId,
+        upload_id: &str,
+    ) -> Result<bool, Error> {
+        let to_error = |e| Error::new("failed to remove upload reference").with_cause(e);
+        if collection == "upload_variants" {
+            return Ok(self
+                .db
+                .collection::<Document>(collection)
+                .delete_one(doc! {"_id": record_id, field: upload_id}, None)
+                .await
+                .map_err(to_error)?
+                .deleted_count
+                > 0);
+        }
+        let modified = match field.split_once('.') {
+            Some((array, nested)) => {
+                self.update_one(
+                    collection,
+                    doc! {"_id": record_id},
+                    doc! {"$pull": {format!("{}.$[].{}", array, nested): upload_id}},
+                    None,
+                )
+                .await
+                .map_err(to_error)?
+                .modified_count
+            }
+            None => {
+                self.update_one(
+                    collection,
+                    doc! {"_id": record_id, field: {"$type": "array"}},
+                    doc! {"$pull": {field: upload_id}},
+                    None,
+                )
+                .await
+                .map_err(to_error)?
+                .modified_count
+                    + self
+                        .update_one(
+                            collection,
+                            doc! {"_id": record_id, field: upload_id},
+                            doc! {"$set": {field: null}},
+                            None,
+                        )
+                        .await
+                        .map_err(to_error)?
+                        .modified_count
+            }
+        };
+        Ok(modified > 0)
+    }
+}
+
+impl MongoDB {
+    // 在事务内改写所有引用并写入审计记录, 被合并账号的刷新令牌一并作废
+    async fn reassign_user_references(
+        &self,
+        session: &mut ClientSession,
+        merge: AccountMergeCreate,
+    ) -> Result<AccountMerge, Error> {
+        let mut references = Vec::new();
+        for (collection, field, is_array) in USER_REFERENCES {
+            let (update, options) = if *is_array {
+                (
+                    doc! {"$set": {format!("{}.$[user]", field): &merge.to_user_id}},
+                    Some(
+                        UpdateOptions::builder()
+                            .array_filters(vec![doc! {"user": &merge.from_user_id}])
+                            .build(),
+                    ),
+                )
+            } else {
+                (doc! {"$set": {*field: &merge.to_user_id}}, None)
+            };
+            let res = self
+                .update_many_with_session(
+                    session,
+                    collection,
+                    doc! {*field: &merge.from_user_id},
+                    update,
+                    options,
+                )
+                .await
+                .map_err(|e| Error::new("failed to reassign user references").with_cause(e))?;
+            references.push(MergedReference {
+                collection: collection.to_string(),
+                field: field.to_string(),
+                count: res.modified_count,
+            });
+        }
+        self.update_many_with_session(
+            session,
+            "refresh_tokens",
+            doc! {"user_id": &merge.from_user_id, "revoked": false},
+            doc! {"$set": {"revoked": true}},
+            None,
+        )
+        .await
+        .map_err(|e| Error::new("failed to revoke refresh tokens").with_cause(e))?;
+        let mut audit = doc! {
+            "from_user_id": &merge.from_user_id,
+            "to_user_id": &merge.to_user_id,
+            "operator_id": &merge.operator_id,
+        };
+        audit.insert(
+            "references",
+            references
+                .iter()
+                .map(|r| doc! {"collection": &r.collection, "field": &r.field, "count": r.count as i64})
+                .collect::<Vec<Document>>(),
+        );
+        let id = self
+            .insert_one_with_session(session, "account_merges", audit)
+            .await
+            .map_err(|e| Error::new("failed to create account merge record").with_cause(e))?
+            .inserted_id
+            .as_object_id()
+            .ok_or(
+                Error::new("failed to create account merge record")
+                    .with_cause("invalid inserted id"),
+            )?
+            .to_string();
+        Ok(AccountMerge {
+            id,
+            from_user_id: merge.from_user_id,
+            to_user_id: merge.to_user_id,
+            operator_id: merge.operator_id,
+            references,
+            created_at: Some(Utc::now()),
+        })
+    }
+
+    // 在事务内清理注销用户的数据: 匿名化档案, 删除或转移狗狗, 取消未完成的遛狗请求,
+    // 撤回接单, 删除收款账户, 标记待删除的上传文件, 作废刷新令牌并写入审计记录
+    async fn remove_user_data(
+        &self,
+        session: &mut ClientSession,
+        deletion: AccountDeletionCreate,
+    ) -> Result<AccountDeletion, Error> {
+        let uid = &deletion.user_id;
+        let now = Utc::now();
+        let dogs = match &deletion.reassign_dogs_to {
+            Some(to_user_id) => {
+                self.update_many_with_session(
+                    session,
+                    "dogs",
+                    doc! {"owner_id": uid},
+                    doc! {"$set": {"owner_id": to_user_id}},
+                    None,
+                )
+                .await
+                .map_err(|e| Error::new("failed to reassign dogs").with_cause(e))?
+                .modified_count
+            }
+            None => {
+                self.db
+                    .collection::<Document>("dogs")
+                    .delete_many_with_session(doc! {"owner_id": uid}, None, session)
+                    .await
+                    .map_err(|e| Error::new("failed to delete dogs").with_cause(e))?
+                    .deleted_count
+            }
+        };
+        let canceled_walk_requests = self
+            .update_many_with_session(
+                session,
+                "walk_requests",
+                doc! {"created_by": uid, "canceled_at": null, "finished_at": null},
+                doc! {"$set": {"canceled_at": now}},
+                None,
+            )
+            .await
+            .map_err(|e| Error::new("failed to cancel walk requests").with_cause(e))?
+            .modified_count;
+        let released = self
+            .update_many_with_session(
+                session,
+                "walk_requests",
+                doc! {"accepted_by": uid, "started_at": null, "finished_at": null, "canceled_at": null},
+                doc! {"$set": {"accepted_by": null, "accepted_at": null}},
+                None,
+            )
+            .await
+            .map_err(|e| Error::new("failed to release accepted walk requests").with_cause(e))?
+            .modified_count;
+        let withdrawn = self
+            .update_many_with_session(
+                session,
+                "walk_requests",
+                doc! {"$or": [{"acceptances": uid}, {"priority_walkers": uid}]},
+                doc! {"$pull": {"acceptances": uid, "priority_walkers": uid}},
+                None,
+            )
+            .await
+            .map_err(|e| Error::new("failed to remove acceptances").with_cause(e))?
+            .modified_count;
+        for (collection, field) in [
+            ("favorites", "owner_id"),
+            ("favorites", "walker_id"),
+            ("blocks", "owner_id"),
+            ("blocks", "walker_id"),
+            ("partner_consents", "owner_id"),
+            ("invites", "inviter_id"),
+            ("short_links", "created_by"),
+            ("payout_accounts", "user_id"),
+            ("notifications", "user_id"),
+            ("device_tokens", "user_id"),
+            ("notification_settings", "user_id"),
+            ("emails", "user_id"),
+            ("meet_and_greets", "owner_id"),
+            ("meet_and_greets", "walker_id"),
+            ("messages", "sender_id"),
+            ("chat_read_markers", "user_id"),
+        ] {
+            self.db
+                .collection::<Document>(collection)
+                .delete_many_with_session(doc! {field: uid}, None, session)
+                .await
+                .map_err(|e| Error::new("failed to delete user relations").with_cause(e))?;
+        }
+        self.update_many_with_session(
+            session,
+            "owners",
+            doc! {"user_id": uid},
+            doc! {"$set": {"nickname": null, "avatar_id": null, "deleted_at": now}},
+            None,
+        )
+        .await
+        .map_err(|e| Error::new("failed to anonymize owner").with_cause(e))?;
+        self.update_many_with_session(
+            session,
+            "walkers",
+            doc! {"user_id": uid},
+            doc! {
+                "$set": {"id_document_ids": [], "deleted_at": now},
+                "$unset": {"location": ""},
+            },
+            None,
+        )
+        .await
+        .map_err(|e| Error::new("failed to anonymize walker").with_cause(e))?;
+        // 上传文件由upload-service的清理任务按deletion_scheduled_at删除
+        let scheduled_uploads = self
+            .update_many_with_session(
+                session,
+                "uploads",
+                doc! {"owner_id": uid, "deletion_scheduled_at": null},
+                doc! {"$set": {"deletion_scheduled_at": now}},
+                None,
+            )
+            .await
+            .map_err(|e| Error::new("failed to schedule upload deletion").with_cause(e))?
+            .modified_count;
+        self.update_many_with_session(
+            session,
+            "refresh_tokens",
+            doc! {"user_id": uid, "revoked": false},
+            doc! {"$set": {"revoked": true}},
+            None,
+        )
+        .await
+        .map_err(|e| Error::new("failed to revoke refresh tokens").with_cause(e))?;
+        let removed_acceptances = released + withdrawn;
+        let id = self
+            .insert_one_with_session(
+                session,
+                "account_deletions",
+                doc! {
+                    "user_id": uid,
+                    "dogs_reassigned_to": &deletion.reassign_dogs_to,
+                    "dogs": dogs as i64,
+                    "canceled_walk_requests": canceled_walk_requests as i64,
+                    "removed_acceptances": removed_acceptances as i64,
+                    "scheduled_uploads": scheduled_uploads as i64,
+                },
+            )
+            .await
+            .map_err(|e| Error::new("failed to create account deletion record").with_cause(e))?
+            .inserted_id
+            .as_object_id()
+            .ok_or(
+                Error::new("failed to create account deletion record")
+                    .with_cause("invalid inserted id"),
+            )?
+            .to_string();
+        Ok(AccountDeletion {
+            id,
+            user_id: deletion.user_id,
+            dogs_reassigned_to: deletion.reassign_dogs_to,
+            dogs,
+            canceled_walk_requests,
+            removed_acceptances,
+            scheduled_uploads,
+            created_at: Some(now),
+        })
+    }
+
+    async fn balance_with_session(
+        &self,
+        session: &mut ClientSession,
+        user_id: &str,
+    ) -> Result<i64, Error> {
+        let mut cursor = self
+            .db
+            .collection::<Document>("ledger_entries")
+            .aggregate_with_session(balance_pipeline(user_id), None, session)
+            .await
+            .map_err(|e| Error::new("failed to query ledger balance").with_cause(e))?;
+        match cursor.next(session).await {
+            Some(doc) => doc
+                .map(|doc| balance_of(&doc))
+                .map_err(|e| Error::new("failed to query ledger balance").with_cause(e)),
+            None => Ok(0),
+        }
+    }
+
+    // 先改写遛狗人档案上的账本版本号, 同一用户并发的提现事务会因写冲突失败, 避免超额提现
+    async fn debit_withdrawal(
+        &self,
+        session: &mut ClientSession,
+        create: WithdrawalCreate,
+    ) -> Result<Option<String>, Error> {
+        self.update_many_with_session(
+            session,
+            "walkers",
+            doc! {"user_id": &create.user_id},
+            doc! {"$inc": {"ledger_version": 1}},
+            None,
+        )
+        .await
+        .map_err(|e| Error::new("failed to lock ledger").with_cause(e))?;
+        if self.balance_with_session(session, &create.user_id).await? < create.amount {
+            return Ok(None);
+        }
+        let user_id = create.user_id.clone();
+        let amount = create.amount;
+        let id = self
+            .insert_one_with_session(session, "withdrawals", Document::from_model(create))
+            .await
+            .map_err(|e| Error::new("failed to create withdrawal").with_cause(e))?
+            .inserted_id
+            .as_object_id()
+            .ok_or(Error::new("failed to create withdrawal").with_cause("invalid inserted id"))?
+            .to_string();
+        self.insert_one_with_session(
+            session,
+            "ledger_entries",
+            doc! {
+                "user_id": user_id,
+                "kind": LedgerEntryKind::Withdrawal.to_string(),
+                "amount": -amount,
+                "withdrawal_id": &id,
+            },
+        )
+        .await
+        .map_err(|e| Error::new("failed to create ledger entry").with_cause(e))?;
+        Ok(Some(id))
+    }
+
+    // 提现被拒绝或打款失败时在同一事务内写入退回流水
+    async fn apply_withdrawal_update(
+        &self,
+        session: &mut ClientSession,
+        query: WithdrawalQuery,
+        update: WithdrawalUpdate,
+    ) -> Result<u64, Error> {
+        let withdrawals = self
+            .db
+            .collection::<Document>("withdrawals")
+            .find_with_session(
+                Document::try_from_model(query)?,
+                FindOptions::builder()
+                    .projection(doc! {"user_id": 1, "amount": 1})
+                    .build(),
+                session,
+            )
+            .await
+            .map_err(|e| Error::new("failed to query withdrawals").with_cause(e))?
+            .stream(session)
+            .try_collect::<Vec<Document>>()
+            .await
+            .map_err(|e| Error::new("failed to query withdrawals").with_cause(e))?;
+        if withdrawals.is_empty() {
+            return Ok(0);
+        }
+        let ids = withdrawals
+            .iter()
+            .filter_map(|w| w.get_object_id("_id").ok())
+            .collect::<Vec<ObjectId>>();
+        let refunded = update.status.map(|s| s.is_refunded()).unwrap_or(false);
+        let updated = self
+            .update_many_with_session(
+                session,
+                "withdrawals",
+                doc! {"_id": {"$in": ids}},
+                Document::from_model(update),
+                None,
+            )
+            .await
+            .map_err(|e| Error::new("failed to update withdrawals").with_cause(e))?
+            .modified_count;
+        if refunded {
+            for withdrawal in withdrawals {
+                let id = withdrawal
+                    .get_object_id("_id")
+                    .map_err(|e| Error::new("invalid withdrawal").with_cause(e))?;
+                self.insert_one_with_session(
+                    session,
+                    "ledger_entries",
+                    doc! {
+                        "user_id": withdrawal.get_str("user_id").unwrap_or_default(),
+                        "kind": LedgerEntryKind::WithdrawalReversal.to_string(),
+                        "amount": withdrawal.get_i64("amount").unwrap_or_default(),
+                        "withdrawal_id": id.to_hex(),
+                    },
+                )
+                .await
+                .map_err(|e| Error::new("failed to create ledger entry").with_cause(e))?;
+            }
+        }
+        Ok(updated)
+    }
+
+    // 以待审核状态为条件更新, 并发审核同一申请时只有一方生效
+    async fn apply_refund_review(
+        &self,
+        session: &mut ClientSession,
+        review: RefundReview,
+    ) -> Result<Option<RefundRequest>, Error> {
+        let id = parse_object_id(&review.id)?;
+        let refund_limit = review.refund_limit;
+        let request = self
+            .db
+            .collection::<RefundRequest>("refund_requests")
+            .find_one_and_update_with_session(
+                doc! {"_id": id, "status": RefundStatus::Requested.to_string()},
+                stamp_update("refund_requests", Document::from_model(review), false),
+                FindOneAndUpdateOptions::builder()
+                    .projection(RefundRequest::projection())
+                    .return_document(Some(mongodb::options::ReturnDocument::After))
+                    .build(),
+                session,
+            )
+            .await
+            .map_err(|e| Error::new("failed to review refund request").with_cause(e))?;
+        let Some(request) = request else {
+            return Ok(None);
+        };
+        if request.status == RefundStatus::Approved {
+            // 与提现相同, 先改写账本版本号使同一遛狗人并发的退款审核发生写冲突, 再核对累计退款
+            self.update_many_with_session(
+                session,
+                "walkers",
+                doc! {"user_id": &request.walker_id},
+                doc! {"$inc": {"ledger_version": 1}},
+                None,
+            )
+            .await
+            .map_err(|e| Error::new("failed to lock ledger").with_cause(e))?;
+            let refunded = self
+                .approved_refunds_with_session(session, &request.walk_request_id)
+                .await?;
+            if refunded > refund_limit {
+                return Err(Error::conflict("累计退款超出遛狗费用"));
+            }
+        }
+        if let Some(walker_debit) = request.walker_debit {
+            self.insert_one_with_session(
+                session,
+                "ledger_entries",
+                doc! {
+                    "user_id": &request.walker_id,
+                    "kind": LedgerEntryKind::Refund.to_string(),
+                    "amount": -walker_debit,
+                    "walk_request_id": &request.walk_request_id,
+                },
+            )
+            .await
+            .map_err(|e| Error::new("failed to create ledger entry").with_cause(e))?;
+        }
+        Ok(Some(request))
+    }
+
+    // 该遛狗请求已批准的退款总额
+    async fn approved_refunds_with_session(
+        &self,
+        session: &mut ClientSession,
+        walk_request_id: &str,
+    ) -> Result<i64, Error> {
+        let mut cursor = self
+            .db
+            .collection::<Document>("refund_requests")
+            .aggregate_with_session(
+                vec![
+                    doc! {"$match": {
+                        "walk_request_id": walk_request_id,
+                        "status": RefundStatus::Approved.to_string(),
+                    }},
+                    doc! {"$group": {"_id": null, "total": {"$sum": "$approved_amount"}}},
+                ],
+                None,
+                session,
+            )
+            .await
+            .map_err(|e| Error::new("failed to sum approved refunds").with_cause(e))?;
+        match cursor.next(session).await {
+            Some(doc) => doc
+                .map(|doc| match doc.get("total") {
+                    Some(Bson::Int64(v)) => *v,
+                    Some(Bson::Int32(v)) => *v as i64,
+                    _ => 0,
+                })
+                .map_err(|e| Error::new("failed to sum approved refunds").with_cause(e)),
+            None => Ok(0),
+        }
+    }
+
+    async fn charge_cancellation_penalty(
+        &self,
+        session: &mut ClientSession,
+        create: CancellationPenaltyCreate,
+    ) -> Result<CancellationPenalty, Error> {
+        self.update_many_with_session(
+            session,
+            "walkers",
+            doc! {"user_id": &create.walker_id},
+            doc! {"$inc": {"ledger_version": 1}},
+            None,
+        )
+        .await
+        .map_err(|e| Error::new("failed to lock ledger").with_cause(e))?;
+        self.insert_one_with_session(
+            session,
+            "ledger_entries",
+            doc! {
+                "user_id": &create.walker_id,
+                "kind": LedgerEntryKind::CancellationPenalty.to_string(),
+                "amount": -create.amount,
+                "walk_request_id": &create.walk_request_id,
+            },
+        )
+        .await
+        .map_err(|e| Error::new("failed to create ledger entry").with_cause(e))?;
+        let balance_after = self
+            .balance_with_session(session, &create.walker_id)
+            .await?;
+        let id = self
+            .insert_one_with_session(
+                session,
+                "cancellation_penalties",
+                doc! {
+                    "walk_request_id": &create.walk_request_id,
+                    "walker_id": &create.walker_id,
+                    "owner_id": &create.owner_id,
+                    "tier": create.tier.to_string(),
+                    "scheduled_start": create.scheduled_start,
+                    "notice_minutes": create.notice_minutes,
+                    "amount": create.amount,
+                    "balance_after": balance_after,
+                },
+            )
+            .await
+            .map_err(|e| Error::new("failed to create cancellation penalty").with_cause(e))?
+            .inserted_id
+            .as_object_id()
+            .ok_or(
+                Error::new("failed to create cancellation penalty")
+                    .with_cause("invalid inserted id"),
+            )?
+            .to_string();
+        Ok(CancellationPenalty {
+            id,
+            walk_request_id: create.walk_request_id,
+            walker_id: create.walker_id,
+            owner_id: create.owner_id,
+            tier: create.tier,
+            scheduled_start: Some(create.scheduled_start),
+            notice_minutes: create.notice_minutes,
+            amount: create.amount,
+            balance_after,
+            created_at: Some(Utc::now()),
+        })
+    }
+}
+
+// 这些集合的文档带有版本号, 每次更新加1, 客户端据此检测并发修改
+const VERSIONED_COLLECTIONS: [&str; 2] = ["dogs", "walk_requests"];
+
+// 由ensure_indexes创建的唯一索引. 写入违反时按索引名找到冲突的字段, 返回Conflict错误
+struct UniqueIndex {
+    collection: &'static str,
+    name: &'static str,
+    keys: &'static [&'static str],
+    partial: Option<(&'static str, &'static str)>, // 只约束该字段等于该值的文档
+}
+
+const UNIQUE_INDEXES: [UniqueIndex; 12] = [
+    UniqueIndex {
+        collection: "breeds",
+        name: "breeds_name_category",
+        keys: &["name", "category"],
+        partial: None,
+    },
+    UniqueIndex {
+        collection: "breed_care_tips",
+        name: "breed_care_tips_breed_locale",
+        keys: &["breed_id", "locale"],
+        partial: None,
+    },
+    UniqueIndex {
+        collection: "oauth_accounts",
+        name: "oauth_accounts_provider_subject",
+        keys: &["provider", "subject"],
+        partial: None,
+    },
+    UniqueIndex {
+        collection: "short_links",
+        name: "short_links_code",
+        keys: &["code"],
+        partial: None,
+    },
+    UniqueIndex {
+        collection: "review_votes",
+        name: "review_votes_review_user",
+        keys: &["review_id", "user_id"],
+        partial: None,
+    },
+    UniqueIndex {
+        collection: "device_tokens",
+        name: "device_tokens_token",
+        keys: &["token"],
+        partial: None,
+    },
+    UniqueIndex {
+        collection: "chat_read_markers",
+        name: "chat_read_markers_request_user",
+        keys: &["walk_request_id", "user_id"],
+        partial: None,
+    },
+    UniqueIndex {
+        collection: "revoked_access_tokens",
+        name: "revoked_access_tokens_token_id",
+        keys: &["token_id"],
+        partial: None,
+    },
+    UniqueIndex {
+        collection: "phone_bindings",
+        name: "phone_bindings_user_id",
+        keys: &["user_id"],
+        partial: None,
+    },
+    // 加密存储时同一密钥下密文相同, 密钥轮换期间新旧密文不受该索引约束
+    UniqueIndex {
+        collection: "phone_bindings",
+        name: "phone_bindings_phone",
+        keys: &["phone"],
+        partial: None,
+    },
+    // 同一遛狗请求同时只能有一个待审核的退款申请
+    UniqueIndex {
+        collection: "refund_requests",
+        name: "refund_requests_pending_walk_request",
+        keys: &["walk_request_id"],
+        partial: Some(("status", "Requested")),
+    },
+    // 每次遛狗只记入一次收入, 并发结束时后写入的一方视为已入账
+    UniqueIndex {
+        collection: "ledger_entries",
+        name: "ledger_entries_walk_earning",
+        keys: &["kind", "walk_request_id"],
+        partial: Some(("kind", "Earning")),
+    },
+];
+
+const DUPLICATE_KEY_CODE: i32 = 11000;
+// IndexNotFound、NoQueryExecutionPlans: 没有可用于$geoNear的地理索引; 40324: 部署不支持该阶段
+const GEO_INDEX_ERROR_CODES: [i32; 3] = [27, 291, 40324];
+
+// 从E11000错误消息"... index: {索引名} dup key: ..."中找到违反的唯一索引
+fn duplicate_key_fields(message: &str) -> &'static [&'static str] {
+    let index = message
+        .split_once("index: ")
+        .and_then(|(_, rest)| rest.split_whitespace().next());
+    UNIQUE_INDEXES
+        .iter()
+        .find(|i| Some(i.name) == index)
+        .map(|i| i.keys)
+        .unwrap_or_default()
+}
+
+// 违反唯一索引时返回带字段的Conflict错误, 其他写入错误按内部错误处理
+fn write_error(e: mongodb::error::Error, msg: &str) -> Error {
+    let duplicate = match e.kind.as_ref() {
+        MongoErrorKind::Write(WriteFailure::WriteError(we)) if we.code == DUPLICATE_KEY_CODE => {
+            Some(we.message.as_str())
+        }
+        MongoErrorKind::Command(ce) if ce.code == DUPLICATE_KEY_CODE => Some(ce.message.as_str()),
+        _ => None,
+    };
+    match duplicate {
+        Some(message) => Error::duplicate(duplicate_key_fields(message)),
+        None => Error::new(msg).with_cause(e),
+    }
+}
+
+fn is_geo_index_error(e: &mongodb::error::Error) -> bool {
+    matches!(e.kind.as_ref(), MongoErrorKind::Command(ce) if GEO_INDEX_ERROR_CODES.contains(&ce.code))
+}
+
+// 版本号字段之前的文档视为版本0
+fn version_filter(version: i64) -> Bson {
+    match version {
+        0 => doc! {"$in": [0_i64, null]}.into(),
+        version => version.into(),
+    }
+}
+
+// 排在游标之后的记录: 排序键在游标之后, 或排序键相同而_id在游标之后.
+// 排序键为null时排在所有非null值之前
+fn cursor_filter(sort_by: Option<&SortBy>, after: &Cursor) -> Document {
+    let Some(sort_by) = sort_by else {
+        return doc! { "_id": { "$gt": after.id } };
+    };
+    let field = sort_by.field.as_str();
+    let asc = sort_by.order == Order::Asc;
+    let op = if asc { "$gt" } else { "$lt" };
+    let tie = doc! { field: after.sort_key, "_id": { op: after.id } };
+    match (after.sort_key, asc) {
+        (Some(key), true) => doc! { "$or": [{ field: { "$gt": key } }, tie] },
+        (Some(key), false) => doc! { "$or": [{ field: { "$lt": key } }, { field: null }, tie] },
+        (None, true) => doc! { "$or": [{ field: { "$ne": null } }, tie] },
+        (None, false) => tie,
+    }
+}
+
+fn stamp_update(collection: &str, mut update: Document, upsert: bool) -> Document {
+    let now = Utc::now();
+    let mut set = update.get_document("$set").cloned().unwrap_or_default();
+    set.insert("updated_at", now);
+    update.insert("$set", set);
+    if VERSIONED_COLLECTIONS.contains(&collection) {
+        let mut inc = update.get_document("$inc").cloned().unwrap_or_default();
+        inc.insert("version", 1_i64);
+        update.insert("$inc", inc);
+    }
+    if upsert {
+        let mut set_on_insert = update
+            .get_document("$setOnInsert")
+            .cloned()
+            .unwrap_or_default();
+        set_on_insert.insert("created_at", now);
+        update.insert("$setOnInsert", set_on_insert);
+    }
+    update
+}
+
+impl Repository for MongoDB {
+    // 已在事务中时复用外层事务, 由外层提交或回滚. 事务中的写入、聚合查询和get_walk_request经由session,
+    // 能读到本事务未提交的写入; 其余直接查询集合的读操作不在事务内, 事务中不应依赖它们的结果
+    async fn with_transaction<T, F, Fut>(&self, f: F) -> Result<T, Error>
+    where
+        F: FnOnce(Self) -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        if self.session.is_some() {
+            return f(self.clone()).await;
+        }
+        let mut session = self
+            .client
+            .start_session(None)
+            .await
+            .map_err(|e| Error::new("failed to start session").with_cause(e))?;
+        session
+            .start_transaction(None)
+            .await
+            .map_err(|e| Error::new("failed to start transaction").with_cause(e))?;
+        let session = Arc::new(Mutex::new(session));
+        let result = f(Self {
+            session: Some(session.clone()),
+            ..self.clone()
+        })
+        .await;
+        let mut session = session.lock().await;
+        match result {
+            Ok(value) => {
+                session
+                    .commit_transaction()
+                    .await
+                    .map_err(|e| Error::new("failed to commit transaction").with_cause(e))?;
+                Ok(value)
+            }
+            Err(e) => {
+                session.abort_transaction().await.ok();
+                Err(e)
+            }
+        }
+    }
+
+    async fn create_breed(&self, breed: &BreedCreate) -> Result<String, Error> {
+        let d = doc! {
+            "name": &breed.name,
+            "category": &breed.category.to_string(),
+        };
+        let res = self
+            .insert_one("breeds", d)
+            .await
+            .map_err(|e| write_error(e, "failed to create breed"))?;
+        res.inserted_id
+            .as_object_id()
+            .ok_or(Error::new("failed to create breed").with_cause("invalid inserted id"))
+            .map(|id| id.to_string())
+    }
+
+    async fn create_dog(&self, dog: &DogCreate) -> Result<Dog, Error> {
+        let dog = Document::try_from_model(dog)?;
+        let res = self
+            .insert_one("dogs", dog)
+            .await
+            .map_err(|e| Error::new("failed to create dog").with_cause(e))?;
+        self.db
+            .collection("dogs")
+            .find_one(
+                doc! {"_id": res.inserted_id},
+                FindOneOptions::builder()
+                    .projection(Dog::projection())
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to get created dog").with_cause(e))?
+            .ok_or(Error::new("created dog not exists"))
+    }
+
+    async fn delete_breed(&self, id: &str) -> Result<bool, Error> {
+        self.db
+            .collection::<Breed>("breeds")
+            .delete_one(
+                doc! {"_id": parse_object_id(id)?},
+                None,
+            )
+            .await
+            .map_err(|e| Error::new("failed to delete breed").with_cause(e))
+            .map(|res| res.deleted_count > 0)
+    }
+
+    async fn delete_dog(&self, id: &str) -> Result<bool, Error> {
+        self.update_one(
+            "dogs",
+            doc! {
+                "_id": parse_object_id(id)?,
+                "deleted_at": null,
+            },
+            doc! {"$set": {"deleted_at": Utc::now()}},
+            None,
+        )
+        .await
+        .map_err(|e| Error::new("failed to delete dog").with_cause(e))
+        .map(|res| res.modified_count > 0)
+    }
+
+    async fn update_dog(&self, id: &str, dog: &DogUpdate) -> Result<bool, Error> {
+        let mut update = doc! {};
+        if let Some(name) = &dog.name {
+            update.insert("name", name);
+        }
+        if let Some(gender) = &dog.gender {
+            update.insert("gender", gender);
+        }
+        if let Some(breed) = &dog.breed {
+            update.insert("breed", &breed.id);
+        }
+        if let Some(birthday) = &dog.birthday {
+            update.insert("birthday", birthday);
+        }
+        if let Some(is_sterilized) = &dog.is_sterilized {
+            update.insert("is_sterilized", is_sterilized);
+        }
+        let mut unset = doc! {};
+        apply_update_field(&mut update, &mut unset, "introduction", dog.introduction.clone());
+        if let Some(owner_id) = &dog.owner_id {
+            update.insert("owner_id", owner_id);
+        }
+        if let Some(tags) = &dog.tags {
+            update.insert("tags", tags);
+        }
+        apply_update_field(&mut update, &mut unset, "portrait_id", dog.portrait_id.clone());
+        if let Some(photos) = &dog.photos {
+            update.insert("photos", photos);
+        }
+        if let Some(medical_flags) = &dog.medical_flags {
+            update.insert("medical_flags", medical_flags);
+        }
+        let mut update = if update.is_empty() {
+            doc! {}
+        } else {
+            doc! {"$set": update}
+        };
+        if !unset.is_empty() {
+            update.insert("$unset", unset);
+        }
+        if let Some(add_to_photos) = &dog.add_to_photos {
+            update.insert("$push", doc! {"photos": add_to_photos});
+        }
+        if let Some(remove_from_photos) = &dog.remove_from_photos {
+            update.insert("$pull", doc! {"photos": remove_from_photos});
+        }
+        if update.is_empty() {
+            return Ok(false);
+        }
+        let mut filter = doc! {
+            "_id": parse_object_id(id)?,
+            "deleted_at": null,
+        };
+        if let Some(edited_at) = dog.edited_at {
+            // 旧数据的updated_at可能不是日期类型, 不参与冲突检测
+            filter.insert(
+                "$or",
+                vec![
+                    doc! {"updated_at": {"$lte": edited_at}},
+                    doc! {"updated_at": {"$not": {"$type": "date"}}},
+                ],
+            );
+        }
+        if let Some(version) = dog.expected_version {
+            filter.insert("version", version_filter(version));
+        }
+        Ok(self
+            .update_one("dogs", filter, update, None)
+            .await
+            .map_err(|e| Error::new("failed to update dog").with_cause(e))?
+            .modified_count
+            > 0)
+    }
+
+    async fn query_breeds(&self, query: &BreedQuery) -> Result<(Vec<Breed>, i64), Error> {
+        let mut q = doc! {};
+        if let Some(id) = &query.id {
+            q.insert("_id", parse_object_id(id)?);
+        }
+        if let Some(category) = &query.category {
+            q.insert("category", category.to_string());
+        }
+        let count = self
+            .db
+            .collection::<Breed>("breeds")
+            .count_documents(q.clone(), None)
+            .await
+            .map_err(|e| Error::new("failed to query breeds").with_cause(e))?;
+        let mut options = FindOptions::builder()
+            .projection(doc! {
+                "id": { "$toString": "$_id" },
+                "category": 1,
+                "name": 1,
+                "created_at": 1,
+                "updated_at": {"$dateToString": {"date":"$updated_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            })
+            .build();
+        if let Some(sort_by) = &query.sort_by {
+            let direction = if sort_by.order == Order::Asc { 1 } else { -1 };
+            options.sort = Some(doc! { sort_by.field.as_str(): direction, "_id": direction });
+        }
+        let breeds = self
+            .db
+            .collection::<Breed>("breeds")
+            .find(q, options)
+            .await
+            .map_err(|e| Error::new("failed to query breeds").with_cause(e))?
+            .try_collect::<Vec<Breed>>()
+            .await
+            .map_err(|e| Error::new("failed to query breeds").with_cause(e))?;
+        Ok((breeds, count as i64))
+    }
+
+    async fn query_breed_care_tips(
+        &self,
+        breed_ids: &[String],
+        locale: &str,
+    ) -> Result<Vec<BreedCareTips>, Error> {
+        self.db
+            .collection::<BreedCareTips>("breed_care_tips")
+            .find(
+                doc! {"breed_id": {"$in": breed_ids}, "locale": locale},
+                FindOptions::builder()
+                    .projection(BreedCareTips::projection())
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to query breed care tips").with_cause(e))?
+            .try_collect::<Vec<BreedCareTips>>()
+            .await
+            .map_err(|e| Error::new("failed to query breed care tips").with_cause(e))
+    }
+
+    async fn upsert_breed_care_tips(&self, upsert: BreedCareTipsUpsert) -> Result<(), Error> {
+        self.update_one(
+            "breed_care_tips",
+            doc! {"breed_id": upsert.breed_id, "locale": upsert.locale},
+            doc! {"$set": {"tips": upsert.tips, "updated_by": upsert.updated_by}},
+            Some(UpdateOptions::builder().upsert(true).build()),
+        )
+        .await
+        .map_err(|e| Error::new("failed to upsert breed care tips").with_cause(e))
+        .map(|_| ())
+    }
+
+    async fn delete_breed_care_tips(&self, breed_id: &str, locale: &str) -> Result<bool, Error> {
+        self.db
+            .collection::<Document>("breed_care_tips")
+            .delete_one(doc! {"breed_id": breed_id, "locale": locale}, None)
+            .await
+            .map_err(|e| Error::new("failed to delete breed care tips").with_cause(e))
+            .map(|res| res.deleted_count > 0)
+    }
+
+    async fn query_dogs(&self, query: &DogQuery) -> Result<Vec<Dog>, Error> {
+        let mut q = doc! {};
+        if !query.include_deleted {
+            q.insert("deleted_at", Bson::Null);
+        }
+        if let Some(owner_id) = &query.owner_id {
+            q.insert("owner_id", owner_id);
+        }
+        if let Some(breed_id) = &query.breed_id {
+            q.insert("breed.id", breed_id);
+        }
+        if let Some(tags) = &query.tags_include_any {
+            q.insert("tags", doc! { "$in": tags });
+        }
+        if let Some(gender) = &query.gender {
+            q.insert("gender", gender.to_string());
+        }
+        let mut birthday = doc! {};
+        if let Some(before) = query.birthday_before {
+            birthday.insert("$lt", before);
+        }
+        if let Some(after) = query.birthday_after {
+            birthday.insert("$gt", after);
+        }
+        if !birthday.is_empty() {
+            q.insert("birthday", birthday);
+        }
+        let mut id_filter = doc! {};
+        if let Some(id_in) = &query.id_in {
+            id_filter.insert("$in", parse_object_ids(id_in)?);
+        }
+        // 分页时按_id排序, 偏移分页的结果也能切换到游标分页
+        let options = FindOptions::builder().projection(sparse_projection(
+            Dog::projection(),
+            query.fields.as_ref(),
+            DOG_REQUIRED_FIELDS,
+        ));
+        let mut options = match &query.pagination {
+            None => options.build(),
+            Some(Page::Offset(pagination)) => options
+                .sort(doc! { "_id": 1 })
+                .skip(pagination.skip as u64)
+                .limit(pagination.limit)
+                .build(),
+            Some(Page::Cursor { after, limit }) => {
+                if let Some(after) = after {
+                    id_filter.insert("$gt", after.id);
+                }
+                options.sort(doc! { "_id": 1 }).limit(*limit).build()
+            }
+        };
+        // 指定排序字段时以_id作为次序
+        if let Some(sort_by) = &query.sort_by {
+            let direction = if sort_by.order == Order::Asc { 1 } else { -1 };
+            options.sort = Some(doc! { sort_by.field.as_str(): direction, "_id": direction });
+        }
+        if !id_filter.is_empty() {
+            q.insert("_id", id_filter);
+        }
+        self.db
+            .collection::<Dog>("dogs")
+            .find(q, options)
+            .await
+            .map_err(|e| Error::new("failed to query my dogs").with_cause(e))?
+            .try_collect::<Vec<Dog>>()
+            .await
+            .map_err(|e| Error::new("failed to query my dogs").with_cause(e))
+        // let mut pipeline = vec![
+        //     doc! {
+        //         "$match": q,
+        //     },
+        //     doc! {
+        //         "$addFields": {
+        //             "breed_id": { "$toObjectId": "$breed" }
+        //         }
+        //     },
+        //     doc! {
+        //         "$lookup": {
+        //             "from": "breeds",
+        //             "localField": "breed_id",
+        //             "foreignField": "_id",
+        //             "as": "breed",
+        //             "pipeline": [
+        //                 {
+        //                     "$project": {
+        //                         "id": { "$toString": "$_id" },
+        //                         "category": 1,
+        //                         "name": 1,
+        //                         "created_at": 1,
+        //                         "updated_at": 1,
+        //                     }
+
+        //                 }
+        //             ]
+
+        //         }
+        //     },
+        //     doc! {
+        //         "$project": {
+        //             "id": { "$toString": "$_id" },
+        //             "name": 1,
+        //             "gender": 1,
+        //             "breed": { "$arrayElemAt": [ "$breed", 0 ] } ,
+        //             "birthday": 1,
+        //             "is_sterilized": 1,
+        //             "introduction": 1,
+        //             "owner_id": 1,
+        //             "tags": 1,
+        //             "portrait_id": 1,
+        //             "created_at": 1,
+        //             "updated_at": 1,
+        //         }
+        //     },
+        // ];
+        // if let Some(pagination) = &query.pagination {
+        //     pipeline.append(&mut vec![
+        //         doc! {
+        //             "$limit": pagination.limit
+        //         },
+        //         doc! {
+        //             "$skip": pagination.skip
+        //         },
+        //     ])
+        // }
+        // let dogs = self
+        //     .db
+        //     .collection::<Dog>("dogs")
+        //     .aggregate(pipeline, None)
+        //     .await
+        //     .map_err(|e| Error::new("failed to query my dogs").with_cause(e))?
+        //     .try_collect::<Vec<Document>>()
+        //     .await
+        //     .map(|ds| {
+        //         ds.into_iter()
+        //             .map(|d| from_document::<Dog>(d).map_err(|e| Error::new("failed to convert dog to document").with_cause(e)))
+        //     })
+        //     .map_err(|e| Error::new("failed to query my dogs").with_cause(e))?
+        //     .collect::<Result<Vec<Dog>, Error>>()?;
+        // Ok(dogs)
+    }
+
+    async fn exists_dog(&self, query: &DogQuery) -> Result<bool, Error> {
+        let mut q = doc! {};
+        if let Some(id) = &query.id {
+            q.insert(
+                "_id",
+                parse_object_id(id)?,
+            );
+        }
+        if let Some(owner_id) = &query.owner_id {
+            q.insert("owner_id", owner_id);
+        }
+        if !query.include_deleted {
+            q.insert("deleted_at", Bson::Null);
+        }
+        Ok(self
+            .db
+            .collection::<Dog>("dogs")
+            .count_documents(q.clone(), None)
+            .await
+            .map_err(|e| Error::new("failed to query my dogs").with_cause(e))?
+            > 0)
+    }
+
+    async fn create_walk_request(&self, request: WalkRequestCreate) -> Result<String, Error> {
+        let inserted = self
+            .insert_one("walk_requests", Document::from_model(request))
+            .await
+            .map_err(|e| Error::new("failed to create walk request").with_cause(e))?;
+        Ok(inserted.inserted_id.to_string())
+    }
+
+    async fn get_walk_request(&self, id: &str) -> Result<WalkRequest, Error> {
+        self.find_one::<WalkRequest>(
+            "walk_requests",
+            doc! {
+                "_id": parse_object_id(id)?,
+                "deleted_at": null,
+            },
+            FindOneOptions::builder()
+                .projection(WalkRequest::projection())
+                .build(),
+        )
+        .await
+        .map_err(|e| Error::new("failed to get walk request").with_cause(e))?
+        .ok_or(Error::not_found("walk request not found"))
+    }
+
+    // 列表和总数在同一个$facet聚合中计算, 只需一次往返
+    async fn query_walk_requests(
+        &self,
+        query: WalkRequestQuery,
+        sort_by: Option<SortBy>,
+        pagination: Option<Page>,
+    ) -> Result<(Vec<WalkRequest>, u64), Error> {
+        let nearby = query.nearby.is_some();
+        let fields = query.fields.clone();
+        let filter = Document::try_from_model(query)?;
+        let mut pipeline = vec![if nearby { filter } else { doc! { "$match": filter } }];
+        // 排序键相同时按_id排序, 使偏移分页的结果也能用最后一条记录生成游标
+        if let Some(sort_by) = &sort_by {
+            let direction = if sort_by.order == Order::Asc { 1 } else { -1 };
+            pipeline.push(doc! {
+                "$sort": { sort_by.field.as_str(): direction, "_id": direction }
+            });
+        } else if matches!(pagination, Some(Page::Cursor { .. })) {
+            pipeline.push(doc! { "$sort": { "_id": 1 } });
+        }
+        let mut page = Vec::new();
+        match pagination {
+            None => {}
+            Some(Page::Offset(pagination)) => {
+                page.push(doc! { "$skip": pagination.skip });
+                if pagination.limit > 0 {
+                    page.push(doc! { "$limit": pagination.limit });
+                }
+            }
+            Some(Page::Cursor { after, limit }) => {
+                if let Some(after) = after {
+                    page.push(doc! { "$match": cursor_filter(sort_by.as_ref(), &after) });
+                }
+                if limit > 0 {
+                    page.push(doc! { "$limit": limit });
+                }
+            }
+        }
+        // 排序键用于生成下一页的游标, 总是投影
+        let required = sort_by.iter().map(|s| s.field.as_str()).collect::<Vec<&str>>();
+        page.push(doc! { "$project": sparse_projection(WalkRequest::projection(), fields.as_ref(), &required) });
+        pipeline.push(doc! {
+            "$facet": {
+                "list": page,
+                "total": [{ "$count": "count" }],
+            }
+        });
+        let listing = self.listing();
+        let mut results = if nearby {
+            listing
+                .aggregate_near("walk_requests", "location", pipeline)
+                .await?
+        } else {
+            listing.aggregate_all("walk_requests", pipeline).await?
+        };
+        let result = results
+            .pop()
+            .ok_or(Error::new("failed to query walk requests").with_cause("empty facet result"))?;
+        let total = result
+            .get_array("total")
+            .ok()
+            .and_then(|total| total.first())
+            .and_then(Bson::as_document)
+            .and_then(|d| d.get("count"))
+            .and_then(|count| count.as_i32().map(i64::from).or(count.as_i64()))
+            .unwrap_or(0) as u64;
+        let list = result
+            .get_array("list")
+            .map_err(|e| Error::new("failed to query walk requests").with_cause(e))?
+            .iter()
+            .filter_map(Bson::as_document)
+            .map(|d| {
+                from_document::<WalkRequest>(d.clone())
+                    .map_err(|e| Error::new("failed to convert document").with_cause(e))
+            })
+            .collect::<Result<Vec<WalkRequest>, Error>>()?;
+        Ok((list, total))
+    }
+
+    async fn update_walk_request(
+        &self,
+        id: &str,
+        request: WalkRequestUpdate,
+    ) -> Result<WalkRequest, Error> {
+        self.update_versioned_walk_request(
+            doc! {"_id": parse_object_id(id)?, "deleted_at": null},
+            request,
+        )
+        .await
+    }
+
+    async fn update_walk_request_by_query(
+        &self,
+        query: WalkRequestQuery,
+        update: WalkRequestUpdate,
+    ) -> Result<WalkRequest, Error> {
+        self.update_versioned_walk_request(Document::try_from_model(query)?, update)
+            .await
+    }
+
+    async fn update_walk_requests_by_query(
+        &self,
+        query: WalkRequestQuery,
+        update: WalkRequestUpdate,
+    ) -> Result<u64, Error> {
+        let mut filter = Document::try_from_model(query)?;
+        if let Some(version) = update.expected_version {
+            filter.insert("version", version_filter(version));
+        }
+        Ok(self
+            .update_many("walk_requests", filter, Document::from_model(update))
+            .await
+            .map_err(Error::from_error)?
+            .modified_count)
+    }
+
+    async fn create_walking_location<'a>(
+        &self,
+        create: WalkingLocationCreate<'a>,
+    ) -> Result<String, Error> {
+        self.insert_one("walking_locations", Document::from_model(create))
+            .await
+            .map_err(|e| Error::wrap(e, "创建Walking定位失败"))
+            .map(|r| r.inserted_id.to_string())
+    }
+
+    async fn query_walking_locations(
+        &self,
+        walk_request_id: &str,
+    ) -> Result<Vec<WalkingLocation>, Error> {
+        self.db
+            .collection::<WalkingLocation>("walking_locations")
+            .find(
+                doc! {"walk_request_id": walk_request_id},
+                FindOptions::builder()
+                    .projection(WalkingLocation::projection())
+                    .sort(doc! {"created_at": 1, "_id": 1})
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::wrap(e, "查询Walking定位失败"))?
+            .try_collect::<Vec<WalkingLocation>>()
+            .await
+            .map_err(|e| Error::wrap(e, "查询Walking定位失败"))
+    }
+
+    async fn create_poi(&self, create: PoiCreate) -> Result<String, Error> {
+        self.insert_one("pois", Document::from_model(create))
+            .await
+            .map_err(|e| Error::new("failed to create poi").with_cause(e))?
+            .inserted_id
+            .as_object_id()
+            .ok_or(Error::new("failed to create poi").with_cause("invalid inserted id"))
+            .map(|id| id.to_hex())
+    }
+
+    // 按中心点筛选, 使用$centerSphere不依赖地理索引
+    async fn query_pois(&self, query: PoiQuery) -> Result<Vec<Poi>, Error> {
+        let mut q = doc! {};
+        if let (Some(near), Some(max_distance)) = (query.near, query.max_distance) {
+            q.insert(
+                "location",
+                doc! {"$geoWithin": {"$centerSphere": [[near.longitude, near.latitude], max_distance / EARTH_RADIUS]}},
+            );
+        }
+        self.db
+            .collection::<Poi>("pois")
+            .find(
+                q,
+                FindOptions::builder()
+                    .projection(Poi::projection())
+                    .sort(doc! {"_id": 1})
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to query pois").with_cause(e))?
+            .try_collect()
+            .await
+            .map_err(|e| Error::new("failed to query pois").with_cause(e))
+    }
+
+    async fn delete_poi(&self, id: &str) -> Result<bool, Error> {
+        self.db
+            .collection::<Document>("pois")
+            .delete_one(doc! {"_id": parse_object_id(id)?}, None)
+            .await
+            .map_err(|e| Error::new("failed to delete poi").with_cause(e))
+            .map(|res| res.deleted_count > 0)
+    }
+
+    async fn get_walker(&self, user_id: &str) -> Result<Option<Walker>, Error> {
+        self.db
+            .collection::<Walker>("walkers")
+            .find_one(
+                doc! {"user_id": user_id},
+                FindOneOptions::builder()
+                    .projection(Walker::projection())
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to get walker").with_cause(e))
+    }
+
+    async fn submit_walker_verification<'a>(
+        &self,
+        submit: WalkerVerificationSubmit<'a>,
+    ) -> Result<Walker, Error> {
+        self.find_one_and_update(
+            "walkers",
+            doc! {"user_id": submit.user_id},
+            doc! {
+                "$set": {
+                    "verification_status": VerificationStatus::Pending.to_string(),
+                    "id_document_ids": submit.id_document_ids,
+                    "submitted_at": Utc::now(),
+                },
+                "$unset": {"rejected_reason": "", "reviewed_at": ""},
+            },
+            FindOneAndUpdateOptions::builder()
+                .upsert(true)
+                .return_document(Some(mongodb::options::ReturnDocument::After))
+                .projection(Walker::projection())
+                .build(),
+        )
+        .await
+        .map_err(|e| Error::new("failed to submit walker verification").with_cause(e))?
+        .ok_or(Error::new("submitted walker not exists"))
+    }
+
+    async fn update_walkers_by_query(
+        &self,
+        query: WalkerQuery,
+        update: WalkerUpdate,
+    ) -> Result<u64, Error> {
+        Ok(self
+            .update_many("walkers", Document::from_model(query), Document::from_model(update))
+            .await
+            .map_err(|e| Error::new("failed to update walkers").with_cause(e))?
+            .modified_count)
+    }
+
+    async fn query_walkers(
+        &self,
+        query: WalkerQuery,
+        pagination: Option<Pagination>,
+    ) -> Result<(Vec<Walker>, i64), Error> {
+        let q = Document::from_model(query);
+        let total = self
+            .db
+            .collection::<Walker>("walkers")
+            .count_documents(q.clone(), None)
+            .await
+            .map_err(|e| Error::new("failed to query walkers").with_cause(e))?;
+        let walkers = self
+            .db
+            .collection::<Walker>("walkers")
+            .find(
+                q,
+                FindOptions::builder()
+                    .projection(Walker::projection())
+                    .sort(doc! {"submitted_at": 1})
+                    .skip(pagination.as_ref().map(|p| p.skip as u64))
+                    .limit(pagination.as_ref().map(|p| p.limit))
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to query walkers").with_cause(e))?
+            .try_collect::<Vec<Walker>>()
+            .await
+            .map_err(|e| Error::new("failed to query walkers").with_cause(e))?;
+        Ok((walkers, total as i64))
+    }
+
+    async fn explain(&self, template: QueryTemplate) -> Result<QueryPlan, Error> {
+        let (collection, command) = match template {
+            QueryTemplate::NearbyWalkRequests {
+                longitude,
+                latitude,
+                radius,
+            } => (
+                "walk_requests",
+                doc! {
+                    "aggregate": "walk_requests",
+                    "pipeline": [
+                        Document::try_from_model(WalkRequestQuery {
+                            accepted_by_is_null: Some(true),
+                            nearby: Some(vec![longitude, latitude, radius]),
+                            ..Default::default()
+                        })?,
+                        { "$project": WalkRequest::projection() },
+                    ],
+                    "cursor": {},
+                },
+            ),
+            QueryTemplate::MyWalkRequests { user_id } => (
+                "walk_requests",
+                doc! {
+                    "find": "walk_requests",
+                    "filter": Document::try_from_model(WalkRequestQuery {
+                        created_by: Some(user_id),
+                        ..Default::default()
+                    })?,
+                    "sort": { "created_at": -1 },
+                },
+            ),
+            QueryTemplate::DogsByOwner { owner_id } => (
+                "dogs",
+                doc! {
+                    "find": "dogs",
+                    "filter": { "owner_id": owner_id, "deleted_at": null },
+                },
+            ),
+            QueryTemplate::WalkerVerifications {
+                verification_status,
+            } => (
+                "walkers",
+                doc! {
+                    "find": "walkers",
+                    "filter": Document::from_model(WalkerQuery {
+                        verification_status: Some(verification_status),
+                        ..Default::default()
+                    }),
+                    "sort": { "submitted_at": 1 },
+                },
+            ),
+        };
+        let explained = self
+            .db
+            .run_command(
+                doc! { "explain": command, "verbosity": "executionStats" },
+                None,
+            )
+            .await
+            .map_err(|e| Error::new("failed to explain query").with_cause(e))?;
+        let mut plan = QueryPlan {
+            collection: collection.to_owned(),
+            ..Default::default()
+        };
+        if let Some(winning_plan) = find_document(&explained, "winningPlan") {
+            collect_plan_stages(winning_plan, &mut plan.stages, &mut plan.indexes);
+        }
+        if let Some(stats) = find_document(&explained, "executionStats") {
+            plan.returned = get_number(stats, "nReturned");
+            plan.keys_examined = get_number(stats, "totalKeysExamined");
+            plan.docs_examined = get_number(stats, "totalDocsExamined");
+            plan.execution_time_millis = get_number(stats, "executionTimeMillis");
+        }
+        Ok(plan)
+    }
+
+    async fn update_walker_location(
+        &self,
+        user_id: &str,
+        longitude: f64,
+        latitude: f64,
+    ) -> Result<(), Error> {
+        self.update_one(
+            "walkers",
+            doc! {"user_id": user_id},
+            doc! {
+                "$set": {
+                    "location": { "type": "Point", "coordinates": [longitude, latitude] },
+                },
+            },
+            Some(UpdateOptions::builder().upsert(true).build()),
+        )
+        .await
+        .map_err(|e| Error::new("failed to update walker location").with_cause(e))
+        .map(|_| ())
+    }
+
+    async fn search_walkers(&self, search: WalkerSearch) -> Result<Vec<RankedWalker>, Error> {
+        let pipeline = vec![
+            doc! {
+                "$geoNear": {
+                    "near": { "type": "Point", "coordinates": [search.longitude, search.latitude] },
+                    "distanceField": "distance",
+                    "maxDistance": search.radius,
+                    "spherical": true,
+                    "query": { "verification_status": VerificationStatus::Verified.to_string() },
+                }
+            },
+            doc! {
+                "$lookup": {
+                    "from": "reviews",
+                    "localField": "user_id",
+                    "foreignField": "walker_id",
+                    "as": "reviews",
+                    "pipeline": [{ "$match": revealed_walker_reviews_filter() }, { "$project": { "rating": 1 } }],
+                }
+            },
+            doc! {
+                "$lookup": {
+                    "from": "walk_requests",
+                    "localField": "user_id",
+                    "foreignField": "accepted_by",
+                    "as": "completed_walks",
+                    "pipeline": [
+                        { "$match": { "finished_at": { "$ne": null }, "canceled_at": null } },
+                        { "$project": { "_id": 1 } },
+                    ],
+                }
+            },
+            doc! {
+                "$addFields": {
+                    "average_rating": { "$ifNull": [{ "$avg": "$reviews.rating" }, 0.0] },
+                    "completed_walks": { "$size": "$completed_walks" },
+                }
+            },
+            doc! {
+                "$addFields": {
+                    "score": {
+                        "$add": [
+                            { "$multiply": [RATING_WEIGHT, { "$divide": ["$average_rating", 5] }] },
+                            { "$multiply": [
+                                COMPLETED_WALKS_WEIGHT,
+                                { "$divide": ["$completed_walks", { "$add": ["$completed_walks", COMPLETED_WALKS_HALF_SCORE] }] },
+                            ] },
+                            { "$multiply": [
+                                DISTANCE_WEIGHT,
+                                { "$subtract": [1, { "$divide": ["$distance", search.radius] }] },
+                            ] },
+                        ]
+                    }
+                }
+            },
+            doc! { "$sort": { "score": -1 } },
+            doc! { "$skip": search.pagination.skip },
+            doc! { "$limit": search.pagination.limit },
+            doc! {
+                "$lookup": {
+                    "from": "owners",
+                    "localField": "user_id",
+                    "foreignField": "user_id",
+                    "as": "owner",
+                    "pipeline": [{ "$project": { "avatar_id": 1 } }],
+                }
+            },
+            doc! {
+                "$project": {
+                    "_id": 0,
+                    "user_id": 1,
+                    "longitude": { "$arrayElemAt": [ "$location.coordinates", 0] },
+                    "latitude": { "$arrayElemAt": [ "$location.coordinates", 1] },
+                    "distance": 1,
+                    "average_rating": { "$toDouble": "$average_rating" },
+                    "completed_walks": { "$toLong": "$completed_walks" },
+                    "score": 1,
+                    "avatar_id": { "$first": "$owner.avatar_id" },
+                }
+            },
+        ];
+        self.listing()
+            .aggregate_near("walkers", "location", pipeline)
+            .await
+            .map_err(|e| Error::new("failed to search walkers").with_cause(e))?
+            .into_iter()
+            .map(|doc| {
+                from_document::<RankedWalker>(doc)
+                    .map_err(|e| Error::new("failed to convert document").with_cause(e))
+            })
+            .collect()
+    }
+
+    async fn walker_stats(&self, walker_id: &str) -> Result<WalkerStats, Error> {
+        let finished = doc! {"$and": [
+            {"$ne": [{"$ifNull": ["$finished_at", null]}, null]},
+            {"$eq": [{"$ifNull": ["$canceled_at", null]}, null]},
+        ]};
+        let canceled = doc! {"$ne": [{"$ifNull": ["$canceled_at", null]}, null]};
+        let mut walks_pipeline = vec![doc! { "$match": { "accepted_by": walker_id } }];
+        walks_pipeline.extend(walk_distance_stages());
+        walks_pipeline.push(doc! {
+            "$group": {
+                "_id": null,
+                "accepted": { "$sum": 1 },
+                "completed_walks": { "$sum": { "$cond": [finished.clone(), 1, 0] } },
+                "canceled": { "$sum": { "$cond": [canceled, 1, 0] } },
+                "total_distance": { "$sum": { "$cond": [finished, "$distance", 0.0] } },
+            }
+        });
+        let walks = self
+            .db
+            .collection::<Document>("walk_requests")
+            .aggregate(walks_pipeline, None)
+            .await
+            .map_err(|e| Error::new("failed to aggregate walker walks").with_cause(e))?
+            .try_next()
+            .await
+            .map_err(|e| Error::new("failed to aggregate walker walks").with_cause(e))?
+            .unwrap_or_default();
+        let mut review_filter = revealed_walker_reviews_filter();
+        review_filter.insert("walker_id", walker_id);
+        let reviews_pipeline = vec![
+            doc! { "$match": review_filter },
+            doc! {
+                "$group": {
+                    "_id": null,
+                    "average_rating": { "$avg": "$rating" },
+                    "review_count": { "$sum": 1 },
+                }
+            },
+        ];
+        let reviews = self
+            .db
+            .collection::<Document>("reviews")
+            .aggregate(reviews_pipeline, None)
+            .await
+            .map_err(|e| Error::new("failed to aggregate walker reviews").with_cause(e))?
+            .try_next()
+            .await
+            .map_err(|e| Error::new("failed to aggregate walker reviews").with_cause(e))?
+            .unwrap_or_default();
+        let accepted = get_number(&walks, "accepted");
+        Ok(WalkerStats {
+            walker_id: walker_id.to_owned(),
+            completed_walks: get_number(&walks, "completed_walks"),
+            total_distance: walks.get_f64("total_distance").unwrap_or_default(),
+            cancellation_rate: if accepted > 0 {
+                get_number(&walks, "canceled") as f64 / accepted as f64
+            } else {
+                0.0
+            },
+            average_rating: reviews.get_f64("average_rating").unwrap_or_default(),
+            review_count: get_number(&reviews, "review_count"),
+        })
+    }
+
+    async fn owner_reputation_stats(
+        &self,
+        owner_id: &str,
+        since: DateTime<Utc>,
+    ) -> Result<OwnerReputationStats, Error> {
+        let accepted = doc! {"$ne": [{"$ifNull": ["$accepted_by", null]}, null]};
+        let canceled = doc! {"$ne": [{"$ifNull": ["$canceled_at", null]}, null]};
+        let not_started = doc! {"$eq": [{"$ifNull": ["$started_at", null]}, null]};
+        // 预定结束时间缺失时按预定开始时间计算
+        let overdue = doc! {"$lt": [{"$ifNull": ["$should_end_before", "$should_start_before"]}, Utc::now()]};
+        let requests = self
+            .aggregate_all(
+                "walk_requests",
+                vec![
+                    doc! { "$match": { "created_by": owner_id, "created_at": { "$gte": since }, "deleted_at": null } },
+                    doc! {
+                        "$group": {
+                            "_id": null,
+                            "requests": { "$sum": 1 },
+                            "accepted": { "$sum": { "$cond": [accepted.clone(), 1, 0] } },
+                            "canceled_after_acceptance": { "$sum": { "$cond": [{ "$and": [accepted.clone(), canceled.clone()] }, 1, 0] } },
+                            "no_shows": { "$sum": { "$cond": [{ "$and": [accepted, { "$not": [canceled] }, not_started, overdue] }, 1, 0] } },
+                        }
+                    },
+                ],
+            )
+            .await?
+            .pop()
+            .unwrap_or_default();
+        let reviews = self
+            .aggregate_all(
+                "reviews",
+                vec![
+                    doc! { "$match": { "owner_id": owner_id, "kind": ReviewKind::WalkerToOwner.to_string(), "state": revealed_filter() } },
+                    doc! {
+                        "$group": {
+                            "_id": null,
+                            "average_rating": { "$avg": "$rating" },
+                            "review_count": { "$sum": 1 },
+                        }
+                    },
+                ],
+            )
+            .await?
+            .pop()
+            .unwrap_or_default();
+        Ok(OwnerReputationStats {
+            requests: get_number(&requests, "requests"),
+            accepted: get_number(&requests, "accepted"),
+            canceled_after_acceptance: get_number(&requests, "canceled_after_acceptance"),
+            no_shows: get_number(&requests, "no_shows"),
+            average_rating: reviews.get_f64("average_rating").unwrap_or_default(),
+            review_count: get_number(&reviews, "review_count"),
+        })
+    }
+
+    async fn achievement_progress(&self) -> Result<Vec<AchievementProgress>, Error> {
+        let mut progress: HashMap<String, AchievementProgress> = HashMap::new();
+        // 遛狗人: 完成次数和累计距离
+        let mut walker_pipeline = vec![doc! {
+            "$match": { "accepted_by": { "$ne": null }, "finished_at": { "$ne": null }, "canceled_at": null }
+        }];
+        walker_pipeline.extend(walk_distance_stages());
+        walker_pipeline.push(doc! {
+            "$group": {
+                "_id": "$accepted_by",
+                "finished_walks": { "$sum": 1 },
+                "total_distance": { "$sum": "$distance" },
+            }
+        });
+        for d in self.aggregate_all("walk_requests", walker_pipeline).await? {
+            let entry = progress_entry(&mut progress, &d);
+            entry.finished_walks += get_number(&d, "finished_walks");
+            entry.total_distance += d.get_f64("total_distance").unwrap_or_default();
+        }
+        // 狗狗主人: 完成次数
+        let owner_pipeline = vec![
+            doc! { "$match": { "finished_at": { "$ne": null }, "canceled_at": null } },
+            doc! { "$group": { "_id": "$created_by", "finished_walks": { "$sum": 1 } } },
+        ];
+        for d in self.aggregate_all("walk_requests", owner_pipeline).await? {
+            progress_entry(&mut progress, &d).finished_walks += get_number(&d, "finished_walks");
+        }
+        // 遛狗人: 五星评价数
+        let mut review_filter = revealed_walker_reviews_filter();
+        review_filter.insert("rating", 5);
+        let review_pipeline = vec![
+            doc! { "$match": review_filter },
+            doc! { "$group": { "_id": "$walker_id", "five_star_reviews": { "$sum": 1 } } },
+        ];
+        for d in self.aggregate_all("reviews", review_pipeline).await? {
+            progress_entry(&mut progress, &d).five_star_reviews +=
+                get_number(&d, "five_star_reviews");
+        }
+        Ok(progress.into_values().collect())
+    }
+
+    async fn grant_achievement(&self, user_id: &str, kind: AchievementKind) -> Result<bool, Error> {
+        self.update_one(
+            "achievements",
+            doc! { "user_id": user_id, "kind": kind.to_string() },
+            doc! { "$setOnInsert": { "achieved_at": Utc::now() } },
+            Some(UpdateOptions::builder().upsert(true).build()),
+        )
+        .await
+        .map_err(|e| Error::new("failed to grant achievement").with_cause(e))
+        .map(|res| res.upserted_id.is_some())
+    }
+
+    async fn query_achievements(&self, user_id: &str) -> Result<Vec<Achievement>, Error> {
+        self.db
+            .collection::<Achievement>("achievements")
+            .find(
+                doc! { "user_id": user_id },
+                FindOptions::builder()
+                    .projection(Achievement::projection())
+                    .sort(doc! { "achieved_at": 1 })
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to query achievements").with_cause(e))?
+            .try_collect::<Vec<Achievement>>()
+            .await
+            .map_err(|e| Error::new("failed to query achievements").with_cause(e))
+    }
+
+    async fn create_review<'a>(&self, create: ReviewCreate<'a>) -> Result<String, Error> {
+        self.insert_one("reviews", Document::from_model(create))
+            .await
+            .map_err(|e| Error::new("failed to create review").with_cause(e))?
+            .inserted_id
+            .as_object_id()
+            .ok_or(Error::new("failed to create review").with_cause("invalid inserted id"))
+            .map(|id| id.to_string())
+    }
+
+    // 有用票数在查询时从review_votes汇总, 可按票数排序
+    async fn query_reviews(
+        &self,
+        query: ReviewQuery,
+        pagination: Option<Pagination>,
+    ) -> Result<(Vec<Review>, i64), Error> {
+        let mut sort = doc! {};
+        if let Some(sort_by) = &query.sort_by {
+            sort.insert(sort_by.field.as_str(), if sort_by.order == Order::Asc { 1 } else { -1 });
+        }
+        sort.insert("created_at", -1);
+        sort.insert("_id", -1);
+        let q = Document::try_from_model(query)?;
+        let total = self
+            .db
+            .collection::<Review>("reviews")
+            .count_documents(q.clone(), None)
+            .await
+            .map_err(|e| Error::new("failed to query reviews").with_cause(e))?;
+        let mut pipeline = vec![
+            doc! {"$match": q},
+            doc! {"$lookup": {
+                "from": "review_votes",
+                "localField": "_id",
+                "foreignField": "review_id",
+                "as": "votes",
+            }},
+            doc! {"$addFields": {"helpful_count": {"$size": "$votes"}}},
+            doc! {"$sort": sort},
+        ];
+        if let Some(pagination) = &pagination {
+            pipeline.push(doc! {"$skip": pagination.skip});
+            if pagination.limit > 0 {
+                pipeline.push(doc! {"$limit": pagination.limit});
+            }
+        }
+        pipeline.push(doc! {"$project": Review::projection()});
+        let reviews = self
+            .aggregate_all("reviews", pipeline)
+            .await?
+            .into_iter()
+            .map(|d| from_document::<Review>(d).map_err(|e| Error::new("failed to query reviews").with_cause(e)))
+            .collect::<Result<Vec<Review>, Error>>()?;
+        Ok((reviews, total as i64))
+    }
+
+    async fn create_review_vote(&self, review_id: &str, user_id: &str) -> Result<bool, Error> {
+        let res = self
+            .update_one(
+                "review_votes",
+                doc! { "review_id": parse_object_id(review_id)?, "user_id": user_id },
+                doc! {},
+                Some(UpdateOptions::builder().upsert(true).build()),
+            )
+            .await
+            .map_err(|e| write_error(e, "failed to create review vote"));
+        match res {
+            Ok(res) => Ok(res.upserted_id.is_some()),
+            // 同一用户并发投票时另一个请求已插入
+            Err(e) if e.is_duplicate("review_id") => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn reveal_reviews(&self, walk_request_id: &str) -> Result<u64, Error> {
+        self.update_many(
+            "reviews",
+            doc! {"walk_request_id": walk_request_id, "state": ReviewState::Hidden.to_string()},
+            doc! {"$set": {"state": ReviewState::Revealed.to_string(), "revealed_at": Utc::now()}},
+        )
+        .await
+        .map_err(|e| Error::new("failed to reveal reviews").with_cause(e))
+        .map(|res| res.modified_count)
+    }
+
+    async fn reveal_expired_reviews(&self, created_before: DateTime<Utc>) -> Result<u64, Error> {
+        self.update_many(
+            "reviews",
+            doc! {"state": ReviewState::Hidden.to_string(), "created_at": {"$lt": created_before}},
+            doc! {"$set": {"state": ReviewState::Revealed.to_string(), "revealed_at": Utc::now()}},
+        )
+        .await
+        .map_err(|e| Error::new("failed to reveal expired reviews").with_cause(e))
+        .map(|res| res.modified_count)
+    }
+
+    async fn count_dogs(&self, query: &DogQuery) -> Result<i64, Error> {
+        let mut q = doc! {};
+        if let Some(owner_id) = &query.owner_id {
+            q.insert("owner_id", owner_id);
+        }
+        if !query.include_deleted {
+            q.insert("deleted_at", Bson::Null);
+        }
+        self.db
+            .collection::<Dog>("dogs")
+            .count_documents(q, None)
+            .await
+            .map_err(|e| Error::new("failed to count dogs").with_cause(e))
+            .map(|n| n as i64)
+    }
+
+    async fn get_owner(&self, user_id: &str) -> Result<Option<Owner>, Error> {
+        let mut owner = self
+            .db
+            .collection::<Owner>("owners")
+            .find_one(
+                doc! {"user_id": user_id},
+                FindOneOptions::builder()
+                    .projection(Owner::projection())
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to get owner").with_cause(e))?;
+        if let Some(owner) = &mut owner {
+            self.decrypt_emergency_contacts(owner)?;
+        }
+        Ok(owner)
+    }
+
+    async fn upsert_owner(&self, user_id: &str, update: OwnerUpdate) -> Result<Owner, Error> {
+        let mut set = doc! {};
+        if let Some(nickname) = update.nickname {
+            set.insert("nickname", nickname);
+        }
+        if let Some(avatar_id) = update.avatar_id {
+            set.insert("avatar_id", avatar_id);
+        }
+        if let Some(emergency_contacts) = update.emergency_contacts {
+            let contacts = emergency_contacts
+                .into_iter()
+                .map(|c| {
+                    Ok(doc! {
+                        "name": c.name,
+                        "phone": self.encrypt_field(&c.phone)?,
+                        "relationship": c.relationship,
+                    })
+                })
+                .collect::<Result<Vec<Document>, Error>>()?;
+            set.insert("emergency_contacts", contacts);
+        }
+        let mut owner: Owner = self.find_one_and_update(
+            "owners",
+            doc! {"user_id": user_id},
+            doc! {"$set": set},
+            FindOneAndUpdateOptions::builder()
+                .upsert(true)
+                .return_document(Some(mongodb::options::ReturnDocument::After))
+                .projection(Owner::projection())
+                .build(),
+        )
+        .await
+        .map_err(|e| Error::new("failed to update owner").with_cause(e))?
+        .ok_or(Error::new("updated owner not exists"))?;
+        self.decrypt_emergency_contacts(&mut owner)?;
+        Ok(owner)
+    }
+
+    async fn create_block(&self, owner_id: &str, walker_id: &str) -> Result<Block, Error> {
+        self.find_one_and_update(
+            "blocks",
+            doc! {"owner_id": owner_id, "walker_id": walker_id},
+            doc! {},
+            FindOneAndUpdateOptions::builder()
+                .upsert(true)
+                .return_document(Some(mongodb::options::ReturnDocument::After))
+                .projection(Block::projection())
+                .build(),
+        )
+        .await
+        .map_err(|e| Error::new("failed to create block").with_cause(e))?
+        .ok_or(Error::new("created block not exists"))
+    }
+
+    async fn delete_block(&self, owner_id: &str, walker_id: &str) -> Result<bool, Error> {
+        self.db
+            .collection::<Block>("blocks")
+            .delete_one(doc! {"owner_id": owner_id, "walker_id": walker_id}, None)
+            .await
+            .map_err(|e| Error::new("failed to delete block").with_cause(e))
+            .map(|res| res.deleted_count > 0)
+    }
+
+    async fn query_blocks(&self, query: BlockQuery) -> Result<Vec<Block>, Error> {
+        let mut q = doc! {};
+        if let Some(owner_id) = query.owner_id {
+            q.insert("owner_id", owner_id);
+        }
+        if let Some(walker_id) = query.walker_id {
+            q.insert("walker_id", walker_id);
+        }
+        self.db
+            .collection::<Block>("blocks")
+            .find(
+                q,
+                FindOptions::builder()
+                    .projection(Block::projection())
+                    .sort(doc! {"created_at": -1})
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to query blocks").with_cause(e))?
+            .try_collect::<Vec<Block>>()
+            .await
+            .map_err(|e| Error::new("failed to query blocks").with_cause(e))
+    }
+
+    async fn create_favorite(&self, owner_id: &str, walker_id: &str) -> Result<Favorite, Error> {
+        self.find_one_and_update(
+            "favorites",
+            doc! {"owner_id": owner_id, "walker_id": walker_id},
+            doc! {},
+            FindOneAndUpdateOptions::builder()
+                .upsert(true)
+                .return_document(Some(mongodb::options::ReturnDocument::After))
+                .projection(Favorite::projection())
+                .build(),
+        )
+        .await
+        .map_err(|e| Error::new("failed to create favorite").with_cause(e))?
+        .ok_or(Error::new("created favorite not exists"))
+    }
+
+    async fn delete_favorite(&self, owner_id: &str, walker_id: &str) -> Result<bool, Error> {
+        self.db
+            .collection::<Favorite>("favorites")
+            .delete_one(doc! {"owner_id": owner_id, "walker_id": walker_id}, None)
+            .await
+            .map_err(|e| Error::new("failed to delete favorite").with_cause(e))
+            .map(|res| res.deleted_count > 0)
+    }
+
+    async fn query_favorites(&self, query: FavoriteQuery) -> Result<Vec<Favorite>, Error> {
+        let mut q = doc! {};
+        if let Some(owner_id) = query.owner_id {
+            q.insert("owner_id", owner_id);
+        }
+        if let Some(walker_id) = query.walker_id {
+            q.insert("walker_id", walker_id);
+        }
+        self.db
+            .collection::<Favorite>("favorites")
+            .find(
+                q,
+                FindOptions::builder()
+                    .projection(Favorite::projection())
+                    .sort(doc! {"created_at": -1})
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to query favorites").with_cause(e))?
+            .try_collect::<Vec<Favorite>>()
+            .await
+            .map_err(|e| Error::new("failed to query favorites").with_cause(e))
+    }
+
+    async fn create_report(&self, create: ReportCreate) -> Result<String, Error> {
+        self.insert_one("reports", Document::from_model(create))
+            .await
+            .map_err(|e| Error::new("failed to create report").with_cause(e))?
+            .inserted_id
+            .as_object_id()
+            .ok_or(Error::new("failed to create report").with_cause("invalid inserted id"))
+            .map(|id| id.to_string())
+    }
+
+    async fn query_reports(
+        &self,
+        query: ReportQuery,
+        pagination: Option<Pagination>,
+    ) -> Result<(Vec<Report>, i64), Error> {
+        let q = Document::try_from_model(query)?;
+        let total = self
+            .db
+            .collection::<Report>("reports")
+            .count_documents(q.clone(), None)
+            .await
+            .map_err(|e| Error::new("failed to query reports").with_cause(e))?;
+        let reports = self
+            .db
+            .collection::<Report>("reports")
+            .find(
+                q,
+                FindOptions::builder()
+                    .projection(Report::projection())
+                    .sort(doc! {"created_at": 1})
+                    .skip(pagination.as_ref().map(|p| p.skip as u64))
+                    .limit(pagination.as_ref().map(|p| p.limit))
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to query reports").with_cause(e))?
+            .try_collect::<Vec<Report>>()
+            .await
+            .map_err(|e| Error::new("failed to query reports").with_cause(e))?;
+        Ok((reports, total as i64))
+    }
+
+    async fn update_reports_by_query(
+        &self,
+        query: ReportQuery,
+        update: ReportUpdate,
+    ) -> Result<u64, Error> {
+        let mut set = doc! {};
+        if let Some(status) = update.status {
+            set.insert("status", status.to_string());
+        }
+        if let Some(resolution_note) = update.resolution_note {
+            set.insert("resolution_note", resolution_note);
+        }
+        if let Some(reviewed_at) = update.reviewed_at {
+            set.insert("reviewed_at", reviewed_at);
+        }
+        Ok(self
+            .update_many("reports", Document::try_from_model(query)?, doc! {"$set": set})
+            .await
+            .map_err(|e| Error::new("failed to update reports").with_cause(e))?
+            .modified_count)
+    }
+
+    async fn create_refresh_token(&self, mut create: RefreshTokenCreate) -> Result<String, Error> {
+        create.phone = self.encrypt_field(&create.phone)?;
+        self.insert_one("refresh_tokens", Document::from_model(create))
+            .await
+            .map_err(|e| Error::new("failed to create refresh token").with_cause(e))?
+            .inserted_id
+            .as_object_id()
+            .ok_or(Error::new("failed to create refresh token").with_cause("invalid inserted id"))
+            .map(|id| id.to_string())
+    }
+
+    async fn consume_refresh_token(&self, token_hash: &str) -> Result<Option<RefreshToken>, Error> {
+        // 以used为条件原子地标记, 并发刷新时只有一个请求能拿到令牌
+        let mut token = self
+            .find_one_and_update::<RefreshToken>(
+                "refresh_tokens",
+                doc! {"token_hash": token_hash, "used": false},
+                doc! {"$set": {"used": true}},
+                FindOneAndUpdateOptions::builder()
+                    .projection(RefreshToken::projection())
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to consume refresh token").with_cause(e))?;
+        if let Some(token) = &mut token {
+            self.decrypt_field(&mut token.phone)?;
+        }
+        Ok(token)
+    }
+
+    async fn get_refresh_token(&self, token_hash: &str) -> Result<Option<RefreshToken>, Error> {
+        let mut token = self
+            .db
+            .collection::<RefreshToken>("refresh_tokens")
+            .find_one(
+                doc! {"token_hash": token_hash},
+                FindOneOptions::builder()
+                    .projection(RefreshToken::projection())
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to get refresh token").with_cause(e))?;
+        if let Some(token) = &mut token {
+            self.decrypt_field(&mut token.phone)?;
+        }
+        Ok(token)
+    }
+
+    // 按token_id upsert, 重复吊销同一令牌不会产生多条记录
+    async fn revoke_access_token(&self, create: RevokedAccessTokenCreate) -> Result<(), Error> {
+        self.update_one(
+            "revoked_access_tokens",
+            doc! {"token_id": create.token_id},
+            doc! {"$set": {"user_id": create.user_id, "expires_at": create.expires_at}},
+            Some(UpdateOptions::builder().upsert(true).build()),
+        )
+        .await
+        .map_err(|e| Error::new("failed to revoke access token").with_cause(e))
+        .map(|_| ())
+    }
+
+    // TTL索引每分钟清理一次, 以过期时间判断而不依赖清理是否及时
+    async fn is_access_token_revoked(&self, token_id: &str) -> Result<bool, Error> {
+        self.db
+            .collection::<Document>("revoked_access_tokens")
+            .count_documents(
+                doc! {"token_id": token_id, "expires_at": {"$gt": Utc::now()}},
+                None,
+            )
+            .await
+            .map_err(|e| Error::new("failed to check revoked access token").with_cause(e))
+            .map(|count| count > 0)
+    }
+
+    async fn count_user_references(&self, user_id: &str) -> Result<Vec<MergedReference>, Error> {
+        let mut references = Vec::new();
+        for (collection, field, _) in USER_REFERENCES {
+            let count = self
+                .db
+                .collection::<Document>(collection)
+                .count_documents(doc! {*field: user_id}, None)
+                .await
+                .map_err(|e| Error::new("failed to count user references").with_cause(e))?;
+            references.push(MergedReference {
+                collection: collection.to_string(),
+                field: field.to_string(),
+                count,
+            });
+        }
+        Ok(references)
+    }
+
+    async fn merge_accounts(&self, merge: AccountMergeCreate) -> Result<AccountMerge, Error> {
+        let mut session = self
+            .client
+            .start_session(None)
+            .await
+            .map_err(|e| Error::new("failed to start session").with_cause(e))?;
+        session
+            .start_transaction(None)
+            .await
+            .map_err(|e| Error::new("failed to start transaction").with_cause(e))?;
+        match self.reassign_user_references(&mut session, merge).await {
+            Ok(merged) => {
+                session
+                    .commit_transaction()
+                    .await
+                    .map_err(|e| Error::new("failed to commit account merge").with_cause(e))?;
+                Ok(merged)
+            }
+            Err(e) => {
+                session.abort_transaction().await.ok();
+                Err(e)
+            }
+        }
+    }
+
+    async fn delete_account(
+        &self,
+        deletion: AccountDeletionCreate,
+    ) -> Result<AccountDeletion, Error> {
+        let mut session = self
+            .client
+            .start_session(None)
+            .await
+            .map_err(|e| Error::new("failed to start session").with_cause(e))?;
+        session
+            .start_transaction(None)
+            .await
+            .map_err(|e| Error::new("failed to start transaction").with_cause(e))?;
+        match self.remove_user_data(&mut session, deletion).await {
+            Ok(deleted) => {
+                session
+                    .commit_transaction()
+                    .await
+                    .map_err(|e| Error::new("failed to commit account deletion").with_cause(e))?;
+                Ok(deleted)
+            }
+            Err(e) => {
+                session.abort_transaction().await.ok();
+                Err(e)
+            }
+        }
+    }
+
+    async fn create_otp(&self, mut create: OtpCreate) -> Result<String, Error> {
+        create.phone = self.encrypt_field(&create.phone)?;
+        self.insert_one("otps", Document::from_model(create))
+            .await
+            .map_err(|e| Error::new("failed to create otp").with_cause(e))?
+            .inserted_id
+            .as_object_id()
+            .ok_or(Error::new("failed to create otp").with_cause("invalid inserted id"))
+            .map(|id| id.to_string())
+    }
+
+    async fn count_otps(&self, phone: &str, since: DateTime<Utc>) -> Result<i64, Error> {
+        self.db
+            .collection::<Document>("otps")
+            .count_documents(
+                doc! {"phone": self.encrypted_field_filter(phone)?, "created_at": {"$gte": since}},
+                None,
+            )
+            .await
+            .map_err(|e| Error::new("failed to count otps").with_cause(e))
+            .map(|n| n as i64)
+    }
+
+    async fn consume_otp(
+        &self,
+        phone: &str,
+        purpose: OtpPurpose,
+        code_hash: &str,
+        max_attempts: i64,
+    ) -> Result<Option<Otp>, Error> {
+        let active = doc! {
+            "phone": self.encrypted_field_filter(phone)?,
+            "purpose": purpose.to_string(),
+            "used": false,
+            "expires_at": {"$gt": Utc::now()},
+        };
+        let mut filter = active.clone();
+        filter.insert("code_hash", code_hash);
+        // 旧的验证码没有failed_attempts字段, 按0次处理
+        filter.insert("failed_attempts", doc! {"$not": {"$gte": max_attempts}});
+        let mut otp = self
+            .find_one_and_update::<Otp>(
+                "otps",
+                filter,
+                doc! {"$set": {"used": true}},
+                FindOneAndUpdateOptions::builder()
+                    .projection(Otp::projection())
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to consume otp").with_cause(e))?;
+        match &mut otp {
+            Some(otp) => self.decrypt_field(&mut otp.phone)?,
+            None => {
+                self.update_many("otps", active.clone(), doc! {"$inc": {"failed_attempts": 1}})
+                    .await
+                    .map_err(|e| Error::new("failed to record otp attempt").with_cause(e))?;
+                let mut exhausted = active;
+                exhausted.insert("failed_attempts", doc! {"$gte": max_attempts});
+                self.update_many("otps", exhausted, doc! {"$set": {"used": true}})
+                    .await
+                    .map_err(|e| Error::new("failed to invalidate otp").with_cause(e))?;
+            }
+        }
+        Ok(otp)
+    }
+
+    async fn create_password_reset_token(
+        &self,
+        mut create: PasswordResetTokenCreate,
+    ) -> Result<String, Error> {
+        create.phone = self.encrypt_field(&create.phone)?;
+        self.insert_one("password_reset_tokens", Document::from_model(create))
+            .await
+            .map_err(|e| Error::new("failed to create password reset token").with_cause(e))?
+            .inserted_id
+            .as_object_id()
+            .ok_or(
+                Error::new("failed to create password reset token")
+                    .with_cause("invalid inserted id"),
+            )
+            .map(|id| id.to_string())
+    }
+
+    async fn consume_password_reset_token(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<PasswordResetToken>, Error> {
+        let mut token = self
+            .find_one_and_update::<PasswordResetToken>(
+                "password_reset_tokens",
+                doc! {
+                    "token_hash": token_hash,
+                    "used": false,
+                    "expires_at": {"$gt": Utc::now()},
+                },
+                doc! {"$set": {"used": true}},
+                FindOneAndUpdateOptions::builder()
+                    .projection(PasswordResetToken::projection())
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to consume password reset token").with_cause(e))?;
+        if let Some(token) = &mut token {
+            self.decrypt_field(&mut token.phone)?;
+        }
+        Ok(token)
+    }
+
+    async fn revoke_refresh_token_family(&self, family_id: &str) -> Result<u64, Error> {
+        Ok(self
+            .update_many(
+                "refresh_tokens",
+                doc! {"family_id": family_id, "revoked": false},
+                doc! {"$set": {"revoked": true}},
+            )
+            .await
+            .map_err(|e| Error::new("failed to revoke refresh tokens").with_cause(e))?
+            .modified_count)
+    }
+    async fn create_ticket(&self, create: TicketCreate) -> Result<String, Error> {
+        self.insert_one("tickets", Document::from_model(create))
+            .await
+            .map_err(|e| Error::new("failed to create ticket").with_cause(e))?
+            .inserted_id
+            .as_object_id()
+            .ok_or(Error::new("failed to create ticket").with_cause("invalid inserted id"))
+            .map(|id| id.to_string())
+    }
+
+    async fn query_tickets(
+        &self,
+        query: TicketQuery,
+        pagination: Option<Pagination>,
+    ) -> Result<(Vec<Ticket>, i64), Error> {
+        let q = Document::try_from_model(query)?;
+        let total = self
+            .db
+            .collection::<Ticket>("tickets")
+            .count_documents(q.clone(), None)
+            .await
+            .map_err(|e| Error::new("failed to query tickets").with_cause(e))?;
+        let tickets = self
+            .db
+            .collection::<Ticket>("tickets")
+            .find(
+                q,
+                FindOptions::builder()
+                    .projection(Ticket::projection())
+                    .sort(doc! {"updated_at": -1})
+                    .skip(pagination.as_ref().map(|p| p.skip as u64))
+                    .limit(pagination.as_ref().map(|p| p.limit))
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to query tickets").with_cause(e))?
+            .try_collect::<Vec<Ticket>>()
+            .await
+            .map_err(|e| Error::new("failed to query tickets").with_cause(e))?;
+        Ok((tickets, total as i64))
+    }
+
+    async fn update_tickets_by_query(
+        &self,
+        query: TicketQuery,
+        update: TicketUpdate,
+    ) -> Result<u64, Error> {
+        let mut set = doc! {};
+        if let Some(status) = update.status {
+            set.insert("status", status.to_string());
+        }
+        if let Some(assignee_id) = update.assignee_id {
+            set.insert("assignee_id", assignee_id);
+        }
+        Ok(self
+            .update_many("tickets", Document::try_from_model(query)?, doc! {"$set": set})
+            .await
+            .map_err(|e| Error::new("failed to update tickets").with_cause(e))?
+            .modified_count)
+    }
+
+    async fn add_ticket_message(
+        &self,
+        query: TicketQuery,
+        message: TicketMessageCreate,
+    ) -> Result<u64, Error> {
+        let mut q = Document::try_from_model(query)?;
+        if !q.contains_key("status") {
+            q.insert("status", doc! {"$ne": TicketStatus::Closed.to_string()});
+        }
+        Ok(self
+            .update_many(
+                "tickets",
+                q,
+                doc! {"$push": {"messages": Document::from_model(message)}},
+            )
+            .await
+            .map_err(|e| Error::new("failed to add ticket message").with_cause(e))?
+            .modified_count)
+    }
+    // 版本号取当前最新版本加一
+    async fn create_help_article(&self, create: HelpArticleCreate) -> Result<HelpArticle, Error> {
+        let version = self
+            .query_help_articles(
+                HelpArticleQuery {
+                    slug: Some(create.slug.clone()),
+                    locale: Some(create.locale.clone()),
+                    ..Default::default()
+                },
+                Some(1),
+            )
+            .await?
+            .first()
+            .map(|a| a.version + 1)
+            .unwrap_or(1);
+        let id = self
+            .insert_one(
+                "help_articles",
+                doc! {
+                    "slug": &create.slug,
+                    "locale": &create.locale,
+                    "version": version,
+                    "title": &create.title,
+                    "markdown": &create.markdown,
+                    "author_id": &create.author_id,
+                },
+            )
+            .await
+            .map_err(|e| Error::new("failed to create help article").with_cause(e))?
+            .inserted_id
+            .as_object_id()
+            .ok_or(Error::new("failed to create help article").with_cause("invalid inserted id"))?
+            .to_string();
+        Ok(HelpArticle {
+            id,
+            slug: create.slug,
+            locale: create.locale,
+            version,
+            title: create.title,
+            markdown: create.markdown,
+            author_id: create.author_id,
+            created_at: Some(Utc::now()),
+        })
+    }
+
+    async fn query_help_articles(
+        &self,
+        query: HelpArticleQuery,
+        limit: Option<i64>,
+    ) -> Result<Vec<HelpArticle>, Error> {
+        self.db
+            .collection::<HelpArticle>("help_articles")
+            .find(
+                Document::from_model(query),
+                FindOptions::builder()
+                    .projection(HelpArticle::projection())
+                    .sort(doc! {"version": -1})
+                    .limit(limit)
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to query help articles").with_cause(e))?
+            .try_collect()
+            .await
+            .map_err(|e| Error::new("failed to query help articles").with_cause(e))
+    }
+
+    async fn latest_help_articles(&self, locale: &str) -> Result<Vec<HelpArticle>, Error> {
+        self.db
+            .collection::<Document>("help_articles")
+            .aggregate(
+                vec![
+                    doc! {"$match": {"locale": locale}},
+                    doc! {"$sort": {"version": -1}},
+                    doc! {"$group": {"_id": "$slug", "article": {"$first": "$$ROOT"}}},
+                    doc! {"$replaceRoot": {"newRoot": "$article"}},
+                    doc! {"$sort": {"slug": 1}},
+                    doc! {"$project": HelpArticle::projection()},
+                ],
+                None,
+            )
+            .await
+            .map_err(|e| Error::new("failed to query latest help articles").with_cause(e))?
+            .map(|res| match res {
+                Err(e) => Err(Error::new("failed to query latest help articles").with_cause(e)),
+                Ok(doc) => from_document::<HelpArticle>(doc)
+                    .map_err(|e| Error::new("failed to convert document").with_cause(e)),
+            })
+            .try_collect::<Vec<HelpArticle>>()
+            .await
+    }
+    async fn get_oauth_account(
+        &self,
+        provider: OAuthProviderKind,
+        subject: &str,
+    ) -> Result<Option<OAuthAccount>, Error> {
+        let mut account = self
+            .db
+            .collection::<OAuthAccount>("oauth_accounts")
+            .find_one(
+                doc! {"provider": provider.to_string(), "subject": subject},
+                FindOneOptions::builder()
+                    .projection(OAuthAccount::projection())
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to get oauth account").with_cause(e))?;
+        if let Some(account) = &mut account {
+            self.decrypt_field(&mut account.phone)?;
+        }
+        Ok(account)
+    }
+
+    // 同一第三方账号重复绑定时保留最早的绑定关系
+    // 并发绑定时两个upsert可能都尝试插入, 落败的一方违反唯一索引, 重试一次即可读到已插入的绑定
+    async fn create_oauth_account(&self, create: OAuthAccountCreate) -> Result<String, Error> {
+        let upsert = || async {
+            self.find_one_and_update::<OAuthAccount>(
+                "oauth_accounts",
+                doc! {"provider": create.provider.to_string(), "subject": &create.subject},
+                doc! {"$setOnInsert": {"user_id": &create.user_id, "phone": self.encrypt_field(&create.phone)?}},
+                FindOneAndUpdateOptions::builder()
+                    .upsert(true)
+                    .return_document(Some(mongodb::options::ReturnDocument::After))
+                    .projection(OAuthAccount::projection())
+                    .build(),
+            )
+            .await
+            .map_err(|e| write_error(e, "failed to create oauth account"))
+        };
+        let account = match upsert().await {
+            Err(e) if e.is_duplicate("subject") => upsert().await,
+            result => result,
+        }?
+        .ok_or(Error::new("failed to create oauth account"))?;
+        if account.user_id != create.user_id {
+            return Err(Error::msg("该第三方账号已绑定其他手机号"));
+        }
+        Ok(account.id)
+    }
+
+    async fn create_oauth_link_token(&self, create: OAuthLinkTokenCreate) -> Result<String, Error> {
+        self.insert_one("oauth_link_tokens", Document::from_model(create))
+            .await
+            .map_err(|e| Error::new("failed to create oauth link token").with_cause(e))?
+            .inserted_id
+            .as_object_id()
+            .ok_or(
+                Error::new("failed to create oauth link token").with_cause("invalid inserted id"),
+            )
+            .map(|id| id.to_string())
+    }
+
+    async fn consume_oauth_link_token(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<OAuthLinkToken>, Error> {
+        self.find_one_and_update(
+            "oauth_link_tokens",
+            doc! {
+                "token_hash": token_hash,
+                "used": false,
+                "expires_at": {"$gt": Utc::now()},
+            },
+            doc! {"$set": {"used": true}},
+            FindOneAndUpdateOptions::builder()
+                .projection(OAuthLinkToken::projection())
+                .build(),
+        )
+        .await
+        .map_err(|e| Error::new("failed to consume oauth link token").with_cause(e))
+    }
+
+    async fn get_phone_binding(&self, phone: &str) -> Result<Option<PhoneBinding>, Error> {
+        let phone = self.encrypted_field_filter(phone)?;
+        let mut binding = self
+            .db
+            .collection::<PhoneBinding>("phone_bindings")
+            .find_one(
+                doc! {"$or": [{"phone": phone.clone()}, {"auth_phone": phone}]},
+                FindOneOptions::builder()
+                    .projection(PhoneBinding::projection())
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to get phone binding").with_cause(e))?;
+        if let Some(binding) = &mut binding {
+            self.decrypt_field(&mut binding.phone)?;
+            self.decrypt_field(&mut binding.auth_phone)?;
+        }
+        Ok(binding)
+    }
+
+    async fn bind_phone(&self, upsert: PhoneBindingUpsert) -> Result<(), Error> {
+        let phone = self.encrypt_field(&upsert.phone)?;
+        self.update_one(
+            "phone_bindings",
+            doc! {"user_id": &upsert.user_id},
+            doc! {
+                "$set": {"phone": &phone},
+                "$setOnInsert": {"auth_phone": self.encrypt_field(&upsert.auth_phone)?},
+            },
+            Some(UpdateOptions::builder().upsert(true).build()),
+        )
+        .await
+        .map_err(|e| write_error(e, "failed to bind phone"))?;
+        self.update_many(
+            "oauth_accounts",
+            doc! {"user_id": &upsert.user_id},
+            doc! {"$set": {"phone": phone}},
+        )
+        .await
+        .map_err(|e| Error::new("failed to update oauth account phone").with_cause(e))
+        .map(|_| ())
+    }
+
+    // 每个遛狗人只保留一个收款账户, 重新绑定时覆盖
+    async fn upsert_payout_account(
+        &self,
+        upsert: PayoutAccountUpsert,
+    ) -> Result<PayoutAccount, Error> {
+        let user_id = upsert.user_id.clone();
+        self.find_one_and_update(
+            "payout_accounts",
+            doc! {"user_id": user_id},
+            Document::from_model(upsert),
+            FindOneAndUpdateOptions::builder()
+                .upsert(true)
+                .return_document(Some(mongodb::options::ReturnDocument::After))
+                .projection(PayoutAccount::projection())
+                .build(),
+        )
+        .await
+        .map_err(|e| Error::new("failed to upsert payout account").with_cause(e))?
+        .ok_or(Error::new("upserted payout account not exists"))
+    }
+
+    async fn get_payout_account(&self, user_id: &str) -> Result<Option<PayoutAccount>, Error> {
+        self.db
+            .collection::<PayoutAccount>("payout_accounts")
+            .find_one(
+                doc! {"user_id": user_id},
+                FindOneOptions::builder()
+                    .projection(PayoutAccount::projection())
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to get payout account").with_cause(e))
+    }
+
+    async fn update_payout_account_status(
+        &self,
+        user_id: &str,
+        provider_reference: &str,
+        status: PayoutAccountStatus,
+        failure_reason: Option<String>,
+    ) -> Result<Option<PayoutAccount>, Error> {
+        self.find_one_and_update(
+            "payout_accounts",
+            doc! {"user_id": user_id, "provider_reference": provider_reference},
+            payout_status_update(status, failure_reason),
+            FindOneAndUpdateOptions::builder()
+                .return_document(Some(mongodb::options::ReturnDocument::After))
+                .projection(PayoutAccount::projection())
+                .build(),
+        )
+        .await
+        .map_err(|e| Error::new("failed to update payout account").with_cause(e))
+    }
+
+    async fn delete_payout_account(&self, user_id: &str) -> Result<bool, Error> {
+        Ok(self
+            .db
+            .collection::<Document>("payout_accounts")
+            .delete_one(doc! {"user_id": user_id}, None)
+            .await
+            .map_err(|e| Error::new("failed to delete payout account").with_cause(e))?
+            .deleted_count
+            > 0)
+    }
+    // 每个family中未使用且未过期的令牌即当前有效的会话
+    async fn query_sessions(&self, user_id: &str) -> Result<Vec<Session>, Error> {
+        self.db
+            .collection::<Document>("refresh_tokens")
+            .aggregate(
+                vec![
+                    doc! {"$match": {
+                        "user_id": user_id,
+                        "used": false,
+                        "revoked": false,
+                        "expires_at": {"$gt": Utc::now()},
+                    }},
+                    doc! {"$sort": {"created_at": -1}},
+                    doc! {"$group": {"_id": "$family_id", "token": {"$first": "$$ROOT"}}},
+                    doc! {"$replaceRoot": {"newRoot": "$token"}},
+                    doc! {"$sort": {"created_at": -1}},
+                    doc! {"$project": {
+                        "id": "$family_id",
+                        "device_id": "$device_id",
+                        "platform": "$platform",
+                        "signed_in_at": {"$dateToString": {"date":{"$ifNull": ["$signed_in_at", "$created_at"]}, "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+                        "last_seen_at": {"$dateToString": {"date":"$created_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+                    }},
+                ],
+                None,
+            )
+            .await
+            .map_err(|e| Error::new("failed to query sessions").with_cause(e))?
+            .map(|res| match res {
+                Err(e) => Err(Error::new("failed to query sessions").with_cause(e)),
+                Ok(doc) => from_document::<Session>(doc)
+                    .map_err(|e| Error::new("failed to convert document").with_cause(e)),
+            })
+            .try_collect::<Vec<Session>>()
+            .await
+    }
+
+    async fn revoke_sessions(&self, query: SessionQuery) -> Result<u64, Error> {
+        let mut filter = doc! {"user_id": query.user_id, "revoked": false};
+        if let Some(id) = query.id {
+            filter.insert("family_id", id);
+        }
+        if let Some(device_id) = query.device_id {
+            filter.insert("device_id", device_id);
+        }
+        Ok(self
+            .update_many("refresh_tokens", filter, doc! {"$set": {"revoked": true}})
+            .await
+            .map_err(|e| Error::new("failed to revoke sessions").with_cause(e))?
+            .modified_count)
+    }
+
+    async fn ledger_balance(&self, user_id: &str) -> Result<i64, Error> {
+        Ok(self
+            .aggregate_all("ledger_entries", balance_pipeline(user_id))
+            .await?
+            .first()
+            .map(balance_of)
+            .unwrap_or(0))
+    }
+
+    async fn query_ledger_entries(
+        &self,
+        user_id: &str,
+        pagination: Option<Pagination>,
+    ) -> Result<(Vec<LedgerEntry>, i64), Error> {
+        let total = self
+            .db
+            .collection::<LedgerEntry>("ledger_entries")
+            .count_documents(doc! {"user_id": user_id}, None)
+            .await
+            .map_err(|e| Error::new("failed to query ledger entries").with_cause(e))?;
+        let entries = self
+            .db
+            .collection::<LedgerEntry>("ledger_entries")
+            .find(
+                doc! {"user_id": user_id},
+                FindOptions::builder()
+                    .projection(LedgerEntry::projection())
+                    .sort(doc! {"created_at": -1})
+                    .skip(pagination.as_ref().map(|p| p.skip as u64))
+                    .limit(pagination.as_ref().map(|p| p.limit))
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to query ledger entries").with_cause(e))?
+            .try_collect::<Vec<LedgerEntry>>()
+            .await
+            .map_err(|e| Error::new("failed to query ledger entries").with_cause(e))?;
+        Ok((entries, total as i64))
+    }
+
+    async fn credit_walk_earning(
+        &self,
+        user_id: &str,
+        walk_request_id: &str,
+        amount: i64,
+    ) -> Result<(), Error> {
+        self.update_one(
+            "ledger_entries",
+            doc! {"kind": LedgerEntryKind::Earning.to_string(), "walk_request_id": walk_request_id},
+            doc! {"$setOnInsert": {"user_id": user_id, "amount": amount}},
+            Some(UpdateOptions::builder().upsert(true).build()),
+        )
+        .await
+        .map_err(|e| write_error(e, "failed to credit walk earning"))
+        .map(|_| ())
+        .or_else(|e| {
+            if e.is_duplicate("walk_request_id") {
+                Ok(())
+            } else {
+                Err(e)
+            }
+        })
+    }
+
+    async fn create_withdrawal(&self, create: WithdrawalCreate) -> Result<Option<String>, Error> {
+        let mut session = self
+            .client
+            .start_session(None)
+            .await
+            .map_err(|e| Error::new("failed to start session").with_cause(e))?;
+        session
+            .start_transaction(None)
+            .await
+            .map_err(|e| Error::new("failed to start transaction").with_cause(e))?;
+        match self.debit_withdrawal(&mut session, create).await {
+            Ok(Some(id)) => {
+                session
+                    .commit_transaction()
+                    .await
+                    .map_err(|e| Error::new("failed to commit withdrawal").with_cause(e))?;
+                Ok(Some(id))
+            }
+            Ok(None) => {
+                session.abort_transaction().await.ok();
+                Ok(None)
+            }
+            Err(e) => {
+                session.abort_transaction().await.ok();
+                Err(e)
+            }
+        }
+    }
+
+    async fn query_withdrawals(
+        &self,
+        query: WithdrawalQuery,
+        pagination: Option<Pagination>,
+    ) -> Result<(Vec<Withdrawal>, i64), Error> {
+        let q = Document::try_from_model(query)?;
+        let total = self
+            .db
+            .collection::<Withdrawal>("withdrawals")
+            .count_documents(q.clone(), None)
+            .await
+            .map_err(|e| Error::new("failed to query withdrawals").with_cause(e))?;
+        let withdrawals = self
+            .db
+            .collection::<Withdrawal>("withdrawals")
+            .find(
+                q,
+                FindOptions::builder()
+                    .projection(Withdrawal::projection())
+                    .sort(doc! {"created_at": -1})
+                    .skip(pagination.as_ref().map(|p| p.skip as u64))
+                    .limit(pagination.as_ref().map(|p| p.limit))
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to query withdrawals").with_cause(e))?
+            .try_collect::<Vec<Withdrawal>>()
+            .await
+            .map_err(|e| Error::new("failed to query withdrawals").with_cause(e))?;
+        Ok((withdrawals, total as i64))
+    }
+
+    async fn update_withdrawals_by_query(
+        &self,
+        query: WithdrawalQuery,
+        update: WithdrawalUpdate,
+    ) -> Result<u64, Error> {
+        let mut session = self
+            .client
+            .start_session(None)
+            .await
+            .map_err(|e| Error::new("failed to start session").with_cause(e))?;
+        session
+            .start_transaction(None)
+            .await
+            .map_err(|e| Error::new("failed to start transaction").with_cause(e))?;
+        match self
+            .apply_withdrawal_update(&mut session, query, update)
+            .await
+        {
+            Ok(updated) => {
+                session
+                    .commit_transaction()
+                    .await
+                    .map_err(|e| Error::new("failed to commit withdrawal update").with_cause(e))?;
+                Ok(updated)
+            }
+            Err(e) => {
+                session.abort_transaction().await.ok();
+                Err(e)
+            }
+        }
+    }
+
+    async fn claim_approved_withdrawal(&self) -> Result<Option<Withdrawal>, Error> {
+        self.find_one_and_update(
+            "withdrawals",
+            doc! {"status": WithdrawalStatus::Approved.to_string()},
+            doc! {"$set": {"status": WithdrawalStatus::Processing.to_string()}},
+            FindOneAndUpdateOptions::builder()
+                .sort(doc! {"reviewed_at": 1})
+                .return_document(Some(mongodb::options::ReturnDocument::After))
+                .projection(Withdrawal::projection())
+                .build(),
+        )
+        .await
+        .map_err(|e| Error::new("failed to claim withdrawal").with_cause(e))
+    }
+
+    async fn create_payment_attempt(&self, create: PaymentAttemptCreate) -> Result<String, Error> {
+        self.insert_one(
+            "payment_attempts",
+            doc! {
+                "user_id": create.user_id,
+                "amount": create.amount,
+                "succeeded": create.succeeded,
+            },
+        )
+        .await
+        .map_err(|e| Error::new("failed to create payment attempt").with_cause(e))?
+        .inserted_id
+        .as_object_id()
+        .ok_or(Error::new("failed to create payment attempt").with_cause("invalid inserted id"))
+        .map(|id| id.to_string())
+    }
+
+    async fn payment_attempt_stats(
+        &self,
+        user_id: &str,
+        since: DateTime<Utc>,
+    ) -> Result<PaymentAttemptStats, Error> {
+        let stats = self
+            .aggregate_all(
+                "payment_attempts",
+                vec![
+                    doc! {"$match": {"user_id": user_id, "created_at": {"$gte": since}}},
+                    doc! {"$group": {
+                        "_id": null,
+                        "failed": {"$sum": {"$cond": ["$succeeded", 0_i64, 1_i64]}},
+                        "succeeded_amount": {"$sum": {"$cond": ["$succeeded", "$amount", 0_i64]}},
+                    }},
+                    doc! {"$project": {"_id": 0, "failed": 1, "succeeded_amount": 1}},
+                ],
+            )
+            .await?;
+        match stats.into_iter().next() {
+            Some(doc) => from_document::<PaymentAttemptStats>(doc)
+                .map_err(|e| Error::new("failed to convert document").with_cause(e)),
+            None => Ok(PaymentAttemptStats::default()),
+        }
+    }
+
+    // 同一图片同一规格只保留一个缩略图
+    async fn create_upload_variant(&self, create: UploadVariantCreate) -> Result<(), Error> {
+        self.update_one(
+            "upload_variants",
+            doc! {"upload_id": &create.upload_id, "size": create.size.to_string()},
+            doc! {"$set": {"variant_id": create.variant_id}},
+            Some(UpdateOptions::builder().upsert(true).build()),
+        )
+        .await
+        .map_err(|e| Error::new("failed to create upload variant").with_cause(e))?;
+        Ok(())
+    }
+
+    async fn get_upload_variant(
+        &self,
+        upload_id: &str,
+        size: ImageSize,
+    ) -> Result<Option<UploadVariant>, Error> {
+        self.db
+            .collection::<UploadVariant>("upload_variants")
+            .find_one(
+                doc! {"upload_id": upload_id, "size": size.to_string()},
+                FindOneOptions::builder()
+                    .projection(doc! {"_id": 0, "upload_id": 1, "size": 1, "variant_id": 1})
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to get upload variant").with_cause(e))
+    }
+
+    // 缩略图按其原图判断
+    async fn is_verification_document(&self, upload_id: &str) -> Result<bool, Error> {
+        let original = self
+            .db
+            .collection::<Document>("upload_variants")
+            .find_one(doc! {"variant_id": upload_id}, None)
+            .await
+            .map_err(|e| Error::new("failed to get upload variant").with_cause(e))?
+            .and_then(|variant| variant.get_str("upload_id").ok().map(str::to_owned))
+            .unwrap_or_else(|| upload_id.to_owned());
+        self.db
+            .collection::<Document>("walkers")
+            .count_documents(doc! {"id_document_ids": original}, None)
+            .await
+            .map_err(|e| Error::new("failed to check verification document").with_cause(e))
+            .map(|count| count > 0)
+    }
+
+    async fn create_cancellation_penalty(
+        &self,
+        create: CancellationPenaltyCreate,
+    ) -> Result<CancellationPenalty, Error> {
+        let mut session = self
+            .client
+            .start_session(None)
+            .await
+            .map_err(|e| Error::new("failed to start session").with_cause(e))?;
+        session
+            .start_transaction(None)
+            .await
+            .map_err(|e| Error::new("failed to start transaction").with_cause(e))?;
+        match self.charge_cancellation_penalty(&mut session, create).await {
+            Ok(penalty) => {
+                session.commit_transaction().await.map_err(|e| {
+                    Error::new("failed to commit cancellation penalty").with_cause(e)
+                })?;
+                Ok(penalty)
+            }
+            Err(e) => {
+                session.abort_transaction().await.ok();
+                Err(e)
+            }
+        }
+    }
+
+    async fn create_refund_request(&self, create: RefundRequestCreate) -> Result<String, Error> {
+        self.insert_one("refund_requests", Document::from_model(create))
+            .await
+            .map_err(|e| write_error(e, "failed to create refund request"))?
+            .inserted_id
+            .as_object_id()
+            .ok_or(Error::new("failed to create refund request").with_cause("invalid inserted id"))
+            .map(|id| id.to_string())
+    }
+
+    async fn query_refund_requests(
+        &self,
+        query: RefundRequestQuery,
+        pagination: Option<Pagination>,
+    ) -> Result<(Vec<RefundRequest>, i64), Error> {
+        let filter = Document::try_from_model(query)?;
+        let total = self
+            .db
+            .collection::<RefundRequest>("refund_requests")
+            .count_documents(filter.clone(), None)
+            .await
+            .map_err(|e| Error::new("failed to query refund requests").with_cause(e))?;
+        let requests = self
+            .db
+            .collection::<RefundRequest>("refund_requests")
+            .find(
+                filter,
+                FindOptions::builder()
+                    .projection(RefundRequest::projection())
+                    .sort(doc! {"created_at": -1})
+                    .skip(pagination.as_ref().map(|p| p.skip as u64))
+                    .limit(pagination.as_ref().map(|p| p.limit))
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to query refund requests").with_cause(e))?
+            .try_collect::<Vec<RefundRequest>>()
+            .await
+            .map_err(|e| Error::new("failed to query refund requests").with_cause(e))?;
+        Ok((requests, total as i64))
+    }
+
+    async fn review_refund_request(
+        &self,
+        review: RefundReview,
+    ) -> Result<Option<RefundRequest>, Error> {
+        let mut session = self
+            .client
+            .start_session(None)
+            .await
+            .map_err(|e| Error::new("failed to start session").with_cause(e))?;
+        session
+            .start_transaction(None)
+            .await
+            .map_err(|e| Error::new("failed to start transaction").with_cause(e))?;
+        match self.apply_refund_review(&mut session, review).await {
+            Ok(request) => {
+                session.commit_transaction().await.map_err(|e| {
+                    Error::new("failed to commit refund review").with_cause(e)
+                })?;
+                Ok(request)
+            }
+            Err(e) => {
+                session.abort_transaction().await.ok();
+                Err(e)
+            }
+        }
+    }
+
+    async fn query_cancellation_penalties(
+        &self,
+        walk_request_id: &str,
+    ) -> Result<Vec<CancellationPenalty>, Error> {
+        self.db
+            .collection::<CancellationPenalty>("cancellation_penalties")
+            .find(
+                doc! {"walk_request_id": walk_request_id},
+                FindOptions::builder()
+                    .projection(CancellationPenalty::projection())
+                    .sort(doc! {"created_at": -1})
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to query cancellation penalties").with_cause(e))?
+            .try_collect::<Vec<CancellationPenalty>>()
+            .await
+            .map_err(|e| Error::new("failed to query cancellation penalties").with_cause(e))
+    }
+
+    async fn create_meet_and_greet(&self, create: MeetAndGreetCreate) -> Result<String, Error> {
+        self.insert_one("meet_and_greets", Document::from_model(create))
+            .await
+            .map_err(|e| Error::new("failed to create meet and greet").with_cause(e))?
+            .inserted_id
+            .as_object_id()
+            .ok_or(Error::new("failed to create meet and greet").with_cause("invalid inserted id"))
+            .map(|id| id.to_string())
+    }
+
+    async fn query_meet_and_greets(
+        &self,
+        query: MeetAndGreetQuery,
+    ) -> Result<Vec<MeetAndGreet>, Error> {
+        self.db
+            .collection::<MeetAndGreet>("meet_and_greets")
+            .find(
+                Document::try_from_model(query)?,
+                FindOptions::builder()
+                    .projection(MeetAndGreet::projection())
+                    .sort(doc! {"starts_at": 1})
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to query meet and greets").with_cause(e))?
+            .try_collect::<Vec<MeetAndGreet>>()
+            .await
+            .map_err(|e| Error::new("failed to query meet and greets").with_cause(e))
+    }
+
+    async fn update_meet_and_greet_by_query(
+        &self,
+        query: MeetAndGreetQuery,
+        update: MeetAndGreetUpdate,
+    ) -> Result<Option<MeetAndGreet>, Error> {
+        self.find_one_and_update(
+            "meet_and_greets",
+            Document::try_from_model(query)?,
+            Document::from_model(update),
+            FindOneAndUpdateOptions::builder()
+                .return_document(Some(mongodb::options::ReturnDocument::After))
+                .projection(MeetAndGreet::projection())
+                .build(),
+        )
+        .await
+        .map_err(|e| Error::new("failed to update meet and greet").with_cause(e))
+    }
+
+    async fn create_chat_message(&self, create: ChatMessageCreate) -> Result<String, Error> {
+        self.insert_one("messages", Document::from_model(create))
+            .await
+            .map_err(|e| Error::new("failed to create chat message").with_cause(e))?
+            .inserted_id
+            .as_object_id()
+            .ok_or(Error::new("failed to create chat message").with_cause("invalid inserted id"))
+            .map(|id| id.to_string())
+    }
+
+    async fn get_chat_message(&self, id: &str) -> Result<ChatMessage, Error> {
+        self.db
+            .collection::<ChatMessage>("messages")
+            .find_one(
+                doc! {"_id": parse_object_id(id)?},
+                FindOneOptions::builder()
+                    .projection(ChatMessage::projection())
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to get chat message").with_cause(e))?
+            .ok_or(Error::not_found("chat message not found"))
+    }
+
+    async fn query_chat_messages(
+        &self,
+        walk_request_id: &str,
+        page: Page,
+    ) -> Result<(Vec<ChatMessage>, u64), Error> {
+        let filter = doc! {"walk_request_id": walk_request_id};
+        let total = self
+            .db
+            .collection::<Document>("messages")
+            .count_documents(filter.clone(), None)
+            .await
+            .map_err(|e| Error::new("failed to count chat messages").with_cause(e))?;
+        let sort_by = SortBy {
+            field: ChatMessage::created_at(),
+            order: Order::Desc,
+        };
+        let (filter, skip, limit) = match page {
+            Page::Offset(pagination) => (filter, pagination.skip, pagination.limit),
+            Page::Cursor { after: None, limit } => (filter, 0, limit),
+            Page::Cursor { after: Some(after), limit } => {
+                (doc! {"$and": [filter, cursor_filter(Some(&sort_by), &after)]}, 0, limit)
+            }
+        };
+        let messages = self
+            .db
+            .collection::<ChatMessage>("messages")
+            .find(
+                filter,
+                FindOptions::builder()
+                    .projection(ChatMessage::projection())
+                    .sort(doc! {"created_at": -1, "_id": -1})
+                    .skip(Some(skip.max(0) as u64))
+                    .limit(Some(limit).filter(|limit| *limit > 0))
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to query chat messages").with_cause(e))?
+            .try_collect::<Vec<ChatMessage>>()
+            .await
+            .map_err(|e| Error::new("failed to query chat messages").with_cause(e))?;
+        Ok((messages, total))
+    }
+
+    async fn query_chat_read_markers(
+        &self,
+        walk_request_id: &str,
+    ) -> Result<Vec<ChatReadMarker>, Error> {
+        self.db
+            .collection::<ChatReadMarker>("chat_read_markers")
+            .find(
+                doc! {"walk_request_id": walk_request_id},
+                FindOptions::builder()
+                    .projection(ChatReadMarker::projection())
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to query chat read markers").with_cause(e))?
+            .try_collect::<Vec<ChatReadMarker>>()
+            .await
+            .map_err(|e| Error::new("failed to query chat read markers").with_cause(e))
+    }
+
+    async fn upsert_chat_read_marker(&self, upsert: ChatReadMarkerUpsert) -> Result<(), Error> {
+        self.update_one(
+            "chat_read_markers",
+            doc! {"walk_request_id": upsert.walk_request_id, "user_id": upsert.user_id},
+            doc! {"$set": {"message_id": upsert.message_id, "message_created_at": upsert.message_created_at}},
+            Some(UpdateOptions::builder().upsert(true).build()),
+        )
+        .await
+        .map_err(|e| Error::new("failed to upsert chat read marker").with_cause(e))
+        .map(|_| ())
+    }
+
+    async fn count_unread_chat_messages(
+        &self,
+        user_id: &str,
+        walk_request_ids: &[String],
+    ) -> Result<HashMap<String, u64>, Error> {
+        if walk_request_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let read_until: HashMap<String, Bson> = self
+            .db
+            .collection::<Document>("chat_read_markers")
+            .find(
+                doc! {"user_id": user_id, "walk_request_id": {"$in": walk_request_ids}},
+                None,
+            )
+            .await
+            .map_err(|e| Error::new("failed to query chat read markers").with_cause(e))?
+            .try_collect::<Vec<Document>>()
+            .await
+            .map_err(|e| Error::new("failed to query chat read markers").with_cause(e))?
+            .into_iter()
+            .filter_map(|d| {
+                let id = d.get_str("walk_request_id").ok()?.to_owned();
+                Some((id, d.get("message_created_at")?.clone()))
+            })
+            .collect();
+        // 每个请求一个条件, 有已读标记的只统计标记之后的消息
+        let conditions: Vec<Document> = walk_request_ids
+            .iter()
+            .map(|id| match read_until.get(id) {
+                Some(until) => doc! {"walk_request_id": id, "created_at": {"$gt": until.clone()}},
+                None => doc! {"walk_request_id": id},
+            })
+            .collect();
+        let pipeline = vec![
+            doc! { "$match": { "sender_id": { "$ne": user_id }, "$or": conditions } },
+            doc! { "$group": { "_id": "$walk_request_id", "unread": { "$sum": 1 } } },
+        ];
+        Ok(self
+            .aggregate_all("messages", pipeline)
+            .await?
+            .into_iter()
+            .filter_map(|d| {
+                let id = d.get_str("_id").ok()?.to_owned();
+                Some((id, get_number(&d, "unread") as u64))
+            })
+            .collect())
+    }
+
+    async fn operations_snapshot(&self, since: DateTime<Utc>) -> Result<OperationsSnapshot, Error> {
+        let active_walks = self
+            .db
+            .collection::<Document>("walk_requests")
+            .count_documents(
+                doc! {"started_at": {"$ne": null}, "finished_at": null, "canceled_at": null},
+                None,
+            )
+            .await
+            .map_err(|e| Error::new("failed to count active walks").with_cause(e))?;
+        let failed_payouts = self
+            .db
+            .collection::<Document>("withdrawals")
+            .count_documents(
+                doc! {"status": WithdrawalStatus::Failed.to_string(), "updated_at": {"$gte": since}},
+                None,
+            )
+            .await
+            .map_err(|e| Error::new("failed to count failed payouts").with_cause(e))?;
+        Ok(OperationsSnapshot {
+            active_walks: active_walks as i64,
+            failed_payouts: failed_payouts as i64,
+        })
+    }
+
+    async fn create_direct_upload(&self, create: DirectUploadCreate) -> Result<String, Error> {
+        self.insert_one(
+            "direct_uploads",
+            doc! {
+                "uploader": create.uploader,
+                "key": create.key,
+                "filename": create.filename,
+                "content_type": create.content_type,
+                "size": create.size,
+                "status": DirectUploadStatus::Pending.to_string(),
+                "expires_at": create.expires_at,
+                "confirmed_at": Bson::Null,
+            },
+        )
+        .await
+        .map_err(|e| Error::new("failed to create direct upload").with_cause(e))?
+        .inserted_id
+        .as_object_id()
+        .ok_or(Error::new("failed to create direct upload").with_cause("invalid inserted id"))
+        .map(|id| id.to_string())
+    }
+
+    async fn get_direct_upload(&self, id: &str) -> Result<Option<DirectUpload>, Error> {
+        self.db
+            .collection::<DirectUpload>("direct_uploads")
+            .find_one(
+                doc! {"_id": parse_object_id(id)?},
+                FindOneOptions::builder()
+                    .projection(DirectUpload::projection())
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to get direct upload").with_cause(e))
+    }
+
+    // 只确认待上传的记录, 返回是否确认成功
+    async fn confirm_direct_upload(&self, id: &str, size: i64) -> Result<bool, Error> {
+        self.update_one(
+            "direct_uploads",
+            doc! {
+                "_id": parse_object_id(id)?,
+                "status": DirectUploadStatus::Pending.to_string(),
+            },
+            doc! {"$set": {
+                "status": DirectUploadStatus::Confirmed.to_string(),
+                "size": size,
+                "confirmed_at": Utc::now(),
+            }},
+            None,
+        )
+        .await
+        .map_err(|e| Error::new("failed to confirm direct upload").with_cause(e))
+        .map(|res| res.modified_count > 0)
+    }
+
+    async fn create_location_access(&self, create: LocationAccessCreate) -> Result<String, Error> {
+        self.insert_one(
+            "location_accesses",
+            doc! {
+                "accessor_id": create.accessor_id,
+                "kind": create.kind.to_string(),
+                "walk_request_id": create.walk_request_id,
+                "subject_ids": create.subject_ids,
+            },
+        )
+        .await
+        .map_err(|e| Error::new("failed to create location access").with_cause(e))?
+        .inserted_id
+        .as_object_id()
+        .ok_or(Error::new("failed to create location access").with_cause("invalid inserted id"))
+        .map(|id| id.to_string())
+    }
+
+    async fn query_location_accesses(
+        &self,
+        query: LocationAccessQuery,
+        pagination: Pagination,
+    ) -> Result<(Vec<LocationAccess>, i64), Error> {
+        let q = Document::from_model(query);
+        let total = self
+            .db
+            .collection::<LocationAccess>("location_accesses")
+            .count_documents(q.clone(), None)
+            .await
+            .map_err(|e| Error::new("failed to query location accesses").with_cause(e))?;
+        let accesses = self
+            .db
+            .collection::<LocationAccess>("location_accesses")
+            .find(
+                q,
+                FindOptions::builder()
+                    .projection(LocationAccess::projection())
+                    .sort(doc! {"created_at": -1})
+                    .skip(pagination.skip as u64)
+                    .limit(pagination.limit)
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to query location accesses").with_cause(e))?
+            .try_collect::<Vec<LocationAccess>>()
+            .await
+            .map_err(|e| Error::new("failed to query location accesses").with_cause(e))?;
+        Ok((accesses, total as i64))
+    }
+
+    async fn count_upload_references(&self, upload_id: &str) -> Result<i64, Error> {
+        let mut count = 0;
+        for (collection, field) in UPLOAD_REFERENCES {
+            count += self
+                .db
+                .collection::<Document>(collection)
+                .count_documents(doc! {*field: upload_id}, None)
+                .await
+                .map_err(|e| Error::new("failed to count upload references").with_cause(e))?;
+        }
+        Ok(count as i64)
+    }
+
+    // upload-service的上传记录没有创建时间, 按ObjectId中的时间戳筛选
+    async fn query_unreferenced_uploads(
+        &self,
+        created_before: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<String>, Error> {
+        let mut pipeline = vec![
+            doc! {"$match": {"_id": {"$lt": ObjectId::from_bytes(object_id_prefix(created_before))}}},
+            doc! {"$project": {"_id": 0, "upload_id": {"$toString": "$_id"}}},
+        ];
+        let mut unreferenced = doc! {};
+        for (i, (collection, field)) in UPLOAD_REFERENCES.iter().enumerate() {
+            let name = format!("references_{}", i);
+            pipeline.push(doc! {"$lookup": {
+                "from": *collection,
+                "localField": "upload_id",
+                "foreignField": *field,
+                "as": &name,
+            }});
+            unreferenced.insert(name, doc! {"$size": 0});
+        }
+        pipeline.push(doc! {"$match": unreferenced});
+        pipeline.push(doc! {"$limit": limit});
+        pipeline.push(doc! {"$project": {"upload_id": 1}});
+        self.aggregate_all("uploads", pipeline)
+            .await?
+            .into_iter()
+            .map(|d| {
+                d.get_str("upload_id")
+                    .map(|id| id.to_owned())
+                    .map_err(|e| Error::new("failed to query unreferenced uploads").with_cause(e))
+            })
+            .collect()
+    }
+
+    // 删除上传记录及其缩略图记录, 返回删除的上传记录数(含缩略图)
+    async fn delete_uploads(&self, upload_ids: &[String]) -> Result<u64, Error> {
+        let variant_ids = self
+            .db
+            .collection::<UploadVariant>("upload_variants")
+            .find(
+                doc! {"upload_id": {"$in": upload_ids}},
+                FindOptions::builder()
+                    .projection(doc! {"_id": 0, "upload_id": 1, "size": 1, "variant_id": 1})
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to delete uploads").with_cause(e))?
+            .try_collect::<Vec<UploadVariant>>()
+            .await
+            .map_err(|e| Error::new("failed to delete uploads").with_cause(e))?
+            .into_iter()
+            .map(|v| v.variant_id);
+        let ids = upload_ids
+            .iter()
+            .cloned()
+            .chain(variant_ids)
+            .map(|id| parse_object_id(&id))
+            .collect::<Result<Vec<ObjectId>, Error>>()?;
+        let deleted = self
+            .db
+            .collection::<Document>("uploads")
+            .delete_many(doc! {"_id": {"$in": ids}}, None)
+            .await
+            .map_err(|e| Error::new("failed to delete uploads").with_cause(e))?
+            .deleted_count;
+        self.db
+            .collection::<Document>("upload_variants")
+            .delete_many(doc! {"upload_id": {"$in": upload_ids}}, None)
+            .await
+            .map_err(|e| Error::new("failed to delete upload variants").with_cause(e))?;
+        Ok(deleted)
+    }
+
+    async fn query_integrity_issues(
+        &self,
+        kind: IntegrityIssueKind,
+        limit: i64,
+    ) -> Result<Vec<IntegrityIssue>, Error> {
+        let mut issues = Vec::new();
+        for (collection, field, mut pipeline) in integrity_pipelines(kind) {
+            let remaining = limit - issues.len() as i64;
+            if remaining <= 0 {
+                break;
+            }
+            pipeline.push(doc! {"$limit": remaining});
+            pipeline.push(doc! {"$project": {
+                "_id": 0,
+                "record_id": {"$toString": "$_id"},
+                "reference_id": {"$toString": "$reference"},
+            }});
+            for d in self.aggregate_all(collection, pipeline).await? {
+                let (Ok(record_id), Ok(reference_id)) =
+                    (d.get_str("record_id"), d.get_str("reference_id"))
+                else {
+                    continue;
+                };
+                issues.push(IntegrityIssue {
+                    kind,
+                    collection: collection.to_owned(),
+                    record_id: record_id.to_owned(),
+                    field: field.to_owned(),
+                    reference_id: reference_id.to_owned(),
+                    fixed: false,
+                });
+            }
+        }
+        Ok(issues)
+    }
+
+    async fn repair_integrity_issue(&self, issue: &IntegrityIssue) -> Result<bool, Error> {
+        let record_id = parse_object_id(&issue.record_id)?;
+        match issue.kind {
+            IntegrityIssueKind::DogMissingBreed => {
+                self.restore_dog_breed(record_id, &issue.reference_id)
+                    .await
+            }
+            IntegrityIssueKind::MissingUpload => {
+                self.remove_upload_reference(
+                    &issue.collection,
+                    &issue.field,
+                    record_id,
+                    &issue.reference_id,
+                )
+                .await
+            }
+            IntegrityIssueKind::WalkRequestDeletedDog => Ok(false),
+        }
+    }
+
+    // 把明文或旧版本密钥加密的字段改用当前密钥加密, 以原值为条件更新避免覆盖并发写入
+    async fn reencrypt_fields(&self, limit: i64) -> Result<u64, Error> {
+        let Some(cipher) = &self.field_cipher else {
+            return Ok(0);
+        };
+        let prefix = cipher.current_prefix();
+        let mut migrated = 0;
+        for collection in ENCRYPTED_PHONE_COLLECTIONS {
+            let docs = self
+                .db
+                .collection::<Document>(collection)
+                .find(
+                    doc! {"phone": {"$type": "string", "$not": {"$regex": format!("^{}", prefix)}}},
+                    FindOptions::builder()
+                        .projection(doc! {"phone": 1})
+                        .limit(limit)
+                        .build(),
+                )
+                .await
+                .map_err(|e| Error::new("failed to query unencrypted fields").with_cause(e))?
+                .try_collect::<Vec<Document>>()
+                .await
+                .map_err(|e| Error::new("failed to query unencrypted fields").with_cause(e))?;
+            for d in docs {
+                let (Ok(id), Ok(phone)) = (d.get_object_id("_id"), d.get_str("phone")) else {
+                    continue;
+                };
+                let encrypted = cipher.encrypt(&cipher.decrypt(phone)?)?;
+                migrated += self
+                    .db
+                    .collection::<Document>(collection)
+                    .update_one(
+                        doc! {"_id": id, "phone": phone},
+                        doc! {"$set": {"phone": encrypted}},
+                        None,
+                    )
+                    .await
+                    .map_err(|e| Error::new("failed to encrypt fields").with_cause(e))?
+                    .modified_count;
+            }
+        }
+        Ok(migrated)
+    }
+
+    async fn get_token_version(&self, user_id: &str) -> Result<i64, Error> {
+        Ok(self
+            .db
+            .collection::<Document>("token_versions")
+            .find_one(doc! {"user_id": user_id}, None)
+            .await
+            .map_err(|e| Error::new("failed to get token version").with_cause(e))?
+            .and_then(|d| d.get_i64("version").ok())
+            .unwrap_or(0))
+    }
+
+    async fn increment_token_version(&self, user_id: &str) -> Result<i64, Error> {
+        self.find_one_and_update::<Document>(
+            "token_versions",
+            doc! {"user_id": user_id},
+            doc! {"$inc": {"version": 1_i64}},
+            FindOneAndUpdateOptions::builder()
+                .upsert(true)
+                .return_document(Some(mongodb::options::ReturnDocument::After))
+                .build(),
+        )
+        .await
+        .map_err(|e| Error::new("failed to increment token version").with_cause(e))?
+        .and_then(|d| d.get_i64("version").ok())
+        .ok_or(Error::new("failed to increment token version"))
+    }
+
+    // 只有第一次标记成功, 同一请求只通知一次
+    async fn mark_walker_nearby_notified(&self, walk_request_id: &str) -> Result<bool, Error> {
+        let id = parse_object_id(walk_request_id)?;
+        Ok(self
+            .update_one(
+                "walk_requests",
+                doc! {"_id": id, "walker_nearby_notified_at": null},
+                doc! {"$set": {"walker_nearby_notified_at": Utc::now()}},
+                None,
+            )
+            .await
+            .map_err(|e| Error::new("failed to mark walker nearby notified").with_cause(e))?
+            .modified_count
+            > 0)
+    }
+
+    async fn create_notification(&self, create: NotificationCreate) -> Result<String, Error> {
+        self.insert_one("notifications", Document::from_model(create))
+            .await
+            .map_err(|e| Error::new("failed to create notification").with_cause(e))?
+            .inserted_id
+            .as_object_id()
+            .ok_or(Error::new("failed to create notification").with_cause("invalid inserted id"))
+            .map(|id| id.to_string())
+    }
+
+    async fn query_notifications(
+        &self,
+        user_id: &str,
+        pagination: Pagination,
+    ) -> Result<Vec<Notification>, Error> {
+        self.db
+            .collection::<Notification>("notifications")
+            .find(
+                doc! {"user_id": user_id},
+                FindOptions::builder()
+                    .projection(Notification::projection())
+                    .sort(doc! {"created_at": -1})
+                    .skip(pagination.skip as u64)
+                    .limit(pagination.limit)
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to query notifications").with_cause(e))?
+            .try_collect::<Vec<Notification>>()
+            .await
+            .map_err(|e| Error::new("failed to query notifications").with_cause(e))
+    }
+
+    async fn claim_unpushed_notification(
+        &self,
+        created_after: DateTime<Utc>,
+    ) -> Result<Option<Notification>, Error> {
+        self.find_one_and_update(
+            "notifications",
+            doc! {"pushed_at": null, "created_at": {"$gt": created_after}},
+            doc! {"$set": {"pushed_at": Utc::now()}},
+            FindOneAndUpdateOptions::builder()
+                .sort(doc! {"created_at": 1})
+                .return_document(Some(mongodb::options::ReturnDocument::After))
+                .projection(Notification::projection())
+                .build(),
+        )
+        .await
+        .map_err(|e| Error::new("failed to claim notification").with_cause(e))
+    }
+
+    async fn upsert_device_token(&self, upsert: DeviceTokenUpsert) -> Result<(), Error> {
+        self.update_one(
+            "device_tokens",
+            doc! {"token": upsert.token},
+            doc! {"$set": {"user_id": upsert.user_id, "platform": upsert.platform.to_string()}},
+            Some(UpdateOptions::builder().upsert(true).build()),
+        )
+        .await
+        .map_err(|e| Error::new("failed to upsert device token").with_cause(e))?;
+        Ok(())
+    }
+
+    async fn query_device_tokens(&self, user_id: &str) -> Result<Vec<DeviceToken>, Error> {
+        self.db
+            .collection::<DeviceToken>("device_tokens")
+            .find(
+                doc! {"user_id": user_id},
+                FindOptions::builder()
+                    .projection(DeviceToken::projection())
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to query device tokens").with_cause(e))?
+            .try_collect::<Vec<DeviceToken>>()
+            .await
+            .map_err(|e| Error::new("failed to query device tokens").with_cause(e))
+    }
+
+    async fn delete_device_tokens(&self, query: DeviceTokenQuery) -> Result<u64, Error> {
+        self.delete_many("device_tokens", Document::from_model(query))
+            .await
+            .map_err(|e| Error::new("failed to delete device tokens").with_cause(e))
+            .map(|res| res.deleted_count)
+    }
+
+    async fn get_notification_settings(
+        &self,
+        user_id: &str,
+    ) -> Result<Option<NotificationSettings>, Error> {
+        let mut settings = self
+            .db
+            .collection::<NotificationSettings>("notification_settings")
+            .find_one(
+                doc! {"user_id": user_id},
+                FindOneOptions::builder()
+                    .projection(NotificationSettings::projection())
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to get notification settings").with_cause(e))?;
+        if let Some(phone) = settings.as_mut().and_then(|s| s.sms_phone.as_mut()) {
+            self.decrypt_field(phone)?;
+        }
+        Ok(settings)
+    }
+
+    async fn upsert_notification_settings(
+        &self,
+        upsert: NotificationSettingsUpsert,
+    ) -> Result<NotificationSettings, Error> {
+        let sms_phone = upsert
+            .sms_phone
+            .map(|phone| self.encrypt_field(&phone))
+            .transpose()?;
+        let mut settings: NotificationSettings = self
+            .find_one_and_update(
+                "notification_settings",
+                doc! {"user_id": upsert.user_id},
+                doc! {"$set": {
+                    "sms_enabled": upsert.sms_enabled,
+                    "sms_phone": sms_phone,
+                    "email": upsert.email,
+                    "email_receipts": upsert.email_receipts,
+                    "email_weekly_summary": upsert.email_weekly_summary,
+                    "email_verification_decisions": upsert.email_verification_decisions,
+                }},
+                FindOneAndUpdateOptions::builder()
+                    .upsert(true)
+                    .return_document(Some(mongodb::options::ReturnDocument::After))
+                    .projection(NotificationSettings::projection())
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to update notification settings").with_cause(e))?
+            .ok_or(Error::new("updated notification settings not exists"))?;
+        if let Some(phone) = settings.sms_phone.as_mut() {
+            self.decrypt_field(phone)?;
+        }
+        Ok(settings)
+    }
+
+    async fn claim_weekly_summary_recipient(
+        &self,
+        week_start: DateTime<Utc>,
+    ) -> Result<Option<NotificationSettings>, Error> {
+        let mut settings: Option<NotificationSettings> = self
+            .find_one_and_update(
+                "notification_settings",
+                doc! {
+                    "email_weekly_summary": true,
+                    "email": {"$ne": null},
+                    "$or": [
+                        {"weekly_summary_sent_at": null},
+                        {"weekly_summary_sent_at": {"$lt": week_start}},
+                    ],
+                },
+                doc! {"$set": {"weekly_summary_sent_at": Utc::now()}},
+                FindOneAndUpdateOptions::builder()
+                    .return_document(Some(mongodb::options::ReturnDocument::After))
+                    .projection(NotificationSettings::projection())
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to claim weekly summary recipient").with_cause(e))?;
+        if let Some(phone) = settings.as_mut().and_then(|s| s.sms_phone.as_mut()) {
+            self.decrypt_field(phone)?;
+        }
+        Ok(settings)
+    }
+
+    async fn create_email(&self, create: EmailCreate) -> Result<String, Error> {
+        self.insert_one("emails", Document::from_model(create))
+            .await
+            .map_err(|e| Error::new("failed to create email").with_cause(e))?
+            .inserted_id
+            .as_object_id()
+            .ok_or(Error::new("failed to create email").with_cause("invalid inserted id"))
+            .map(|id| id.to_string())
+    }
+
+    async fn claim_unsent_email(
+        &self,
+        created_after: DateTime<Utc>,
+    ) -> Result<Option<Email>, Error> {
+        self.find_one_and_update(
+            "emails",
+            doc! {"sent_at": null, "created_at": {"$gt": created_after}},
+            doc! {"$set": {"sent_at": Utc::now()}},
+            FindOneAndUpdateOptions::builder()
+                .sort(doc! {"created_at": 1})
+                .return_document(Some(mongodb::options::ReturnDocument::After))
+                .projection(Email::projection())
+                .build(),
+        )
+        .await
+        .map_err(|e| Error::new("failed to claim email").with_cause(e))
+    }
+
+    async fn query_translations(
+        &self,
+        source_ids: &[String],
+        language: Language,
+    ) -> Result<Vec<Translation>, Error> {
+        self.db
+            .collection::<Translation>("translations")
+            .find(
+                doc! {"source_id": {"$in": source_ids}, "language": language.to_string()},
+                FindOptions::builder()
+                    .projection(Translation::projection())
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to query translations").with_cause(e))?
+            .try_collect::<Vec<Translation>>()
+            .await
+            .map_err(|e| Error::new("failed to query translations").with_cause(e))
+    }
+
+    async fn upsert_translation(&self, upsert: TranslationUpsert) -> Result<(), Error> {
+        self.update_one(
+            "translations",
+            doc! {"source_id": upsert.source_id, "language": upsert.language.to_string()},
+            doc! {"$set": {"source_hash": upsert.source_hash, "text": upsert.text}},
+            Some(UpdateOptions::builder().upsert(true).build()),
+        )
+        .await
+        .map_err(|e| Error::new("failed to upsert translation").with_cause(e))
+        .map(|_| ())
+    }
+
+    async fn create_action_token(&self, create: ActionTokenCreate) -> Result<String, Error> {
+        self.insert_one("action_tokens", Document::from_model(create))
+            .await
+            .map_err(|e| Error::new("failed to create action token").with_cause(e))?
+            .inserted_id
+            .as_object_id()
+            .ok_or(Error::new("failed to create action token").with_cause("invalid inserted id"))
+            .map(|id| id.to_string())
+    }
+
+    async fn consume_action_token(
+        &self,
+        user_id: &str,
+        action: SensitiveAction,
+        token_hash: &str,
+    ) -> Result<Option<ActionToken>, Error> {
+        self.find_one_and_update::<ActionToken>(
+            "action_tokens",
+            doc! {
+                "user_id": user_id,
+                "action": action.to_string(),
+                "token_hash": token_hash,
+                "used": false,
+                "expires_at": {"$gt": Utc::now()},
+            },
+            doc! {"$set": {"used": true}},
+            FindOneAndUpdateOptions::builder()
+                .projection(ActionToken::projection())
+                .build(),
+        )
+        .await
+        .map_err(|e| Error::new("failed to consume action token").with_cause(e))
+    }
+
+    async fn create_audit_log(&self, create: AuditLogCreate) -> Result<String, Error> {
+        self.insert_one("audit_logs", Document::from_model(create))
+            .await
+            .map_err(|e| Error::new("failed to create audit log").with_cause(e))?
+            .inserted_id
+            .as_object_id()
+            .ok_or(Error::new("failed to create audit log").with_cause("invalid inserted id"))
+            .map(|id| id.to_string())
+    }
+
+    async fn query_audit_logs(
+        &self,
+        query: AuditLogQuery,
+        pagination: Pagination,
+    ) -> Result<(Vec<AuditLog>, i64), Error> {
+        let q = Document::from_model(query);
+        let total = self
+            .db
+            .collection::<AuditLog>("audit_logs")
+            .count_documents(q.clone(), None)
+            .await
+            .map_err(|e| Error::new("failed to query audit logs").with_cause(e))?;
+        let logs = self
+            .db
+            .collection::<AuditLog>("audit_logs")
+            .find(
+                q,
+                FindOptions::builder()
+                    .projection(AuditLog::projection())
+                    .sort(doc! {"created_at": -1})
+                    .skip(pagination.skip as u64)
+                    .limit(pagination.limit)
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to query audit logs").with_cause(e))?
+            .try_collect::<Vec<AuditLog>>()
+            .await
+            .map_err(|e| Error::new("failed to query audit logs").with_cause(e))?;
+        Ok((logs, total as i64))
+    }
+
+    async fn purge_deleted(&self, deleted_before: DateTime<Utc>) -> Result<PurgedCounts, Error> {
+        let deleted = doc! {"deleted_at": {"$lte": deleted_before}};
+        let walk_request_ids = self
+            .db
+            .collection::<Document>("walk_requests")
+            .find(
+                deleted.clone(),
+                FindOptions::builder().projection(doc! {"_id": 1}).build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to query deleted walk requests").with_cause(e))?
+            .try_collect::<Vec<Document>>()
+            .await
+            .map_err(|e| Error::new("failed to query deleted walk requests").with_cause(e))?
+            .into_iter()
+            .filter_map(|d| d.get_object_id("_id").ok())
+            .collect::<Vec<ObjectId>>();
+        self.with_transaction(|repository| async move {
+            let walking_locations = repository
+                .delete_many(
+                    "walking_locations",
+                    doc! {"walk_request_id": {"$in": walk_request_ids.iter().map(|id| id.to_hex()).collect::<Vec<String>>()}},
+                )
+                .await
+                .map_err(|e| Error::new("failed to purge walking locations").with_cause(e))?
+                .deleted_count;
+            let mut filter = deleted.clone();
+            filter.insert("_id", doc! {"$in": walk_request_ids});
+            let walk_requests = repository
+                .delete_many("walk_requests", filter)
+                .await
+                .map_err(|e| Error::new("failed to purge walk requests").with_cause(e))?
+                .deleted_count;
+            let dogs = repository
+                .delete_many("dogs", deleted)
+                .await
+                .map_err(|e| Error::new("failed to purge dogs").with_cause(e))?
+                .deleted_count;
+            Ok(PurgedCounts {
+                dogs,
+                walk_requests,
+                walking_locations,
+            })
+        })
+        .await
+    }
+
+    async fn create_partner(&self, create: PartnerCreate) -> Result<String, Error> {
+        self.insert_one("partners", Document::from_model(create))
+            .await
+            .map_err(|e| Error::new("failed to create partner").with_cause(e))?
+            .inserted_id
+            .as_object_id()
+            .ok_or(Error::new("failed to create partner").with_cause("invalid inserted id"))
+            .map(|id| id.to_string())
+    }
+
+    async fn get_partner(&self, id: &str) -> Result<Option<Partner>, Error> {
+        self.db
+            .collection::<Partner>("partners")
+            .find_one(
+                doc! {"_id": parse_object_id(id)?},
+                FindOneOptions::builder()
+                    .projection(Partner::projection())
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to get partner").with_cause(e))
+    }
+
+    async fn query_partners(&self, pagination: Pagination) -> Result<(Vec<Partner>, i64), Error> {
+        let total = self
+            .db
+            .collection::<Partner>("partners")
+            .count_documents(doc! {}, None)
+            .await
+            .map_err(|e| Error::new("failed to query partners").with_cause(e))?;
+        let partners = self
+            .db
+            .collection::<Partner>("partners")
+            .find(
+                doc! {},
+                FindOptions::builder()
+                    .projection(Partner::projection())
+                    .sort(doc! {"created_at": -1})
+                    .skip(pagination.skip as u64)
+                    .limit(pagination.limit)
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to query partners").with_cause(e))?
+            .try_collect::<Vec<Partner>>()
+            .await
+            .map_err(|e| Error::new("failed to query partners").with_cause(e))?;
+        Ok((partners, total as i64))
+    }
+
+    async fn update_partner(&self, id: &str, update: PartnerUpdate) -> Result<Option<Partner>, Error> {
+        self.find_one_and_update(
+            "partners",
+            doc! {"_id": parse_object_id(id)?},
+            Document::from_model(update),
+            FindOneAndUpdateOptions::builder()
+                .return_document(Some(mongodb::options::ReturnDocument::After))
+                .projection(Partner::projection())
+                .build(),
+        )
+        .await
+        .map_err(|e| Error::new("failed to update partner").with_cause(e))
+    }
+
+    async fn create_partner_api_key(&self, create: PartnerApiKeyCreate) -> Result<String, Error> {
+        self.insert_one(
+            "partner_api_keys",
+            doc! {
+                "partner_id": create.partner_id,
+                "key_hash": create.key_hash,
+                "prefix": create.prefix,
+                "revoked_at": null,
+            },
+        )
+        .await
+        .map_err(|e| Error::new("failed to create partner api key").with_cause(e))?
+        .inserted_id
+        .as_object_id()
+        .ok_or(Error::new("failed to create partner api key").with_cause("invalid inserted id"))
+        .map(|id| id.to_string())
+    }
+
+    async fn get_partner_api_key(&self, key_hash: &str) -> Result<Option<PartnerApiKey>, Error> {
+        self.db
+            .collection::<PartnerApiKey>("partner_api_keys")
+            .find_one(
+                doc! {"key_hash": key_hash, "revoked_at": null},
+                FindOneOptions::builder()
+                    .projection(PartnerApiKey::projection())
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to get partner api key").with_cause(e))
+    }
+
+    async fn query_partner_api_keys(&self, partner_id: &str) -> Result<Vec<PartnerApiKey>, Error> {
+        self.db
+            .collection::<PartnerApiKey>("partner_api_keys")
+            .find(
+                doc! {"partner_id": partner_id},
+                FindOptions::builder()
+                    .projection(PartnerApiKey::projection())
+                    .sort(doc! {"created_at": -1})
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to query partner api keys").with_cause(e))?
+            .try_collect::<Vec<PartnerApiKey>>()
+            .await
+            .map_err(|e| Error::new("failed to query partner api keys").with_cause(e))
+    }
+
+    async fn revoke_partner_api_key(&self, partner_id: &str, id: &str) -> Result<bool, Error> {
+        self.update_one(
+            "partner_api_keys",
+            doc! {
+                "_id": parse_object_id(id)?,
+                "partner_id": partner_id,
+                "revoked_at": null,
+            },
+            doc! {"$set": {"revoked_at": Utc::now()}},
+            None,
+        )
+        .await
+        .map_err(|e| Error::new("failed to revoke partner api key").with_cause(e))
+        .map(|res| res.modified_count > 0)
+    }
+
+    async fn create_partner_consent(
+        &self,
+        partner_id: &str,
+        owner_id: &str,
+    ) -> Result<PartnerConsent, Error> {
+        self.find_one_and_update(
+            "partner_consents",
+            doc! {"partner_id": partner_id, "owner_id": owner_id},
+            doc! {},
+            FindOneAndUpdateOptions::builder()
+                .upsert(true)
+                .return_document(Some(mongodb::options::ReturnDocument::After))
+                .projection(PartnerConsent::projection())
+                .build(),
+        )
+        .await
+        .map_err(|e| Error::new("failed to create partner consent").with_cause(e))?
+        .ok_or(Error::new("created partner consent not exists"))
+    }
+
+    async fn delete_partner_consent(&self, partner_id: &str, owner_id: &str) -> Result<bool, Error> {
+        self.delete_many(
+            "partner_consents",
+            doc! {"partner_id": partner_id, "owner_id": owner_id},
+        )
+        .await
+        .map_err(|e| Error::new("failed to delete partner consent").with_cause(e))
+        .map(|res| res.deleted_count > 0)
+    }
+
+    async fn query_partner_consents(
+        &self,
+        query: PartnerConsentQuery,
+    ) -> Result<Vec<PartnerConsent>, Error> {
+        let mut q = doc! {};
+        if let Some(partner_id) = query.partner_id {
+            q.insert("partner_id", partner_id);
+        }
+        if let Some(owner_id) = query.owner_id {
+            q.insert("owner_id", owner_id);
+        }
+        self.db
+            .collection::<PartnerConsent>("partner_consents")
+            .find(
+                q,
+                FindOptions::builder()
+                    .projection(PartnerConsent::projection())
+                    .sort(doc! {"created_at": -1})
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to query partner consents").with_cause(e))?
+            .try_collect::<Vec<PartnerConsent>>()
+            .await
+            .map_err(|e| Error::new("failed to query partner consents").with_cause(e))
+    }
+
+    async fn create_partner_usage(&self, partner_id: &str, endpoint: &str) -> Result<(), Error> {
+        self.insert_one(
+            "partner_usages",
+            doc! {"partner_id": partner_id, "endpoint": endpoint},
+        )
+        .await
+        .map_err(|e| Error::new("failed to create partner usage").with_cause(e))
+        .map(|_| ())
+    }
+
+    async fn count_partner_usages(
+        &self,
+        partner_id: &str,
+        since: DateTime<Utc>,
+    ) -> Result<i64, Error> {
+        self.db
+            .collection::<Document>("partner_usages")
+            .count_documents(
+                doc! {"partner_id": partner_id, "created_at": {"$gte": since}},
+                None,
+            )
+            .await
+            .map_err(|e| Error::new("failed to count partner usages").with_cause(e))
+            .map(|n| n as i64)
+    }
+
+    async fn partner_usage_report(
+        &self,
+        partner_id: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<PartnerUsage>, Error> {
+        self.aggregate_all(
+            "partner_usages",
+            vec![
+                doc! {"$match": {"partner_id": partner_id, "created_at": {"$gte": from, "$lt": to}}},
+                doc! {"$group": {
+                    "_id": {
+                        "date": {"$dateToString": {"date": "$created_at", "format": "%Y-%m-%d"}},
+                        "endpoint": "$endpoint",
+                    },
+                    "count": {"$sum": 1},
+                }},
+                doc! {"$project": {"_id": 0, "date": "$_id.date", "endpoint": "$_id.endpoint", "count": 1}},
+                doc! {"$sort": {"date": 1, "endpoint": 1}},
+            ],
+        )
+        .await?
+        .into_iter()
+        .map(|d| {
+            from_document::<PartnerUsage>(d)
+                .map_err(|e| Error::new("failed to parse partner usage").with_cause(e))
+        })
+        .collect()
+    }
+
+    async fn available_walker_ids(&self, query: AvailableWalkerQuery) -> Result<Vec<String>, Error> {
+        self.aggregate_near(
+            "walkers",
+            "location",
+            vec![
+                doc! {
+                    "$geoNear": {
+                        "near": { "type": "Point", "coordinates": [query.longitude, query.latitude] },
+                        "distanceField": "distance",
+                        "maxDistance": query.radius,
+                        "spherical": true,
+                        "query": { "verification_status": VerificationStatus::Verified.to_string() },
+                    }
+                },
+                doc! {
+                    "$lookup": {
+                        "from": "walk_requests",
+                        "localField": "user_id",
+                        "foreignField": "accepted_by",
+                        "as": "busy",
+                        "pipeline": [
+                            { "$match": {
+                                "canceled_at": null,
+                                "finished_at": null,
+                                "deleted_at": null,
+                                "$and": [
+                                    { "$or": [{ "should_start_after": null }, { "should_start_after": { "$lt": query.to } }] },
+                                    { "$or": [{ "should_end_before": null }, { "should_end_before": { "$gt": query.from } }] },
+                                ],
+                            } },
+                            { "$project": { "_id": 1 } },
+                        ],
+                    }
+                },
+                doc! { "$match": { "busy": { "$size": 0 } } },
+                doc! { "$project": { "_id": 0, "user_id": 1 } },
+            ],
+        )
+        .await?
+        .into_iter()
+        .map(|d| {
+            d.get_str("user_id")
+                .map(str::to_owned)
+                .map_err(|e| Error::new("failed to parse walker user id").with_cause(e))
+        })
+        .collect()
+    }
+
+    async fn walker_response_times(
+        &self,
+        walker_ids: Vec<String>,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<i64>, Error> {
+        self.aggregate_all(
+            "walk_requests",
+            vec![
+                doc! {"$match": {"accepted_by": {"$in": walker_ids}, "accepted_at": {"$gte": since}}},
+                doc! {"$project": {
+                    "_id": 0,
+                    "seconds": {"$toLong": {"$divide": [{"$subtract": ["$accepted_at", "$created_at"]}, 1000]}},
+                }},
+            ],
+        )
+        .await?
+        .into_iter()
+        .map(|d| {
+            d.get_i64("seconds")
+                .map_err(|e| Error::new("failed to parse walker response time").with_cause(e))
+        })
+        .collect()
+    }
+
+    async fn neighborhood_stats(
+        &self,
+        query: NeighborhoodStatsQuery,
+    ) -> Result<Vec<NeighborhoodStats>, Error> {
+        self.aggregate_near(
+            "walk_requests",
+            "location",
+            vec![
+                doc! {
+                    "$geoNear": {
+                        "near": { "type": "Point", "coordinates": [query.longitude, query.latitude] },
+                        "distanceField": "distance",
+                        "maxDistance": query.radius,
+                        "spherical": true,
+                        "query": { "canceled_at": null, "deleted_at": null },
+                    }
+                },
+                doc! {
+                    "$lookup": {
+                        "from": "ledger_entries",
+                        "let": { "walk_request_id": { "$toString": "$_id" } },
+                        "as": "earnings",
+                        "pipeline": [
+                            { "$match": {
+                                "$expr": { "$eq": ["$walk_request_id", "$$walk_request_id"] },
+                                "kind": LedgerEntryKind::Earning.to_string(),
+                            } },
+                            { "$project": { "amount": 1 } },
+                        ],
+                    }
+                },
+                doc! {
+                    "$project": {
+                        "cell_latitude": { "$floor": { "$divide": [{ "$arrayElemAt": ["$location.coordinates", 1] }, query.cell_size] } },
+                        "cell_longitude": { "$floor": { "$divide": [{ "$arrayElemAt": ["$location.coordinates", 0] }, query.cell_size] } },
+                        "active": { "$cond": [{ "$eq": [{ "$ifNull": ["$finished_at", null] }, null] }, 1, 0] },
+                        "price": { "$cond": [
+                            { "$and": [
+                                { "$gte": ["$finished_at", query.priced_since] },
+                                { "$gt": [{ "$size": "$earnings" }, 0] },
+                            ] },
+                            { "$sum": "$earnings.amount" },
+                            null,
+                        ] },
+                    }
+                },
+                doc! {
+                    "$group": {
+                        "_id": { "latitude": "$cell_latitude", "longitude": "$cell_longitude" },
+                        "active_requests": { "$sum": "$active" },
+                        "priced_walks": { "$sum": { "$cond": [{ "$eq": ["$price", null] }, 0, 1] } },
+                        "average_price": { "$avg": "$price" },
+                    }
+                },
+                doc! {
+                    "$project": {
+                        "_id": 0,
+                        "latitude": { "$multiply": [{ "$add": ["$_id.latitude", 0.5] }, query.cell_size] },
+                        "longitude": { "$multiply": [{ "$add": ["$_id.longitude", 0.5] }, query.cell_size] },
+                        "active_requests": { "$toLong": "$active_requests" },
+                        "priced_walks": { "$toLong": "$priced_walks" },
+                        "average_price": 1,
+                    }
+                },
+                doc! { "$sort": { "latitude": 1, "longitude": 1 } },
+            ],
+        )
+        .await?
+        .into_iter()
+        .map(|d| {
+            from_document::<NeighborhoodStats>(d)
+                .map_err(|e| Error::new("failed to parse neighborhood stats").with_cause(e))
+        })
+        .collect()
+    }
+
+    async fn create_invite(&self, mut create: InviteCreate) -> Result<String, Error> {
+        create.phone = self.encrypt_field(&create.phone)?;
+        self.insert_one("invites", Document::from_model(create))
+            .await
+            .map_err(|e| Error::new("failed to create invite").with_cause(e))?
+            .inserted_id
+            .as_object_id()
+            .ok_or(Error::new("failed to create invite").with_cause("invalid inserted id"))
+            .map(|id| id.to_string())
+    }
+
+    async fn count_invites(&self, query: InviteQuery) -> Result<i64, Error> {
+        let mut q = doc! {};
+        if let Some(inviter_id) = query.inviter_id {
+            q.insert("inviter_id", inviter_id);
+        }
+        if let Some(phone) = query.phone {
+            q.insert("phone", self.encrypted_field_filter(&phone)?);
+        }
+        if let Some(created_after) = query.created_after {
+            q.insert("created_at", doc! {"$gte": created_after});
+        }
+        self.db
+            .collection::<Document>("invites")
+            .count_documents(q, None)
+            .await
+            .map_err(|e| Error::new("failed to count invites").with_cause(e))
+            .map(|n| n as i64)
+    }
+
+    async fn query_invites(
+        &self,
+        inviter_id: &str,
+        pagination: Pagination,
+    ) -> Result<(Vec<Invite>, i64), Error> {
+        let total = self
+            .db
+            .collection::<Invite>("invites")
+            .count_documents(doc! {"inviter_id": inviter_id}, None)
+            .await
+            .map_err(|e| Error::new("failed to query invites").with_cause(e))?;
+        let mut invites = self
+            .db
+            .collection::<Invite>("invites")
+            .find(
+                doc! {"inviter_id": inviter_id},
+                FindOptions::builder()
+                    .projection(Invite::projection())
+                    .sort(doc! {"created_at": -1})
+                    .skip(pagination.skip as u64)
+                    .limit(pagination.limit)
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to query invites").with_cause(e))?
+            .try_collect::<Vec<Invite>>()
+            .await
+            .map_err(|e| Error::new("failed to query invites").with_cause(e))?;
+        for invite in &mut invites {
+            self.decrypt_field(&mut invite.phone)?;
+        }
+        Ok((invites, total as i64))
+    }
+
+    async fn open_invite(&self, code_hash: &str) -> Result<Option<Invite>, Error> {
+        self.update_one(
+            "invites",
+            doc! {"code_hash": code_hash, "opened_at": null},
+            doc! {"$set": {"opened_at": Utc::now()}},
+            None,
+        )
+        .await
+        .map_err(|e| Error::new("failed to open invite").with_cause(e))?;
+        let invite = self
+            .db
+            .collection::<Invite>("invites")
+            .find_one(
+                doc! {"code_hash": code_hash},
+                FindOneOptions::builder()
+                    .projection(Invite::projection())
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to open invite").with_cause(e))?;
+        let Some(mut invite) = invite else {
+            return Ok(None);
+        };
+        self.decrypt_field(&mut invite.phone)?;
+        Ok(Some(invite))
+    }
+
+    async fn convert_invites(&self, phone: &str, user_id: &str) -> Result<u64, Error> {
+        self.update_many(
+            "invites",
+            doc! {"phone": self.encrypted_field_filter(phone)?, "converted_at": null},
+            doc! {"$set": {"converted_user_id": user_id, "converted_at": Utc::now()}},
+        )
+        .await
+        .map_err(|e| Error::new("failed to convert invites").with_cause(e))
+        .map(|res| res.modified_count)
+    }
+
+    async fn invite_conversion(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<InviteConversion>, Error> {
+        self.aggregate_all(
+            "invites",
+            vec![
+                doc! {"$match": {"created_at": {"$gte": from, "$lt": to}}},
+                doc! {"$group": {
+                    "_id": "$kind",
+                    "sent": {"$sum": 1},
+                    "opened": {"$sum": {"$cond": [{"$eq": [{"$ifNull": ["$opened_at", null]}, null]}, 0, 1]}},
+                    "converted": {"$sum": {"$cond": [{"$eq": [{"$ifNull": ["$converted_at", null]}, null]}, 0, 1]}},
+                }},
+                doc! {"$project": {
+                    "_id": 0,
+                    "kind": "$_id",
+                    "sent": {"$toLong": "$sent"},
+                    "opened": {"$toLong": "$opened"},
+                    "converted": {"$toLong": "$converted"},
+                }},
+                doc! {"$sort": {"kind": 1}},
+            ],
+        )
+        .await?
+        .into_iter()
+        .map(|d| {
+            from_document::<InviteConversion>(d)
+                .map_err(|e| Error::new("failed to parse invite conversion").with_cause(e))
+        })
+        .collect()
+    }
+
+    async fn create_short_link(&self, create: ShortLinkCreate) -> Result<String, Error> {
+        self.insert_one("short_links", Document::from_model(create))
+            .await
+            .map_err(|e| write_error(e, "failed to create short link"))?
+            .inserted_id
+            .as_object_id()
+            .ok_or(Error::new("failed to create short link").with_cause("invalid inserted id"))
+            .map(|id| id.to_string())
+    }
+
+    async fn get_short_link(&self, code: &str) -> Result<Option<ShortLink>, Error> {
+        self.db
+            .collection::<ShortLink>("short_links")
+            .find_one(
+                doc! {"code": code, "expires_at": {"$gt": Utc::now()}},
+                FindOneOptions::builder()
+                    .projection(ShortLink::projection())
+                    .build(),
+            )
+            .await
+            .map_err(|e| Error::new("failed to get short link").with_cause(e))
+    }
+
+    // ping经由连接池获取连接, 连接池耗尽或无法选出服务器时在超时后返回错误
+    async fn self_check(&self) -> Result<(), Error> {
+        self.db
+            .run_command(doc! {"ping": 1}, None)
+            .await
+            .map_err(|e| Error::new("mongodb self check failed").with_cause(e))?;
+        Ok(())
+    }
+}
+
+// 只含时间戳的ObjectId, 用于按创建时间比较
+fn object_id_prefix(time: DateTime<Utc>) -> [u8; 12] {
+    let mut bytes = [0; 12];
+    bytes[..4].copy_from_slice(&(time.timestamp() as u32).to_be_bytes());
+    bytes
+}
+
+// explain的输出结构随查询类型和服务端版本变化, 因此递归查找所需字段
+fn find_document<'a>(d: &'a Document, key: &str) -> Option<&'a Document> {
+    if let Ok(found) = d.get_document(key) {
+        return Some(found);
+    }
+    d.values().find_map(|v| match v {
+        Bson::Document(sub) => find_document(sub, key),
+        Bson::Array(arr) => arr
+            .iter()
+            .filter_map(Bson::as_document)
+            .find_map(|sub| find_document(sub, key)),
+        _ => None,
+    })
+}
+
+fn collect_plan_stages(plan: &Document, stages: &mut Vec<String>, indexes: &mut Vec<String>) {
+    if let Ok(stage) = plan.get_str("stage") {
+        stages.push(stage.to_owned());
+    }
+    if let Ok(index) = plan.get_str("indexName") {
+        indexes.push(index.to_owned());
+    }
+    if let Ok(query_plan) = plan.get_document("queryPlan") {
+        collect_plan_stages(query_plan, stages, indexes);
+    }
+    if let Ok(input) = plan.get_document("inputStage") {
+        collect_plan_stages(input, stages, indexes);
+    }
+    if let Ok(inputs) = plan.get_array("inputStages") {
+        for input in inputs.iter().filter_map(Bson::as_document) {
+            collect_plan_stages(input, stages, indexes);
+        }
+    }
+}
+
+fn get_number(d: &Document, key: &str) -> i64 {
+    match d.get(key) {
+        Some(Bson::Int32(n)) => *n as i64,
+        Some(Bson::Int64(n)) => *n,
+        Some(Bson::Double(n)) => *n as i64,
+        _ => 0,
+    }
+}
+
+fn progress_entry<'a>(
+    progress: &'a mut HashMap<String, AchievementProgress>,
+    d: &Document,
+) -> &'a mut AchievementProgress {
+    let user_id = d.get_str("_id").unwrap_or_default().to_owned();
+    progress
+        .entry(user_id.clone())
+        .or_insert_with(|| AchievementProgress {
+            user_id,
+            ..Default::default()
+        })
+}
+
+// 为每个遛狗请求关联轨迹点并按顺序累加相邻点距离, 结果写入distance字段
+fn walk_distance_stages() -> Vec<Document> {
+    vec![
+        doc! {
+            "$lookup": {
+                "from": "walking_locations",
+                "let": { "request_id": { "$toString": "$_id" } },
+                "pipeline": [
+                    { "$match": { "$expr": { "$eq": ["$walk_request_id", "$$request_id"] } } },
+                    { "$sort": { "created_at": 1 } },
+                    { "$project": { "_id": 0, "longitude": 1, "latitude": 1 } },
+                ],
+                "as": "locations",
+            }
+        },
+        doc! {
+            "$addFields": {
+                "distance": {
+                    "$reduce": {
+                        "input": { "$range": [1, { "$size": "$locations" }] },
+                        "initialValue": 0.0,
+                        "in": { "$add": ["$$value", haversine_expression(
+                            doc! { "$arrayElemAt": ["$locations", { "$subtract": ["$$this", 1] }] },
+                            doc! { "$arrayElemAt": ["$locations", "$$this"] },
+                        )] },
+                    }
+                }
+            }
+        },
+    ]
+}
+
+// 两个{longitude, latitude}点之间的球面距离(米), 与core::geo::haversine_distance算法一致
+fn haversine_expression(from: Document, to: Document) -> Document {
+    doc! {
+        "$let": {
+            "vars": { "from": from, "to": to },
+            "in": {
+                "$let": {
+                    "vars": {
+                        "from_lat": { "$degreesToRadians": "$$from.latitude" },
+                        "to_lat": { "$degreesToRadians": "$$to.latitude" },
+                        "d_lat": { "$degreesToRadians": { "$subtract": ["$$to.latitude", "$$from.latitude"] } },
+                        "d_lng": { "$degreesToRadians": { "$subtract": ["$$to.longitude", "$$from.longitude"] } },
+                    },
+                    "in": {
+                        "$multiply": [2.0, EARTH_RADIUS, { "$asin": { "$sqrt": { "$add": [
+                            { "$pow": [{ "$sin": { "$divide": ["$$d_lat", 2] } }, 2] },
+                            { "$multiply": [
+                                { "$cos": "$$from_lat" },
+                                { "$cos": "$$to_lat" },
+                                { "$pow": [{ "$sin": { "$divide": ["$$d_lng", 2] } }, 2] },
+                            ] },
+                        ] } } }]
+                    }
+                }
+            }
+        }
+    }
+}
+
+// #[cfg(test)]
+// mod test {
+
+//     use super::*;
+//     use mongodb::Client;
+
+//     #[tokio::test]
+//     async fn test_create_breed() {
+//         let client = Client::with_uri_str("mongodb://localhost:27017").await.expect("Failed to connect to MongoDB");
+//         let db = client.database("test");
+//         let repo = MongoDB::new(db);
+//         let id = repo.create_breed(BreedCreate { name: "金毛".to_owned() }).await.expect("Failed to create breed");
+//         println!("{}", id);
+//     }
+
+//     #[tokio::test]
+//     async fn delete_breeds() {
+//         let client = Client::with_uri_str("mongodb://localhost:27017").await.expect("Failed to connect to MongoDB");
+//         let db = client.database("test");
+//         let repo = MongoDB::new(db);
+//         let id = repo.create_breed(BreedCreate { name: "金毛".to_owned() }).await.expect("Failed to create breed");
+//         repo.create_breed(BreedCreate { name: "拉布拉多".to_owned() }).await.expect("Failed to create breed");
+//         let deleted = repo.delete_breeds(BreedQuery { id_eq: Some(id) }).await.expect("Failed to delete breeds");
+//         assert!(deleted == 1);
+//         repo.delete_breeds(BreedQuery { id_eq: None }).await.expect("Failed to delete breeds");
+//     }
+
+//     #[tokio::test]
+//     async fn query_breeds() {
+//         let client = Client::with_uri_str("mongodb://localhost:27017").await.expect("Failed to connect to MongoDB");
+//         let db = client.database("test");
+//         let repo = MongoDB::new(db);
+//         repo.create_breed(BreedCreate { name: "金毛".to_owned() }).await.expect("Failed to create breed");
+//         repo.create_breed(BreedCreate { name: "拉布拉多".to_owned() }).await.expect("Failed to create breed");
+//         let (breeds, total) = repo
+//             .query_breeds(BreedQuery { id_eq: None }, Some(Pagination { page: 1, size: 1 }))
+//             .await
+//             .expect("Failed to query breeds");
+//         println!("breeds: {:?}, total: {}", breeds, total);
+//     }
+// }
+
+use mongodb::error::{ErrorKind as MongoErrorKind, WriteFailure};
+use mongodb::options::{FindOneAndUpdateOptions, IndexOptions, UpdateOptions};
+use mongodb::results::{DeleteResult, InsertOneResult, UpdateResult};
+use serde::de::DeserializeOwned;
+
+use little_walk_core::entities::AuditLog;
+use little_walk_core::entities::{Partner, PartnerApiKey, PartnerConsent, PartnerScope, PartnerUsage};
+use little_walk_core::entities::LocationAccess;
+use little_walk_core::entities::NeighborhoodStats;
+use little_walk_core::entities::Poi;
+use little_walk_core::entities::{Invite, InviteConversion};
+use little_walk_core::entities::OperationsSnapshot;
+use little_walk_core::entities::PurgedCounts;
+use little_walk_core::entities::PaymentAttemptStats;
+use little_walk_core::entities::Session;
+use little_walk_core::entities::{OwnerReputationStats, ReviewKind, ReviewState};
+use little_walk_core::entities::{AccountDeletion, AccountMerge, MergedReference};
+use little_walk_core::entities::{Achievement, AchievementKind, AchievementProgress};
+use little_walk_core::entities::{ActionToken, SensitiveAction};
+use little_walk_core::entities::{Block, Favorite, Owner, RankedWalker, Report, ReportStatus, Review};
+use little_walk_core::entities::{CancellationPenalty, ImageSize, UploadVariant};
+use little_walk_core::entities::{ChatMessage, ChatReadMarker, MeetAndGreet, MeetAndGreetStatus};
+use little_walk_core::entities::{DirectUpload, DirectUploadStatus};
+use little_walk_core::entities::{HelpArticle, Otp, OtpPurpose, PasswordResetToken};
+use little_walk_core::entities::{LedgerEntry, LedgerEntryKind, Withdrawal, WithdrawalStatus};
+use little_walk_core::entities::{DeviceToken, Email, Notification, NotificationSettings};
+use little_walk_core::entities::Translation;
+use little_walk_core::translation::Language;
+use little_walk_core::entities::{RouteAssessment, RoutePreference, WalkingLocation};
+use little_walk_core::entities::{OAuthAccount, OAuthLinkToken, OAuthProviderKind};
+use little_walk_core::entities::PhoneBinding;
+use little_walk_core::entities::{RefundRequest, RefundStatus};
+use little_walk_core::entities::{PayoutAccount, PayoutAccountStatus};
+use little_walk_core::entities::{RefreshToken, VerificationStatus, WalkRequest, Walker, WalkerStats};
+use little_walk_core::entities::ShortLink;
+use little_walk_core::entities::{IntegrityIssue, IntegrityIssueKind};
+use little_walk_core::entities::{Ticket, TicketStatus};
+use little_walk_core::geo::{bounding_box, haversine_distance, EARTH_RADIUS};
+use crate::metrics::GEO_NEAR_FALLBACKS;
+use little_walk_core::repository::ActionTokenCreate;
+use little_walk_core::repository::DirectUploadCreate;
+use little_walk_core::repository::{ChatMessageCreate, ChatReadMarkerUpsert, MeetAndGreetCreate, MeetAndGreetQuery, MeetAndGreetUpdate};
+use little_walk_core::repository::NotificationCreate;
+use little_walk_core::repository::{DeviceTokenQuery, DeviceTokenUpsert, EmailCreate, NotificationSettingsUpsert};
+use little_walk_core::repository::TranslationUpsert;
+use little_walk_core::repository::PasswordResetTokenCreate;
+use little_walk_core::repository::PaymentAttemptCreate;
+use little_walk_core::repository::SessionQuery;
+use little_walk_core::repository::ShortLinkCreate;
+use little_walk_core::repository::{AuditLogCreate, AuditLogQuery};
+use little_walk_core::repository::{InviteCreate, InviteQuery};
+use little_walk_core::repository::{PoiCreate, PoiQuery};
+use little_walk_core::repository::{AvailableWalkerQuery, NeighborhoodStatsQuery, PartnerApiKeyCreate, PartnerConsentQuery, PartnerCreate, PartnerUpdate};
+use little_walk_core::repository::{
+    WalkerSearch, COMPLETED_WALKS_HALF_SCORE, COMPLETED_WALKS_WEIGHT, DISTANCE_WEIGHT,
+    RATING_WEIGHT,
+};
+use little_walk_core::repository::RevokedAccessTokenCreate;
+use little_walk_core::repository::{
+    AccountDeletionCreate, AccountMergeCreate, OtpCreate, RefreshTokenCreate,
+};
+use little_walk_core::repository::{BlockQuery, FavoriteQuery, ReportCreate, ReportQuery, ReportUpdate};
+use little_walk_core::repository::{CancellationPenaltyCreate, UploadVariantCreate};
+use little_walk_core::repository::{HelpArticleCreate, HelpArticleQuery};
+use little_walk_core::repository::{LocationAccessCreate, LocationAccessQuery};
+use little_walk_core::repository::{OAuthAccountCreate, OAuthLinkTokenCreate, PayoutAccountUpsert};
+use little_walk_core::repository::PhoneBindingUpsert;
+use little_walk_core::repository::{RefundRequestCreate, RefundRequestQuery, RefundReview};
+use little_walk_core::repository::{Cursor, Order, Page, Pagination, SortBy, WalkingLocationCreate};
+use little_walk_core::repository::{OwnerUpdate, QueryPlan, QueryTemplate, ReviewCreate, ReviewQuery};
+use little_walk_core::repository::{TicketCreate, TicketMessageCreate, TicketQuery, TicketUpdate};
+use little_walk_core::repository::{UpdateField, WalkRequestCreate, WalkRequestQuery, WalkRequestUpdate};
+use little_walk_core::repository::{WalkerQuery, WalkerUpdate, WalkerVerificationSubmit};
+use little_walk_core::repository::{WithdrawalCreate, WithdrawalQuery, WithdrawalUpdate};
+use crate::field_cipher::FieldCipher;
+use futures::lock::Mutex;
+use futures::StreamExt;
+use mongodb::{ClientSession, IndexModel};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+impl Projection for WalkRequest {
+    fn projection() -> Document {
+        doc! {
+            "id": {"$toString": "$_id"},
+            "dogs": Dog::projection(),
+            "should_start_after": {"$dateToString": {"date":"$should_start_after", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "should_start_before": {"$dateToString": {"date":"$should_start_before", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "should_end_after": {"$dateToString": {"date":"$should_end_after", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "should_end_before": {"$dateToString": {"date":"$should_end_before", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "longitude": { "$arrayElemAt": [ "$location.coordinates", 0]},
+            "latitude": { "$arrayElemAt": [ "$location.coordinates", 1]},
+            "distance": "$distance",
+            "canceled_at": {"$dateToString": {"date":"$canceled_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "accepted_by": "$accepted_by",
+            "accepted_at": {"$dateToString": {"date":"$accepted_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "started_at": {"$dateToString": {"date":"$started_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "finished_at": {"$dateToString": {"date":"$finished_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "status": {
+                "$switch": {
+                    "branches": [
+                        {"case": {"$ne": [{"$ifNull": ["$canceled_at", null]}, null]}, "then": "Canceled" },
+                        {"case": {"$ne": [{"$ifNull": ["$accepted_at", null]}, null]}, "then": "Accepted" },
+                        {"case": {"$ne": [{"$ifNull": ["$started_at", null]}, null]}, "then": "Started" },
+                        {"case": {"$ne": [{"$ifNull": ["$finished_at", null]}, null]}, "then": "Finished" },
+                    ],
+                    "default": "Waiting"
+                }
+            },
+            "acceptances": "$acceptances",
+            "priority_walkers": "$priority_walkers",
+            "priority_until": {"$dateToString": {"date":"$priority_until", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "notify_walker_nearby": {"$ifNull": ["$notify_walker_nearby", false]},
+            "walker_nearby_notified_at": {"$dateToString": {"date":"$walker_nearby_notified_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "route_preference": "$route_preference",
+            "route_assessment": "$route_assessment",
+            "deleted_at": {"$dateToString": {"date":"$deleted_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "partner_id": 1,
+            "version": 1,
+            "created_by": 1,
+            "created_at": {"$dateToString": {"date":"$created_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "updated_at": {"$dateToString": {"date":"$updated_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+        }
+    }
+}
+
+impl TryFromModel<WalkRequestQuery> for Document {
+    type Error = Error;
+    fn try_from_model(value: WalkRequestQuery) -> Result<Self, Self::Error> {
+        let mut q = doc! {};
+        if let Some(id) = value.id {
+            q.insert("_id", parse_object_id(&id)?);
+        }
+        if let Some(ids) = value.dog_ids_includes_any {
+            q.insert("dogs.id", doc! {"$elemMatch": {"$in": ids }});
+        }
+        if let Some(ids) = value.dog_ids_includes_all {
+            q.insert("dogs.id", doc! {"$all": ids });
+        }
+        if let Some(accepted_by) = value.accepted_by {
+            q.insert("accepted_by", accepted_by);
+        }
+        if let Some(accepted_by_neq) = value.accepted_by_neq {
+            q.insert("accepted_by", doc! {"$ne": accepted_by_neq });
+        }
+        if let Some(accepted_by_is_null) = value.accepted_by_is_null {
+            if accepted_by_is_null {
+                q.insert("accepted_by", doc! {"$eq": null});
+            } else {
+                q.insert("accepted_by", doc! {"$neq": null});
+            }
+        }
+        if let Some(acceptances_includes_all) = value.acceptances_includes_all {
+            q.insert("acceptances", doc! {"$all": acceptances_includes_all });
+        }
+        if let Some(acceptances_includes_any) = value.acceptances_includes_any {
+            q.insert(
+                "acceptances",
+                doc! {"$elemMatch": {"$in": acceptances_includes_any }},
+            );
+        }
+        if let Some(created_by) = value.created_by {
+            q.insert("created_by", created_by);
+        }
+        if let Some(created_by_nin) = value.created_by_nin {
+            if let Ok(created_by) = q.get_str("created_by") {
+                let created_by = created_by.to_owned();
+                q.insert(
+                    "created_by",
+                    doc! {"$eq": created_by, "$nin": created_by_nin},
+                );
+            } else {
+                q.insert("created_by", doc! {"$nin": created_by_nin});
+            }
+        }
+        if let Some(started_at_is_null) = value.started_at_is_null {
+            if started_at_is_null {
+                q.insert("started_at", doc! {"$eq": null});
+            } else {
+                q.insert("started_at", doc! {"$ne": null});
+            }
+        }
+        if let Some(finished_at_is_null) = value.finished_at_is_null {
+            if finished_at_is_null {
+                q.insert("finished_at", doc! {"$eq": null});
+            } else {
+                q.insert("finished_at", doc! {"$ne": null});
+            }
+        }
+        if let Some(canceled_at_is_null) = value.canceled_at_is_null {
+            if canceled_at_is_null {
+                q.insert("canceled_at", doc! {"$eq": null});
+            } else {
+                q.insert("canceled_at", doc! {"$ne": null});
+            }
+        }
+        let mut and = Vec::new();
+        if let Some(open_to) = value.open_to {
+            and.push(doc! {"$or": [
+                {"priority_until": {"$eq": null}},
+                {"priority_until": {"$lte": Utc::now()}},
+                {"priority_walkers": open_to},
+            ]});
+        }
+        if let Some(involves) = value.involves {
+            and.push(doc! {"$or": [
+                {"created_by": &involves},
+                {"accepted_by": &involves},
+                {"acceptances": &involves},
+            ]});
+        }
+        if !and.is_empty() {
+            q.insert("$and", and);
+        }
+        if let Some(updated_after) = value.updated_after {
+            q.insert("updated_at", doc! {"$gt": updated_after});
+        }
+        if !value.include_deleted {
+            q.insert("deleted_at", Bson::Null);
+        }
+        if let Some(nearby) = value.nearby {
+            if nearby.len() != 3 {
+                return Err(Error::new("Invalid nearby query, expect [f64;3]"));
+            }
+            return Ok(doc! {
+                "$geoNear": {
+                    "near": { "type": "Point", "coordinates": [nearby[0], nearby[1]] },
+                    "distanceField": "distance",
+                    "maxDistance": nearby[2],
+                    "spherical": true,
+                    "query": q,
+                    "includeLocs": "location",
+                }
+            });
+        }
+        Ok(q)
+    }
+}
+
+impl FromModel<WalkRequestUpdate> for Document {
+    fn from_model(update: WalkRequestUpdate) -> Self {
+        let mut set = doc! {};
+        let mut unset = doc! {};
+        if let Some(dogs) = update.dogs {
+            set.insert("dogs", dogs.into_iter().map(Bson::from_model).collect::<Vec<Bson>>());
+        }
+        apply_update_field(&mut set, &mut unset, "accepted_by", update.accepted_by);
+        apply_update_field(&mut set, &mut unset, "accepted_at", update.accepted_at);
+        if let Some(canceled_at) = update.canceled_at {
+            set.insert("canceled_at", canceled_at);
+        }
+        if let Some(latitude) = update.latitude {
+            set.insert("latitude", latitude);
+        }
+        if let Some(longitude) = update.longitude {
+            set.insert("longitude", longitude);
+        }
+        if let Some(should_start_after) = update.should_start_after {
+            set.insert("should_start_after", should_start_after);
+        }
+        if let Some(should_start_before) = update.should_start_before {
+            set.insert("should_start_before", should_start_before);
+        }
+        if let Some(should_end_before) = update.should_end_before {
+            set.insert("should_end_before", should_end_before);
+        }
+        if let Some(should_end_after) = update.should_end_after {
+            set.insert("should_end_after", should_end_after);
+        }
+        if let Some(add_to_acceptances) = update.add_to_acceptances {
+            set.insert("$addToSet", doc! {"acceptances": add_to_acceptances});
+        }
+        if let Some(started_at) = update.started_at {
+            set.insert("started_at", started_at);
+        }
+        if let Some(finished_at) = update.finished_at {
+            set.insert("finished_at", finished_at);
+        }
+        apply_update_field(
+            &mut set,
+            &mut unset,
+            "route_preference",
+            update.route_preference.map(Bson::from_model),
+        );
+        if let Some(route_assessment) = update.route_assessment {
+            set.insert("route_assessment", Bson::from_model(route_assessment));
+        }
+        if let Some(deleted_at) = update.deleted_at {
+            set.insert("deleted_at", deleted_at);
+        }
+        let mut pull = doc! {};
+        if let Some(remove_from_acceptances) = update.remove_from_acceptances {
+            pull.insert("acceptances", remove_from_acceptances);
+        }
+        doc! {"$set": set, "$unset": unset, "$pull": pull}
+    }
+}
+
+// Set写入$set, Unset写入$unset, Keep不修改
+fn apply_update_field<T: Into<Bson>>(set: &mut Document, unset: &mut Document, key: &str, field: UpdateField<T>) {
+    match field {
+        UpdateField::Keep => {}
+        UpdateField::Set(value) => {
+            set.insert(key, value);
+        }
+        UpdateField::Unset => {
+            unset.insert(key, "");
+        }
+    }
+}
+
+impl FromModel<WalkRequestCreate> for Document {
+    fn from_model(value: WalkRequestCreate) -> Self {
+        doc! {
+            "dogs": value.dogs.into_iter().map(Bson::from_model).collect::<Vec<Bson>>(),
+            "should_start_after": value.should_start_after,
+            "should_start_before": value.should_start_before,
+            "should_end_before": value.should_end_before,
+            "should_end_after": value.should_end_after,
+            "location": { "type": "Point", "coordinates": [value.longitude, value.latitude] },
+            "created_by": value.created_by,
+            "priority_walkers": value.priority_walkers,
+            "priority_until": value.priority_until,
+            "notify_walker_nearby": value.notify_walker_nearby,
+            "route_preference": value.route_preference.map(Bson::from_model),
+            "partner_id": value.partner_id,
+        }
+    }
+}
+
+impl FromModel<RoutePreference> for Bson {
+    fn from_model(value: RoutePreference) -> Self {
+        Bson::Document(to_document(&value).unwrap())
+    }
+}
+
+impl FromModel<RouteAssessment> for Bson {
+    fn from_model(value: RouteAssessment) -> Self {
+        Bson::Document(to_document(&value).unwrap())
+    }
+}
+
+impl<'a> FromModel<WalkingLocationCreate<'a>> for Document {
+    fn from_model(value: WalkingLocationCreate) -> Self {
+        doc! {
+            "walk_request_id": value.walk_request_id,
+            "longitude": value.longitude,
+            "latitude": value.latitude,
+        }
+    }
+}
+
+impl Projection for WalkingLocation {
+    fn projection() -> Document {
+        doc! {
+            "id": {"$toString": "$_id"},
+            "request_id": "$walk_request_id",
+            "longitude": 1,
+            "latitude": 1,
+            "created_at": {"$dateToString": {"date":"$created_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+        }
+    }
+}
+
+impl FromModel<PoiCreate> for Document {
+    fn from_model(value: PoiCreate) -> Self {
+        doc! {
+            "name": value.name,
+            "location": { "type": "Point", "coordinates": [value.longitude, value.latitude] },
+            "radius": value.radius,
+            "boundary": value.boundary.iter().map(|p| doc! {"longitude": p.longitude, "latitude": p.latitude}).collect::<Vec<Document>>(),
+        }
+    }
+}
+
+impl Projection for Poi {
+    fn projection() -> Document {
+        doc! {
+            "id": {"$toString": "$_id"},
+            "name": 1,
+            "longitude": { "$arrayElemAt": [ "$location.coordinates", 0]},
+            "latitude": { "$arrayElemAt": [ "$location.coordinates", 1]},
+            "radius": 1,
+            "boundary": {"$ifNull": ["$boundary", []]},
+        }
+    }
+}
+
+impl Projection for Walker {
+    fn projection() -> Document {
+        doc! {
+            "id": {"$toString": "$_id"},
+            "user_id": 1,
+            "verification_status": {"$ifNull": ["$verification_status", VerificationStatus::Unverified.to_string()]},
+            "id_document_ids": {"$ifNull": ["$id_document_ids", []]},
+            "rejected_reason": "$rejected_reason",
+            "longitude": { "$arrayElemAt": [ "$location.coordinates", 0]},
+            "latitude": { "$arrayElemAt": [ "$location.coordinates", 1]},
+            "submitted_at": {"$dateToString": {"date":"$submitted_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "reviewed_at": {"$dateToString": {"date":"$reviewed_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "created_at": {"$dateToString": {"date":"$created_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "updated_at": {"$dateToString": {"date":"$updated_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+        }
+    }
+}
+
+impl FromModel<WalkerQuery> for Document {
+    fn from_model(value: WalkerQuery) -> Self {
+        let mut q = doc! {};
+        if let Some(user_id) = value.user_id {
+            q.insert("user_id", user_id);
+        }
+        if let Some(verification_status) = value.verification_status {
+            q.insert("verification_status", verification_status.to_string());
+        }
+        q
+    }
+}
+
+impl FromModel<WalkerUpdate> for Document {
+    fn from_model(update: WalkerUpdate) -> Self {
+        let mut set = doc! {};
+        if let Some(verification_status) = update.verification_status {
+            set.insert("verification_status", verification_status.to_string());
+        }
+        if let Some(rejected_reason) = update.rejected_reason {
+            set.insert("rejected_reason", rejected_reason);
+        }
+        if let Some(reviewed_at) = update.reviewed_at {
+            set.insert("reviewed_at", reviewed_at);
+        }
+        let mut unset = doc! {};
+        if update.unset_rejected_reason {
+            unset.insert("rejected_reason", "");
+        }
+        if unset.is_empty() {
+            return doc! {"$set": set};
+        }
+        doc! {"$set": set, "$unset": unset}
+    }
+}
+
+impl Projection for Review {
+    fn projection() -> Document {
+        doc! {
+            "id": {"$toString": "$_id"},
+            "walk_request_id": 1,
+            "walker_id": 1,
+            "owner_id": 1,
+            "kind": {"$ifNull": ["$kind", ReviewKind::OwnerToWalker.to_string()]},
+            "rating": 1,
+            "content": 1,
+            "photo_ids": {"$ifNull": ["$photo_ids", []]},
+            "helpful_count": 1,
+            "state": {"$ifNull": ["$state", ReviewState::Revealed.to_string()]},
+            "revealed_at": {"$dateToString": {"date":"$revealed_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "created_at": {"$dateToString": {"date":"$created_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+        }
+    }
+}
+
+impl<'a> FromModel<ReviewCreate<'a>> for Document {
+    fn from_model(value: ReviewCreate) -> Self {
+        doc! {
+            "walk_request_id": value.walk_request_id,
+            "walker_id": value.walker_id,
+            "owner_id": value.owner_id,
+            "kind": value.kind.to_string(),
+            "rating": value.rating,
+            "content": value.content,
+            "photo_ids": value.photo_ids,
+            "state": ReviewState::Hidden.to_string(),
+        }
+    }
+}
+
+impl TryFromModel<ReviewQuery> for Document {
+    type Error = Error;
+    fn try_from_model(value: ReviewQuery) -> Result<Self, Self::Error> {
+        let mut q = doc! {};
+        if let Some(id) = value.id {
+            q.insert("_id", parse_object_id(&id)?);
+        }
+        if let Some(walk_request_id) = value.walk_request_id {
+            q.insert("walk_request_id", walk_request_id);
+        }
+        if let Some(walker_id) = value.walker_id {
+            q.insert("walker_id", walker_id);
+        }
+        if let Some(owner_id) = value.owner_id {
+            q.insert("owner_id", owner_id);
+        }
+        match value.kind {
+            Some(ReviewKind::OwnerToWalker) => q.extend(walker_reviews_filter()),
+            Some(ReviewKind::WalkerToOwner) => {
+                q.insert("kind", ReviewKind::WalkerToOwner.to_string());
+            }
+            None => {}
+        }
+        match value.state {
+            Some(ReviewState::Hidden) => {
+                q.insert("state", ReviewState::Hidden.to_string());
+            }
+            Some(ReviewState::Revealed) => {
+                q.insert("state", revealed_filter());
+            }
+            None => {}
+        }
+        Ok(q)
+    }
+}
+
+// 狗狗主人对遛狗人的评价, 早期的评价没有kind字段
+fn walker_reviews_filter() -> Document {
+    doc! {"kind": {"$ne": ReviewKind::WalkerToOwner.to_string()}}
+}
+
+// 早期的评价没有state字段, 视为已公开
+fn revealed_filter() -> Document {
+    doc! {"$ne": ReviewState::Hidden.to_string()}
+}
+
+// 评分统计只计入已公开的评价, 避免在互评公开前泄露对方的评分
+fn revealed_walker_reviews_filter() -> Document {
+    let mut filter = walker_reviews_filter();
+    filter.insert("state", revealed_filter());
+    filter
+}
+
+impl Projection for Owner {
+    fn projection() -> Document {
+        doc! {
+            "id": {"$toString": "$_id"},
+            "user_id": 1,
+            "nickname": "$nickname",
+            "avatar_id": "$avatar_id",
+            "emergency_contacts": {"$ifNull": ["$emergency_contacts", []]},
+            "created_at": {"$dateToString": {"date":"$created_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "updated_at": {"$dateToString": {"date":"$updated_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+        }
+    }
+}
+
+impl Projection for Block {
+    fn projection() -> Document {
+        doc! {
+            "id": {"$toString": "$_id"},
+            "owner_id": 1,
+            "walker_id": 1,
+            "created_at": {"$dateToString": {"date":"$created_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+        }
+    }
+}
+
+impl Projection for Achievement {
+    fn projection() -> Document {
+        doc! {
+            "id": {"$toString": "$_id"},
+            "user_id": 1,
+            "kind": 1,
+            "achieved_at": {"$dateToString": {"date":"$achieved_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+        }
+    }
+}
+
+impl Projection for Favorite {
+    fn projection() -> Document {
+        doc! {
+            "id": {"$toString": "$_id"},
+            "owner_id": 1,
+            "walker_id": 1,
+            "created_at": {"$dateToString": {"date":"$created_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+        }
+    }
+}
+
+impl Projection for Report {
+    fn projection() -> Document {
+        doc! {
+            "id": {"$toString": "$_id"},
+            "reporter_id": 1,
+            "target_user_id": 1,
+            "walk_request_id": "$walk_request_id",
+            "reason": 1,
+            "description": 1,
+            "status": 1,
+            "resolution_note": "$resolution_note",
+            "reviewed_at": {"$dateToString": {"date":"$reviewed_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "created_at": {"$dateToString": {"date":"$created_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+        }
+    }
+}
+
+impl FromModel<ReportCreate> for Document {
+    fn from_model(value: ReportCreate) -> Self {
+        doc! {
+            "reporter_id": value.reporter_id,
+            "target_user_id": value.target_user_id,
+            "walk_request_id": value.walk_request_id,
+            "reason": value.reason,
+            "description": value.description,
+            "status": ReportStatus::Pending.to_string(),
+        }
+    }
+}
+
+impl TryFromModel<ReportQuery> for Document {
+    type Error = Error;
+    fn try_from_model(value: ReportQuery) -> Result<Self, Self::Error> {
+        let mut q = doc! {};
+        if let Some(id) = value.id {
+            q.insert("_id", parse_object_id(&id)?);
+        }
+        if let Some(reporter_id) = value.reporter_id {
+            q.insert("reporter_id", reporter_id);
+        }
+        if let Some(status) = value.status {
+            q.insert("status", status.to_string());
+        }
+        Ok(q)
+    }
+}
+
+impl Projection for Ticket {
+    fn projection() -> Document {
+        doc! {
+            "id": {"$toString": "$_id"},
+            "user_id": 1,
+            "category": 1,
+            "subject": 1,
+            "status": 1,
+            "assignee_id": "$assignee_id",
+            "messages": {
+                "$map": {
+                    "input": {"$ifNull": ["$messages", []]},
+                    "as": "m",
+                    "in": {
+                        "author_id": "$$m.author_id",
+                        "from_staff": "$$m.from_staff",
+                        "content": "$$m.content",
+                        "attachment_ids": "$$m.attachment_ids",
+                        "created_at": {"$dateToString": {"date":"$$m.created_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+                    }
+                }
+            },
+            "created_at": {"$dateToString": {"date":"$created_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "updated_at": {"$dateToString": {"date":"$updated_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+        }
+    }
+}
+
+impl FromModel<TicketMessageCreate> for Document {
+    fn from_model(value: TicketMessageCreate) -> Self {
+        doc! {
+            "author_id": value.author_id,
+            "from_staff": value.from_staff,
+            "content": value.content,
+            "attachment_ids": value.attachment_ids,
+            "created_at": Utc::now(),
+        }
+    }
+}
+
+// 创建工单时的描述作为第一条消息保存
+impl FromModel<TicketCreate> for Document {
+    fn from_model(value: TicketCreate) -> Self {
+        doc! {
+            "user_id": value.user_id.clone(),
+            "category": value.category.to_string(),
+            "subject": value.subject,
+            "status": TicketStatus::Open.to_string(),
+            "assignee_id": Bson::Null,
+            "messages": [Document::from_model(TicketMessageCreate {
+                author_id: value.user_id,
+                from_staff: false,
+                content: value.content,
+                attachment_ids: value.attachment_ids,
+            })],
+        }
+    }
+}
+
+impl TryFromModel<TicketQuery> for Document {
+    type Error = Error;
+    fn try_from_model(value: TicketQuery) -> Result<Self, Self::Error> {
+        let mut q = doc! {};
+        if let Some(id) = value.id {
+            q.insert("_id", parse_object_id(&id)?);
+        }
+        if let Some(user_id) = value.user_id {
+            q.insert("user_id", user_id);
+        }
+        if let Some(status) = value.status {
+            q.insert("status", status.to_string());
+        }
+        if let Some(assignee_id) = value.assignee_id {
+            q.insert("assignee_id", assignee_id);
+        }
+        Ok(q)
+    }
+}
+
+impl Projection for HelpArticle {
+    fn projection() -> Document {
+        doc! {
+            "id": {"$toString": "$_id"},
+            "slug": 1,
+            "locale": 1,
+            "version": 1,
+            "title": 1,
+            "markdown": 1,
+            "author_id": 1,
+            "created_at": {"$dateToString": {"date":"$created_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+        }
+    }
+}
+
+impl FromModel<HelpArticleQuery> for Document {
+    fn from_model(value: HelpArticleQuery) -> Self {
+        let mut q = doc! {};
+        if let Some(slug) = value.slug {
+            q.insert("slug", slug);
+        }
+        if let Some(locale) = value.locale {
+            q.insert("locale", locale);
+        }
+        if let Some(version) = value.version {
+            q.insert("version", version);
+        }
+        q
+    }
+}
+
+impl Projection for OAuthAccount {
+    fn projection() -> Document {
+        doc! {
+            "id": {"$toString": "$_id"},
+            "provider": 1,
+            "subject": 1,
+            "user_id": 1,
+            "phone": 1,
+            "created_at": {"$dateToString": {"date":"$created_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+        }
+    }
+}
+
+impl Projection for PhoneBinding {
+    fn projection() -> Document {
+        doc! {
+            "id": {"$toString": "$_id"},
+            "user_id": 1,
+            "phone": 1,
+            "auth_phone": 1,
+            "created_at": {"$dateToString": {"date":"$created_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "updated_at": {"$dateToString": {"date":"$updated_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+        }
+    }
+}
+
+impl Projection for OAuthLinkToken {
+    fn projection() -> Document {
+        doc! {
+            "id": {"$toString": "$_id"},
+            "provider": 1,
+            "subject": 1,
+            "token_hash": 1,
+            "used": 1,
+            "expires_at": {"$dateToString": {"date":"$expires_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "created_at": {"$dateToString": {"date":"$created_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+        }
+    }
+}
+
+impl FromModel<OAuthLinkTokenCreate> for Document {
+    fn from_model(value: OAuthLinkTokenCreate) -> Self {
+        doc! {
+            "provider": value.provider.to_string(),
+            "subject": value.subject,
+            "token_hash": value.token_hash,
+            "used": false,
+            "expires_at": value.expires_at,
+        }
+    }
+}
+
+impl Projection for PayoutAccount {
+    fn projection() -> Document {
+        doc! {
+            "id": {"$toString": "$_id"},
+            "user_id": 1,
+            "method": 1,
+            "masked_holder_name": 1,
+            "masked_account_number": 1,
+            "bank_name": "$bank_name",
+            "provider_reference": 1,
+            "status": 1,
+            "failure_reason": "$failure_reason",
+            "verified_at": {"$dateToString": {"date":"$verified_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "created_at": {"$dateToString": {"date":"$created_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "updated_at": {"$dateToString": {"date":"$updated_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+        }
+    }
+}
+
+// 核验通过时记录核验时间, 其他状态清除核验时间
+fn payout_status_update(status: PayoutAccountStatus, failure_reason: Option<String>) -> Document {
+    let mut set = doc! {"status": status.to_string(), "failure_reason": failure_reason};
+    if status == PayoutAccountStatus::Verified {
+        set.insert("verified_at", Utc::now());
+        doc! {"$set": set}
+    } else {
+        doc! {"$set": set, "$unset": {"verified_at": ""}}
+    }
+}
+
+impl FromModel<PayoutAccountUpsert> for Document {
+    fn from_model(value: PayoutAccountUpsert) -> Self {
+        let mut update = payout_status_update(value.status, value.failure_reason);
+        let set = update.get_document_mut("$set").expect("missing $set");
+        set.insert("method", value.method.to_string());
+        set.insert("masked_holder_name", value.masked_holder_name);
+        set.insert("masked_account_number", value.masked_account_number);
+        set.insert("bank_name", value.bank_name);
+        set.insert("provider_reference", value.provider_reference);
+        update
+    }
+}
+
+impl Projection for RefreshToken {
+    fn projection() -> Document {
+        doc! {
+            "id": {"$toString": "$_id"},
+            "family_id": 1,
+            "user_id": 1,
+            "phone": 1,
+            "token_hash": 1,
+            "used": 1,
+            "revoked": 1,
+            "device_id": "$device_id",
+            "platform": "$platform",
+            "signed_in_at": {"$dateToString": {"date":{"$ifNull": ["$signed_in_at", "$created_at"]}, "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "expires_at": {"$dateToString": {"date":"$expires_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "created_at": {"$dateToString": {"date":"$created_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+        }
+    }
+}
+
+impl Projection for Otp {
+    fn projection() -> Document {
+        doc! {
+            "id": {"$toString": "$_id"},
+            "phone": 1,
+            "purpose": 1,
+            "code_hash": 1,
+            "used": 1,
+            "failed_attempts": {"$ifNull": ["$failed_attempts", 0]},
+            "expires_at": {"$dateToString": {"date":"$expires_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "created_at": {"$dateToString": {"date":"$created_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+        }
+    }
+}
+
+impl FromModel<OtpCreate> for Document {
+    fn from_model(value: OtpCreate) -> Self {
+        doc! {
+            "phone": value.phone,
+            "purpose": value.purpose.to_string(),
+            "code_hash": value.code_hash,
+            "used": false,
+            "failed_attempts": 0,
+            "expires_at": value.expires_at,
+        }
+    }
+}
+
+impl Projection for PasswordResetToken {
+    fn projection() -> Document {
+        doc! {
+            "id": {"$toString": "$_id"},
+            "phone": 1,
+            "token_hash": 1,
+            "used": 1,
+            "expires_at": {"$dateToString": {"date":"$expires_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "created_at": {"$dateToString": {"date":"$created_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+        }
+    }
+}
+
+impl FromModel<PasswordResetTokenCreate> for Document {
+    fn from_model(value: PasswordResetTokenCreate) -> Self {
+        doc! {
+            "phone": value.phone,
+            "token_hash": value.token_hash,
+            "used": false,
+            "expires_at": value.expires_at,
+        }
+    }
+}
+
+impl FromModel<RefreshTokenCreate> for Document {
+    fn from_model(value: RefreshTokenCreate) -> Self {
+        doc! {
+            "family_id": value.family_id,
+            "user_id": value.user_id,
+            "phone": value.phone,
+            "token_hash": value.token_hash,
+            "used": false,
+            "revoked": false,
+            "device_id": value.device.device_id,
+            "platform": value.device.platform,
+            "signed_in_at": value.signed_in_at,
+            "expires_at": value.expires_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Mongodb {
+    db: Database,
+}
+
+impl Mongodb {
+    pub fn new(db: Database) -> Self {
+        Mongodb { db }
+    }
+}
+
+fn balance_pipeline(user_id: &str) -> Vec<Document> {
+    vec![
+        doc! {"$match": {"user_id": user_id}},
+        doc! {"$group": {"_id": null, "balance": {"$sum": "$amount"}}},
+    ]
+}
+
+fn balance_of(doc: &Document) -> i64 {
+    match doc.get("balance") {
+        Some(Bson::Int64(v)) => *v,
+        Some(Bson::Int32(v)) => *v as i64,
+        _ => 0,
+    }
+}
+
+impl Projection for LedgerEntry {
+    fn projection() -> Document {
+        doc! {
+            "id": {"$toString": "$_id"},
+            "user_id": 1,
+            "kind": 1,
+            "amount": 1,
+            "withdrawal_id": "$withdrawal_id",
+            "walk_request_id": "$walk_request_id",
+            "created_at": {"$dateToString": {"date":"$created_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+        }
+    }
+}
+
+impl Projection for Withdrawal {
+    fn projection() -> Document {
+        doc! {
+            "id": {"$toString": "$_id"},
+            "user_id": 1,
+            "amount": 1,
+            "status": 1,
+            "destination_reference": 1,
+            "payout_reference": "$payout_reference",
+            "failure_reason": "$failure_reason",
+            "reviewed_by": "$reviewed_by",
+            "reviewed_at": {"$dateToString": {"date":"$reviewed_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "paid_at": {"$dateToString": {"date":"$paid_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "created_at": {"$dateToString": {"date":"$created_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "updated_at": {"$dateToString": {"date":"$updated_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+        }
+    }
+}
+
+impl Projection for RefundRequest {
+    fn projection() -> Document {
+        doc! {
+            "id": {"$toString": "$_id"},
+            "walk_request_id": 1,
+            "owner_id": 1,
+            "walker_id": 1,
+            "amount": 1,
+            "reason": 1,
+            "evidence_ids": 1,
+            "status": 1,
+            "approved_amount": "$approved_amount",
+            "walker_debit": "$walker_debit",
+            "reviewed_by": "$reviewed_by",
+            "review_note": "$review_note",
+            "reviewed_at": {"$dateToString": {"date":"$reviewed_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "created_at": {"$dateToString": {"date":"$created_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "updated_at": {"$dateToString": {"date":"$updated_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+        }
+    }
+}
+
+impl FromModel<RefundRequestCreate> for Document {
+    fn from_model(value: RefundRequestCreate) -> Self {
+        doc! {
+            "walk_request_id": value.walk_request_id,
+            "owner_id": value.owner_id,
+            "walker_id": value.walker_id,
+            "amount": value.amount,
+            "reason": value.reason,
+            "evidence_ids": value.evidence_ids,
+            "status": RefundStatus::Requested.to_string(),
+            "approved_amount": Bson::Null,
+            "walker_debit": Bson::Null,
+            "reviewed_by": Bson::Null,
+            "review_note": Bson::Null,
+        }
+    }
+}
+
+impl TryFromModel<RefundRequestQuery> for Document {
+    type Error = Error;
+    fn try_from_model(value: RefundRequestQuery) -> Result<Self, Self::Error> {
+        let mut q = doc! {};
+        if let Some(id) = value.id {
+            q.insert("_id", parse_object_id(&id)?);
+        }
+        if let Some(walk_request_id) = value.walk_request_id {
+            q.insert("walk_request_id", walk_request_id);
+        }
+        if let Some(owner_id) = value.owner_id {
+            q.insert("owner_id", owner_id);
+        }
+        if let Some(status) = value.status {
+            q.insert("status", status.to_string());
+        }
+        Ok(q)
+    }
+}
+
+// 审核时记录审核时间
+impl FromModel<RefundReview> for Document {
+    fn from_model(value: RefundReview) -> Self {
+        doc! {
+            "$set": {
+                "status": value.status.to_string(),
+                "approved_amount": value.approved_amount,
+                "walker_debit": value.walker_debit,
+                "reviewed_by": value.reviewed_by,
+                "review_note": value.review_note,
+                "reviewed_at": Utc::now(),
+            }
+        }
+    }
+}
+
+impl FromModel<WithdrawalCreate> for Document {
+    fn from_model(value: WithdrawalCreate) -> Self {
+        doc! {
+            "user_id": value.user_id,
+            "amount": value.amount,
+            "status": WithdrawalStatus::Requested.to_string(),
+            "destination_reference": value.destination_reference,
+            "payout_reference": Bson::Null,
+            "failure_reason": Bson::Null,
+            "reviewed_by": Bson::Null,
+        }
+    }
+}
+
+impl TryFromModel<WithdrawalQuery> for Document {
+    type Error = Error;
+    fn try_from_model(value: WithdrawalQuery) -> Result<Self, Self::Error> {
+        let mut q = doc! {};
+        if let Some(id) = value.id {
+            q.insert("_id", parse_object_id(&id)?);
+        }
+        if let Some(ids) = value.ids {
+            let ids = parse_object_ids(&ids)?;
+            q.insert("_id", doc! {"$in": ids});
+        }
+        if let Some(user_id) = value.user_id {
+            q.insert("user_id", user_id);
+        }
+        if let Some(status) = value.status {
+            q.insert("status", status.to_string());
+        }
+        if let Some(payout_reference) = value.payout_reference {
+            q.insert("payout_reference", payout_reference);
+        }
+        Ok(q)
+    }
+}
+
+// 审核时记录审核时间, 打款成功时记录到账时间
+impl FromModel<WithdrawalUpdate> for Document {
+    fn from_model(value: WithdrawalUpdate) -> Self {
+        let mut set = doc! {};
+        if let Some(status) = value.status {
+            set.insert("status", status.to_string());
+            if status == WithdrawalStatus::Paid {
+                set.insert("paid_at", Utc::now());
+            }
+        }
+        if let Some(payout_reference) = value.payout_reference {
+            set.insert("payout_reference", payout_reference);
+        }
+        if let Some(failure_reason) = value.failure_reason {
+            set.insert("failure_reason", failure_reason);
+        }
+        if let Some(reviewed_by) = value.reviewed_by {
+            set.insert("reviewed_by", reviewed_by);
+            set.insert("reviewed_at", Utc::now());
+        }
+        doc! {"$set": set}
+    }
+}
+
+impl Projection for CancellationPenalty {
+    fn projection() -> Document {
+        doc! {
+            "id": {"$toString": "$_id"},
+            "walk_request_id": 1,
+            "walker_id": 1,
+            "owner_id": 1,
+            "tier": 1,
+            "scheduled_start": {"$dateToString": {"date":"$scheduled_start", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "notice_minutes": 1,
+            "amount": 1,
+            "balance_after": 1,
+            "created_at": {"$dateToString": {"date":"$created_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+        }
+    }
+}
+
+impl Projection for MeetAndGreet {
+    fn projection() -> Document {
+        doc! {
+            "id": {"$toString": "$_id"},
+            "walk_request_id": 1,
+            "owner_id": 1,
+            "walker_id": 1,
+            "status": 1,
+            "starts_at": {"$dateToString": {"date":"$starts_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "ends_at": {"$dateToString": {"date":"$ends_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "longitude": 1,
+            "latitude": 1,
+            "note": "$note",
+            "accepted_at": {"$dateToString": {"date":"$accepted_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "completed_at": {"$dateToString": {"date":"$completed_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "canceled_by": "$canceled_by",
+            "canceled_at": {"$dateToString": {"date":"$canceled_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "created_at": {"$dateToString": {"date":"$created_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "updated_at": {"$dateToString": {"date":"$updated_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+        }
+    }
+}
+
+impl Projection for BreedCareTips {
+    fn projection() -> Document {
+        doc! {
+            "id": {"$toString": "$_id"},
+            "breed_id": 1,
+            "locale": 1,
+            "tips": 1,
+            "updated_by": 1,
+            "updated_at": {"$dateToString": {"date":"$updated_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+        }
+    }
+}
+
+impl Projection for ChatMessage {
+    fn projection() -> Document {
+        doc! {
+            "id": {"$toString": "$_id"},
+            "walk_request_id": 1,
+            "sender_id": 1,
+            "text": 1,
+            "image_ids": 1,
+            "created_at": {"$dateToString": {"date":"$created_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+        }
+    }
+}
+
+impl Projection for ChatReadMarker {
+    fn projection() -> Document {
+        doc! {
+            "walk_request_id": 1,
+            "user_id": 1,
+            "message_id": 1,
+            "message_created_at": {"$dateToString": {"date":"$message_created_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "updated_at": {"$dateToString": {"date":"$updated_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+        }
+    }
+}
+
+impl FromModel<ChatMessageCreate> for Document {
+    fn from_model(value: ChatMessageCreate) -> Self {
+        doc! {
+            "walk_request_id": value.walk_request_id,
+            "sender_id": value.sender_id,
+            "text": value.text,
+            "image_ids": value.image_ids,
+        }
+    }
+}
+
+impl FromModel<MeetAndGreetCreate> for Document {
+    fn from_model(value: MeetAndGreetCreate) -> Self {
+        doc! {
+            "walk_request_id": value.walk_request_id,
+            "owner_id": value.owner_id,
+            "walker_id": value.walker_id,
+            "status": MeetAndGreetStatus::Proposed.to_string(),
+            "starts_at": value.starts_at,
+            "ends_at": value.ends_at,
+            "longitude": value.longitude,
+            "latitude": value.latitude,
+            "note": value.note,
+        }
+    }
+}
+
+impl TryFromModel<MeetAndGreetQuery> for Document {
+    type Error = Error;
+    fn try_from_model(value: MeetAndGreetQuery) -> Result<Self, Self::Error> {
+        let mut q = doc! {};
+        if let Some(id) = value.id {
+            q.insert("_id", parse_object_id(&id)?);
+        }
+        if let Some(walk_request_id) = value.walk_request_id {
+            q.insert("walk_request_id", walk_request_id);
+        }
+        if let Some(walker_id) = value.walker_id {
+            q.insert("walker_id", walker_id);
+        }
+        if let Some(participants) = value.participants_any {
+            q.insert(
+                "$or",
+                vec![
+                    doc! {"owner_id": {"$in": &participants}},
+                    doc! {"walker_id": {"$in": &participants}},
+                ],
+            );
+        }
+        if let Some(status_in) = value.status_in {
+            q.insert(
+                "status",
+                doc! {"$in": status_in.iter().map(|s| s.to_string()).collect::<Vec<String>>()},
+            );
+        }
+        if let Some(starts_before) = value.starts_before {
+            q.insert("starts_at", doc! {"$lt": starts_before});
+        }
+        if let Some(ends_after) = value.ends_after {
+            q.insert("ends_at", doc! {"$gt": ends_after});
+        }
+        Ok(q)
+    }
+}
+
+impl FromModel<MeetAndGreetUpdate> for Document {
+    fn from_model(value: MeetAndGreetUpdate) -> Self {
+        let mut set = doc! {};
+        if let Some(status) = value.status {
+            set.insert("status", status.to_string());
+            match status {
+                MeetAndGreetStatus::Accepted => {
+                    set.insert("accepted_at", Utc::now());
+                }
+                MeetAndGreetStatus::Completed => {
+                    set.insert("completed_at", Utc::now());
+                }
+                MeetAndGreetStatus::Declined | MeetAndGreetStatus::Canceled => {
+                    set.insert("canceled_at", Utc::now());
+                }
+                MeetAndGreetStatus::Proposed => {}
+            }
+        }
+        if let Some(canceled_by) = value.canceled_by {
+            set.insert("canceled_by", canceled_by);
+        }
+        doc! {"$set": set}
+    }
+}
+
+impl Projection for DirectUpload {
+    fn projection() -> Document {
+        doc! {
+            "id": {"$toString": "$_id"},
+            "uploader": 1,
+            "key": 1,
+            "filename": 1,
+            "content_type": 1,
+            "size": 1,
+            "status": 1,
+            "expires_at": {"$dateToString": {"date":"$expires_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "confirmed_at": {"$dateToString": {"date":"$confirmed_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "created_at": {"$dateToString": {"date":"$created_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+        }
+    }
+}
+
+impl Projection for LocationAccess {
+    fn projection() -> Document {
+        doc! {
+            "id": {"$toString": "$_id"},
+            "accessor_id": 1,
+            "kind": 1,
+            "walk_request_id": "$walk_request_id",
+            "subject_ids": 1,
+            "created_at": {"$dateToString": {"date":"$created_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+        }
+    }
+}
+
+impl FromModel<LocationAccessQuery> for Document {
+    fn from_model(value: LocationAccessQuery) -> Self {
+        let mut q = doc! {};
+        if let Some(accessor_id) = value.accessor_id {
+            q.insert("accessor_id", accessor_id);
+        }
+        if let Some(subject_id) = value.subject_id {
+            q.insert("subject_ids", subject_id);
+        }
+        if let Some(walk_request_id) = value.walk_request_id {
+            q.insert("walk_request_id", walk_request_id);
+        }
+        if let Some(kind) = value.kind {
+            q.insert("kind", kind.to_string());
+        }
+        let mut created_at = doc! {};
+        if let Some(created_after) = value.created_after {
+            created_at.insert("$gte", created_after);
+        }
+        if let Some(created_before) = value.created_before {
+            created_at.insert("$lt", created_before);
+        }
+        if !created_at.is_empty() {
+            q.insert("created_at", created_at);
+        }
+        q
+    }
+}
+
+impl Projection for ActionToken {
+    fn projection() -> Document {
+        doc! {
+            "id": {"$toString": "$_id"},
+            "user_id": 1,
+            "action": 1,
+            "token_hash": 1,
+            "used": 1,
+            "expires_at": {"$dateToString": {"date":"$expires_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "created_at": {"$dateToString": {"date":"$created_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+        }
+    }
+}
+
+impl FromModel<ActionTokenCreate> for Document {
+    fn from_model(value: ActionTokenCreate) -> Self {
+        doc! {
+            "user_id": value.user_id,
+            "action": value.action.to_string(),
+            "token_hash": value.token_hash,
+            "used": false,
+            "expires_at": value.expires_at,
+        }
+    }
+}
+
+impl Projection for Notification {
+    fn projection() -> Document {
+        doc! {
+            "id": {"$toString": "$_id"},
+            "user_id": 1,
+            "kind": 1,
+            "walk_request_id": 1,
+            "content": 1,
+            "sms": {"$ifNull": ["$sms", false]},
+            "pushed_at": {"$dateToString": {"date":"$pushed_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "created_at": {"$dateToString": {"date":"$created_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+        }
+    }
+}
+
+impl Projection for DeviceToken {
+    fn projection() -> Document {
+        doc! {
+            "id": {"$toString": "$_id"},
+            "user_id": 1,
+            "platform": 1,
+            "token": 1,
+            "updated_at": {"$dateToString": {"date":"$updated_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+        }
+    }
+}
+
+impl Projection for NotificationSettings {
+    fn projection() -> Document {
+        doc! {
+            "user_id": 1,
+            "sms_enabled": {"$ifNull": ["$sms_enabled", false]},
+            "sms_phone": 1,
+            "email": 1,
+            "email_receipts": {"$ifNull": ["$email_receipts", false]},
+            "email_weekly_summary": {"$ifNull": ["$email_weekly_summary", false]},
+            "email_verification_decisions": {"$ifNull": ["$email_verification_decisions", false]},
+            "weekly_summary_sent_at": {"$dateToString": {"date":"$weekly_summary_sent_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "updated_at": {"$dateToString": {"date":"$updated_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+        }
+    }
+}
+
+impl Projection for Translation {
+    fn projection() -> Document {
+        doc! {
+            "source_id": 1,
+            "language": 1,
+            "source_hash": 1,
+            "text": 1,
+            "updated_at": {"$dateToString": {"date":"$updated_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+        }
+    }
+}
+
+impl Projection for Email {
+    fn projection() -> Document {
+        doc! {
+            "id": {"$toString": "$_id"},
+            "user_id": 1,
+            "kind": 1,
+            "to": 1,
+            "subject": 1,
+            "text": 1,
+            "html": 1,
+            "sent_at": {"$dateToString": {"date":"$sent_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "created_at": {"$dateToString": {"date":"$created_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+        }
+    }
+}
+
+impl FromModel<EmailCreate> for Document {
+    fn from_model(value: EmailCreate) -> Self {
+        doc! {
+            "user_id": value.user_id,
+            "kind": value.kind.to_string(),
+            "to": value.to,
+            "subject": value.subject,
+            "text": value.text,
+            "html": value.html,
+        }
+    }
+}
+
+impl FromModel<DeviceTokenQuery> for Document {
+    fn from_model(value: DeviceTokenQuery) -> Self {
+        let mut q = doc! {};
+        if let Some(user_id) = value.user_id {
+            q.insert("user_id", user_id);
+        }
+        if let Some(token) = value.token {
+            q.insert("token", token);
+        }
+        q
+    }
+}
+
+// 快照按WalkRequest的序列化结果保存, 读取时直接反序列化
+impl FromModel<AuditLogCreate> for Document {
+    fn from_model(value: AuditLogCreate) -> Self {
+        doc! {
+            "walk_request_id": value.walk_request_id,
+            "actor_id": value.actor_id,
+            "action": value.action.to_string(),
+            "before": value.before.map(Bson::from_model),
+            "after": Bson::from_model(value.after),
+        }
+    }
+}
+
+impl FromModel<WalkRequest> for Bson {
+    fn from_model(value: WalkRequest) -> Self {
+        Bson::Document(to_document(&value).unwrap())
+    }
+}
+
+impl Projection for AuditLog {
+    fn projection() -> Document {
+        doc! {
+            "id": {"$toString": "$_id"},
+            "walk_request_id": 1,
+            "actor_id": 1,
+            "action": 1,
+            "before": 1,
+            "after": 1,
+            "created_at": {"$dateToString": {"date":"$created_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+        }
+    }
+}
+
+impl FromModel<AuditLogQuery> for Document {
+    fn from_model(value: AuditLogQuery) -> Self {
+        let mut q = doc! {};
+        if let Some(walk_request_id) = value.walk_request_id {
+            q.insert("walk_request_id", walk_request_id);
+        }
+        if let Some(actor_id) = value.actor_id {
+            q.insert("actor_id", actor_id);
+        }
+        if let Some(action) = value.action {
+            q.insert("action", action.to_string());
+        }
+        let mut created_at = doc! {};
+        if let Some(created_after) = value.created_after {
+            created_at.insert("$gte", created_after);
+        }
+        if let Some(created_before) = value.created_before {
+            created_at.insert("$lt", created_before);
+        }
+        if !created_at.is_empty() {
+            q.insert("created_at", created_at);
+        }
+        q
+    }
+}
+
+impl FromModel<PartnerCreate> for Document {
+    fn from_model(value: PartnerCreate) -> Self {
+        doc! {
+            "name": value.name,
+            "kind": value.kind.to_string(),
+            "scopes": value.scopes.iter().map(PartnerScope::to_string).collect::<Vec<String>>(),
+            "rate_limit": value.rate_limit,
+            "disabled": false,
+        }
+    }
+}
+
+impl FromModel<PartnerUpdate> for Document {
+    fn from_model(value: PartnerUpdate) -> Self {
+        let mut set = doc! {};
+        if let Some(name) = value.name {
+            set.insert("name", name);
+        }
+        if let Some(scopes) = value.scopes {
+            set.insert(
+                "scopes",
+                scopes.iter().map(PartnerScope::to_string).collect::<Vec<String>>(),
+            );
+        }
+        if let Some(rate_limit) = value.rate_limit {
+            set.insert("rate_limit", rate_limit);
+        }
+        if let Some(disabled) = value.disabled {
+            set.insert("disabled", disabled);
+        }
+        doc! {"$set": set}
+    }
+}
+
+impl Projection for Partner {
+    fn projection() -> Document {
+        doc! {
+            "id": {"$toString": "$_id"},
+            "name": 1,
+            "kind": 1,
+            "scopes": 1,
+            "rate_limit": 1,
+            "disabled": 1,
+            "created_at": {"$dateToString": {"date":"$created_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "updated_at": {"$dateToString": {"date":"$updated_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+        }
+    }
+}
+
+impl Projection for PartnerApiKey {
+    fn projection() -> Document {
+        doc! {
+            "id": {"$toString": "$_id"},
+            "partner_id": 1,
+            "prefix": 1,
+            "revoked_at": {"$dateToString": {"date":"$revoked_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "created_at": {"$dateToString": {"date":"$created_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+        }
+    }
+}
+
+impl FromModel<InviteCreate> for Document {
+    fn from_model(value: InviteCreate) -> Self {
+        doc! {
+            "inviter_id": value.inviter_id,
+            "phone": value.phone,
+            "kind": value.kind.to_string(),
+            "locale": value.locale,
+            "code_hash": value.code_hash,
+            "opened_at": null,
+            "converted_user_id": null,
+            "converted_at": null,
+        }
+    }
+}
+
+impl Projection for Invite {
+    fn projection() -> Document {
+        doc! {
+            "id": {"$toString": "$_id"},
+            "inviter_id": 1,
+            "phone": 1,
+            "kind": 1,
+            "locale": 1,
+            "opened_at": {"$dateToString": {"date":"$opened_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "converted_user_id": 1,
+            "converted_at": {"$dateToString": {"date":"$converted_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "created_at": {"$dateToString": {"date":"$created_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+        }
+    }
+}
+
+impl Projection for PartnerConsent {
+    fn projection() -> Document {
+        doc! {
+            "id": {"$toString": "$_id"},
+            "partner_id": 1,
+            "owner_id": 1,
+            "created_at": {"$dateToString": {"date":"$created_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+        }
+    }
+}
+
+impl FromModel<NotificationCreate> for Document {
+    fn from_model(value: NotificationCreate) -> Self {
+        doc! {
+            "user_id": value.user_id,
+            "kind": value.kind.to_string(),
+            "walk_request_id": value.walk_request_id,
+            "content": value.content,
+            "sms": value.sms,
+        }
+    }
+}
+
+impl FromModel<ShortLinkCreate> for Document {
+    fn from_model(value: ShortLinkCreate) -> Self {
+        doc! {
+            "code": value.code,
+            "kind": value.kind.to_string(),
+            "token": value.token,
+            "created_by": value.created_by,
+            "expires_at": value.expires_at,
+        }
+    }
+}
+
+impl Projection for ShortLink {
+    fn projection() -> Document {
+        doc! {
+            "id": {"$toString": "$_id"},
+            "code": 1,
+            "kind": 1,
+            "token": 1,
+            "created_by": 1,
+            "expires_at": {"$dateToString": {"date":"$expires_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+            "created_at": {"$dateToString": {"date":"$created_at", "format": "%Y-%m-%dT%H:%M:%S.%LZ"}},
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use little_walk_core::error::ErrorKind;
+    use mongodb::error::{CommandError, WriteError};
+
+    fn duplicate_message(collection: &str, index: &str) -> String {
+        format!(
+            "E11000 duplicate key error collection: little_walk.{} index: {} dup key: {{ }}",
+            collection, index
+        )
+    }
+
+    #[test]
+    fn breed_name_and_category_are_unique() {
+        let message = duplicate_message("breeds", "breeds_name_category");
+        assert_eq!(duplicate_key_fields(&message), ["name", "category"]);
+    }
+
+    #[test]
+    fn oauth_account_provider_and_subject_are_unique() {
+        let message = duplicate_message("oauth_accounts", "oauth_accounts_provider_subject");
+        assert_eq!(duplicate_key_fields(&message), ["provider", "subject"]);
+    }
+
+    #[test]
+    fn short_link_code_is_unique() {
+        let message = duplicate_message("short_links", "short_links_code");
+        assert_eq!(duplicate_key_fields(&message), ["code"]);
+    }
+
+    #[test]
+    fn bound_phone_is_unique() {
+        let message = duplicate_message("phone_bindings", "phone_bindings_phone");
+        assert_eq!(duplicate_key_fields(&message), ["phone"]);
+    }
+
+    #[test]
+    fn pending_refund_per_walk_request_is_unique() {
+        let message = duplicate_message("refund_requests", "refund_requests_pending_walk_request");
+        assert_eq!(duplicate_key_fields(&message), ["walk_request_id"]);
+    }
+
+    #[tokio::test]
+    async fn only_listing_reads_use_the_configured_read_preference() {
+        let client = Client::with_uri_str("mongodb://localhost:27017")
+            .await
+            .unwrap();
+        let repository = MongoDB::new(client.clone(), client.database("little_walk"))
+            .with_listing_read_preference(ReadPreference::Secondary {
+                options: Default::default(),
+            });
+        assert!(repository.db.selection_criteria().is_none());
+        assert!(matches!(
+            repository.listing().db.selection_criteria(),
+            Some(SelectionCriteria::ReadPreference(
+                ReadPreference::Secondary { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn walk_earning_is_credited_once() {
+        let message = duplicate_message("ledger_entries", "ledger_entries_walk_earning");
+        assert_eq!(duplicate_key_fields(&message), ["kind", "walk_request_id"]);
+    }
+
+    #[test]
+    fn duplicate_write_and_command_errors_map_to_conflicts() {
+        let message = duplicate_message("phone_bindings", "phone_bindings_phone");
+        let write_error: WriteError = from_document(doc! { "code": DUPLICATE_KEY_CODE, "errmsg": &message }).unwrap();
+        let e = write_error_from(MongoErrorKind::Write(WriteFailure::WriteError(write_error)));
+        assert_eq!(e.kind(), ErrorKind::Conflict);
+        assert!(e.is_duplicate("phone"));
+        let command_error: CommandError = from_document(doc! { "code": DUPLICATE_KEY_CODE, "codeName": "DuplicateKey", "errmsg": &message }).unwrap();
+        let e = write_error_from(MongoErrorKind::Command(command_error));
+        assert!(e.is_duplicate("phone"));
+        let other: WriteError = from_document(doc! { "code": 121, "errmsg": "Document failed validation" }).unwrap();
+        let e = write_error_from(MongoErrorKind::Write(WriteFailure::WriteError(other)));
+        assert_eq!(e.kind(), ErrorKind::Internal);
+        assert!(e.fields().is_empty());
+    }
+
+    fn write_error_from(kind: MongoErrorKind) -> Error {
+        write_error(mongodb::error::Error::from(kind), "failed to bind phone")
+    }
+
+    #[test]
+    fn unknown_index_has_no_fields() {
+        let message = duplicate_message("breeds", "_id_");
+        assert!(duplicate_key_fields(&message).is_empty());
+        assert_eq!(Error::duplicate(&[]).message(), "记录已存在");
+    }
+
+    #[test]
+    fn sparse_dog_projection_keeps_required_fields() {
+        let fields = FieldSet::parse("portrait_id", little_walk_core::repository::DOG_FIELDS).unwrap();
+        let projection = sparse_projection(Dog::projection(), Some(&fields), DOG_REQUIRED_FIELDS);
+        let keys = projection.keys().map(String::as_str).collect::<Vec<&str>>();
+        assert_eq!(keys, ["id", "name", "gender", "breed", "birthday", "owner_id", "portrait_id", "updated_at"]);
+    }
+
+    #[test]
+    fn sparse_walk_request_projection_matches_aliases() {
+        let fields = FieldSet::parse("status,route_deviation_score", little_walk_core::repository::WALK_REQUEST_FIELDS).unwrap();
+        let projection = sparse_projection(WalkRequest::projection(), Some(&fields), &["created_at"]);
+        let keys = projection.keys().map(String::as_str).collect::<Vec<&str>>();
+        assert_eq!(keys, ["id", "status", "route_assessment", "created_at"]);
+        assert!(FieldSet::parse("priority_walkers", little_walk_core::repository::WALK_REQUEST_FIELDS).is_err());
+    }
+
+    #[test]
+    fn revealed_review_query_includes_reviews_without_state() {
+        let query = ReviewQuery { state: Some(ReviewState::Revealed), ..Default::default() };
+        let q = Document::try_from_model(query).unwrap();
+        assert_eq!(q, doc! { "state": { "$ne": "Hidden" } });
+        let query = ReviewQuery { state: Some(ReviewState::Hidden), ..Default::default() };
+        assert_eq!(Document::try_from_model(query).unwrap(), doc! { "state": "Hidden" });
+    }
+
+    #[test]
+    fn update_fields_map_to_set_and_unset() {
+        let update: WalkRequestUpdate = serde_json::from_str(r#"{"accepted_by": "walker", "route_preference": null}"#).unwrap();
+        assert!(update.accepted_at.is_keep());
+        let update = Document::from_model(update);
+        assert_eq!(update.get_document("$set").unwrap(), &doc! { "accepted_by": "walker" });
+        assert_eq!(update.get_document("$unset").unwrap(), &doc! { "route_preference": "" });
+    }
+}
@@ -1,4 +0,0 @@
-pub mod entities;
-pub mod error;
-pub mod repository;
-pub mod service;
@@ -1,5 +0,0 @@
-pub(crate) mod auth;
-pub(crate) mod breed;
-pub(crate) mod common;
-pub(crate) mod dog;
-pub(crate) mod upload;
@@ -1,3 +0,0 @@
-pub mod mongodb;
-pub mod postgres;
-pub mod surrealdb;
@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+use utoipa::IntoParams;
+
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteAccountReq {
+    #[serde(alias = "reassign_dogs_to")]
+    pub reassign_dogs_to: Option<String>,
+}
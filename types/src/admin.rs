@@ -0,0 +1,52 @@
+use chrono::{DateTime, Utc};
+use little_walk_core::entities::{LocationAccessKind, WalkRequestAuditAction};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(rename_all = "camelCase")]
+pub struct LocationAccessesReq {
+    #[serde(alias = "accessor_id")]
+    pub accessor_id: Option<String>,
+    #[serde(alias = "subject_id")]
+    pub subject_id: Option<String>,
+    #[serde(alias = "walk_request_id")]
+    pub walk_request_id: Option<String>,
+    pub kind: Option<LocationAccessKind>,
+    #[serde(alias = "created_after")]
+    pub created_after: Option<DateTime<Utc>>,
+    #[serde(alias = "created_before")]
+    pub created_before: Option<DateTime<Utc>>,
+    pub limit: i64,
+    pub skip: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditLogsReq {
+    #[serde(alias = "walk_request_id")]
+    pub walk_request_id: Option<String>,
+    #[serde(alias = "actor_id")]
+    pub actor_id: Option<String>,
+    pub action: Option<WalkRequestAuditAction>,
+    #[serde(alias = "created_after")]
+    pub created_after: Option<DateTime<Utc>>,
+    #[serde(alias = "created_before")]
+    pub created_before: Option<DateTime<Utc>>,
+    pub limit: i64,
+    pub skip: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatePartnerResp {
+    pub id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RevokePartnerApiKeyResp {
+    pub success: bool,
+}
@@ -0,0 +1,226 @@
+use little_walk_core::entities::SensitiveAction;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::validation::{FieldErrors, Validate};
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LoginByPasswordParams {
+    pub phone: String,
+    pub password: String,
+}
+
+impl Validate for LoginByPasswordParams {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.phone("phone", &self.phone);
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LoginByPasswordResp {
+    pub token: String,
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyTokenResp {
+    pub id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RevokeTokenResp {
+    pub success: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SignupParams {
+    pub phone: String,
+    pub password: String,
+}
+
+impl Validate for SignupParams {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.phone("phone", &self.phone);
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SignupResp {
+    pub token: String,
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ExistsUserResp {
+    pub exists: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RefreshTokenParams {
+    #[serde(alias = "refresh_token")]
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RefreshTokenResp {
+    pub token: String,
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SendOtpResp {
+    pub success: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LoginByOtpParams {
+    pub phone: String,
+    pub code: String,
+}
+
+impl Validate for LoginByOtpParams {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.phone("phone", &self.phone);
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LoginByOtpResp {
+    pub token: String,
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyPasswordResetCodeParams {
+    pub phone: String,
+    pub code: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyPasswordResetCodeResp {
+    pub reset_token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ResetPasswordParams {
+    #[serde(alias = "reset_token")]
+    pub reset_token: String,
+    pub password: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ResetPasswordResp {
+    pub success: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangePasswordParams {
+    pub phone: String,
+    #[serde(alias = "old_password")]
+    pub old_password: String,
+    #[serde(alias = "new_password")]
+    pub new_password: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangePasswordResp {
+    pub success: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SendPhoneChangeCodeParams {
+    #[serde(alias = "new_phone")]
+    pub new_phone: String,
+}
+
+impl Validate for SendPhoneChangeCodeParams {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.phone("newPhone", &self.new_phone);
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangePhoneParams {
+    pub phone: String,
+    pub password: String,
+    #[serde(alias = "new_phone")]
+    pub new_phone: String,
+    pub code: String,
+}
+
+impl Validate for ChangePhoneParams {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.phone("newPhone", &self.new_phone);
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangePhoneResp {
+    pub success: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IssueActionTokenParams {
+    pub action: SensitiveAction,
+    pub phone: String,
+    pub password: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IssueActionTokenResp {
+    pub action_token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LoginByOAuthParams {
+    pub code: String,
+}
+
+// 已绑定时返回令牌, 未绑定时只返回linkToken, 客户端需验证手机号后调用绑定接口
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LoginByOAuthResp {
+    pub token: Option<String>,
+    pub refresh_token: Option<String>,
+    pub link_token: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkOAuthAccountParams {
+    #[serde(alias = "link_token")]
+    pub link_token: String,
+    pub phone: String,
+    pub code: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkOAuthAccountResp {
+    pub token: String,
+    pub refresh_token: String,
+}
@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+// 客户端发来的文本帧
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChatReq {
+    #[serde(rename_all = "camelCase")]
+    Message {
+        #[serde(default)]
+        text: String,
+        #[serde(default)]
+        image_ids: Vec<String>, // 已上传图片的ID, 最多9张
+    },
+    #[serde(rename_all = "camelCase")]
+    Read {
+        message_id: String, // 已读到的最后一条消息
+    },
+}
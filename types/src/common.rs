@@ -0,0 +1,50 @@
+use little_walk_core::repository::{Cursor, Pagination};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ListResp<T> {
+    pub list: Vec<T>,
+    pub total: i64,
+}
+
+impl<T> ListResp<T> {
+    pub fn new(list: Vec<T>, total: i64) -> Self {
+        Self { list, total }
+    }
+}
+
+// 带页码的分页列表, page从1开始, limit为0时表示不分页
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PageResp<T> {
+    pub list: Vec<T>,
+    pub total: u64,
+    pub page: i64,
+    pub page_size: i64,
+    // 本页已满时返回, 作为cursor参数获取下一页
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+impl<T> PageResp<T> {
+    pub fn new(list: Vec<T>, total: u64, pagination: &Pagination) -> Self {
+        let page = match pagination.limit {
+            limit if limit > 0 => pagination.skip / limit + 1,
+            _ => 1,
+        };
+        Self {
+            list,
+            total,
+            page,
+            page_size: pagination.limit,
+            next_cursor: None,
+        }
+    }
+
+    pub fn with_next_cursor(mut self, cursor: Option<Cursor>) -> Self {
+        self.next_cursor = cursor.map(|cursor| cursor.encode());
+        self
+    }
+}
@@ -0,0 +1,80 @@
+use little_walk_core::entities::PortraitIssue;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::dto::DogResp;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateDogResult {
+    pub id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateDogResult {
+    pub updated: bool,
+}
+
+// 离线编辑冲突时返回服务端当前版本, 由客户端合并后重新提交
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeRequiredResp {
+    pub merge_required: bool,
+    pub server: DogResp,
+}
+
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(rename_all = "camelCase")]
+pub struct IsOwnerOfTheDogReq {
+    pub id: String,
+    #[serde(alias = "owner_id")]
+    pub owner_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IsOwnerOfTheDogResp {
+    pub is_owner: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateDogPortraitReq {
+    #[serde(alias = "portrait_id")]
+    pub portrait_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateDogPortraitResp {
+    pub has_updated: bool,
+    pub warnings: Vec<PortraitIssue>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PortraitRejectedResp {
+    pub issues: Vec<PortraitIssue>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BreedSuggestionsReq {
+    #[serde(alias = "upload_id")]
+    pub upload_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AddDogPhotoReq {
+    #[serde(alias = "photo_id")]
+    pub photo_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReorderDogPhotosReq {
+    pub photos: Vec<String>,
+}
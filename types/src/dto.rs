@@ -0,0 +1,2531 @@
+// 对外接口的请求和响应结构, 与存储实体分离, 通过显式的映射函数互相转换
+use chrono::{DateTime, Utc};
+use little_walk_core::{
+    entities::{
+        AccountDeletion, AccountMerge, Achievement, AchievementKind, AuditLog, Block, Breed,
+        BreedCareTips, BreedSuggestion, CancellationPenalty, CancellationPenaltyTier, Category,
+        ChatMessage, DirectUpload, DirectUploadStatus, Dog, EmergencyContact, Favorite, Gender,
+        GeoPoint, HelpArticle, IntegrityIssue, IntegrityIssueKind, IntegrityReport, Invite,
+        InviteConversion, InviteKind, LedgerEntry, LedgerEntryKind, LocationAccess,
+        LocationAccessKind, MeetAndGreet, MeetAndGreetStatus, MergedReference, NeighborhoodStats,
+        NoGoZone, Notification, NotificationKind, NotificationSettings, OperationsSnapshot,
+        OwnerProfile, OwnerReputation, Partner, PartnerApiKey, PartnerConsent, PartnerKind,
+        PartnerScope, PartnerUsage, PayoutAccount, PayoutAccountStatus, PayoutMethod, Poi,
+        PoiVisit, PurgedCounts, PushPlatform, RankedWalker, RefundRequest, RefundStatus, Report,
+        ReportStatus, Review, ReviewKind, RouteDeviation, RouteDeviationKind, RoutePreference,
+        Session, ShortLinkKind, Ticket, TicketCategory, TicketMessage, TicketStatus,
+        VerificationStatus, WalkRequest, WalkRequestAuditAction, WalkRequestChanges,
+        WalkRouteReport, Walker, WalkerAvailability, WalkerStats, Withdrawal, WithdrawalStatus,
+    },
+    error::Error,
+    ids::{BreedId, DogId, WalkRequestId},
+    payout::PayoutDestination,
+    repository::{
+        AccountMergeCreate, AvailableWalkerQuery, BreedCreate, BreedQuery, Cursor, DogCreate,
+        DogQuery, DogUpdate, FieldSet, HelpArticleCreate, MeetAndGreetCreate, OwnerUpdate, Page,
+        Pagination, PartnerCreate, PartnerUpdate, PoiCreate, PoiQuery, QueryPlan, QueryTemplate,
+        ReportCreate, SortBy, TicketCreate, UpdateField, WalkRequestCreate,
+    },
+    service::DEFAULT_HELP_LOCALE,
+    translation::Language,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::validation::{
+    FieldErrors, Validate, MAX_CARE_TIPS, MAX_CARE_TIP_CHARS, MAX_NAME_CHARS, MAX_NOTE_CHARS,
+    MAX_PUSH_TOKEN_CHARS,
+};
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BreedResp {
+    pub id: BreedId,
+    pub category: Category,
+    pub name: String,
+}
+
+impl From<Breed> for BreedResp {
+    fn from(breed: Breed) -> Self {
+        Self {
+            id: breed.id,
+            category: breed.category,
+            name: breed.name,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BreedCareTipsResp {
+    pub locale: String, // 请求的语言没有建议时为默认语言
+    pub tips: Vec<String>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl From<BreedCareTips> for BreedCareTipsResp {
+    fn from(tips: BreedCareTips) -> Self {
+        Self {
+            locale: tips.locale,
+            tips: tips.tips,
+            updated_at: tips.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BreedDetailResp {
+    pub id: BreedId,
+    pub category: Category,
+    pub name: String,
+    pub care_tips: Option<BreedCareTipsResp>,
+}
+
+impl BreedDetailResp {
+    pub fn new(breed: Breed, care_tips: Option<BreedCareTips>) -> Self {
+        Self {
+            id: breed.id,
+            category: breed.category,
+            name: breed.name,
+            care_tips: care_tips.map(BreedCareTipsResp::from),
+        }
+    }
+}
+
+// 遛狗请求中每只狗狗一项, 品种没有建议时careTips为空
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DogCareTipsResp {
+    pub dog_id: DogId,
+    pub dog_name: String,
+    pub breed: BreedResp,
+    pub care_tips: Option<BreedCareTipsResp>,
+}
+
+impl DogCareTipsResp {
+    pub fn new(dog: Dog, care_tips: Option<BreedCareTips>) -> Self {
+        Self {
+            dog_id: dog.id,
+            dog_name: dog.name,
+            breed: dog.breed.into(),
+            care_tips: care_tips.map(BreedCareTipsResp::from),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateBreedCareTipsReq {
+    pub tips: Vec<String>,
+}
+
+impl Validate for UpdateBreedCareTipsReq {
+    fn validate(&self, errors: &mut FieldErrors) {
+        if self.tips.is_empty() {
+            errors.add("tips", "至少填写一条建议");
+        }
+        if self.tips.len() > MAX_CARE_TIPS {
+            errors.add("tips", format!("最多{}条建议", MAX_CARE_TIPS));
+        }
+        for (i, tip) in self.tips.iter().enumerate() {
+            errors.not_blank(&format!("tips[{}]", i), tip, MAX_CARE_TIP_CHARS);
+        }
+    }
+}
+
+// 照护建议的语言, 如locale=en-US, 默认为zh-CN
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct LocaleReq {
+    #[serde(default = "default_locale")]
+    pub locale: String,
+}
+
+fn default_locale() -> String {
+    DEFAULT_HELP_LOCALE.to_owned()
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BreedSuggestionResp {
+    pub breed: BreedResp,
+    pub confidence: f64,
+}
+
+impl From<BreedSuggestion> for BreedSuggestionResp {
+    fn from(suggestion: BreedSuggestion) -> Self {
+        Self {
+            breed: suggestion.breed.into(),
+            confidence: suggestion.confidence,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BreedReq {
+    pub id: BreedId,
+    pub category: Category,
+    pub name: String,
+}
+
+impl From<BreedReq> for Breed {
+    fn from(req: BreedReq) -> Self {
+        Self {
+            id: req.id,
+            category: req.category,
+            name: req.name,
+            updated_at: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateBreedReq {
+    pub category: Category,
+    pub name: String,
+}
+
+impl From<CreateBreedReq> for BreedCreate {
+    fn from(req: CreateBreedReq) -> Self {
+        Self {
+            category: req.category,
+            name: req.name,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(rename_all = "camelCase")]
+pub struct BreedQueryReq {
+    pub id: Option<String>,
+    pub category: Option<Category>,
+    pub name: Option<String>,
+}
+
+impl From<BreedQueryReq> for BreedQuery {
+    fn from(req: BreedQueryReq) -> Self {
+        Self {
+            id: req.id,
+            category: req.category,
+            name: req.name,
+            sort_by: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DogResp {
+    pub id: DogId,
+    pub name: String,
+    pub gender: Gender,
+    pub breed: BreedResp,
+    pub birthday: DateTime<Utc>,
+    pub owner_id: String,
+    pub tags: Vec<String>,
+    pub portrait_id: Option<String>,
+    pub photos: Vec<String>,
+    pub medical_flags: Vec<String>,
+    pub version: i64,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl From<Dog> for DogResp {
+    fn from(dog: Dog) -> Self {
+        Self {
+            id: dog.id,
+            name: dog.name,
+            gender: dog.gender,
+            breed: dog.breed.into(),
+            birthday: dog.birthday,
+            owner_id: dog.owner_id,
+            tags: dog.tags,
+            portrait_id: dog.portrait_id,
+            photos: dog.photos,
+            medical_flags: dog.medical_flags,
+            version: dog.version,
+            updated_at: dog.updated_at,
+        }
+    }
+}
+
+// 创建遛狗请求时客户端提交的狗狗信息
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DogReq {
+    pub id: DogId,
+    pub name: String,
+    pub gender: Gender,
+    pub breed: BreedReq,
+    pub birthday: DateTime<Utc>,
+    #[serde(alias = "owner_id")]
+    pub owner_id: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(alias = "portrait_id")]
+    pub portrait_id: Option<String>,
+    #[serde(default, alias = "medical_flags")]
+    pub medical_flags: Vec<String>,
+}
+
+impl From<DogReq> for Dog {
+    fn from(req: DogReq) -> Self {
+        Self {
+            id: req.id,
+            name: req.name,
+            gender: req.gender,
+            breed: req.breed.into(),
+            birthday: req.birthday,
+            owner_id: req.owner_id,
+            tags: req.tags,
+            portrait_id: req.portrait_id,
+            photos: Vec::new(),
+            medical_flags: req.medical_flags,
+            version: 0,
+            updated_at: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateDogReq {
+    pub name: String,
+    pub gender: String,
+    pub breed: BreedQueryReq,
+    pub birthday: DateTime<Utc>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(alias = "portrait_id")]
+    pub portrait_id: Option<String>,
+    #[serde(default, alias = "medical_flags")]
+    pub medical_flags: Vec<String>,
+}
+
+impl Validate for CreateDogReq {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.not_blank("name", &self.name, MAX_NAME_CHARS);
+        errors.tags("tags", &self.tags);
+    }
+}
+
+impl CreateDogReq {
+    pub fn into_create(self, owner_id: String) -> DogCreate {
+        DogCreate {
+            owner_id,
+            name: self.name,
+            gender: self.gender,
+            breed: self.breed.into(),
+            birthday: self.birthday,
+            tags: self.tags,
+            portrait_id: self.portrait_id,
+            medical_flags: self.medical_flags,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateDogReq {
+    pub name: Option<String>,
+    pub gender: Option<String>,
+    pub breed: Option<BreedQueryReq>,
+    pub birthday: Option<String>,
+    #[serde(alias = "is_sterilized")]
+    pub is_sterilized: Option<bool>,
+    #[serde(default)]
+    #[schema(value_type = Option<String>)]
+    pub introduction: UpdateField<String>, // 传null清空, 不传不修改
+    #[serde(alias = "owner_id")]
+    pub owner_id: Option<String>,
+    pub tags: Option<Vec<String>>,
+    #[serde(default, alias = "portrait_id")]
+    #[schema(value_type = Option<String>)]
+    pub portrait_id: UpdateField<String>, // 传null移除头像, 不传不修改
+    #[serde(alias = "medical_flags")]
+    pub medical_flags: Option<Vec<String>>,
+    #[serde(alias = "edited_at")]
+    pub edited_at: Option<DateTime<Utc>>,
+    pub version: Option<i64>, // 读取时的版本, 与服务端不一致时返回冲突
+}
+
+impl Validate for UpdateDogReq {
+    fn validate(&self, errors: &mut FieldErrors) {
+        if let Some(name) = &self.name {
+            errors.not_blank("name", name, MAX_NAME_CHARS);
+        }
+        if let Some(tags) = &self.tags {
+            errors.tags("tags", tags);
+        }
+    }
+}
+
+impl From<UpdateDogReq> for DogUpdate {
+    fn from(req: UpdateDogReq) -> Self {
+        Self {
+            name: req.name,
+            gender: req.gender,
+            breed: req.breed.map(BreedQuery::from),
+            birthday: req.birthday,
+            is_sterilized: req.is_sterilized,
+            introduction: req.introduction,
+            owner_id: req.owner_id,
+            tags: req.tags,
+            portrait_id: req.portrait_id,
+            medical_flags: req.medical_flags,
+            edited_at: req.edited_at,
+            expected_version: req.version,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(rename_all = "camelCase")]
+pub struct DogsReq {
+    pub id: Option<String>,
+    #[serde(alias = "id_in")]
+    pub id_in: Option<Vec<String>>,
+    #[serde(alias = "owner_id")]
+    pub owner_id: Option<String>,
+    pub pagination: Option<Pagination>,
+    pub cursor: Option<String>, // 传入时按游标翻页, 忽略pagination中的skip
+    #[serde(alias = "breed_id")]
+    pub breed_id: Option<String>,
+    #[serde(alias = "tags_include_any")]
+    pub tags_include_any: Option<Vec<String>>,
+    pub gender: Option<Gender>,
+    #[serde(alias = "birthday_before")]
+    pub birthday_before: Option<DateTime<Utc>>,
+    #[serde(alias = "birthday_after")]
+    pub birthday_after: Option<DateTime<Utc>>,
+}
+
+impl TryFrom<DogsReq> for DogQuery {
+    type Error = Error;
+
+    fn try_from(req: DogsReq) -> Result<Self, Self::Error> {
+        let pagination = match req.cursor {
+            Some(cursor) => Some(Page::Cursor {
+                after: Some(cursor.parse::<Cursor>()?),
+                limit: req.pagination.map(|p| p.limit).unwrap_or_default(),
+            }),
+            None => req.pagination.map(Page::from),
+        };
+        Ok(Self {
+            id: req.id,
+            id_in: req.id_in,
+            owner_id: req.owner_id,
+            pagination,
+            include_deleted: false,
+            fields: None,
+            sort_by: None,
+            breed_id: req.breed_id,
+            tags_include_any: req.tags_include_any,
+            gender: req.gender,
+            birthday_before: req.birthday_before,
+            birthday_after: req.birthday_after,
+        })
+    }
+}
+
+// 列表分页参数, 传cursor时从游标之后开始取limit条并忽略skip
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PageReq {
+    pub limit: i64,
+    #[serde(default)]
+    pub skip: i64,
+    pub cursor: Option<String>,
+}
+
+impl PageReq {
+    // 游标翻页时没有页码, 按第一页计算
+    pub fn pagination(&self) -> Pagination {
+        Pagination {
+            limit: self.limit,
+            skip: if self.cursor.is_some() { 0 } else { self.skip },
+        }
+    }
+}
+
+impl TryFrom<&PageReq> for Page {
+    type Error = Error;
+
+    fn try_from(req: &PageReq) -> Result<Self, Self::Error> {
+        Ok(match &req.cursor {
+            Some(cursor) => Page::Cursor {
+                after: Some(cursor.parse()?),
+                limit: req.limit,
+            },
+            None => Page::Offset(req.pagination()),
+        })
+    }
+}
+
+// 需要翻译的内容附带目标语言的译文, 如translate=en
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TranslateReq {
+    pub translate: Option<Language>,
+}
+
+// 列表字段选择, 如fields=name,portrait_id, 不传时返回全部字段
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FieldsReq {
+    pub fields: Option<String>,
+}
+
+impl FieldsReq {
+    pub fn parse(&self, allowed: &[&str]) -> Result<Option<FieldSet>, Error> {
+        self.fields
+            .as_deref()
+            .map(|fields| FieldSet::parse(fields, allowed))
+            .transpose()
+    }
+}
+
+// 列表排序, 如sort_by=name&order=desc, sort_by须为实体允许排序的字段名, order默认asc
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SortReq {
+    pub sort_by: Option<String>,
+    pub order: Option<String>,
+}
+
+impl SortReq {
+    pub fn parse(&self, sortable: &[String]) -> Result<Option<SortBy>, Error> {
+        self.sort_by
+            .as_deref()
+            .map(|field| SortBy::parse(field, self.order.as_deref(), sortable))
+            .transpose()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WalkRequestResp {
+    pub id: WalkRequestId,
+    pub dogs: Vec<DogResp>,
+    pub should_start_after: Option<DateTime<Utc>>,
+    pub should_start_before: Option<DateTime<Utc>>,
+    pub should_end_after: Option<DateTime<Utc>>,
+    pub should_end_before: Option<DateTime<Utc>>,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub distance: Option<f64>,
+    pub canceled_at: Option<DateTime<Utc>>,
+    pub accepted_by: Option<String>,
+    pub accepted_at: Option<DateTime<Utc>>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub status: String,
+    pub acceptances: Vec<String>,
+    pub priority_until: Option<DateTime<Utc>>,
+    pub notify_walker_nearby: bool,
+    pub route_preference: Option<RoutePreferenceResp>,
+    pub route_deviation_score: Option<f64>,
+    pub version: i64,
+    pub created_by: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    // 对方发来的未读聊天消息数, 只在我的请求列表中返回
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unread_count: Option<u64>,
+}
+
+impl From<WalkRequest> for WalkRequestResp {
+    fn from(request: WalkRequest) -> Self {
+        Self {
+            id: request.id,
+            dogs: request.dogs.into_iter().map(DogResp::from).collect(),
+            should_start_after: request.should_start_after,
+            should_start_before: request.should_start_before,
+            should_end_after: request.should_end_after,
+            should_end_before: request.should_end_before,
+            latitude: request.latitude,
+            longitude: request.longitude,
+            distance: request.distance,
+            canceled_at: request.canceled_at,
+            accepted_by: request.accepted_by,
+            accepted_at: request.accepted_at,
+            started_at: request.started_at,
+            finished_at: request.finished_at,
+            status: request.status,
+            acceptances: request.acceptances.unwrap_or_default(),
+            priority_until: request.priority_until,
+            notify_walker_nearby: request.notify_walker_nearby,
+            route_preference: request.route_preference.map(RoutePreferenceResp::from),
+            route_deviation_score: request.route_assessment.map(|a| a.score),
+            version: request.version,
+            created_by: request.created_by,
+            created_at: request.created_at,
+            updated_at: request.updated_at,
+            unread_count: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WalkRequestSummaryResp {
+    pub id: WalkRequestId,
+    pub status: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub should_start_after: Option<DateTime<Utc>>,
+    pub should_start_before: Option<DateTime<Utc>>,
+    pub accepted_by: Option<String>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl From<WalkRequest> for WalkRequestSummaryResp {
+    fn from(request: WalkRequest) -> Self {
+        Self {
+            id: request.id,
+            status: request.status,
+            latitude: request.latitude,
+            longitude: request.longitude,
+            should_start_after: request.should_start_after,
+            should_start_before: request.should_start_before,
+            accepted_by: request.accepted_by,
+            updated_at: request.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WalkRequestChangesResp {
+    pub created: Vec<WalkRequestSummaryResp>,
+    pub updated: Vec<WalkRequestSummaryResp>,
+    pub deleted: Vec<String>,
+    pub next_token: String, // 下次同步时作为since传回
+    pub has_more: bool,
+}
+
+impl From<WalkRequestChanges> for WalkRequestChangesResp {
+    fn from(changes: WalkRequestChanges) -> Self {
+        Self {
+            created: changes
+                .created
+                .into_iter()
+                .map(WalkRequestSummaryResp::from)
+                .collect(),
+            updated: changes
+                .updated
+                .into_iter()
+                .map(WalkRequestSummaryResp::from)
+                .collect(),
+            deleted: changes.deleted,
+            next_token: changes.checkpoint.encode(),
+            has_more: changes.has_more,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateWalkRequestReq {
+    pub dogs: Vec<DogReq>,
+    #[serde(alias = "should_start_after")]
+    pub should_start_after: Option<DateTime<Utc>>,
+    #[serde(alias = "should_start_before")]
+    pub should_start_before: Option<DateTime<Utc>>,
+    #[serde(alias = "should_end_before")]
+    pub should_end_before: Option<DateTime<Utc>>,
+    #[serde(alias = "should_end_after")]
+    pub should_end_after: Option<DateTime<Utc>>,
+    pub latitude: f64,
+    pub longitude: f64,
+    #[serde(default, alias = "notify_favorites")]
+    pub notify_favorites: bool,
+    #[serde(default, alias = "notify_walker_nearby")]
+    pub notify_walker_nearby: bool,
+    #[serde(alias = "route_preference")]
+    pub route_preference: Option<RoutePreferenceReq>,
+}
+
+impl Validate for CreateWalkRequestReq {
+    fn validate(&self, errors: &mut FieldErrors) {
+        if self.dogs.is_empty() {
+            errors.add("dogs", "至少选择一只狗狗");
+        }
+        errors.coordinate("", self.longitude, self.latitude);
+        errors.walk_window(
+            self.should_start_after,
+            self.should_start_before,
+            self.should_end_after,
+            self.should_end_before,
+        );
+    }
+}
+
+impl CreateWalkRequestReq {
+    pub fn into_create(self, created_by: String) -> WalkRequestCreate {
+        WalkRequestCreate {
+            dogs: self.dogs.into_iter().map(Dog::from).collect(),
+            should_start_after: self.should_start_after,
+            should_start_before: self.should_start_before,
+            should_end_before: self.should_end_before,
+            should_end_after: self.should_end_after,
+            latitude: self.latitude,
+            longitude: self.longitude,
+            created_by,
+            notify_favorites: self.notify_favorites,
+            priority_walkers: Vec::new(),
+            priority_until: None,
+            notify_walker_nearby: self.notify_walker_nearby,
+            route_preference: self.route_preference.map(RoutePreference::from),
+            partner_id: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WalkerResp {
+    pub id: String,
+    pub user_id: String,
+    pub verification_status: VerificationStatus,
+    pub id_document_ids: Vec<String>,
+    pub rejected_reason: Option<String>,
+    pub longitude: Option<f64>,
+    pub latitude: Option<f64>,
+    pub avatar_id: Option<String>,
+    pub submitted_at: Option<DateTime<Utc>>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl From<Walker> for WalkerResp {
+    fn from(walker: Walker) -> Self {
+        Self {
+            id: walker.id,
+            user_id: walker.user_id,
+            verification_status: walker.verification_status,
+            id_document_ids: walker.id_document_ids,
+            rejected_reason: walker.rejected_reason,
+            longitude: walker.longitude,
+            latitude: walker.latitude,
+            avatar_id: walker.avatar_id,
+            submitted_at: walker.submitted_at,
+            reviewed_at: walker.reviewed_at,
+            created_at: walker.created_at,
+            updated_at: walker.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RankedWalkerResp {
+    pub user_id: String,
+    pub longitude: f64,
+    pub latitude: f64,
+    pub distance: f64,
+    pub average_rating: f64,
+    pub completed_walks: i64,
+    pub score: f64,
+    pub avatar_id: Option<String>,
+}
+
+impl From<RankedWalker> for RankedWalkerResp {
+    fn from(walker: RankedWalker) -> Self {
+        Self {
+            user_id: walker.user_id,
+            longitude: walker.longitude,
+            latitude: walker.latitude,
+            distance: walker.distance,
+            average_rating: walker.average_rating,
+            completed_walks: walker.completed_walks,
+            score: walker.score,
+            avatar_id: walker.avatar_id,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WalkerStatsResp {
+    pub walker_id: String,
+    pub completed_walks: i64,
+    pub total_distance: f64,
+    pub cancellation_rate: f64,
+    pub average_rating: f64,
+    pub review_count: i64,
+}
+
+impl From<WalkerStats> for WalkerStatsResp {
+    fn from(stats: WalkerStats) -> Self {
+        Self {
+            walker_id: stats.walker_id,
+            completed_walks: stats.completed_walks,
+            total_distance: stats.total_distance,
+            cancellation_rate: stats.cancellation_rate,
+            average_rating: stats.average_rating,
+            review_count: stats.review_count,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct OwnerReputationResp {
+    pub owner_id: String,
+    pub requests: i64,
+    pub cancellation_rate: f64,
+    pub no_show_rate: f64,
+    pub payment_failures: i64,
+    pub average_rating: f64,
+    pub review_count: i64,
+    pub score: f64, // 0-100
+}
+
+impl From<OwnerReputation> for OwnerReputationResp {
+    fn from(reputation: OwnerReputation) -> Self {
+        Self {
+            owner_id: reputation.owner_id,
+            requests: reputation.requests,
+            cancellation_rate: reputation.cancellation_rate,
+            no_show_rate: reputation.no_show_rate,
+            payment_failures: reputation.payment_failures,
+            average_rating: reputation.average_rating,
+            review_count: reputation.review_count,
+            score: reputation.score,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewResp {
+    pub id: String,
+    pub walk_request_id: String,
+    pub walker_id: String,
+    pub owner_id: String,
+    pub kind: ReviewKind,
+    pub rating: i32,
+    pub content: String,
+    pub photo_ids: Vec<String>,
+    pub helpful_count: i64,
+    pub created_at: Option<DateTime<Utc>>,
+    // 请求translate时为content的译文
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub translated_content: Option<String>,
+}
+
+impl From<Review> for ReviewResp {
+    fn from(review: Review) -> Self {
+        Self {
+            id: review.id,
+            walk_request_id: review.walk_request_id,
+            walker_id: review.walker_id,
+            owner_id: review.owner_id,
+            kind: review.kind,
+            rating: review.rating,
+            content: review.content,
+            photo_ids: review.photo_ids,
+            helpful_count: review.helpful_count,
+            created_at: review.created_at,
+            translated_content: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AchievementResp {
+    pub kind: AchievementKind,
+    pub achieved_at: Option<DateTime<Utc>>,
+}
+
+impl From<Achievement> for AchievementResp {
+    fn from(achievement: Achievement) -> Self {
+        Self {
+            kind: achievement.kind,
+            achieved_at: achievement.achieved_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct OwnerProfileResp {
+    pub user_id: String,
+    pub nickname: Option<String>,
+    pub avatar_id: Option<String>,
+    pub dog_count: i64,
+    pub member_since: Option<DateTime<Utc>>,
+    pub achievements: Vec<AchievementResp>,
+}
+
+impl From<OwnerProfile> for OwnerProfileResp {
+    fn from(profile: OwnerProfile) -> Self {
+        Self {
+            user_id: profile.user_id,
+            nickname: profile.nickname,
+            avatar_id: profile.avatar_id,
+            dog_count: profile.dog_count,
+            member_since: profile.member_since,
+            achievements: profile
+                .achievements
+                .into_iter()
+                .map(AchievementResp::from)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateOwnerReq {
+    pub nickname: Option<String>,
+    #[serde(alias = "avatar_id")]
+    pub avatar_id: Option<String>,
+    #[serde(alias = "emergency_contacts")]
+    pub emergency_contacts: Option<Vec<EmergencyContactReq>>,
+}
+
+impl Validate for UpdateOwnerReq {
+    fn validate(&self, errors: &mut FieldErrors) {
+        if let Some(nickname) = &self.nickname {
+            errors.not_blank("nickname", nickname, MAX_NAME_CHARS);
+        }
+        for (i, contact) in self.emergency_contacts.iter().flatten().enumerate() {
+            errors.not_blank(
+                &format!("emergencyContacts[{i}].name"),
+                &contact.name,
+                MAX_NAME_CHARS,
+            );
+            errors.phone(&format!("emergencyContacts[{i}].phone"), &contact.phone);
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EmergencyContactReq {
+    pub name: String,
+    pub phone: String,
+    pub relationship: Option<String>,
+}
+
+impl From<EmergencyContactReq> for EmergencyContact {
+    fn from(req: EmergencyContactReq) -> Self {
+        Self {
+            name: req.name,
+            phone: req.phone,
+            relationship: req.relationship,
+        }
+    }
+}
+
+impl From<UpdateOwnerReq> for OwnerUpdate {
+    fn from(req: UpdateOwnerReq) -> Self {
+        Self {
+            nickname: req.nickname,
+            avatar_id: req.avatar_id,
+            emergency_contacts: req
+                .emergency_contacts
+                .map(|contacts| contacts.into_iter().map(EmergencyContact::from).collect()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockResp {
+    pub id: String,
+    pub owner_id: String,
+    pub walker_id: String,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl From<Block> for BlockResp {
+    fn from(block: Block) -> Self {
+        Self {
+            id: block.id,
+            owner_id: block.owner_id,
+            walker_id: block.walker_id,
+            created_at: block.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FavoriteResp {
+    pub id: String,
+    pub owner_id: String,
+    pub walker_id: String,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl From<Favorite> for FavoriteResp {
+    fn from(favorite: Favorite) -> Self {
+        Self {
+            id: favorite.id,
+            owner_id: favorite.owner_id,
+            walker_id: favorite.walker_id,
+            created_at: favorite.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportResp {
+    pub id: String,
+    pub reporter_id: String,
+    pub target_user_id: String,
+    pub walk_request_id: Option<String>,
+    pub reason: String,
+    pub description: String,
+    pub status: ReportStatus,
+    pub resolution_note: Option<String>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl From<Report> for ReportResp {
+    fn from(report: Report) -> Self {
+        Self {
+            id: report.id,
+            reporter_id: report.reporter_id,
+            target_user_id: report.target_user_id,
+            walk_request_id: report.walk_request_id,
+            reason: report.reason,
+            description: report.description,
+            status: report.status,
+            resolution_note: report.resolution_note,
+            reviewed_at: report.reviewed_at,
+            created_at: report.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateReportReq {
+    #[serde(alias = "target_user_id")]
+    pub target_user_id: String,
+    #[serde(alias = "walk_request_id")]
+    pub walk_request_id: Option<String>,
+    pub reason: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+impl CreateReportReq {
+    pub fn into_create(self, reporter_id: String) -> ReportCreate {
+        ReportCreate {
+            reporter_id,
+            target_user_id: self.target_user_id,
+            walk_request_id: self.walk_request_id,
+            reason: self.reason,
+            description: self.description,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all_fields = "camelCase")]
+pub enum ExplainReq {
+    NearbyWalkRequests {
+        longitude: f64,
+        latitude: f64,
+        radius: f64,
+    },
+    MyWalkRequests {
+        #[serde(alias = "user_id")]
+        user_id: String,
+    },
+    DogsByOwner {
+        #[serde(alias = "owner_id")]
+        owner_id: String,
+    },
+    WalkerVerifications {
+        #[serde(alias = "verification_status")]
+        verification_status: VerificationStatus,
+    },
+}
+
+impl From<ExplainReq> for QueryTemplate {
+    fn from(req: ExplainReq) -> Self {
+        match req {
+            ExplainReq::NearbyWalkRequests {
+                longitude,
+                latitude,
+                radius,
+            } => QueryTemplate::NearbyWalkRequests {
+                longitude,
+                latitude,
+                radius,
+            },
+            ExplainReq::MyWalkRequests { user_id } => QueryTemplate::MyWalkRequests { user_id },
+            ExplainReq::DogsByOwner { owner_id } => QueryTemplate::DogsByOwner { owner_id },
+            ExplainReq::WalkerVerifications {
+                verification_status,
+            } => QueryTemplate::WalkerVerifications {
+                verification_status,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryPlanResp {
+    pub collection: String,
+    pub stages: Vec<String>,
+    pub indexes: Vec<String>,
+    pub returned: i64,
+    pub keys_examined: i64,
+    pub docs_examined: i64,
+    pub execution_time_millis: i64,
+}
+
+impl From<QueryPlan> for QueryPlanResp {
+    fn from(plan: QueryPlan) -> Self {
+        Self {
+            collection: plan.collection,
+            stages: plan.stages,
+            indexes: plan.indexes,
+            returned: plan.returned,
+            keys_examined: plan.keys_examined,
+            docs_examined: plan.docs_examined,
+            execution_time_millis: plan.execution_time_millis,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountMergeReq {
+    #[serde(alias = "from_user_id")]
+    pub from_user_id: String,
+    #[serde(alias = "to_user_id")]
+    pub to_user_id: String,
+}
+
+impl AccountMergeReq {
+    pub fn into_create(self, operator_id: String) -> AccountMergeCreate {
+        AccountMergeCreate {
+            from_user_id: self.from_user_id,
+            to_user_id: self.to_user_id,
+            operator_id,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MergedReferenceResp {
+    pub collection: String,
+    pub field: String,
+    pub count: u64,
+}
+
+impl From<MergedReference> for MergedReferenceResp {
+    fn from(reference: MergedReference) -> Self {
+        Self {
+            collection: reference.collection,
+            field: reference.field,
+            count: reference.count,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountMergeResp {
+    pub id: String,
+    pub from_user_id: String,
+    pub to_user_id: String,
+    pub operator_id: String,
+    pub references: Vec<MergedReferenceResp>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl From<AccountMerge> for AccountMergeResp {
+    fn from(merge: AccountMerge) -> Self {
+        Self {
+            id: merge.id,
+            from_user_id: merge.from_user_id,
+            to_user_id: merge.to_user_id,
+            operator_id: merge.operator_id,
+            references: merge
+                .references
+                .into_iter()
+                .map(MergedReferenceResp::from)
+                .collect(),
+            created_at: merge.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountDeletionResp {
+    pub id: String,
+    pub user_id: String,
+    pub dogs_reassigned_to: Option<String>,
+    pub dogs: u64,
+    pub canceled_walk_requests: u64,
+    pub removed_acceptances: u64,
+    pub scheduled_uploads: u64,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl From<AccountDeletion> for AccountDeletionResp {
+    fn from(deletion: AccountDeletion) -> Self {
+        Self {
+            id: deletion.id,
+            user_id: deletion.user_id,
+            dogs_reassigned_to: deletion.dogs_reassigned_to,
+            dogs: deletion.dogs,
+            canceled_walk_requests: deletion.canceled_walk_requests,
+            removed_acceptances: deletion.removed_acceptances,
+            scheduled_uploads: deletion.scheduled_uploads,
+            created_at: deletion.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TicketMessageResp {
+    pub author_id: String,
+    pub from_staff: bool,
+    pub content: String,
+    pub attachment_ids: Vec<String>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl From<TicketMessage> for TicketMessageResp {
+    fn from(message: TicketMessage) -> Self {
+        Self {
+            author_id: message.author_id,
+            from_staff: message.from_staff,
+            content: message.content,
+            attachment_ids: message.attachment_ids,
+            created_at: message.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TicketResp {
+    pub id: String,
+    pub user_id: String,
+    pub category: TicketCategory,
+    pub subject: String,
+    pub status: TicketStatus,
+    pub assignee_id: Option<String>,
+    pub messages: Vec<TicketMessageResp>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl From<Ticket> for TicketResp {
+    fn from(ticket: Ticket) -> Self {
+        Self {
+            id: ticket.id,
+            user_id: ticket.user_id,
+            category: ticket.category,
+            subject: ticket.subject,
+            status: ticket.status,
+            assignee_id: ticket.assignee_id,
+            messages: ticket
+                .messages
+                .into_iter()
+                .map(TicketMessageResp::from)
+                .collect(),
+            created_at: ticket.created_at,
+            updated_at: ticket.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateTicketReq {
+    pub category: TicketCategory,
+    pub subject: String,
+    #[serde(default)]
+    pub content: String,
+    #[serde(default, alias = "attachment_ids")]
+    pub attachment_ids: Vec<String>,
+}
+
+impl CreateTicketReq {
+    pub fn into_create(self, user_id: String) -> TicketCreate {
+        TicketCreate {
+            user_id,
+            category: self.category,
+            subject: self.subject,
+            content: self.content,
+            attachment_ids: self.attachment_ids,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplyTicketReq {
+    #[serde(default)]
+    pub content: String,
+    #[serde(default, alias = "attachment_ids")]
+    pub attachment_ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct HelpArticleSummaryResp {
+    pub slug: String,
+    pub locale: String,
+    pub version: i64,
+    pub title: String,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl From<HelpArticle> for HelpArticleSummaryResp {
+    fn from(article: HelpArticle) -> Self {
+        Self {
+            slug: article.slug,
+            locale: article.locale,
+            version: article.version,
+            title: article.title,
+            created_at: article.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct HelpArticleResp {
+    pub slug: String,
+    pub locale: String,
+    pub version: i64,
+    pub title: String,
+    pub content: String,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishHelpArticleReq {
+    pub locale: String,
+    pub title: String,
+    pub markdown: String,
+}
+
+impl PublishHelpArticleReq {
+    pub fn into_create(self, slug: String, author_id: String) -> HelpArticleCreate {
+        HelpArticleCreate {
+            slug,
+            locale: self.locale,
+            title: self.title,
+            markdown: self.markdown,
+            author_id,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PayoutAccountResp {
+    pub method: PayoutMethod,
+    pub masked_holder_name: String,
+    pub masked_account_number: String,
+    pub bank_name: Option<String>,
+    pub status: PayoutAccountStatus,
+    pub failure_reason: Option<String>,
+    pub can_withdraw: bool,
+    pub verified_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl From<PayoutAccount> for PayoutAccountResp {
+    fn from(account: PayoutAccount) -> Self {
+        Self {
+            can_withdraw: account.can_withdraw(),
+            method: account.method,
+            masked_holder_name: account.masked_holder_name,
+            masked_account_number: account.masked_account_number,
+            bank_name: account.bank_name,
+            status: account.status,
+            failure_reason: account.failure_reason,
+            verified_at: account.verified_at,
+            updated_at: account.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkPayoutAccountReq {
+    pub method: PayoutMethod,
+    #[serde(alias = "holder_name")]
+    pub holder_name: String,
+    #[serde(alias = "account_number")]
+    pub account_number: String,
+    #[serde(alias = "bank_name")]
+    pub bank_name: Option<String>,
+}
+
+impl From<LinkPayoutAccountReq> for PayoutDestination {
+    fn from(req: LinkPayoutAccountReq) -> Self {
+        Self {
+            method: req.method,
+            holder_name: req.holder_name,
+            account_number: req.account_number,
+            bank_name: req.bank_name,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionResp {
+    pub id: String,
+    pub device_id: Option<String>,
+    pub platform: Option<String>,
+    pub signed_in_at: Option<DateTime<Utc>>,
+    pub last_seen_at: Option<DateTime<Utc>>,
+    pub current: bool,
+}
+
+impl SessionResp {
+    pub fn new(session: Session, current_device_id: Option<&str>) -> Self {
+        Self {
+            current: current_device_id.is_some()
+                && session.device_id.as_deref() == current_device_id,
+            id: session.id,
+            device_id: session.device_id,
+            platform: session.platform,
+            signed_in_at: session.signed_in_at,
+            last_seen_at: session.last_seen_at,
+        }
+    }
+}
+
+// 金额均以分为单位
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LedgerEntryResp {
+    pub id: String,
+    pub kind: LedgerEntryKind,
+    pub amount: i64,
+    pub withdrawal_id: Option<String>,
+    pub walk_request_id: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl From<LedgerEntry> for LedgerEntryResp {
+    fn from(entry: LedgerEntry) -> Self {
+        Self {
+            id: entry.id,
+            kind: entry.kind,
+            amount: entry.amount,
+            withdrawal_id: entry.withdrawal_id,
+            walk_request_id: entry.walk_request_id,
+            created_at: entry.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WithdrawalResp {
+    pub id: String,
+    pub user_id: String,
+    pub amount: i64,
+    pub status: WithdrawalStatus,
+    pub failure_reason: Option<String>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub paid_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl From<Withdrawal> for WithdrawalResp {
+    fn from(withdrawal: Withdrawal) -> Self {
+        Self {
+            id: withdrawal.id,
+            user_id: withdrawal.user_id,
+            amount: withdrawal.amount,
+            status: withdrawal.status,
+            failure_reason: withdrawal.failure_reason,
+            reviewed_at: withdrawal.reviewed_at,
+            paid_at: withdrawal.paid_at,
+            created_at: withdrawal.created_at,
+            updated_at: withdrawal.updated_at,
+        }
+    }
+}
+
+// 迟取消罚金明细, 金额以分为单位, balanceAfter为扣除后遛狗人的余额, 可以为负
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CancellationPenaltyResp {
+    pub id: String,
+    pub walk_request_id: String,
+    pub walker_id: String,
+    pub tier: CancellationPenaltyTier,
+    pub scheduled_start: Option<DateTime<Utc>>,
+    pub notice_minutes: i64,
+    pub amount: i64,
+    pub balance_after: i64,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl From<CancellationPenalty> for CancellationPenaltyResp {
+    fn from(penalty: CancellationPenalty) -> Self {
+        Self {
+            id: penalty.id,
+            walk_request_id: penalty.walk_request_id,
+            walker_id: penalty.walker_id,
+            tier: penalty.tier,
+            scheduled_start: penalty.scheduled_start,
+            notice_minutes: penalty.notice_minutes,
+            amount: penalty.amount,
+            balance_after: penalty.balance_after,
+            created_at: penalty.created_at,
+        }
+    }
+}
+
+// 退款申请, 金额以分为单位; walkerDebit为批准后从遛狗人余额扣回的部分, 不含平台服务费
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RefundRequestResp {
+    pub id: String,
+    pub walk_request_id: String,
+    pub owner_id: String,
+    pub walker_id: String,
+    pub amount: i64,
+    pub reason: String,
+    pub evidence_ids: Vec<String>,
+    pub status: RefundStatus,
+    pub approved_amount: Option<i64>,
+    pub walker_debit: Option<i64>,
+    pub review_note: Option<String>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl From<RefundRequest> for RefundRequestResp {
+    fn from(refund: RefundRequest) -> Self {
+        Self {
+            id: refund.id,
+            walk_request_id: refund.walk_request_id,
+            owner_id: refund.owner_id,
+            walker_id: refund.walker_id,
+            amount: refund.amount,
+            reason: refund.reason,
+            evidence_ids: refund.evidence_ids,
+            status: refund.status,
+            approved_amount: refund.approved_amount,
+            walker_debit: refund.walker_debit,
+            review_note: refund.review_note,
+            reviewed_at: refund.reviewed_at,
+            created_at: refund.created_at,
+            updated_at: refund.updated_at,
+        }
+    }
+}
+
+// 遛狗前的见面地点和时间, 时长需在10到60分钟之间, 且须在遛狗开始前结束
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ProposeMeetAndGreetReq {
+    pub walker_id: String,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub longitude: f64,
+    pub latitude: f64,
+    pub note: Option<String>,
+}
+
+impl Validate for ProposeMeetAndGreetReq {
+    fn validate(&self, errors: &mut FieldErrors) {
+        if self.walker_id.trim().is_empty() {
+            errors.add("walkerId", "不能为空");
+        }
+        if self.ends_at <= self.starts_at {
+            errors.add("endsAt", "必须晚于startsAt");
+        }
+        errors.coordinate("", self.longitude, self.latitude);
+        if let Some(note) = &self.note {
+            errors.not_blank("note", note, MAX_NOTE_CHARS);
+        }
+    }
+}
+
+impl ProposeMeetAndGreetReq {
+    pub fn into_create(self, walk_request_id: String, owner_id: String) -> MeetAndGreetCreate {
+        MeetAndGreetCreate {
+            walk_request_id,
+            owner_id,
+            walker_id: self.walker_id,
+            starts_at: self.starts_at,
+            ends_at: self.ends_at,
+            longitude: self.longitude,
+            latitude: self.latitude,
+            note: self.note,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MeetAndGreetResp {
+    pub id: String,
+    pub walk_request_id: String,
+    pub owner_id: String,
+    pub walker_id: String,
+    pub status: MeetAndGreetStatus,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub longitude: f64,
+    pub latitude: f64,
+    pub note: Option<String>,
+    pub accepted_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub canceled_by: Option<String>,
+    pub canceled_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl From<MeetAndGreet> for MeetAndGreetResp {
+    fn from(meet_and_greet: MeetAndGreet) -> Self {
+        Self {
+            id: meet_and_greet.id,
+            walk_request_id: meet_and_greet.walk_request_id,
+            owner_id: meet_and_greet.owner_id,
+            walker_id: meet_and_greet.walker_id,
+            status: meet_and_greet.status,
+            starts_at: meet_and_greet.starts_at,
+            ends_at: meet_and_greet.ends_at,
+            longitude: meet_and_greet.longitude,
+            latitude: meet_and_greet.latitude,
+            note: meet_and_greet.note,
+            accepted_at: meet_and_greet.accepted_at,
+            completed_at: meet_and_greet.completed_at,
+            canceled_by: meet_and_greet.canceled_by,
+            canceled_at: meet_and_greet.canceled_at,
+            created_at: meet_and_greet.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatMessageResp {
+    pub id: String,
+    pub walk_request_id: String,
+    pub sender_id: String,
+    pub text: String,
+    pub image_ids: Vec<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    // 请求translate时为text的译文
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub translated_text: Option<String>,
+}
+
+impl From<ChatMessage> for ChatMessageResp {
+    fn from(message: ChatMessage) -> Self {
+        Self {
+            id: message.id,
+            walk_request_id: message.walk_request_id,
+            sender_id: message.sender_id,
+            text: message.text,
+            image_ids: message.image_ids,
+            created_at: message.created_at,
+            translated_text: None,
+        }
+    }
+}
+
+// 运营看板推送的一帧, 仓储操作次数为与上一帧之间的增量
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationsSnapshotResp {
+    pub active_walks: i64,
+    pub failed_payouts: i64,
+    pub repository_operations: u64,
+    pub repository_failures: u64,
+    pub error_rate: f64,
+    pub at: DateTime<Utc>,
+}
+
+impl OperationsSnapshotResp {
+    pub fn new(snapshot: OperationsSnapshot, operations: u64, failures: u64) -> Self {
+        Self {
+            active_walks: snapshot.active_walks,
+            failed_payouts: snapshot.failed_payouts,
+            repository_operations: operations,
+            repository_failures: failures,
+            error_rate: if operations == 0 {
+                0.0
+            } else {
+                failures as f64 / operations as f64
+            },
+            at: Utc::now(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PresignUploadReq {
+    pub filename: String,
+    #[serde(alias = "content_type")]
+    pub content_type: String,
+    pub size: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectUploadResp {
+    pub id: String,
+    pub filename: String,
+    pub content_type: String,
+    pub size: i64,
+    pub status: DirectUploadStatus,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub confirmed_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl From<DirectUpload> for DirectUploadResp {
+    fn from(upload: DirectUpload) -> Self {
+        Self {
+            id: upload.id,
+            filename: upload.filename,
+            content_type: upload.content_type,
+            size: upload.size,
+            status: upload.status,
+            expires_at: upload.expires_at,
+            confirmed_at: upload.confirmed_at,
+            created_at: upload.created_at,
+        }
+    }
+}
+
+// 客户端以PUT方式把文件上传到url, Content-Type需与申请时一致
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PresignUploadResp {
+    pub upload: DirectUploadResp,
+    pub url: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LocationAccessResp {
+    pub id: String,
+    pub accessor_id: String,
+    pub kind: LocationAccessKind,
+    pub walk_request_id: Option<String>,
+    pub subject_ids: Vec<String>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl From<LocationAccess> for LocationAccessResp {
+    fn from(access: LocationAccess) -> Self {
+        Self {
+            id: access.id,
+            accessor_id: access.accessor_id,
+            kind: access.kind,
+            walk_request_id: access.walk_request_id,
+            subject_ids: access.subject_ids,
+            created_at: access.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditLogResp {
+    pub id: String,
+    pub walk_request_id: String,
+    pub actor_id: Option<String>,
+    pub action: WalkRequestAuditAction,
+    pub before: Option<WalkRequestResp>,
+    pub after: WalkRequestResp,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl From<AuditLog> for AuditLogResp {
+    fn from(log: AuditLog) -> Self {
+        Self {
+            id: log.id,
+            walk_request_id: log.walk_request_id,
+            actor_id: log.actor_id,
+            action: log.action,
+            before: log.before.map(WalkRequestResp::from),
+            after: WalkRequestResp::from(log.after),
+            created_at: log.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationResp {
+    pub id: String,
+    pub kind: NotificationKind,
+    pub walk_request_id: Option<String>,
+    pub content: String,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl From<Notification> for NotificationResp {
+    fn from(notification: Notification) -> Self {
+        Self {
+            id: notification.id,
+            kind: notification.kind,
+            walk_request_id: notification.walk_request_id,
+            content: notification.content,
+            created_at: notification.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationSettingsResp {
+    pub sms_enabled: bool,
+    pub sms_phone: Option<String>,
+    pub email: Option<String>,
+    pub email_receipts: bool,
+    pub email_weekly_summary: bool,
+    pub email_verification_decisions: bool,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl From<NotificationSettings> for NotificationSettingsResp {
+    fn from(settings: NotificationSettings) -> Self {
+        Self {
+            sms_enabled: settings.sms_enabled,
+            sms_phone: settings.sms_phone,
+            email: settings.email,
+            email_receipts: settings.email_receipts,
+            email_weekly_summary: settings.email_weekly_summary,
+            email_verification_decisions: settings.email_verification_decisions,
+            updated_at: settings.updated_at,
+        }
+    }
+}
+
+// 接单和临近开始时取消等紧急通知是否同时发送短信. 更换smsPhone时需附带向该号码发送的登录验证码.
+// email*为提现到账凭证、遛狗周报和认证结果邮件的开关, 开启时需已填写邮箱
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationSettingsReq {
+    pub sms_enabled: bool,
+    pub sms_phone: Option<String>,
+    pub code: Option<String>,
+    pub email: Option<String>,
+    #[serde(default)]
+    pub email_receipts: bool,
+    #[serde(default)]
+    pub email_weekly_summary: bool,
+    #[serde(default)]
+    pub email_verification_decisions: bool,
+}
+
+impl Validate for NotificationSettingsReq {
+    fn validate(&self, errors: &mut FieldErrors) {
+        if let Some(sms_phone) = &self.sms_phone {
+            errors.phone("smsPhone", sms_phone);
+        }
+        if let Some(email) = &self.email {
+            errors.email("email", email);
+        }
+    }
+}
+
+// token为FCM注册令牌或APNs设备令牌
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisterPushTokenReq {
+    pub platform: PushPlatform,
+    pub token: String,
+}
+
+impl Validate for RegisterPushTokenReq {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.not_blank("token", &self.token, MAX_PUSH_TOKEN_CHARS);
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RoutePreferenceReq {
+    #[serde(default, alias = "preferred_route")]
+    pub preferred_route: Vec<GeoPoint>,
+    #[serde(default, alias = "no_go_zones")]
+    pub no_go_zones: Vec<NoGoZone>,
+    pub version: Option<i64>, // 读取遛狗请求时的版本, 与服务端不一致时返回冲突
+}
+
+impl From<RoutePreferenceReq> for RoutePreference {
+    fn from(req: RoutePreferenceReq) -> Self {
+        Self {
+            preferred_route: req.preferred_route,
+            no_go_zones: req.no_go_zones,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RoutePreferenceResp {
+    pub preferred_route: Vec<GeoPoint>,
+    pub no_go_zones: Vec<NoGoZone>,
+}
+
+impl From<RoutePreference> for RoutePreferenceResp {
+    fn from(route_preference: RoutePreference) -> Self {
+        Self {
+            preferred_route: route_preference.preferred_route,
+            no_go_zones: route_preference.no_go_zones,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteDeviationResp {
+    pub kind: RouteDeviationKind,
+    pub zone: Option<String>,
+    pub longitude: f64,
+    pub latitude: f64,
+    pub points: i64,
+    pub max_distance: f64,
+}
+
+impl From<RouteDeviation> for RouteDeviationResp {
+    fn from(deviation: RouteDeviation) -> Self {
+        Self {
+            kind: deviation.kind,
+            zone: deviation.zone,
+            longitude: deviation.longitude,
+            latitude: deviation.latitude,
+            points: deviation.points,
+            max_distance: deviation.max_distance,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WalkRouteReportResp {
+    pub walk_request_id: String,
+    pub route: Vec<GeoPoint>,
+    pub route_preference: Option<RoutePreferenceResp>,
+    pub deviation_score: Option<f64>,
+    pub deviations: Vec<RouteDeviationResp>,
+    pub assessed_at: Option<DateTime<Utc>>,
+    pub poi_visits: Vec<PoiVisitResp>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PoiVisitResp {
+    pub poi_id: String,
+    pub name: String,
+    pub entered_at: DateTime<Utc>,
+    pub left_at: DateTime<Utc>,
+    pub minutes: i64,
+}
+
+impl From<PoiVisit> for PoiVisitResp {
+    fn from(visit: PoiVisit) -> Self {
+        Self {
+            minutes: visit.minutes(),
+            poi_id: visit.poi_id,
+            name: visit.name,
+            entered_at: visit.entered_at,
+            left_at: visit.left_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatePoiReq {
+    pub name: String,
+    pub longitude: f64,
+    pub latitude: f64,
+    #[serde(default)]
+    pub radius: f64,
+    #[serde(default)]
+    pub boundary: Vec<GeoPoint>,
+}
+
+impl Validate for CreatePoiReq {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.not_blank("name", &self.name, MAX_NAME_CHARS);
+        errors.coordinate("", self.longitude, self.latitude);
+        for (i, point) in self.boundary.iter().enumerate() {
+            errors.coordinate(&format!("boundary[{i}]."), point.longitude, point.latitude);
+        }
+    }
+}
+
+impl From<CreatePoiReq> for PoiCreate {
+    fn from(req: CreatePoiReq) -> Self {
+        Self {
+            name: req.name,
+            longitude: req.longitude,
+            latitude: req.latitude,
+            radius: req.radius,
+            boundary: req.boundary,
+        }
+    }
+}
+
+// 不带坐标时返回全部兴趣点
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(rename_all = "camelCase")]
+pub struct PoisReq {
+    pub longitude: Option<f64>,
+    pub latitude: Option<f64>,
+    pub radius: Option<f64>,
+}
+
+impl From<PoisReq> for PoiQuery {
+    fn from(req: PoisReq) -> Self {
+        Self {
+            near: req
+                .longitude
+                .zip(req.latitude)
+                .map(|(longitude, latitude)| GeoPoint {
+                    longitude,
+                    latitude,
+                }),
+            max_distance: req.radius,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PoiResp {
+    pub id: String,
+    pub name: String,
+    pub longitude: f64,
+    pub latitude: f64,
+    pub radius: f64,
+    pub boundary: Vec<GeoPoint>,
+}
+
+impl From<Poi> for PoiResp {
+    fn from(poi: Poi) -> Self {
+        Self {
+            id: poi.id,
+            name: poi.name,
+            longitude: poi.longitude,
+            latitude: poi.latitude,
+            radius: poi.radius,
+            boundary: poi.boundary,
+        }
+    }
+}
+
+impl From<WalkRouteReport> for WalkRouteReportResp {
+    fn from(report: WalkRouteReport) -> Self {
+        Self {
+            walk_request_id: report.walk_request_id,
+            poi_visits: report
+                .poi_visits
+                .into_iter()
+                .map(PoiVisitResp::from)
+                .collect(),
+            route: report.route,
+            route_preference: report.route_preference.map(RoutePreferenceResp::from),
+            deviation_score: report.route_assessment.as_ref().map(|a| a.score),
+            assessed_at: report.route_assessment.as_ref().map(|a| a.assessed_at),
+            deviations: report
+                .route_assessment
+                .map(|a| a.deviations)
+                .unwrap_or_default()
+                .into_iter()
+                .map(RouteDeviationResp::from)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PurgeDeletedReq {
+    #[serde(alias = "deleted_before")]
+    pub deleted_before: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PurgedCountsResp {
+    pub dogs: u64,
+    pub walk_requests: u64,
+    pub walking_locations: u64,
+}
+
+impl From<PurgedCounts> for PurgedCountsResp {
+    fn from(counts: PurgedCounts) -> Self {
+        Self {
+            dogs: counts.dogs,
+            walk_requests: counts.walk_requests,
+            walking_locations: counts.walking_locations,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityIssueResp {
+    pub kind: IntegrityIssueKind,
+    pub collection: String,
+    pub record_id: String,
+    pub field: String,
+    pub reference_id: String,
+    pub fixed: bool,
+}
+
+impl From<IntegrityIssue> for IntegrityIssueResp {
+    fn from(issue: IntegrityIssue) -> Self {
+        Self {
+            kind: issue.kind,
+            collection: issue.collection,
+            record_id: issue.record_id,
+            field: issue.field,
+            reference_id: issue.reference_id,
+            fixed: issue.fixed,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityReportResp {
+    pub checked_at: DateTime<Utc>,
+    pub issues: Vec<IntegrityIssueResp>,
+}
+
+impl From<IntegrityReport> for IntegrityReportResp {
+    fn from(report: IntegrityReport) -> Self {
+        Self {
+            checked_at: report.checked_at,
+            issues: report.issues.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatePartnerReq {
+    pub name: String,
+    pub kind: PartnerKind,
+    pub scopes: Vec<PartnerScope>,
+    #[serde(alias = "rate_limit")]
+    pub rate_limit: i64,
+}
+
+impl From<CreatePartnerReq> for PartnerCreate {
+    fn from(req: CreatePartnerReq) -> Self {
+        Self {
+            name: req.name,
+            kind: req.kind,
+            scopes: req.scopes,
+            rate_limit: req.rate_limit,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdatePartnerReq {
+    pub name: Option<String>,
+    pub scopes: Option<Vec<PartnerScope>>,
+    #[serde(alias = "rate_limit")]
+    pub rate_limit: Option<i64>,
+    pub disabled: Option<bool>,
+}
+
+impl From<UpdatePartnerReq> for PartnerUpdate {
+    fn from(req: UpdatePartnerReq) -> Self {
+        Self {
+            name: req.name,
+            scopes: req.scopes,
+            rate_limit: req.rate_limit,
+            disabled: req.disabled,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PartnerResp {
+    pub id: String,
+    pub name: String,
+    pub kind: PartnerKind,
+    pub scopes: Vec<PartnerScope>,
+    pub rate_limit: i64,
+    pub disabled: bool,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl From<Partner> for PartnerResp {
+    fn from(partner: Partner) -> Self {
+        Self {
+            id: partner.id,
+            name: partner.name,
+            kind: partner.kind,
+            scopes: partner.scopes,
+            rate_limit: partner.rate_limit,
+            disabled: partner.disabled,
+            created_at: partner.created_at,
+            updated_at: partner.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PartnerApiKeyResp {
+    pub id: String,
+    pub partner_id: String,
+    pub prefix: String,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl From<PartnerApiKey> for PartnerApiKeyResp {
+    fn from(key: PartnerApiKey) -> Self {
+        Self {
+            id: key.id,
+            partner_id: key.partner_id,
+            prefix: key.prefix,
+            revoked_at: key.revoked_at,
+            created_at: key.created_at,
+        }
+    }
+}
+
+// 签发时返回的密钥明文, 之后无法再次获取
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IssuedPartnerApiKeyResp {
+    pub key: PartnerApiKeyResp,
+    pub api_key: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PartnerConsentResp {
+    pub id: String,
+    pub partner_id: String,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl From<PartnerConsent> for PartnerConsentResp {
+    fn from(consent: PartnerConsent) -> Self {
+        Self {
+            id: consent.id,
+            partner_id: consent.partner_id,
+            created_at: consent.created_at,
+        }
+    }
+}
+
+// 用量统计的时间范围[from, to)
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(rename_all = "camelCase")]
+pub struct PartnerUsageReq {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PartnerUsageResp {
+    pub date: String,
+    pub endpoint: String,
+    pub count: i64,
+}
+
+impl From<PartnerUsage> for PartnerUsageResp {
+    fn from(usage: PartnerUsage) -> Self {
+        Self {
+            date: usage.date,
+            endpoint: usage.endpoint,
+            count: usage.count,
+        }
+    }
+}
+
+// 合作方代狗狗主人创建请求, 狗狗按id引用
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatePartnerWalkRequestReq {
+    #[serde(alias = "owner_id")]
+    pub owner_id: String,
+    #[serde(alias = "dog_ids")]
+    pub dog_ids: Vec<String>,
+    #[serde(alias = "should_start_after")]
+    pub should_start_after: Option<DateTime<Utc>>,
+    #[serde(alias = "should_start_before")]
+    pub should_start_before: Option<DateTime<Utc>>,
+    #[serde(alias = "should_end_before")]
+    pub should_end_before: Option<DateTime<Utc>>,
+    #[serde(alias = "should_end_after")]
+    pub should_end_after: Option<DateTime<Utc>>,
+    pub latitude: f64,
+    pub longitude: f64,
+    #[serde(default, alias = "notify_walker_nearby")]
+    pub notify_walker_nearby: bool,
+}
+
+impl Validate for CreatePartnerWalkRequestReq {
+    fn validate(&self, errors: &mut FieldErrors) {
+        if self.dog_ids.is_empty() {
+            errors.add("dogIds", "至少选择一只狗狗");
+        }
+        errors.coordinate("", self.longitude, self.latitude);
+        errors.walk_window(
+            self.should_start_after,
+            self.should_start_before,
+            self.should_end_after,
+            self.should_end_before,
+        );
+    }
+}
+
+impl CreatePartnerWalkRequestReq {
+    pub fn into_create(self) -> (Vec<String>, WalkRequestCreate) {
+        (
+            self.dog_ids,
+            WalkRequestCreate {
+                dogs: Vec::new(),
+                should_start_after: self.should_start_after,
+                should_start_before: self.should_start_before,
+                should_end_before: self.should_end_before,
+                should_end_after: self.should_end_after,
+                latitude: self.latitude,
+                longitude: self.longitude,
+                created_by: self.owner_id,
+                notify_favorites: false,
+                priority_walkers: Vec::new(),
+                priority_until: None,
+                notify_walker_nearby: self.notify_walker_nearby,
+                route_preference: None,
+                partner_id: None,
+            },
+        )
+    }
+}
+
+// 合作方只能看到请求的进度, 不包含遛狗人等其他用户的信息
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PartnerWalkRequestResp {
+    pub id: WalkRequestId,
+    pub owner_id: String,
+    pub status: String,
+    pub accepted_at: Option<DateTime<Utc>>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub canceled_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl From<WalkRequest> for PartnerWalkRequestResp {
+    fn from(request: WalkRequest) -> Self {
+        Self {
+            id: request.id,
+            owner_id: request.created_by,
+            status: request.status,
+            accepted_at: request.accepted_at,
+            started_at: request.started_at,
+            finished_at: request.finished_at,
+            canceled_at: request.canceled_at,
+            created_at: request.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct WalkerAvailabilityReq {
+    pub longitude: f64,
+    pub latitude: f64,
+    pub radius: f64,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+}
+
+impl From<WalkerAvailabilityReq> for AvailableWalkerQuery {
+    fn from(req: WalkerAvailabilityReq) -> Self {
+        Self {
+            longitude: req.longitude,
+            latitude: req.latitude,
+            radius: req.radius,
+            from: req.from,
+            to: req.to,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WalkerAvailabilityResp {
+    pub available_walkers: i64,
+    pub median_response_seconds: Option<i64>,
+}
+
+impl From<WalkerAvailability> for WalkerAvailabilityResp {
+    fn from(availability: WalkerAvailability) -> Self {
+        Self {
+            available_walkers: availability.available_walkers,
+            median_response_seconds: availability.median_response_seconds,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NeighborhoodStatsResp {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub active_requests: i64,
+    pub average_price: Option<f64>,
+}
+
+impl From<NeighborhoodStats> for NeighborhoodStatsResp {
+    fn from(stats: NeighborhoodStats) -> Self {
+        Self {
+            latitude: stats.latitude,
+            longitude: stats.longitude,
+            active_requests: stats.active_requests,
+            average_price: stats.average_price,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SendInviteReq {
+    pub phone: String,
+    pub kind: InviteKind,
+    pub locale: Option<String>,
+}
+
+impl Validate for SendInviteReq {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.phone("phone", &self.phone);
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct InviteResp {
+    pub id: String,
+    pub phone: String,
+    pub kind: InviteKind,
+    pub locale: String,
+    pub opened_at: Option<DateTime<Utc>>,
+    pub converted_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl From<Invite> for InviteResp {
+    fn from(invite: Invite) -> Self {
+        Self {
+            id: invite.id,
+            phone: invite.phone,
+            kind: invite.kind,
+            locale: invite.locale,
+            opened_at: invite.opened_at,
+            converted_at: invite.converted_at,
+            created_at: invite.created_at,
+        }
+    }
+}
+
+// 被邀请人打开链接时只返回落地页需要的信息, 不暴露邀请人和手机号
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenedInviteResp {
+    pub kind: InviteKind,
+    pub locale: String,
+}
+
+impl From<Invite> for OpenedInviteResp {
+    fn from(invite: Invite) -> Self {
+        Self {
+            kind: invite.kind,
+            locale: invite.locale,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateShortLinkReq {
+    pub kind: ShortLinkKind,
+    pub token: String,         // 分享令牌、邀请码或二维码内容
+    pub ttl_days: Option<i64>, // 有效天数, 默认7天
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ShortLinkResp {
+    pub url: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(rename_all = "camelCase")]
+pub struct InviteConversionReq {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct InviteConversionResp {
+    pub kind: InviteKind,
+    pub sent: i64,
+    pub opened: i64,
+    pub converted: i64,
+}
+
+impl From<InviteConversion> for InviteConversionResp {
+    fn from(conversion: InviteConversion) -> Self {
+        Self {
+            kind: conversion.kind,
+            sent: conversion.sent,
+            opened: conversion.opened,
+            converted: conversion.converted,
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+// 请求体校验失败的字段和原因
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiErrorResp {
+    pub code: String,
+    pub message: String,
+    // 违反唯一约束的字段, 仅duplicate错误返回
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<String>,
+    // 校验失败的字段, 仅validation_failed错误返回
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<FieldError>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 客户端按同一类型解析错误响应, 省略的fields和errors解析为空列表
+    #[test]
+    fn error_response_round_trips_without_optional_lists() {
+        let resp = ApiErrorResp {
+            code: "not_found".to_owned(),
+            message: "狗狗不存在".to_owned(),
+            fields: Vec::new(),
+            errors: Vec::new(),
+        };
+        let json = serde_json::to_value(&resp).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"code": "not_found", "message": "狗狗不存在"})
+        );
+        let parsed: ApiErrorResp = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.code, "not_found");
+        assert!(parsed.fields.is_empty() && parsed.errors.is_empty());
+    }
+}
@@ -0,0 +1,34 @@
+use little_walk_core::service::DEFAULT_HELP_LOCALE;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+fn default_locale() -> String {
+    DEFAULT_HELP_LOCALE.to_owned()
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum HelpArticleFormat {
+    #[default]
+    Html,
+    Markdown,
+}
+
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(rename_all = "camelCase")]
+pub struct HelpArticlesReq {
+    #[serde(default = "default_locale")]
+    pub locale: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(rename_all = "camelCase")]
+pub struct HelpArticleReq {
+    #[serde(default = "default_locale")]
+    pub locale: String,
+    #[serde(default)]
+    pub format: HelpArticleFormat,
+    pub version: Option<i64>,
+}
@@ -0,0 +1,31 @@
+// 对外接口的请求和响应类型, 服务端和客户端(集成测试, 生成的Rust/Dart客户端)共用同一份定义, 不依赖actix
+// dto为多个接口共用的类型, 其余模块与http-api中同名的handler模块一一对应
+pub mod account;
+pub mod admin;
+pub mod auth;
+pub mod block;
+pub mod chat;
+pub mod common;
+pub mod dog;
+pub mod dto;
+pub mod error;
+pub mod favorite;
+pub mod health;
+pub mod help;
+pub mod invite;
+pub mod notification;
+pub mod partner;
+pub mod payout;
+pub mod poi;
+pub mod public;
+pub mod refund;
+pub mod report;
+pub mod review;
+pub mod session;
+pub mod synthetic;
+pub mod ticket;
+pub mod upload;
+pub mod validation;
+pub mod walk_request;
+pub mod walker;
+pub mod withdrawal;
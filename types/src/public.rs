@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+use utoipa::IntoParams;
+
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct NeighborhoodsReq {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub radius: f64,
+}
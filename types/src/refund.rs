@@ -0,0 +1,41 @@
+use little_walk_core::entities::RefundStatus;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestRefundReq {
+    pub amount: i64,
+    pub reason: String,
+    #[serde(default, alias = "evidence_ids")]
+    pub evidence_ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestRefundResp {
+    pub id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(rename_all = "camelCase")]
+pub struct RefundRequestsReq {
+    pub status: Option<RefundStatus>,
+    pub limit: i64,
+    pub skip: i64,
+}
+
+// amount为空时按申请金额全额退款
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ApproveRefundReq {
+    pub amount: Option<i64>,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RejectRefundReq {
+    pub note: Option<String>,
+}
@@ -0,0 +1,32 @@
+use little_walk_core::entities::ReportStatus;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateReportResp {
+    pub id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportsReq {
+    pub status: Option<ReportStatus>,
+    pub limit: i64,
+    pub skip: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewReportReq {
+    pub status: ReportStatus,
+    #[serde(alias = "resolution_note")]
+    pub resolution_note: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewReportResp {
+    pub success: bool,
+}
@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateReviewReq {
+    pub rating: i32,
+    #[serde(default)]
+    pub content: String,
+    #[serde(default)]
+    pub photo_ids: Vec<String>, // 已上传照片的ID, 最多9张
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateReviewResp {
+    pub id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct HelpfulVoteResp {
+    pub counted: bool, // 已投过票时为false
+}
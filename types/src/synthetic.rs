@@ -0,0 +1,29 @@
+use little_walk_core::entities::SyntheticStep;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SyntheticStepResp {
+    pub name: String,
+    pub millis: i64,
+    pub error: Option<String>,
+}
+
+impl From<SyntheticStep> for SyntheticStepResp {
+    fn from(step: SyntheticStep) -> Self {
+        Self {
+            name: step.name,
+            millis: step.millis,
+            error: step.error,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SyntheticWalkResp {
+    pub success: bool,
+    pub total_millis: i64,
+    pub steps: Vec<SyntheticStepResp>,
+}
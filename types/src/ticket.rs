@@ -0,0 +1,41 @@
+use little_walk_core::entities::TicketStatus;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateTicketResp {
+    pub id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(rename_all = "camelCase")]
+pub struct MyTicketsReq {
+    pub status: Option<TicketStatus>,
+    pub limit: i64,
+    pub skip: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateTicketResp {
+    pub success: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(rename_all = "camelCase")]
+pub struct TicketsReq {
+    pub status: Option<TicketStatus>,
+    #[serde(alias = "assignee_id")]
+    pub assignee_id: Option<String>,
+    pub limit: i64,
+    pub skip: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateTicketStatusReq {
+    pub status: TicketStatus,
+}
@@ -0,0 +1,55 @@
+use std::str::FromStr;
+
+use little_walk_core::entities::ImageSize;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+// 上传用途, 由客户端在上传时指明, 未指明时按other处理
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum UploadPurpose {
+    Portrait,
+    Avatar,
+    IdDocument,
+    Attachment,
+    Other,
+}
+
+impl FromStr for UploadPurpose {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "portrait" => Ok(UploadPurpose::Portrait),
+            "avatar" => Ok(UploadPurpose::Avatar),
+            "id_document" => Ok(UploadPurpose::IdDocument),
+            "attachment" => Ok(UploadPurpose::Attachment),
+            "other" => Ok(UploadPurpose::Other),
+            _ => Err(format!("invalid upload purpose: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct UploadReq {
+    pub purpose: Option<UploadPurpose>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadResult {
+    pub ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DownloadReq {
+    pub size: Option<ImageSize>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteResult {
+    pub success: bool,
+}
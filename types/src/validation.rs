@@ -0,0 +1,157 @@
+use chrono::{DateTime, Utc};
+
+use crate::error::FieldError;
+
+const MAX_TAGS: usize = 20;
+const MAX_TAG_CHARS: usize = 20;
+const MAX_EMAIL_CHARS: usize = 254;
+pub const MAX_NAME_CHARS: usize = 50;
+pub const MAX_PUSH_TOKEN_CHARS: usize = 512;
+pub const MAX_NOTE_CHARS: usize = 200;
+pub const MAX_CARE_TIPS: usize = 20;
+pub const MAX_CARE_TIP_CHARS: usize = 500;
+
+// 请求体的格式校验, 在进入服务层之前发现并一次性返回所有字段的错误
+pub trait Validate {
+    fn validate(&self, errors: &mut FieldErrors);
+}
+
+// 字段名与JSON中的字段名一致, 嵌套字段形如emergencyContacts[0].phone
+#[derive(Debug, Default)]
+pub struct FieldErrors(Vec<FieldError>);
+
+impl FieldErrors {
+    pub fn add(&mut self, field: impl Into<String>, message: impl Into<String>) {
+        self.0.push(FieldError {
+            field: field.into(),
+            message: message.into(),
+        });
+    }
+
+    // 中国大陆11位手机号, 或带+号的国际号码
+    pub fn phone(&mut self, field: &str, phone: &str) {
+        let valid = match phone.strip_prefix('+') {
+            Some(digits) => {
+                (8..=15).contains(&digits.len()) && digits.bytes().all(|b| b.is_ascii_digit())
+            }
+            None => {
+                phone.len() == 11
+                    && phone.starts_with('1')
+                    && matches!(phone.as_bytes()[1], b'3'..=b'9')
+                    && phone.bytes().all(|b| b.is_ascii_digit())
+            }
+        };
+        if !valid {
+            self.add(field, "手机号格式不正确");
+        }
+    }
+
+    // 只做基本的格式检查, 能否送达由邮件服务判断
+    pub fn email(&mut self, field: &str, email: &str) {
+        let valid = email.len() <= MAX_EMAIL_CHARS
+            && !email.chars().any(char::is_whitespace)
+            && email.split_once('@').is_some_and(|(local, domain)| {
+                !local.is_empty()
+                    && !domain.contains('@')
+                    && domain.contains('.')
+                    && !domain.starts_with('.')
+                    && !domain.ends_with('.')
+            });
+        if !valid {
+            self.add(field, "邮箱格式不正确");
+        }
+    }
+
+    pub fn coordinate(&mut self, prefix: &str, longitude: f64, latitude: f64) {
+        if !(-180.0..=180.0).contains(&longitude) {
+            self.add(format!("{prefix}longitude"), "经度必须在-180到180之间");
+        }
+        if !(-90.0..=90.0).contains(&latitude) {
+            self.add(format!("{prefix}latitude"), "纬度必须在-90到90之间");
+        }
+    }
+
+    pub fn not_blank(&mut self, field: &str, value: &str, max_chars: usize) {
+        if value.trim().is_empty() {
+            self.add(field, "不能为空");
+        } else if value.chars().count() > max_chars {
+            self.add(field, format!("不能超过{max_chars}个字符"));
+        }
+    }
+
+    pub fn tags(&mut self, field: &str, tags: &[String]) {
+        if tags.len() > MAX_TAGS {
+            self.add(field, format!("最多{MAX_TAGS}个标签"));
+        }
+        for (i, tag) in tags.iter().enumerate() {
+            self.not_blank(&format!("{field}[{i}]"), tag, MAX_TAG_CHARS);
+        }
+    }
+
+    // 遛狗的时间窗口: 开始和结束的最早时间都不能晚于对应的最晚时间, 最早开始不能晚于最晚结束
+    pub fn walk_window(
+        &mut self,
+        should_start_after: Option<DateTime<Utc>>,
+        should_start_before: Option<DateTime<Utc>>,
+        should_end_after: Option<DateTime<Utc>>,
+        should_end_before: Option<DateTime<Utc>>,
+    ) {
+        if let (Some(after), Some(before)) = (should_start_after, should_start_before) {
+            if after > before {
+                self.add("shouldStartBefore", "不能早于shouldStartAfter");
+            }
+        }
+        if let (Some(after), Some(before)) = (should_end_after, should_end_before) {
+            if after > before {
+                self.add("shouldEndBefore", "不能早于shouldEndAfter");
+            }
+        }
+        if let (Some(start), Some(end)) = (should_start_after, should_end_before) {
+            if start > end {
+                self.add("shouldEndBefore", "不能早于shouldStartAfter");
+            }
+        }
+    }
+
+    // 没有错误时返回Ok, 否则返回收集到的所有字段错误
+    pub fn into_result(self) -> Result<(), Vec<FieldError>> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(self.0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(errors: &FieldErrors) -> Vec<&str> {
+        errors.0.iter().map(|e| e.field.as_str()).collect()
+    }
+
+    #[test]
+    fn phone_formats() {
+        let mut errors = FieldErrors::default();
+        errors.phone("a", "13800138000");
+        errors.phone("b", "+8613800138000");
+        errors.phone("c", "12800138000");
+        errors.phone("d", "1380013800");
+        errors.phone("e", "+86-138");
+        assert_eq!(fields(&errors), ["c", "d", "e"]);
+    }
+
+    #[test]
+    fn walk_window_and_coordinates() {
+        let now = Utc::now();
+        let later = now + chrono::Duration::hours(1);
+        let mut errors = FieldErrors::default();
+        errors.walk_window(Some(later), Some(now), None, Some(now));
+        errors.coordinate("boundary[0].", 181.0, -90.0);
+        assert_eq!(
+            fields(&errors),
+            ["shouldStartBefore", "shouldEndBefore", "boundary[0].longitude"]
+        );
+    }
+}
@@ -0,0 +1,72 @@
+use chrono::{DateTime, Utc};
+use little_walk_core::export::ExportFormat;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::dto::{CancellationPenaltyResp, RoutePreferenceResp};
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateWalkRequestResp {
+    pub id: String,
+}
+
+// 与创建时的检查结果一致, 优先推送的收藏遛狗人只返回人数. 金额以分为单位
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidateWalkRequestResp {
+    pub priority_walker_count: usize,
+    pub priority_until: Option<DateTime<Utc>>,
+    pub route_preference: Option<RoutePreferenceResp>,
+    pub price: i64,
+    pub platform_fee: i64,
+    pub walker_earning: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangesReq {
+    pub since: String, // 上次返回的next_token, 首次同步时为RFC3339时间或毫秒时间戳
+}
+
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct NearbyReq {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub radius: f64,
+    pub limit: i64,
+    pub skip: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteWalkRequestResp {
+    pub success: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AssignAccepterResp {
+    pub success: bool,
+}
+
+// 遛狗人放弃已被指定的请求, 迟取消时返回罚金明细
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ResignAcceptanceResp {
+    pub penalty: Option<CancellationPenaltyResp>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateRoutePreferenceResp {
+    pub success: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExportReq {
+    pub format: ExportFormat,
+}
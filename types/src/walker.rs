@@ -0,0 +1,63 @@
+use little_walk_core::entities::VerificationStatus;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::validation::{FieldErrors, Validate};
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SubmitVerificationReq {
+    #[serde(alias = "id_document_ids")]
+    pub id_document_ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationsReq {
+    pub status: Option<VerificationStatus>,
+    pub limit: i64,
+    pub skip: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewVerificationResp {
+    pub success: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RejectVerificationReq {
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateLocationReq {
+    pub longitude: f64,
+    pub latitude: f64,
+}
+
+impl Validate for UpdateLocationReq {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.coordinate("", self.longitude, self.latitude);
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateLocationResp {
+    pub success: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchReq {
+    pub longitude: f64,
+    pub latitude: f64,
+    pub radius: f64,
+    pub limit: i64,
+    pub skip: i64,
+}